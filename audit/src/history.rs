@@ -0,0 +1,101 @@
+//! Fetches a vault's events from an RPC node that keeps its full history.
+
+use anchor_lang::prelude::Pubkey;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::UiTransactionEncoding;
+use vault_pda_events::{parse_logs, VaultEvent};
+
+use crate::Result;
+
+/// Signatures per `getSignaturesForAddress` page, the RPC maximum
+const PAGE_SIZE: usize = 1_000;
+
+/// An event and the transaction that emitted it
+pub struct Entry {
+    pub signature: String,
+    pub slot: u64,
+    pub event: VaultEvent,
+}
+
+/// Signatures of every successful transaction that touched `vault` after
+/// `until`, oldest first
+pub async fn signatures(
+    rpc: &RpcClient,
+    vault: &Pubkey,
+    until: Option<Signature>,
+) -> Result<Vec<(Signature, u64)>> {
+    let mut signatures = Vec::new();
+    let mut before = None;
+    loop {
+        let config = GetConfirmedSignaturesForAddress2Config {
+            before,
+            until,
+            limit: Some(PAGE_SIZE),
+            commitment: Some(rpc.commitment()),
+        };
+        let page = rpc
+            .get_signatures_for_address_with_config(vault, config)
+            .await?;
+        let full = page.len() == PAGE_SIZE;
+        for status in page {
+            let signature = status.signature.parse::<Signature>()?;
+            before = Some(signature);
+            if status.err.is_none() {
+                signatures.push((signature, status.slot));
+            }
+        }
+        if !full {
+            break;
+        }
+    }
+    signatures.reverse();
+    Ok(signatures)
+}
+
+/// Every event `program_id` emitted about `vault` in `signatures`, in order
+pub async fn events(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    signatures: &[(Signature, u64)],
+) -> Result<Vec<Entry>> {
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Json),
+        commitment: Some(rpc.commitment()),
+        max_supported_transaction_version: Some(0),
+    };
+    let mut entries = Vec::new();
+    for (signature, slot) in signatures {
+        let transaction = rpc.get_transaction_with_config(signature, config).await?;
+        let logs = transaction
+            .transaction
+            .meta
+            .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages))
+            .unwrap_or_default();
+        entries.extend(
+            parse_logs(program_id, &logs)
+                .into_iter()
+                .filter(|event| event_vault(event) == vault)
+                .map(|event| Entry {
+                    signature: signature.to_string(),
+                    slot: *slot,
+                    event,
+                }),
+        );
+    }
+    Ok(entries)
+}
+
+fn event_vault(event: &VaultEvent) -> &Pubkey {
+    match event {
+        VaultEvent::Deposit(e) => &e.vault,
+        VaultEvent::Redeem(e) => &e.vault,
+        VaultEvent::FlagCaptured(e) => &e.vault,
+        VaultEvent::Report(e) => &e.vault,
+        VaultEvent::Skim(e) => &e.vault,
+        VaultEvent::VaultNamed(e) => &e.vault,
+    }
+}
@@ -0,0 +1,287 @@
+//! Replays a vault's events into the totals they account for.
+//!
+//! Deposits add their amount and shares, redeems remove theirs, a report
+//! mints the treasury's shares without moving any underlying, and a skim
+//! pays the treasury's cut out without touching the shares. Every event
+//! also records the vault's totals after it ran, so each one is a checkpoint:
+//! whatever the ledger cannot explain since the previous event is reported
+//! as a divergence, and the ledger then resyncs to the checkpoint so a single
+//! incident is reported once rather than at every later event.
+
+use std::fmt;
+
+use vault_pda_events::VaultEvent;
+
+/// What the ledger expects the vault to hold
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Totals {
+    /// Underlying in the vault's token account
+    pub assets: i128,
+    /// Supply of the share mint
+    pub shares: i128,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Divergence {
+    /// Underlying arrived without a deposit, e.g. a donation inflating the
+    /// share price, or yield
+    UnexplainedInflow { expected: i128, actual: u64 },
+    /// Underlying left the vault without a redeem paying it out
+    UnexplainedOutflow { expected: i128, actual: u64 },
+    /// Shares were minted or burned outside deposits, redeems and reports,
+    /// or an event reported the supply of another mint
+    SupplyMismatch { expected: i128, actual: u64 },
+}
+
+impl Divergence {
+    /// Whether value may have been stolen, rather than only a warning sign
+    pub fn is_critical(&self) -> bool {
+        !matches!(self, Divergence::UnexplainedInflow { .. })
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Divergence::UnexplainedInflow { expected, actual } => write!(
+                f,
+                "vault holds {actual} but replayed events leave {expected}: tokens arrived without a deposit"
+            ),
+            Divergence::UnexplainedOutflow { expected, actual } => write!(
+                f,
+                "vault holds {actual} but replayed events leave {expected}: tokens left without a redeem"
+            ),
+            Divergence::SupplyMismatch { expected, actual } => write!(
+                f,
+                "share supply is {actual} but replayed events leave {expected}"
+            ),
+        }
+    }
+}
+
+/// Replayed totals of one vault
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Ledger {
+    expected: Totals,
+    events: usize,
+}
+
+impl Ledger {
+    /// A ledger for a vault created empty
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The totals the events replayed so far account for
+    pub fn expected(&self) -> Totals {
+        self.expected
+    }
+
+    /// How many events were replayed
+    pub fn events(&self) -> usize {
+        self.events
+    }
+
+    /// Applies `event` and compares the result with the totals it recorded,
+    /// returning what the ledger could not explain. Flag captures and
+    /// renames move nothing and are ignored.
+    pub fn apply(&mut self, event: &VaultEvent) -> Vec<Divergence> {
+        let (assets, shares, total_assets, total_shares) = match event {
+            VaultEvent::Deposit(e) => (
+                e.amount as i128,
+                e.shares as i128,
+                e.total_assets,
+                e.total_shares,
+            ),
+            VaultEvent::Redeem(e) => (
+                -(e.amount as i128),
+                -(e.shares as i128),
+                e.total_assets,
+                e.total_shares,
+            ),
+            VaultEvent::Report(e) => (0, e.shares as i128, e.total_assets, e.total_shares),
+            VaultEvent::Skim(e) => (-(e.reserve as i128), 0, e.total_assets, e.total_shares),
+            VaultEvent::FlagCaptured(_) | VaultEvent::VaultNamed(_) => return Vec::new(),
+        };
+        self.events += 1;
+        self.expected.assets += assets;
+        self.expected.shares += shares;
+        self.checkpoint(total_assets, total_shares)
+    }
+
+    /// Compares the replayed totals with the vault's current ones
+    pub fn finish(&mut self, total_assets: u64, total_shares: u64) -> Vec<Divergence> {
+        self.checkpoint(total_assets, total_shares)
+    }
+
+    fn checkpoint(&mut self, total_assets: u64, total_shares: u64) -> Vec<Divergence> {
+        let mut divergences = Vec::new();
+
+        let expected = self.expected.assets;
+        if (total_assets as i128) < expected {
+            divergences.push(Divergence::UnexplainedOutflow {
+                expected,
+                actual: total_assets,
+            });
+        } else if total_assets as i128 > expected {
+            divergences.push(Divergence::UnexplainedInflow {
+                expected,
+                actual: total_assets,
+            });
+        }
+
+        let expected = self.expected.shares;
+        if total_shares as i128 != expected {
+            divergences.push(Divergence::SupplyMismatch {
+                expected,
+                actual: total_shares,
+            });
+        }
+
+        self.expected = Totals {
+            assets: total_assets as i128,
+            shares: total_shares as i128,
+        };
+        divergences
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::Pubkey;
+    use vault_pda_events::{DepositEvent, RedeemEvent, ReportEvent, SkimEvent};
+
+    fn deposit(amount: u64, shares: u64, total_assets: u64, total_shares: u64) -> VaultEvent {
+        VaultEvent::Deposit(DepositEvent {
+            vault: Pubkey::default(),
+            depositor: Pubkey::default(),
+            amount,
+            shares,
+            total_assets,
+            total_shares,
+        })
+    }
+
+    fn redeem(shares: u64, amount: u64, total_assets: u64, total_shares: u64) -> VaultEvent {
+        VaultEvent::Redeem(RedeemEvent {
+            vault: Pubkey::default(),
+            redeemer: Pubkey::default(),
+            shares,
+            amount,
+            total_assets,
+            total_shares,
+        })
+    }
+
+    #[test]
+    fn explained_history_has_no_divergences() {
+        let mut ledger = Ledger::new();
+        assert!(ledger
+            .apply(&deposit(1_000, 1_000, 1_000, 1_000))
+            .is_empty());
+        assert!(ledger.apply(&deposit(500, 500, 1_500, 1_500)).is_empty());
+        assert!(ledger.apply(&redeem(600, 600, 900, 900)).is_empty());
+        assert!(ledger.finish(900, 900).is_empty());
+        assert_eq!(ledger.events(), 3);
+    }
+
+    #[test]
+    fn donations_show_up_at_the_next_event() {
+        let mut ledger = Ledger::new();
+        // The attacker's 1-token deposit, then a donation before the victim's
+        ledger.apply(&deposit(1, 1, 1, 1));
+        let divergences = ledger.apply(&deposit(10_000, 0, 20_001, 1));
+        assert_eq!(
+            divergences,
+            vec![Divergence::UnexplainedInflow {
+                expected: 10_001,
+                actual: 20_001
+            }]
+        );
+        assert!(!divergences[0].is_critical());
+        // Reported once, then resynced
+        assert!(ledger.apply(&redeem(1, 20_001, 0, 0)).is_empty());
+        assert!(ledger.finish(0, 0).is_empty());
+    }
+
+    #[test]
+    fn flags_outflows_and_foreign_share_supplies() {
+        let mut ledger = Ledger::new();
+        ledger.apply(&deposit(1_000, 1_000, 1_000, 1_000));
+        // Burning shares of another mint records that mint's supply, so the
+        // redeem itself looks consistent and the vault's real supply only
+        // surfaces at the next event
+        assert!(ledger.apply(&redeem(1_000, 1_000, 0, 0)).is_empty());
+        let divergences = ledger.apply(&deposit(1_000, 1_000, 1_000, 2_000));
+        assert_eq!(
+            divergences,
+            vec![Divergence::SupplyMismatch {
+                expected: 1_000,
+                actual: 2_000
+            }]
+        );
+
+        let divergences = ledger.finish(400, 2_000);
+        assert_eq!(
+            divergences,
+            vec![Divergence::UnexplainedOutflow {
+                expected: 1_000,
+                actual: 400
+            }]
+        );
+        assert!(divergences[0].is_critical());
+    }
+
+    #[test]
+    fn reports_mint_without_moving_assets() {
+        let mut ledger = Ledger::new();
+        ledger.apply(&deposit(1_000, 1_000, 1_000, 1_000));
+        let report = VaultEvent::Report(ReportEvent {
+            vault: Pubkey::default(),
+            treasury: Pubkey::default(),
+            profit: 200,
+            reserve: 20,
+            shares: 17,
+            total_assets: 1_200,
+            total_shares: 1_017,
+        });
+        // The profit itself arrived outside any event
+        assert_eq!(
+            ledger.apply(&report),
+            vec![Divergence::UnexplainedInflow {
+                expected: 1_000,
+                actual: 1_200
+            }]
+        );
+        assert_eq!(
+            ledger.expected(),
+            Totals {
+                assets: 1_200,
+                shares: 1_017
+            }
+        );
+    }
+
+    #[test]
+    fn skims_pay_out_without_burning() {
+        let mut ledger = Ledger::new();
+        ledger.apply(&deposit(1_000, 1_000, 1_000, 1_000));
+        let skim = VaultEvent::Skim(SkimEvent {
+            vault: Pubkey::default(),
+            treasury: Pubkey::default(),
+            profit: 0,
+            reserve: 100,
+            total_assets: 900,
+            total_shares: 1_000,
+        });
+        assert!(ledger.apply(&skim).is_empty());
+        assert_eq!(
+            ledger.expected(),
+            Totals {
+                assets: 900,
+                shares: 1_000
+            }
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Replays a vault's events and diffs them against its on-chain state.
+//!
+//! ```bash
+//! audit --vault <underlying-mint> [--url <rpc>] [--program-id <pubkey>]
+//! ```
+//!
+//! Fetches every successful transaction that touched the vault, oldest
+//! first, decodes the program's events from their logs and replays them with
+//! `ledger.rs`. Each event records the vault's totals after it ran, so
+//! whatever the events do not explain is pinned to the first event that saw
+//! it; the replayed totals are then diffed against the vault's current
+//! balance and share supply. The node at `--url` must keep the full history,
+//! since transactions it has pruned look like donations or exploits.
+//!
+//! Divergences are printed as `DIVERGENCE critical` lines, or `DIVERGENCE
+//! warning` for tokens that arrived without a deposit. Exits with status 2
+//! when any is critical.
+
+use std::process::ExitCode;
+
+use anchor_lang::prelude::Pubkey;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use vault_pda_client::fetch_vault_state;
+use vault_pda_client::pda::VaultAddresses;
+
+mod history;
+mod ledger;
+
+use ledger::{Divergence, Ledger};
+
+const USAGE: &str = "usage: audit --vault <underlying-mint> [--url <rpc>] [--program-id <pubkey>]";
+
+const DEFAULT_URL: &str = "http://127.0.0.1:8899";
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+struct Config {
+    url: String,
+    program_id: Pubkey,
+    mint: Pubkey,
+}
+
+fn main() -> ExitCode {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to build tokio runtime");
+
+    let result = parse_args(std::env::args().skip(1).collect())
+        .and_then(|config| runtime.block_on(run(config)));
+    match result {
+        Ok(true) => ExitCode::from(2),
+        Ok(false) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("audit failed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(args: Vec<String>) -> Result<Config> {
+    let mut url = DEFAULT_URL.to_string();
+    let mut program_id = vault_pda::ID;
+    let mut mint = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--url" => url = args.next().ok_or(USAGE)?,
+            "--program-id" => program_id = args.next().ok_or(USAGE)?.parse()?,
+            "--vault" => mint = Some(args.next().ok_or(USAGE)?.parse()?),
+            _ => return Err(USAGE.into()),
+        }
+    }
+
+    Ok(Config {
+        url,
+        program_id,
+        mint: mint.ok_or(USAGE)?,
+    })
+}
+
+/// Audits the vault, returning whether any divergence was critical
+async fn run(config: Config) -> Result<bool> {
+    let rpc = RpcClient::new_with_commitment(config.url, CommitmentConfig::confirmed());
+    let vault = VaultAddresses::new(&config.program_id, &config.mint).vault;
+
+    // Re-read the state until no transaction landed after the history, so
+    // the final diff compares the same point in time
+    let mut signatures = history::signatures(&rpc, &vault, None).await?;
+    let state = loop {
+        let state = fetch_vault_state(&rpc, &config.program_id, &config.mint).await?;
+        let last = signatures.last().map(|(signature, _)| *signature);
+        let newer = history::signatures(&rpc, &vault, last).await?;
+        if newer.is_empty() {
+            break state;
+        }
+        signatures.extend(newer);
+    };
+    let entries = history::events(&rpc, &config.program_id, &vault, &signatures).await?;
+
+    let mut ledger = Ledger::new();
+    let mut critical = false;
+    let mut report = |divergences: Vec<Divergence>, at: &str| {
+        for divergence in divergences {
+            let severity = if divergence.is_critical() {
+                critical = true;
+                "critical"
+            } else {
+                "warning"
+            };
+            println!("DIVERGENCE {severity} {at}: {divergence}");
+        }
+    };
+    for entry in &entries {
+        let at = format!("before {} (slot {})", entry.signature, entry.slot);
+        report(ledger.apply(&entry.event), &at);
+    }
+    report(
+        ledger.finish(state.total_assets, state.total_shares),
+        "since the last event",
+    );
+
+    println!(
+        "Replayed {} events from {} transactions for vault {vault}: {} assets, {} shares",
+        ledger.events(),
+        signatures.len(),
+        state.total_assets,
+        state.total_shares
+    );
+    Ok(critical)
+}
@@ -0,0 +1,222 @@
+//! Subcommands. The payer is the protocol owner, depositor and redeemer.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use vault_pda::constants::MAX_VAULT_NAME_LEN;
+use vault_pda::state::Vault;
+use vault_pda_client::pda::VaultAddresses;
+use vault_pda_client::{
+    deserialize, fetch, fetch_protocol_state, fetch_vault_state, fetch_vaults, initialize_ix,
+    initialize_vault_ix, migrate_protocol_ix, migrate_vault_ix, set_vault_name_ix, DepositBuilder,
+    RedeemBuilder, VaultClient, VaultFilter, VaultState,
+};
+
+use crate::Result;
+
+pub async fn init(client: &VaultClient) -> Result<()> {
+    let ix = initialize_ix(&client.program(), &client.payer());
+    let signature = client.send(&[ix], &[]).await?;
+    println!(
+        "Initialized protocol, owner {}: {signature}",
+        client.payer()
+    );
+    Ok(())
+}
+
+/// Brings a protocol state that predates the current layout up to date
+pub async fn migrate_protocol(client: &VaultClient) -> Result<()> {
+    let ix = migrate_protocol_ix(&client.program(), &client.payer());
+    let signature = client.send(&[ix], &[]).await?;
+    println!("Migrated protocol: {signature}");
+    Ok(())
+}
+
+/// Brings a vault that predates the current layout up to date and registers
+/// it
+pub async fn migrate_vault(client: &VaultClient, mint: &Pubkey) -> Result<()> {
+    let ix = migrate_vault_ix(&client.program(), mint, &client.payer());
+    let signature = client.send(&[ix], &[]).await?;
+    let vault = VaultAddresses::new(&client.program(), mint);
+    println!("Migrated vault {}: {signature}", vault.vault);
+    Ok(())
+}
+
+pub async fn create_vault(client: &VaultClient, mint: &Pubkey, name: &str) -> Result<()> {
+    check_name(name)?;
+    let token_program = token_program_of(client, mint).await?;
+    let ix = initialize_vault_ix(
+        &client.program(),
+        mint,
+        &client.payer(),
+        &token_program,
+        name,
+    );
+    let signature = client.send(&[ix], &[]).await?;
+
+    let vault = VaultAddresses::new(&client.program(), mint);
+    println!("Created vault {}: {signature}", vault.vault);
+    println!("  share mint: {}", vault.share_mint);
+    Ok(())
+}
+
+pub async fn rename_vault(client: &VaultClient, mint: &Pubkey, name: &str) -> Result<()> {
+    check_name(name)?;
+    let ix = set_vault_name_ix(&client.program(), mint, &client.payer(), name);
+    let signature = client.send(&[ix], &[]).await?;
+    println!("Renamed vault to {name:?}: {signature}");
+    Ok(())
+}
+
+/// Deposits from the payer's associated token account, creating its share
+/// account if needed
+pub async fn deposit(client: &VaultClient, mint: &Pubkey, amount: u64) -> Result<()> {
+    let token_program = token_program_of(client, mint).await?;
+    let state = client.vault_state(mint).await?;
+    let expected = state.preview_deposit(amount)?;
+
+    let payer = client.payer();
+    let create = create_ata_idempotent_ix(&payer, &state.vault.share_mint, &token_program);
+    let mut builder = DepositBuilder::new(*mint, payer, amount)
+        .program_id(client.program())
+        .token_program(token_program);
+    if let Some(treasury) = client.treasury(mint).await? {
+        builder = builder.treasury(treasury);
+    }
+    let ix = builder.build();
+    let signature = client.send(&[create, ix], &[]).await?;
+    println!("Deposited {amount} for {expected} shares (previewed): {signature}");
+    Ok(())
+}
+
+/// Redeems `shares`, or every share the payer holds and then closes the
+/// emptied share account for its rent
+pub async fn redeem(client: &VaultClient, mint: &Pubkey, shares: Option<u64>) -> Result<()> {
+    let token_program = token_program_of(client, mint).await?;
+    let state = client.vault_state(mint).await?;
+    let payer = client.payer();
+    let share_account = get_associated_token_address_with_program_id(
+        &payer,
+        &state.vault.share_mint,
+        &token_program,
+    );
+
+    let close = shares.is_none();
+    let shares = match shares {
+        Some(shares) => shares,
+        None => {
+            let account: anchor_spl::token_interface::TokenAccount =
+                fetch(client.rpc(), &share_account).await?;
+            account.amount
+        }
+    };
+    let expected = state.preview_redeem(shares)?;
+
+    let mut builder = RedeemBuilder::new(*mint, payer, shares)
+        .program_id(client.program())
+        .token_program(token_program);
+    if let Some(treasury) = client.treasury(mint).await? {
+        builder = builder.treasury(treasury);
+    }
+    let mut ixs = vec![builder.build()];
+    if close {
+        ixs.push(builder.build_close());
+    }
+    let signature = client.send(&ixs, &[]).await?;
+    println!("Redeemed {shares} shares for {expected} (previewed): {signature}");
+    if close {
+        println!("  closed share account {share_account}");
+    }
+    Ok(())
+}
+
+pub async fn inspect(client: &VaultClient, vault: &Pubkey) -> Result<()> {
+    let account: Vault = fetch(client.rpc(), vault).await?;
+    let state =
+        fetch_vault_state(client.rpc(), &client.program(), &account.underlying_mint).await?;
+    if state.address != *vault {
+        return Err(format!("{vault} is not at its PDA, {}", state.address).into());
+    }
+    print_vault(&state);
+
+    if let Ok(protocol) = fetch_protocol_state(client.rpc(), &client.program()).await {
+        println!("  protocol owner: {}", protocol.owner);
+    }
+    Ok(())
+}
+
+pub async fn list_vaults(client: &VaultClient, filter: VaultFilter) -> Result<()> {
+    let vaults = fetch_vaults(client.rpc(), &client.program(), filter).await?;
+    if vaults.is_empty() {
+        println!("No vaults");
+    }
+    for (address, vault) in vaults {
+        println!(
+            "{address}  {:?}  underlying {}  shares {}",
+            vault.name(),
+            vault.underlying_mint,
+            vault.share_mint
+        );
+    }
+    Ok(())
+}
+
+fn print_vault(state: &VaultState) {
+    let vault = &state.vault;
+    let price = match state.totals().share_price() {
+        Some(price) => format!("{price:.6}"),
+        None => "-".to_string(),
+    };
+    println!("Vault {}", state.address);
+    println!("  name:                {:?}", vault.name());
+    println!("  underlying mint:     {}", vault.underlying_mint);
+    println!("  share mint:          {}", vault.share_mint);
+    println!("  vault token account: {}", vault.vault_token_account);
+    println!("  creator:             {}", vault.creator);
+    println!("  bump:                {}", vault.bump);
+    println!("  total assets:        {}", state.total_assets);
+    println!("  total shares:        {}", state.total_shares);
+    println!("  share price:         {price}");
+    if state.frozen {
+        println!("  vault token account is frozen");
+    }
+}
+
+/// Fails before sending a name the program would reject
+fn check_name(name: &str) -> Result<()> {
+    if Vault::encode_name(name).is_none() {
+        return Err(format!(
+            "Vault names must be at most {MAX_VAULT_NAME_LEN} bytes, without NUL bytes"
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// SPL Token or Token-2022, whichever owns `mint`
+async fn token_program_of(client: &VaultClient, mint: &Pubkey) -> Result<Pubkey> {
+    let account = client.rpc().get_account(mint).await?;
+    // Fails on anything but a mint
+    deserialize::<anchor_spl::token_interface::Mint>(&account.data)?;
+    Ok(account.owner)
+}
+
+/// The associated token program's `CreateIdempotent` for the payer's `mint`
+/// account
+fn create_ata_idempotent_ix(payer: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: anchor_spl::associated_token::ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(payer, mint, token_program),
+                false,
+            ),
+            AccountMeta::new_readonly(*payer, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(anchor_lang::system_program::ID, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data: vec![1],
+    }
+}
@@ -0,0 +1,135 @@
+//! `decode`: prints a program account as JSON, detecting its type by
+//! discriminator.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::Hash;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::json;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use vault_pda::state::AdminAction;
+use vault_pda_client::{try_deserialize_any, AccountType, AnyAccount};
+
+use crate::Result;
+
+/// Decodes `input`, an account address or its base64 data
+pub async fn decode(rpc: &RpcClient, program_id: &Pubkey, input: &str) -> Result<()> {
+    let data = match input.parse::<Pubkey>() {
+        Ok(address) => {
+            let account = rpc.get_account(&address).await?;
+            if account.owner != *program_id {
+                return Err(
+                    format!("{address} is owned by {}, not the program", account.owner).into(),
+                );
+            }
+            account.data
+        }
+        Err(_) => STANDARD
+            .decode(input.trim())
+            .map_err(|e| format!("Neither a pubkey nor base64 account data: {e}"))?,
+    };
+
+    let (name, json) = decode_data(&data)?;
+    println!("{name}");
+    println!("{json}");
+    Ok(())
+}
+
+/// The account type's name and its fields as pretty JSON
+fn decode_data(data: &[u8]) -> Result<(&'static str, String)> {
+    if AccountType::detect(data).is_none() {
+        let prefix = &data[..data.len().min(8)];
+        return Err(format!("Unknown discriminator {prefix:?}").into());
+    }
+    let account = try_deserialize_any(data)?;
+    let json = match &account {
+        AnyAccount::ProtocolState(state) => serde_json::to_value(state)?,
+        AnyAccount::VaultAuthority(authority) => serde_json::to_value(authority)?,
+        AnyAccount::Vault(vault) => serde_json::to_value(vault)?,
+        AnyAccount::VaultRegistry(vaults) => json!({
+            "count": vaults.len(),
+            "vaults": vaults.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+        }),
+        AnyAccount::PriceHistory(history) => json!({
+            "vault": history.vault.to_string(),
+            "head": history.head,
+            "len": history.len,
+            "latest": history.latest().map(|point| json!({
+                "slot": point.slot,
+                "total_assets": point.total_assets,
+                "total_shares": point.total_shares,
+            })),
+        }),
+        AnyAccount::Flag(flag) => serde_json::to_value(flag)?,
+        AnyAccount::Player(player) => serde_json::to_value(player)?,
+        AnyAccount::ReserveConfig(config) => serde_json::to_value(config)?,
+        AnyAccount::FeeExemption(exemption) => serde_json::to_value(exemption)?,
+        AnyAccount::ActivityStats(stats) => json!({
+            "vault": stats.vault.to_string(),
+            "head": stats.head,
+            "len": stats.len,
+            "withdrawal_limit": stats.withdrawal_limit,
+            "limit_window_slots": stats.limit_window_slots,
+            "latest": stats.iter().next().map(|bucket| json!({
+                "slot": bucket.slot,
+                "deposited": bucket.deposited,
+                "withdrawn": bucket.withdrawn,
+            })),
+        }),
+        AnyAccount::AdminLog(log) => json!({
+            "len": log.len,
+            "entries": log.iter().map(|entry| json!({
+                "slot": entry.slot,
+                "actor": entry.actor.to_string(),
+                "action": AdminAction::from_code(entry.action)
+                    .map_or_else(|| entry.action.to_string(), |action| format!("{action:?}")),
+                "params_hash": Hash::new_from_array(entry.params_hash).to_string(),
+            })).collect::<Vec<_>>(),
+        }),
+    };
+    Ok((
+        account.account_type().name(),
+        serde_json::to_string_pretty(&json)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{AccountSerialize, Discriminator};
+    use vault_pda::state::{PriceHistory, ProtocolState, VaultAuthority};
+
+    use super::*;
+
+    #[test]
+    fn detects_accounts_by_discriminator() {
+        let mut data = Vec::new();
+        ProtocolState {
+            owner: Pubkey::new_unique(),
+            upgrade_authority: None,
+            renounce_upgrades_expected: false,
+            upgrade_authority_synced_slot: None,
+            version: [0, 1, 0],
+            features: ProtocolState::DEFAULT_FEATURES,
+            bump: 254,
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        let (name, json) = decode_data(&data).unwrap();
+        assert_eq!(name, "ProtocolState");
+        assert!(json.contains("\"bump\": 254"));
+        assert!(json.contains("\"upgrade_authority\": null"));
+
+        let mut data = Vec::new();
+        VaultAuthority { bump: 7 }.try_serialize(&mut data).unwrap();
+        assert_eq!(decode_data(&data).unwrap().0, "VaultAuthority");
+
+        let mut data = PriceHistory::DISCRIMINATOR.to_vec();
+        data.resize(PriceHistory::LEN, 0);
+        let (name, json) = decode_data(&data).unwrap();
+        assert_eq!(name, "PriceHistory");
+        assert!(json.contains("\"latest\": null"));
+
+        assert!(decode_data(&[0; 8]).is_err());
+        assert!(decode_data(&[]).is_err());
+    }
+}
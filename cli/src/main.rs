@@ -0,0 +1,145 @@
+//! Command-line tool for operating vault deployments.
+//!
+//! ```bash
+//! vault-cli [--url <rpc>] [--keypair <path>] [--program-id <pubkey>] <command>
+//!
+//!   init                                  initialize the protocol, owned by the keypair
+//!   create-vault <underlying-mint> [<name>]
+//!                                         create the vault over a mint
+//!   rename-vault <underlying-mint> <name> rename a vault, as its creator
+//!   migrate-protocol                      migrate a protocol state from the first release
+//!   migrate-vault <underlying-mint>       migrate and register a vault from the first release
+//!   deposit <underlying-mint> <amount>    deposit from the keypair's token account
+//!   redeem <underlying-mint> [<shares>]   redeem shares, all of them by default
+//!   inspect <vault>                       print a vault with its balances
+//!   list-vaults [--mint <mint> | --creator <pubkey>]
+//!                                         print the program's vaults, optionally
+//!                                         only those over a mint or by a creator
+//!   decode <address | base64-data>        print a program account as JSON
+//! ```
+//!
+//! `--url` takes an RPC URL or `localnet`, `devnet` or `mainnet-beta`
+//! (localnet by default). The keypair defaults to the Solana CLI's and pays
+//! for and signs every transaction; `decode` needs none.
+
+use std::process::ExitCode;
+
+use anchor_lang::prelude::Pubkey;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::read_keypair_file;
+use vault_pda_client::{VaultClient, VaultFilter};
+
+mod commands;
+mod decode;
+
+const USAGE: &str =
+    "usage: vault-cli [--url <rpc>] [--keypair <path>] [--program-id <pubkey>] <command>
+
+commands:
+  init
+  create-vault <underlying-mint> [<name>]
+  rename-vault <underlying-mint> <name>
+  migrate-protocol
+  migrate-vault <underlying-mint>
+  deposit <underlying-mint> <amount>
+  redeem <underlying-mint> [<shares>]
+  inspect <vault>
+  list-vaults [--mint <mint> | --creator <pubkey>]
+  decode <address | base64-data>";
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn main() -> ExitCode {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to build tokio runtime");
+
+    match runtime.block_on(run(std::env::args().skip(1).collect())) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("vault-cli: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: Vec<String>) -> Result<()> {
+    let mut url = "localnet".to_string();
+    let mut keypair = None;
+    let mut program_id = vault_pda::ID;
+    let mut positional = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--url" | "-u" => url = args.next().ok_or(USAGE)?,
+            "--keypair" | "-k" => keypair = Some(args.next().ok_or(USAGE)?),
+            "--program-id" => program_id = args.next().ok_or(USAGE)?.parse()?,
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let args: Vec<&str> = positional.iter().map(String::as_str).collect();
+    if let ["decode", input] = args.as_slice() {
+        let rpc = RpcClient::new_with_commitment(rpc_url(&url), CommitmentConfig::confirmed());
+        return decode::decode(&rpc, &program_id, input).await;
+    }
+
+    let keypair = match keypair {
+        Some(path) => path,
+        None => format!("{}/.config/solana/id.json", std::env::var("HOME")?),
+    };
+    let payer =
+        read_keypair_file(&keypair).map_err(|e| format!("Failed to read {keypair}: {e}"))?;
+    let rpc = RpcClient::new_with_commitment(rpc_url(&url), CommitmentConfig::confirmed());
+    let client = VaultClient::new(rpc, payer).program_id(program_id);
+
+    match args.as_slice() {
+        ["init"] => commands::init(&client).await,
+        ["create-vault", mint] => commands::create_vault(&client, &pubkey(mint)?, "").await,
+        ["create-vault", mint, name] => commands::create_vault(&client, &pubkey(mint)?, name).await,
+        ["rename-vault", mint, name] => commands::rename_vault(&client, &pubkey(mint)?, name).await,
+        ["migrate-protocol"] => commands::migrate_protocol(&client).await,
+        ["migrate-vault", mint] => commands::migrate_vault(&client, &pubkey(mint)?).await,
+        ["deposit", mint, amount] => {
+            commands::deposit(&client, &pubkey(mint)?, amount.parse()?).await
+        }
+        ["redeem", mint] => commands::redeem(&client, &pubkey(mint)?, None).await,
+        ["redeem", mint, shares] => {
+            commands::redeem(&client, &pubkey(mint)?, Some(shares.parse()?)).await
+        }
+        ["inspect", vault] => commands::inspect(&client, &pubkey(vault)?).await,
+        ["list-vaults"] => commands::list_vaults(&client, VaultFilter::All).await,
+        ["list-vaults", "--mint", mint] => {
+            let filter = VaultFilter::UnderlyingMint(pubkey(mint)?);
+            commands::list_vaults(&client, filter).await
+        }
+        ["list-vaults", "--creator", creator] => {
+            let filter = VaultFilter::Creator(pubkey(creator)?);
+            commands::list_vaults(&client, filter).await
+        }
+        _ => Err(USAGE.into()),
+    }
+}
+
+fn rpc_url(url: &str) -> String {
+    match url {
+        "localnet" | "l" => "http://127.0.0.1:8899",
+        "devnet" | "d" => "https://api.devnet.solana.com",
+        "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+        url => url,
+    }
+    .to_string()
+}
+
+fn pubkey(value: &str) -> Result<Pubkey> {
+    value
+        .parse()
+        .map_err(|e| format!("Invalid pubkey {value}: {e}").into())
+}
@@ -0,0 +1,304 @@
+//! A token program that speaks the SPL Token instruction set but cheats.
+//!
+//! Tests load [`process_instruction`] into `solana-program-test` as a builtin,
+//! once per [`Behavior`] at that behavior's [`Behavior::program_id`], and hand
+//! it to the vault in place of a real token program. The vault takes its token
+//! program and token accounts through Anchor's token interface, which only
+//! admits SPL Token and Token-2022, so every behavior must be refused before
+//! the vault issues a single CPI.
+//!
+//! Mints and token accounts owned by the mock use the SPL Token layout, so
+//! their bytes parse as real ones and only the owner gives them away. Only the
+//! instructions the vault issues are implemented, for single-signer
+//! authorities; delegates, multisigs and freezing are not.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program::invoke,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+};
+use spl_token::{
+    error::TokenError,
+    instruction::TokenInstruction,
+    state::{Account, AccountState, Mint},
+};
+
+/// How the mock departs from SPL Token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Behavior {
+    /// Initializes mints with one more decimal than requested and accepts any
+    /// decimals in the `*Checked` instructions
+    WrongDecimals,
+    /// Credits the destination of a transfer one token less than it debits
+    /// the source
+    Skim,
+    /// Before acting, calls the program passed as the instruction's last
+    /// account, if it is executable, with no data
+    Reenter,
+}
+
+impl Behavior {
+    pub const ALL: [Behavior; 3] = [Behavior::WrongDecimals, Behavior::Skim, Behavior::Reenter];
+
+    /// Address the mock is loaded at for this behavior
+    pub const fn program_id(self) -> Pubkey {
+        Pubkey::new_from_array(padded(self.name().as_bytes()))
+    }
+
+    /// Name of the builtin, unique per behavior
+    pub const fn name(self) -> &'static str {
+        match self {
+            Behavior::WrongDecimals => "malicious_token_wrong_decimals",
+            Behavior::Skim => "malicious_token_skim",
+            Behavior::Reenter => "malicious_token_reenter",
+        }
+    }
+
+    /// The behavior loaded at `program_id`, if any
+    pub fn from_program_id(program_id: &Pubkey) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|behavior| behavior.program_id() == *program_id)
+    }
+}
+
+/// `name` zero-padded to a pubkey, so the addresses read back as their names
+const fn padded(name: &[u8]) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    let mut index = 0;
+    while index < name.len() {
+        bytes[index] = name[index];
+        index += 1;
+    }
+    bytes
+}
+
+/// Processes an SPL Token instruction the way the behavior at `program_id`
+/// does
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    let behavior = Behavior::from_program_id(program_id).ok_or(ProgramError::IncorrectProgramId)?;
+
+    if behavior == Behavior::Reenter {
+        if let Some(target) = accounts.last().filter(|account| account.executable) {
+            invoke(
+                &Instruction::new_with_bytes(*target.key, &[], vec![]),
+                std::slice::from_ref(target),
+            )?;
+        }
+    }
+
+    let checked = |decimals: u8, mint: &Mint| {
+        if behavior != Behavior::WrongDecimals && decimals != mint.decimals {
+            return Err(ProgramError::from(TokenError::MintDecimalsMismatch));
+        }
+        Ok(())
+    };
+
+    let iter = &mut accounts.iter();
+    match TokenInstruction::unpack(input)? {
+        TokenInstruction::InitializeMint2 {
+            decimals,
+            mint_authority,
+            freeze_authority,
+        } => {
+            let mint = next_account_info(iter)?;
+            uninitialized::<Mint>(mint)?;
+            let decimals = match behavior {
+                Behavior::WrongDecimals => decimals.saturating_add(1),
+                _ => decimals,
+            };
+            store(
+                program_id,
+                mint,
+                Mint {
+                    mint_authority: COption::Some(mint_authority),
+                    supply: 0,
+                    decimals,
+                    is_initialized: true,
+                    freeze_authority,
+                },
+            )
+        }
+        TokenInstruction::InitializeAccount3 { owner } => {
+            let account = next_account_info(iter)?;
+            let mint = next_account_info(iter)?;
+            uninitialized::<Account>(account)?;
+            load::<Mint>(program_id, mint)?;
+            store(
+                program_id,
+                account,
+                Account {
+                    mint: *mint.key,
+                    owner,
+                    state: AccountState::Initialized,
+                    ..Account::default()
+                },
+            )
+        }
+        TokenInstruction::MintTo { amount } => mint_to(program_id, iter, amount, |_| Ok(())),
+        TokenInstruction::MintToChecked { amount, decimals } => {
+            mint_to(program_id, iter, amount, |mint| checked(decimals, mint))
+        }
+        TokenInstruction::Transfer { amount } => {
+            let source = next_account_info(iter)?;
+            let destination = next_account_info(iter)?;
+            let authority = next_account_info(iter)?;
+            transfer(program_id, behavior, source, destination, authority, amount)
+        }
+        TokenInstruction::TransferChecked { amount, decimals } => {
+            let source = next_account_info(iter)?;
+            let mint = next_account_info(iter)?;
+            let destination = next_account_info(iter)?;
+            let authority = next_account_info(iter)?;
+            checked(decimals, &load(program_id, mint)?)?;
+            transfer(program_id, behavior, source, destination, authority, amount)
+        }
+        TokenInstruction::Burn { amount } => burn(program_id, iter, amount, |_| Ok(())),
+        TokenInstruction::BurnChecked { amount, decimals } => {
+            burn(program_id, iter, amount, |mint| checked(decimals, mint))
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn mint_to<'a, 'info: 'a>(
+    program_id: &Pubkey,
+    iter: &mut impl Iterator<Item = &'a AccountInfo<'info>>,
+    amount: u64,
+    check: impl Fn(&Mint) -> ProgramResult,
+) -> ProgramResult {
+    let mint_info = next_account_info(iter)?;
+    let account_info = next_account_info(iter)?;
+    let authority = next_account_info(iter)?;
+
+    let mut mint: Mint = load(program_id, mint_info)?;
+    let mut account: Account = load(program_id, account_info)?;
+    check(&mint)?;
+    if account.mint != *mint_info.key {
+        return Err(TokenError::MintMismatch.into());
+    }
+    authorize(mint.mint_authority.into(), authority)?;
+
+    mint.supply = mint
+        .supply
+        .checked_add(amount)
+        .ok_or(TokenError::Overflow)?;
+    account.amount = account
+        .amount
+        .checked_add(amount)
+        .ok_or(TokenError::Overflow)?;
+    store(program_id, mint_info, mint)?;
+    store(program_id, account_info, account)
+}
+
+fn transfer(
+    program_id: &Pubkey,
+    behavior: Behavior,
+    source_info: &AccountInfo,
+    destination_info: &AccountInfo,
+    authority: &AccountInfo,
+    amount: u64,
+) -> ProgramResult {
+    let mut source: Account = load(program_id, source_info)?;
+    let mut destination: Account = load(program_id, destination_info)?;
+    if source.mint != destination.mint {
+        return Err(TokenError::MintMismatch.into());
+    }
+    authorize(Some(source.owner), authority)?;
+
+    let credited = match behavior {
+        Behavior::Skim => amount.saturating_sub(1),
+        _ => amount,
+    };
+    source.amount = source
+        .amount
+        .checked_sub(amount)
+        .ok_or(TokenError::InsufficientFunds)?;
+    store(program_id, source_info, source)?;
+
+    // Reload, so a transfer to self sees the debit
+    if destination_info.key == source_info.key {
+        destination = load(program_id, destination_info)?;
+    }
+    destination.amount = destination
+        .amount
+        .checked_add(credited)
+        .ok_or(TokenError::Overflow)?;
+    store(program_id, destination_info, destination)
+}
+
+fn burn<'a, 'info: 'a>(
+    program_id: &Pubkey,
+    iter: &mut impl Iterator<Item = &'a AccountInfo<'info>>,
+    amount: u64,
+    check: impl Fn(&Mint) -> ProgramResult,
+) -> ProgramResult {
+    let account_info = next_account_info(iter)?;
+    let mint_info = next_account_info(iter)?;
+    let authority = next_account_info(iter)?;
+
+    let mut account: Account = load(program_id, account_info)?;
+    let mut mint: Mint = load(program_id, mint_info)?;
+    check(&mint)?;
+    if account.mint != *mint_info.key {
+        return Err(TokenError::MintMismatch.into());
+    }
+    authorize(Some(account.owner), authority)?;
+
+    account.amount = account
+        .amount
+        .checked_sub(amount)
+        .ok_or(TokenError::InsufficientFunds)?;
+    mint.supply = mint
+        .supply
+        .checked_sub(amount)
+        .ok_or(TokenError::Overflow)?;
+    store(program_id, account_info, account)?;
+    store(program_id, mint_info, mint)
+}
+
+/// Requires `authority` to be `expected` and to have signed
+fn authorize(expected: Option<Pubkey>, authority: &AccountInfo) -> ProgramResult {
+    if expected != Some(*authority.key) {
+        return Err(TokenError::OwnerMismatch.into());
+    }
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+/// Refuses to initialize an account twice
+fn uninitialized<T: Pack + IsInitialized>(info: &AccountInfo) -> ProgramResult {
+    if T::unpack_unchecked(&info.data.borrow())?.is_initialized() {
+        return Err(TokenError::AlreadyInUse.into());
+    }
+    Ok(())
+}
+
+/// Unpacks the initialized state of an account the mock owns
+fn load<T: Pack + IsInitialized>(
+    program_id: &Pubkey,
+    info: &AccountInfo,
+) -> Result<T, ProgramError> {
+    if info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    T::unpack(&info.data.borrow())
+}
+
+fn store<T: Pack>(program_id: &Pubkey, info: &AccountInfo, state: T) -> ProgramResult {
+    if info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    T::pack(state, &mut info.data.borrow_mut())
+}
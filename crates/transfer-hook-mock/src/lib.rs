@@ -0,0 +1,104 @@
+//! Transfer-hook programs for Token-2022 mints, one honest and one not.
+//!
+//! Tests load [`process_instruction`] into `solana-program-test` as a builtin,
+//! once per [`Variant`] at that variant's [`Variant::program_id`], and point a
+//! mint's `TransferHook` extension at it. Token-2022 then calls the hook on
+//! every `transfer_checked` of the mint, provided the transfer lists the hook
+//! program among its accounts.
+//!
+//! Only `Execute` is implemented. Neither variant needs extra accounts, so no
+//! validation account is read and `InitializeExtraAccountMetaList` is refused.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use spl_token_2022::{
+    extension::{transfer_hook::TransferHookAccount, BaseStateWithExtensions, StateWithExtensions},
+    state::Account,
+};
+use spl_transfer_hook_interface::instruction::TransferHookInstruction;
+
+/// Error the malicious variant fails every transfer with
+pub const REJECTED: u32 = u32::from_be_bytes(*b"hook");
+
+/// What the hook does with a transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// Allows every transfer made by Token-2022, checking the source account
+    /// is mid-transfer the way the interface promises
+    Benign,
+    /// Fails every transfer with [`REJECTED`], locking whatever the mint's
+    /// accounts hold
+    Malicious,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 2] = [Variant::Benign, Variant::Malicious];
+
+    /// Address the hook is loaded at for this variant
+    pub const fn program_id(self) -> Pubkey {
+        Pubkey::new_from_array(padded(self.name().as_bytes()))
+    }
+
+    /// Name of the builtin, unique per variant
+    pub const fn name(self) -> &'static str {
+        match self {
+            Variant::Benign => "transfer_hook_benign",
+            Variant::Malicious => "transfer_hook_malicious",
+        }
+    }
+
+    /// The variant loaded at `program_id`, if any
+    pub fn from_program_id(program_id: &Pubkey) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|variant| variant.program_id() == *program_id)
+    }
+}
+
+/// `name` zero-padded to a pubkey, so the addresses read back as their names
+const fn padded(name: &[u8]) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    let mut index = 0;
+    while index < name.len() {
+        bytes[index] = name[index];
+        index += 1;
+    }
+    bytes
+}
+
+/// Processes a transfer-hook instruction the way the variant at `program_id`
+/// does
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    let variant = Variant::from_program_id(program_id).ok_or(ProgramError::IncorrectProgramId)?;
+
+    match TransferHookInstruction::unpack(input)? {
+        TransferHookInstruction::Execute { .. } => {
+            let iter = &mut accounts.iter();
+            let source = next_account_info(iter)?;
+            let mint = next_account_info(iter)?;
+
+            match variant {
+                Variant::Benign => {
+                    let data = source.try_borrow_data()?;
+                    let state = StateWithExtensions::<Account>::unpack(&data)?;
+                    let transferring =
+                        bool::from(state.get_extension::<TransferHookAccount>()?.transferring);
+                    if state.base.mint != *mint.key || !transferring {
+                        return Err(ProgramError::InvalidAccountData);
+                    }
+                    Ok(())
+                }
+                Variant::Malicious => Err(ProgramError::Custom(REJECTED)),
+            }
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
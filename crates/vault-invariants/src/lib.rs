@@ -0,0 +1,273 @@
+//! Invariants every deposit and redeem must satisfy.
+//!
+//! The fuzz targets and the integration tests snapshot the same four balances
+//! around an operation and hand both snapshots to these checks, so a property
+//! is stated once and cannot drift between harnesses. Expected amounts are
+//! computed here in plain `u128` arithmetic, independently of `vault-math`,
+//! since the kernel is part of what is being checked.
+//!
+//! Every check panics with a message naming the violated property. They are
+//! `#[track_caller]`, so the reported location is the harness that observed
+//! the violation rather than this crate.
+//!
+//! Disable the default `vuln-inflation` feature to check a program built
+//! without it, whose conversions add [`VIRTUAL_OFFSET`] to both sides.
+
+#![no_std]
+
+/// Precision used when comparing the value of one share
+pub const PRECISION: u128 = 1_000_000_000;
+
+/// Virtual shares and assets the program adds to both sides of a conversion
+pub const VIRTUAL_OFFSET: u128 = if cfg!(feature = "vuln-inflation") {
+    0
+} else {
+    1
+};
+
+/// Balances around a single deposit or redeem
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Underlying tokens held by the vault
+    pub vault_balance: u64,
+    /// Share mint supply
+    pub share_supply: u64,
+    /// Underlying tokens held by the acting user
+    pub user_balance: u64,
+    /// Shares held by the acting user
+    pub user_shares: u64,
+}
+
+impl Snapshot {
+    /// Vault tokens backing one share, scaled by [`PRECISION`], counting the
+    /// [`VIRTUAL_OFFSET`] on both sides
+    ///
+    /// `None` while no shares are outstanding.
+    pub fn value_per_share(&self) -> Option<u128> {
+        if self.share_supply == 0 {
+            return None;
+        }
+        Some(
+            (self.vault_balance as u128 + VIRTUAL_OFFSET) * PRECISION
+                / (self.share_supply as u128 + VIRTUAL_OFFSET),
+        )
+    }
+}
+
+/// The operation performed between two snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exchange {
+    /// `amount` underlying tokens were deposited
+    Deposit { amount: u64 },
+    /// `shares` shares were redeemed
+    Redeem { shares: u64 },
+}
+
+/// Shares the user gained across a deposit
+#[track_caller]
+fn minted(before: &Snapshot, after: &Snapshot) -> u64 {
+    after
+        .user_shares
+        .checked_sub(before.user_shares)
+        .expect("User share balance decreased during a deposit")
+}
+
+/// Underlying tokens the user gained across a redeem
+#[track_caller]
+fn returned(before: &Snapshot, after: &Snapshot) -> u64 {
+    after
+        .user_balance
+        .checked_sub(before.user_balance)
+        .expect("User balance decreased during a redeem")
+}
+
+/// Tokens are neither created nor destroyed between the vault and the user
+#[track_caller]
+pub fn assert_conservation(before: &Snapshot, after: &Snapshot) {
+    assert_eq!(
+        before.vault_balance as u128 + before.user_balance as u128,
+        after.vault_balance as u128 + after.user_balance as u128,
+        "CRITICAL: Token conservation violated! Tokens created or destroyed. Before: vault={} user={}, After: vault={} user={}",
+        before.vault_balance,
+        before.user_balance,
+        after.vault_balance,
+        after.user_balance
+    );
+}
+
+/// Balances move by exactly the deposited amount or the redeemed shares
+#[track_caller]
+pub fn assert_exact_accounting(before: &Snapshot, after: &Snapshot, exchange: Exchange) {
+    match exchange {
+        Exchange::Deposit { amount } => {
+            assert_eq!(
+                after.vault_balance,
+                before.vault_balance + amount,
+                "Vault balance should increase by exact deposit amount"
+            );
+            assert_eq!(
+                after.user_balance,
+                before.user_balance - amount,
+                "User balance should decrease by exact deposit amount"
+            );
+
+            let minted = minted(before, after);
+            assert_eq!(
+                after.share_supply,
+                before.share_supply + minted,
+                "Share supply should increase by exactly the shares minted"
+            );
+        }
+        Exchange::Redeem { shares } => {
+            assert_eq!(
+                after.share_supply,
+                before.share_supply - shares,
+                "Share supply should decrease by exactly the shares redeemed"
+            );
+            assert_eq!(
+                after.user_shares,
+                before.user_shares - shares,
+                "User share balance should decrease by exactly the shares redeemed"
+            );
+        }
+    }
+}
+
+/// The value of one share never decreases
+///
+/// Only meaningful while shares are outstanding on both sides, so the first
+/// deposit and a full exit are skipped.
+#[track_caller]
+pub fn assert_no_dilution(before: &Snapshot, after: &Snapshot) {
+    let (Some(value_before), Some(value_after)) =
+        (before.value_per_share(), after.value_per_share())
+    else {
+        return;
+    };
+
+    assert!(
+        value_after >= value_before,
+        "CRITICAL VULNERABILITY: Share dilution! Value per share decreased from {} to {} (precision=1e9). \
+        Existing shareholders lost value! \
+        Before: vault={} shares={}, After: vault={} shares={}",
+        value_before,
+        value_after,
+        before.vault_balance,
+        before.share_supply,
+        after.vault_balance,
+        after.share_supply
+    );
+}
+
+/// The user never receives more than their contribution is worth
+///
+/// A deposit must mint at least one share and no more than 0.1% over the
+/// proportional amount. A redeem must pay out no more than the proportional
+/// amount, so rounding always favors the vault.
+#[track_caller]
+pub fn assert_fair_exchange(before: &Snapshot, after: &Snapshot, exchange: Exchange) {
+    match exchange {
+        Exchange::Deposit { amount } => {
+            let minted = minted(before, after);
+            assert!(
+                minted > 0,
+                "User deposited {} tokens but received 0 shares - value extraction vulnerability!",
+                amount
+            );
+
+            if before.share_supply > 0 {
+                let expected = amount as u128 * (before.share_supply as u128 + VIRTUAL_OFFSET)
+                    / (before.vault_balance as u128 + VIRTUAL_OFFSET);
+                let max_acceptable = expected + expected / 1000;
+                assert!(
+                    minted as u128 <= max_acceptable,
+                    "VULNERABILITY: User received too many shares! Possible rounding exploit. \
+                    Expected: {} shares, Got: {} shares, Max acceptable: {} (with 0.1% tolerance). \
+                    Deposit: {}, Vault before: {}, Shares before: {}",
+                    expected,
+                    minted,
+                    max_acceptable,
+                    amount,
+                    before.vault_balance,
+                    before.share_supply
+                );
+            }
+        }
+        Exchange::Redeem { shares } => {
+            let returned = returned(before, after);
+            assert!(
+                returned as u128 * (before.share_supply as u128 + VIRTUAL_OFFSET)
+                    <= shares as u128 * (before.vault_balance as u128 + VIRTUAL_OFFSET),
+                "VULNERABILITY: Redeemer received too much! Got {} for {} shares. \
+                Vault before: {}, Shares before: {}",
+                returned,
+                shares,
+                before.vault_balance,
+                before.share_supply
+            );
+        }
+    }
+}
+
+/// Once the last share is burned, the vault holds nothing
+///
+/// Skipped with a [`VIRTUAL_OFFSET`], where the virtual share keeps its part
+/// of the vault.
+#[track_caller]
+pub fn assert_full_exit_drains(after: &Snapshot) {
+    if after.share_supply == 0 && VIRTUAL_OFFSET == 0 {
+        assert_eq!(
+            after.vault_balance, 0,
+            "Last redeemer should receive every remaining token"
+        );
+    }
+}
+
+/// The amount exchanged matches the proportional formula
+///
+/// Redeems must pay exactly `shares * vault / supply`, rounded down. Deposits
+/// into a vault with shares outstanding may differ from
+/// `amount * supply / vault` by one share. Both sides of either formula
+/// include the [`VIRTUAL_OFFSET`].
+#[track_caller]
+pub fn assert_expected_conversion(before: &Snapshot, after: &Snapshot, exchange: Exchange) {
+    let supply = before.share_supply as u128 + VIRTUAL_OFFSET;
+    let vault = before.vault_balance as u128 + VIRTUAL_OFFSET;
+    match exchange {
+        Exchange::Deposit { amount } => {
+            if supply == 0 {
+                return;
+            }
+            let minted = minted(before, after) as u128;
+            let expected = amount as u128 * supply / vault;
+            let diff = minted.abs_diff(expected);
+            assert!(
+                diff <= 1,
+                "Share calculation incorrect. Expected: {} (±1), Got: {}, Diff: {}",
+                expected,
+                minted,
+                diff
+            );
+        }
+        Exchange::Redeem { shares } => {
+            let returned = returned(before, after) as u128;
+            let expected = shares as u128 * vault / supply;
+            assert_eq!(
+                returned, expected,
+                "Redeem calculation incorrect. Expected: {}, Got: {}",
+                expected, returned
+            );
+        }
+    }
+}
+
+/// Runs every check above for one successful operation
+#[track_caller]
+pub fn assert_exchange(before: &Snapshot, after: &Snapshot, exchange: Exchange) {
+    assert_conservation(before, after);
+    assert_exact_accounting(before, after, exchange);
+    assert_no_dilution(before, after);
+    assert_fair_exchange(before, after, exchange);
+    assert_full_exit_drains(after);
+    assert_expected_conversion(before, after, exchange);
+}
@@ -0,0 +1,107 @@
+//! Each check accepts an honest operation and rejects the matching exploit.
+
+use vault_invariants::*;
+
+const BEFORE: Snapshot = Snapshot {
+    vault_balance: 1_000,
+    share_supply: 500,
+    user_balance: 300,
+    user_shares: 100,
+};
+
+#[test]
+fn honest_deposit_passes() {
+    // 300 * 500 / 1000 = 150 shares
+    let after = Snapshot {
+        vault_balance: 1_300,
+        share_supply: 650,
+        user_balance: 0,
+        user_shares: 250,
+    };
+    assert_exchange(&BEFORE, &after, Exchange::Deposit { amount: 300 });
+}
+
+#[test]
+fn first_deposit_passes() {
+    let before = Snapshot {
+        user_balance: 300,
+        ..Snapshot::default()
+    };
+    let after = Snapshot {
+        vault_balance: 300,
+        share_supply: 300,
+        user_balance: 0,
+        user_shares: 300,
+    };
+    assert_exchange(&before, &after, Exchange::Deposit { amount: 300 });
+}
+
+#[test]
+#[cfg(feature = "vuln-inflation")]
+fn honest_redeem_passes() {
+    // 100 * 1000 / 500 = 200 tokens
+    let after = Snapshot {
+        vault_balance: 800,
+        share_supply: 400,
+        user_balance: 500,
+        user_shares: 0,
+    };
+    assert_exchange(&BEFORE, &after, Exchange::Redeem { shares: 100 });
+}
+
+#[test]
+#[should_panic(expected = "Token conservation violated")]
+fn minted_tokens_break_conservation() {
+    let after = Snapshot {
+        user_balance: BEFORE.user_balance + 1,
+        ..BEFORE
+    };
+    assert_conservation(&BEFORE, &after);
+}
+
+#[test]
+#[should_panic(expected = "Share dilution")]
+fn free_shares_dilute_holders() {
+    let after = Snapshot {
+        share_supply: BEFORE.share_supply + 1,
+        ..BEFORE
+    };
+    assert_no_dilution(&BEFORE, &after);
+}
+
+#[test]
+#[should_panic(expected = "too many shares")]
+fn over_minting_deposit_is_unfair() {
+    let after = Snapshot {
+        vault_balance: 1_300,
+        share_supply: 700,
+        user_balance: 0,
+        user_shares: 300,
+    };
+    assert_fair_exchange(&BEFORE, &after, Exchange::Deposit { amount: 300 });
+}
+
+#[test]
+#[should_panic(expected = "Redeemer received too much")]
+fn over_paying_redeem_is_unfair() {
+    let after = Snapshot {
+        vault_balance: 799,
+        share_supply: 400,
+        user_balance: 501,
+        user_shares: 0,
+    };
+    assert_fair_exchange(&BEFORE, &after, Exchange::Redeem { shares: 100 });
+}
+
+#[test]
+#[cfg(feature = "vuln-inflation")]
+#[should_panic(expected = "Last redeemer")]
+fn full_exit_leaving_dust_is_caught() {
+    let after = Snapshot {
+        vault_balance: 1,
+        share_supply: 0,
+        user_balance: 1_299,
+        user_shares: 0,
+    };
+    assert_full_exit_drains(&after);
+}
@@ -0,0 +1,77 @@
+//! Benchmarks for the share conversion kernel.
+//!
+//! Run with `cargo bench -p vault-math`. Each function is measured across a
+//! few input distributions since the cost of `u128` division depends on the
+//! magnitude of the operands.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use vault_math::{convert_to_assets, convert_to_shares, mul_div_ceil, mul_div_floor, Rounding};
+
+/// (amount, total_shares, total_assets) triples per distribution
+const DISTRIBUTIONS: &[(&str, u64, u64, u64)] = &[
+    ("small", 7, 13, 17),
+    ("typical", 1_000_000, 5_000_000_000, 5_250_000_000),
+    ("skewed", 1_000_000_000, 1, u64::MAX / 3),
+    ("near_max", u64::MAX - 1, u64::MAX / 2, u64::MAX),
+];
+
+fn bench_mul_div(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mul_div");
+    for &(name, a, b, d) in DISTRIBUTIONS {
+        let input = (a as u128, b as u128, d as u128);
+        group.bench_with_input(
+            BenchmarkId::new("floor", name),
+            &input,
+            |bench, &(a, b, d)| {
+                bench.iter(|| mul_div_floor(black_box(a), black_box(b), black_box(d)))
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("ceil", name),
+            &input,
+            |bench, &(a, b, d)| {
+                bench.iter(|| mul_div_ceil(black_box(a), black_box(b), black_box(d)))
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_conversions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("convert");
+    for &(name, amount, total_shares, total_assets) in DISTRIBUTIONS {
+        let input = (amount, total_shares, total_assets);
+        group.bench_with_input(
+            BenchmarkId::new("to_shares", name),
+            &input,
+            |bench, &(amount, shares, assets)| {
+                bench.iter(|| {
+                    convert_to_shares(
+                        black_box(amount),
+                        black_box(shares),
+                        black_box(assets),
+                        Rounding::Down,
+                    )
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("to_assets", name),
+            &input,
+            |bench, &(amount, shares, assets)| {
+                bench.iter(|| {
+                    convert_to_assets(
+                        black_box(amount),
+                        black_box(shares),
+                        black_box(assets),
+                        Rounding::Down,
+                    )
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_mul_div, bench_conversions);
+criterion_main!(benches);
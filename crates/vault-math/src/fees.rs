@@ -0,0 +1,248 @@
+//! Reserve factor accounting.
+//!
+//! A vault with a reserve factor sends `reserve_factor_bps` of its profit to
+//! a treasury. Profit is measured against a high-water mark, the vault's
+//! totals at the last report: it is whatever the vault holds above its
+//! current supply valued at the mark's share price. That valuation cannot
+//! tell a deposit at a price above the mark from yield, so the program
+//! settles the reserve before every deposit and redeem: the mark moves to the
+//! current price, and the flow trades at it without making or losing profit.
+//!
+//! `report` pays the treasury in shares, priced as if it deposited its cut
+//! out of the profit, so no underlying leaves the vault and the rest of the
+//! profit stays with the holders. `skim` pays the cut in underlying instead,
+//! see [`compute_skim`]. Prices carry [`VIRTUAL_OFFSET`] like every other
+//! conversion. Both the profit and the minted shares round down, in the
+//! holders' favor: the treasury is never paid more than its cut, and a report
+//! never leaves the share price below the mark.
+//!
+//! A withdrawal fee is separate from the reserve: [`withdrawal_fee`] is the
+//! part of a redeem's shares handed to the treasury instead of burned.
+
+use crate::kernel::{shares_with_offset, KernelError, VIRTUAL_OFFSET};
+use crate::{mul_div_ceil, mul_div_floor};
+
+/// Basis points in a whole
+pub const MAX_BPS: u16 = 10_000;
+
+/// Highest withdrawal fee a vault can charge, in basis points
+pub const MAX_WITHDRAWAL_FEE_BPS: u16 = 1_000;
+
+/// Outcome of reporting a vault's profit, see [`compute_reserve`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReserveSplit {
+    /// Underlying the vault holds above its supply valued at the mark price
+    pub profit: u64,
+    /// The treasury's cut of `profit`
+    pub reserve: u64,
+    /// Shares minted to the treasury for `reserve`
+    pub shares: u64,
+    /// Vault assets at the new high-water mark
+    pub mark_assets: u64,
+    /// Share supply at the new high-water mark, zero while unset
+    pub mark_shares: u64,
+}
+
+/// Profit of a vault holding `total_assets` against `total_shares` over the
+/// share price `mark_assets / mark_shares`, rounded down
+///
+/// `None` while the price is below the mark (or the valuation overflows). Zero when either supply is
+/// zero: an empty vault has no holders to profit, and an unset mark no price
+/// to profit over.
+pub fn profit_over_mark(
+    total_assets: u64,
+    total_shares: u64,
+    mark_assets: u64,
+    mark_shares: u64,
+) -> Option<u64> {
+    if total_shares == 0 || mark_shares == 0 {
+        return Some(0);
+    }
+    let offset = VIRTUAL_OFFSET as u128;
+    let at_mark = mul_div_ceil(
+        total_shares as u128 + offset,
+        mark_assets as u128 + offset,
+        mark_shares as u128 + offset,
+    )?;
+    let profit = (total_assets as u128 + offset).checked_sub(at_mark)?;
+    // At most `total_assets`, as `at_mark` is at least the offset
+    u64::try_from(profit).ok()
+}
+
+/// Splits the profit a vault made since its high-water mark between its
+/// holders and a treasury taking `reserve_factor_bps` (capped at [`MAX_BPS`])
+///
+/// The new mark is the vault's totals once the treasury's shares are minted,
+/// unless the price is below the old mark, which is then kept so nothing is
+/// paid until the loss is recovered. A cut too small to buy one share is left
+/// with the holders.
+pub fn compute_reserve(
+    total_assets: u64,
+    total_shares: u64,
+    mark_assets: u64,
+    mark_shares: u64,
+    reserve_factor_bps: u16,
+) -> Result<ReserveSplit, KernelError> {
+    let Some(profit) = profit_over_mark(total_assets, total_shares, mark_assets, mark_shares)
+    else {
+        return Ok(ReserveSplit {
+            mark_assets,
+            mark_shares,
+            ..ReserveSplit::default()
+        });
+    };
+
+    let bps = reserve_factor_bps.min(MAX_BPS);
+    let reserve = mul_div_floor(profit as u128, bps as u128, MAX_BPS as u128)
+        .ok_or(KernelError::MathOverflow)? as u64;
+    let shares = if reserve == 0 {
+        0
+    } else {
+        let assets_without_reserve = total_assets
+            .checked_sub(reserve)
+            .ok_or(KernelError::MathOverflow)?;
+        shares_with_offset(
+            reserve,
+            total_shares,
+            assets_without_reserve,
+            VIRTUAL_OFFSET,
+        )
+        .ok_or(KernelError::MathOverflow)?
+    };
+    let mark_shares = total_shares
+        .checked_add(shares)
+        .ok_or(KernelError::MathOverflow)?;
+
+    Ok(ReserveSplit {
+        profit,
+        reserve,
+        shares,
+        mark_assets: total_assets,
+        mark_shares,
+    })
+}
+
+/// Splits the profit like [`compute_reserve`], but for a treasury paid its
+/// cut in underlying taken out of the vault
+///
+/// No shares are minted: the new mark is the vault's totals once the cut has
+/// left, a price the holders keep the rest of the profit above. A price below
+/// the old mark keeps it and pays nothing.
+pub fn compute_skim(
+    total_assets: u64,
+    total_shares: u64,
+    mark_assets: u64,
+    mark_shares: u64,
+    reserve_factor_bps: u16,
+) -> Result<ReserveSplit, KernelError> {
+    let Some(profit) = profit_over_mark(total_assets, total_shares, mark_assets, mark_shares)
+    else {
+        return Ok(ReserveSplit {
+            mark_assets,
+            mark_shares,
+            ..ReserveSplit::default()
+        });
+    };
+
+    let bps = reserve_factor_bps.min(MAX_BPS);
+    let reserve = mul_div_floor(profit as u128, bps as u128, MAX_BPS as u128)
+        .ok_or(KernelError::MathOverflow)? as u64;
+
+    Ok(ReserveSplit {
+        profit,
+        reserve,
+        shares: 0,
+        // `reserve` is at most `profit`, which is at most `total_assets`
+        mark_assets: total_assets - reserve,
+        mark_shares: total_shares,
+    })
+}
+
+/// Shares of a redeem of `shares` kept as a `withdrawal_fee_bps` fee
+/// (capped at [`MAX_BPS`]), rounded up in the vault's favor
+pub fn withdrawal_fee(shares: u64, withdrawal_fee_bps: u16) -> u64 {
+    let bps = withdrawal_fee_bps.min(MAX_BPS);
+    // At most `shares`, so neither the product nor the result overflows
+    mul_div_ceil(shares as u128, bps as u128, MAX_BPS as u128).unwrap_or(shares as u128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn withdrawal_fees_round_up() {
+        assert_eq!(withdrawal_fee(1_000, 30), 3);
+        assert_eq!(withdrawal_fee(1_001, 30), 4);
+        assert_eq!(withdrawal_fee(1, 1), 1);
+        assert_eq!(withdrawal_fee(1_000, 0), 0);
+        assert_eq!(withdrawal_fee(u64::MAX, u16::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn first_report_sets_the_mark() {
+        let split = compute_reserve(1_000, 1_000, 0, 0, 1_000).unwrap();
+        assert_eq!(
+            split,
+            ReserveSplit {
+                mark_assets: 1_000,
+                mark_shares: 1_000,
+                ..ReserveSplit::default()
+            }
+        );
+    }
+
+    #[test]
+    fn splits_yield_at_the_reserve_factor() {
+        // 10% of 1_000 profit, paid in shares at the pre-cut price of 1.9
+        let split = compute_reserve(2_000, 1_000, 1_000, 1_000, 1_000).unwrap();
+        assert_eq!(split.profit, 1_000);
+        assert_eq!(split.reserve, split.profit / 10);
+        assert_eq!(
+            split.shares,
+            shares_with_offset(split.reserve, 1_000, 2_000 - split.reserve, VIRTUAL_OFFSET)
+                .unwrap()
+        );
+        assert_eq!(split.mark_assets, 2_000);
+        assert_eq!(split.mark_shares, 1_000 + split.shares);
+    }
+
+    #[test]
+    fn a_price_below_the_mark_keeps_it() {
+        let split = compute_reserve(900, 1_000, 1_000, 1_000, 1_000).unwrap();
+        assert_eq!(
+            split,
+            ReserveSplit {
+                mark_assets: 1_000,
+                mark_shares: 1_000,
+                ..ReserveSplit::default()
+            }
+        );
+    }
+
+    #[test]
+    fn skims_yield_at_the_reserve_factor() {
+        let split = compute_skim(2_000, 1_000, 1_000, 1_000, 1_000).unwrap();
+        assert_eq!(
+            split,
+            ReserveSplit {
+                profit: 1_000,
+                reserve: 100,
+                shares: 0,
+                mark_assets: 1_900,
+                mark_shares: 1_000,
+            }
+        );
+
+        let split = compute_skim(900, 1_000, 1_000, 1_000, 1_000).unwrap();
+        assert_eq!((split.reserve, split.mark_assets), (0, 1_000));
+    }
+
+    #[test]
+    fn zero_factor_pays_nothing() {
+        let split = compute_reserve(2_000, 1_000, 1_000, 1_000, 0).unwrap();
+        assert_eq!(split.reserve, 0);
+        assert_eq!(split.shares, 0);
+        assert_eq!(split.mark_shares, 1_000);
+    }
+}
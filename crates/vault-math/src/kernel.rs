@@ -0,0 +1,186 @@
+//! Side-effect-free deposit/redeem kernels.
+//!
+//! These mirror the full validation and math done by the `deposit` and
+//! `redeem` handlers on plain values, so they can be unit tested and fuzzed
+//! natively without a banks client. The handlers call them directly and map
+//! `KernelError` onto their own error codes.
+
+use crate::{convert_to_assets, convert_to_shares, mul_div_floor, Rounding};
+
+/// Virtual shares and assets added to both sides of every kernel conversion.
+///
+/// Zero in the challenge build (`vuln-inflation`), where shares are priced
+/// off the raw balances and a donation into a near-empty vault inflates the
+/// share price at the next depositor's expense. One otherwise: the virtual
+/// share soaks up part of any donation, so the attack costs more than it
+/// takes.
+#[cfg(feature = "vuln-inflation")]
+pub const VIRTUAL_OFFSET: u64 = 0;
+#[cfg(not(feature = "vuln-inflation"))]
+pub const VIRTUAL_OFFSET: u64 = 1;
+
+/// Reasons a deposit or redeem is rejected by the kernel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KernelError {
+    /// The input amount is zero
+    InvalidAmount,
+    /// No shares are in circulation
+    NoShares,
+    /// Shares are in circulation but the vault holds no assets
+    EmptyVault,
+    /// The result does not fit in a `u64`
+    MathOverflow,
+    /// The conversion rounds down to zero
+    ZeroOutput,
+}
+
+/// Shares minted for depositing `amount` into a vault with `total_shares`
+/// outstanding and `total_assets` held. Rounds down.
+pub fn compute_shares(
+    amount: u64,
+    total_shares: u64,
+    total_assets: u64,
+) -> Result<u64, KernelError> {
+    if amount == 0 {
+        return Err(KernelError::InvalidAmount);
+    }
+    if total_shares > 0 && total_assets == 0 {
+        return Err(KernelError::EmptyVault);
+    }
+
+    let shares = to_shares(amount, total_shares, total_assets).ok_or(KernelError::MathOverflow)?;
+
+    if shares == 0 {
+        return Err(KernelError::ZeroOutput);
+    }
+    Ok(shares)
+}
+
+/// Underlying assets returned for redeeming `shares` from a vault with
+/// `total_shares` outstanding and `total_assets` held. Rounds down.
+pub fn compute_assets(
+    shares: u64,
+    total_shares: u64,
+    total_assets: u64,
+) -> Result<u64, KernelError> {
+    if shares == 0 {
+        return Err(KernelError::InvalidAmount);
+    }
+    if total_shares == 0 {
+        return Err(KernelError::NoShares);
+    }
+    if total_assets == 0 {
+        return Err(KernelError::EmptyVault);
+    }
+
+    let assets = to_assets(shares, total_shares, total_assets).ok_or(KernelError::MathOverflow)?;
+
+    if assets == 0 {
+        return Err(KernelError::ZeroOutput);
+    }
+    Ok(assets)
+}
+
+/// `convert_to_shares` rounding down, with [`VIRTUAL_OFFSET`] applied
+fn to_shares(amount: u64, total_shares: u64, total_assets: u64) -> Option<u64> {
+    shares_with_offset(amount, total_shares, total_assets, VIRTUAL_OFFSET)
+}
+
+/// `convert_to_assets` rounding down, with [`VIRTUAL_OFFSET`] applied
+fn to_assets(shares: u64, total_shares: u64, total_assets: u64) -> Option<u64> {
+    assets_with_offset(shares, total_shares, total_assets, VIRTUAL_OFFSET)
+}
+
+/// Shares for `amount`, rounding down, with `offset` virtual shares and
+/// assets on both sides
+///
+/// The kernels use [`VIRTUAL_OFFSET`]; other offsets are for tooling that
+/// compares them. Without an offset this is `convert_to_shares`, so an empty
+/// vault converts 1:1.
+pub fn shares_with_offset(
+    amount: u64,
+    total_shares: u64,
+    total_assets: u64,
+    offset: u64,
+) -> Option<u64> {
+    if offset == 0 {
+        return convert_to_shares(amount, total_shares, total_assets, Rounding::Down);
+    }
+    let shares = mul_div_floor(
+        amount as u128,
+        total_shares as u128 + offset as u128,
+        total_assets as u128 + offset as u128,
+    )?;
+    u64::try_from(shares).ok()
+}
+
+/// Assets for `shares`, rounding down, with `offset` virtual shares and
+/// assets on both sides (see [`shares_with_offset`])
+pub fn assets_with_offset(
+    shares: u64,
+    total_shares: u64,
+    total_assets: u64,
+    offset: u64,
+) -> Option<u64> {
+    if offset == 0 {
+        return convert_to_assets(shares, total_shares, total_assets, Rounding::Down);
+    }
+    let assets = mul_div_floor(
+        shares as u128,
+        total_assets as u128 + offset as u128,
+        total_shares as u128 + offset as u128,
+    )?;
+    u64::try_from(assets).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute_shares_matches_handler_rules() {
+        assert_eq!(compute_shares(0, 0, 0), Err(KernelError::InvalidAmount));
+        assert_eq!(compute_shares(100, 0, 0), Ok(100));
+        assert_eq!(compute_shares(100, 10, 0), Err(KernelError::EmptyVault));
+        assert_eq!(compute_shares(1_000, 1_000, 1_100), Ok(909));
+        assert_eq!(compute_shares(1, 1, 2), Err(KernelError::ZeroOutput));
+        assert_eq!(
+            compute_shares(u64::MAX, u64::MAX, 1),
+            Err(KernelError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn compute_assets_matches_handler_rules() {
+        assert_eq!(compute_assets(0, 10, 10), Err(KernelError::InvalidAmount));
+        assert_eq!(compute_assets(5, 0, 10), Err(KernelError::NoShares));
+        assert_eq!(compute_assets(5, 10, 0), Err(KernelError::EmptyVault));
+        assert_eq!(compute_assets(5, 10, 11), Ok(5));
+        assert_eq!(compute_assets(1, 2, 1), Err(KernelError::ZeroOutput));
+        assert_eq!(
+            compute_assets(u64::MAX, 1, u64::MAX),
+            Err(KernelError::MathOverflow)
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "vuln-inflation"))]
+    fn virtual_offset_makes_donation_inflation_unprofitable() {
+        // One-token first deposit, then a 1_000_000 token donation ahead of
+        // the victim's deposit
+        let attacker = compute_shares(1, 0, 0).unwrap();
+        let victim = compute_shares(2_000_001, attacker, 1_000_001).unwrap();
+        let redeemed = compute_assets(attacker, attacker + victim, 3_000_002).unwrap();
+        assert!(redeemed < 1_000_001);
+    }
+
+    #[test]
+    fn larger_offsets_absorb_more_of_a_donation() {
+        // One share against a donation-inflated 1_000_001 assets
+        let priced = |offset| shares_with_offset(1_000_000, 1, 1_000_001, offset);
+        assert_eq!(priced(0), Some(0));
+        assert_eq!(priced(1), Some(1));
+        assert_eq!(priced(1_000), Some(999));
+        assert_eq!(assets_with_offset(1, 1, 1_000_001, 1_000), Some(1_000));
+    }
+}
@@ -0,0 +1,209 @@
+//! Pure share/asset conversion math for the vault program.
+//!
+//! This crate is `no_std` and has no required dependencies so the exact same rounding
+//! logic can be used on-chain, in client tooling, in fuzz targets and in
+//! verification harnesses. The `wasm` feature additionally exposes the
+//! conversions to JavaScript through `wasm-bindgen`.
+//!
+//! Every conversion goes through `mul_div_floor` or `mul_div_ceil` so the
+//! rounding direction is explicit at the call site. As a rule, rounding must
+//! always favor the vault: shares minted and assets paid out round down,
+//! shares burned and assets pulled in round up.
+
+#![cfg_attr(not(feature = "wasm"), no_std)]
+
+pub mod fees;
+pub mod kernel;
+#[cfg(kani)]
+mod proofs;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use fees::{
+    compute_reserve, compute_skim, profit_over_mark, withdrawal_fee, ReserveSplit, MAX_BPS,
+    MAX_WITHDRAWAL_FEE_BPS,
+};
+pub use kernel::{
+    assets_with_offset, compute_assets, compute_shares, shares_with_offset, KernelError,
+    VIRTUAL_OFFSET,
+};
+
+/// Rounding direction for a conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round towards zero
+    Down,
+    /// Round away from zero
+    Up,
+}
+
+/// Computes `a * b / c`, rounding the result down.
+///
+/// Returns `None` if `c` is zero or the intermediate product overflows `u128`.
+pub fn mul_div_floor(a: u128, b: u128, c: u128) -> Option<u128> {
+    if c == 0 {
+        return None;
+    }
+    a.checked_mul(b)?.checked_div(c)
+}
+
+/// Computes `a * b / c`, rounding the result up.
+///
+/// Returns `None` if `c` is zero or the intermediate product overflows `u128`.
+pub fn mul_div_ceil(a: u128, b: u128, c: u128) -> Option<u128> {
+    if c == 0 {
+        return None;
+    }
+    let product = a.checked_mul(b)?;
+    let quotient = product / c;
+    if product % c == 0 {
+        Some(quotient)
+    } else {
+        quotient.checked_add(1)
+    }
+}
+
+/// Computes `a * b / c` with the given rounding direction.
+pub fn mul_div(a: u128, b: u128, c: u128, rounding: Rounding) -> Option<u128> {
+    match rounding {
+        Rounding::Down => mul_div_floor(a, b, c),
+        Rounding::Up => mul_div_ceil(a, b, c),
+    }
+}
+
+/// Converts an amount of underlying assets into shares.
+///
+/// An empty vault (`total_shares == 0`) converts 1:1. Otherwise computes
+/// `assets * total_shares / total_assets`. Returns `None` on division by zero
+/// or if the result does not fit in a `u64`.
+pub fn convert_to_shares(
+    assets: u64,
+    total_shares: u64,
+    total_assets: u64,
+    rounding: Rounding,
+) -> Option<u64> {
+    if total_shares == 0 {
+        return Some(assets);
+    }
+    let shares = mul_div(
+        assets as u128,
+        total_shares as u128,
+        total_assets as u128,
+        rounding,
+    )?;
+    u64::try_from(shares).ok()
+}
+
+/// Converts an amount of shares into underlying assets.
+///
+/// Computes `shares * total_assets / total_shares`. Returns `None` on division
+/// by zero or if the result does not fit in a `u64`.
+pub fn convert_to_assets(
+    shares: u64,
+    total_shares: u64,
+    total_assets: u64,
+    rounding: Rounding,
+) -> Option<u64> {
+    let assets = mul_div(
+        shares as u128,
+        total_assets as u128,
+        total_shares as u128,
+        rounding,
+    )?;
+    u64::try_from(assets).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn floor_and_ceil_agree_on_exact_division() {
+        for a in 0..64u128 {
+            for b in 0..64u128 {
+                for c in 1..64u128 {
+                    let floor = mul_div_floor(a, b, c).unwrap();
+                    let ceil = mul_div_ceil(a, b, c).unwrap();
+                    assert_eq!(floor, a * b / c);
+                    if (a * b) % c == 0 {
+                        assert_eq!(floor, ceil);
+                    } else {
+                        assert_eq!(floor + 1, ceil);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn zero_divisor_is_rejected() {
+        assert_eq!(mul_div_floor(1, 1, 0), None);
+        assert_eq!(mul_div_ceil(1, 1, 0), None);
+        assert_eq!(mul_div_floor(0, 0, 0), None);
+        assert_eq!(mul_div_ceil(0, 0, 0), None);
+    }
+
+    #[test]
+    fn u64_operands_never_overflow() {
+        let max = u64::MAX as u128;
+        assert_eq!(mul_div_floor(max, max, max), Some(max));
+        assert_eq!(mul_div_ceil(max, max, max), Some(max));
+        assert_eq!(mul_div_floor(max, max, 1), Some(max * max));
+        assert_eq!(mul_div_ceil(max, max - 1, max), Some(max - 1));
+    }
+
+    #[test]
+    fn product_overflow_is_rejected() {
+        assert_eq!(mul_div_floor(u128::MAX, 2, 2), None);
+        assert_eq!(mul_div_ceil(u128::MAX, 2, 2), None);
+    }
+
+    #[test]
+    fn ceil_rounds_up_remainders() {
+        assert_eq!(mul_div_floor(10, 1, 3), Some(3));
+        assert_eq!(mul_div_ceil(10, 1, 3), Some(4));
+        assert_eq!(mul_div_floor(1, 1, u64::MAX as u128), Some(0));
+        assert_eq!(mul_div_ceil(1, 1, u64::MAX as u128), Some(1));
+        assert_eq!(mul_div_ceil(u128::MAX, 1, 2), Some(u128::MAX / 2 + 1));
+    }
+
+    #[test]
+    fn first_deposit_converts_one_to_one() {
+        assert_eq!(convert_to_shares(0, 0, 0, Rounding::Down), Some(0));
+        assert_eq!(convert_to_shares(42, 0, 0, Rounding::Down), Some(42));
+        assert_eq!(convert_to_shares(42, 0, 1_000, Rounding::Up), Some(42));
+    }
+
+    #[test]
+    fn conversions_reject_results_above_u64() {
+        assert_eq!(
+            convert_to_shares(u64::MAX, u64::MAX, 1, Rounding::Down),
+            None
+        );
+        assert_eq!(
+            convert_to_assets(u64::MAX, 1, u64::MAX, Rounding::Down),
+            None
+        );
+        assert_eq!(convert_to_assets(1, 0, 1, Rounding::Down), None);
+    }
+
+    #[test]
+    fn conversions_follow_rounding_direction() {
+        assert_eq!(
+            convert_to_shares(1_000, 1_000, 1_100, Rounding::Down),
+            Some(909)
+        );
+        assert_eq!(
+            convert_to_shares(1_000, 1_000, 1_100, Rounding::Up),
+            Some(910)
+        );
+        assert_eq!(
+            convert_to_assets(909, 1_909, 2_100, Rounding::Down),
+            Some(999)
+        );
+        assert_eq!(
+            convert_to_assets(909, 1_909, 2_100, Rounding::Up),
+            Some(1_000)
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Kani proof harnesses for the conversion math.
+//!
+//! Compiled only under `cargo kani`, which sets `cfg(kani)`:
+//!
+//! ```text
+//! cargo kani -p vault-math
+//! ```
+//!
+//! Overflow harnesses cover the full `u64` operand range. The round-trip
+//! harness is bounded to a small domain to keep the `u128` division tractable
+//! for the solver, which still checks every combination within it.
+
+use crate::*;
+
+/// Largest value used by the bounded harnesses
+const SMALL: u64 = 1 << 10;
+
+#[kani::proof]
+fn mul_div_never_panics_on_u64_operands() {
+    let a: u64 = kani::any();
+    let b: u64 = kani::any();
+    let c: u64 = kani::any();
+
+    let floor = mul_div_floor(a as u128, b as u128, c as u128);
+    let ceil = mul_div_ceil(a as u128, b as u128, c as u128);
+
+    // The product of two u64 values always fits in a u128
+    assert_eq!(floor.is_some(), c != 0);
+    assert_eq!(ceil.is_some(), c != 0);
+}
+
+#[kani::proof]
+fn conversions_never_panic() {
+    let amount: u64 = kani::any();
+    let total_shares: u64 = kani::any();
+    let total_assets: u64 = kani::any();
+
+    let _ = convert_to_shares(amount, total_shares, total_assets, Rounding::Down);
+    let _ = convert_to_shares(amount, total_shares, total_assets, Rounding::Up);
+    let _ = convert_to_assets(amount, total_shares, total_assets, Rounding::Down);
+    let _ = convert_to_assets(amount, total_shares, total_assets, Rounding::Up);
+}
+
+#[kani::proof]
+fn ceil_is_floor_or_floor_plus_one() {
+    let a: u64 = kani::any();
+    let b: u64 = kani::any();
+    let c: u64 = kani::any();
+    kani::assume(c != 0);
+
+    let floor = mul_div_floor(a as u128, b as u128, c as u128).unwrap();
+    let ceil = mul_div_ceil(a as u128, b as u128, c as u128).unwrap();
+
+    assert!(ceil == floor || ceil == floor + 1);
+}
+
+#[kani::proof]
+fn no_free_shares() {
+    let amount: u64 = kani::any();
+    let total_shares: u64 = kani::any();
+    let total_assets: u64 = kani::any();
+    kani::assume(amount <= SMALL);
+    kani::assume(total_shares <= SMALL && total_assets <= SMALL);
+    // Either an empty vault or one holding both shares and assets
+    kani::assume((total_shares == 0) == (total_assets == 0));
+
+    let shares = convert_to_shares(amount, total_shares, total_assets, Rounding::Down).unwrap();
+    kani::assume(shares > 0);
+
+    let returned = convert_to_assets(
+        shares,
+        total_shares + shares,
+        total_assets + amount,
+        Rounding::Down,
+    )
+    .unwrap();
+
+    assert!(returned <= amount);
+}
@@ -0,0 +1,60 @@
+//! JavaScript bindings for the share conversion math.
+//!
+//! Build the module as a `cdylib` with the `wasm` feature and generate the
+//! JS glue with `wasm-bindgen`:
+//!
+//! ```text
+//! cargo rustc -p vault-math --lib --release --features wasm \
+//!     --target wasm32-unknown-unknown --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg \
+//!     target/wasm32-unknown-unknown/release/vault_math.wasm
+//! ```
+//!
+//! `u64` values cross the boundary as `bigint`; a conversion that the program
+//! would reject (division by zero, result above `u64::MAX`) returns `undefined`.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{convert_to_assets, convert_to_shares, Rounding};
+
+/// Shares minted for depositing `assets`, rounded down exactly like `deposit`.
+#[wasm_bindgen(js_name = previewDeposit)]
+pub fn preview_deposit(assets: u64, total_shares: u64, total_assets: u64) -> Option<u64> {
+    convert_to_shares(assets, total_shares, total_assets, Rounding::Down)
+}
+
+/// Underlying assets returned for redeeming `shares`, rounded down exactly like `redeem`.
+#[wasm_bindgen(js_name = previewRedeem)]
+pub fn preview_redeem(shares: u64, total_shares: u64, total_assets: u64) -> Option<u64> {
+    convert_to_assets(shares, total_shares, total_assets, Rounding::Down)
+}
+
+/// Shares worth `assets`, in the requested rounding direction.
+#[wasm_bindgen(js_name = convertToShares)]
+pub fn convert_to_shares_js(
+    assets: u64,
+    total_shares: u64,
+    total_assets: u64,
+    round_up: bool,
+) -> Option<u64> {
+    convert_to_shares(assets, total_shares, total_assets, rounding(round_up))
+}
+
+/// Assets worth `shares`, in the requested rounding direction.
+#[wasm_bindgen(js_name = convertToAssets)]
+pub fn convert_to_assets_js(
+    shares: u64,
+    total_shares: u64,
+    total_assets: u64,
+    round_up: bool,
+) -> Option<u64> {
+    convert_to_assets(shares, total_shares, total_assets, rounding(round_up))
+}
+
+fn rounding(round_up: bool) -> Rounding {
+    if round_up {
+        Rounding::Up
+    } else {
+        Rounding::Down
+    }
+}
@@ -0,0 +1,193 @@
+//! Property-based tests for reserve factor accounting.
+//!
+//! Totals are drawn below 2^48 so prices can be compared by cross
+//! multiplication in `u128`; every property is about ratios, which this
+//! range covers from dust vaults to ones far past any real supply.
+
+use proptest::prelude::*;
+use vault_math::{
+    assets_with_offset, compute_reserve, compute_skim, profit_over_mark, shares_with_offset,
+    withdrawal_fee, MAX_BPS, MAX_WITHDRAWAL_FEE_BPS, VIRTUAL_OFFSET,
+};
+
+const MAX_TOTAL: u64 = 1 << 48;
+
+/// Whether the share price `a_assets / a_shares` is at least
+/// `b_assets / b_shares`, both with the virtual offset
+fn price_at_least(a_assets: u64, a_shares: u64, b_assets: u64, b_shares: u64) -> bool {
+    let offset = VIRTUAL_OFFSET as u128;
+    (a_assets as u128 + offset) * (b_shares as u128 + offset)
+        >= (b_assets as u128 + offset) * (a_shares as u128 + offset)
+}
+
+/// A vault whose shares are each backed by at least one unit, with a mark
+/// at its current price and some yield on top
+fn vault_with_yield() -> impl Strategy<Value = (u64, u64, u64)> {
+    (1..MAX_TOTAL, 1..MAX_TOTAL / 2, 0..MAX_TOTAL / 2).prop_map(|(shares, backing, yielded)| {
+        let assets = shares.max(backing);
+        (assets, shares, yielded)
+    })
+}
+
+fn reserve_factor() -> impl Strategy<Value = u16> {
+    prop_oneof![Just(0), Just(MAX_BPS), 0..=MAX_BPS]
+}
+
+proptest! {
+    #[test]
+    fn treasury_is_never_paid_more_than_its_cut(
+        (assets, shares, yielded) in vault_with_yield(),
+        bps in reserve_factor(),
+    ) {
+        let total_assets = assets + yielded;
+        let split = compute_reserve(total_assets, shares, assets, shares, bps).unwrap();
+
+        prop_assert!(split.reserve <= split.profit);
+        prop_assert!(
+            split.reserve as u128 * MAX_BPS as u128 <= split.profit as u128 * bps as u128
+        );
+        let paid = assets_with_offset(split.shares, shares + split.shares, total_assets, VIRTUAL_OFFSET)
+            .unwrap();
+        prop_assert!(
+            paid <= split.reserve,
+            "treasury's {} shares are worth {}, its cut is {}",
+            split.shares, paid, split.reserve
+        );
+    }
+
+    #[test]
+    fn report_never_leaves_the_price_below_the_mark(
+        (assets, shares, yielded) in vault_with_yield(),
+        bps in reserve_factor(),
+    ) {
+        let total_assets = assets + yielded;
+        let split = compute_reserve(total_assets, shares, assets, shares, bps).unwrap();
+
+        prop_assert_eq!(split.mark_assets, total_assets);
+        prop_assert_eq!(split.mark_shares, shares + split.shares);
+        prop_assert!(price_at_least(split.mark_assets, split.mark_shares, assets, shares));
+    }
+
+    #[test]
+    fn skim_pays_the_cut_and_keeps_the_price_above_the_mark(
+        (assets, shares, yielded) in vault_with_yield(),
+        bps in reserve_factor(),
+    ) {
+        let split = compute_skim(assets + yielded, shares, assets, shares, bps).unwrap();
+
+        prop_assert_eq!(split.shares, 0);
+        prop_assert!(
+            split.reserve as u128 * MAX_BPS as u128 <= split.profit as u128 * bps as u128
+        );
+        prop_assert_eq!(split.mark_assets, assets + yielded - split.reserve);
+        prop_assert!(price_at_least(split.mark_assets, split.mark_shares, assets, shares));
+
+        let (mark_assets, mark_shares) = (split.mark_assets, split.mark_shares);
+        let again = compute_skim(mark_assets, mark_shares, mark_assets, mark_shares, bps).unwrap();
+        prop_assert_eq!(again.reserve, 0);
+    }
+
+    #[test]
+    fn profit_is_only_paid_once(
+        (assets, shares, yielded) in vault_with_yield(),
+        bps in reserve_factor(),
+    ) {
+        let first = compute_reserve(assets + yielded, shares, assets, shares, bps).unwrap();
+        let second = compute_reserve(
+            first.mark_assets,
+            first.mark_shares,
+            first.mark_assets,
+            first.mark_shares,
+            bps,
+        )
+        .unwrap();
+
+        prop_assert_eq!(second.profit, 0);
+        prop_assert_eq!(second.shares, 0);
+        prop_assert_eq!(
+            (second.mark_assets, second.mark_shares),
+            (first.mark_assets, first.mark_shares)
+        );
+    }
+
+    #[test]
+    fn deposits_and_redeems_are_not_profit(
+        (assets, shares, _) in vault_with_yield(),
+        amount in 1..MAX_TOTAL,
+        redeemed in 1..MAX_TOTAL,
+    ) {
+        // At most the rounding the vault keeps, under one share's worth
+        let one_share = assets_with_offset(1, shares, assets, VIRTUAL_OFFSET).unwrap() + 1;
+
+        let minted = shares_with_offset(amount, shares, assets, VIRTUAL_OFFSET).unwrap();
+        let profit = profit_over_mark(assets + amount, shares + minted, assets, shares);
+        prop_assert!(
+            matches!(profit, Some(profit) if profit <= one_share),
+            "depositing {} made {:?} profit", amount, profit
+        );
+
+        let burned = redeemed % shares + 1;
+        let paid = assets_with_offset(burned, shares, assets, VIRTUAL_OFFSET).unwrap();
+        let profit = profit_over_mark(assets - paid, shares - burned, assets, shares);
+        prop_assert!(
+            matches!(profit, Some(profit) if profit <= one_share),
+            "redeeming {} made {:?} profit", burned, profit
+        );
+    }
+
+    #[test]
+    fn flows_after_a_settlement_are_not_profit(
+        (assets, shares, yielded) in vault_with_yield(),
+        bps in reserve_factor(),
+        amount in 1..MAX_TOTAL,
+        redeemed in 1..MAX_TOTAL,
+    ) {
+        // Yield arrived since the mark; deposits and redeems settle first
+        let settled = compute_reserve(assets + yielded, shares, assets, shares, bps).unwrap();
+        let (assets, shares) = (settled.mark_assets, settled.mark_shares);
+        let one_share = assets_with_offset(1, shares, assets, VIRTUAL_OFFSET).unwrap() + 1;
+
+        let minted = shares_with_offset(amount, shares, assets, VIRTUAL_OFFSET).unwrap();
+        let split = compute_reserve(assets + amount, shares + minted, assets, shares, bps).unwrap();
+        prop_assert!(
+            split.profit <= one_share,
+            "depositing {} after yield made {} profit", amount, split.profit
+        );
+
+        let burned = redeemed % shares + 1;
+        let paid = assets_with_offset(burned, shares, assets, VIRTUAL_OFFSET).unwrap();
+        let split = compute_reserve(assets - paid, shares - burned, assets, shares, bps).unwrap();
+        prop_assert!(
+            split.profit <= one_share,
+            "redeeming {} after yield made {} profit", burned, split.profit
+        );
+    }
+
+    #[test]
+    fn a_price_below_the_mark_pays_nothing(
+        (assets, shares, _) in vault_with_yield(),
+        lost in 1..MAX_TOTAL,
+        bps in reserve_factor(),
+    ) {
+        let lost = lost % assets + 1;
+        let split = compute_reserve(assets - lost, shares, assets, shares, bps).unwrap();
+
+        prop_assert_eq!(split.shares, 0);
+        prop_assert_eq!((split.mark_assets, split.mark_shares), (assets, shares));
+    }
+
+    #[test]
+    fn withdrawal_fees_are_never_short(
+        shares in 1..u64::MAX,
+        bps in 0..=MAX_WITHDRAWAL_FEE_BPS,
+    ) {
+        let fee = withdrawal_fee(shares, bps);
+
+        prop_assert!(fee <= shares);
+        prop_assert!(fee as u128 * MAX_BPS as u128 >= shares as u128 * bps as u128);
+
+        // Rounded up by less than one share
+        let (fee, shares, bps) = (fee as u128, shares as u128, bps as u128);
+        prop_assert!(fee * (MAX_BPS as u128) < shares * bps + MAX_BPS as u128);
+    }
+}
@@ -0,0 +1,127 @@
+//! Property-based tests for the core safety properties of share conversion.
+//!
+//! Amounts are drawn from the full `u64` range and, separately, as whole-token
+//! amounts scaled by every decimals value from 0 to 18.
+
+use proptest::prelude::*;
+use vault_math::{convert_to_assets, convert_to_shares, Rounding};
+
+/// A consistent vault state: either empty, or holding both assets and shares.
+fn vault_state() -> impl Strategy<Value = (u64, u64)> {
+    prop_oneof![
+        1 => Just((0u64, 0u64)),
+        9 => (1..=u64::MAX, 1..=u64::MAX),
+    ]
+}
+
+/// A whole-token amount in the smallest unit for a mint with `decimals`.
+fn scaled_amount() -> impl Strategy<Value = u64> {
+    (1..=1_000_000_000u64, 0..=18u32)
+        .prop_filter_map("amount overflows u64", |(tokens, decimals)| {
+            tokens.checked_mul(10u64.pow(decimals))
+        })
+}
+
+fn any_amount() -> impl Strategy<Value = u64> {
+    prop_oneof![any::<u64>(), scaled_amount()]
+}
+
+proptest! {
+    #[test]
+    fn deposit_then_redeem_never_profits(
+        (total_shares, total_assets) in vault_state(),
+        amount in any_amount(),
+    ) {
+        let shares = convert_to_shares(amount, total_shares, total_assets, Rounding::Down);
+        prop_assume!(shares.is_some());
+        let shares = shares.unwrap();
+        prop_assume!(shares > 0);
+
+        let (Some(shares_after), Some(assets_after)) = (
+            total_shares.checked_add(shares),
+            total_assets.checked_add(amount),
+        ) else {
+            return Ok(());
+        };
+
+        let returned = convert_to_assets(shares, shares_after, assets_after, Rounding::Down)
+            .expect("redeeming freshly minted shares cannot overflow");
+
+        prop_assert!(
+            returned <= amount,
+            "deposited {} but redeemed {} (state: {} shares / {} assets)",
+            amount, returned, total_shares, total_assets
+        );
+    }
+
+    #[test]
+    fn convert_to_shares_is_monotonic(
+        (total_shares, total_assets) in vault_state(),
+        a in any_amount(),
+        b in any_amount(),
+    ) {
+        let (low, high) = if a <= b { (a, b) } else { (b, a) };
+        for rounding in [Rounding::Down, Rounding::Up] {
+            let low_shares = convert_to_shares(low, total_shares, total_assets, rounding);
+            let high_shares = convert_to_shares(high, total_shares, total_assets, rounding);
+            if let (Some(low_shares), Some(high_shares)) = (low_shares, high_shares) {
+                prop_assert!(low_shares <= high_shares);
+            }
+        }
+    }
+
+    #[test]
+    fn convert_to_assets_is_monotonic(
+        (total_shares, total_assets) in vault_state(),
+        a in any_amount(),
+        b in any_amount(),
+    ) {
+        let (low, high) = if a <= b { (a, b) } else { (b, a) };
+        for rounding in [Rounding::Down, Rounding::Up] {
+            let low_assets = convert_to_assets(low, total_shares, total_assets, rounding);
+            let high_assets = convert_to_assets(high, total_shares, total_assets, rounding);
+            if let (Some(low_assets), Some(high_assets)) = (low_assets, high_assets) {
+                prop_assert!(low_assets <= high_assets);
+            }
+        }
+    }
+
+    #[test]
+    fn rounding_down_never_exceeds_exact_value(
+        (total_shares, total_assets) in vault_state(),
+        amount in any_amount(),
+    ) {
+        prop_assume!(total_shares > 0);
+
+        if let Some(shares) = convert_to_shares(amount, total_shares, total_assets, Rounding::Down) {
+            // shares <= amount * total_shares / total_assets
+            prop_assert!(
+                shares as u128 * total_assets as u128 <= amount as u128 * total_shares as u128
+            );
+        }
+        if let Some(assets) = convert_to_assets(amount, total_shares, total_assets, Rounding::Down) {
+            // assets <= amount * total_assets / total_shares
+            prop_assert!(
+                assets as u128 * total_shares as u128 <= amount as u128 * total_assets as u128
+            );
+        }
+    }
+
+    #[test]
+    fn rounding_up_is_at_most_one_above_rounding_down(
+        (total_shares, total_assets) in vault_state(),
+        amount in any_amount(),
+    ) {
+        let down = convert_to_shares(amount, total_shares, total_assets, Rounding::Down);
+        let up = convert_to_shares(amount, total_shares, total_assets, Rounding::Up);
+        if let (Some(down), Some(up)) = (down, up) {
+            prop_assert!(up >= down && up - down <= 1);
+        }
+
+        let down = convert_to_assets(amount, total_shares, total_assets, Rounding::Down);
+        let up = convert_to_assets(amount, total_shares, total_assets, Rounding::Up);
+        if let (Some(down), Some(up)) = (down, up) {
+            prop_assert!(up >= down && up - down <= 1);
+        }
+    }
+}
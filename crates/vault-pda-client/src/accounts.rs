@@ -0,0 +1,518 @@
+//! Typed account helpers.
+//!
+//! [`deserialize`] checks the Anchor discriminator, so handing it the wrong
+//! account fails instead of decoding garbage. [`AccountType::detect`] tells
+//! which of the program's accounts some data is, and [`try_deserialize_any`]
+//! decodes it as that type. With the `rpc` feature, the
+//! `fetch_*` helpers read accounts from a cluster; [`fetch_vault_state`]
+//! reads a vault and both balances it prices shares off in one request, so
+//! they come from the same slot, and [`preview_deposit`] and
+//! [`preview_redeem`] price an operation off that read. [`fetch_vaults`]
+//! has the node select vaults by a [`VaultFilter`] instead of downloading
+//! every one.
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use bytemuck::Pod;
+use vault_pda::state::{
+    ActivityStats, AdminLog, FeeExemption, Flag, Player, PriceHistory, ProtocolState,
+    ReserveConfig, Vault, VaultAuthority, VaultRegistry,
+};
+
+use crate::{ClientError, VaultTotals};
+
+/// Deserializes an Anchor account (or token account or mint) from `data`
+pub fn deserialize<T: AccountDeserialize>(data: &[u8]) -> Result<T, ClientError> {
+    Ok(T::try_deserialize(&mut &data[..])?)
+}
+
+/// A vault with the balances it prices shares off
+#[derive(Clone)]
+pub struct VaultState {
+    pub address: Pubkey,
+    pub vault: Vault,
+    /// Supply of `vault.share_mint`
+    pub total_shares: u64,
+    /// Balance of `vault.vault_token_account`
+    pub total_assets: u64,
+    /// Whether the underlying mint's freeze authority froze
+    /// `vault.vault_token_account`
+    pub frozen: bool,
+}
+
+impl VaultState {
+    /// Decodes a vault and its share mint and token account
+    pub fn from_accounts(
+        address: Pubkey,
+        vault: &[u8],
+        share_mint: &[u8],
+        vault_token_account: &[u8],
+    ) -> Result<Self, ClientError> {
+        let vault_token_account = deserialize::<TokenAccount>(vault_token_account)?;
+        Ok(Self {
+            address,
+            vault: deserialize(vault)?,
+            total_shares: deserialize::<Mint>(share_mint)?.supply,
+            total_assets: vault_token_account.amount,
+            frozen: vault_token_account.is_frozen(),
+        })
+    }
+
+    pub fn totals(&self) -> VaultTotals {
+        VaultTotals {
+            total_shares: self.total_shares,
+            total_assets: self.total_assets,
+        }
+    }
+
+    /// Shares a deposit of `amount` mints, or why the program rejects it
+    pub fn preview_deposit(&self, amount: u64) -> Result<u64, ClientError> {
+        self.check_not_frozen()?;
+        Ok(self.totals().preview_deposit(amount)?)
+    }
+
+    /// Underlying a redeem of `shares` pays out, or why the program rejects it
+    pub fn preview_redeem(&self, shares: u64) -> Result<u64, ClientError> {
+        self.check_not_frozen()?;
+        Ok(self.totals().preview_redeem(shares)?)
+    }
+
+    fn check_not_frozen(&self) -> Result<(), ClientError> {
+        if self.frozen {
+            return Err(ClientError::Frozen(self.vault.vault_token_account));
+        }
+        Ok(())
+    }
+}
+
+/// Every account type the program owns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccountType {
+    ProtocolState,
+    VaultAuthority,
+    Vault,
+    VaultRegistry,
+    PriceHistory,
+    Flag,
+    Player,
+    ReserveConfig,
+    ActivityStats,
+    AdminLog,
+    FeeExemption,
+}
+
+impl AccountType {
+    pub const ALL: [AccountType; 11] = [
+        AccountType::ProtocolState,
+        AccountType::VaultAuthority,
+        AccountType::Vault,
+        AccountType::VaultRegistry,
+        AccountType::PriceHistory,
+        AccountType::Flag,
+        AccountType::Player,
+        AccountType::ReserveConfig,
+        AccountType::ActivityStats,
+        AccountType::AdminLog,
+        AccountType::FeeExemption,
+    ];
+
+    /// The type whose discriminator `data` starts with
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|ty| data.starts_with(ty.discriminator()))
+    }
+
+    pub fn discriminator(self) -> &'static [u8] {
+        match self {
+            AccountType::ProtocolState => ProtocolState::DISCRIMINATOR,
+            AccountType::VaultAuthority => VaultAuthority::DISCRIMINATOR,
+            AccountType::Vault => Vault::DISCRIMINATOR,
+            AccountType::VaultRegistry => VaultRegistry::DISCRIMINATOR,
+            AccountType::PriceHistory => PriceHistory::DISCRIMINATOR,
+            AccountType::Flag => Flag::DISCRIMINATOR,
+            AccountType::Player => Player::DISCRIMINATOR,
+            AccountType::ReserveConfig => ReserveConfig::DISCRIMINATOR,
+            AccountType::ActivityStats => ActivityStats::DISCRIMINATOR,
+            AccountType::AdminLog => AdminLog::DISCRIMINATOR,
+            AccountType::FeeExemption => FeeExemption::DISCRIMINATOR,
+        }
+    }
+
+    /// The state struct's name
+    pub fn name(self) -> &'static str {
+        match self {
+            AccountType::ProtocolState => "ProtocolState",
+            AccountType::VaultAuthority => "VaultAuthority",
+            AccountType::Vault => "Vault",
+            AccountType::VaultRegistry => "VaultRegistry",
+            AccountType::PriceHistory => "PriceHistory",
+            AccountType::Flag => "Flag",
+            AccountType::Player => "Player",
+            AccountType::ReserveConfig => "ReserveConfig",
+            AccountType::ActivityStats => "ActivityStats",
+            AccountType::AdminLog => "AdminLog",
+            AccountType::FeeExemption => "FeeExemption",
+        }
+    }
+}
+
+/// Any program account, decoded by [`try_deserialize_any`]
+///
+/// The zero-copy accounts are boxed, as each is kilobytes. The registry is
+/// its list of vaults, which trails its header.
+#[derive(Clone)]
+pub enum AnyAccount {
+    ProtocolState(ProtocolState),
+    VaultAuthority(VaultAuthority),
+    Vault(Vault),
+    VaultRegistry(Vec<Pubkey>),
+    PriceHistory(Box<PriceHistory>),
+    Flag(Flag),
+    Player(Player),
+    ReserveConfig(ReserveConfig),
+    ActivityStats(Box<ActivityStats>),
+    AdminLog(Box<AdminLog>),
+    FeeExemption(FeeExemption),
+}
+
+impl AnyAccount {
+    pub fn account_type(&self) -> AccountType {
+        match self {
+            AnyAccount::ProtocolState(_) => AccountType::ProtocolState,
+            AnyAccount::VaultAuthority(_) => AccountType::VaultAuthority,
+            AnyAccount::Vault(_) => AccountType::Vault,
+            AnyAccount::VaultRegistry(_) => AccountType::VaultRegistry,
+            AnyAccount::PriceHistory(_) => AccountType::PriceHistory,
+            AnyAccount::Flag(_) => AccountType::Flag,
+            AnyAccount::Player(_) => AccountType::Player,
+            AnyAccount::ReserveConfig(_) => AccountType::ReserveConfig,
+            AnyAccount::ActivityStats(_) => AccountType::ActivityStats,
+            AnyAccount::AdminLog(_) => AccountType::AdminLog,
+            AnyAccount::FeeExemption(_) => AccountType::FeeExemption,
+        }
+    }
+}
+
+/// Deserializes whichever program account `data` holds, by its discriminator
+pub fn try_deserialize_any(data: &[u8]) -> Result<AnyAccount, ClientError> {
+    let Some(ty) = AccountType::detect(data) else {
+        let error = if data.len() < 8 {
+            ErrorCode::AccountDiscriminatorNotFound
+        } else {
+            ErrorCode::AccountDiscriminatorMismatch
+        };
+        return Err(anchor_lang::error::Error::from(error).into());
+    };
+    Ok(match ty {
+        AccountType::ProtocolState => AnyAccount::ProtocolState(deserialize(data)?),
+        AccountType::VaultAuthority => AnyAccount::VaultAuthority(deserialize(data)?),
+        AccountType::Vault => AnyAccount::Vault(deserialize(data)?),
+        AccountType::VaultRegistry => {
+            AnyAccount::VaultRegistry(VaultRegistry::vaults(data).to_vec())
+        }
+        AccountType::PriceHistory => AnyAccount::PriceHistory(deserialize_zero_copy(data)?),
+        AccountType::Flag => AnyAccount::Flag(deserialize(data)?),
+        AccountType::Player => AnyAccount::Player(deserialize(data)?),
+        AccountType::ReserveConfig => AnyAccount::ReserveConfig(deserialize(data)?),
+        AccountType::ActivityStats => AnyAccount::ActivityStats(deserialize_zero_copy(data)?),
+        AccountType::AdminLog => AnyAccount::AdminLog(deserialize_zero_copy(data)?),
+        AccountType::FeeExemption => AnyAccount::FeeExemption(deserialize(data)?),
+    })
+}
+
+/// Copies a zero-copy account out of `data`, whose discriminator was checked
+///
+/// Anchor's own `try_deserialize` for these panics unless the data is exactly
+/// the struct's size and aligned for it.
+fn deserialize_zero_copy<T: Pod>(data: &[u8]) -> Result<Box<T>, ClientError> {
+    let body = data
+        .get(8..8 + std::mem::size_of::<T>())
+        .ok_or_else(|| anchor_lang::error::Error::from(ErrorCode::AccountDidNotDeserialize))?;
+    Ok(Box::new(bytemuck::pod_read_unaligned(body)))
+}
+
+/// Which of the program's vaults to fetch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultFilter {
+    All,
+    /// The vault over this underlying mint
+    UnderlyingMint(Pubkey),
+    /// Vaults whose `initialize_vault` this key paid for
+    Creator(Pubkey),
+}
+
+impl VaultFilter {
+    /// The offset in a vault's data and the key it must hold there, if the
+    /// filter narrows anything down
+    pub fn memcmp(&self) -> Option<(usize, Pubkey)> {
+        match self {
+            VaultFilter::All => None,
+            VaultFilter::UnderlyingMint(mint) => Some((Vault::UNDERLYING_MINT_OFFSET, *mint)),
+            VaultFilter::Creator(creator) => Some((Vault::CREATOR_OFFSET, *creator)),
+        }
+    }
+
+    /// Whether `data` is a vault the filter selects, the same checks the
+    /// node runs for [`fetch_vaults`]
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.len() == Vault::LEN
+            && data.starts_with(Vault::DISCRIMINATOR)
+            && self
+                .memcmp()
+                .is_none_or(|(offset, key)| data[offset..offset + 32] == key.to_bytes())
+    }
+}
+
+#[cfg(feature = "rpc")]
+pub use fetch::*;
+
+#[cfg(feature = "rpc")]
+mod fetch {
+    use super::*;
+    use crate::pda::{derive_flag_pda, derive_protocol_state_pda, VaultAddresses};
+    use solana_account_decoder::UiAccountEncoding;
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+    use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+
+    /// Fetches and deserializes the account at `address`
+    pub async fn fetch<T: AccountDeserialize>(
+        rpc: &RpcClient,
+        address: &Pubkey,
+    ) -> Result<T, ClientError> {
+        let account = rpc
+            .get_account_with_commitment(address, rpc.commitment())
+            .await?
+            .value
+            .ok_or(ClientError::AccountNotFound(*address))?;
+        deserialize(&account.data)
+    }
+
+    pub async fn fetch_protocol_state(
+        rpc: &RpcClient,
+        program_id: &Pubkey,
+    ) -> Result<ProtocolState, ClientError> {
+        fetch(rpc, &derive_protocol_state_pda(program_id).0).await
+    }
+
+    pub async fn fetch_flag(
+        rpc: &RpcClient,
+        program_id: &Pubkey,
+        vault: &Pubkey,
+    ) -> Result<Flag, ClientError> {
+        fetch(rpc, &derive_flag_pda(program_id, vault).0).await
+    }
+
+    /// The vault over `underlying_mint` with its balances, from one slot
+    pub async fn fetch_vault_state(
+        rpc: &RpcClient,
+        program_id: &Pubkey,
+        underlying_mint: &Pubkey,
+    ) -> Result<VaultState, ClientError> {
+        let addresses = VaultAddresses::new(program_id, underlying_mint);
+        let keys = [
+            addresses.vault,
+            addresses.share_mint,
+            addresses.vault_token_account,
+        ];
+        let accounts = rpc
+            .get_multiple_accounts_with_commitment(&keys, rpc.commitment())
+            .await?
+            .value;
+
+        let mut data = Vec::with_capacity(keys.len());
+        for (key, account) in keys.iter().zip(accounts) {
+            data.push(account.ok_or(ClientError::AccountNotFound(*key))?.data);
+        }
+        VaultState::from_accounts(addresses.vault, &data[0], &data[1], &data[2])
+    }
+
+    /// Shares a deposit of `amount` into the vault over `underlying_mint`
+    /// would mint right now, from one [`fetch_vault_state`] request
+    pub async fn preview_deposit(
+        rpc: &RpcClient,
+        program_id: &Pubkey,
+        underlying_mint: &Pubkey,
+        amount: u64,
+    ) -> Result<u64, ClientError> {
+        fetch_vault_state(rpc, program_id, underlying_mint)
+            .await?
+            .preview_deposit(amount)
+    }
+
+    /// Underlying a redeem of `shares` from the vault over `underlying_mint`
+    /// would pay out right now, from one [`fetch_vault_state`] request
+    pub async fn preview_redeem(
+        rpc: &RpcClient,
+        program_id: &Pubkey,
+        underlying_mint: &Pubkey,
+        shares: u64,
+    ) -> Result<u64, ClientError> {
+        fetch_vault_state(rpc, program_id, underlying_mint)
+            .await?
+            .preview_redeem(shares)
+    }
+
+    impl VaultFilter {
+        /// `getProgramAccounts` filters selecting the vaults: size and
+        /// discriminator, then the filter's key
+        pub fn rpc_filters(&self) -> Vec<RpcFilterType> {
+            let mut filters = vec![
+                RpcFilterType::DataSize(Vault::LEN as u64),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, Vault::DISCRIMINATOR.to_vec())),
+            ];
+            if let Some((offset, key)) = self.memcmp() {
+                filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    offset,
+                    key.to_bytes().to_vec(),
+                )));
+            }
+            filters
+        }
+    }
+
+    /// Every vault of the program
+    pub async fn fetch_all_vaults(
+        rpc: &RpcClient,
+        program_id: &Pubkey,
+    ) -> Result<Vec<(Pubkey, Vault)>, ClientError> {
+        fetch_vaults(rpc, program_id, VaultFilter::All).await
+    }
+
+    /// The program's vaults `filter` selects, matched by the node
+    pub async fn fetch_vaults(
+        rpc: &RpcClient,
+        program_id: &Pubkey,
+        filter: VaultFilter,
+    ) -> Result<Vec<(Pubkey, Vault)>, ClientError> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filter.rpc_filters()),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(rpc.commitment()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        rpc.get_program_accounts_with_config(program_id, config)
+            .await?
+            .into_iter()
+            .map(|(address, account)| Ok((address, deserialize(&account.data)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preview::KernelError;
+
+    #[test]
+    fn previews_check_the_freeze() {
+        let state = VaultState {
+            address: Pubkey::new_unique(),
+            vault: Vault {
+                share_mint: Pubkey::new_unique(),
+                underlying_mint: Pubkey::new_unique(),
+                vault_token_account: Pubkey::new_unique(),
+                creator: Pubkey::new_unique(),
+                name: [0; 32],
+                sunset: false,
+                bump: 255,
+            },
+            total_shares: 1_000,
+            total_assets: 2_000,
+            frozen: false,
+        };
+        assert_eq!(
+            state.preview_deposit(100).unwrap(),
+            state.totals().preview_deposit(100).unwrap()
+        );
+        assert!(matches!(
+            state.preview_redeem(0),
+            Err(ClientError::Rejected(KernelError::InvalidAmount))
+        ));
+
+        let frozen = VaultState {
+            frozen: true,
+            ..state.clone()
+        };
+        assert!(matches!(
+            frozen.preview_deposit(100),
+            Err(ClientError::Frozen(account)) if account == state.vault.vault_token_account
+        ));
+        assert!(frozen.preview_redeem(10).is_err());
+    }
+
+    #[test]
+    fn vault_filters_select_by_mint_and_creator() {
+        use anchor_lang::AccountSerialize;
+
+        let vault = Vault {
+            share_mint: Pubkey::new_unique(),
+            underlying_mint: Pubkey::new_unique(),
+            vault_token_account: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            name: [0; 32],
+            sunset: false,
+            bump: 255,
+        };
+        let mut data = Vec::new();
+        vault.try_serialize(&mut data).unwrap();
+
+        assert!(VaultFilter::All.matches(&data));
+        assert!(VaultFilter::UnderlyingMint(vault.underlying_mint).matches(&data));
+        assert!(VaultFilter::Creator(vault.creator).matches(&data));
+        assert!(!VaultFilter::UnderlyingMint(vault.creator).matches(&data));
+        assert!(!VaultFilter::Creator(vault.share_mint).matches(&data));
+
+        let mut other = Vec::new();
+        VaultAuthority { bump: 7 }
+            .try_serialize(&mut other)
+            .unwrap();
+        other.resize(Vault::LEN, 0);
+        assert!(!VaultFilter::All.matches(&other));
+    }
+
+    #[test]
+    fn detects_and_decodes_any_account() {
+        use anchor_lang::AccountSerialize;
+
+        let mut data = Vec::new();
+        VaultAuthority { bump: 7 }.try_serialize(&mut data).unwrap();
+        assert_eq!(
+            AccountType::detect(&data),
+            Some(AccountType::VaultAuthority)
+        );
+        assert!(matches!(
+            try_deserialize_any(&data).unwrap(),
+            AnyAccount::VaultAuthority(VaultAuthority { bump: 7 })
+        ));
+
+        let vault = Pubkey::new_unique();
+        let mut data = PriceHistory::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(vault.as_ref());
+        data.resize(PriceHistory::LEN, 0);
+        let AnyAccount::PriceHistory(history) = try_deserialize_any(&data).unwrap() else {
+            panic!("not a price history");
+        };
+        assert_eq!(history.vault, vault);
+        // Misaligned and oversized data decodes, truncated data errors
+        let mut shifted = vec![0];
+        shifted.extend_from_slice(&data);
+        shifted.push(0);
+        assert!(try_deserialize_any(&shifted[1..]).is_ok());
+        assert!(try_deserialize_any(&data[..PriceHistory::LEN - 1]).is_err());
+
+        assert_eq!(AccountType::detect(&[0; 8]), None);
+        assert!(try_deserialize_any(&[0; 8]).is_err());
+        assert!(try_deserialize_any(&[]).is_err());
+        for ty in AccountType::ALL {
+            assert_eq!(AccountType::detect(ty.discriminator()), Some(ty));
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Blocking send and airdrop helpers for tools driving a local validator.
+//!
+//! The scenario runner and the soak test send one transaction at a time and
+//! want a rejection reported rather than retried, so they use these instead
+//! of `VaultClient`. The payer pays every fee and signs every transaction.
+
+use anchor_lang::prelude::Pubkey;
+use solana_client::client_error::ClientError;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    signature::{Keypair, Signature, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+/// Lamports below which [`top_up`] asks for an airdrop
+pub const MIN_PAYER_BALANCE: u64 = LAMPORTS_PER_SOL;
+
+/// Lamports [`top_up`] asks for
+pub const TOP_UP_AMOUNT: u64 = 10 * LAMPORTS_PER_SOL;
+
+/// A transaction [`send`] got an answer for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sent {
+    Confirmed(Signature),
+    /// The program or runtime refused it
+    Rejected(Signature, TransactionError),
+}
+
+/// Airdrops to `payer` when it runs low, as fees add up over a long run
+pub fn top_up(rpc: &RpcClient, payer: &Pubkey) -> Result<(), ClientError> {
+    if rpc.get_balance(payer)? < MIN_PAYER_BALANCE {
+        let signature = rpc.request_airdrop(payer, TOP_UP_AMOUNT)?;
+        rpc.poll_for_signature(&signature)?;
+    }
+    Ok(())
+}
+
+/// Sends `instructions` paid for and signed by `payer` plus `signers`, and
+/// waits for confirmation
+///
+/// Fails only when the validator cannot be reached; a transaction it refuses
+/// is [`Sent::Rejected`].
+pub fn send(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<Sent, ClientError> {
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        rpc.get_latest_blockhash()?,
+    );
+    match rpc.send_and_confirm_transaction(&tx) {
+        Ok(signature) => Ok(Sent::Confirmed(signature)),
+        Err(e) => match e.get_transaction_error() {
+            Some(error) => Ok(Sent::Rejected(tx.signatures[0], error)),
+            None => Err(e),
+        },
+    }
+}
@@ -0,0 +1,977 @@
+//! Instruction builders.
+//!
+//! A builder needs the underlying mint, the user and the amount; the vault's
+//! accounts are derived from the mint, and the user's token accounts default
+//! to their associated token accounts under SPL Token:
+//!
+//! ```ignore
+//! let ix = DepositBuilder::new(usdc, user, 1_000_000)
+//!     .token_program(anchor_spl::token_2022::ID)
+//!     .build();
+//! ```
+//!
+//! `DepositBuilder::build_approve` and `build_delegated` split a deposit
+//! between the owner, who approves once, and a relayer that triggers it.
+//!
+//! Deposits and redeems settle the vault's reserve first, so on a vault with
+//! a reserve config the builders need its treasury (`.treasury(..)`), which
+//! is also paid the withdrawal fee of redeemers without an exemption.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_2022::spl_token_2022;
+
+use crate::pda::{
+    derive_admin_log_pda, derive_deposit_delegate_pda, derive_fee_exemption_pda, derive_player_pda,
+    derive_program_data_address, derive_protocol_state_pda, derive_reserve_config_pda,
+    derive_vault_authority_pda, derive_vault_registry_pda, VaultAddresses,
+};
+
+/// Builds `initialize`, making `owner` the protocol owner and payer
+pub fn initialize_ix(program_id: &Pubkey, owner: &Pubkey) -> Instruction {
+    let accounts = vault_pda::accounts::Initialize {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        vault_authority: derive_vault_authority_pda(program_id).0,
+        owner: *owner,
+        payer: *owner,
+        system_program: anchor_lang::system_program::ID,
+        admin_log: derive_admin_log_pda(program_id).0,
+        vault_registry: derive_vault_registry_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::Initialize {}.data(),
+    }
+}
+
+/// Builds `initialize_vault` for `underlying_mint`, owned by `token_program`
+pub fn initialize_vault_ix(
+    program_id: &Pubkey,
+    underlying_mint: &Pubkey,
+    payer: &Pubkey,
+    token_program: &Pubkey,
+    name: &str,
+) -> Instruction {
+    let vault = VaultAddresses::new(program_id, underlying_mint);
+    let accounts = vault_pda::accounts::InitializeVault {
+        vault: vault.vault,
+        underlying_mint: *underlying_mint,
+        vault_token_account: vault.vault_token_account,
+        share_mint: vault.share_mint,
+        vault_authority: vault.vault_authority,
+        payer: *payer,
+        system_program: anchor_lang::system_program::ID,
+        token_program: *token_program,
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        activity_stats: vault.activity_stats,
+        vault_registry: derive_vault_registry_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::InitializeVault {
+            name: name.to_string(),
+        }
+        .data(),
+    }
+}
+
+/// Builds `initialize_reserve` for the vault over `underlying_mint`, sending
+/// `reserve_factor_bps` of its profit to the share account `treasury`
+pub fn initialize_reserve_ix(
+    program_id: &Pubkey,
+    underlying_mint: &Pubkey,
+    owner: &Pubkey,
+    treasury: &Pubkey,
+    reserve_factor_bps: u16,
+) -> Instruction {
+    let vault = VaultAddresses::new(program_id, underlying_mint);
+    let accounts = vault_pda::accounts::InitializeReserve {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        reserve_config: derive_reserve_config_pda(program_id, &vault.vault).0,
+        vault: vault.vault,
+        vault_token_account: vault.vault_token_account,
+        share_mint: vault.share_mint,
+        treasury: *treasury,
+        owner: *owner,
+        system_program: anchor_lang::system_program::ID,
+        admin_log: derive_admin_log_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::InitializeReserve { reserve_factor_bps }.data(),
+    }
+}
+
+/// Builds `set_reserve_factor` for the vault over `underlying_mint`, which
+/// first reports, paying `treasury` its cut at the old factor
+pub fn set_reserve_factor_ix(
+    program_id: &Pubkey,
+    underlying_mint: &Pubkey,
+    owner: &Pubkey,
+    treasury: &Pubkey,
+    token_program: &Pubkey,
+    reserve_factor_bps: u16,
+) -> Instruction {
+    let vault = VaultAddresses::new(program_id, underlying_mint);
+    let accounts = vault_pda::accounts::SetReserveFactor {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        owner: *owner,
+        report: report_accounts(program_id, &vault, treasury, token_program),
+        admin_log: derive_admin_log_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::SetReserveFactor { reserve_factor_bps }.data(),
+    }
+}
+
+/// Builds a `report` of the vault over `underlying_mint`, paying `treasury`
+/// its cut; anyone can send it
+pub fn report_ix(
+    program_id: &Pubkey,
+    underlying_mint: &Pubkey,
+    treasury: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let vault = VaultAddresses::new(program_id, underlying_mint);
+    let accounts = report_accounts(program_id, &vault, treasury, token_program);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::Report {}.data(),
+    }
+}
+
+fn report_accounts(
+    program_id: &Pubkey,
+    vault: &VaultAddresses,
+    treasury: &Pubkey,
+    token_program: &Pubkey,
+) -> vault_pda::accounts::Report {
+    vault_pda::accounts::Report {
+        reserve_config: derive_reserve_config_pda(program_id, &vault.vault).0,
+        vault: vault.vault,
+        vault_token_account: vault.vault_token_account,
+        share_mint: vault.share_mint,
+        vault_authority: vault.vault_authority,
+        treasury: *treasury,
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        token_program: *token_program,
+    }
+}
+
+/// Builds `skim`, paying the treasury its cut of `underlying_mint`'s vault
+/// profit into `treasury_underlying_account`
+pub fn skim_ix(
+    program_id: &Pubkey,
+    underlying_mint: &Pubkey,
+    treasury: &Pubkey,
+    treasury_underlying_account: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let vault = VaultAddresses::new(program_id, underlying_mint);
+    let accounts = vault_pda::accounts::Skim {
+        reserve_config: derive_reserve_config_pda(program_id, &vault.vault).0,
+        vault: vault.vault,
+        underlying_mint: *underlying_mint,
+        vault_token_account: vault.vault_token_account,
+        share_mint: vault.share_mint,
+        vault_authority: vault.vault_authority,
+        treasury: *treasury,
+        treasury_underlying_account: *treasury_underlying_account,
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        token_program: *token_program,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::Skim {}.data(),
+    }
+}
+
+/// Builds `sync_upgrade_authority`, recording the program's current upgrade
+/// authority and whether `owner` claims upgrades are renounced
+pub fn sync_upgrade_authority_ix(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    renounce_upgrades_expected: bool,
+) -> Instruction {
+    let accounts = vault_pda::accounts::SyncUpgradeAuthority {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        program_data: derive_program_data_address(program_id),
+        owner: *owner,
+        admin_log: derive_admin_log_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::SyncUpgradeAuthority {
+            renounce_upgrades_expected,
+        }
+        .data(),
+    }
+}
+
+/// Builds `verify_upgrade_authority`, which fails unless the program still
+/// has the synced upgrade authority
+pub fn verify_upgrade_authority_ix(program_id: &Pubkey) -> Instruction {
+    let accounts = vault_pda::accounts::VerifyUpgradeAuthority {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        program_data: derive_program_data_address(program_id),
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::VerifyUpgradeAuthority {}.data(),
+    }
+}
+
+/// Builds `set_features`, enabling exactly the `ProtocolState` feature bits
+/// in `features`
+pub fn set_features_ix(program_id: &Pubkey, owner: &Pubkey, features: u8) -> Instruction {
+    let accounts = vault_pda::accounts::SetFeatures {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        owner: *owner,
+        admin_log: derive_admin_log_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::SetFeatures { features }.data(),
+    }
+}
+
+/// Builds `sunset_vault` for the vault over `underlying_mint`
+pub fn sunset_vault_ix(
+    program_id: &Pubkey,
+    underlying_mint: &Pubkey,
+    owner: &Pubkey,
+) -> Instruction {
+    let accounts = vault_pda::accounts::SunsetVault {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        vault: VaultAddresses::new(program_id, underlying_mint).vault,
+        owner: *owner,
+        admin_log: derive_admin_log_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::SunsetVault {}.data(),
+    }
+}
+
+/// Builds `set_withdrawal_fee`, sending `withdrawal_fee_bps` of every later
+/// redeem's shares from the vault over `underlying_mint` to its treasury
+pub fn set_withdrawal_fee_ix(
+    program_id: &Pubkey,
+    underlying_mint: &Pubkey,
+    owner: &Pubkey,
+    withdrawal_fee_bps: u16,
+) -> Instruction {
+    let vault = VaultAddresses::new(program_id, underlying_mint);
+    let accounts = vault_pda::accounts::SetWithdrawalFee {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        reserve_config: vault.reserve_config,
+        vault: vault.vault,
+        owner: *owner,
+        admin_log: derive_admin_log_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::SetWithdrawalFee { withdrawal_fee_bps }.data(),
+    }
+}
+
+/// Builds `set_withdrawal_limit`, capping what redeems from the vault over
+/// `underlying_mint` pay out over any `window_slots` slots; a zero
+/// `withdrawal_limit` lifts the cap
+pub fn set_withdrawal_limit_ix(
+    program_id: &Pubkey,
+    underlying_mint: &Pubkey,
+    owner: &Pubkey,
+    withdrawal_limit: u64,
+    window_slots: u64,
+) -> Instruction {
+    let vault = VaultAddresses::new(program_id, underlying_mint);
+    let accounts = vault_pda::accounts::SetWithdrawalLimit {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        activity_stats: vault.activity_stats,
+        vault: vault.vault,
+        owner: *owner,
+        admin_log: derive_admin_log_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::SetWithdrawalLimit {
+            withdrawal_limit,
+            window_slots,
+        }
+        .data(),
+    }
+}
+
+/// Builds `add_fee_exemption`, exempting `address`'s redeems from withdrawal
+/// fees; `owner` pays for the exemption
+pub fn add_fee_exemption_ix(program_id: &Pubkey, owner: &Pubkey, address: &Pubkey) -> Instruction {
+    let accounts = vault_pda::accounts::AddFeeExemption {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        fee_exemption: derive_fee_exemption_pda(program_id, address).0,
+        address: *address,
+        owner: *owner,
+        system_program: anchor_lang::system_program::ID,
+        admin_log: derive_admin_log_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::AddFeeExemption {}.data(),
+    }
+}
+
+/// Builds `remove_fee_exemption`, refunding the exemption's rent to `owner`
+pub fn remove_fee_exemption_ix(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    address: &Pubkey,
+) -> Instruction {
+    let accounts = vault_pda::accounts::RemoveFeeExemption {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        fee_exemption: derive_fee_exemption_pda(program_id, address).0,
+        owner: *owner,
+        admin_log: derive_admin_log_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::RemoveFeeExemption {}.data(),
+    }
+}
+
+/// Builds `set_vault_name` for the vault over `underlying_mint`, signed by
+/// its `creator`
+pub fn set_vault_name_ix(
+    program_id: &Pubkey,
+    underlying_mint: &Pubkey,
+    creator: &Pubkey,
+    name: &str,
+) -> Instruction {
+    let accounts = vault_pda::accounts::SetVaultName {
+        vault: VaultAddresses::new(program_id, underlying_mint).vault,
+        creator: *creator,
+        admin_log: derive_admin_log_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::SetVaultName {
+            name: name.to_string(),
+        }
+        .data(),
+    }
+}
+
+/// Builds `migrate_protocol`, growing a legacy protocol state to the current
+/// layout; `owner` is the protocol owner and pays
+pub fn migrate_protocol_ix(program_id: &Pubkey, owner: &Pubkey) -> Instruction {
+    let accounts = vault_pda::accounts::MigrateProtocol {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        owner: *owner,
+        payer: *owner,
+        system_program: anchor_lang::system_program::ID,
+        admin_log: derive_admin_log_pda(program_id).0,
+        vault_registry: derive_vault_registry_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::MigrateProtocol {}.data(),
+    }
+}
+
+/// Builds `migrate_vault` for the legacy vault over `underlying_mint`;
+/// `owner` is the protocol owner and pays
+pub fn migrate_vault_ix(
+    program_id: &Pubkey,
+    underlying_mint: &Pubkey,
+    owner: &Pubkey,
+) -> Instruction {
+    let vault = VaultAddresses::new(program_id, underlying_mint);
+    let accounts = vault_pda::accounts::MigrateVault {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        vault: vault.vault,
+        owner: *owner,
+        payer: *owner,
+        system_program: anchor_lang::system_program::ID,
+        vault_registry: derive_vault_registry_pda(program_id).0,
+        admin_log: derive_admin_log_pda(program_id).0,
+        activity_stats: vault.activity_stats,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::MigrateVault {}.data(),
+    }
+}
+
+/// Builds `close_share_account` for `owner`'s emptied share ATA of the
+/// vault over `underlying_mint`, refunding its rent to `owner`
+pub fn close_share_account_ix(
+    program_id: &Pubkey,
+    underlying_mint: &Pubkey,
+    owner: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    RedeemBuilder::new(*underlying_mint, *owner, 0)
+        .program_id(*program_id)
+        .token_program(*token_program)
+        .build_close()
+}
+
+/// Accounts both builders share
+#[derive(Debug, Clone)]
+struct UserSide {
+    program_id: Pubkey,
+    underlying_mint: Pubkey,
+    user: Pubkey,
+    token_program: Pubkey,
+    underlying_account: Option<Pubkey>,
+    share_account: Option<Pubkey>,
+    treasury: Option<Pubkey>,
+}
+
+impl UserSide {
+    fn new(underlying_mint: Pubkey, user: Pubkey) -> Self {
+        Self {
+            program_id: vault_pda::ID,
+            underlying_mint,
+            user,
+            token_program: anchor_spl::token::ID,
+            underlying_account: None,
+            share_account: None,
+            treasury: None,
+        }
+    }
+
+    /// The vault's addresses and the user's underlying and share accounts
+    fn resolve(&self) -> (VaultAddresses, Pubkey, Pubkey) {
+        let vault = VaultAddresses::new(&self.program_id, &self.underlying_mint);
+        let underlying_account = self.underlying_account.unwrap_or_else(|| {
+            get_associated_token_address_with_program_id(
+                &self.user,
+                &self.underlying_mint,
+                &self.token_program,
+            )
+        });
+        let share_account = self.share_account.unwrap_or_else(|| {
+            get_associated_token_address_with_program_id(
+                &self.user,
+                &vault.share_mint,
+                &self.token_program,
+            )
+        });
+        (vault, underlying_account, share_account)
+    }
+
+    /// `owner`'s entry for the vault's flag, which deposits and redeems
+    /// always pass whether or not it exists
+    fn player_record(&self, vault: &VaultAddresses, owner: &Pubkey) -> Pubkey {
+        derive_player_pda(&self.program_id, &vault.flag, owner).0
+    }
+
+    /// The protocol state, passed with the treasury to settle the reserve
+    fn protocol_state(&self) -> Option<Pubkey> {
+        self.treasury
+            .map(|_| derive_protocol_state_pda(&self.program_id).0)
+    }
+}
+
+/// Builds a `deposit` of `amount` underlying by `depositor`
+#[derive(Debug, Clone)]
+pub struct DepositBuilder {
+    side: UserSide,
+    amount: u64,
+}
+
+impl DepositBuilder {
+    pub fn new(underlying_mint: Pubkey, depositor: Pubkey, amount: u64) -> Self {
+        Self {
+            side: UserSide::new(underlying_mint, depositor),
+            amount,
+        }
+    }
+
+    /// Program to call instead of `vault_pda::ID`, e.g. a challenge clone
+    pub fn program_id(mut self, program_id: Pubkey) -> Self {
+        self.side.program_id = program_id;
+        self
+    }
+
+    /// Token program of the underlying and share mints (SPL Token by default)
+    pub fn token_program(mut self, token_program: Pubkey) -> Self {
+        self.side.token_program = token_program;
+        self
+    }
+
+    /// Underlying account to pay from instead of the depositor's ATA
+    pub fn depositor_underlying_account(mut self, account: Pubkey) -> Self {
+        self.side.underlying_account = Some(account);
+        self
+    }
+
+    /// Share account to mint to instead of the depositor's ATA
+    pub fn depositor_share_account(mut self, account: Pubkey) -> Self {
+        self.side.share_account = Some(account);
+        self
+    }
+
+    /// The treasury of the vault's reserve config, which the deposit
+    /// settles first; required once the vault has one
+    pub fn treasury(mut self, treasury: Pubkey) -> Self {
+        self.side.treasury = Some(treasury);
+        self
+    }
+
+    pub fn build(&self) -> Instruction {
+        let (vault, underlying_account, share_account) = self.side.resolve();
+        let accounts = vault_pda::accounts::Deposit {
+            vault: vault.vault,
+            underlying_mint: self.side.underlying_mint,
+            vault_token_account: vault.vault_token_account,
+            share_mint: vault.share_mint,
+            vault_authority: vault.vault_authority,
+            depositor_underlying_account: underlying_account,
+            depositor_share_account: share_account,
+            depositor: self.side.user,
+            flag: vault.flag,
+            player_record: self.side.player_record(&vault, &self.side.user),
+            activity_stats: vault.activity_stats,
+            reserve_config: vault.reserve_config,
+            treasury: self.side.treasury,
+            protocol_state: self.side.protocol_state(),
+            token_program: self.side.token_program,
+        };
+
+        Instruction {
+            program_id: self.side.program_id,
+            accounts: accounts.to_account_metas(None),
+            data: vault_pda::instruction::Deposit {
+                amount: self.amount,
+            }
+            .data(),
+        }
+    }
+
+    /// `deposit_delegated` of `amount` from the depositor's accounts,
+    /// triggered by `relayer` once the depositor signed [`Self::build_approve`]
+    pub fn build_delegated(&self, relayer: Pubkey) -> Instruction {
+        let (vault, underlying_account, share_account) = self.side.resolve();
+        let accounts = vault_pda::accounts::DepositDelegated {
+            vault: vault.vault,
+            underlying_mint: self.side.underlying_mint,
+            vault_token_account: vault.vault_token_account,
+            share_mint: vault.share_mint,
+            vault_authority: vault.vault_authority,
+            owner_underlying_account: underlying_account,
+            owner_share_account: share_account,
+            deposit_delegate: self.deposit_delegate(&vault.vault, &relayer),
+            relayer,
+            flag: vault.flag,
+            player_record: self.side.player_record(&vault, &self.side.user),
+            activity_stats: vault.activity_stats,
+            reserve_config: vault.reserve_config,
+            treasury: self.side.treasury,
+            protocol_state: self.side.protocol_state(),
+            token_program: self.side.token_program,
+        };
+
+        Instruction {
+            program_id: self.side.program_id,
+            accounts: accounts.to_account_metas(None),
+            data: vault_pda::instruction::DepositDelegated {
+                amount: self.amount,
+            }
+            .data(),
+        }
+    }
+
+    /// The token program's `approve`, signed by the depositor, letting
+    /// `relayer` deposit up to `amount` from the depositor's underlying
+    /// account through [`Self::build_delegated`]
+    ///
+    /// Panics unless the token program is SPL Token or Token-2022.
+    pub fn build_approve(&self, relayer: Pubkey) -> Instruction {
+        let (vault, underlying_account, _) = self.side.resolve();
+        spl_token_2022::instruction::approve(
+            &self.side.token_program,
+            &underlying_account,
+            &self.deposit_delegate(&vault.vault, &relayer),
+            &self.side.user,
+            &[],
+            self.amount,
+        )
+        .expect("SPL Token and Token-2022 both take approve")
+    }
+
+    fn deposit_delegate(&self, vault: &Pubkey, relayer: &Pubkey) -> Pubkey {
+        derive_deposit_delegate_pda(&self.side.program_id, vault, &self.side.user, relayer).0
+    }
+}
+
+/// Builds a `redeem` of `shares` by `redeemer`
+#[derive(Debug, Clone)]
+pub struct RedeemBuilder {
+    side: UserSide,
+    shares: u64,
+}
+
+impl RedeemBuilder {
+    pub fn new(underlying_mint: Pubkey, redeemer: Pubkey, shares: u64) -> Self {
+        Self {
+            side: UserSide::new(underlying_mint, redeemer),
+            shares,
+        }
+    }
+
+    /// Program to call instead of `vault_pda::ID`, e.g. a challenge clone
+    pub fn program_id(mut self, program_id: Pubkey) -> Self {
+        self.side.program_id = program_id;
+        self
+    }
+
+    /// Token program of the underlying and share mints (SPL Token by default)
+    pub fn token_program(mut self, token_program: Pubkey) -> Self {
+        self.side.token_program = token_program;
+        self
+    }
+
+    /// Underlying account to pay out to instead of the redeemer's ATA
+    pub fn redeemer_underlying_account(mut self, account: Pubkey) -> Self {
+        self.side.underlying_account = Some(account);
+        self
+    }
+
+    /// Share account to burn from instead of the redeemer's ATA
+    pub fn redeemer_share_account(mut self, account: Pubkey) -> Self {
+        self.side.share_account = Some(account);
+        self
+    }
+
+    /// The treasury of the vault's reserve config, which the redeem settles
+    /// first; required once the vault has one
+    pub fn treasury(mut self, treasury: Pubkey) -> Self {
+        self.side.treasury = Some(treasury);
+        self
+    }
+
+    pub fn build(&self) -> Instruction {
+        let (vault, underlying_account, share_account) = self.side.resolve();
+        let accounts = vault_pda::accounts::Redeem {
+            vault: vault.vault,
+            underlying_mint: self.side.underlying_mint,
+            vault_token_account: vault.vault_token_account,
+            share_mint: vault.share_mint,
+            vault_authority: vault.vault_authority,
+            redeemer_underlying_account: underlying_account,
+            redeemer_share_account: share_account,
+            redeemer: self.side.user,
+            flag: vault.flag,
+            player_record: self.side.player_record(&vault, &self.side.user),
+            activity_stats: vault.activity_stats,
+            reserve_config: vault.reserve_config,
+            treasury: self.side.treasury,
+            protocol_state: self.side.protocol_state(),
+            fee_exemption: derive_fee_exemption_pda(&self.side.program_id, &self.side.user).0,
+            token_program: self.side.token_program,
+        };
+
+        Instruction {
+            program_id: self.side.program_id,
+            accounts: accounts.to_account_metas(None),
+            data: vault_pda::instruction::Redeem {
+                shares: self.shares,
+            }
+            .data(),
+        }
+    }
+
+    /// `close_share_account` for the share account this redeem burns from.
+    /// Send it after a redeem of every share to exit in one transaction
+    pub fn build_close(&self) -> Instruction {
+        let (vault, _, share_account) = self.side.resolve();
+        let accounts = vault_pda::accounts::CloseShareAccount {
+            vault: vault.vault,
+            share_mint: vault.share_mint,
+            share_account,
+            owner: self.side.user,
+            token_program: self.side.token_program,
+        };
+
+        Instruction {
+            program_id: self.side.program_id,
+            accounts: accounts.to_account_metas(None),
+            data: vault_pda::instruction::CloseShareAccount {}.data(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pda::derive_activity_stats_pda;
+
+    #[test]
+    fn deposit_derives_vault_accounts_and_atas() {
+        let mint = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let ix = DepositBuilder::new(mint, user, 42).build();
+        let vault = VaultAddresses::new(&vault_pda::ID, &mint);
+
+        assert_eq!(ix.program_id, vault_pda::ID);
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(keys[0], vault.vault);
+        assert_eq!(keys[2], vault.vault_token_account);
+        assert_eq!(keys[3], vault.share_mint);
+        assert_eq!(keys[4], vault.vault_authority);
+        assert_eq!(
+            keys[5],
+            get_associated_token_address_with_program_id(&user, &mint, &anchor_spl::token::ID)
+        );
+        assert!(ix.accounts[7].is_signer);
+        assert_eq!(keys[8], vault.flag);
+        assert_eq!(
+            keys[9],
+            derive_player_pda(&vault_pda::ID, &vault.flag, &user).0
+        );
+        assert!(ix.accounts[8].is_writable && ix.accounts[9].is_writable);
+        assert_eq!(
+            ix.data,
+            vault_pda::instruction::Deposit { amount: 42 }.data()
+        );
+    }
+
+    #[test]
+    fn redeem_overrides_take_precedence() {
+        let program_id = Pubkey::new_unique();
+        let share_account = Pubkey::new_unique();
+        let ix = RedeemBuilder::new(Pubkey::new_unique(), Pubkey::new_unique(), 7)
+            .program_id(program_id)
+            .token_program(anchor_spl::token_2022::ID)
+            .redeemer_share_account(share_account)
+            .build();
+
+        assert_eq!(ix.program_id, program_id);
+        assert_eq!(ix.accounts[6].pubkey, share_account);
+        assert_eq!(ix.accounts[15].pubkey, anchor_spl::token_2022::ID);
+    }
+
+    #[test]
+    fn the_treasury_settles_the_reserve() {
+        let (mint, user, treasury) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let vault = VaultAddresses::new(&vault_pda::ID, &mint);
+
+        // Without a reserve the optional accounts are left out
+        let ix = RedeemBuilder::new(mint, user, 7).build();
+        assert_eq!(ix.accounts[11].pubkey, vault.reserve_config);
+        assert!(ix.accounts[11].is_writable);
+        assert_eq!(ix.accounts[12].pubkey, vault_pda::ID);
+        assert_eq!(ix.accounts[13].pubkey, vault_pda::ID);
+
+        for ix in [
+            DepositBuilder::new(mint, user, 42)
+                .treasury(treasury)
+                .build(),
+            RedeemBuilder::new(mint, user, 7).treasury(treasury).build(),
+        ] {
+            assert_eq!(ix.accounts[11].pubkey, vault.reserve_config);
+            assert_eq!(ix.accounts[12].pubkey, treasury);
+            assert!(ix.accounts[12].is_writable);
+            assert_eq!(
+                ix.accounts[13].pubkey,
+                derive_protocol_state_pda(&vault_pda::ID).0
+            );
+        }
+    }
+
+    #[test]
+    fn redeems_pass_the_redeemers_fee_exemption() {
+        let (mint, user) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let exemption = derive_fee_exemption_pda(&vault_pda::ID, &user).0;
+        let ix = RedeemBuilder::new(mint, user, 7).build();
+        assert_eq!(ix.accounts[14].pubkey, exemption);
+        assert!(!ix.accounts[14].is_writable);
+
+        let owner = Pubkey::new_unique();
+        let add = add_fee_exemption_ix(&vault_pda::ID, &owner, &user);
+        let remove = remove_fee_exemption_ix(&vault_pda::ID, &owner, &user);
+        assert_eq!(add.accounts[1].pubkey, exemption);
+        assert_eq!(add.accounts[2].pubkey, user);
+        assert_eq!(remove.accounts[1].pubkey, exemption);
+        assert!(add.accounts[3].is_signer && remove.accounts[2].is_signer);
+    }
+
+    #[test]
+    fn closing_targets_the_redeemed_share_account() {
+        let mint = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let share_account = Pubkey::new_unique();
+        let builder = RedeemBuilder::new(mint, user, 7).redeemer_share_account(share_account);
+        let [redeem, close] = [builder.build(), builder.build_close()];
+
+        assert_eq!(close.accounts[2].pubkey, redeem.accounts[6].pubkey);
+        assert!(close.accounts[2].is_writable);
+        assert_eq!(close.accounts[3].pubkey, user);
+        assert!(close.accounts[3].is_signer && close.accounts[3].is_writable);
+
+        let ata = close_share_account_ix(&vault_pda::ID, &mint, &user, &anchor_spl::token::ID);
+        assert_eq!(
+            ata.accounts[2].pubkey,
+            RedeemBuilder::new(mint, user, 7).build().accounts[6].pubkey
+        );
+    }
+
+    #[test]
+    fn delegated_deposits_pull_through_the_approved_delegate() {
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let relayer = Pubkey::new_unique();
+        let builder = DepositBuilder::new(mint, owner, 500);
+        let approve = builder.build_approve(relayer);
+        let deposit = builder.build_delegated(relayer);
+        let plain = builder.build();
+
+        // Pulls from and mints to the owner's accounts, as `deposit` would
+        assert_eq!(deposit.accounts[5].pubkey, plain.accounts[5].pubkey);
+        assert_eq!(deposit.accounts[6].pubkey, plain.accounts[6].pubkey);
+        assert_eq!(approve.accounts[0].pubkey, plain.accounts[5].pubkey);
+
+        let vault = VaultAddresses::new(&vault_pda::ID, &mint).vault;
+        let delegate = derive_deposit_delegate_pda(&vault_pda::ID, &vault, &owner, &relayer).0;
+        assert_eq!(approve.accounts[1].pubkey, delegate);
+        assert_eq!(deposit.accounts[7].pubkey, delegate);
+        assert!(approve.accounts[2].is_signer && approve.accounts[2].pubkey == owner);
+        assert!(deposit.accounts[8].is_signer && deposit.accounts[8].pubkey == relayer);
+        assert!(deposit.accounts.iter().all(|meta| meta.pubkey != owner));
+
+        // Counted against the owner's flag entry, not the relayer's
+        assert_eq!(deposit.accounts[10].pubkey, plain.accounts[9].pubkey);
+    }
+
+    #[test]
+    fn deposits_and_redeems_record_activity() {
+        let mint = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let deposit = DepositBuilder::new(mint, user, 42).build();
+        let vault = VaultAddresses::new(&vault_pda::ID, &mint).vault;
+
+        let stats = &deposit.accounts[10];
+        assert_eq!(
+            stats.pubkey,
+            derive_activity_stats_pda(&vault_pda::ID, &vault).0
+        );
+        assert!(stats.is_writable && !stats.is_signer);
+        assert_eq!(
+            RedeemBuilder::new(mint, user, 7).build().accounts[10],
+            *stats
+        );
+        assert_eq!(
+            DepositBuilder::new(mint, user, 42)
+                .build_delegated(Pubkey::new_unique())
+                .accounts[11],
+            *stats
+        );
+    }
+
+    #[test]
+    fn report_derives_the_reserve_config() {
+        let mint = Pubkey::new_unique();
+        let treasury = Pubkey::new_unique();
+        let ix = report_ix(&vault_pda::ID, &mint, &treasury, &anchor_spl::token::ID);
+        let vault = VaultAddresses::new(&vault_pda::ID, &mint);
+
+        assert_eq!(
+            ix.accounts[0].pubkey,
+            derive_reserve_config_pda(&vault_pda::ID, &vault.vault).0
+        );
+        assert!(ix.accounts[0].is_writable);
+        assert_eq!(ix.accounts[5].pubkey, treasury);
+        assert_eq!(
+            ix.accounts[6].pubkey,
+            derive_protocol_state_pda(&vault_pda::ID).0
+        );
+        assert!(ix.accounts.iter().all(|meta| !meta.is_signer));
+    }
+
+    #[test]
+    fn set_reserve_factor_reports_first() {
+        let (mint, owner, treasury) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let token_program = anchor_spl::token::ID;
+        let ix = set_reserve_factor_ix(&vault_pda::ID, &mint, &owner, &treasury, &token_program, 0);
+        let report = report_ix(&vault_pda::ID, &mint, &treasury, &token_program);
+
+        assert_eq!(ix.accounts[1].pubkey, owner);
+        assert!(ix.accounts[1].is_signer);
+        assert_eq!(ix.accounts[2..10], report.accounts[..]);
+    }
+
+    #[test]
+    fn skim_pays_the_treasury_owners_underlying_account() {
+        let (mint, treasury, paid) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let ix = skim_ix(
+            &vault_pda::ID,
+            &mint,
+            &treasury,
+            &paid,
+            &anchor_spl::token::ID,
+        );
+        let vault = VaultAddresses::new(&vault_pda::ID, &mint);
+
+        assert_eq!(
+            ix.accounts[0].pubkey,
+            derive_reserve_config_pda(&vault_pda::ID, &vault.vault).0
+        );
+        assert_eq!(ix.accounts[3].pubkey, vault.vault_token_account);
+        assert!(ix.accounts[3].is_writable);
+        assert_eq!(ix.accounts[6].pubkey, treasury);
+        assert!(!ix.accounts[6].is_writable);
+        assert_eq!(ix.accounts[7].pubkey, paid);
+        assert!(ix.accounts[7].is_writable);
+        assert!(ix.accounts.iter().all(|meta| !meta.is_signer));
+    }
+}
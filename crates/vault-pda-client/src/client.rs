@@ -0,0 +1,265 @@
+//! `VaultClient`: transaction plumbing for integrators.
+//!
+//! Every send is simulated first, so a transaction the program would reject
+//! fails fast with the Anchor error it logged ([`ClientError::Simulation`])
+//! instead of burning a fee. A transaction whose blockhash expires before it
+//! lands is re-signed with a fresh one and sent again, up to `max_retries`
+//! times. An optional [`PriorityFee`] prepends compute budget instructions.
+//!
+//! ```ignore
+//! let client = VaultClient::new(RpcClient::new(url), payer)
+//!     .priority_fee(PriorityFee::new(10_000).compute_unit_limit(100_000));
+//! let shares = client.vault_state(&usdc).await?.preview_deposit(amount)?;
+//! client.deposit(&usdc, &depositor, amount).await?;
+//! ```
+
+use std::time::Duration;
+
+use anchor_lang::prelude::Pubkey;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+
+use vault_pda::state::ReserveConfig;
+
+use crate::pda::VaultAddresses;
+use crate::{
+    fetch, fetch_vault_state, AnchorErrorLog, ClientError, DepositBuilder, RedeemBuilder,
+    VaultState,
+};
+
+/// How often a sent transaction's status is polled
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Compute budget instructions added to every transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriorityFee {
+    pub micro_lamports_per_cu: u64,
+    /// Requested compute units, or the runtime default
+    pub compute_unit_limit: Option<u32>,
+}
+
+impl PriorityFee {
+    pub fn new(micro_lamports_per_cu: u64) -> Self {
+        Self {
+            micro_lamports_per_cu,
+            compute_unit_limit: None,
+        }
+    }
+
+    /// Requests `units` compute units, which also caps the fee paid
+    pub fn compute_unit_limit(mut self, units: u32) -> Self {
+        self.compute_unit_limit = Some(units);
+        self
+    }
+
+    fn instructions(&self) -> Vec<Instruction> {
+        let mut instructions = Vec::with_capacity(2);
+        if let Some(units) = self.compute_unit_limit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
+            self.micro_lamports_per_cu,
+        ));
+        instructions
+    }
+}
+
+pub struct VaultClient {
+    rpc: RpcClient,
+    payer: Keypair,
+    program_id: Pubkey,
+    priority_fee: Option<PriorityFee>,
+    max_retries: usize,
+}
+
+impl VaultClient {
+    /// A client for `vault_pda::ID` where `payer` pays every fee
+    pub fn new(rpc: RpcClient, payer: Keypair) -> Self {
+        Self {
+            rpc,
+            payer,
+            program_id: vault_pda::ID,
+            priority_fee: None,
+            max_retries: 3,
+        }
+    }
+
+    /// Program to call instead of `vault_pda::ID`, e.g. a challenge clone
+    pub fn program_id(mut self, program_id: Pubkey) -> Self {
+        self.program_id = program_id;
+        self
+    }
+
+    pub fn priority_fee(mut self, fee: PriorityFee) -> Self {
+        self.priority_fee = Some(fee);
+        self
+    }
+
+    /// Resends after this many blockhash expiries before giving up
+    pub fn max_retries(mut self, retries: usize) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// The program this client calls
+    pub fn program(&self) -> Pubkey {
+        self.program_id
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    pub async fn vault_state(&self, underlying_mint: &Pubkey) -> Result<VaultState, ClientError> {
+        fetch_vault_state(&self.rpc, &self.program_id, underlying_mint).await
+    }
+
+    /// Deposits `amount` from `depositor`'s associated token accounts
+    pub async fn deposit(
+        &self,
+        underlying_mint: &Pubkey,
+        depositor: &Keypair,
+        amount: u64,
+    ) -> Result<Signature, ClientError> {
+        let mut builder = DepositBuilder::new(*underlying_mint, depositor.pubkey(), amount)
+            .program_id(self.program_id);
+        if let Some(treasury) = self.treasury(underlying_mint).await? {
+            builder = builder.treasury(treasury);
+        }
+        self.send(&[builder.build()], &[depositor]).await
+    }
+
+    /// Redeems `shares` to `redeemer`'s associated token accounts
+    pub async fn redeem(
+        &self,
+        underlying_mint: &Pubkey,
+        redeemer: &Keypair,
+        shares: u64,
+    ) -> Result<Signature, ClientError> {
+        let mut builder = RedeemBuilder::new(*underlying_mint, redeemer.pubkey(), shares)
+            .program_id(self.program_id);
+        if let Some(treasury) = self.treasury(underlying_mint).await? {
+            builder = builder.treasury(treasury);
+        }
+        self.send(&[builder.build()], &[redeemer]).await
+    }
+
+    /// The treasury of the vault's reserve config, which deposits and
+    /// redeems settle first, if the vault has one
+    pub async fn treasury(&self, underlying_mint: &Pubkey) -> Result<Option<Pubkey>, ClientError> {
+        let address = VaultAddresses::new(&self.program_id, underlying_mint).reserve_config;
+        match fetch::<ReserveConfig>(&self.rpc, &address).await {
+            Ok(config) => Ok(Some(config.treasury)),
+            Err(ClientError::AccountNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Simulates `instructions` and returns the compute units they consumed
+    pub async fn simulate(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Option<u64>, ClientError> {
+        let (tx, _) = self.sign(instructions, signers).await?;
+        self.preflight(&tx).await
+    }
+
+    /// Signs `instructions` with the payer and `signers`, simulates, sends and
+    /// waits for confirmation, resending with a fresh blockhash on expiry
+    pub async fn send(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Signature, ClientError> {
+        let config = RpcSendTransactionConfig {
+            // Simulated just before
+            skip_preflight: true,
+            ..Default::default()
+        };
+
+        for _ in 0..=self.max_retries {
+            let (tx, last_valid_block_height) = self.sign(instructions, signers).await?;
+            self.preflight(&tx).await?;
+            let signature = self.rpc.send_transaction_with_config(&tx, config).await?;
+
+            loop {
+                if let Some(status) = self.rpc.get_signature_status(&signature).await? {
+                    return status.map(|()| signature).map_err(ClientError::Transaction);
+                }
+                if self.rpc.get_block_height().await? > last_valid_block_height {
+                    break;
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+
+        Err(ClientError::Expired {
+            attempts: self.max_retries + 1,
+        })
+    }
+
+    /// Signs against the latest blockhash, returning the block height it is
+    /// valid until
+    async fn sign(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(Transaction, u64), ClientError> {
+        let mut all = self
+            .priority_fee
+            .map(|fee| fee.instructions())
+            .unwrap_or_default();
+        all.extend_from_slice(instructions);
+
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+
+        let (blockhash, last_valid_block_height) = self
+            .rpc
+            .get_latest_blockhash_with_commitment(self.rpc.commitment())
+            .await?;
+        let tx = Transaction::new_signed_with_payer(
+            &all,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        Ok((tx, last_valid_block_height))
+    }
+
+    /// Fails with the decoded program error if simulation rejects `tx`
+    async fn preflight(&self, tx: &Transaction) -> Result<Option<u64>, ClientError> {
+        let config = RpcSimulateTransactionConfig {
+            commitment: Some(self.rpc.commitment()),
+            ..Default::default()
+        };
+        let result = self
+            .rpc
+            .simulate_transaction_with_config(tx, config)
+            .await?
+            .value;
+
+        match result.err {
+            Some(error) => {
+                let logs = result.logs.unwrap_or_default();
+                Err(ClientError::Simulation {
+                    error,
+                    program_error: AnchorErrorLog::from_logs(&logs),
+                    logs,
+                })
+            }
+            None => Ok(result.units_consumed),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Client errors.
+
+use std::fmt;
+
+use anchor_lang::prelude::Pubkey;
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// No account exists at the address
+    AccountNotFound(Pubkey),
+    /// The account is not of the expected type
+    Deserialize(anchor_lang::error::Error),
+    /// The program would reject the deposit or redeem
+    Rejected(crate::preview::KernelError),
+    /// The vault's token account is frozen, so no token can move in or out
+    Frozen(Pubkey),
+    /// The RPC request failed
+    #[cfg(feature = "rpc")]
+    Rpc(Box<solana_client::client_error::ClientError>),
+    /// Preflight simulation rejected the transaction, so it was never sent
+    #[cfg(feature = "rpc")]
+    Simulation {
+        error: solana_sdk::transaction::TransactionError,
+        /// The Anchor error the program logged, if any
+        program_error: Option<AnchorErrorLog>,
+        logs: Vec<String>,
+    },
+    /// The transaction landed and failed
+    #[cfg(feature = "rpc")]
+    Transaction(solana_sdk::transaction::TransactionError),
+    /// Every attempt's blockhash expired before the transaction landed
+    #[cfg(feature = "rpc")]
+    Expired { attempts: usize },
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::AccountNotFound(address) => write!(f, "Account {address} not found"),
+            ClientError::Deserialize(e) => write!(f, "Failed to deserialize account: {e}"),
+            ClientError::Rejected(e) => write!(f, "The program would reject this: {e:?}"),
+            ClientError::Frozen(account) => write!(f, "Vault token account {account} is frozen"),
+            #[cfg(feature = "rpc")]
+            ClientError::Rpc(e) => write!(f, "RPC request failed: {e}"),
+            #[cfg(feature = "rpc")]
+            ClientError::Simulation {
+                program_error: Some(log),
+                ..
+            } => write!(f, "Simulation failed: {log}"),
+            #[cfg(feature = "rpc")]
+            ClientError::Simulation { error, .. } => write!(f, "Simulation failed: {error}"),
+            #[cfg(feature = "rpc")]
+            ClientError::Transaction(e) => write!(f, "Transaction failed: {e}"),
+            #[cfg(feature = "rpc")]
+            ClientError::Expired { attempts } => {
+                write!(f, "Blockhash expired on all {attempts} attempts")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<anchor_lang::error::Error> for ClientError {
+    fn from(e: anchor_lang::error::Error) -> Self {
+        ClientError::Deserialize(e)
+    }
+}
+
+impl From<crate::preview::KernelError> for ClientError {
+    fn from(e: crate::preview::KernelError) -> Self {
+        ClientError::Rejected(e)
+    }
+}
+
+#[cfg(feature = "rpc")]
+impl From<solana_client::client_error::ClientError> for ClientError {
+    fn from(e: solana_client::client_error::ClientError) -> Self {
+        ClientError::Rpc(Box::new(e))
+    }
+}
+
+/// An error as Anchor logs it when an instruction fails
+///
+/// The custom error code alone is ambiguous (each instruction's
+/// `#[error_code]` enum numbers from 6000), while the log names the variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorErrorLog {
+    /// Variant name, e.g. `InvalidAmount` or `ConstraintSeeds`
+    pub name: String,
+    pub number: u32,
+    pub message: String,
+}
+
+impl AnchorErrorLog {
+    /// The last Anchor error in `logs`
+    pub fn from_logs(logs: &[String]) -> Option<Self> {
+        logs.iter().rev().find_map(|line| Self::parse(line))
+    }
+
+    /// Parses one `AnchorError ... Error Code: X. Error Number: N. Error
+    /// Message: M.` line
+    pub fn parse(line: &str) -> Option<Self> {
+        if !line.contains("AnchorError") {
+            return None;
+        }
+        let (_, rest) = line.split_once("Error Code: ")?;
+        let (name, rest) = rest.split_once(". Error Number: ")?;
+        let (number, message) = rest.split_once(". Error Message: ")?;
+        Some(Self {
+            name: name.to_string(),
+            number: number.parse().ok()?,
+            message: message.strip_suffix('.').unwrap_or(message).to_string(),
+        })
+    }
+}
+
+impl fmt::Display for AnchorErrorLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.name, self.number, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_anchor_error_logs() {
+        let logs = vec![
+            "Program 8qsydpwMiRcFtJ8wrKkM4xrMMEWfnw2szibQGLgBw6KH invoke [1]".to_string(),
+            "Program log: AnchorError thrown in programs/vault-pda/src/instructions/deposit.rs:62. Error Code: InvalidAmount. Error Number: 6000. Error Message: Deposit amount must be greater than zero.".to_string(),
+        ];
+        assert_eq!(
+            AnchorErrorLog::from_logs(&logs),
+            Some(AnchorErrorLog {
+                name: "InvalidAmount".to_string(),
+                number: 6000,
+                message: "Deposit amount must be greater than zero".to_string(),
+            })
+        );
+
+        let constraint = "Program log: AnchorError caused by account: vault. Error Code: ConstraintSeeds. Error Number: 2006. Error Message: A seeds constraint was violated.";
+        assert_eq!(
+            AnchorErrorLog::parse(constraint).map(|log| log.number),
+            Some(2006)
+        );
+        assert_eq!(
+            AnchorErrorLog::parse("Program log: Instruction: Deposit"),
+            None
+        );
+    }
+}
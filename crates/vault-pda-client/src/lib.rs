@@ -0,0 +1,46 @@
+//! Rust client SDK for the vault program.
+//!
+//! The canonical way for services and bots to integrate with a deployed
+//! vault, with no dependency on the test harnesses:
+//!
+//! - [`pda`] derives every program address
+//! - [`DepositBuilder`] and [`RedeemBuilder`] build instructions from an
+//!   underlying mint and a user, deriving everything else
+//! - [`accounts`] deserializes and (with the `rpc` feature) fetches typed
+//!   accounts
+//! - [`preview`] computes what a deposit or redeem returns, with the
+//!   program's own math, and (with the `rpc` feature) `preview_deposit` and
+//!   `preview_redeem` fetch everything that takes in one request
+//! - `VaultClient` (with the `rpc` feature) sends transactions with
+//!   preflight simulation, blockhash refresh and an optional priority fee
+//! - [`blocking`] (with the `rpc` feature) sends one transaction at a time
+//!   and tops up a payer, for tools driving a local validator
+//!
+//! ```ignore
+//! let state = fetch_vault_state(&rpc, &vault_pda::ID, &usdc).await?;
+//! let shares = state.preview_deposit(1_000_000)?;
+//! let ix = DepositBuilder::new(usdc, user, 1_000_000).build();
+//! ```
+
+pub mod accounts;
+#[cfg(feature = "rpc")]
+pub mod blocking;
+pub mod builders;
+#[cfg(feature = "rpc")]
+pub mod client;
+pub mod error;
+pub mod pda;
+pub mod preview;
+
+pub use accounts::*;
+pub use builders::{
+    add_fee_exemption_ix, close_share_account_ix, initialize_ix, initialize_reserve_ix,
+    initialize_vault_ix, migrate_protocol_ix, migrate_vault_ix, remove_fee_exemption_ix, report_ix,
+    set_features_ix, set_reserve_factor_ix, set_vault_name_ix, set_withdrawal_fee_ix,
+    set_withdrawal_limit_ix, skim_ix, sunset_vault_ix, sync_upgrade_authority_ix,
+    verify_upgrade_authority_ix, DepositBuilder, RedeemBuilder,
+};
+#[cfg(feature = "rpc")]
+pub use client::{PriorityFee, VaultClient};
+pub use error::{AnchorErrorLog, ClientError};
+pub use preview::VaultTotals;
@@ -0,0 +1,113 @@
+//! Program derived addresses, with the seeds the program constrains them to.
+
+use anchor_lang::prelude::Pubkey;
+
+/// Derive protocol state PDA
+pub fn derive_protocol_state_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"protocol_state"], program_id)
+}
+
+/// Derive vault authority PDA
+pub fn derive_vault_authority_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault_authority"], program_id)
+}
+
+/// Derive vault PDA
+pub fn derive_vault_pda(program_id: &Pubkey, underlying_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", underlying_mint.as_ref()], program_id)
+}
+
+/// Derive share mint PDA
+pub fn derive_share_mint_pda(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"share_mint", vault.as_ref()], program_id)
+}
+
+/// Derive vault token account PDA
+pub fn derive_vault_token_account_pda(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault_token_account", vault.as_ref()], program_id)
+}
+
+/// Derive flag PDA
+pub fn derive_flag_pda(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"flag", vault.as_ref()], program_id)
+}
+
+/// Derive player PDA
+pub fn derive_player_pda(program_id: &Pubkey, flag: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"player", flag.as_ref(), player.as_ref()], program_id)
+}
+
+/// Derive reserve config PDA
+pub fn derive_reserve_config_pda(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"reserve_config", vault.as_ref()], program_id)
+}
+
+/// Derive activity stats PDA
+pub fn derive_activity_stats_pda(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"activity_stats", vault.as_ref()], program_id)
+}
+
+/// Derive the delegate `owner` approves so `relayer` can deposit for them
+pub fn derive_deposit_delegate_pda(
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    owner: &Pubkey,
+    relayer: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"deposit_delegate",
+            vault.as_ref(),
+            owner.as_ref(),
+            relayer.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// Derive the PDA that exempts `address`'s redeems from withdrawal fees
+pub fn derive_fee_exemption_pda(program_id: &Pubkey, address: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"fee_exemption", address.as_ref()], program_id)
+}
+
+/// Derive the admin log PDA
+pub fn derive_admin_log_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"admin_log"], program_id)
+}
+
+/// Derive the vault registry PDA
+pub fn derive_vault_registry_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault_registry"], program_id)
+}
+
+/// Derive the program's `ProgramData` address under the upgradeable loader
+pub fn derive_program_data_address(program_id: &Pubkey) -> Pubkey {
+    solana_loader_v3_interface::get_program_data_address(program_id)
+}
+
+/// Every address of the vault over `underlying_mint`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VaultAddresses {
+    pub vault: Pubkey,
+    pub vault_token_account: Pubkey,
+    pub share_mint: Pubkey,
+    pub vault_authority: Pubkey,
+    pub flag: Pubkey,
+    pub activity_stats: Pubkey,
+    pub reserve_config: Pubkey,
+}
+
+impl VaultAddresses {
+    pub fn new(program_id: &Pubkey, underlying_mint: &Pubkey) -> Self {
+        let vault = derive_vault_pda(program_id, underlying_mint).0;
+        Self {
+            vault,
+            vault_token_account: derive_vault_token_account_pda(program_id, &vault).0,
+            share_mint: derive_share_mint_pda(program_id, &vault).0,
+            vault_authority: derive_vault_authority_pda(program_id).0,
+            flag: derive_flag_pda(program_id, &vault).0,
+            activity_stats: derive_activity_stats_pda(program_id, &vault).0,
+            reserve_config: derive_reserve_config_pda(program_id, &vault).0,
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Deposit and redeem previews.
+//!
+//! The program prices every conversion with the `vault-math` kernels, so the
+//! previews here are exact: a deposit previewed against the totals a
+//! transaction will see mints exactly the previewed shares. Totals move
+//! between fetching and landing, so bots should still bound the result with
+//! a slippage check.
+//!
+//! [`VaultState::preview_deposit`](crate::VaultState::preview_deposit) and
+//! its redeem twin also check that the vault's token account is not frozen.
+//! The program has no deposit cap or pause switch, so a freeze by the
+//! underlying mint's freeze authority is the only thing that halts a vault.
+
+pub use vault_math::KernelError;
+
+/// What the program prices shares off: the share mint's supply and the
+/// vault token account's balance
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VaultTotals {
+    pub total_shares: u64,
+    pub total_assets: u64,
+}
+
+impl VaultTotals {
+    /// Shares a deposit of `amount` mints, or why it is rejected
+    pub fn preview_deposit(&self, amount: u64) -> Result<u64, KernelError> {
+        vault_math::compute_shares(amount, self.total_shares, self.total_assets)
+    }
+
+    /// Underlying a redeem of `shares` pays out, or why it is rejected
+    pub fn preview_redeem(&self, shares: u64) -> Result<u64, KernelError> {
+        vault_math::compute_assets(shares, self.total_shares, self.total_assets)
+    }
+
+    /// Underlying per share, or `None` while no shares are in circulation
+    pub fn share_price(&self) -> Option<f64> {
+        (self.total_shares > 0).then(|| self.total_assets as f64 / self.total_shares as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previews_match_the_kernels() {
+        let totals = VaultTotals {
+            total_shares: 1_000,
+            total_assets: 3_000,
+        };
+        assert_eq!(
+            totals.preview_deposit(300),
+            vault_math::compute_shares(300, 1_000, 3_000)
+        );
+        assert_eq!(
+            totals.preview_redeem(10),
+            vault_math::compute_assets(10, 1_000, 3_000)
+        );
+        assert_eq!(totals.share_price(), Some(3.0));
+
+        let empty = VaultTotals::default();
+        assert_eq!(empty.preview_deposit(0), Err(KernelError::InvalidAmount));
+        assert_eq!(empty.preview_redeem(1), Err(KernelError::NoShares));
+        assert_eq!(empty.share_price(), None);
+    }
+}
@@ -0,0 +1,123 @@
+//! A downstream program built against `vault_pda` as a library.
+//!
+//! It reads the vault's account types and forwards a deposit through the
+//! generated `vault_pda::cpi` client, the way an integrator's program would.
+//! Its only purpose is to prove the wiring: `vault-pda` is pulled in with
+//! `cpi` (and so `no-entrypoint`), leaving this crate's entrypoint as the only
+//! one linked. Both of these must build:
+//!
+//! ```bash
+//! # As a program, with its own entrypoint
+//! cargo build -p vault-pda-consumer
+//! # As a library for yet another program
+//! cargo build -p vault-pda-consumer --features no-entrypoint
+//! ```
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use vault_pda::program::VaultPda;
+use vault_pda::state::Vault;
+
+declare_id!("7GNYxWQA5FyjFSEJnyHWacBpmuswE2zPU2hnDacURabC");
+
+#[program]
+pub mod vault_pda_consumer {
+    use super::*;
+
+    /// Deposits the depositor's whole underlying balance into the vault
+    pub fn deposit_all(ctx: Context<DepositAll>) -> Result<()> {
+        let amount = ctx.accounts.depositor_underlying_account.amount;
+        require!(amount > 0, ConsumerError::NothingToDeposit);
+
+        let accounts = vault_pda::cpi::accounts::Deposit {
+            vault: ctx.accounts.vault.to_account_info(),
+            underlying_mint: ctx.accounts.underlying_mint.to_account_info(),
+            vault_token_account: ctx.accounts.vault_token_account.to_account_info(),
+            share_mint: ctx.accounts.share_mint.to_account_info(),
+            vault_authority: ctx.accounts.vault_authority.to_account_info(),
+            depositor_underlying_account: ctx
+                .accounts
+                .depositor_underlying_account
+                .to_account_info(),
+            depositor_share_account: ctx.accounts.depositor_share_account.to_account_info(),
+            depositor: ctx.accounts.depositor.to_account_info(),
+            flag: ctx.accounts.flag.to_account_info(),
+            player_record: ctx.accounts.player_record.to_account_info(),
+            activity_stats: ctx.accounts.activity_stats.to_account_info(),
+            reserve_config: ctx.accounts.reserve_config.to_account_info(),
+            treasury: ctx.accounts.treasury.as_ref().map(|t| t.to_account_info()),
+            protocol_state: ctx
+                .accounts
+                .protocol_state
+                .as_ref()
+                .map(|p| p.to_account_info()),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.vault_program.to_account_info(), accounts);
+        vault_pda::cpi::deposit(cpi_ctx, amount)
+    }
+}
+
+/// Accounts of `vault_pda::deposit`, plus the program itself
+///
+/// Only the vault is typed here; the vault program checks the rest.
+#[derive(Accounts)]
+pub struct DepositAll<'info> {
+    #[account(has_one = underlying_mint, has_one = share_mint)]
+    pub vault: Account<'info, Vault>,
+
+    pub underlying_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: checked by the vault program
+    #[account(mut)]
+    pub vault_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: checked against the vault above and by the vault program
+    #[account(mut)]
+    pub share_mint: UncheckedAccount<'info>,
+
+    /// CHECK: checked by the vault program
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(mut, token::mint = underlying_mint, token::authority = depositor)]
+    pub depositor_underlying_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: checked by the vault program
+    #[account(mut)]
+    pub depositor_share_account: UncheckedAccount<'info>,
+
+    pub depositor: Signer<'info>,
+
+    /// CHECK: checked by the vault program
+    #[account(mut)]
+    pub flag: UncheckedAccount<'info>,
+
+    /// CHECK: checked by the vault program
+    #[account(mut)]
+    pub player_record: UncheckedAccount<'info>,
+
+    /// CHECK: checked by the vault program
+    #[account(mut)]
+    pub activity_stats: UncheckedAccount<'info>,
+
+    /// CHECK: checked by the vault program
+    #[account(mut)]
+    pub reserve_config: UncheckedAccount<'info>,
+
+    /// CHECK: checked by the vault program
+    #[account(mut)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked by the vault program
+    pub protocol_state: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub vault_program: Program<'info, VaultPda>,
+}
+
+#[error_code]
+pub enum ConsumerError {
+    #[msg("The depositor holds no underlying tokens")]
+    NothingToDeposit,
+}
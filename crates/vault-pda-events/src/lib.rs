@@ -0,0 +1,533 @@
+//! The vault program's events, decodable without Anchor.
+//!
+//! Third-party indexers get the event structs, their discriminators and a
+//! schema registry, and need only `borsh` and `solana-pubkey`. The structs
+//! mirror `vault_pda::events` field for field; the tests check both the
+//! discriminators and the Borsh encoding against the program's own types.
+//!
+//! `emit!` logs an event as `Program data: <base64>`, the 8-byte
+//! discriminator followed by the Borsh encoding. `emit_cpi!` puts the same
+//! bytes, after [`EVENT_IX_TAG_LE`], in the data of a self-invocation.
+//!
+//! ```ignore
+//! for event in parse_logs(&vault_pda_id, &logs) {
+//!     if let VaultEvent::Deposit(deposit) = event { /* ... */ }
+//! }
+//! ```
+
+use std::fmt;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_pubkey::Pubkey;
+
+/// Prefix of a self-invocation's data carrying an `emit_cpi!` event
+pub const EVENT_IX_TAG_LE: [u8; 8] = 0x1d9acb512ea545e4u64.to_le_bytes();
+
+/// How a flag was captured
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WinCondition {
+    /// The captor owns the protocol without having planted the flag
+    OwnsProtocol,
+    /// The captor holds more underlying than when they registered
+    Profit,
+}
+
+/// Emitted after a successful deposit
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DepositEvent {
+    pub vault: Pubkey,
+    pub depositor: Pubkey,
+    /// Underlying tokens transferred into the vault
+    pub amount: u64,
+    /// Shares minted to the depositor
+    pub shares: u64,
+    /// Vault assets after the deposit
+    pub total_assets: u64,
+    /// Share supply after the deposit
+    pub total_shares: u64,
+}
+
+/// Emitted after a successful redeem
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RedeemEvent {
+    pub vault: Pubkey,
+    pub redeemer: Pubkey,
+    /// Shares burned from the redeemer; the withdrawal fee goes to the
+    /// treasury unburned
+    pub shares: u64,
+    /// Underlying tokens transferred out of the vault
+    pub amount: u64,
+    /// Vault assets after the redeem
+    pub total_assets: u64,
+    /// Share supply after the redeem
+    pub total_shares: u64,
+}
+
+/// Emitted when a player captures a vault's flag
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FlagCapturedEvent {
+    pub flag: Pubkey,
+    pub vault: Pubkey,
+    pub captor: Pubkey,
+    /// Slot of the capture
+    pub slot: u64,
+    /// Win condition the captor met
+    pub condition: WinCondition,
+}
+
+/// Emitted when a vault's profit is reported and split with its treasury
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReportEvent {
+    pub vault: Pubkey,
+    pub treasury: Pubkey,
+    /// Underlying the vault made since the last report
+    pub profit: u64,
+    /// The treasury's cut of the profit
+    pub reserve: u64,
+    /// Shares minted to the treasury for its cut
+    pub shares: u64,
+    /// Vault assets at the report
+    pub total_assets: u64,
+    /// Share supply once the treasury's shares are minted
+    pub total_shares: u64,
+}
+
+/// Emitted when a vault's profit is reported and the treasury's cut paid out
+/// in underlying
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SkimEvent {
+    pub vault: Pubkey,
+    /// The underlying account the cut was paid to
+    pub treasury: Pubkey,
+    /// Underlying the vault made since the last report
+    pub profit: u64,
+    /// The treasury's cut of the profit, paid out of the vault
+    pub reserve: u64,
+    /// Vault assets once the cut has left
+    pub total_assets: u64,
+    /// Share supply at the skim
+    pub total_shares: u64,
+}
+
+/// Emitted when a vault is created and whenever it is renamed
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultNamedEvent {
+    pub vault: Pubkey,
+    pub underlying_mint: Pubkey,
+    /// The new name, zero-padded UTF-8
+    pub name: [u8; 32],
+}
+
+impl VaultNamedEvent {
+    /// The name without its padding
+    pub fn name(&self) -> String {
+        let len = self
+            .name
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .map_or(0, |last| last + 1);
+        String::from_utf8_lossy(&self.name[..len]).into_owned()
+    }
+}
+
+/// An event and its discriminator, `sha256("event:<Name>")[..8]`
+pub trait Event: BorshDeserialize {
+    const NAME: &'static str;
+    const DISCRIMINATOR: [u8; 8];
+}
+
+impl Event for DepositEvent {
+    const NAME: &'static str = "DepositEvent";
+    const DISCRIMINATOR: [u8; 8] = [120, 248, 61, 83, 31, 142, 107, 144];
+}
+
+impl Event for RedeemEvent {
+    const NAME: &'static str = "RedeemEvent";
+    const DISCRIMINATOR: [u8; 8] = [90, 114, 83, 146, 212, 26, 217, 59];
+}
+
+impl Event for FlagCapturedEvent {
+    const NAME: &'static str = "FlagCapturedEvent";
+    const DISCRIMINATOR: [u8; 8] = [181, 91, 116, 156, 250, 121, 214, 30];
+}
+
+impl Event for ReportEvent {
+    const NAME: &'static str = "ReportEvent";
+    const DISCRIMINATOR: [u8; 8] = [213, 226, 237, 143, 58, 216, 185, 176];
+}
+
+impl Event for SkimEvent {
+    const NAME: &'static str = "SkimEvent";
+    const DISCRIMINATOR: [u8; 8] = [211, 175, 8, 213, 80, 183, 141, 214];
+}
+
+impl Event for VaultNamedEvent {
+    const NAME: &'static str = "VaultNamedEvent";
+    const DISCRIMINATOR: [u8; 8] = [174, 237, 0, 141, 252, 109, 10, 112];
+}
+
+/// An event's name, discriminator and fields in encoding order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventSchema {
+    pub name: &'static str,
+    pub discriminator: [u8; 8],
+    /// Field names and their Borsh types
+    pub fields: &'static [(&'static str, &'static str)],
+}
+
+/// Every event the program emits
+pub const EVENTS: &[EventSchema] = &[
+    EventSchema {
+        name: DepositEvent::NAME,
+        discriminator: DepositEvent::DISCRIMINATOR,
+        fields: &[
+            ("vault", "pubkey"),
+            ("depositor", "pubkey"),
+            ("amount", "u64"),
+            ("shares", "u64"),
+            ("total_assets", "u64"),
+            ("total_shares", "u64"),
+        ],
+    },
+    EventSchema {
+        name: RedeemEvent::NAME,
+        discriminator: RedeemEvent::DISCRIMINATOR,
+        fields: &[
+            ("vault", "pubkey"),
+            ("redeemer", "pubkey"),
+            ("shares", "u64"),
+            ("amount", "u64"),
+            ("total_assets", "u64"),
+            ("total_shares", "u64"),
+        ],
+    },
+    EventSchema {
+        name: FlagCapturedEvent::NAME,
+        discriminator: FlagCapturedEvent::DISCRIMINATOR,
+        fields: &[
+            ("flag", "pubkey"),
+            ("vault", "pubkey"),
+            ("captor", "pubkey"),
+            ("slot", "u64"),
+            ("condition", "enum WinCondition { OwnsProtocol, Profit }"),
+        ],
+    },
+    EventSchema {
+        name: ReportEvent::NAME,
+        discriminator: ReportEvent::DISCRIMINATOR,
+        fields: &[
+            ("vault", "pubkey"),
+            ("treasury", "pubkey"),
+            ("profit", "u64"),
+            ("reserve", "u64"),
+            ("shares", "u64"),
+            ("total_assets", "u64"),
+            ("total_shares", "u64"),
+        ],
+    },
+    EventSchema {
+        name: SkimEvent::NAME,
+        discriminator: SkimEvent::DISCRIMINATOR,
+        fields: &[
+            ("vault", "pubkey"),
+            ("treasury", "pubkey"),
+            ("profit", "u64"),
+            ("reserve", "u64"),
+            ("total_assets", "u64"),
+            ("total_shares", "u64"),
+        ],
+    },
+    EventSchema {
+        name: VaultNamedEvent::NAME,
+        discriminator: VaultNamedEvent::DISCRIMINATOR,
+        fields: &[
+            ("vault", "pubkey"),
+            ("underlying_mint", "pubkey"),
+            ("name", "[u8; 32]"),
+        ],
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VaultEvent {
+    Deposit(DepositEvent),
+    Redeem(RedeemEvent),
+    FlagCaptured(FlagCapturedEvent),
+    Report(ReportEvent),
+    Skim(SkimEvent),
+    VaultNamed(VaultNamedEvent),
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    /// Shorter than a discriminator
+    TooShort,
+    /// Not the discriminator of any event in [`EVENTS`]
+    UnknownDiscriminator([u8; 8]),
+    /// The discriminator matched but the data did not decode
+    Borsh(std::io::Error),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::TooShort => write!(f, "Event data is shorter than a discriminator"),
+            DecodeError::UnknownDiscriminator(d) => write!(f, "Unknown event discriminator {d:?}"),
+            DecodeError::Borsh(e) => write!(f, "Failed to decode event: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decodes an event from its discriminator and Borsh encoding
+pub fn decode_event(data: &[u8]) -> Result<VaultEvent, DecodeError> {
+    let (discriminator, data) = data.split_first_chunk::<8>().ok_or(DecodeError::TooShort)?;
+    match *discriminator {
+        DepositEvent::DISCRIMINATOR => decode(data).map(VaultEvent::Deposit),
+        RedeemEvent::DISCRIMINATOR => decode(data).map(VaultEvent::Redeem),
+        FlagCapturedEvent::DISCRIMINATOR => decode(data).map(VaultEvent::FlagCaptured),
+        ReportEvent::DISCRIMINATOR => decode(data).map(VaultEvent::Report),
+        SkimEvent::DISCRIMINATOR => decode(data).map(VaultEvent::Skim),
+        VaultNamedEvent::DISCRIMINATOR => decode(data).map(VaultEvent::VaultNamed),
+        _ => Err(DecodeError::UnknownDiscriminator(*discriminator)),
+    }
+}
+
+/// Decodes an `emit_cpi!` event from the data of the program's
+/// self-invocation, or `None` if the instruction is not one
+pub fn decode_cpi_event(ix_data: &[u8]) -> Option<Result<VaultEvent, DecodeError>> {
+    ix_data.strip_prefix(&EVENT_IX_TAG_LE).map(decode_event)
+}
+
+/// Every event `program_id` emitted in a transaction's logs, in order
+///
+/// Only `Program data:` lines logged while `program_id` itself is executing
+/// are decoded, so a program it invokes (or that invokes it) cannot forge
+/// events by logging the same bytes. Lines that do not decode are skipped.
+pub fn parse_logs(program_id: &Pubkey, logs: &[String]) -> Vec<VaultEvent> {
+    let program_id = program_id.to_string();
+    // Whether each program on the invocation stack is `program_id`
+    let mut stack = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let mut words = line.split_whitespace();
+        if words.next() != Some("Program") {
+            continue;
+        }
+        match (words.next(), words.next()) {
+            (Some("data:"), Some(data)) if stack.last() == Some(&true) => {
+                if let Some(event) = STANDARD
+                    .decode(data)
+                    .ok()
+                    .and_then(|data| decode_event(&data).ok())
+                {
+                    events.push(event);
+                }
+            }
+            (Some(program), Some("invoke")) => stack.push(program == program_id),
+            (Some(_), Some(outcome)) if outcome == "success" || outcome == "failed:" => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+fn decode<T: BorshDeserialize>(data: &[u8]) -> Result<T, DecodeError> {
+    T::try_from_slice(data).map_err(DecodeError::Borsh)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{Discriminator, Event as _};
+    use vault_pda::events as program;
+
+    use super::*;
+
+    fn pubkey() -> Pubkey {
+        Pubkey::new_unique()
+    }
+
+    fn key(pubkey: &Pubkey) -> anchor_lang::prelude::Pubkey {
+        anchor_lang::prelude::Pubkey::new_from_array(pubkey.to_bytes())
+    }
+
+    #[test]
+    fn matches_the_programs_events() {
+        assert_eq!(
+            DepositEvent::DISCRIMINATOR,
+            program::DepositEvent::DISCRIMINATOR
+        );
+        assert_eq!(
+            RedeemEvent::DISCRIMINATOR,
+            program::RedeemEvent::DISCRIMINATOR
+        );
+        assert_eq!(
+            FlagCapturedEvent::DISCRIMINATOR,
+            program::FlagCapturedEvent::DISCRIMINATOR
+        );
+        assert_eq!(
+            ReportEvent::DISCRIMINATOR,
+            program::ReportEvent::DISCRIMINATOR
+        );
+        assert_eq!(SkimEvent::DISCRIMINATOR, program::SkimEvent::DISCRIMINATOR);
+        assert_eq!(
+            VaultNamedEvent::DISCRIMINATOR,
+            program::VaultNamedEvent::DISCRIMINATOR
+        );
+        assert_eq!(EVENT_IX_TAG_LE, anchor_lang::event::EVENT_IX_TAG_LE);
+
+        let deposit = DepositEvent {
+            vault: pubkey(),
+            depositor: pubkey(),
+            amount: 1_000,
+            shares: 990,
+            total_assets: 5_000,
+            total_shares: 4_950,
+        };
+        let emitted = program::DepositEvent {
+            vault: key(&deposit.vault),
+            depositor: key(&deposit.depositor),
+            amount: deposit.amount,
+            shares: deposit.shares,
+            total_assets: deposit.total_assets,
+            total_shares: deposit.total_shares,
+        };
+        assert_eq!(
+            decode_event(&emitted.data()).unwrap(),
+            VaultEvent::Deposit(deposit)
+        );
+
+        let captured = FlagCapturedEvent {
+            flag: pubkey(),
+            vault: pubkey(),
+            captor: pubkey(),
+            slot: 42,
+            condition: WinCondition::Profit,
+        };
+        let emitted = program::FlagCapturedEvent {
+            flag: key(&captured.flag),
+            vault: key(&captured.vault),
+            captor: key(&captured.captor),
+            slot: captured.slot,
+            condition: vault_pda::state::WinCondition::Profit,
+        };
+        let mut ix_data = EVENT_IX_TAG_LE.to_vec();
+        ix_data.extend(emitted.data());
+        assert_eq!(
+            decode_cpi_event(&ix_data).unwrap().unwrap(),
+            VaultEvent::FlagCaptured(captured)
+        );
+
+        let report = ReportEvent {
+            vault: pubkey(),
+            treasury: pubkey(),
+            profit: 1_000,
+            reserve: 100,
+            shares: 52,
+            total_assets: 2_000,
+            total_shares: 1_052,
+        };
+        let emitted = program::ReportEvent {
+            vault: key(&report.vault),
+            treasury: key(&report.treasury),
+            profit: report.profit,
+            reserve: report.reserve,
+            shares: report.shares,
+            total_assets: report.total_assets,
+            total_shares: report.total_shares,
+        };
+        assert_eq!(
+            decode_event(&emitted.data()).unwrap(),
+            VaultEvent::Report(report)
+        );
+
+        let skim = SkimEvent {
+            vault: pubkey(),
+            treasury: pubkey(),
+            profit: 1_000,
+            reserve: 100,
+            total_assets: 1_900,
+            total_shares: 1_000,
+        };
+        let emitted = program::SkimEvent {
+            vault: key(&skim.vault),
+            treasury: key(&skim.treasury),
+            profit: skim.profit,
+            reserve: skim.reserve,
+            total_assets: skim.total_assets,
+            total_shares: skim.total_shares,
+        };
+        assert_eq!(
+            decode_event(&emitted.data()).unwrap(),
+            VaultEvent::Skim(skim)
+        );
+
+        let named = VaultNamedEvent {
+            vault: pubkey(),
+            underlying_mint: pubkey(),
+            name: vault_pda::state::Vault::encode_name("USDC vault").unwrap(),
+        };
+        let emitted = program::VaultNamedEvent {
+            vault: key(&named.vault),
+            underlying_mint: key(&named.underlying_mint),
+            name: named.name,
+        };
+        let decoded = decode_event(&emitted.data()).unwrap();
+        assert!(matches!(&decoded, VaultEvent::VaultNamed(e) if e.name() == "USDC vault"));
+        assert_eq!(decoded, VaultEvent::VaultNamed(named));
+
+        assert!(matches!(decode_event(&[1, 2]), Err(DecodeError::TooShort)));
+        assert!(matches!(
+            decode_event(&[0; 8]),
+            Err(DecodeError::UnknownDiscriminator(_))
+        ));
+        assert!(matches!(
+            decode_event(&DepositEvent::DISCRIMINATOR),
+            Err(DecodeError::Borsh(_))
+        ));
+        assert!(decode_cpi_event(&DepositEvent::DISCRIMINATOR).is_none());
+    }
+
+    #[test]
+    fn parses_only_the_programs_log_lines() {
+        let program_id = pubkey();
+        let other = pubkey();
+        let data = |amount: u64| {
+            let mut data = DepositEvent::DISCRIMINATOR.to_vec();
+            DepositEvent {
+                vault: program_id,
+                depositor: other,
+                amount,
+                shares: amount,
+                total_assets: amount,
+                total_shares: amount,
+            }
+            .serialize(&mut data)
+            .unwrap();
+            format!("Program data: {}", STANDARD.encode(data))
+        };
+        let logs = vec![
+            format!("Program {program_id} invoke [1]"),
+            "Program log: Instruction: Deposit".to_string(),
+            format!("Program {other} invoke [2]"),
+            data(666),
+            format!("Program {other} success"),
+            data(1_000),
+            format!("Program {program_id} consumed 30000 of 200000 compute units"),
+            format!("Program {program_id} success"),
+            format!("Program {other} invoke [1]"),
+            data(777),
+            format!("Program {other} success"),
+        ];
+
+        let events = parse_logs(&program_id, &logs);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], VaultEvent::Deposit(e) if e.amount == 1_000));
+    }
+}
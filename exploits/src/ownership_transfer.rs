@@ -0,0 +1,43 @@
+//! Unauthorized ownership transfer.
+//!
+//! `transfer_ownership` compares `current_owner` with the stored owner but
+//! never requires it to sign, so anyone who knows the owner's pubkey (it is
+//! public) can hand the protocol to themselves.
+
+use fuzz_helpers::fuzz_core::sequence::Sender;
+use fuzz_helpers::*;
+
+use crate::{FuzzResult, Verdict};
+
+/// Whether this build includes the code path the exploit targets
+/// (`vuln-ownership`)
+pub const ENABLED: bool = vault_pda::VULN_OWNERSHIP;
+
+/// An outsider transfers the protocol to themselves
+pub async fn run() -> FuzzResult<Verdict> {
+    let (mut env, setup) = setup_complete_environment(0, 6, TokenFlavor::SplToken).await?;
+    let attacker = Attacker::new(&mut env.context, &setup.underlying, &setup.vault, 0, 0).await?;
+    let mut sender = Sender::default();
+
+    // Only the attacker signs; the owner is passed by pubkey alone, even
+    // where the build marks it as a signer
+    let mut ix = transfer_ownership_ix(&env.program_id, &setup.protocol.owner, &attacker.pubkey());
+    ix.accounts[1].is_signer = false;
+    let result = attacker.send(&mut env, &mut sender, ix).await?;
+
+    let owner = get_protocol_state(&mut env.context, &setup.protocol.protocol_state)
+        .await?
+        .owner;
+    Ok(match result {
+        Ok(()) if owner == attacker.pubkey() => Verdict::Vulnerable(format!(
+            "{} took the protocol from {} without its signature",
+            attacker.pubkey(),
+            setup.protocol.owner
+        )),
+        Ok(()) => Verdict::Patched(format!(
+            "transfer succeeded but the owner is still {}",
+            owner
+        )),
+        Err(e) => Verdict::Patched(format!("transfer rejected: {:?}", e)),
+    })
+}
@@ -0,0 +1,32 @@
+//! Runs every exploit and checks its verdict.
+//!
+//! An exploit must work while its `vuln-*` feature is enabled and be stopped
+//! once it is not. Set `EXPLOITS_EXPECT=vulnerable` or `patched` to require
+//! one verdict from every exploit instead.
+//!
+//! Requires the program to be built first, with the same features:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-exploits -- --nocapture
+//! ```
+
+use vault_exploits::*;
+
+#[tokio::test]
+async fn ownership_transfer() {
+    let verdict = ownership_transfer::run().await.unwrap();
+    assert_expected("ownership_transfer", ownership_transfer::ENABLED, &verdict);
+}
+
+#[tokio::test]
+async fn donation_inflation() {
+    let verdict = donation_inflation::run().await.unwrap();
+    assert_expected("donation_inflation", donation_inflation::ENABLED, &verdict);
+}
+
+#[tokio::test]
+async fn cross_vault_mint() {
+    let verdict = cross_vault_mint::run().await.unwrap();
+    assert_expected("cross_vault_mint", cross_vault_mint::ENABLED, &verdict);
+}
@@ -0,0 +1,172 @@
+//! Adversarial participant shared by fuzz targets and exploit PoCs.
+//!
+//! An [`Attacker`] is a funded keypair with a position in one vault (an
+//! underlying and a share account) and a spare underlying account it donates
+//! from, so donations never blur with the position's own balance. Its methods
+//! are the attack primitives targets kept rebuilding: donating straight into
+//! the vault, front-running a pending instruction, and rewriting the accounts
+//! of someone else's instruction.
+
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use crate::fuzz_core::sequence::Sender;
+use crate::*;
+
+/// An adversarial user of one vault
+#[derive(Debug, Clone)]
+pub struct Attacker {
+    /// The attacker's position; `position.owner` is the attacker's keypair
+    pub position: UserAccounts,
+    /// Second underlying account owned by the attacker, used for donations
+    pub spare: Pubkey,
+    underlying: UnderlyingMintAccounts,
+}
+
+/// Outcome of [`Attacker::front_run`]
+#[derive(Debug)]
+pub struct FrontRun {
+    /// The attacker's transaction, processed first
+    pub attack: Result<(), BanksClientError>,
+    /// The pending transaction, processed right after
+    pub pending: Result<(), BanksClientError>,
+}
+
+impl Attacker {
+    /// Creates a funded attacker with a position in `vault` holding `balance`
+    /// underlying tokens and a spare account holding `spare_balance`
+    pub async fn new(
+        context: &mut ProgramTestContext,
+        underlying: &UnderlyingMintAccounts,
+        vault: &VaultAccounts,
+        balance: u64,
+        spare_balance: u64,
+    ) -> FuzzResult<Self> {
+        let position =
+            create_and_fund_user(context, underlying, &vault.share_mint, balance).await?;
+        let spare = setup_token_account(
+            context,
+            &underlying.mint,
+            &position.owner.pubkey(),
+            underlying.flavor,
+        )
+        .await?;
+        if spare_balance > 0 {
+            mint_tokens_to_user(
+                context,
+                &underlying.mint,
+                &underlying.mint_authority,
+                &spare,
+                spare_balance,
+            )
+            .await?;
+        }
+
+        Ok(Self {
+            position,
+            spare,
+            underlying: underlying.clone(),
+        })
+    }
+
+    pub fn keypair(&self) -> &Keypair {
+        &self.position.owner
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.position.owner.pubkey()
+    }
+
+    /// Creates another token account owned by the attacker, e.g. for a mint
+    /// of another vault
+    pub async fn token_account(
+        &self,
+        context: &mut ProgramTestContext,
+        mint: &Pubkey,
+        flavor: TokenFlavor,
+    ) -> FuzzResult<Pubkey> {
+        setup_token_account(context, mint, &self.pubkey(), flavor).await
+    }
+
+    /// Sends `ix`, signed and paid for by the attacker
+    pub async fn send(
+        &self,
+        env: &mut FuzzTestEnv,
+        sender: &mut Sender,
+        ix: Instruction,
+    ) -> FuzzResult<Result<(), BanksClientError>> {
+        sender.send(env, ix, self.keypair()).await
+    }
+
+    /// Transfer of `amount` from the spare account straight into `vault`'s
+    /// token account, bypassing deposit
+    pub fn donate_ix(&self, vault: &VaultAccounts, amount: u64) -> FuzzResult<Instruction> {
+        Ok(spl_token_2022::instruction::transfer_checked(
+            &vault.token_program,
+            &self.spare,
+            &self.underlying.mint,
+            &vault.vault_token_account,
+            &self.pubkey(),
+            &[],
+            amount,
+            self.underlying.decimals,
+        )?)
+    }
+
+    /// Donates `amount` from the spare account into `vault`
+    pub async fn donate(
+        &self,
+        env: &mut FuzzTestEnv,
+        sender: &mut Sender,
+        vault: &VaultAccounts,
+        amount: u64,
+    ) -> FuzzResult<Result<(), BanksClientError>> {
+        let ix = self.donate_ix(vault, amount)?;
+        self.send(env, sender, ix).await
+    }
+
+    /// Sends `ix` as the attacker ahead of `pending`, which `pending_signers`
+    /// sign (the first one pays), as if it had jumped the queue
+    pub async fn front_run(
+        &self,
+        env: &mut FuzzTestEnv,
+        sender: &mut Sender,
+        ix: Instruction,
+        pending: Instruction,
+        pending_signers: &[&Keypair],
+    ) -> FuzzResult<FrontRun> {
+        let attack = self.send(env, sender, ix).await?;
+        let pending = sender
+            .send_with_signers(env, pending, pending_signers)
+            .await?;
+        Ok(FrontRun { attack, pending })
+    }
+
+    /// Copy of `ix` with every occurrence of `original` replaced by
+    /// `replacement`, keeping the signer and writable flags
+    pub fn substitute_account(
+        ix: &Instruction,
+        original: &Pubkey,
+        replacement: Pubkey,
+    ) -> Instruction {
+        let mut ix = ix.clone();
+        for meta in ix
+            .accounts
+            .iter_mut()
+            .filter(|meta| meta.pubkey == *original)
+        {
+            meta.pubkey = replacement;
+        }
+        ix
+    }
+
+    /// Copy of `ix` with `victim` replaced by the attacker, so the attacker
+    /// can sign where the victim was expected to
+    pub fn impersonate(&self, ix: &Instruction, victim: &Pubkey) -> Instruction {
+        Self::substitute_account(ix, victim, self.pubkey())
+    }
+}
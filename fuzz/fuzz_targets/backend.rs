@@ -0,0 +1,563 @@
+//! Execution backends.
+//!
+//! `FuzzBackend` covers the handful of operations a scenario needs (send a
+//! transaction, read and write accounts), so the same scenario can run on
+//! `solana-program-test` or, with the `litesvm` feature, on LiteSVM. LiteSVM
+//! executes in-process without a banks server and is an order of magnitude
+//! faster per transaction.
+
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    instruction::Instruction,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use spl_token::state as token_state;
+use spl_token_2022::extension::{
+    immutable_owner::ImmutableOwner, BaseStateWithExtensionsMut, ExtensionType,
+    StateWithExtensionsMut,
+};
+use spl_token_2022::state as token_2022_state;
+
+use crate::{
+    deposit_ix, derive_protocol_state_pda, derive_share_mint_pda, derive_vault_authority_pda,
+    derive_vault_pda, derive_vault_token_account_pda, initialize_ix, initialize_vault_ix,
+    new_address, new_keypair, redeem_ix, CompleteSetup, EdgeAccountState, EdgeTokenAccount,
+    FuzzResult, ProgramTestContext, ProtocolAccounts, TokenFlavor, UnderlyingMintAccounts,
+    UserAccounts, VaultAccounts,
+};
+
+/// Where transactions execute and accounts live
+#[allow(async_fn_in_trait)]
+pub trait FuzzBackend {
+    /// Funded keypair that pays for setup
+    fn payer(&self) -> &Keypair;
+
+    /// Blockhash to sign new transactions with
+    async fn latest_blockhash(&mut self) -> FuzzResult<Hash>;
+
+    /// Executes `tx`, failing if the transaction fails
+    async fn process_transaction(&mut self, tx: Transaction) -> FuzzResult<()>;
+
+    async fn get_account(&mut self, address: &Pubkey) -> FuzzResult<Option<Account>>;
+
+    /// Overwrites `address` without a transaction
+    fn store_account(&mut self, address: &Pubkey, account: Account) -> FuzzResult<()>;
+
+    /// Rent-exempt minimum for `len` bytes of data
+    async fn minimum_balance(&mut self, len: usize) -> FuzzResult<u64>;
+}
+
+impl FuzzBackend for ProgramTestContext {
+    fn payer(&self) -> &Keypair {
+        &self.payer
+    }
+
+    async fn latest_blockhash(&mut self) -> FuzzResult<Hash> {
+        Ok(self.last_blockhash)
+    }
+
+    async fn process_transaction(&mut self, tx: Transaction) -> FuzzResult<()> {
+        Ok(self.banks_client.process_transaction(tx).await?)
+    }
+
+    async fn get_account(&mut self, address: &Pubkey) -> FuzzResult<Option<Account>> {
+        Ok(self.banks_client.get_account(*address).await?)
+    }
+
+    fn store_account(&mut self, address: &Pubkey, account: Account) -> FuzzResult<()> {
+        self.set_account(address, &account.into());
+        Ok(())
+    }
+
+    async fn minimum_balance(&mut self, len: usize) -> FuzzResult<u64> {
+        Ok(self.banks_client.get_rent().await?.minimum_balance(len))
+    }
+}
+
+/// LiteSVM with the vault program loaded
+#[cfg(feature = "litesvm")]
+pub struct LiteSvmBackend {
+    pub svm: litesvm::LiteSVM,
+    pub payer: Keypair,
+}
+
+#[cfg(feature = "litesvm")]
+impl LiteSvmBackend {
+    /// Loads the program (`vault_pda.so`, see `PROGRAM_NAME`) from
+    /// `SBF_OUT_DIR` (`target/deploy` by default), like `setup_program_test`
+    /// does
+    pub fn new() -> FuzzResult<Self> {
+        let mut svm = litesvm::LiteSVM::new();
+        let out_dir = std::env::var("SBF_OUT_DIR").unwrap_or_else(|_| "target/deploy".into());
+        svm.add_program_from_file(
+            vault_pda::id(),
+            std::path::Path::new(&out_dir).join(format!("{}.so", crate::PROGRAM_NAME)),
+        )?;
+
+        let payer = Keypair::new();
+        svm.airdrop(&payer.pubkey(), 1_000_000_000_000)
+            .map_err(|e| format!("Airdrop failed: {:?}", e.err))?;
+
+        Ok(Self { svm, payer })
+    }
+}
+
+#[cfg(feature = "litesvm")]
+impl FuzzBackend for LiteSvmBackend {
+    fn payer(&self) -> &Keypair {
+        &self.payer
+    }
+
+    async fn latest_blockhash(&mut self) -> FuzzResult<Hash> {
+        Ok(self.svm.latest_blockhash())
+    }
+
+    async fn process_transaction(&mut self, tx: Transaction) -> FuzzResult<()> {
+        self.svm
+            .send_transaction(tx)
+            .map(|_| ())
+            .map_err(|e| Box::new(e.err) as _)
+    }
+
+    async fn get_account(&mut self, address: &Pubkey) -> FuzzResult<Option<Account>> {
+        Ok(self.svm.get_account(address))
+    }
+
+    fn store_account(&mut self, address: &Pubkey, account: Account) -> FuzzResult<()> {
+        Ok(self.svm.set_account(*address, account)?)
+    }
+
+    async fn minimum_balance(&mut self, len: usize) -> FuzzResult<u64> {
+        Ok(self.svm.minimum_balance_for_rent_exemption(len))
+    }
+}
+
+// ============================================================================
+// Backend-generic Helpers
+// ============================================================================
+
+/// Signs `instructions` with `signers` (the first one pays) and executes them
+pub async fn send<B: FuzzBackend>(
+    backend: &mut B,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> FuzzResult<()> {
+    let blockhash = backend.latest_blockhash().await?;
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&signers[0].pubkey()),
+        signers,
+        blockhash,
+    );
+    backend.process_transaction(tx).await
+}
+
+/// Gives `address` a system account holding 1 SOL on top of rent
+pub async fn fund_account<B: FuzzBackend>(backend: &mut B, address: &Pubkey) -> FuzzResult<()> {
+    let lamports = backend.minimum_balance(0).await? + 1_000_000_000; // 1 SOL
+    fund_lamports(backend, address, lamports).await
+}
+
+/// Adds `lamports` to `address`, creating a system account if there is none
+///
+/// Writes the balance directly instead of sending a system transfer, so no
+/// payer is needed.
+pub async fn fund_lamports<B: FuzzBackend>(
+    backend: &mut B,
+    address: &Pubkey,
+    lamports: u64,
+) -> FuzzResult<()> {
+    let account = match backend.get_account(address).await? {
+        Some(mut account) => {
+            account.lamports = account
+                .lamports
+                .checked_add(lamports)
+                .ok_or("Lamport balance overflow")?;
+            account
+        }
+        None => Account::new(lamports, 0, &solana_sdk::system_program::ID),
+    };
+    backend.store_account(address, account)
+}
+
+/// Stores packed SPL Token state at `address`, rent-exempt and owned by the
+/// token program, without sending a transaction
+pub async fn seed_packed_account<B: FuzzBackend, T: Pack>(
+    backend: &mut B,
+    address: &Pubkey,
+    state: T,
+) -> FuzzResult<()> {
+    seed_packed_account_for(backend, TokenFlavor::SplToken, address, state).await
+}
+
+/// Stores packed token state at `address`, owned by `flavor`'s program
+///
+/// The base layouts are shared, so the state is a valid Token-2022 account
+/// without extensions as well.
+pub async fn seed_packed_account_for<B: FuzzBackend, T: Pack>(
+    backend: &mut B,
+    flavor: TokenFlavor,
+    address: &Pubkey,
+    state: T,
+) -> FuzzResult<()> {
+    let mut data = vec![0; T::LEN];
+    T::pack(state, &mut data)?;
+    let account = Account {
+        lamports: backend.minimum_balance(T::LEN).await?,
+        data,
+        owner: flavor.program_id(),
+        executable: false,
+        rent_epoch: 0,
+    };
+    backend.store_account(address, account)
+}
+
+/// Token program owning the account at `address`
+async fn flavor_of<B: FuzzBackend>(backend: &mut B, address: &Pubkey) -> FuzzResult<TokenFlavor> {
+    let account = backend
+        .get_account(address)
+        .await?
+        .ok_or("Token account not found")?;
+    TokenFlavor::from_program_id(&account.owner)
+        .ok_or_else(|| "Account not owned by a token program".into())
+}
+
+/// Reads packed token state stored at `address`
+///
+/// Fails on Token-2022 accounts carrying extensions, whose data is longer
+/// than the base layout.
+pub async fn get_packed_account<B: FuzzBackend, T: Pack>(
+    backend: &mut B,
+    address: &Pubkey,
+) -> FuzzResult<T> {
+    let account = backend
+        .get_account(address)
+        .await?
+        .ok_or("Token account not found")?;
+    if TokenFlavor::from_program_id(&account.owner).is_none() {
+        return Err("Account not owned by a token program".into());
+    }
+    Ok(T::unpack(&account.data)?)
+}
+
+/// Seeds a new mint under `flavor` with `mint_authority` as its authority
+pub async fn create_mint<B: FuzzBackend>(
+    backend: &mut B,
+    decimals: u8,
+    flavor: TokenFlavor,
+) -> FuzzResult<UnderlyingMintAccounts> {
+    let mint_authority = new_keypair();
+    let mint = new_address();
+
+    let state = token_state::Mint {
+        mint_authority: COption::Some(mint_authority.pubkey()),
+        supply: 0,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    seed_packed_account_for(backend, flavor, &mint, state).await?;
+
+    Ok(UnderlyingMintAccounts {
+        mint,
+        mint_authority,
+        decimals,
+        flavor,
+    })
+}
+
+/// Seeds an empty token account for `mint` owned by `owner`, under `flavor`
+pub async fn create_token_account<B: FuzzBackend>(
+    backend: &mut B,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    flavor: TokenFlavor,
+) -> FuzzResult<Pubkey> {
+    let token_account = new_address();
+
+    let state = token_state::Account {
+        mint: *mint,
+        owner: *owner,
+        state: token_state::AccountState::Initialized,
+        ..Default::default()
+    };
+    seed_packed_account_for(backend, flavor, &token_account, state).await?;
+
+    Ok(token_account)
+}
+
+/// Seeds a token account owned by `owner` in the edge state `state`, under
+/// `flavor`
+///
+/// Wrapped SOL states belong to `flavor`'s native mint, which is seeded too
+/// if it does not exist yet.
+pub async fn create_edge_token_account<B: FuzzBackend>(
+    backend: &mut B,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    flavor: TokenFlavor,
+    state: EdgeAccountState,
+) -> FuzzResult<EdgeTokenAccount> {
+    let token_account = new_address();
+    let mint = if state.is_native() {
+        create_native_mint(backend, flavor).await?
+    } else {
+        *mint
+    };
+    let mut account = token_state::Account {
+        mint,
+        owner: *owner,
+        state: token_state::AccountState::Initialized,
+        ..Default::default()
+    };
+    let mut authority = None;
+
+    match state {
+        EdgeAccountState::Delegated { delegated_amount } => {
+            let delegate = new_keypair();
+            account.delegate = COption::Some(delegate.pubkey());
+            account.delegated_amount = delegated_amount;
+            authority = Some(delegate);
+        }
+        EdgeAccountState::CloseAuthority => {
+            let close_authority = new_keypair();
+            account.close_authority = COption::Some(close_authority.pubkey());
+            authority = Some(close_authority);
+        }
+        EdgeAccountState::ImmutableOwner if flavor == TokenFlavor::Token2022 => {
+            seed_immutable_owner_account(backend, &token_account, account).await?;
+            return Ok(EdgeTokenAccount {
+                address: token_account,
+                mint,
+                authority,
+            });
+        }
+        EdgeAccountState::ImmutableOwner => {}
+        EdgeAccountState::Native { lamports } => {
+            account.is_native =
+                COption::Some(backend.minimum_balance(token_state::Account::LEN).await?);
+            account.amount = lamports as u64;
+        }
+        EdgeAccountState::ZeroRentExcess => {
+            account.is_native =
+                COption::Some(backend.minimum_balance(token_state::Account::LEN).await?);
+        }
+    }
+
+    let wrapped = account.amount;
+    seed_packed_account_for(backend, flavor, &token_account, account).await?;
+    if state.is_native() && wrapped > 0 {
+        // A native account's balance is its lamports above the reserve
+        fund_lamports(backend, &token_account, wrapped).await?;
+    }
+
+    Ok(EdgeTokenAccount {
+        address: token_account,
+        mint,
+        authority,
+    })
+}
+
+/// Seeds `flavor`'s native mint unless it already exists, returning its address
+async fn create_native_mint<B: FuzzBackend>(
+    backend: &mut B,
+    flavor: TokenFlavor,
+) -> FuzzResult<Pubkey> {
+    let mint = flavor.native_mint();
+    if backend.get_account(&mint).await?.is_none() {
+        let state = token_state::Mint {
+            mint_authority: COption::None,
+            supply: 0,
+            decimals: spl_token::native_mint::DECIMALS,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        seed_packed_account_for(backend, flavor, &mint, state).await?;
+    }
+    Ok(mint)
+}
+
+/// Stores `account` at `address` as a Token-2022 account carrying the
+/// `ImmutableOwner` extension
+async fn seed_immutable_owner_account<B: FuzzBackend>(
+    backend: &mut B,
+    address: &Pubkey,
+    account: token_state::Account,
+) -> FuzzResult<()> {
+    let len = ExtensionType::try_calculate_account_len::<token_2022_state::Account>(&[
+        ExtensionType::ImmutableOwner,
+    ])?;
+    let mut data = vec![0; len];
+    let mut state =
+        StateWithExtensionsMut::<token_2022_state::Account>::unpack_uninitialized(&mut data)?;
+    state.init_extension::<ImmutableOwner>(true)?;
+    // The base layouts are shared, so the SPL Token state packs as is
+    let mut base = vec![0; token_state::Account::LEN];
+    token_state::Account::pack(account, &mut base)?;
+    state.base = token_2022_state::Account::unpack_unchecked(&base)?;
+    state.pack_base();
+    state.init_account_type()?;
+
+    let account = Account {
+        lamports: backend.minimum_balance(len).await?,
+        data,
+        owner: TokenFlavor::Token2022.program_id(),
+        executable: false,
+        rent_epoch: 0,
+    };
+    backend.store_account(address, account)
+}
+
+/// Writes a `mint_to` of `amount` directly, with the checks `mint_to` makes
+///
+/// Both accounts keep the token program that owns them.
+pub async fn mint_to<B: FuzzBackend>(
+    backend: &mut B,
+    mint: &Pubkey,
+    mint_authority: &Keypair,
+    destination: &Pubkey,
+    amount: u64,
+) -> FuzzResult<()> {
+    let mint_flavor = flavor_of(backend, mint).await?;
+    let account_flavor = flavor_of(backend, destination).await?;
+    let mut mint_state: token_state::Mint = get_packed_account(backend, mint).await?;
+    let mut account_state: token_state::Account = get_packed_account(backend, destination).await?;
+
+    if mint_state.mint_authority != COption::Some(mint_authority.pubkey()) {
+        return Err("Mint authority mismatch".into());
+    }
+    if account_state.mint != *mint {
+        return Err("Destination belongs to another mint".into());
+    }
+    if account_state.is_frozen() {
+        return Err("Destination is frozen".into());
+    }
+
+    mint_state.supply = mint_state
+        .supply
+        .checked_add(amount)
+        .ok_or("Mint supply overflow")?;
+    account_state.amount = account_state
+        .amount
+        .checked_add(amount)
+        .ok_or("Token balance overflow")?;
+
+    seed_packed_account_for(backend, mint_flavor, mint, mint_state).await?;
+    seed_packed_account_for(backend, account_flavor, destination, account_state).await
+}
+
+/// Token balance of `address`
+pub async fn token_balance<B: FuzzBackend>(backend: &mut B, address: &Pubkey) -> FuzzResult<u64> {
+    let state: token_state::Account = get_packed_account(backend, address).await?;
+    Ok(state.amount)
+}
+
+/// Supply of `mint`
+pub async fn mint_supply<B: FuzzBackend>(backend: &mut B, mint: &Pubkey) -> FuzzResult<u64> {
+    let state: token_state::Mint = get_packed_account(backend, mint).await?;
+    Ok(state.supply)
+}
+
+// ============================================================================
+// Scenarios
+// ============================================================================
+
+/// Same accounts as `setup_complete_environment`, on any backend
+pub async fn setup_complete_on<B: FuzzBackend>(
+    backend: &mut B,
+    program_id: &Pubkey,
+    initial_user_balance: u64,
+    decimals: u8,
+) -> FuzzResult<CompleteSetup> {
+    let owner_keypair = new_keypair();
+    let owner = owner_keypair.pubkey();
+    fund_account(backend, &owner).await?;
+    send(
+        backend,
+        &[initialize_ix(program_id, &owner)],
+        &[&owner_keypair],
+    )
+    .await?;
+    let protocol = ProtocolAccounts {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        vault_authority: derive_vault_authority_pda(program_id).0,
+        owner,
+        owner_keypair,
+    };
+
+    let underlying = create_mint(backend, decimals, TokenFlavor::SplToken).await?;
+    let ix = initialize_vault_ix(program_id, &underlying.mint, &owner);
+    send(backend, &[ix], &[&protocol.owner_keypair]).await?;
+    let (vault, _) = derive_vault_pda(program_id, &underlying.mint);
+    let vault = VaultAccounts {
+        vault,
+        vault_token_account: derive_vault_token_account_pda(program_id, &vault).0,
+        share_mint: derive_share_mint_pda(program_id, &vault).0,
+        underlying_mint: underlying.mint,
+        token_program: spl_token::id(),
+    };
+
+    let user_owner = new_keypair();
+    fund_account(backend, &user_owner.pubkey()).await?;
+    let user = UserAccounts {
+        underlying_token_account: create_token_account(
+            backend,
+            &underlying.mint,
+            &user_owner.pubkey(),
+            TokenFlavor::SplToken,
+        )
+        .await?,
+        share_token_account: create_token_account(
+            backend,
+            &vault.share_mint,
+            &user_owner.pubkey(),
+            TokenFlavor::SplToken,
+        )
+        .await?,
+        owner: user_owner,
+    };
+    if initial_user_balance > 0 {
+        mint_to(
+            backend,
+            &underlying.mint,
+            &underlying.mint_authority,
+            &user.underlying_token_account,
+            initial_user_balance,
+        )
+        .await?;
+    }
+
+    Ok(CompleteSetup {
+        protocol,
+        underlying,
+        vault,
+        user,
+    })
+}
+
+/// Deposits `amount`, redeems every share minted for it, and returns the
+/// shares minted and the assets paid back
+pub async fn deposit_redeem_round_trip<B: FuzzBackend>(
+    backend: &mut B,
+    program_id: &Pubkey,
+    setup: &CompleteSetup,
+    amount: u64,
+) -> FuzzResult<(u64, u64)> {
+    let user = &setup.user;
+    let shares_before = token_balance(backend, &user.share_token_account).await?;
+    let ix = deposit_ix(program_id, &setup.vault, user, amount);
+    send(backend, &[ix], &[&user.owner]).await?;
+    let shares = token_balance(backend, &user.share_token_account).await? - shares_before;
+
+    let underlying_before = token_balance(backend, &user.underlying_token_account).await?;
+    let ix = redeem_ix(program_id, &setup.vault, user, shares);
+    send(backend, &[ix], &[&user.owner]).await?;
+    let assets = token_balance(backend, &user.underlying_token_account).await? - underlying_before;
+
+    Ok((shares, assets))
+}
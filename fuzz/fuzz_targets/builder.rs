@@ -0,0 +1,247 @@
+//! Declarative environment setup.
+//!
+//! `FuzzEnvBuilder` describes the topology a target needs (how many users and
+//! vaults, the underlying decimals, SPL Token or Token-2022) and builds it in
+//! one call:
+//!
+//! ```ignore
+//! let (mut env, setup) = FuzzEnvBuilder::new()
+//!     .users(3)
+//!     .vaults(2)
+//!     .decimals(6)
+//!     .flavor(TokenFlavor::Token2022)
+//!     .build()
+//!     .await?;
+//! ```
+//!
+//! Every vault gets its own underlying mint. User `i` is the same keypair in
+//! every vault, with its own token accounts per vault, so only the vault
+//! constraints keep positions apart.
+
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use crate::{
+    fund_account, mint_tokens_to_user, new_keypair, setup_program_test, setup_protocol,
+    setup_token_account, setup_underlying_mint, setup_vault, CompleteSetup, FuzzResult,
+    FuzzTestEnv, ProgramTestContext, ProtocolAccounts, TokenFlavor, TwoVaultSetup,
+    UnderlyingMintAccounts, UserAccounts, VaultAccounts,
+};
+
+/// One vault, its underlying mint and every user's accounts for it
+#[derive(Debug, Clone)]
+pub struct VaultSetup {
+    pub underlying: UnderlyingMintAccounts,
+    pub vault: VaultAccounts,
+    /// Indexed like the builder's users
+    pub users: Vec<UserAccounts>,
+}
+
+/// Everything a `FuzzEnvBuilder` set up
+#[derive(Debug, Clone)]
+pub struct FuzzEnv {
+    pub protocol: ProtocolAccounts,
+    pub vaults: Vec<VaultSetup>,
+    /// Token program every mint and token account belongs to
+    pub token_program: Pubkey,
+}
+
+impl FuzzEnv {
+    /// Accounts of `user` in `vault`
+    pub fn user(&self, vault: usize, user: usize) -> &UserAccounts {
+        &self.vaults[vault].users[user]
+    }
+
+    /// The first vault with its first user, as `setup_complete_environment` returns it
+    pub fn into_complete(mut self) -> CompleteSetup {
+        let mut first = self.vaults.swap_remove(0);
+        CompleteSetup {
+            protocol: self.protocol,
+            underlying: first.underlying,
+            vault: first.vault,
+            user: first.users.swap_remove(0),
+        }
+    }
+
+    /// The first two vaults with the first user, as `setup_two_vault_environment` returns them
+    ///
+    /// Panics if fewer than two vaults were built.
+    pub fn into_two_vault(mut self) -> TwoVaultSetup {
+        let mut b = self.vaults.swap_remove(1);
+        let (underlying_b, vault_b, user_b) = (b.underlying, b.vault, b.users.swap_remove(0));
+        TwoVaultSetup {
+            a: self.into_complete(),
+            underlying_b,
+            vault_b,
+            user_b,
+        }
+    }
+}
+
+/// Builder for a `FuzzEnv`
+#[derive(Debug, Clone)]
+pub struct FuzzEnvBuilder {
+    users: usize,
+    vaults: usize,
+    decimals: u8,
+    flavor: TokenFlavor,
+    balance: u64,
+}
+
+impl Default for FuzzEnvBuilder {
+    fn default() -> Self {
+        Self {
+            users: 1,
+            vaults: 1,
+            decimals: 6,
+            flavor: TokenFlavor::SplToken,
+            balance: 0,
+        }
+    }
+}
+
+impl FuzzEnvBuilder {
+    /// One user in one SPL Token vault with 6 decimals and no balance
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of users, each with accounts in every vault (at least one)
+    pub fn users(mut self, users: usize) -> Self {
+        self.users = users.max(1);
+        self
+    }
+
+    /// Number of vaults, each over its own underlying mint (at least one)
+    pub fn vaults(mut self, vaults: usize) -> Self {
+        self.vaults = vaults.max(1);
+        self
+    }
+
+    /// Decimals of every underlying mint
+    pub fn decimals(mut self, decimals: u8) -> Self {
+        self.decimals = decimals;
+        self
+    }
+
+    /// Token program every mint and token account is created under
+    pub fn flavor(mut self, flavor: TokenFlavor) -> Self {
+        self.flavor = flavor;
+        self
+    }
+
+    /// Create the mints and token accounts under Token-2022 instead of SPL Token
+    pub fn token_2022(self, token_2022: bool) -> Self {
+        self.flavor(if token_2022 {
+            TokenFlavor::Token2022
+        } else {
+            TokenFlavor::SplToken
+        })
+    }
+
+    /// Underlying tokens every user starts with in every vault
+    pub fn balance(mut self, balance: u64) -> Self {
+        self.balance = balance;
+        self
+    }
+
+    /// Starts a program test and sets up the requested topology in it
+    pub async fn build(self) -> FuzzResult<(FuzzTestEnv, FuzzEnv)> {
+        let mut env = setup_program_test().await;
+        let program_id = env.program_id;
+
+        let protocol = setup_protocol(&mut env.context, &program_id).await?;
+        let owners: Vec<Keypair> = (0..self.users).map(|_| new_keypair()).collect();
+        for owner in &owners {
+            fund_account(&mut *env.context, &owner.pubkey()).await?;
+        }
+
+        let mut vaults = Vec::with_capacity(self.vaults);
+        for _ in 0..self.vaults {
+            let vault = self
+                .build_vault(&mut env.context, &program_id, &protocol, &owners)
+                .await?;
+            vaults.push(vault);
+        }
+
+        let setup = FuzzEnv {
+            protocol,
+            vaults,
+            token_program: self.flavor.program_id(),
+        };
+
+        Ok((env, setup))
+    }
+
+    async fn build_vault(
+        &self,
+        context: &mut ProgramTestContext,
+        program_id: &Pubkey,
+        protocol: &ProtocolAccounts,
+        owners: &[Keypair],
+    ) -> FuzzResult<VaultSetup> {
+        let underlying = setup_underlying_mint(context, self.decimals, self.flavor).await?;
+        let vault = setup_vault(
+            context,
+            program_id,
+            &protocol.vault_authority,
+            &underlying.mint,
+            &protocol.owner_keypair,
+            self.flavor,
+        )
+        .await?;
+
+        let mut users = Vec::with_capacity(owners.len());
+        for owner in owners {
+            let user = self.build_user(context, &underlying, &vault, owner).await?;
+            users.push(user);
+        }
+
+        Ok(VaultSetup {
+            underlying,
+            vault,
+            users,
+        })
+    }
+
+    async fn build_user(
+        &self,
+        context: &mut ProgramTestContext,
+        underlying: &UnderlyingMintAccounts,
+        vault: &VaultAccounts,
+        owner: &Keypair,
+    ) -> FuzzResult<UserAccounts> {
+        let user = UserAccounts {
+            owner: owner.insecure_clone(),
+            underlying_token_account: setup_token_account(
+                context,
+                &underlying.mint,
+                &owner.pubkey(),
+                self.flavor,
+            )
+            .await?,
+            share_token_account: setup_token_account(
+                context,
+                &vault.share_mint,
+                &owner.pubkey(),
+                self.flavor,
+            )
+            .await?,
+        };
+
+        if self.balance > 0 {
+            mint_tokens_to_user(
+                context,
+                &underlying.mint,
+                &underlying.mint_authority,
+                &user.underlying_token_account,
+                self.balance,
+            )
+            .await?;
+        }
+
+        Ok(user)
+    }
+}
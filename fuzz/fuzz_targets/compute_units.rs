@@ -0,0 +1,225 @@
+//! Compute units consumed while fuzzing.
+//!
+//! [`process_transaction`] is a drop-in for `BanksClient::process_transaction`
+//! that reads the units the transaction consumed from its metadata. Every
+//! transaction is recorded under a label naming its vault instructions
+//! (`deposit`, `deposit+redeem`, ...), keeping the count, total and worst case
+//! per label along with the input that reached the worst case, and a
+//! histogram in [`BUCKET_UNITS`] steps for percentiles. Stats are
+//! dumped with the scenario summary (see `fuzz_core::scenarios`), so a
+//! campaign doubles as a search for worst-case compute usage.
+//!
+//! A transaction made only of vault and compute budget instructions must stay
+//! under the sum of its instructions' ceilings. Ceilings default to the
+//! budgets `tests/compute_units.rs` checks in, and `FUZZ_CU_CEILING` replaces
+//! all of them with a single value.
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+
+use anchor_lang::Discriminator;
+use serde_json::{json, Value};
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::{
+    compute_budget, instruction::CompiledInstruction, pubkey::Pubkey, transaction::Transaction,
+};
+
+/// Environment variable replacing every instruction's ceiling
+pub const CEILING_VAR: &str = "FUZZ_CU_CEILING";
+
+/// Ceiling of an instruction without a checked-in budget, the runtime's
+/// default per-instruction limit
+pub const DEFAULT_CEILING: u64 = 200_000;
+
+/// Compute units each compute budget instruction consumes itself
+const BUDGET_IX_UNITS: u64 = 150;
+
+/// Width of a histogram bucket; percentiles are reported to this precision
+pub const BUCKET_UNITS: u64 = 100;
+
+/// Percentiles reported per label
+const PERCENTILES: [(&str, u64); 3] = [("p50", 50), ("p90", 90), ("p99", 99)];
+
+/// Stats of every transaction recorded under one label
+#[derive(Debug, Clone, Default)]
+struct Stats {
+    transactions: u64,
+    total: u64,
+    max: u64,
+    /// Target and JSON input of the transaction that consumed `max`
+    worst: Option<(&'static str, Value)>,
+    /// Transactions per `units / BUCKET_UNITS`
+    histogram: BTreeMap<u64, u64>,
+}
+
+impl Stats {
+    /// Upper bound of the bucket holding the `percent`th percentile, capped
+    /// at the worst case
+    fn percentile(&self, percent: u64) -> u64 {
+        let rank = (self.transactions * percent).div_ceil(100).max(1);
+        let mut seen = 0;
+        for (bucket, count) in &self.histogram {
+            seen += count;
+            if seen >= rank {
+                return ((bucket + 1) * BUCKET_UNITS).min(self.max);
+            }
+        }
+        self.max
+    }
+}
+
+/// Stats per label since the process started
+static STATS: Mutex<BTreeMap<String, Stats>> = Mutex::new(BTreeMap::new());
+
+/// Name of the vault instruction `data` encodes, if any
+pub fn instruction_name(data: &[u8]) -> Option<&'static str> {
+    use vault_pda::instruction as ix;
+
+    let names: [(&[u8], &'static str); 10] = [
+        (ix::Initialize::DISCRIMINATOR, "initialize"),
+        (ix::InitializeVault::DISCRIMINATOR, "initialize_vault"),
+        (ix::Deposit::DISCRIMINATOR, "deposit"),
+        (ix::Redeem::DISCRIMINATOR, "redeem"),
+        (ix::TransferOwnership::DISCRIMINATOR, "transfer_ownership"),
+        (
+            ix::InitializePriceHistory::DISCRIMINATOR,
+            "initialize_price_history",
+        ),
+        (ix::RecordPrice::DISCRIMINATOR, "record_price"),
+        (ix::InitializeFlag::DISCRIMINATOR, "initialize_flag"),
+        (ix::RegisterPlayer::DISCRIMINATOR, "register_player"),
+        (ix::CaptureFlag::DISCRIMINATOR, "capture_flag"),
+    ];
+    names
+        .into_iter()
+        .find(|(discriminator, _)| data.starts_with(discriminator))
+        .map(|(_, name)| name)
+}
+
+/// Budget `tests/compute_units.rs` holds `instruction` to
+pub fn default_ceiling(instruction: &str) -> u64 {
+    match instruction {
+        "initialize" => 40_000,
+        "initialize_vault" => 80_000,
+        "deposit" | "redeem" => 40_000,
+        "transfer_ownership" => 10_000,
+        _ => DEFAULT_CEILING,
+    }
+}
+
+/// Ceiling for `instruction`, [`default_ceiling`] unless `FUZZ_CU_CEILING`
+/// is set
+pub fn ceiling(instruction: &str) -> u64 {
+    static OVERRIDE: OnceLock<Option<u64>> = OnceLock::new();
+    OVERRIDE
+        .get_or_init(|| {
+            std::env::var(CEILING_VAR)
+                .ok()
+                .and_then(|ceiling| ceiling.parse().ok())
+        })
+        .unwrap_or_else(|| default_ceiling(instruction))
+}
+
+/// Label and ceiling of `tx`
+///
+/// The ceiling is `None` when the transaction has instructions of other
+/// programs, whose cost no budget covers.
+fn classify(tx: &Transaction, program_id: &Pubkey) -> (String, Option<u64>) {
+    let keys = &tx.message.account_keys;
+    let program_of = |ix: &CompiledInstruction| keys.get(ix.program_id_index as usize);
+
+    let mut names = Vec::new();
+    let mut limit = Some(0u64);
+    for ix in &tx.message.instructions {
+        match program_of(ix) {
+            Some(program) if program == program_id => {
+                let name = instruction_name(&ix.data).unwrap_or("unknown");
+                names.push(name);
+                limit = limit.map(|sum| sum + ceiling(name));
+            }
+            Some(program) if *program == compute_budget::id() => {
+                limit = limit.map(|sum| sum + BUDGET_IX_UNITS);
+            }
+            _ => limit = None,
+        }
+    }
+
+    let label = if names.is_empty() {
+        "other".to_string()
+    } else {
+        names.join("+")
+    };
+    (label, limit.filter(|_| !names.is_empty()))
+}
+
+/// Records `units` consumed by a transaction labelled `label`
+pub fn record(label: &str, units: u64) {
+    let Ok(mut stats) = STATS.lock() else {
+        return;
+    };
+    let stats = stats.entry(label.to_string()).or_default();
+    if stats.transactions == 0 || units > stats.max {
+        stats.max = units;
+        stats.worst = crate::fuzz_core::reproducer::current();
+    }
+    stats.transactions += 1;
+    stats.total += units;
+    *stats.histogram.entry(units / BUCKET_UNITS).or_default() += 1;
+}
+
+/// Sends `tx` for `program_id`'s instructions, records the compute units it
+/// consumed and checks them against the ceiling
+///
+/// Returns what `BanksClient::process_transaction` would.
+pub async fn process_transaction(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    tx: Transaction,
+) -> Result<(), BanksClientError> {
+    let (label, ceiling) = classify(&tx, program_id);
+    let processed = context
+        .banks_client
+        .process_transaction_with_metadata(tx)
+        .await?;
+
+    if let Some(metadata) = processed.metadata {
+        let units = metadata.compute_units_consumed;
+        record(&label, units);
+        if let Some(ceiling) = ceiling {
+            assert!(
+                units <= ceiling,
+                "{} consumed {} compute units, ceiling is {}",
+                label,
+                units,
+                ceiling
+            );
+        }
+    }
+
+    processed.result.map_err(BanksClientError::TransactionError)
+}
+
+/// Stats so far, per label
+pub fn summary() -> Value {
+    let stats = STATS.lock().map(|stats| stats.clone()).unwrap_or_default();
+    let labels: serde_json::Map<String, Value> = stats
+        .into_iter()
+        .map(|(label, stats)| {
+            let mean = stats.total / stats.transactions.max(1);
+            let mut entry = json!({
+                "transactions": stats.transactions,
+                "mean": mean,
+                "max": stats.max,
+            });
+            for (name, percent) in PERCENTILES {
+                entry[name] = stats.percentile(percent).into();
+            }
+            entry["worst"] = stats
+                .worst
+                .map(|(target, input)| json!({ "target": target, "input": input }))
+                .into();
+            (label, entry)
+        })
+        .collect();
+    Value::Object(labels)
+}
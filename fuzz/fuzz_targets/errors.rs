@@ -0,0 +1,159 @@
+//! Typed decoding of failed transactions.
+//!
+//! A rejected transaction only carries `InstructionError::Custom(code)`, and
+//! its `Debug` form prints the number, not the error's name, so matching on
+//! `format!("{:?}", e)` silently misses program errors. [`decode_error`] maps
+//! the code back onto the program's `#[error_code]` enum instead, so bodies
+//! can match acceptable failures exactly.
+//!
+//! The code ranges decide who raised an error: SPL Token errors are below 100,
+//! Anchor framework errors run from 100 up to `ERROR_CODE_OFFSET` and the
+//! program's own errors start there.
+
+use std::fmt::Debug;
+
+use anchor_lang::error::ERROR_CODE_OFFSET;
+use num_traits::FromPrimitive;
+use solana_program_test::BanksClientError;
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+use spl_token::error::TokenError;
+use vault_pda::instructions::{
+    CaptureFlagError, DepositError, InitializeFlagError, MigrateProtocolError, MigrateVaultError,
+    RedeemError, RegisterPlayerError, TransferOwnershipError,
+};
+
+/// Anchor framework errors start here; anything lower comes from SPL Token
+const ANCHOR_ERROR_START: u32 = 100;
+
+/// A `#[error_code]` enum of the vault program
+///
+/// Anchor generates `From<Self> for u32` but no way back, so each enum lists
+/// its variants here. A variant missing from `VARIANTS` decodes as
+/// [`TxFailure::Other`], which no body accepts.
+pub trait ProgramError: Copy + Debug + Into<u32> {
+    /// Every variant, in declaration order
+    const VARIANTS: &'static [Self];
+
+    /// The variant with the given custom error code
+    fn from_code(code: u32) -> Option<Self> {
+        Self::VARIANTS
+            .iter()
+            .copied()
+            .find(|variant| (*variant).into() == code)
+    }
+}
+
+impl ProgramError for DepositError {
+    const VARIANTS: &'static [Self] = &[
+        DepositError::InvalidAmount,
+        DepositError::InvalidVaultState,
+        DepositError::MathOverflow,
+        DepositError::InsufficientShares,
+        DepositError::InvalidShareMint,
+        DepositError::VaultSunset,
+    ];
+}
+
+impl ProgramError for RedeemError {
+    const VARIANTS: &'static [Self] = &[
+        RedeemError::InvalidAmount,
+        RedeemError::NoShares,
+        RedeemError::EmptyVault,
+        RedeemError::MathOverflow,
+        RedeemError::InsufficientUnderlying,
+        RedeemError::InvalidShareMint,
+        RedeemError::WithdrawalLimitExceeded,
+    ];
+}
+
+impl ProgramError for TransferOwnershipError {
+    const VARIANTS: &'static [Self] = &[TransferOwnershipError::Unauthorized];
+}
+
+impl ProgramError for InitializeFlagError {
+    const VARIANTS: &'static [Self] = &[InitializeFlagError::Unauthorized];
+}
+
+impl ProgramError for RegisterPlayerError {
+    const VARIANTS: &'static [Self] = &[RegisterPlayerError::AlreadyCaptured];
+}
+
+impl ProgramError for CaptureFlagError {
+    const VARIANTS: &'static [Self] = &[
+        CaptureFlagError::AlreadyCaptured,
+        CaptureFlagError::NotExploited,
+    ];
+}
+
+impl ProgramError for MigrateProtocolError {
+    const VARIANTS: &'static [Self] = &[
+        MigrateProtocolError::Unauthorized,
+        MigrateProtocolError::NotProtocolState,
+        MigrateProtocolError::AlreadyMigrated,
+    ];
+}
+
+impl ProgramError for MigrateVaultError {
+    const VARIANTS: &'static [Self] = &[
+        MigrateVaultError::Unauthorized,
+        MigrateVaultError::NotAVault,
+        MigrateVaultError::AlreadyMigrated,
+    ];
+}
+
+/// Why a transaction was rejected
+#[derive(Debug, Clone)]
+pub enum TxFailure<E> {
+    /// The instruction returned one of the program's own errors
+    Program(E),
+    /// SPL Token rejected a CPI
+    Token(TokenError),
+    /// Anchor rejected the instruction (constraint, account or discriminator)
+    Framework(u32),
+    /// The program aborted instead of returning an error
+    Panic,
+    /// Anything else, including errors outside any instruction
+    Other(Option<TransactionError>),
+}
+
+impl<E> TxFailure<E> {
+    /// Whether the program aborted instead of returning an error
+    pub fn is_panic(&self) -> bool {
+        matches!(self, TxFailure::Panic)
+    }
+}
+
+/// Decodes `error` against the `#[error_code]` enum `E` of the instruction sent
+///
+/// Every enum numbers its variants from `ERROR_CODE_OFFSET`, so `E` must be
+/// the enum of the instruction that failed.
+pub fn decode_error<E: ProgramError>(error: &BanksClientError) -> TxFailure<E> {
+    let transaction_error = match error {
+        BanksClientError::TransactionError(e)
+        | BanksClientError::SimulationError { err: e, .. } => e,
+        _ => return TxFailure::Other(None),
+    };
+
+    match transaction_error {
+        TransactionError::InstructionError(_, InstructionError::ProgramFailedToComplete) => {
+            TxFailure::Panic
+        }
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            let code = *code;
+            if code >= ERROR_CODE_OFFSET {
+                match E::from_code(code) {
+                    Some(e) => TxFailure::Program(e),
+                    None => TxFailure::Other(Some(transaction_error.clone())),
+                }
+            } else if code >= ANCHOR_ERROR_START {
+                TxFailure::Framework(code)
+            } else {
+                match TokenError::from_u32(code) {
+                    Some(e) => TxFailure::Token(e),
+                    None => TxFailure::Other(Some(transaction_error.clone())),
+                }
+            }
+        }
+        e => TxFailure::Other(Some(e.clone())),
+    }
+}
@@ -1,6 +1,19 @@
-// Placeholder for fuzz_all_instructions target
-// TODO: Implement combined fuzzing for all instructions
+use fuzz_helpers::fuzz_core::sequence::{fuzz_sequence_once, SequenceFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use honggfuzz::fuzz;
 
 fn main() {
-    println!("fuzz_all_instructions - To be implemented");
+    // Initialize honggfuzz
+    loop {
+        fuzz!(|input: SequenceFuzzInput| {
+            reproducer::track("sequence", &input);
+            scenarios::begin("sequence");
+            // Run the async fuzz test
+            block_on(async {
+                if let Err(e) = fuzz_sequence_once(input).await {
+                    eprintln!("Fuzz iteration failed: {}", e);
+                }
+            });
+        });
+    }
 }
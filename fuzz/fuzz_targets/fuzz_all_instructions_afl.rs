@@ -0,0 +1,18 @@
+use fuzz_helpers::fuzz_core::sequence::{fuzz_sequence_once, SequenceFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<SequenceFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("sequence", &input);
+        scenarios::begin("sequence");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_sequence_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::sequence::{fuzz_sequence_once, SequenceFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: SequenceFuzzInput| {
+    reproducer::track("sequence", &input);
+    scenarios::begin("sequence");
+    // Run the async fuzz test
+    block_on(async {
+        if let Err(e) = fuzz_sequence_once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+});
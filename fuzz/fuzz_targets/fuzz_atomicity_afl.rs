@@ -0,0 +1,18 @@
+use fuzz_helpers::fuzz_core::atomicity::{fuzz_atomicity_once, AtomicityFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<AtomicityFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("atomicity", &input);
+        scenarios::begin("atomicity");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_atomicity_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
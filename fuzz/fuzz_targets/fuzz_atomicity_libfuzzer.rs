@@ -0,0 +1,16 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::atomicity::{fuzz_atomicity_once, AtomicityFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: AtomicityFuzzInput| {
+    reproducer::track("atomicity", &input);
+    scenarios::begin("atomicity");
+    // Run the async fuzz test
+    block_on(async {
+        if let Err(e) = fuzz_atomicity_once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+});
@@ -0,0 +1,18 @@
+use fuzz_helpers::fuzz_core::boundary::{fuzz_boundary_once, BoundaryFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<BoundaryFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("boundary", &input);
+        scenarios::begin("boundary");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_boundary_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::boundary::{fuzz_boundary_once, BoundaryFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: BoundaryFuzzInput| {
+    reproducer::track("boundary", &input);
+    scenarios::begin("boundary");
+    // Run the async fuzz test
+    block_on(async {
+        if let Err(e) = fuzz_boundary_once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+});
@@ -0,0 +1,19 @@
+use fuzz_helpers::fuzz_core::bump::{fuzz_bump_once, BumpFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use honggfuzz::fuzz;
+
+fn main() {
+    // Initialize honggfuzz
+    loop {
+        fuzz!(|input: BumpFuzzInput| {
+            reproducer::track("bump", &input);
+            scenarios::begin("bump");
+            // Run the async fuzz test
+            block_on(async {
+                if let Err(e) = fuzz_bump_once(input).await {
+                    eprintln!("Fuzz iteration failed: {}", e);
+                }
+            });
+        });
+    }
+}
@@ -0,0 +1,18 @@
+use fuzz_helpers::fuzz_core::bump::{fuzz_bump_once, BumpFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<BumpFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("bump", &input);
+        scenarios::begin("bump");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_bump_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::bump::{fuzz_bump_once, BumpFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: BumpFuzzInput| {
+    reproducer::track("bump", &input);
+    scenarios::begin("bump");
+    // Run the async fuzz test
+    block_on(async {
+        if let Err(e) = fuzz_bump_once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+});
@@ -0,0 +1,18 @@
+use fuzz_helpers::fuzz_core::clock_warp::{fuzz_clock_warp_once, ClockWarpFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<ClockWarpFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("clock_warp", &input);
+        scenarios::begin("clock_warp");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_clock_warp_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
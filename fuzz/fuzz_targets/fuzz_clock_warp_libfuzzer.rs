@@ -0,0 +1,16 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::clock_warp::{fuzz_clock_warp_once, ClockWarpFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: ClockWarpFuzzInput| {
+    reproducer::track("clock_warp", &input);
+    scenarios::begin("clock_warp");
+    // Run the async fuzz test
+    block_on(async {
+        if let Err(e) = fuzz_clock_warp_once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+});
@@ -0,0 +1,9 @@
+use fuzz_helpers::fuzz_core::compute_budget::{fuzz_compute_budget_once, ComputeBudgetFuzzInput};
+use fuzz_helpers::vault_fuzz_main;
+
+vault_fuzz_main!(
+    honggfuzz,
+    "compute_budget",
+    ComputeBudgetFuzzInput,
+    fuzz_compute_budget_once
+);
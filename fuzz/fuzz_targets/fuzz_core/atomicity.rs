@@ -0,0 +1,202 @@
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{instruction::Instruction, signature::Signer};
+use spl_token_2022::instruction as token_instruction;
+use vault_math::{compute_assets, compute_shares};
+
+use super::corruption::is_panic;
+use super::sequence::Sender;
+use crate::*;
+
+/// Underlying balance each user starts with
+const INITIAL_BALANCE: u64 = 1_000_000;
+
+/// Position opened by each user before the fuzzed transaction
+const POSITION: u64 = 100_000;
+
+/// Upper bound on instructions per transaction
+const MAX_INSTRUCTIONS: usize = 4;
+
+/// A single instruction inside the fuzzed transaction
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum AtomicOperation {
+    /// Deposit (reduced modulo the starting balance + 2)
+    Deposit { amount: u32 },
+    /// Redeem (reduced modulo the starting position + 2)
+    Redeem { shares: u32 },
+    /// Plain token transfer into the vault, moving the share price mid-transaction
+    Donate { amount: u16 },
+}
+
+/// Fuzzable input for several vault instructions in one transaction
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct AtomicityFuzzInput {
+    /// Token program the vault and its accounts belong to
+    #[serde(default)]
+    pub flavor: TokenFlavor,
+    pub operations: Vec<AtomicOperation>,
+}
+
+/// Balances the transaction can change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct State {
+    vault: u64,
+    supply: u64,
+    user_underlying: u64,
+    user_shares: u64,
+}
+
+impl State {
+    /// Applies one instruction, `None` if it must fail (failing the whole
+    /// transaction)
+    fn apply(mut self, operation: AtomicOperation) -> Option<State> {
+        match operation {
+            AtomicOperation::Deposit { amount } => {
+                let amount = amount as u64 % (INITIAL_BALANCE + 2);
+                let shares = compute_shares(amount, self.supply, self.vault).ok()?;
+                self.user_underlying = self.user_underlying.checked_sub(amount)?;
+                self.vault += amount;
+                self.supply += shares;
+                self.user_shares += shares;
+            }
+            AtomicOperation::Redeem { shares } => {
+                let shares = shares as u64 % (POSITION + 2);
+                let assets = compute_assets(shares, self.supply, self.vault).ok()?;
+                self.user_shares = self.user_shares.checked_sub(shares)?;
+                self.supply -= shares;
+                self.vault = self.vault.checked_sub(assets)?;
+                self.user_underlying += assets;
+            }
+            AtomicOperation::Donate { amount } => {
+                let amount = amount as u64;
+                self.user_underlying = self.user_underlying.checked_sub(amount)?;
+                self.vault += amount;
+            }
+        }
+        Some(self)
+    }
+}
+
+async fn state(env: &mut FuzzTestEnv, setup: &CompleteSetup) -> FuzzResult<State> {
+    Ok(State {
+        vault: get_token_balance(&mut env.context, &setup.vault.vault_token_account).await?,
+        supply: get_mint_supply(&mut env.context, &setup.vault.share_mint).await?,
+        user_underlying: get_token_balance(&mut env.context, &setup.user.underlying_token_account)
+            .await?,
+        user_shares: get_token_balance(&mut env.context, &setup.user.share_token_account).await?,
+    })
+}
+
+/// Execute a single same-transaction atomicity iteration
+pub async fn fuzz_atomicity_once(input: AtomicityFuzzInput) -> FuzzResult<()> {
+    let operations: Vec<AtomicOperation> = input
+        .operations
+        .iter()
+        .take(MAX_INSTRUCTIONS)
+        .copied()
+        .collect();
+    if operations.is_empty() {
+        return Ok(());
+    }
+
+    // ========================================
+    // SETUP: the user and a second holder each hold a position
+    // ========================================
+
+    let (mut env, setup) = setup_complete_environment(INITIAL_BALANCE, 6, input.flavor).await?;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+
+    let holder = create_and_fund_user(
+        &mut env.context,
+        &setup.underlying,
+        &setup.vault.share_mint,
+        INITIAL_BALANCE,
+    )
+    .await?;
+    for user in [&setup.user, &holder] {
+        let ix = deposit_ix(&program_id, &setup.vault, user, POSITION);
+        if let Err(e) = sender.send(&mut env, ix, &user.owner).await? {
+            return Err(e.into());
+        }
+    }
+
+    // ========================================
+    // EXECUTE: every operation in one transaction
+    // ========================================
+
+    let instructions = operations
+        .iter()
+        .map(|operation| -> FuzzResult<Instruction> {
+            Ok(match *operation {
+                AtomicOperation::Deposit { amount } => deposit_ix(
+                    &program_id,
+                    &setup.vault,
+                    &setup.user,
+                    amount as u64 % (INITIAL_BALANCE + 2),
+                ),
+                AtomicOperation::Redeem { shares } => redeem_ix(
+                    &program_id,
+                    &setup.vault,
+                    &setup.user,
+                    shares as u64 % (POSITION + 2),
+                ),
+                AtomicOperation::Donate { amount } => token_instruction::transfer_checked(
+                    &setup.vault.token_program,
+                    &setup.user.underlying_token_account,
+                    &setup.underlying.mint,
+                    &setup.vault.vault_token_account,
+                    &setup.user.owner.pubkey(),
+                    &[],
+                    amount as u64,
+                    setup.underlying.decimals,
+                )?,
+            })
+        })
+        .collect::<FuzzResult<Vec<_>>>()?;
+
+    let before = state(&mut env, &setup).await?;
+    let expected = operations
+        .iter()
+        .try_fold(before, |state, operation| state.apply(*operation));
+
+    let result = sender
+        .send_instructions(&mut env, &instructions, &[&setup.user.owner])
+        .await?;
+    let after = state(&mut env, &setup).await?;
+
+    // ========================================
+    // INVARIANT CHECKS
+    // ========================================
+
+    match (result, expected) {
+        // INVARIANT 1: every instruction sees the state left by the previous
+        // one, so the transaction ends exactly where sequential execution would
+        (Ok(()), Some(expected)) => assert_eq!(
+            after, expected,
+            "CRITICAL VULNERABILITY: {:?} in one transaction diverged from sequential execution",
+            operations
+        ),
+        (Ok(()), None) => panic!(
+            "CRITICAL VULNERABILITY: {:?} succeeded in one transaction although a step must fail: {:?} -> {:?}",
+            operations, before, after
+        ),
+        // INVARIANT 2: a failing instruction reverts the whole transaction
+        (Err(e), expected) => {
+            assert!(!is_panic(&e), "Program panicked on {:?}: {:?}", operations, e);
+            assert_eq!(
+                before, after,
+                "Failed transaction {:?} left partial state",
+                operations
+            );
+            assert!(
+                expected.is_none(),
+                "{:?} rejected in one transaction although every step is valid: {:?}",
+                operations,
+                e
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use anchor_lang::{InstructionData, ToAccountMetas};
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Signer,
+};
+
+use super::sequence::Sender;
+use crate::*;
+
+/// Upper bound on attempts per input
+const MAX_ATTEMPTS: usize = 8;
+
+/// Participants holding keypairs: the original owner, an attacker and a bystander
+const PARTICIPANTS: usize = 3;
+
+/// An account passed to `transfer_ownership`
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum AccountChoice {
+    /// One of the participants (reduced modulo the participant count)
+    Participant(u8),
+    /// A fresh address nobody holds the key for
+    Random,
+    /// The protocol state PDA itself
+    ProtocolState,
+    /// The all-zero address
+    Default,
+}
+
+/// A single `transfer_ownership` call
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct Attempt {
+    /// Account passed as `current_owner`
+    pub current_owner: AccountChoice,
+    /// Mark `current_owner` as a signer (only possible for participants)
+    pub current_owner_signs: bool,
+    /// Account passed as `new_owner`
+    pub new_owner: AccountChoice,
+    /// Participant paying the fee (reduced modulo the participant count)
+    pub payer: u8,
+}
+
+/// Fuzzable input for transfer_ownership authorization
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct AuthorizationFuzzInput {
+    pub attempts: Vec<Attempt>,
+}
+
+/// Execute a single authorization iteration
+pub async fn fuzz_authorization_once(input: AuthorizationFuzzInput) -> FuzzResult<()> {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let protocol = setup_protocol(&mut env.context, &program_id).await?;
+
+    let mut sender = Sender::default();
+    let participants = [
+        protocol.owner_keypair.insecure_clone(),
+        new_keypair(),
+        new_keypair(),
+    ];
+    for participant in &participants[1..] {
+        let payer = env.context.payer.insecure_clone();
+        let ix = solana_sdk::system_instruction::transfer(
+            &payer.pubkey(),
+            &participant.pubkey(),
+            1_000_000_000,
+        );
+        if let Err(e) = sender.send(&mut env, ix, &payer).await? {
+            return Err(e.into());
+        }
+    }
+
+    let mut owner = protocol.owner;
+
+    for (step, attempt) in input.attempts.iter().take(MAX_ATTEMPTS).enumerate() {
+        let resolve = |choice: AccountChoice| match choice {
+            AccountChoice::Participant(index) => (
+                participants[index as usize % PARTICIPANTS].pubkey(),
+                Some(index as usize % PARTICIPANTS),
+            ),
+            AccountChoice::Random => (new_address(), None),
+            AccountChoice::ProtocolState => (protocol.protocol_state, None),
+            AccountChoice::Default => (Pubkey::default(), None),
+        };
+        let (current_owner, current_index) = resolve(attempt.current_owner);
+        let (new_owner, _) = resolve(attempt.new_owner);
+        let payer = &participants[attempt.payer as usize % PARTICIPANTS];
+
+        let mut signers = vec![payer];
+        let current_owner_signs = attempt.current_owner_signs && current_index.is_some();
+        if let (true, Some(index)) = (current_owner_signs, current_index) {
+            if participants[index].pubkey() != payer.pubkey() {
+                signers.push(&participants[index]);
+            }
+        }
+
+        let mut accounts = vault_pda::accounts::TransferOwnership {
+            protocol_state: protocol.protocol_state,
+            current_owner,
+            new_owner,
+            admin_log: derive_admin_log_pda(&program_id).0,
+        }
+        .to_account_metas(None);
+        accounts[1] = AccountMeta::new_readonly(current_owner, current_owner_signs);
+        let ix = Instruction {
+            program_id,
+            accounts,
+            data: vault_pda::instruction::TransferOwnership {}.data(),
+        };
+
+        let result = sender.send_with_signers(&mut env, ix, &signers).await?;
+        let on_chain = get_protocol_state(&mut env.context, &protocol.protocol_state)
+            .await?
+            .owner;
+
+        let owner_signed = signers.iter().any(|signer| signer.pubkey() == owner);
+        let context = format!(
+            "at attempt {} ({:?}), owner {}, signers {:?}",
+            step,
+            attempt,
+            owner,
+            signers.iter().map(|s| s.pubkey()).collect::<Vec<_>>()
+        );
+
+        match result {
+            Ok(()) => {
+                // INVARIANT 1: ownership only moves when the true owner signed
+                assert!(
+                    owner_signed,
+                    "CRITICAL VULNERABILITY: ownership moved to {} without the owner's signature {}",
+                    new_owner, context
+                );
+                // INVARIANT 2: it moves exactly to the requested account
+                assert_eq!(on_chain, new_owner, "Ownership moved elsewhere {}", context);
+                owner = new_owner;
+            }
+            Err(e) => {
+                // INVARIANT 3: a rejected call leaves ownership untouched
+                assert_eq!(
+                    on_chain, owner,
+                    "Rejected call changed ownership {}",
+                    context
+                );
+
+                // INVARIANT 4: the true owner naming and signing as itself always succeeds
+                assert!(
+                    !(current_owner == owner && owner_signed),
+                    "Owner-signed transfer was rejected: {:?} {}",
+                    e,
+                    context
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,272 @@
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    account::AccountSharedData, instruction::InstructionError, pubkey::Pubkey,
+    transaction::TransactionError,
+};
+use vault_pda::instructions::{DepositError, RedeemError};
+
+use super::corruption::is_panic;
+use super::scenarios;
+use super::sequence::Sender;
+use crate::*;
+
+/// Offset of `amount` in the SPL Token account layout
+const TOKEN_AMOUNT_OFFSET: usize = 64;
+
+/// Offset of `supply` in the SPL Token mint layout
+const MINT_SUPPLY_OFFSET: usize = 36;
+
+/// A `u64` biased towards the top of its range
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum Boundary {
+    /// `u64::MAX`
+    Max,
+    /// `u64::MAX - offset`
+    NearMax(u16),
+    /// `2^63 + offset - 2^15`, around the midpoint of the range
+    NearHalf(u16),
+    /// A small value
+    Small(u32),
+    /// Any value
+    Raw(u64),
+}
+
+impl Boundary {
+    fn value(self) -> u64 {
+        match self {
+            Boundary::Max => u64::MAX,
+            Boundary::NearMax(offset) => u64::MAX - offset as u64,
+            Boundary::NearHalf(offset) => (1u64 << 63) + offset as u64 - (1 << 15),
+            Boundary::Small(value) => value as u64,
+            Boundary::Raw(value) => value,
+        }
+    }
+}
+
+/// Instruction run against the pre-loaded balances
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum BoundaryOperation {
+    Deposit { amount: Boundary },
+    Redeem { shares: Boundary },
+}
+
+/// Fuzzable input for balances and amounts near `u64::MAX`
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct BoundaryFuzzInput {
+    /// Token program the vault and its accounts belong to
+    #[serde(default)]
+    pub flavor: TokenFlavor,
+    pub vault_balance: Boundary,
+    pub share_supply: Boundary,
+    pub user_underlying: Boundary,
+    /// User share balance (capped at the share supply)
+    pub user_shares: Boundary,
+    pub operation: BoundaryOperation,
+}
+
+/// What the program must do, computed independently of `vault_math` in plain
+/// `u128` arithmetic
+#[derive(Debug, PartialEq, Eq)]
+enum Expected {
+    /// Succeeds and moves exactly this many tokens or shares
+    Success(u64),
+    /// Rejected with `MathOverflow`
+    Overflow,
+    /// Rejected with any error (zero amounts, empty vault, insufficient
+    /// funds, token account overflow)
+    Rejected,
+}
+
+/// Overwrites the little-endian `u64` at `offset` in `address`'s data
+async fn set_u64(
+    env: &mut FuzzTestEnv,
+    address: &Pubkey,
+    offset: usize,
+    value: u64,
+) -> FuzzResult<()> {
+    let mut account: AccountSharedData = env
+        .context
+        .banks_client
+        .get_account(*address)
+        .await?
+        .ok_or("Account not found")?
+        .into();
+    let mut data = account.data().to_vec();
+    data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+    account.set_data_from_slice(&data);
+    env.context.set_account(address, &account);
+    Ok(())
+}
+
+/// Whether `error` is the program's custom error `code`
+fn is_error(error: &BanksClientError, code: u32) -> bool {
+    matches!(
+        error,
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(c)
+        )) if *c == code
+    )
+}
+
+fn expect_deposit(amount: u64, vault: u64, supply: u64, user_underlying: u64) -> Expected {
+    let shares = if supply == 0 {
+        amount as u128
+    } else if vault == 0 || amount == 0 {
+        return Expected::Rejected;
+    } else {
+        amount as u128 * supply as u128 / vault as u128
+    };
+    if shares > u64::MAX as u128 {
+        return Expected::Overflow;
+    }
+    let fits = amount <= user_underlying
+        && vault.checked_add(amount).is_some()
+        && supply.checked_add(shares as u64).is_some();
+    if shares == 0 || amount == 0 || !fits {
+        return Expected::Rejected;
+    }
+    Expected::Success(shares as u64)
+}
+
+fn expect_redeem(
+    shares: u64,
+    vault: u64,
+    supply: u64,
+    user_underlying: u64,
+    user_shares: u64,
+) -> Expected {
+    if shares == 0 || supply == 0 || vault == 0 {
+        return Expected::Rejected;
+    }
+    let assets = shares as u128 * vault as u128 / supply as u128;
+    if assets > u64::MAX as u128 {
+        return Expected::Overflow;
+    }
+    let fits = shares <= user_shares
+        && assets as u64 <= vault
+        && user_underlying.checked_add(assets as u64).is_some();
+    if assets == 0 || !fits {
+        return Expected::Rejected;
+    }
+    Expected::Success(assets as u64)
+}
+
+/// Execute a single near-`u64::MAX` iteration
+pub async fn fuzz_boundary_once(input: BoundaryFuzzInput) -> FuzzResult<()> {
+    let vault_balance = input.vault_balance.value();
+    let share_supply = input.share_supply.value();
+    let user_underlying = input.user_underlying.value();
+    let user_shares = input.user_shares.value().min(share_supply);
+
+    // ========================================
+    // SETUP: pre-load huge balances via set_account
+    // ========================================
+
+    let (mut env, setup) = setup_complete_environment(0, 6, input.flavor).await?;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    let vault = &setup.vault;
+    let user = &setup.user;
+
+    let writes = [
+        (
+            vault.vault_token_account,
+            TOKEN_AMOUNT_OFFSET,
+            vault_balance,
+        ),
+        (vault.share_mint, MINT_SUPPLY_OFFSET, share_supply),
+        (
+            user.underlying_token_account,
+            TOKEN_AMOUNT_OFFSET,
+            user_underlying,
+        ),
+        (user.share_token_account, TOKEN_AMOUNT_OFFSET, user_shares),
+    ];
+    for (address, offset, value) in writes {
+        set_u64(&mut env, &address, offset, value).await?;
+    }
+
+    // ========================================
+    // EXECUTE
+    // ========================================
+
+    let (ix, expected, overflow_code, moved_account) = match input.operation {
+        BoundaryOperation::Deposit { amount } => {
+            let amount = amount.value();
+            (
+                deposit_ix(&program_id, vault, user, amount),
+                expect_deposit(amount, vault_balance, share_supply, user_underlying),
+                u32::from(DepositError::MathOverflow),
+                user.share_token_account,
+            )
+        }
+        BoundaryOperation::Redeem { shares } => {
+            let shares = shares.value();
+            (
+                redeem_ix(&program_id, vault, user, shares),
+                expect_redeem(
+                    shares,
+                    vault_balance,
+                    share_supply,
+                    user_underlying,
+                    user_shares,
+                ),
+                u32::from(RedeemError::MathOverflow),
+                user.underlying_token_account,
+            )
+        }
+    };
+
+    let before = get_token_balance(&mut env.context, &moved_account).await?;
+    let result = sender.send(&mut env, ix, &user.owner).await?;
+    let after = get_token_balance(&mut env.context, &moved_account).await?;
+
+    // ========================================
+    // INVARIANT CHECKS
+    // ========================================
+
+    let context = format!(
+        "{:?} with vault {}, supply {}, user underlying {}, user shares {}",
+        input.operation, vault_balance, share_supply, user_underlying, user_shares
+    );
+
+    scenarios::hit(match (&result, &expected) {
+        (Ok(()), _) => "BOUNDARY_SUCCESS",
+        (Err(_), Expected::Overflow) => "BOUNDARY_OVERFLOW",
+        (Err(_), _) => "BOUNDARY_REJECTED",
+    });
+
+    match (result, expected) {
+        // INVARIANT 1: the u128 result is cast back to u64 without truncation
+        (Ok(()), Expected::Success(amount)) => assert_eq!(
+            after.wrapping_sub(before),
+            amount,
+            "CRITICAL VULNERABILITY: truncated result for {}",
+            context
+        ),
+        (Ok(()), expected) => panic!(
+            "CRITICAL VULNERABILITY: succeeded although {:?} was expected for {}",
+            expected, context
+        ),
+        (Err(e), Expected::Success(amount)) => panic!(
+            "Rejected although {} was expected for {}: {:?}",
+            amount, context, e
+        ),
+        // INVARIANT 2: results that do not fit in a u64 surface as MathOverflow
+        (Err(e), Expected::Overflow) => assert!(
+            is_error(&e, overflow_code),
+            "Overflow not reported as MathOverflow for {}: {:?}",
+            context,
+            e
+        ),
+        // INVARIANT 3: every other boundary failure is an error, never an abort
+        (Err(e), Expected::Rejected) => {
+            assert!(!is_panic(&e), "Program panicked for {}: {:?}", context, e)
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,248 @@
+use anchor_lang::{AccountDeserialize, AccountSerialize};
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Signer};
+
+use super::corruption::is_panic;
+use super::sequence::Sender;
+use crate::*;
+
+/// Underlying balance the user starts with
+const INITIAL_BALANCE: u64 = 1_000_000;
+
+/// Position opened before the fuzzed call, so redeems have shares to burn
+const POSITION: u64 = 100_000;
+
+/// PDA that gets a twin at another bump
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum BumpTarget {
+    ProtocolState,
+    VaultAuthority,
+    Vault,
+}
+
+/// Where the twin account lives
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum Location {
+    /// The canonical PDA itself, only its stored bump changes
+    Canonical,
+    /// A valid off-curve address for the same seeds with another bump
+    /// (reduced modulo the number of such bumps)
+    NonCanonical(u8),
+}
+
+/// Bump written into the twin account's data
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum StoredBump {
+    /// The canonical bump, as `initialize` would store it
+    Canonical,
+    /// The bump of the twin's own address, so the stored bump and the
+    /// address agree
+    Address,
+    Other(u8),
+}
+
+/// Fuzzable input for non-canonical bump PDAs
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct BumpFuzzInput {
+    pub target: BumpTarget,
+    pub location: Location,
+    pub stored: StoredBump,
+    /// Redeem instead of deposit for vault-level targets
+    pub redeem: bool,
+    /// Amount or shares (reduced below the opened position)
+    pub amount: u32,
+}
+
+/// Every bump other than `canonical` that yields a valid PDA for `seeds`
+fn non_canonical_bumps(seeds: &[&[u8]], canonical: u8, program_id: &Pubkey) -> Vec<(u8, Pubkey)> {
+    (0..canonical)
+        .rev()
+        .filter_map(|bump| {
+            let mut with_bump = seeds.to_vec();
+            let bump_seed = [bump];
+            with_bump.push(&bump_seed);
+            Pubkey::create_program_address(&with_bump, program_id)
+                .ok()
+                .map(|address| (bump, address))
+        })
+        .collect()
+}
+
+/// Reads `address` as the Anchor account `T`, rewrites its bump and
+/// serializes it back
+async fn rewrite_bump<T: AccountDeserialize + AccountSerialize>(
+    env: &mut FuzzTestEnv,
+    address: &Pubkey,
+    set_bump: impl FnOnce(&mut T),
+) -> FuzzResult<Account> {
+    let mut account = env
+        .context
+        .banks_client
+        .get_account(*address)
+        .await?
+        .ok_or("Target account not found")?;
+    let mut state = T::try_deserialize(&mut account.data.as_ref())?;
+    set_bump(&mut state);
+    let mut data = Vec::new();
+    state.try_serialize(&mut data)?;
+    account.data = data;
+    Ok(account)
+}
+
+/// Execute a single non-canonical bump iteration
+pub async fn fuzz_bump_once(input: BumpFuzzInput) -> FuzzResult<()> {
+    let amount = input.amount as u64 % POSITION + 1;
+
+    // ========================================
+    // SETUP: one open position
+    // ========================================
+
+    let (mut env, setup) =
+        setup_complete_environment(INITIAL_BALANCE, 6, TokenFlavor::SplToken).await?;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+
+    let ix = deposit_ix(&program_id, &setup.vault, &setup.user, POSITION);
+    if let Err(e) = sender.send(&mut env, ix, &setup.user.owner).await? {
+        return Err(e.into());
+    }
+
+    // ========================================
+    // BUILD THE TWIN ACCOUNT
+    // ========================================
+
+    let underlying_mint = setup.vault.underlying_mint;
+    let (canonical, canonical_bump, seeds): (Pubkey, u8, Vec<&[u8]>) = match input.target {
+        BumpTarget::ProtocolState => {
+            let (address, bump) = derive_protocol_state_pda(&program_id);
+            (address, bump, vec![b"protocol_state".as_ref()])
+        }
+        BumpTarget::VaultAuthority => {
+            let (address, bump) = derive_vault_authority_pda(&program_id);
+            (address, bump, vec![b"vault_authority".as_ref()])
+        }
+        BumpTarget::Vault => {
+            let (address, bump) = derive_vault_pda(&program_id, &underlying_mint);
+            (
+                address,
+                bump,
+                vec![b"vault".as_ref(), underlying_mint.as_ref()],
+            )
+        }
+    };
+
+    let (address, address_bump) = match input.location {
+        Location::Canonical => (canonical, canonical_bump),
+        Location::NonCanonical(index) => {
+            let candidates = non_canonical_bumps(&seeds, canonical_bump, &program_id);
+            if candidates.is_empty() {
+                return Ok(());
+            }
+            let (bump, address) = candidates[index as usize % candidates.len()];
+            (address, bump)
+        }
+    };
+    let stored = match input.stored {
+        StoredBump::Canonical => canonical_bump,
+        StoredBump::Address => address_bump,
+        StoredBump::Other(bump) => bump,
+    };
+
+    let twin = match input.target {
+        BumpTarget::ProtocolState => {
+            rewrite_bump::<vault_pda::state::ProtocolState>(&mut env, &canonical, |state| {
+                state.bump = stored
+            })
+            .await?
+        }
+        BumpTarget::VaultAuthority => {
+            rewrite_bump::<vault_pda::state::VaultAuthority>(&mut env, &canonical, |state| {
+                state.bump = stored
+            })
+            .await?
+        }
+        BumpTarget::Vault => {
+            rewrite_bump::<vault_pda::state::Vault>(&mut env, &canonical, |state| {
+                state.bump = stored
+            })
+            .await?
+        }
+    };
+    env.context.set_account(&address, &twin.into());
+
+    // ========================================
+    // EXECUTE with the twin in place of the canonical account
+    // ========================================
+
+    let (mut ix, signer) = match input.target {
+        BumpTarget::ProtocolState => (
+            transfer_ownership_ix(&program_id, &setup.protocol.owner, &new_keypair().pubkey()),
+            &setup.protocol.owner_keypair,
+        ),
+        _ if input.redeem => (
+            redeem_ix(&program_id, &setup.vault, &setup.user, amount),
+            &setup.user.owner,
+        ),
+        _ => (
+            deposit_ix(&program_id, &setup.vault, &setup.user, amount),
+            &setup.user.owner,
+        ),
+    };
+    for meta in &mut ix.accounts {
+        if meta.pubkey == canonical {
+            meta.pubkey = address;
+        }
+    }
+
+    let owner_before = get_protocol_state(&mut env.context, &setup.protocol.protocol_state)
+        .await?
+        .owner;
+    let vault_before =
+        get_token_balance(&mut env.context, &setup.vault.vault_token_account).await?;
+    let result = sender.send(&mut env, ix, signer).await?;
+
+    // ========================================
+    // INVARIANT CHECKS
+    // ========================================
+
+    let canonical_everything = address == canonical && stored == canonical_bump;
+    let context = format!(
+        "{:?} at {} (canonical bump {}, address bump {}, stored bump {})",
+        input.target, address, canonical_bump, address_bump, stored
+    );
+
+    match result {
+        // INVARIANT 1: the untouched canonical account keeps working
+        Err(e) if canonical_everything => {
+            panic!("Canonical {} rejected: {:?}", context, e)
+        }
+        Ok(()) if canonical_everything => {}
+        // INVARIANT 2: a twin at another bump, or a canonical account whose
+        // stored bump was changed, is always rejected
+        Ok(()) => panic!(
+            "CRITICAL VULNERABILITY: non-canonical bump accepted for {}",
+            context
+        ),
+        // INVARIANT 3: the rejection is an error and changes nothing
+        Err(e) => {
+            assert!(!is_panic(&e), "Program panicked for {}: {:?}", context, e);
+            assert_eq!(
+                get_protocol_state(&mut env.context, &setup.protocol.protocol_state)
+                    .await?
+                    .owner,
+                owner_before,
+                "Rejected call changed the owner for {}",
+                context
+            );
+            assert_eq!(
+                get_token_balance(&mut env.context, &setup.vault.vault_token_account).await?,
+                vault_before,
+                "Rejected call moved funds for {}",
+                context
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,226 @@
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_sdk::clock::Clock;
+use vault_math::{compute_assets, compute_shares};
+use vault_pda::state::PriceHistory;
+
+use super::sequence::Sender;
+use crate::*;
+
+/// Underlying balance the user starts with
+const INITIAL_BALANCE: u64 = 1_000_000;
+
+/// Upper bound on operations per input
+const MAX_OPERATIONS: usize = 24;
+
+/// How far the clock moves
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum Warp {
+    /// A few slots forward
+    Slots(u16),
+    /// To just around the first slot of the next epoch
+    EpochBoundary { offset: i8 },
+    /// Several epochs forward, a long gap between operations
+    Epochs(u8),
+}
+
+/// A single step in the fuzzed sequence
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum ClockOperation {
+    /// Deposit (reduced modulo the user's balance + 2)
+    Deposit {
+        amount: u32,
+    },
+    /// Redeem (reduced modulo the user's shares + 2)
+    Redeem {
+        shares: u32,
+    },
+    /// Record a share price observation
+    RecordPrice,
+    Warp(Warp),
+}
+
+/// Fuzzable input for operations interleaved with clock warps
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct ClockWarpFuzzInput {
+    /// Token program the vault and its accounts belong to
+    #[serde(default)]
+    pub flavor: TokenFlavor,
+    pub operations: Vec<ClockOperation>,
+}
+
+/// Moves the clock according to `warp`, always strictly forward
+async fn warp(env: &mut FuzzTestEnv, warp: Warp) -> FuzzResult<()> {
+    let clock = env.context.banks_client.get_sysvar::<Clock>().await?;
+    let schedule = env.context.genesis_config().epoch_schedule.clone();
+
+    let target = match warp {
+        Warp::Slots(slots) => clock.slot + slots as u64 + 1,
+        Warp::EpochBoundary { offset } => {
+            let boundary = schedule.get_first_slot_in_epoch(clock.epoch + 1);
+            boundary.saturating_add_signed(offset as i64)
+        }
+        Warp::Epochs(epochs) => {
+            schedule.get_first_slot_in_epoch(clock.epoch + epochs as u64 % 16 + 1)
+        }
+    };
+
+    warp_forward_to(&mut env.context, target).await?;
+    Ok(())
+}
+
+/// Execute a single clock-warp iteration
+pub async fn fuzz_clock_warp_once(input: ClockWarpFuzzInput) -> FuzzResult<()> {
+    // ========================================
+    // SETUP
+    // ========================================
+
+    let (mut env, setup) = setup_complete_environment(INITIAL_BALANCE, 6, input.flavor).await?;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    let vault = &setup.vault;
+    let user = &setup.user;
+    let price_history = setup_price_history(&mut env.context, &program_id, &vault.vault).await?;
+
+    let mut last_recorded_slot = 0;
+
+    for (step, operation) in input.operations.iter().take(MAX_OPERATIONS).enumerate() {
+        let vault_balance = get_token_balance(&mut env.context, &vault.vault_token_account).await?;
+        let supply = get_mint_supply(&mut env.context, &vault.share_mint).await?;
+        let user_underlying =
+            get_token_balance(&mut env.context, &user.underlying_token_account).await?;
+        let user_shares = get_token_balance(&mut env.context, &user.share_token_account).await?;
+        let clock = env.context.banks_client.get_sysvar::<Clock>().await?;
+        let context = format!(
+            "at operation {} ({:?}), slot {}, epoch {}",
+            step, operation, clock.slot, clock.epoch
+        );
+
+        match *operation {
+            ClockOperation::Deposit { amount } => {
+                let amount = amount as u64 % (user_underlying + 2);
+                let ix = deposit_ix(&program_id, vault, user, amount);
+                let result = sender.send(&mut env, ix, &user.owner).await?;
+                let expected = compute_shares(amount, supply, vault_balance);
+
+                // INVARIANT 1: deposits behave the same however much time passed
+                match (result, expected) {
+                    (Ok(()), Ok(shares)) => {
+                        let minted = get_token_balance(&mut env.context, &user.share_token_account)
+                            .await?
+                            - user_shares;
+                        assert_eq!(minted, shares, "Deposit mis-minted {}", context);
+                    }
+                    (Err(e), Ok(_)) if amount <= user_underlying => {
+                        panic!("Valid deposit rejected {}: {:?}", context, e)
+                    }
+                    (Ok(()), Err(e)) => {
+                        panic!("Deposit succeeded despite {:?} {}", e, context)
+                    }
+                    _ => {}
+                }
+            }
+            ClockOperation::Redeem { shares } => {
+                let shares = shares as u64 % (user_shares + 2);
+                let ix = redeem_ix(&program_id, vault, user, shares);
+                let result = sender.send(&mut env, ix, &user.owner).await?;
+                let expected = compute_assets(shares, supply, vault_balance);
+
+                // INVARIANT 2: redeems behave the same however much time passed
+                match (result, expected) {
+                    (Ok(()), Ok(assets)) => {
+                        let paid =
+                            get_token_balance(&mut env.context, &user.underlying_token_account)
+                                .await?
+                                - user_underlying;
+                        assert_eq!(paid, assets, "Redeem mis-paid {}", context);
+                    }
+                    (Err(e), Ok(_)) if shares <= user_shares => {
+                        panic!("Valid redeem rejected {}: {:?}", context, e)
+                    }
+                    (Ok(()), Err(e)) => {
+                        panic!("Redeem succeeded despite {:?} {}", e, context)
+                    }
+                    _ => {}
+                }
+            }
+            ClockOperation::RecordPrice => {
+                let (len_before, before) =
+                    get_latest_price_point(&mut env.context, &price_history).await?;
+                let ix = record_price_ix(&program_id, &price_history, vault);
+                let result = sender.send(&mut env, ix, &user.owner).await?;
+
+                // INVARIANT 4: a slot holds at most one observation
+                if before.is_some_and(|point| point.slot >= clock.slot) {
+                    assert!(
+                        result.is_err(),
+                        "Second observation in slot {} accepted {}",
+                        clock.slot,
+                        context
+                    );
+                    continue;
+                }
+                if let Err(e) = result {
+                    panic!("record_price rejected {}: {:?}", context, e);
+                }
+
+                // INVARIANT 3: observations carry the current slot and balances
+                let (len, point) = get_latest_price_point(&mut env.context, &price_history).await?;
+                let point = point.ok_or("Price point missing after record_price")?;
+                assert_eq!(
+                    len,
+                    (len_before + 1).min(PriceHistory::CAPACITY as u64),
+                    "History length wrong {}",
+                    context
+                );
+                let now = env.context.banks_client.get_sysvar::<Clock>().await?.slot;
+                assert!(
+                    (clock.slot..=now).contains(&point.slot),
+                    "Observation slot {} outside {}..={} {}",
+                    point.slot,
+                    clock.slot,
+                    now,
+                    context
+                );
+                assert_eq!(
+                    point.total_assets, vault_balance,
+                    "Observed assets wrong {}",
+                    context
+                );
+                assert_eq!(
+                    point.total_shares, supply,
+                    "Observed shares wrong {}",
+                    context
+                );
+
+                // ...so observations move strictly forward across warps
+                assert!(
+                    point.slot > last_recorded_slot || len_before == 0,
+                    "Observation slot went backwards from {} {}",
+                    last_recorded_slot,
+                    context
+                );
+                last_recorded_slot = point.slot;
+            }
+            ClockOperation::Warp(kind) => {
+                warp(&mut env, kind).await?;
+
+                // INVARIANT 5: time alone never moves funds
+                assert_eq!(
+                    get_token_balance(&mut env.context, &vault.vault_token_account).await?,
+                    vault_balance,
+                    "Warp changed the vault balance {}",
+                    context
+                );
+                assert_eq!(
+                    get_mint_supply(&mut env.context, &vault.share_mint).await?,
+                    supply,
+                    "Warp changed the share supply {}",
+                    context
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
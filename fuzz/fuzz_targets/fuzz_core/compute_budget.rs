@@ -0,0 +1,298 @@
+//! Compute budget chaos.
+//!
+//! Deposits and redeems are sent with fuzzed `set_compute_unit_limit` and
+//! `set_compute_unit_price` instructions prepended. Each operation is first
+//! simulated without them, which gives the outcome and the compute units the
+//! operation needs. With the budget instructions:
+//!
+//! - a limit comfortably above what the operation needs, or no limit at all,
+//!   must not change the outcome
+//! - a limit below it must fail with `ComputationalBudgetExceeded`, never with
+//!   another error or a partial effect
+//! - anything in between may go either way, but only fail on the budget
+//!
+//! So a handler that takes a different path when it runs close to the limit
+//! (logging, fallbacks, early returns) shows up as a divergent outcome.
+
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{Instruction, InstructionError},
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use vault_invariants::{assert_exchange, Exchange};
+
+use super::harness::Harness;
+use super::scenarios;
+use crate::*;
+
+/// Upper bound on operations per input, keeps a single iteration short
+const MAX_OPERATIONS: usize = 16;
+
+/// Number of users operations are spread across
+const USERS: usize = 3;
+
+/// Fuzzed limits are reduced below this, around what a deposit or redeem
+/// needs; any limit above what an operation uses behaves the same
+const MAX_FUZZED_LIMIT: u32 = 100_000;
+
+/// Fuzzed prices are reduced below this (micro-lamports per compute unit), so
+/// the fee always fits in the signer's lamports
+const MAX_FUZZED_PRICE: u64 = 1_000_000;
+
+/// Compute units each compute budget instruction consumes itself
+const BUDGET_IX_UNITS: u64 = 150;
+
+/// Headroom above the simulated units under which a limit counts as tight
+const TIGHT_MARGIN: u64 = 1_000;
+
+/// Compute budget instructions prepended to one operation
+#[derive(Debug, Clone, Copy, Default, Arbitrary, Serialize, Deserialize)]
+pub struct BudgetChaos {
+    /// `set_compute_unit_limit`, reduced below `MAX_FUZZED_LIMIT`
+    pub unit_limit: Option<u32>,
+    /// `set_compute_unit_price`, reduced below `MAX_FUZZED_PRICE`
+    pub unit_price: Option<u64>,
+}
+
+impl BudgetChaos {
+    pub fn limit(&self) -> Option<u32> {
+        self.unit_limit.map(|limit| limit % MAX_FUZZED_LIMIT)
+    }
+
+    pub fn price(&self) -> Option<u64> {
+        self.unit_price.map(|price| price % MAX_FUZZED_PRICE)
+    }
+
+    /// The compute budget instructions, in front of `ix`
+    pub fn wrap(&self, ix: Instruction) -> Vec<Instruction> {
+        let mut instructions = Vec::new();
+        if let Some(limit) = self.limit() {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+        }
+        if let Some(price) = self.price() {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        instructions.push(ix);
+        instructions
+    }
+}
+
+/// A vault operation and the budget it is sent with
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub enum Operation {
+    /// A user deposits (amount is reduced modulo their balance + 2)
+    Deposit {
+        user: u8,
+        amount: u64,
+        budget: BudgetChaos,
+    },
+    /// A user redeems (shares are reduced modulo their position + 2)
+    Redeem {
+        user: u8,
+        shares: u64,
+        budget: BudgetChaos,
+    },
+    /// Yield is minted straight into the vault, bypassing deposit
+    Donate { amount: u32 },
+}
+
+/// Fuzzable input for compute budget chaos
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct ComputeBudgetFuzzInput {
+    /// Token program the vault and its accounts belong to
+    #[serde(default)]
+    pub flavor: TokenFlavor,
+    /// Initial balance of every user (for setup)
+    pub initial_balance: u64,
+    /// Operations executed in order against one environment
+    pub operations: Vec<Operation>,
+}
+
+/// The error of the failing instruction, whatever its index
+fn instruction_error(error: &TransactionError) -> Option<&InstructionError> {
+    match error {
+        TransactionError::InstructionError(_, e) => Some(e),
+        _ => None,
+    }
+}
+
+fn banks_transaction_error(error: &BanksClientError) -> Option<&TransactionError> {
+    match error {
+        BanksClientError::TransactionError(e)
+        | BanksClientError::SimulationError { err: e, .. } => Some(e),
+        _ => None,
+    }
+}
+
+/// Outcome and compute units of `ix` sent on its own
+async fn simulate_plain(
+    harness: &mut Harness,
+    ix: &Instruction,
+    user: &UserAccounts,
+) -> FuzzResult<(Result<(), TransactionError>, u64)> {
+    let context = &mut harness.env.context;
+    loop {
+        let tx = Transaction::new_signed_with_payer(
+            std::slice::from_ref(ix),
+            Some(&user.owner.pubkey()),
+            &[&user.owner],
+            context.last_blockhash,
+        );
+        let simulation = context.banks_client.simulate_transaction(tx).await?;
+        let result = simulation.result.ok_or("Simulation returned no result")?;
+
+        // The same operation was already sent with this blockhash
+        if result == Err(TransactionError::AlreadyProcessed) {
+            context.last_blockhash = context.get_new_latest_blockhash().await?;
+            continue;
+        }
+
+        let units = simulation
+            .simulation_details
+            .map(|details| details.units_consumed)
+            .unwrap_or_default();
+        return Ok((result, units));
+    }
+}
+
+/// Sends `ix` as `user` under `budget` and checks the outcome against the
+/// same instruction simulated without it
+async fn send_with_budget(
+    harness: &mut Harness,
+    index: usize,
+    ix: Instruction,
+    budget: BudgetChaos,
+    exchange: Exchange,
+) -> FuzzResult<()> {
+    let user = harness.user(index).clone();
+    let (plain, units) = simulate_plain(harness, &ix, &user).await?;
+
+    let instructions = budget.wrap(ix);
+    let budget_units = BUDGET_IX_UNITS * (instructions.len() as u64 - 1);
+    let needed = units + budget_units;
+
+    let before = harness.balances(index).await?;
+    let result = harness
+        .sender
+        .send_instructions(&mut harness.env, &instructions, &[&user.owner])
+        .await?;
+    let after = harness.balances(index).await?;
+
+    if budget.price().is_some() {
+        scenarios::hit("PRIORITY_FEE");
+    }
+    let (comfortable, starved) = match budget.limit().map(u64::from) {
+        Some(limit) => (limit >= needed + TIGHT_MARGIN, limit < units),
+        None => (true, false),
+    };
+
+    match result {
+        Ok(()) => {
+            assert!(
+                plain.is_ok(),
+                "Succeeded under {:?} but fails without it: {:?}",
+                budget,
+                plain
+            );
+            assert!(
+                !starved,
+                "Succeeded with a limit of {:?} CU below the {} it needs",
+                budget.limit(),
+                units
+            );
+            assert_exchange(&before, &after, exchange);
+            scenarios::hit(if comfortable {
+                "WITHIN_BUDGET"
+            } else {
+                "TIGHT_BUDGET"
+            });
+        }
+        Err(e) => {
+            assert_eq!(before, after, "Failed under {:?} but moved funds", budget);
+            let error = banks_transaction_error(&e)
+                .and_then(instruction_error)
+                .cloned();
+            match (&plain, error) {
+                (_, Some(InstructionError::ComputationalBudgetExceeded)) => {
+                    assert!(
+                        !comfortable,
+                        "Ran out of compute with a limit of {:?} CU, {} needed",
+                        budget.limit(),
+                        needed
+                    );
+                    scenarios::hit("BUDGET_EXCEEDED");
+                }
+                (Err(plain), error) => {
+                    assert!(!starved, "Starved operation failed with {:?}", error);
+                    assert_eq!(
+                        instruction_error(plain),
+                        error.as_ref(),
+                        "Failure changed under {:?}",
+                        budget
+                    );
+                    scenarios::hit("SAME_FAILURE");
+                }
+                (Ok(()), error) => panic!(
+                    "Failed under {:?} with {:?} but succeeds without it ({} CU)",
+                    budget, error, units
+                ),
+            }
+        }
+    }
+    Ok(())
+}
+
+vault_fuzz_target! {
+    /// Execute a single fuzz iteration with compute budget chaos
+    pub async fn fuzz_compute_budget_once(input: ComputeBudgetFuzzInput, harness) {
+        setup: FuzzEnvBuilder::new()
+            .flavor(input.flavor)
+            .users(USERS)
+            .balance(input.initial_balance % 1_000_000_000_000_000),
+        scenario: {
+            for operation in input.operations.into_iter().take(MAX_OPERATIONS) {
+                match operation {
+                    Operation::Deposit {
+                        user,
+                        amount,
+                        budget,
+                    } => {
+                        let index = user as usize;
+                        let balance = harness.balances(index).await?.user_balance;
+                        let amount = amount % (balance + 2);
+                        let ix = deposit_ix(
+                            &harness.program_id(),
+                            harness.vault(),
+                            harness.user(index),
+                            amount,
+                        );
+                        let exchange = Exchange::Deposit { amount };
+                        send_with_budget(&mut harness, index, ix, budget, exchange).await?;
+                    }
+                    Operation::Redeem {
+                        user,
+                        shares,
+                        budget,
+                    } => {
+                        let index = user as usize;
+                        let position = harness.balances(index).await?.user_shares;
+                        let shares = shares % (position + 2);
+                        let ix = redeem_ix(
+                            &harness.program_id(),
+                            harness.vault(),
+                            harness.user(index),
+                            shares,
+                        );
+                        let exchange = Exchange::Redeem { shares };
+                        send_with_budget(&mut harness, index, ix, budget, exchange).await?;
+                    }
+                    Operation::Donate { amount } => harness.donate(amount as u64).await?,
+                }
+            }
+        }
+    }
+}
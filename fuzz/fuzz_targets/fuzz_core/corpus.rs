@@ -0,0 +1,182 @@
+//! Corpus format bridge between fuzzing engines.
+//!
+//! libFuzzer, honggfuzz and AFL++ all store corpora as flat directories of raw
+//! byte files, but they differ in file naming, size limits and where the
+//! interesting inputs end up (`corpus/<target>`, `hfuzz_workspace/<target>/input`,
+//! `<out>/default/queue`). Structured inputs are decoded from those bytes the
+//! same way libFuzzer does it, so one engine's findings are valid seeds for the
+//! others once copied across with [`bridge`].
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+use arbitrary::{Arbitrary, Unstructured};
+use serde::Serialize;
+
+use super::scenarios;
+use crate::FuzzResult;
+
+/// Largest input AFL++ accepts by default
+pub const AFL_MAX_LEN: usize = 1024 * 1024;
+
+/// Decodes a structured fuzz input from raw corpus bytes.
+///
+/// Uses `arbitrary_take_rest`, matching `libfuzzer_sys::fuzz_target!`, so the
+/// same file always produces the same input regardless of the engine.
+pub fn decode<'a, T: Arbitrary<'a>>(data: &'a [u8]) -> Option<T> {
+    T::arbitrary_take_rest(Unstructured::new(data)).ok()
+}
+
+/// Maps a fuzz binary (or its corpus directory) to the `fuzz_core` module
+/// whose body it runs, e.g. `fuzz_deposit_libfuzzer` to `deposit`
+pub fn target_module(binary: &str) -> &str {
+    let name = binary.strip_prefix("fuzz_").unwrap_or(binary);
+    let name = name
+        .strip_suffix("_libfuzzer")
+        .or_else(|| name.strip_suffix("_afl"))
+        .unwrap_or(name);
+    match name {
+        "transfer_ownership" => "authorization",
+        "all_instructions" => "sequence",
+        other => other,
+    }
+}
+
+/// Runs the body of `target` on raw corpus bytes, decoded as every engine
+/// decodes them and seeded as the wrappers seed them
+pub fn replay(target: &str, data: &[u8]) -> FuzzResult<()> {
+    fn from_bytes<'a, T: Arbitrary<'a> + Serialize>(data: &'a [u8]) -> FuzzResult<T> {
+        let input = decode(data).ok_or("Input does not decode")?;
+        crate::seed::seed_from_input(&input);
+        Ok(input)
+    }
+
+    dispatch_target!(target, from_bytes(data))
+}
+
+/// Result of a [`bridge`] run
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BridgeStats {
+    /// Files written to the destination
+    pub copied: usize,
+    /// Files skipped because identical content was already present
+    pub duplicates: usize,
+    /// Files skipped because they exceed the size limit
+    pub oversized: usize,
+}
+
+/// Copies every regular file in `src` into `dst`, deduplicating by content and
+/// dropping inputs larger than `max_len`.
+///
+/// Files are renamed to a content hash so repeated bridging between the same
+/// directories is idempotent. Dotfiles and engine metadata (`README.txt`,
+/// `.state`, ...) are skipped.
+pub fn bridge(src: &Path, dst: &Path, max_len: usize) -> io::Result<BridgeStats> {
+    fs::create_dir_all(dst)?;
+
+    let mut seen = HashSet::new();
+    for entry in fs::read_dir(dst)? {
+        let path = entry?.path();
+        if path.is_file() {
+            seen.insert(content_hash(&fs::read(path)?));
+        }
+    }
+
+    let mut stats = BridgeStats::default();
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if !path.is_file() || name.starts_with('.') || name == "README.txt" {
+            continue;
+        }
+
+        let data = fs::read(&path)?;
+        if data.len() > max_len {
+            stats.oversized += 1;
+            continue;
+        }
+
+        let hash = content_hash(&data);
+        if !seen.insert(hash) {
+            stats.duplicates += 1;
+            continue;
+        }
+
+        fs::write(dst.join(format!("{:016x}", hash)), data)?;
+        stats.copied += 1;
+    }
+
+    Ok(stats)
+}
+
+/// Result of a [`minimize`] run
+#[derive(Debug, Default, Clone)]
+pub struct MinimizeStats {
+    /// Inputs replayed
+    pub inputs: usize,
+    /// Inputs written, one per distinct scenario set
+    pub kept: usize,
+    /// Number of inputs reaching each scenario set, keyed by the set
+    pub signatures: BTreeMap<String, usize>,
+}
+
+/// Replays every input in `src` through `target` and writes to `dst` the
+/// shortest input for each distinct set of scenarios reached.
+///
+/// The set includes how the iteration ended (`OK`, `ERROR` or `PANIC`), so
+/// inputs that fail differently are all kept. Files are named by content hash,
+/// as [`bridge`] names them.
+pub fn minimize(src: &Path, dst: &Path, target: &str) -> io::Result<MinimizeStats> {
+    let mut stats = MinimizeStats::default();
+    let mut best: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+
+    let mut paths: Vec<_> = fs::read_dir(src)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let data = fs::read(&path)?;
+        stats.inputs += 1;
+
+        scenarios::take();
+        let outcome = match panic::catch_unwind(AssertUnwindSafe(|| replay(target, &data))) {
+            Ok(Ok(())) => "OK",
+            Ok(Err(_)) => "ERROR",
+            Err(_) => "PANIC",
+        };
+        let mut reached = scenarios::take();
+        reached.insert(outcome);
+        let signature = reached.into_iter().collect::<Vec<_>>().join(",");
+
+        *stats.signatures.entry(signature.clone()).or_default() += 1;
+        match best.get(&signature) {
+            Some(kept) if (kept.len(), content_hash(kept)) <= (data.len(), content_hash(&data)) => {
+            }
+            _ => {
+                best.insert(signature, data);
+            }
+        }
+    }
+
+    fs::create_dir_all(dst)?;
+    for data in best.values() {
+        fs::write(dst.join(format!("{:016x}", content_hash(data))), data)?;
+    }
+    stats.kept = best.len();
+
+    Ok(stats)
+}
+
+fn content_hash(data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
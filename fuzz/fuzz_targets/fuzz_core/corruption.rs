@@ -0,0 +1,317 @@
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    account::AccountSharedData, instruction::InstructionError, pubkey::Pubkey, signature::Signer,
+    transaction::TransactionError,
+};
+use vault_math::{compute_assets, compute_shares};
+use vault_pda_client::{try_deserialize_any, AccountType};
+
+use super::sequence::Sender;
+use crate::*;
+
+/// Underlying balance the user starts with
+const INITIAL_BALANCE: u64 = 1_000_000;
+
+/// Position opened before the corruption, so redeems have shares to burn
+const POSITION: u64 = 100_000;
+
+/// Account whose data gets corrupted
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum CorruptionTarget {
+    Vault,
+    ProtocolState,
+    VaultAuthority,
+    VaultTokenAccount,
+    ShareMint,
+    UserUnderlying,
+    UserShares,
+}
+
+/// How the account data gets corrupted
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub enum Corruption {
+    /// XOR `count` consecutive bytes starting at `offset` with `mask`
+    FlipBytes { offset: u16, mask: u8, count: u8 },
+    /// Cut the data down to `len` bytes
+    Truncate { len: u16 },
+    /// Overwrite the data with bytes repeated from `pattern`
+    Garbage { pattern: Vec<u8> },
+    /// Append bytes after the existing data
+    Extend { extra: Vec<u8> },
+    /// Zero the first eight bytes (the Anchor discriminator)
+    ZeroDiscriminator,
+    /// Reassign the account to the system program
+    ChangeOwner,
+}
+
+/// Instruction invoked against the corrupted account
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum CorruptedInstruction {
+    Deposit,
+    Redeem,
+    TransferOwnership,
+}
+
+/// Fuzzable input for account-data corruption
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct CorruptionFuzzInput {
+    pub target: CorruptionTarget,
+    pub corruption: Corruption,
+    pub instruction: CorruptedInstruction,
+    /// Amount or shares (reduced below the opened position)
+    pub amount: u32,
+}
+
+/// Balances the program reads and writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Balances {
+    vault: u64,
+    supply: u64,
+    user_underlying: u64,
+    user_shares: u64,
+}
+
+/// Reads all balances, `None` if any account is corrupted past parsing
+async fn balances(env: &mut FuzzTestEnv, setup: &CompleteSetup) -> Option<Balances> {
+    Some(Balances {
+        vault: get_token_balance(&mut env.context, &setup.vault.vault_token_account)
+            .await
+            .ok()?,
+        supply: get_mint_supply(&mut env.context, &setup.vault.share_mint)
+            .await
+            .ok()?,
+        user_underlying: get_token_balance(&mut env.context, &setup.user.underlying_token_account)
+            .await
+            .ok()?,
+        user_shares: get_token_balance(&mut env.context, &setup.user.share_token_account)
+            .await
+            .ok()?,
+    })
+}
+
+fn corrupt(account: &mut AccountSharedData, corruption: &Corruption) {
+    let mut data = account.data().to_vec();
+    match corruption {
+        Corruption::FlipBytes {
+            offset,
+            mask,
+            count,
+        } => {
+            if !data.is_empty() {
+                let start = *offset as usize % data.len();
+                let end = (start + *count as usize + 1).min(data.len());
+                for byte in &mut data[start..end] {
+                    *byte ^= mask | 1;
+                }
+            }
+        }
+        Corruption::Truncate { len } => data.truncate(*len as usize % data.len().max(1)),
+        Corruption::Garbage { pattern } => {
+            if !pattern.is_empty() {
+                for (byte, value) in data.iter_mut().zip(pattern.iter().cycle()) {
+                    *byte = *value;
+                }
+            }
+        }
+        Corruption::Extend { extra } => data.extend_from_slice(extra),
+        Corruption::ZeroDiscriminator => {
+            let end = data.len().min(8);
+            data[..end].fill(0);
+        }
+        Corruption::ChangeOwner => account.set_owner(solana_sdk::system_program::ID),
+    }
+    account.set_data_from_slice(&data);
+}
+
+/// Whether the program aborted instead of returning an error
+pub(crate) fn is_panic(error: &BanksClientError) -> bool {
+    matches!(
+        error,
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::ProgramFailedToComplete
+        )) | BanksClientError::SimulationError {
+            err: TransactionError::InstructionError(_, InstructionError::ProgramFailedToComplete),
+            ..
+        }
+    )
+}
+
+/// Execute a single account-data corruption iteration
+pub async fn fuzz_corruption_once(input: CorruptionFuzzInput) -> FuzzResult<()> {
+    let amount = input.amount as u64 % POSITION + 1;
+
+    // ========================================
+    // SETUP: one open position
+    // ========================================
+
+    let (mut env, setup) =
+        setup_complete_environment(INITIAL_BALANCE, 6, TokenFlavor::SplToken).await?;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+
+    let ix = deposit_ix(&program_id, &setup.vault, &setup.user, POSITION);
+    if let Err(e) = sender.send(&mut env, ix, &setup.user.owner).await? {
+        return Err(e.into());
+    }
+
+    // ========================================
+    // CORRUPT
+    // ========================================
+
+    let target: Pubkey = match input.target {
+        CorruptionTarget::Vault => setup.vault.vault,
+        CorruptionTarget::ProtocolState => setup.protocol.protocol_state,
+        CorruptionTarget::VaultAuthority => setup.protocol.vault_authority,
+        CorruptionTarget::VaultTokenAccount => setup.vault.vault_token_account,
+        CorruptionTarget::ShareMint => setup.vault.share_mint,
+        CorruptionTarget::UserUnderlying => setup.user.underlying_token_account,
+        CorruptionTarget::UserShares => setup.user.share_token_account,
+    };
+
+    let mut account: AccountSharedData = env
+        .context
+        .banks_client
+        .get_account(target)
+        .await?
+        .ok_or("Target account not found")?
+        .into();
+    corrupt(&mut account, &input.corruption);
+    env.context.set_account(&target, &account);
+
+    let before = balances(&mut env, &setup).await;
+
+    // ========================================
+    // EXECUTE
+    // ========================================
+
+    let (ix, signer) = match input.instruction {
+        CorruptedInstruction::Deposit => (
+            deposit_ix(&program_id, &setup.vault, &setup.user, amount),
+            &setup.user.owner,
+        ),
+        CorruptedInstruction::Redeem => (
+            redeem_ix(&program_id, &setup.vault, &setup.user, amount),
+            &setup.user.owner,
+        ),
+        CorruptedInstruction::TransferOwnership => (
+            transfer_ownership_ix(&program_id, &setup.protocol.owner, &new_keypair().pubkey()),
+            &setup.protocol.owner_keypair,
+        ),
+    };
+    let result = sender.send(&mut env, ix, signer).await?;
+    let after = balances(&mut env, &setup).await;
+
+    // ========================================
+    // INVARIANT CHECKS
+    // ========================================
+
+    // INVARIANT 1: corrupted input is an error, never an abort
+    if let Err(e) = &result {
+        assert!(
+            !is_panic(e),
+            "Program panicked on corrupted {:?} ({:?}): {:?}",
+            input.target,
+            input.corruption,
+            e
+        );
+        return Ok(());
+    }
+
+    // INVARIANT 2: a corrupted program account the instruction read must
+    // still decode as its own type
+    let read_as = match (input.target, input.instruction) {
+        (CorruptionTarget::Vault, CorruptedInstruction::Deposit | CorruptedInstruction::Redeem) => {
+            Some(AccountType::Vault)
+        }
+        (
+            CorruptionTarget::VaultAuthority,
+            CorruptedInstruction::Deposit | CorruptedInstruction::Redeem,
+        ) => Some(AccountType::VaultAuthority),
+        (CorruptionTarget::ProtocolState, CorruptedInstruction::TransferOwnership) => {
+            Some(AccountType::ProtocolState)
+        }
+        _ => None,
+    };
+    if let Some(expected) = read_as {
+        assert!(
+            matches!(try_deserialize_any(account.data()), Ok(decoded) if decoded.account_type() == expected),
+            "{:?} accepted a {:?} that {:?} left undecodable as one",
+            input.instruction,
+            input.target,
+            input.corruption
+        );
+    }
+
+    // INVARIANT 3: nothing is credited that the corrupted state does not justify
+    let (Some(before), Some(after)) = (before, after) else {
+        panic!(
+            "{:?} succeeded although {:?} of {:?} made a token account unreadable",
+            input.instruction, input.corruption, input.target
+        );
+    };
+
+    match input.instruction {
+        CorruptedInstruction::Deposit => {
+            let expected = compute_shares(amount, before.supply, before.vault);
+            let minted = after.user_shares.wrapping_sub(before.user_shares);
+            assert_eq!(
+                before.user_underlying.wrapping_sub(after.user_underlying),
+                amount,
+                "Depositor paid the wrong amount after corrupting {:?}",
+                input.target
+            );
+            assert_eq!(
+                after.vault.wrapping_sub(before.vault),
+                amount,
+                "Vault mis-credited"
+            );
+            assert_eq!(
+                after.supply.wrapping_sub(before.supply),
+                minted,
+                "Supply and balance diverged"
+            );
+            assert_eq!(
+                Ok(minted),
+                expected,
+                "CRITICAL VULNERABILITY: corrupted {:?} ({:?}) mis-credited shares",
+                input.target,
+                input.corruption
+            );
+        }
+        CorruptedInstruction::Redeem => {
+            let expected = compute_assets(amount, before.supply, before.vault);
+            let paid = after.user_underlying.wrapping_sub(before.user_underlying);
+            assert_eq!(
+                before.user_shares.wrapping_sub(after.user_shares),
+                amount,
+                "Redeemer burned the wrong amount after corrupting {:?}",
+                input.target
+            );
+            assert_eq!(
+                before.supply.wrapping_sub(after.supply),
+                amount,
+                "Supply mis-burned"
+            );
+            assert_eq!(
+                before.vault.wrapping_sub(after.vault),
+                paid,
+                "Vault and balance diverged"
+            );
+            assert_eq!(
+                Ok(paid),
+                expected,
+                "CRITICAL VULNERABILITY: corrupted {:?} ({:?}) mis-credited assets",
+                input.target,
+                input.corruption
+            );
+        }
+        CorruptedInstruction::TransferOwnership => {
+            assert_eq!(before, after, "transfer_ownership moved funds");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,281 @@
+use anchor_lang::{InstructionData, ToAccountMetas};
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{instruction::Instruction, signature::Signer};
+use vault_math::{compute_assets, compute_shares};
+
+use super::corruption::is_panic;
+use super::sequence::Sender;
+use crate::*;
+
+/// Underlying balance the user starts with in each vault
+const INITIAL_BALANCE: u64 = 1_000_000;
+
+/// Position opened in each vault before the fuzzed operations
+const POSITION: u64 = 10_000;
+
+/// Upper bound on operations per input
+const MAX_OPERATIONS: usize = 16;
+
+/// Vault an account is taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Arbitrary, Serialize, Deserialize)]
+pub enum Side {
+    A,
+    B,
+}
+
+/// A deposit or redeem whose accounts are each taken from vault A or vault B
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct CrossVaultOperation {
+    pub redeem: bool,
+    /// Amount or shares (reduced to 1..=1000)
+    pub amount: u16,
+    pub vault: Side,
+    pub underlying_mint: Side,
+    pub vault_token_account: Side,
+    pub share_mint: Side,
+    pub user_underlying: Side,
+    pub user_shares: Side,
+}
+
+impl CrossVaultOperation {
+    /// The vault every account belongs to, if they all agree
+    fn target(&self) -> Option<Side> {
+        let sides = [
+            self.vault,
+            self.underlying_mint,
+            self.vault_token_account,
+            self.share_mint,
+            self.user_underlying,
+            self.user_shares,
+        ];
+        sides
+            .iter()
+            .all(|side| *side == self.vault)
+            .then_some(self.vault)
+    }
+}
+
+/// Fuzzable input for operations mixing accounts of two vaults
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct CrossVaultFuzzInput {
+    /// Token program the vault and its accounts belong to
+    #[serde(default)]
+    pub flavor: TokenFlavor,
+    pub operations: Vec<CrossVaultOperation>,
+}
+
+/// Balances of one vault and the user's accounts for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct VaultBalances {
+    vault: u64,
+    supply: u64,
+    user_underlying: u64,
+    user_shares: u64,
+}
+
+async fn balances(
+    env: &mut FuzzTestEnv,
+    vault: &VaultAccounts,
+    user: &UserAccounts,
+) -> FuzzResult<VaultBalances> {
+    Ok(VaultBalances {
+        vault: get_token_balance(&mut env.context, &vault.vault_token_account).await?,
+        supply: get_mint_supply(&mut env.context, &vault.share_mint).await?,
+        user_underlying: get_token_balance(&mut env.context, &user.underlying_token_account)
+            .await?,
+        user_shares: get_token_balance(&mut env.context, &user.share_token_account).await?,
+    })
+}
+
+/// Execute a single cross-vault iteration
+pub async fn fuzz_cross_vault_once(input: CrossVaultFuzzInput) -> FuzzResult<()> {
+    // ========================================
+    // SETUP: two vaults, one position in each
+    // ========================================
+
+    let (mut env, two) = setup_two_vault_environment(INITIAL_BALANCE, 6, input.flavor).await?;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    let owner = &two.a.user.owner;
+    let sides = [(&two.a.vault, &two.a.user), (&two.vault_b, &two.user_b)];
+
+    for (vault, user) in sides {
+        let ix = deposit_ix(&program_id, vault, user, POSITION);
+        if let Err(e) = sender.send(&mut env, ix, owner).await? {
+            return Err(e.into());
+        }
+    }
+
+    let pick = |side: Side| match side {
+        Side::A => sides[0],
+        Side::B => sides[1],
+    };
+
+    for (step, operation) in input.operations.iter().take(MAX_OPERATIONS).enumerate() {
+        let amount = operation.amount as u64 % 1_000 + 1;
+        let before = [
+            balances(&mut env, sides[0].0, sides[0].1).await?,
+            balances(&mut env, sides[1].0, sides[1].1).await?,
+        ];
+
+        // ========================================
+        // EXECUTE
+        // ========================================
+
+        let vault = pick(operation.vault).0;
+        let vault_authority = derive_vault_authority_pda(&program_id).0;
+        let flag = derive_flag_pda(&program_id, &vault.vault).0;
+        let player_record = derive_player_pda(&program_id, &flag, &owner.pubkey()).0;
+        let activity_stats = derive_activity_stats_pda(&program_id, &vault.vault).0;
+        let reserve_config = derive_reserve_config_pda(&program_id, &vault.vault).0;
+        let ix = if operation.redeem {
+            let accounts = vault_pda::accounts::Redeem {
+                vault: vault.vault,
+                underlying_mint: pick(operation.underlying_mint).0.underlying_mint,
+                vault_token_account: pick(operation.vault_token_account).0.vault_token_account,
+                share_mint: pick(operation.share_mint).0.share_mint,
+                vault_authority,
+                redeemer_underlying_account: pick(operation.user_underlying)
+                    .1
+                    .underlying_token_account,
+                redeemer_share_account: pick(operation.user_shares).1.share_token_account,
+                redeemer: owner.pubkey(),
+                flag,
+                player_record,
+                activity_stats,
+                reserve_config,
+                treasury: None,
+                protocol_state: None,
+                fee_exemption: derive_fee_exemption_pda(&program_id, &owner.pubkey()).0,
+                token_program: vault.token_program,
+            };
+            Instruction {
+                program_id,
+                accounts: accounts.to_account_metas(None),
+                data: vault_pda::instruction::Redeem { shares: amount }.data(),
+            }
+        } else {
+            let accounts = vault_pda::accounts::Deposit {
+                vault: vault.vault,
+                underlying_mint: pick(operation.underlying_mint).0.underlying_mint,
+                vault_token_account: pick(operation.vault_token_account).0.vault_token_account,
+                share_mint: pick(operation.share_mint).0.share_mint,
+                vault_authority,
+                depositor_underlying_account: pick(operation.user_underlying)
+                    .1
+                    .underlying_token_account,
+                depositor_share_account: pick(operation.user_shares).1.share_token_account,
+                depositor: owner.pubkey(),
+                flag,
+                player_record,
+                activity_stats,
+                reserve_config,
+                treasury: None,
+                protocol_state: None,
+                token_program: vault.token_program,
+            };
+            Instruction {
+                program_id,
+                accounts: accounts.to_account_metas(None),
+                data: vault_pda::instruction::Deposit { amount }.data(),
+            }
+        };
+
+        let result = sender.send(&mut env, ix, owner).await?;
+        let after = [
+            balances(&mut env, sides[0].0, sides[0].1).await?,
+            balances(&mut env, sides[1].0, sides[1].1).await?,
+        ];
+        let context = format!("at operation {} ({:?})", step, operation);
+
+        // ========================================
+        // INVARIANT CHECKS
+        // ========================================
+
+        let Some(target) = operation.target() else {
+            // INVARIANT 1: any mix of the two vaults' accounts is rejected
+            if result.is_ok() {
+                panic!(
+                    "CRITICAL VULNERABILITY: cross-vault operation accepted {}: {:?} -> {:?}",
+                    context, before, after
+                );
+            }
+            // INVARIANT 2: a rejected mix is an error and leaves both vaults untouched
+            if let Err(e) = &result {
+                assert!(!is_panic(e), "Program panicked {}: {:?}", context, e);
+            }
+            assert_eq!(before, after, "Rejected operation moved funds {}", context);
+            continue;
+        };
+
+        let (index, other) = match target {
+            Side::A => (0, 1),
+            Side::B => (1, 0),
+        };
+
+        // INVARIANT 3: an operation on one vault never touches the other
+        assert_eq!(
+            before[other], after[other],
+            "Operation on vault {:?} touched the other vault {}",
+            target, context
+        );
+
+        let (before, after) = (before[index], after[index]);
+        match result {
+            Ok(()) if operation.redeem => {
+                let paid = after.user_underlying - before.user_underlying;
+                assert_eq!(
+                    Ok(paid),
+                    compute_assets(amount, before.supply, before.vault),
+                    "Redeem mis-paid {}",
+                    context
+                );
+                assert_eq!(
+                    before.vault - after.vault,
+                    paid,
+                    "Vault mis-debited {}",
+                    context
+                );
+                assert_eq!(before.user_shares - after.user_shares, amount);
+                assert_eq!(before.supply - after.supply, amount);
+            }
+            Ok(()) => {
+                let minted = after.user_shares - before.user_shares;
+                assert_eq!(
+                    Ok(minted),
+                    compute_shares(amount, before.supply, before.vault),
+                    "Deposit mis-minted {}",
+                    context
+                );
+                assert_eq!(
+                    after.vault - before.vault,
+                    amount,
+                    "Vault mis-credited {}",
+                    context
+                );
+                assert_eq!(before.user_underlying - after.user_underlying, amount);
+                assert_eq!(after.supply - before.supply, minted);
+            }
+            Err(e) => {
+                assert!(!is_panic(&e), "Program panicked {}: {:?}", context, e);
+                assert_eq!(before, after, "Rejected operation moved funds {}", context);
+                // INVARIANT 4: a well-formed operation within the user's means succeeds
+                let affordable = if operation.redeem {
+                    amount <= before.user_shares
+                        && compute_assets(amount, before.supply, before.vault).is_ok()
+                } else {
+                    amount <= before.user_underlying
+                        && compute_shares(amount, before.supply, before.vault).is_ok()
+                };
+                assert!(
+                    !affordable,
+                    "Well-formed operation on vault {:?} rejected {}: {:?}",
+                    target, context, e
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
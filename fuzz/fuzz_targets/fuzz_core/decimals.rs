@@ -0,0 +1,198 @@
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    account::AccountSharedData, instruction::InstructionError, transaction::TransactionError,
+};
+use spl_token::error::TokenError;
+use vault_math::{compute_assets, compute_shares};
+
+use super::corruption::is_panic;
+use super::sequence::Sender;
+use crate::*;
+
+/// Underlying balance the user starts with, above the sum of all deposits
+const INITIAL_BALANCE: u64 = 1 << 40;
+
+/// Upper bound on a single deposit
+const MAX_DEPOSIT: u64 = 1 << 32;
+
+/// Upper bound on deposits per input
+const MAX_DEPOSITS: usize = 4;
+
+/// Offset of `decimals` in the SPL Token mint layout
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+/// Decimals of a mint, biased towards the extremes
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum DecimalsChoice {
+    Zero,
+    Max,
+    Value(u8),
+}
+
+impl DecimalsChoice {
+    fn decimals(self) -> u8 {
+        match self {
+            DecimalsChoice::Zero => 0,
+            DecimalsChoice::Max => u8::MAX,
+            DecimalsChoice::Value(decimals) => decimals,
+        }
+    }
+}
+
+/// Fuzzable input for decimals mismatches and extreme decimals
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct DecimalsFuzzInput {
+    /// Token program the vault and its accounts belong to
+    #[serde(default)]
+    pub flavor: TokenFlavor,
+    pub underlying_decimals: DecimalsChoice,
+    /// Decimals written over the share mint after initialization, simulating
+    /// a vault configured with share decimals that differ from the underlying
+    pub share_decimals: Option<DecimalsChoice>,
+    /// Deposit amounts (reduced to 1..=2^32)
+    pub deposits: Vec<u64>,
+    /// Fraction of the held shares to redeem, in 256ths (plus one)
+    pub redeem_fraction: u8,
+}
+
+/// Whether a token transfer was rejected because it named the wrong decimals
+fn is_decimals_mismatch(error: &BanksClientError) -> bool {
+    let code = TokenError::MintDecimalsMismatch as u32;
+    matches!(
+        error,
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(c)
+        )) if *c == code
+    )
+}
+
+/// Execute a single decimals iteration
+pub async fn fuzz_decimals_once(input: DecimalsFuzzInput) -> FuzzResult<()> {
+    let decimals = input.underlying_decimals.decimals();
+
+    // ========================================
+    // SETUP
+    // ========================================
+
+    let (mut env, setup) =
+        setup_complete_environment(INITIAL_BALANCE, decimals, input.flavor).await?;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    let vault = &setup.vault;
+    let user = &setup.user;
+
+    // INVARIANT 1: the share mint inherits the underlying decimals, whatever they are
+    let share_mint = env
+        .context
+        .banks_client
+        .get_account(vault.share_mint)
+        .await?
+        .ok_or("Share mint missing")?;
+    assert_eq!(
+        share_mint.data[MINT_DECIMALS_OFFSET], decimals,
+        "Share mint decimals differ from the underlying"
+    );
+
+    if let Some(share_decimals) = input.share_decimals {
+        let mut account: AccountSharedData = share_mint.into();
+        let mut data = account.data().to_vec();
+        data[MINT_DECIMALS_OFFSET] = share_decimals.decimals();
+        account.set_data_from_slice(&data);
+        env.context.set_account(&vault.share_mint, &account);
+    }
+    let context = format!(
+        "(underlying decimals {}, share decimals {:?})",
+        decimals,
+        input.share_decimals.map(DecimalsChoice::decimals)
+    );
+
+    // ========================================
+    // DEPOSITS
+    // ========================================
+
+    for amount in input.deposits.iter().take(MAX_DEPOSITS) {
+        let amount = amount % MAX_DEPOSIT + 1;
+        let vault_before = get_token_balance(&mut env.context, &vault.vault_token_account).await?;
+        let supply_before = get_mint_supply(&mut env.context, &vault.share_mint).await?;
+        let shares_before = get_token_balance(&mut env.context, &user.share_token_account).await?;
+
+        let ix = deposit_ix(&program_id, vault, user, amount);
+        if let Err(e) = sender.send(&mut env, ix, &user.owner).await? {
+            // INVARIANT 2: transfer_checked always names the underlying decimals
+            assert!(
+                !is_decimals_mismatch(&e),
+                "CRITICAL VULNERABILITY: deposit passed the wrong decimals {}",
+                context
+            );
+            assert!(!is_panic(&e), "Deposit panicked {}: {:?}", context, e);
+            panic!("Deposit of {} rejected {}: {:?}", amount, context, e);
+        }
+
+        // INVARIANT 3: shares are computed in raw units, with no decimal scaling
+        let vault_after = get_token_balance(&mut env.context, &vault.vault_token_account).await?;
+        let minted =
+            get_token_balance(&mut env.context, &user.share_token_account).await? - shares_before;
+        assert_eq!(vault_after - vault_before, amount, "Vault mis-credited");
+        assert_eq!(
+            Ok(minted),
+            compute_shares(amount, supply_before, vault_before),
+            "Deposit of {} minted {} shares {}",
+            amount,
+            minted,
+            context
+        );
+    }
+
+    // ========================================
+    // REDEEM
+    // ========================================
+
+    let vault_before = get_token_balance(&mut env.context, &vault.vault_token_account).await?;
+    let supply = get_mint_supply(&mut env.context, &vault.share_mint).await?;
+    let held = get_token_balance(&mut env.context, &user.share_token_account).await?;
+    let shares = (held as u128 * (input.redeem_fraction as u128 + 1) / 256) as u64;
+    if shares == 0 {
+        return Ok(());
+    }
+    let underlying_before =
+        get_token_balance(&mut env.context, &user.underlying_token_account).await?;
+
+    let ix = redeem_ix(&program_id, vault, user, shares);
+    if let Err(e) = sender.send(&mut env, ix, &user.owner).await? {
+        assert!(
+            !is_decimals_mismatch(&e),
+            "CRITICAL VULNERABILITY: redeem passed the wrong decimals {}",
+            context
+        );
+        assert!(!is_panic(&e), "Redeem panicked {}: {:?}", context, e);
+        panic!("Redeem of {} shares rejected {}: {:?}", shares, context, e);
+    }
+
+    let paid = get_token_balance(&mut env.context, &user.underlying_token_account).await?
+        - underlying_before;
+    assert_eq!(
+        Ok(paid),
+        compute_assets(shares, supply, vault_before),
+        "Redeem of {} shares paid {} {}",
+        shares,
+        paid,
+        context
+    );
+
+    // INVARIANT 4: the sole holder redeeming everything empties the vault, so
+    // no precision is silently lost to dust
+    if shares == supply {
+        assert_eq!(
+            paid,
+            vault_before,
+            "Full redemption stranded {} tokens {}",
+            vault_before - paid,
+            context
+        );
+    }
+
+    Ok(())
+}
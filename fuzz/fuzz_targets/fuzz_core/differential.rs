@@ -0,0 +1,234 @@
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+
+use super::reference::{Quote, ReferenceVault};
+use super::sequence::Sender;
+use crate::*;
+
+/// Upper bound on operations per input, keeps a single iteration short
+const MAX_OPERATIONS: usize = 32;
+
+/// Number of depositors sharing the vault
+const USERS: u8 = 2;
+
+/// A single step applied to both the program and the reference vault
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub enum DifferentialOperation {
+    /// A user deposits (amount is reduced modulo their balance + 2)
+    Deposit { user: u8, amount: u64 },
+    /// A user redeems (shares are reduced modulo their position + 2)
+    Redeem { user: u8, shares: u64 },
+    /// Yield is minted straight into the vault, bypassing deposit
+    Donate { amount: u64 },
+}
+
+/// Fuzzable input for differential fuzzing against the reference vault
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct DifferentialFuzzInput {
+    /// Token program the vault and its accounts belong to
+    #[serde(default)]
+    pub flavor: TokenFlavor,
+    /// Token decimals (for setup)
+    pub decimals: u8,
+    /// Initial balance of every user (for setup)
+    pub initial_balance: u64,
+    /// Operations executed in order against both implementations
+    pub operations: Vec<DifferentialOperation>,
+}
+
+/// Fails unless the program's result matches the reference floor exactly,
+/// naming the rounding direction when it does not
+fn assert_matches(what: &str, actual: u64, quote: Quote, context: &str) {
+    if actual == quote.floor {
+        return;
+    }
+    if actual == quote.ceil {
+        panic!(
+            "ROUNDING DIVERGENCE: {} rounded up to {} instead of down to {} {}",
+            what, actual, quote.floor, context
+        );
+    }
+    panic!(
+        "DIVERGENCE: {} was {}, reference expects {} {}",
+        what, actual, quote.floor, context
+    );
+}
+
+/// Compares every on-chain balance against the reference vault
+async fn assert_state(
+    env: &mut FuzzTestEnv,
+    setup: &CompleteSetup,
+    users: &[&UserAccounts],
+    reference: &ReferenceVault,
+    context: &str,
+) -> FuzzResult<()> {
+    let vault_balance =
+        get_token_balance(&mut env.context, &setup.vault.vault_token_account).await?;
+    let share_supply = get_mint_supply(&mut env.context, &setup.vault.share_mint).await?;
+    assert_eq!(
+        vault_balance, reference.total_assets,
+        "Total assets diverged {}",
+        context
+    );
+    assert_eq!(
+        share_supply, reference.total_supply,
+        "Total supply diverged {}",
+        context
+    );
+
+    for (index, user) in users.iter().enumerate() {
+        let position = reference.position(index as u8);
+        let assets = get_token_balance(&mut env.context, &user.underlying_token_account).await?;
+        let shares = get_token_balance(&mut env.context, &user.share_token_account).await?;
+        assert_eq!(
+            assets, position.assets,
+            "User {} assets diverged {}",
+            index, context
+        );
+        assert_eq!(
+            shares, position.shares,
+            "User {} shares diverged {}",
+            index, context
+        );
+    }
+
+    Ok(())
+}
+
+/// Execute a single differential iteration
+pub async fn fuzz_differential_once(input: DifferentialFuzzInput) -> FuzzResult<()> {
+    let decimals = input.decimals % 19;
+    let initial_balance = input.initial_balance % 1_000_000_000_000_000;
+
+    let (mut env, setup) =
+        match setup_complete_environment(initial_balance, decimals, input.flavor).await {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("Setup failed: {}", e);
+                return Ok(());
+            }
+        };
+
+    let second = create_and_fund_user(
+        &mut env.context,
+        &setup.underlying,
+        &setup.vault.share_mint,
+        initial_balance,
+    )
+    .await?;
+    let users = [&setup.user, &second];
+
+    let mut reference = ReferenceVault::default();
+    for user in 0..USERS {
+        reference.fund(user, initial_balance);
+    }
+    let mut sender = Sender::default();
+
+    for (step, operation) in input.operations.iter().take(MAX_OPERATIONS).enumerate() {
+        let context = format!("at step {} ({:?})", step, operation);
+
+        match *operation {
+            DifferentialOperation::Deposit { user, amount } => {
+                let index = user % USERS;
+                let amount = amount % reference.position(index).assets.saturating_add(2);
+                let quote = reference.preview_deposit(amount);
+                let before = reference.position(index).shares;
+                let expected = reference.deposit(index, amount);
+
+                let ix = deposit_ix(&env.program_id, &setup.vault, users[index as usize], amount);
+                let result = sender
+                    .send(&mut env, ix, &users[index as usize].owner)
+                    .await?;
+
+                match (result, expected) {
+                    (Ok(()), Ok(_)) => {
+                        let after = get_token_balance(
+                            &mut env.context,
+                            &users[index as usize].share_token_account,
+                        )
+                        .await?;
+                        assert_matches(
+                            "shares minted",
+                            after - before,
+                            quote.expect("reference accepted the deposit"),
+                            &context,
+                        );
+                    }
+                    (Ok(()), Err(e)) => {
+                        panic!(
+                            "Program accepted a deposit the reference rejects ({:?}) {}",
+                            e, context
+                        )
+                    }
+                    (Err(e), Ok(shares)) => panic!(
+                        "Program rejected a deposit the reference accepts for {} shares: {:?} {}",
+                        shares, e, context
+                    ),
+                    (Err(_), Err(_)) => {}
+                }
+            }
+            DifferentialOperation::Redeem { user, shares } => {
+                let index = user % USERS;
+                let shares = shares % reference.position(index).shares.saturating_add(2);
+                let quote = reference.preview_redeem(shares);
+                let before = reference.position(index).assets;
+                let expected = reference.redeem(index, shares);
+
+                let ix = redeem_ix(&env.program_id, &setup.vault, users[index as usize], shares);
+                let result = sender
+                    .send(&mut env, ix, &users[index as usize].owner)
+                    .await?;
+
+                match (result, expected) {
+                    (Ok(()), Ok(_)) => {
+                        let after = get_token_balance(
+                            &mut env.context,
+                            &users[index as usize].underlying_token_account,
+                        )
+                        .await?;
+                        assert_matches(
+                            "assets returned",
+                            after - before,
+                            quote.expect("reference accepted the redeem"),
+                            &context,
+                        );
+                    }
+                    (Ok(()), Err(e)) => {
+                        panic!(
+                            "Program accepted a redeem the reference rejects ({:?}) {}",
+                            e, context
+                        )
+                    }
+                    (Err(e), Ok(assets)) => panic!(
+                        "Program rejected a redeem the reference accepts for {} assets: {:?} {}",
+                        assets, e, context
+                    ),
+                    (Err(_), Err(_)) => {}
+                }
+            }
+            DifferentialOperation::Donate { amount } => {
+                let amount = amount % 1_000_000_000_000;
+                if amount > 0
+                    && donate_to_vault(&mut env.context, &setup.underlying, &setup.vault, amount)
+                        .await
+                        .is_ok()
+                {
+                    reference
+                        .donate(amount)
+                        .expect("donation accepted on-chain overflows the reference");
+                }
+            }
+        }
+
+        assert_state(&mut env, &setup, &users, &reference, &context).await?;
+    }
+
+    println!(
+        "✓ PASS - {} operations, total_assets={}, total_supply={}",
+        input.operations.len().min(MAX_OPERATIONS),
+        reference.total_assets,
+        reference.total_supply
+    );
+
+    Ok(())
+}
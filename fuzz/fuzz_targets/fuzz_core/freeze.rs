@@ -0,0 +1,188 @@
+//! Freezing token accounts between setup and the instruction under test.
+//!
+//! The underlying mint is given a freeze authority and the depositor opens a
+//! position. Then the depositor's underlying account, the vault token account
+//! or both are frozen, and a fuzzed deposit or redeem is sent. Either account
+//! is on the transfer that operation makes, so:
+//!
+//! - the operation must fail with an error, never an abort or a success
+//! - it must leave every balance untouched, even though redeem has already
+//!   burned the shares when its transfer out of the vault is refused
+//! - once thawed, an operation that succeeds must have failed on the freeze
+//!   (`AccountFrozen`) rather than for some other reason
+//! - once thawed, the depositor can always redeem their whole position
+//!
+//! The share mint has no freeze authority, so share accounts cannot be frozen.
+
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use spl_token::error::TokenError;
+use std::fmt::Debug;
+
+use super::harness::Harness;
+use super::scenarios;
+use crate::*;
+
+/// Underlying balance the depositor starts with
+const INITIAL_BALANCE: u64 = 1_000_000;
+
+/// Position opened before anything is frozen
+const POSITION: u64 = 100_000;
+
+/// Underlying token accounts frozen before the operation
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum FreezeTarget {
+    Depositor,
+    Vault,
+    Both,
+}
+
+impl FreezeTarget {
+    fn accounts(self, vault: &VaultAccounts, user: &UserAccounts) -> Vec<Pubkey> {
+        match self {
+            FreezeTarget::Depositor => vec![user.underlying_token_account],
+            FreezeTarget::Vault => vec![vault.vault_token_account],
+            FreezeTarget::Both => vec![user.underlying_token_account, vault.vault_token_account],
+        }
+    }
+}
+
+/// The instruction under test
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum FrozenOperation {
+    /// Deposit (reduced modulo the remaining balance + 2)
+    Deposit { amount: u32 },
+    /// Redeem (reduced modulo the position + 2)
+    Redeem { shares: u32 },
+}
+
+/// Fuzzable input for a deposit or redeem against frozen accounts
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct FreezeFuzzInput {
+    /// Token program the vault and its accounts belong to
+    #[serde(default)]
+    pub flavor: TokenFlavor,
+    pub target: FreezeTarget,
+    pub operation: FrozenOperation,
+    /// Underlying minted into the vault before freezing, moving the share price
+    pub donation: Option<u16>,
+}
+
+/// How an operation ended
+enum Outcome {
+    Succeeded,
+    /// Refused by the token program because an account is frozen
+    Frozen,
+    /// Failed for any other reason
+    Rejected(String),
+}
+
+impl Outcome {
+    fn of<T, E: Debug>(result: Result<T, TxFailure<E>>) -> Self {
+        match result {
+            Ok(_) => Outcome::Succeeded,
+            Err(TxFailure::Token(TokenError::AccountFrozen)) => Outcome::Frozen,
+            Err(failure) => Outcome::Rejected(format!("{:?}", failure)),
+        }
+    }
+}
+
+/// Runs `operation` as the depositor
+///
+/// A success is checked against the exchange invariants by the harness.
+async fn run(harness: &mut Harness, operation: FrozenOperation) -> FuzzResult<Outcome> {
+    Ok(match operation {
+        FrozenOperation::Deposit { amount } => {
+            let amount = amount as u64 % (INITIAL_BALANCE - POSITION + 2);
+            Outcome::of(harness.deposit(0, amount).await?)
+        }
+        FrozenOperation::Redeem { shares } => {
+            let shares = shares as u64 % (POSITION + 2);
+            Outcome::of(harness.redeem(0, shares).await?)
+        }
+    })
+}
+
+vault_fuzz_target! {
+    /// Execute a single freeze iteration
+    pub async fn fuzz_freeze_once(input: FreezeFuzzInput, harness) {
+        setup: FuzzEnvBuilder::new()
+            .flavor(input.flavor)
+            .balance(INITIAL_BALANCE),
+        scenario: {
+            // ========================================
+            // SETUP: a position, then the fuzzed accounts frozen
+            // ========================================
+
+            let underlying = harness.setup.vaults[0].underlying.clone();
+            grant_freeze_authority(&mut harness.env.context, &underlying).await?;
+            if let Err(e) = harness.deposit(0, POSITION).await? {
+                return Err(format!("Opening the position failed: {:?}", e).into());
+            }
+            if let Some(donation) = input.donation {
+                harness.donate(donation as u64).await?;
+            }
+
+            let frozen = input.target.accounts(harness.vault(), harness.user(0));
+            for account in &frozen {
+                freeze_token_account(&mut harness.env.context, &underlying, account).await?;
+            }
+
+            // ========================================
+            // EXECUTE: the operation against the frozen accounts
+            // ========================================
+
+            let before = harness.balances(0).await?;
+            let while_frozen = run(&mut harness, input.operation).await?;
+            let after = harness.balances(0).await?;
+
+            // INVARIANT 1: a frozen account on the transfer path blocks the operation
+            assert!(
+                !matches!(while_frozen, Outcome::Succeeded),
+                "CRITICAL VULNERABILITY: {:?} succeeded with {:?} frozen: {:?} -> {:?}",
+                input.operation,
+                input.target,
+                before,
+                after
+            );
+
+            // INVARIANT 2: the refused operation leaves no partial state behind
+            assert_eq!(
+                before, after,
+                "{:?} failed with {:?} frozen but moved funds",
+                input.operation, input.target
+            );
+
+            // ========================================
+            // THAW: the same operation, then a full exit
+            // ========================================
+
+            for account in &frozen {
+                thaw_token_account(&mut harness.env.context, &underlying, account).await?;
+            }
+            let thawed = run(&mut harness, input.operation).await?;
+
+            // INVARIANT 3: an operation valid once thawed was only refused for the freeze
+            match (&thawed, &while_frozen) {
+                (Outcome::Succeeded, Outcome::Rejected(failure)) => panic!(
+                    "{:?} succeeds once thawed but was refused while {:?} was frozen with {}",
+                    input.operation, input.target, failure
+                ),
+                (Outcome::Succeeded, _) => scenarios::hit("THAWED"),
+                _ => scenarios::hit("REJECTED_WHILE_THAWED"),
+            }
+
+            // INVARIANT 4: freezing never strands the depositor's position
+            let shares = harness.balances(0).await?.user_shares;
+            if shares > 0 {
+                if let Err(e) = harness.redeem(0, shares).await? {
+                    panic!(
+                        "Depositor cannot redeem {} shares after {:?} was thawed: {:?}",
+                        shares, input.target, e
+                    );
+                }
+            }
+        },
+    }
+}
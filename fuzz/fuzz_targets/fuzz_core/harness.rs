@@ -0,0 +1,277 @@
+//! Shared frame for vault fuzz targets.
+//!
+//! Most bodies repeat the same frame around their scenario: build an
+//! environment, send deposits and redeems, decode failures and check the
+//! exchange invariants after every success. [`Harness`] does that once, and
+//! [`vault_fuzz_target!`](crate::vault_fuzz_target) turns a scenario into the
+//! `*_once` function wrappers and replays call, so a new target is its input
+//! type plus the scenario:
+//!
+//! ```ignore
+//! vault_fuzz_target! {
+//!     /// Deposits the fuzzed amounts, then redeems everything
+//!     pub async fn fuzz_example_once(input: ExampleFuzzInput, harness) {
+//!         setup: FuzzEnvBuilder::new().flavor(input.flavor).users(2).balance(1 << 40),
+//!         scenario: {
+//!             for (user, amount) in input.deposits {
+//!                 let _ = harness.deposit(user as usize, amount).await?;
+//!             }
+//!             for user in 0..2 {
+//!                 let shares = harness.balances(user).await?.user_shares;
+//!                 let _ = harness.redeem(user, shares).await?;
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Wrappers are one line per engine with
+//! [`vault_fuzz_main!`](crate::vault_fuzz_main), which seeds, tracks and runs
+//! each input on the shared runtime (see `fuzz_deposit*.rs`). The target still
+//! needs its `dispatch_target!` arm, `[[bin]]` entries and seed corpus.
+
+use std::fmt::Debug;
+use std::future::Future;
+
+use serde::Serialize;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Keypair};
+use vault_invariants::{assert_exchange, Exchange};
+use vault_pda::instructions::{DepositError, RedeemError};
+
+use super::sequence::Sender;
+use super::{block_on, metrics, reproducer, scenarios};
+use crate::*;
+
+/// Check run on the vault state after every successful operation
+pub type InvariantHook = Box<dyn Fn(&Snapshot)>;
+
+/// Environment, sender and invariant checks for one iteration
+///
+/// Operations act on the first vault of the setup. Users are picked modulo
+/// the number of users, so any fuzzed index is valid.
+pub struct Harness {
+    pub env: FuzzTestEnv,
+    pub setup: FuzzEnv,
+    pub sender: Sender,
+    hooks: Vec<InvariantHook>,
+}
+
+impl Harness {
+    /// Builds the environment `builder` describes
+    pub async fn new(builder: FuzzEnvBuilder) -> FuzzResult<Self> {
+        let (env, setup) = builder.build().await?;
+        Ok(Self {
+            env,
+            setup,
+            sender: Sender::default(),
+            hooks: Vec::new(),
+        })
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        self.env.program_id
+    }
+
+    /// The vault operations act on
+    pub fn vault(&self) -> &VaultAccounts {
+        &self.setup.vaults[0].vault
+    }
+
+    /// Accounts of user `index` (modulo the number of users)
+    pub fn user(&self, index: usize) -> &UserAccounts {
+        let users = &self.setup.vaults[0].users;
+        &users[index % users.len()]
+    }
+
+    /// Adds a check run after every successful deposit, redeem and donation,
+    /// on top of the exchange invariants
+    pub fn on_success(&mut self, hook: impl Fn(&Snapshot) + 'static) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// Vault balances as seen by user `index`
+    pub async fn balances(&mut self, index: usize) -> FuzzResult<Snapshot> {
+        let vault = self.vault().clone();
+        let user = self.user(index).clone();
+        get_snapshot(&mut self.env.context, &vault, &user).await
+    }
+
+    /// Sends `ix` signed by `signers` (the first one pays) and decodes a
+    /// failure against `E`
+    ///
+    /// A program abort is never an acceptable outcome, so it panics here.
+    pub async fn send<E: ProgramError>(
+        &mut self,
+        ix: Instruction,
+        signers: &[&Keypair],
+    ) -> FuzzResult<Result<(), TxFailure<E>>> {
+        match self
+            .sender
+            .send_with_signers(&mut self.env, ix, signers)
+            .await?
+        {
+            Ok(()) => Ok(Ok(())),
+            Err(e) => {
+                let failure = decode_error::<E>(&e);
+                if failure.is_panic() {
+                    panic!("Program aborted instead of returning an error: {:?}", e);
+                }
+                Ok(Err(failure))
+            }
+        }
+    }
+
+    /// Deposits `amount` as user `index` and returns the shares minted,
+    /// checking every exchange invariant on success
+    pub async fn deposit(
+        &mut self,
+        index: usize,
+        amount: u64,
+    ) -> FuzzResult<Result<u64, TxFailure<DepositError>>> {
+        let user = self.user(index).clone();
+        let before = self.balances(index).await?;
+
+        let ix = deposit_ix(&self.program_id(), self.vault(), &user, amount);
+        if let Err(failure) = self.send::<DepositError>(ix, &[&user.owner]).await? {
+            scenarios::hit("DEPOSIT_FAILED");
+            return Ok(Err(failure));
+        }
+
+        let after = self.balances(index).await?;
+        assert_exchange(&before, &after, Exchange::Deposit { amount });
+        metrics::checked("deposit_exchange");
+        self.run_hooks(&after);
+        scenarios::hit(if before.share_supply == 0 {
+            "FIRST_DEPOSIT"
+        } else {
+            "DEPOSIT"
+        });
+        Ok(Ok(after.user_shares - before.user_shares))
+    }
+
+    /// Redeems `shares` as user `index` and returns the underlying paid out,
+    /// checking every exchange invariant on success
+    pub async fn redeem(
+        &mut self,
+        index: usize,
+        shares: u64,
+    ) -> FuzzResult<Result<u64, TxFailure<RedeemError>>> {
+        let user = self.user(index).clone();
+        let before = self.balances(index).await?;
+
+        let ix = redeem_ix(&self.program_id(), self.vault(), &user, shares);
+        if let Err(failure) = self.send::<RedeemError>(ix, &[&user.owner]).await? {
+            scenarios::hit("REDEEM_FAILED");
+            return Ok(Err(failure));
+        }
+
+        let after = self.balances(index).await?;
+        assert_exchange(&before, &after, Exchange::Redeem { shares });
+        metrics::checked("redeem_exchange");
+        self.run_hooks(&after);
+        scenarios::hit(if after.share_supply == 0 {
+            "FULL_EXIT"
+        } else {
+            "REDEEM"
+        });
+        Ok(Ok(after.user_balance - before.user_balance))
+    }
+
+    /// Mints `amount` underlying straight into the vault
+    pub async fn donate(&mut self, amount: u64) -> FuzzResult<()> {
+        let underlying = self.setup.vaults[0].underlying.clone();
+        let vault = self.vault().clone();
+        donate_to_vault(&mut self.env.context, &underlying, &vault, amount).await?;
+
+        let after = self.balances(0).await?;
+        self.run_hooks(&after);
+        scenarios::hit("YIELD_GROWTH");
+        Ok(())
+    }
+
+    fn run_hooks(&self, snapshot: &Snapshot) {
+        for hook in &self.hooks {
+            hook(snapshot);
+            metrics::checked("success_hook");
+        }
+    }
+}
+
+/// Runs one wrapper iteration: seeds and tracks `input`, then runs `once` on
+/// the shared runtime, reporting (not panicking on) infrastructure errors
+pub fn run_iteration<T, F, Fut>(target: &'static str, input: T, once: F)
+where
+    T: Serialize + Debug,
+    F: FnOnce(T) -> Fut,
+    Fut: Future<Output = FuzzResult<()>>,
+{
+    reproducer::track(target, &input);
+    scenarios::begin(target);
+    block_on(async {
+        if let Err(e) = once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+}
+
+/// Defines a `*_once` fuzz body from a setup and a scenario run on a
+/// [`Harness`](crate::fuzz_core::harness::Harness)
+///
+/// `setup` is a `FuzzEnvBuilder` and may use the input. Inputs the setup
+/// rejects (extreme decimals or balances) are skipped. `scenario` runs with
+/// the harness bound to the given name and may use `?`.
+#[macro_export]
+macro_rules! vault_fuzz_target {
+    (
+        $(#[$meta:meta])*
+        $vis:vis async fn $name:ident($input:ident: $ty:ty, $harness:ident) {
+            setup: $setup:expr,
+            scenario: $scenario:block $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis async fn $name($input: $ty) -> $crate::FuzzResult<()> {
+            #[allow(unused_mut)]
+            let mut $harness = match $crate::fuzz_core::harness::Harness::new($setup).await {
+                Ok(harness) => harness,
+                Err(e) => {
+                    eprintln!("Setup failed: {}", e);
+                    return Ok(());
+                }
+            };
+            $scenario
+            Ok(())
+        }
+    };
+}
+
+/// Defines the `main` of an engine wrapper for a `fuzz_core` body
+///
+/// `$target` is the `fuzz_core` module name, as reproducers record it. The
+/// libFuzzer form still needs `#![no_main]` at the top of the file.
+#[macro_export]
+macro_rules! vault_fuzz_main {
+    (libfuzzer, $target:literal, $ty:ty, $once:path) => {
+        ::libfuzzer_sys::fuzz_target!(|input: $ty| {
+            $crate::fuzz_core::harness::run_iteration($target, input, $once);
+        });
+    };
+    (honggfuzz, $target:literal, $ty:ty, $once:path) => {
+        fn main() {
+            loop {
+                ::honggfuzz::fuzz!(|input: $ty| {
+                    $crate::fuzz_core::harness::run_iteration($target, input, $once);
+                });
+            }
+        }
+    };
+    (afl, $target:literal, $ty:ty, $once:path) => {
+        fn main() {
+            ::afl::fuzz!(|data: &[u8]| {
+                if let Some(input) = $crate::fuzz_core::corpus::decode::<$ty>(data) {
+                    $crate::fuzz_core::harness::run_iteration($target, input, $once);
+                }
+            });
+        }
+    };
+}
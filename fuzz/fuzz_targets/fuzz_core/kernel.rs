@@ -0,0 +1,118 @@
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use vault_math::{compute_assets, compute_shares, KernelError, VIRTUAL_OFFSET};
+
+/// Fuzzable input for the pure deposit/redeem kernels
+///
+/// Unlike the deposit target this never touches a banks client, so it
+/// runs at native speed and can explore the full `u64` state space.
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct KernelFuzzInput {
+    /// Amount to deposit
+    pub amount: u64,
+    /// Shares to redeem (interpreted as a fraction of the post-deposit position)
+    pub redeem_shares: u64,
+    /// Outstanding share supply before the deposit
+    pub total_shares: u64,
+    /// Assets held by the vault before the deposit
+    pub total_assets: u64,
+}
+
+/// Execute a single fuzz iteration against the pure kernels
+pub fn fuzz_kernel_once(input: KernelFuzzInput) {
+    let KernelFuzzInput {
+        amount,
+        redeem_shares,
+        total_shares,
+        total_assets,
+    } = input;
+
+    // Totals as the kernel prices them, with the virtual offset of builds
+    // without `vuln-inflation`
+    let supply = total_shares as u128 + VIRTUAL_OFFSET as u128;
+    let assets = total_assets as u128 + VIRTUAL_OFFSET as u128;
+
+    // ========================================
+    // DEPOSIT KERNEL
+    // ========================================
+
+    let shares = match compute_shares(amount, total_shares, total_assets) {
+        Ok(shares) => shares,
+        Err(e) => {
+            // Every rejection must have a concrete cause
+            match e {
+                KernelError::InvalidAmount => assert_eq!(amount, 0),
+                KernelError::EmptyVault => assert!(total_shares > 0 && total_assets == 0),
+                KernelError::MathOverflow => {
+                    assert!((amount as u128 * supply / assets) > u64::MAX as u128)
+                }
+                KernelError::ZeroOutput => assert!((amount as u128 * supply) < assets),
+                KernelError::NoShares => panic!("deposit kernel returned NoShares"),
+            }
+            return;
+        }
+    };
+
+    // PROPERTY 1: non-zero deposit always mints non-zero shares
+    assert!(shares > 0, "Deposited {} but minted 0 shares", amount);
+
+    // PROPERTY 2: first deposit is 1:1 (without a virtual offset), otherwise
+    // rounding favors the vault
+    if total_shares == 0 && VIRTUAL_OFFSET == 0 {
+        assert_eq!(shares, amount, "First deposit must mint 1:1");
+    } else {
+        assert!(
+            shares as u128 * assets <= amount as u128 * supply,
+            "Minted too many shares: {} for {} (supply={}, assets={})",
+            shares,
+            amount,
+            total_shares,
+            total_assets
+        );
+    }
+
+    // ========================================
+    // REDEEM KERNEL (after the deposit)
+    // ========================================
+
+    let (Some(shares_after), Some(assets_after)) = (
+        total_shares.checked_add(shares),
+        total_assets.checked_add(amount),
+    ) else {
+        // The token program would reject the deposit before reaching this state
+        return;
+    };
+
+    // Redeem somewhere between 1 and all of the freshly minted shares
+    let redeem = redeem_shares % shares + 1;
+
+    match compute_assets(redeem, shares_after, assets_after) {
+        Ok(paid) => {
+            // PROPERTY 3: redeeming never pays more than the shares are worth
+            assert!(
+                paid as u128 * (shares_after as u128 + VIRTUAL_OFFSET as u128)
+                    <= redeem as u128 * (assets_after as u128 + VIRTUAL_OFFSET as u128),
+                "Redeemed {} assets for {} shares (supply={}, assets={})",
+                paid,
+                redeem,
+                shares_after,
+                assets_after
+            );
+
+            // PROPERTY 4: deposit-then-redeem never profits, absent pre-existing
+            // assets in an empty vault (those are donations the first depositor
+            // legitimately receives)
+            if total_shares > 0 || total_assets == 0 {
+                assert!(
+                    paid <= amount,
+                    "Round trip profit: deposited {}, redeemed {} for {} shares",
+                    amount,
+                    paid,
+                    redeem
+                );
+            }
+        }
+        Err(KernelError::ZeroOutput) => {}
+        Err(e) => panic!("Unexpected redeem error {:?} for input {:?}", e, input),
+    }
+}
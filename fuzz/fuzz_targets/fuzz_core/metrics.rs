@@ -0,0 +1,170 @@
+//! Campaign metrics for dashboards.
+//!
+//! Every scenario summary (see [`scenarios`](super::scenarios)) is also
+//! written as a Prometheus textfile next to the JSON, `<target>.prom`, for
+//! node_exporter's textfile collector or anything else that scrapes the stats
+//! directory. It carries the iteration count and execs/sec, scenario hits,
+//! compute unit percentiles per instruction mix and how often each invariant
+//! was checked, all labelled with the target. The file is replaced with a
+//! rename, so a scrape never sees half of it.
+//!
+//! Invariants are counted with [`checked`] where they are asserted; a failed
+//! check panics, which the fuzzer reports as a crash.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+use crate::FuzzResult;
+
+/// Checks per invariant since the process started
+static CHECKS: Mutex<BTreeMap<&'static str, u64>> = Mutex::new(BTreeMap::new());
+
+/// Records that `invariant` was checked and held
+pub fn checked(invariant: &'static str) {
+    if let Ok(mut checks) = CHECKS.lock() {
+        *checks.entry(invariant).or_default() += 1;
+    }
+}
+
+/// Checks per invariant so far
+pub fn invariants() -> Value {
+    let checks = CHECKS
+        .lock()
+        .map(|checks| checks.clone())
+        .unwrap_or_default();
+    checks
+        .into_iter()
+        .map(|(invariant, count)| (invariant.to_string(), Value::from(count)))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+/// Renders a scenario summary in the Prometheus text exposition format
+pub fn prometheus(summary: &Value) -> String {
+    let target = escape(summary["target"].as_str().unwrap_or("unknown"));
+    let mut out = String::new();
+
+    let mut metric = |name: &str, kind: &str, help: &str, samples: Vec<(String, f64)>| {
+        let _ = writeln!(out, "# HELP vault_fuzz_{name} {help}");
+        let _ = writeln!(out, "# TYPE vault_fuzz_{name} {kind}");
+        for (labels, value) in samples {
+            let _ = writeln!(
+                out,
+                "vault_fuzz_{name}{{target=\"{target}\"{labels}}} {value}"
+            );
+        }
+    };
+
+    let number = |value: &Value| value.as_f64().unwrap_or(0.0);
+    let entries = |value: &Value| value.as_object().cloned().unwrap_or_default();
+
+    metric(
+        "iterations_total",
+        "counter",
+        "Inputs run since the fuzzer started",
+        vec![(String::new(), number(&summary["iterations"]))],
+    );
+    metric(
+        "execs_per_second",
+        "gauge",
+        "Inputs per second since the fuzzer started",
+        vec![(String::new(), number(&summary["execs_per_sec"]))],
+    );
+    metric(
+        "uptime_seconds",
+        "gauge",
+        "Seconds since the fuzzer started",
+        vec![(String::new(), number(&summary["elapsed_secs"]))],
+    );
+    metric(
+        "scenario_hits_total",
+        "counter",
+        "Times an input reached the scenario",
+        entries(&summary["scenarios"])
+            .iter()
+            .map(|(scenario, stats)| {
+                (
+                    format!(",scenario=\"{}\"", escape(scenario)),
+                    number(&stats["hits"]),
+                )
+            })
+            .collect(),
+    );
+    metric(
+        "invariant_checks_total",
+        "counter",
+        "Times the invariant was checked and held",
+        entries(&summary["invariants"])
+            .iter()
+            .map(|(invariant, count)| {
+                (
+                    format!(",invariant=\"{}\"", escape(invariant)),
+                    number(count),
+                )
+            })
+            .collect(),
+    );
+
+    let compute_units = entries(&summary["compute_units"]);
+    metric(
+        "compute_units",
+        "gauge",
+        "Compute units consumed per transaction, by vault instruction mix",
+        compute_units
+            .iter()
+            .flat_map(|(label, stats)| {
+                let label = escape(label);
+                [
+                    ("0.5", "p50"),
+                    ("0.9", "p90"),
+                    ("0.99", "p99"),
+                    ("1", "max"),
+                ]
+                .into_iter()
+                .map(move |(quantile, field)| {
+                    (
+                        format!(",instructions=\"{label}\",quantile=\"{quantile}\""),
+                        number(&stats[field]),
+                    )
+                })
+            })
+            .collect(),
+    );
+    metric(
+        "compute_units_transactions_total",
+        "counter",
+        "Transactions recorded per vault instruction mix",
+        compute_units
+            .iter()
+            .map(|(label, stats)| {
+                (
+                    format!(",instructions=\"{}\"", escape(label)),
+                    number(&stats["transactions"]),
+                )
+            })
+            .collect(),
+    );
+
+    out
+}
+
+/// Writes [`prometheus`] of `summary` to `path` through a temporary file
+pub fn write_textfile(path: &Path, summary: &Value) -> FuzzResult<()> {
+    let partial = path.with_extension("prom.tmp");
+    fs::write(&partial, prometheus(summary))?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// Escapes a label value
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,93 @@
+//! Engine-independent fuzz bodies.
+//!
+//! Each module owns a fuzz input type and a `*_once` function that runs a
+//! single iteration. The binaries in `fuzz_targets/` are thin wrappers that
+//! feed these from libFuzzer (`*_libfuzzer`), AFL++ (`*_afl`) or honggfuzz, so
+//! every engine exercises exactly the same checks.
+
+/// Runs the body of the module named `$target` on an input decoded by
+/// `$decode`, a generic `fn(_) -> FuzzResult<T>`
+macro_rules! dispatch_target {
+    ($target:expr, $decode:ident($source:expr)) => {{
+        use $crate::fuzz_core::*;
+        match $target {
+            "atomicity" => block_on(atomicity::fuzz_atomicity_once($decode($source)?)),
+            "authorization" => block_on(authorization::fuzz_authorization_once($decode($source)?)),
+            "boundary" => block_on(boundary::fuzz_boundary_once($decode($source)?)),
+            "bump" => block_on(bump::fuzz_bump_once($decode($source)?)),
+            "clock_warp" => block_on(clock_warp::fuzz_clock_warp_once($decode($source)?)),
+            "corruption" => block_on(corruption::fuzz_corruption_once($decode($source)?)),
+            "compute_budget" => block_on(compute_budget::fuzz_compute_budget_once($decode(
+                $source,
+            )?)),
+            "cross_vault" => block_on(cross_vault::fuzz_cross_vault_once($decode($source)?)),
+            "decimals" => block_on(decimals::fuzz_decimals_once($decode($source)?)),
+            "deposit" => block_on(deposit::fuzz_deposit_once($decode($source)?)),
+            "differential" => block_on(differential::fuzz_differential_once($decode($source)?)),
+            "freeze" => block_on(freeze::fuzz_freeze_once($decode($source)?)),
+            "kernel" => {
+                kernel::fuzz_kernel_once($decode($source)?);
+                Ok(())
+            }
+            "precreation" => block_on(precreation::fuzz_precreation_once($decode($source)?)),
+            "redeem" => block_on(redeem::fuzz_redeem_once($decode($source)?)),
+            "remaining_accounts" => block_on(remaining_accounts::fuzz_remaining_accounts_once(
+                $decode($source)?,
+            )),
+            "round_trip" => block_on(round_trip::fuzz_round_trip_once($decode($source)?)),
+            "sequence" => block_on(sequence::fuzz_sequence_once($decode($source)?)),
+            "substitution" => block_on(substitution::fuzz_substitution_once($decode($source)?)),
+            "token_extensions" => block_on(token_extensions::fuzz_token_extensions_once($decode(
+                $source,
+            )?)),
+            target => Err(format!("Unknown fuzz target {}", target).into()),
+        }
+    }};
+}
+
+pub mod atomicity;
+pub mod authorization;
+pub mod boundary;
+pub mod bump;
+pub mod clock_warp;
+pub mod compute_budget;
+pub mod corpus;
+pub mod corruption;
+pub mod cross_vault;
+pub mod decimals;
+pub mod deposit;
+pub mod differential;
+pub mod freeze;
+pub mod harness;
+pub mod kernel;
+pub mod metrics;
+pub mod precreation;
+pub mod redeem;
+pub mod reference;
+pub mod remaining_accounts;
+pub mod reproducer;
+pub mod round_trip;
+pub mod scenarios;
+pub mod sequence;
+pub mod substitution;
+pub mod token_extensions;
+
+use std::future::Future;
+use std::sync::OnceLock;
+
+use tokio::runtime::Runtime;
+
+/// Runs `future` to completion on the process-wide runtime
+///
+/// The runtime is built on first use and lives until the process exits, so
+/// wrappers no longer pay for (or leak threads from) a runtime per input. As
+/// every iteration on the calling thread now runs on the same runtime, this
+/// also turns on context reuse for the thread (see `enable_context_reuse`).
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+    crate::enable_context_reuse();
+    RUNTIME
+        .get_or_init(|| Runtime::new().expect("failed to build the fuzzing runtime"))
+        .block_on(future)
+}
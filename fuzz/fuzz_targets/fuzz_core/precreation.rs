@@ -0,0 +1,287 @@
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    account::Account,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use super::sequence::Sender;
+use crate::*;
+
+/// Decimals of the underlying mint
+const DECIMALS: u8 = 6;
+
+/// Program the attacker can make a pre-created account belong to
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum ForeignOwner {
+    TokenProgram,
+    VaultProgram,
+    Random,
+}
+
+/// What the attacker leaves at a PDA address before initialization
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum PreState {
+    /// Nothing, the address is empty
+    Untouched,
+    /// Lamports sent with a plain system transfer, which anyone can do
+    Prefunded { lamports: u32 },
+    /// A system-owned account that already holds data
+    SystemWithData { len: u8 },
+    /// An account owned by another program
+    ForeignOwned { owner: ForeignOwner, len: u8 },
+}
+
+/// Fuzzable input for PDA pre-creation before initialize/initialize_vault
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct PrecreationFuzzInput {
+    pub protocol_state: PreState,
+    pub vault_authority: PreState,
+    pub vault: PreState,
+    pub vault_token_account: PreState,
+    pub share_mint: PreState,
+}
+
+impl PrecreationFuzzInput {
+    /// Whether every address is only in a state any attacker can reach
+    /// without the PDA's signature, in which case initialization must succeed
+    fn only_prefunded(&self) -> bool {
+        [
+            self.protocol_state,
+            self.vault_authority,
+            self.vault,
+            self.vault_token_account,
+            self.share_mint,
+        ]
+        .iter()
+        .all(|state| matches!(state, PreState::Untouched | PreState::Prefunded { .. }))
+    }
+}
+
+async fn apply(
+    env: &mut FuzzTestEnv,
+    sender: &mut Sender,
+    attacker: &Keypair,
+    address: &Pubkey,
+    state: PreState,
+) -> FuzzResult<()> {
+    let rent = env.context.banks_client.get_rent().await?;
+    let (owner, len) = match state {
+        PreState::Untouched => return Ok(()),
+        PreState::Prefunded { lamports } => {
+            let ix = solana_sdk::system_instruction::transfer(
+                &attacker.pubkey(),
+                address,
+                lamports as u64 + 1,
+            );
+            // Transfers below the rent-exempt minimum are rejected by the runtime,
+            // leaving the address untouched
+            let _ = sender.send(env, ix, attacker).await?;
+            return Ok(());
+        }
+        PreState::SystemWithData { len } => (solana_sdk::system_program::ID, len),
+        PreState::ForeignOwned { owner, len } => {
+            let owner = match owner {
+                ForeignOwner::TokenProgram => spl_token::id(),
+                ForeignOwner::VaultProgram => env.program_id,
+                ForeignOwner::Random => new_address(),
+            };
+            (owner, len)
+        }
+    };
+
+    let account = Account {
+        lamports: rent.minimum_balance(len as usize),
+        data: vec![0xAA; len as usize],
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    };
+    env.context.set_account(address, &account.into());
+    Ok(())
+}
+
+/// Checks that whatever initialization succeeded produced exactly the
+/// expected accounts, so a pre-created account can never be adopted
+async fn assert_initialized_correctly(
+    env: &mut FuzzTestEnv,
+    owner: &Pubkey,
+    underlying_mint: &Pubkey,
+    protocol_ok: bool,
+    vault_ok: bool,
+) -> FuzzResult<()> {
+    let program_id = env.program_id;
+    let (protocol_state, protocol_bump) = derive_protocol_state_pda(&program_id);
+    let (vault_authority, authority_bump) = derive_vault_authority_pda(&program_id);
+
+    if protocol_ok {
+        let state = get_protocol_state(&mut env.context, &protocol_state).await?;
+        assert_eq!(&state.owner, owner, "Protocol owner hijacked");
+        assert_eq!(state.bump, protocol_bump);
+        let authority = get_vault_authority(&mut env.context, &vault_authority).await?;
+        assert_eq!(authority.bump, authority_bump);
+    }
+
+    if vault_ok {
+        let (vault, vault_bump) = derive_vault_pda(&program_id, underlying_mint);
+        let state = get_vault_state(&mut env.context, &vault).await?;
+        let share_mint = derive_share_mint_pda(&program_id, &vault).0;
+        let vault_token_account = derive_vault_token_account_pda(&program_id, &vault).0;
+        let token_authority = vault_pda::token_authority(&vault);
+        assert_eq!(&state.underlying_mint, underlying_mint);
+        assert_eq!(state.share_mint, share_mint);
+        assert_eq!(state.vault_token_account, vault_token_account);
+        assert_eq!(state.bump, vault_bump);
+
+        let account = env
+            .context
+            .banks_client
+            .get_account(vault_token_account)
+            .await?
+            .ok_or("Vault token account missing")?;
+        assert_eq!(
+            account.owner,
+            spl_token::id(),
+            "Vault token account not owned by SPL Token"
+        );
+        let token = spl_token::state::Account::unpack(&account.data)?;
+        assert_eq!(
+            &token.mint, underlying_mint,
+            "Vault token account has the wrong mint"
+        );
+        assert_eq!(
+            token.owner, token_authority,
+            "Vault token account authority hijacked"
+        );
+        assert_eq!(token.amount, 0, "Vault token account pre-loaded");
+
+        let account = env
+            .context
+            .banks_client
+            .get_account(share_mint)
+            .await?
+            .ok_or("Share mint missing")?;
+        assert_eq!(
+            account.owner,
+            spl_token::id(),
+            "Share mint not owned by SPL Token"
+        );
+        let mint = spl_token::state::Mint::unpack(&account.data)?;
+        assert_eq!(
+            mint.mint_authority,
+            COption::Some(token_authority),
+            "Share mint authority hijacked"
+        );
+        assert_eq!(
+            mint.freeze_authority,
+            COption::None,
+            "Share mint has a freeze authority"
+        );
+        assert_eq!(mint.decimals, DECIMALS);
+        assert_eq!(mint.supply, 0, "Share mint pre-minted");
+    }
+
+    Ok(())
+}
+
+/// Execute a single PDA pre-creation iteration
+pub async fn fuzz_precreation_once(input: PrecreationFuzzInput) -> FuzzResult<()> {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+
+    let payer = env.context.payer.insecure_clone();
+    let attacker = new_keypair();
+    let owner = new_keypair();
+    for keypair in [&attacker, &owner] {
+        let ix = solana_sdk::system_instruction::transfer(
+            &payer.pubkey(),
+            &keypair.pubkey(),
+            100_000_000_000,
+        );
+        if let Err(e) = sender.send(&mut env, ix, &payer).await? {
+            return Err(e.into());
+        }
+    }
+    let underlying =
+        setup_underlying_mint(&mut env.context, DECIMALS, TokenFlavor::SplToken).await?;
+
+    // ========================================
+    // ATTACKER PRE-CREATES THE PDAS
+    // ========================================
+
+    let vault = derive_vault_pda(&program_id, &underlying.mint).0;
+    let targets = [
+        (
+            derive_protocol_state_pda(&program_id).0,
+            input.protocol_state,
+        ),
+        (
+            derive_vault_authority_pda(&program_id).0,
+            input.vault_authority,
+        ),
+        (vault, input.vault),
+        (
+            derive_vault_token_account_pda(&program_id, &vault).0,
+            input.vault_token_account,
+        ),
+        (
+            derive_share_mint_pda(&program_id, &vault).0,
+            input.share_mint,
+        ),
+    ];
+    for (address, state) in targets {
+        apply(&mut env, &mut sender, &attacker, &address, state).await?;
+    }
+
+    // ========================================
+    // INITIALIZE
+    // ========================================
+
+    let ix = initialize_ix(&program_id, &owner.pubkey());
+    let protocol_ok = sender.send(&mut env, ix, &owner).await?.is_ok();
+    let ix = initialize_vault_ix(&program_id, &underlying.mint, &owner.pubkey());
+    let vault_ok = sender.send(&mut env, ix, &owner).await?.is_ok();
+
+    // ========================================
+    // INVARIANT CHECKS
+    // ========================================
+
+    // INVARIANT 1: pre-funding alone can never block initialization
+    if input.only_prefunded() {
+        assert!(
+            protocol_ok,
+            "Pre-funded PDAs blocked initialize: {:?}",
+            input
+        );
+        assert!(
+            vault_ok,
+            "Pre-funded PDAs blocked initialize_vault: {:?}",
+            input
+        );
+    }
+
+    // INVARIANT 2: the vault can never be initialized without the protocol
+    if vault_ok {
+        assert!(
+            protocol_ok,
+            "initialize_vault succeeded without initialize: {:?}",
+            input
+        );
+    }
+
+    // INVARIANT 3: successful initialization never adopts attacker state
+    assert_initialized_correctly(
+        &mut env,
+        &owner.pubkey(),
+        &underlying.mint,
+        protocol_ok,
+        vault_ok,
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use super::scenarios;
+use crate::*;
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{signature::Signer, transaction::Transaction};
+use spl_token::error::TokenError;
+use vault_invariants::Exchange;
+use vault_pda::instructions::RedeemError;
+
+/// Fuzzable input for redeem instruction
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct RedeemFuzzInput {
+    /// Token program the vault and its accounts belong to
+    #[serde(default)]
+    pub flavor: TokenFlavor,
+    /// Amount deposited to open the position (for setup)
+    pub deposit_amount: u64,
+    /// Token decimals (for setup)
+    pub decimals: u8,
+    /// Amount of yield/profit to add to vault before redeeming
+    pub yield_amount: u64,
+    /// Shares to redeem in the first redemption (fuzzed)
+    pub shares: u64,
+    /// Whether to redeem a second time with the remaining position
+    pub do_second_redeem: bool,
+    /// Shares to redeem in the second redemption (if do_second_redeem is true)
+    pub second_shares: u64,
+}
+
+/// Redeems `shares` and checks every invariant, returning whether it succeeded
+async fn redeem_and_check(
+    env: &mut FuzzTestEnv,
+    setup: &CompleteSetup,
+    shares: u64,
+    input: &RedeemFuzzInput,
+) -> FuzzResult<bool> {
+    let before = get_snapshot(&mut env.context, &setup.vault, &setup.user).await?;
+
+    let ix = redeem_ix(&env.program_id, &setup.vault, &setup.user, shares);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&setup.user.owner.pubkey()),
+        &[&setup.user.owner],
+        env.context.last_blockhash,
+    );
+
+    let result = compute_units::process_transaction(&mut env.context, &env.program_id, tx).await;
+
+    let e = match result {
+        Ok(_) => {
+            let after = get_snapshot(&mut env.context, &setup.vault, &setup.user).await?;
+            let returned = after.user_balance - before.user_balance;
+
+            // Conservation, exact accounting, rounding in favor of the vault,
+            // no dilution of remaining holders, full exits and the exact formula
+            vault_invariants::assert_exchange(&before, &after, Exchange::Redeem { shares });
+
+            scenarios::hit(if after.share_supply == 0 {
+                "FULL_EXIT"
+            } else {
+                "PARTIAL_REDEEM"
+            });
+
+            println!(
+                "✓ PASS - redeem shares={}, returned={}, vault: {}→{}, supply: {}→{}, all invariants ✓",
+                shares,
+                returned,
+                before.vault_balance,
+                after.vault_balance,
+                before.share_supply,
+                after.share_supply
+            );
+
+            return Ok(true);
+        }
+        Err(e) => e,
+    };
+
+    println!("✗ Redeem failed: shares={}, error={:?}", shares, e);
+
+    let accepted = match decode_error::<RedeemError>(&e) {
+        TxFailure::Token(TokenError::InsufficientFunds) => Some("InsufficientFunds"),
+        TxFailure::Program(RedeemError::InvalidAmount) => Some("InvalidAmount"),
+        TxFailure::Program(RedeemError::InsufficientUnderlying) => Some("InsufficientUnderlying"),
+        TxFailure::Program(RedeemError::MathOverflow) => Some("MathOverflow"),
+        _ => None,
+    };
+    scenarios::hit("REDEEM_FAILED");
+    if let Some(error) = accepted {
+        scenarios::hit(error);
+    }
+
+    if accepted.is_none() {
+        panic!(
+            "Unexpected error during redeem: {:?}\nInput: {:?}",
+            e, input
+        );
+    }
+
+    Ok(false)
+}
+
+/// Execute a single fuzz iteration for the redeem instruction
+pub async fn fuzz_redeem_once(input: RedeemFuzzInput) -> FuzzResult<()> {
+    let deposit_amount = input.deposit_amount.max(1);
+    let decimals = input.decimals % 19;
+    let yield_amount = input.yield_amount % 1_000_000_000;
+
+    // Setup complete environment with a funded user
+    let (mut env, setup) =
+        match setup_complete_environment(deposit_amount, decimals, input.flavor).await {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("Setup failed: {}", e);
+                return Ok(());
+            }
+        };
+
+    // Open the position
+    let ix = deposit_ix(&env.program_id, &setup.vault, &setup.user, deposit_amount);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&setup.user.owner.pubkey()),
+        &[&setup.user.owner],
+        env.context.last_blockhash,
+    );
+    if compute_units::process_transaction(&mut env.context, &env.program_id, tx)
+        .await
+        .is_err()
+    {
+        return Ok(());
+    }
+
+    // Simulate yield accumulation (vault value increases)
+    if yield_amount > 0 {
+        scenarios::hit("YIELD_GROWTH");
+        let _ = donate_to_vault(
+            &mut env.context,
+            &setup.underlying,
+            &setup.vault,
+            yield_amount,
+        )
+        .await;
+    }
+
+    // Redeem a fuzzed portion of the position (0 and oversize amounts included)
+    let position = get_token_balance(&mut env.context, &setup.user.share_token_account).await?;
+    let shares = input.shares % position.saturating_add(2);
+
+    let redeemed = redeem_and_check(&mut env, &setup, shares, &input).await?;
+
+    // Partial redemption: redeem again from what is left
+    if redeemed && input.do_second_redeem {
+        let remaining =
+            get_token_balance(&mut env.context, &setup.user.share_token_account).await?;
+        if remaining > 0 {
+            let second = input.second_shares % remaining + 1;
+            scenarios::hit("SECOND_REDEEM");
+            redeem_and_check(&mut env, &setup, second, &input).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,181 @@
+//! Pure-Rust ERC-4626 reference vault.
+//!
+//! Deliberately independent of `vault-math`: conversions are written straight
+//! from the ERC-4626 definitions in `u128` so the differential target compares
+//! the program against a second implementation rather than against itself.
+//! Like the program, the first deposit into a vault without shares mints 1:1,
+//! and both `deposit` and `redeem` round down (in favor of the vault).
+//! Outside the challenge build (`vuln-inflation` off) both conversions add
+//! one virtual share and one virtual asset, as OpenZeppelin's ERC-4626 does.
+
+use std::collections::HashMap;
+
+/// Virtual shares and assets added to both sides of a conversion
+const VIRTUAL_OFFSET: u128 = if vault_pda::VULN_INFLATION { 0 } else { 1 };
+
+/// Why the reference vault rejected an operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceError {
+    /// Zero assets or shares requested
+    ZeroAmount,
+    /// The caller does not hold enough assets or shares
+    InsufficientBalance,
+    /// Shares are outstanding but the vault holds no assets
+    EmptyVault,
+    /// Redeeming while no shares exist
+    NoShares,
+    /// The operation rounds down to nothing
+    ZeroOutput,
+    /// A result or a token balance would exceed `u64::MAX`
+    Overflow,
+}
+
+/// Outcome of a conversion, with both rounding directions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quote {
+    /// Result rounded down, the documented direction for deposit and redeem
+    pub floor: u64,
+    /// Result rounded up, only used to describe divergences
+    pub ceil: u64,
+}
+
+/// Position of a single account in the reference vault
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    /// Underlying assets held outside the vault
+    pub assets: u64,
+    /// Vault shares held
+    pub shares: u64,
+}
+
+/// Reference vault state
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceVault {
+    /// Underlying assets held by the vault
+    pub total_assets: u64,
+    /// Outstanding shares
+    pub total_supply: u64,
+    /// Positions keyed by an arbitrary account id
+    pub positions: HashMap<u8, Position>,
+}
+
+fn quote(numerator: u128, denominator: u128) -> Result<Quote, ReferenceError> {
+    let floor = numerator / denominator;
+    let ceil = if numerator % denominator == 0 {
+        floor
+    } else {
+        floor + 1
+    };
+    Ok(Quote {
+        floor: u64::try_from(floor).map_err(|_| ReferenceError::Overflow)?,
+        ceil: u64::try_from(ceil).unwrap_or(u64::MAX),
+    })
+}
+
+impl ReferenceVault {
+    /// Returns the position of `account`, defaulting to an empty one
+    pub fn position(&self, account: u8) -> Position {
+        self.positions.get(&account).copied().unwrap_or_default()
+    }
+
+    /// Credits `assets` to `account` outside the vault
+    pub fn fund(&mut self, account: u8, assets: u64) {
+        self.positions.entry(account).or_default().assets += assets;
+    }
+
+    /// ERC-4626 `previewDeposit`
+    pub fn preview_deposit(&self, assets: u64) -> Result<Quote, ReferenceError> {
+        if assets == 0 {
+            return Err(ReferenceError::ZeroAmount);
+        }
+        if self.total_supply == 0 && VIRTUAL_OFFSET == 0 {
+            return Ok(Quote {
+                floor: assets,
+                ceil: assets,
+            });
+        }
+        if self.total_supply > 0 && self.total_assets == 0 {
+            return Err(ReferenceError::EmptyVault);
+        }
+        quote(
+            assets as u128 * (self.total_supply as u128 + VIRTUAL_OFFSET),
+            self.total_assets as u128 + VIRTUAL_OFFSET,
+        )
+    }
+
+    /// ERC-4626 `previewRedeem`
+    pub fn preview_redeem(&self, shares: u64) -> Result<Quote, ReferenceError> {
+        if shares == 0 {
+            return Err(ReferenceError::ZeroAmount);
+        }
+        if self.total_supply == 0 {
+            return Err(ReferenceError::NoShares);
+        }
+        if self.total_assets == 0 {
+            return Err(ReferenceError::EmptyVault);
+        }
+        quote(
+            shares as u128 * (self.total_assets as u128 + VIRTUAL_OFFSET),
+            self.total_supply as u128 + VIRTUAL_OFFSET,
+        )
+    }
+
+    /// Deposits `assets` from `account`, returning the shares minted
+    pub fn deposit(&mut self, account: u8, assets: u64) -> Result<u64, ReferenceError> {
+        let shares = self.preview_deposit(assets)?.floor;
+        if shares == 0 {
+            return Err(ReferenceError::ZeroOutput);
+        }
+        let position = self.position(account);
+        if assets > position.assets {
+            return Err(ReferenceError::InsufficientBalance);
+        }
+        let total_assets = self
+            .total_assets
+            .checked_add(assets)
+            .ok_or(ReferenceError::Overflow)?;
+        let total_supply = self
+            .total_supply
+            .checked_add(shares)
+            .ok_or(ReferenceError::Overflow)?;
+
+        self.total_assets = total_assets;
+        self.total_supply = total_supply;
+        let position = self.positions.entry(account).or_default();
+        position.assets -= assets;
+        position.shares += shares;
+        Ok(shares)
+    }
+
+    /// Redeems `shares` held by `account`, returning the assets paid out
+    pub fn redeem(&mut self, account: u8, shares: u64) -> Result<u64, ReferenceError> {
+        let assets = self.preview_redeem(shares)?.floor;
+        if assets == 0 {
+            return Err(ReferenceError::ZeroOutput);
+        }
+        let position = self.position(account);
+        if shares > position.shares {
+            return Err(ReferenceError::InsufficientBalance);
+        }
+        let balance = position
+            .assets
+            .checked_add(assets)
+            .ok_or(ReferenceError::Overflow)?;
+
+        self.total_assets -= assets;
+        self.total_supply -= shares;
+        let position = self.positions.entry(account).or_default();
+        position.assets = balance;
+        position.shares -= shares;
+        Ok(assets)
+    }
+
+    /// Adds assets to the vault without minting shares
+    pub fn donate(&mut self, assets: u64) -> Result<(), ReferenceError> {
+        self.total_assets = self
+            .total_assets
+            .checked_add(assets)
+            .ok_or(ReferenceError::Overflow)?;
+        Ok(())
+    }
+}
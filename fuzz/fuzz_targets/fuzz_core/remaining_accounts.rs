@@ -0,0 +1,280 @@
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    instruction::AccountMeta,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use vault_math::{compute_assets, compute_shares};
+
+use super::corruption::is_panic;
+use super::sequence::Sender;
+use crate::*;
+
+/// Underlying balance each user starts with
+const INITIAL_BALANCE: u64 = 1_000_000;
+
+/// Position opened by each user before the fuzzed call
+const POSITION: u64 = 100_000;
+
+/// Upper bound on appended accounts
+const MAX_EXTRA_ACCOUNTS: usize = 16;
+
+/// Instruction padded with extra accounts
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum PaddedInstruction {
+    Deposit,
+    Redeem,
+    TransferOwnership,
+}
+
+/// An account appended after the instruction's own accounts
+#[derive(Debug, Clone, Copy, Arbitrary, Serialize, Deserialize)]
+pub struct ExtraAccount {
+    /// Index into the candidate list (reduced modulo its length)
+    pub choice: u8,
+    pub writable: bool,
+    /// Only honored for accounts whose keypair the test holds
+    pub signer: bool,
+}
+
+/// Fuzzable input for remaining-accounts padding
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct RemainingAccountsFuzzInput {
+    pub instruction: PaddedInstruction,
+    /// Amount or shares (reduced below the opened position)
+    pub amount: u32,
+    pub extra: Vec<ExtraAccount>,
+}
+
+/// Snapshot of every candidate account
+async fn snapshot(
+    env: &mut FuzzTestEnv,
+    candidates: &[(Pubkey, Option<&Keypair>)],
+) -> FuzzResult<Vec<Option<(u64, Vec<u8>, Pubkey)>>> {
+    let mut accounts = Vec::with_capacity(candidates.len());
+    for (address, _) in candidates {
+        let account = env.context.banks_client.get_account(*address).await?;
+        accounts.push(account.map(|a| (a.lamports, a.data, a.owner)));
+    }
+    Ok(accounts)
+}
+
+/// Execute a single remaining-accounts iteration
+pub async fn fuzz_remaining_accounts_once(input: RemainingAccountsFuzzInput) -> FuzzResult<()> {
+    let amount = input.amount as u64 % POSITION + 1;
+
+    // ========================================
+    // SETUP: the user, a victim, and an attacker who tags along
+    // ========================================
+
+    let (mut env, setup) =
+        setup_complete_environment(INITIAL_BALANCE, 6, TokenFlavor::SplToken).await?;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    let user = &setup.user;
+    let vault = &setup.vault;
+
+    let victim = setup_user_accounts(
+        &mut env.context,
+        &setup.underlying.mint,
+        &vault.share_mint,
+        TokenFlavor::SplToken,
+    )
+    .await?;
+    mint_tokens_to_user(
+        &mut env.context,
+        &setup.underlying.mint,
+        &setup.underlying.mint_authority,
+        &victim.underlying_token_account,
+        INITIAL_BALANCE,
+    )
+    .await?;
+    for depositor in [user, &victim] {
+        let ix = deposit_ix(&program_id, vault, depositor, POSITION);
+        if let Err(e) = sender.send(&mut env, ix, &depositor.owner).await? {
+            return Err(e.into());
+        }
+    }
+
+    let attacker = new_keypair();
+    let attacker_account = setup_token_account(
+        &mut env.context,
+        &vault.underlying_mint,
+        &attacker.pubkey(),
+        TokenFlavor::SplToken,
+    )
+    .await?;
+
+    let candidates: Vec<(Pubkey, Option<&Keypair>)> = vec![
+        (attacker.pubkey(), Some(&attacker)),
+        (attacker_account, None),
+        (user.owner.pubkey(), Some(&user.owner)),
+        (victim.owner.pubkey(), None),
+        (victim.underlying_token_account, None),
+        (victim.share_token_account, None),
+        (user.underlying_token_account, None),
+        (user.share_token_account, None),
+        (vault.vault, None),
+        (vault.vault_token_account, None),
+        (vault.share_mint, None),
+        (setup.protocol.vault_authority, None),
+        (setup.protocol.protocol_state, None),
+        (setup.protocol.owner, Some(&setup.protocol.owner_keypair)),
+        (spl_token::id(), None),
+        (new_address(), None),
+    ];
+
+    // ========================================
+    // BUILD THE PADDED INSTRUCTION
+    // ========================================
+
+    let new_owner = new_keypair().pubkey();
+    let (mut ix, payer, touched) = match input.instruction {
+        PaddedInstruction::Deposit => (
+            deposit_ix(&program_id, vault, user, amount),
+            &user.owner,
+            vec![
+                vault.vault_token_account,
+                vault.share_mint,
+                user.underlying_token_account,
+                user.share_token_account,
+            ],
+        ),
+        PaddedInstruction::Redeem => (
+            redeem_ix(&program_id, vault, user, amount),
+            &user.owner,
+            vec![
+                vault.vault_token_account,
+                vault.share_mint,
+                user.underlying_token_account,
+                user.share_token_account,
+            ],
+        ),
+        PaddedInstruction::TransferOwnership => (
+            transfer_ownership_ix(&program_id, &setup.protocol.owner, &new_owner),
+            &setup.protocol.owner_keypair,
+            vec![setup.protocol.protocol_state],
+        ),
+    };
+
+    let mut signers = vec![payer];
+    for extra in input.extra.iter().take(MAX_EXTRA_ACCOUNTS) {
+        let (address, keypair) = candidates[extra.choice as usize % candidates.len()];
+        let signs = extra.signer && keypair.is_some();
+        if let (true, Some(keypair)) = (signs, keypair) {
+            if !signers.iter().any(|s| s.pubkey() == keypair.pubkey()) {
+                signers.push(keypair);
+            }
+        }
+        // The runtime rejects executable accounts marked writable before the
+        // program runs, which says nothing about the handler
+        let writable = extra.writable && address != spl_token::id();
+        ix.accounts.push(if writable {
+            AccountMeta::new(address, signs)
+        } else {
+            AccountMeta::new_readonly(address, signs)
+        });
+    }
+
+    // ========================================
+    // EXECUTE
+    // ========================================
+
+    let vault_balance = get_token_balance(&mut env.context, &vault.vault_token_account).await?;
+    let supply = get_mint_supply(&mut env.context, &vault.share_mint).await?;
+    let user_underlying =
+        get_token_balance(&mut env.context, &user.underlying_token_account).await?;
+    let user_shares = get_token_balance(&mut env.context, &user.share_token_account).await?;
+    let before = snapshot(&mut env, &candidates).await?;
+
+    let result = sender.send_with_signers(&mut env, ix, &signers).await?;
+    let after = snapshot(&mut env, &candidates).await?;
+    let context = format!(
+        "{:?} of {} padded with {:?}",
+        input.instruction, amount, input.extra
+    );
+
+    // ========================================
+    // INVARIANT CHECKS
+    // ========================================
+
+    // INVARIANT 1: padding never changes the outcome of a valid call
+    if let Err(e) = &result {
+        assert!(!is_panic(e), "Program panicked on {}: {:?}", context, e);
+        panic!("Valid call rejected once padded, {}: {:?}", context, e);
+    }
+
+    // INVARIANT 2: the call moves exactly what it would without padding
+    match input.instruction {
+        PaddedInstruction::Deposit => {
+            let minted =
+                get_token_balance(&mut env.context, &user.share_token_account).await? - user_shares;
+            assert_eq!(
+                Ok(minted),
+                compute_shares(amount, supply, vault_balance),
+                "Padded deposit mis-minted, {}",
+                context
+            );
+        }
+        PaddedInstruction::Redeem => {
+            let paid = get_token_balance(&mut env.context, &user.underlying_token_account).await?
+                - user_underlying;
+            assert_eq!(
+                Ok(paid),
+                compute_assets(amount, supply, vault_balance),
+                "Padded redeem mis-paid, {}",
+                context
+            );
+        }
+        PaddedInstruction::TransferOwnership => {
+            let owner = get_protocol_state(&mut env.context, &setup.protocol.protocol_state)
+                .await?
+                .owner;
+            assert_eq!(
+                owner, new_owner,
+                "Padded transfer_ownership misdirected, {}",
+                context
+            );
+        }
+    }
+
+    // INVARIANT 3: no appended account is touched unless the call itself
+    // touches it (fee payers only lose lamports)
+    for ((address, _), (before, after)) in candidates.iter().zip(before.iter().zip(&after)) {
+        if touched.contains(address) {
+            continue;
+        }
+        let (Some(before), Some(after)) = (before, after) else {
+            assert_eq!(
+                before, after,
+                "Account {} created or closed, {}",
+                address, context
+            );
+            continue;
+        };
+        if signers.iter().any(|s| s.pubkey() == *address) {
+            assert_eq!(
+                (&before.1, &before.2),
+                (&after.1, &after.2),
+                "Signer {} modified, {}",
+                address,
+                context
+            );
+            assert!(
+                after.0 <= before.0,
+                "Signer {} credited, {}",
+                address,
+                context
+            );
+        } else {
+            assert_eq!(
+                before, after,
+                "CRITICAL VULNERABILITY: appended account {} modified, {}",
+                address, context
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,135 @@
+//! Reproducer files for failing inputs.
+//!
+//! Wrappers call [`track`] with every input before running it. If an
+//! invariant fires, a panic hook writes the input as JSON, together with its
+//! `Debug` form (the operations the body derives from it) and the panic
+//! message, and prints the `cargo test` command that replays it through
+//! [`replay`]. The hook runs before the engine's own hook aborts the process,
+//! so this works under libFuzzer, honggfuzz and AFL++ alike.
+
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::path::PathBuf;
+use std::sync::Once;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::FuzzResult;
+
+/// Environment variable overriding where reproducers are written
+pub const DIR_VAR: &str = "FUZZ_REPRODUCER_DIR";
+
+/// Environment variable naming the reproducer `tests/reproducers.rs` replays
+pub const FILE_VAR: &str = "REPRODUCER";
+
+thread_local! {
+    /// Target name, JSON input and `Debug` form of the input being run
+    static CURRENT: RefCell<Option<(&'static str, Value, String)>> = const { RefCell::new(None) };
+}
+
+/// Records `input` as the one `target` is about to run and seeds the
+/// thread's keypairs from it (see `crate::seed`)
+///
+/// `target` is the `fuzz_core` module name, which is what [`replay`]
+/// dispatches on.
+pub fn track<T: Serialize + Debug>(target: &'static str, input: &T) {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(install_hook);
+
+    let Ok(value) = serde_json::to_value(input) else {
+        crate::seed::clear_seed();
+        return;
+    };
+    crate::seed::seed_from_input(&value);
+    CURRENT.set(Some((target, value, format!("{:#?}", input))));
+}
+
+/// Target and JSON input of the input being run, if tracked
+pub(crate) fn current() -> Option<(&'static str, Value)> {
+    CURRENT.with_borrow(|current| {
+        current
+            .as_ref()
+            .map(|(target, input, _)| (*target, input.clone()))
+    })
+}
+
+/// Chains a hook in front of whatever the engine installed
+fn install_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if let Some((target, input, operations)) = CURRENT.with_borrow_mut(Option::take) {
+            match write(target, input, operations, info) {
+                Ok(path) => eprintln!(
+                    "\nReproducer written to {}\nReplay with:\n    {}={} cargo test -p vault-pda-fuzz --test reproducers -- --nocapture\n",
+                    path.display(),
+                    FILE_VAR,
+                    path.display()
+                ),
+                Err(e) => eprintln!("Failed to write reproducer: {}", e),
+            }
+        }
+        previous(info);
+    }));
+}
+
+fn write(
+    target: &str,
+    input: Value,
+    operations: String,
+    info: &PanicHookInfo,
+) -> FuzzResult<PathBuf> {
+    let dir = std::env::var(DIR_VAR).unwrap_or_else(|_| "reproducers".into());
+    fs::create_dir_all(&dir)?;
+    let dir = fs::canonicalize(dir)?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let path = dir.join(format!(
+        "{}-{}.{:09}.json",
+        target,
+        now.as_secs(),
+        now.subsec_nanos()
+    ));
+
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_default();
+    let reproducer = json!({
+        "target": target,
+        "input": input,
+        "operations": operations,
+        "panic": message,
+        "location": info.location().map(|l| l.to_string()),
+    });
+    fs::write(&path, serde_json::to_string_pretty(&reproducer)?)?;
+
+    Ok(path)
+}
+
+/// Runs the body of `target` on a JSON input, as written by [`track`], with
+/// the keypairs the original run derived from it
+pub fn replay(target: &str, input: Value) -> FuzzResult<()> {
+    crate::seed::seed_from_input(&input);
+
+    fn from_json<T: serde::de::DeserializeOwned>(input: Value) -> FuzzResult<T> {
+        Ok(serde_json::from_value(input)?)
+    }
+
+    dispatch_target!(target, from_json(input))
+}
+
+/// Reads a reproducer file and replays it
+pub fn replay_file(path: &std::path::Path) -> FuzzResult<()> {
+    let reproducer: Value = serde_json::from_slice(&fs::read(path)?)?;
+    let target = reproducer["target"]
+        .as_str()
+        .ok_or("Reproducer has no target")?
+        .to_string();
+    replay(&target, reproducer["input"].clone())
+}
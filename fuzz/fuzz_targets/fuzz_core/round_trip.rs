@@ -0,0 +1,148 @@
+use crate::*;
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{instruction::Instruction, signature::Signer, transaction::Transaction};
+
+/// Fuzzable input for a full deposit -> (yield) -> redeem round trip
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct RoundTripFuzzInput {
+    /// Token program the vault and its accounts belong to
+    #[serde(default)]
+    pub flavor: TokenFlavor,
+    /// Token decimals (for setup)
+    pub decimals: u8,
+    /// Deposit made by another user first, so the vault has an exchange rate
+    pub seed_deposit: u64,
+    /// Tokens donated to the vault before the round trip, skewing the rate
+    pub donation: u64,
+    /// Amount deposited by the user under test
+    pub amount: u64,
+    /// Yield added to the vault while the user holds shares
+    pub yield_amount: u64,
+}
+
+async fn process(env: &mut FuzzTestEnv, ix: Instruction, user: &UserAccounts) -> bool {
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&user.owner.pubkey()),
+        &[&user.owner],
+        env.context.last_blockhash,
+    );
+    compute_units::process_transaction(&mut env.context, &env.program_id, tx)
+        .await
+        .is_ok()
+}
+
+/// Execute a single round trip and check the no-profit invariant
+pub async fn fuzz_round_trip_once(input: RoundTripFuzzInput) -> FuzzResult<()> {
+    let decimals = input.decimals % 19;
+    let seed_deposit = input.seed_deposit % 1_000_000_000_000 + 1;
+    let donation = input.donation % 1_000_000_000_000;
+    let amount = input.amount % 1_000_000_000_000 + 1;
+    let yield_amount = input.yield_amount % 1_000_000_000;
+
+    // Setup complete environment, the default user is the one under test
+    let (mut env, setup) = match setup_complete_environment(amount, decimals, input.flavor).await {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Setup failed: {}", e);
+            return Ok(());
+        }
+    };
+
+    // A second user seeds the vault so the round trip hits the proportional path
+    let seeder = create_and_fund_user(
+        &mut env.context,
+        &setup.underlying,
+        &setup.vault.share_mint,
+        seed_deposit,
+    )
+    .await?;
+    let ix = deposit_ix(&env.program_id, &setup.vault, &seeder, seed_deposit);
+    if !process(&mut env, ix, &seeder).await {
+        return Ok(());
+    }
+
+    // Skew the exchange rate with a direct donation
+    if donation > 0 {
+        donate_to_vault(&mut env.context, &setup.underlying, &setup.vault, donation).await?;
+    }
+
+    // Deposit
+    let ix = deposit_ix(&env.program_id, &setup.vault, &setup.user, amount);
+    if !process(&mut env, ix, &setup.user).await {
+        // Rejected deposits (e.g. rounding to zero shares) must not move funds
+        let balance =
+            get_token_balance(&mut env.context, &setup.user.underlying_token_account).await?;
+        assert_eq!(balance, amount, "Failed deposit moved user funds");
+        return Ok(());
+    }
+
+    // Yield accrues while the user holds shares
+    if yield_amount > 0 {
+        donate_to_vault(
+            &mut env.context,
+            &setup.underlying,
+            &setup.vault,
+            yield_amount,
+        )
+        .await?;
+    }
+
+    // Redeem the whole position
+    let shares = get_token_balance(&mut env.context, &setup.user.share_token_account).await?;
+    let ix = redeem_ix(&env.program_id, &setup.vault, &setup.user, shares);
+    if !process(&mut env, ix, &setup.user).await {
+        return Ok(());
+    }
+
+    let exit_balance =
+        get_token_balance(&mut env.context, &setup.user.underlying_token_account).await?;
+
+    // ========================================
+    // SECURITY PROPERTY CHECKS
+    // ========================================
+
+    // SECURITY PROPERTY 1: NO PROFIT WITHOUT YIELD
+    if yield_amount == 0 {
+        assert!(
+            exit_balance <= amount,
+            "CRITICAL VULNERABILITY: Round trip profit! Deposited {}, withdrew {} \
+            (seed={}, donation={}, shares={})",
+            amount,
+            exit_balance,
+            seed_deposit,
+            donation,
+            shares
+        );
+    }
+
+    // SECURITY PROPERTY 2: NEVER MORE THAN DEPOSIT PLUS ALL YIELD
+    assert!(
+        exit_balance as u128 <= amount as u128 + yield_amount as u128,
+        "CRITICAL VULNERABILITY: Withdrew {} after depositing {} with only {} yield",
+        exit_balance,
+        amount,
+        yield_amount
+    );
+
+    // SECURITY PROPERTY 3: THE SEEDER WAS NOT DILUTED
+    // The seeder's shares must still be worth at least what they were before
+    // the round trip started (seed deposit plus the donation they received)
+    let vault_balance =
+        get_token_balance(&mut env.context, &setup.vault.vault_token_account).await?;
+    assert!(
+        vault_balance as u128 >= seed_deposit as u128 + donation as u128,
+        "CRITICAL VULNERABILITY: Round trip drained the remaining holder! Vault holds {}, \
+        seeder is owed at least {}",
+        vault_balance,
+        seed_deposit as u128 + donation as u128
+    );
+
+    println!(
+        "✓ PASS - deposit={}, withdrew={}, yield={}, seed={}, donation={}",
+        amount, exit_balance, yield_amount, seed_deposit, donation
+    );
+
+    Ok(())
+}
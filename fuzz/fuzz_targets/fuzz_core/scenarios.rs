@@ -0,0 +1,162 @@
+//! Scenario classes reached by fuzz bodies.
+//!
+//! Bodies call [`hit`] when an iteration reaches a named branch
+//! (`FIRST_DEPOSIT`, `YIELD_GROWTH`, an accepted error, ...). Tools read the
+//! set back with [`take`] after each input, e.g. to keep one input per
+//! distinct set when minimizing a corpus.
+//!
+//! Every hit is also counted for the whole process. Wrappers call [`begin`]
+//! once per input, and every `FUZZ_STATS_EVERY` inputs (1000 by default), or
+//! `FUZZ_STATS_INTERVAL` seconds (60 by default) for slow targets, the counts
+//! are dumped as JSON to `FUZZ_STATS_DIR/<target>.json` (`fuzz_stats` by
+//! default), showing whether rare branches are reached at all. The dump also
+//! carries execs/sec, the compute units recorded by `crate::compute_units` and
+//! the invariant checks counted by [`metrics`](super::metrics), and is
+//! written again as a Prometheus textfile beside it.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use super::metrics;
+use crate::FuzzResult;
+
+/// Environment variable setting how many inputs pass between dumps
+pub const EVERY_VAR: &str = "FUZZ_STATS_EVERY";
+
+/// Environment variable setting how many seconds pass between dumps
+pub const INTERVAL_VAR: &str = "FUZZ_STATS_INTERVAL";
+
+/// Environment variable overriding where summaries are written
+pub const DIR_VAR: &str = "FUZZ_STATS_DIR";
+
+thread_local! {
+    static HIT: RefCell<BTreeSet<&'static str>> = const { RefCell::new(BTreeSet::new()) };
+}
+
+/// Hits per scenario since the process started
+static COUNTS: Mutex<BTreeMap<&'static str, u64>> = Mutex::new(BTreeMap::new());
+
+/// Inputs started since the process started
+static ITERATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Target named by the first [`begin`]
+static TARGET: OnceLock<&'static str> = OnceLock::new();
+
+/// When the first [`begin`] ran
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+/// When the summary was last dumped
+static LAST_DUMP: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Records that the current iteration reached `scenario`
+pub fn hit(scenario: &'static str) {
+    HIT.with_borrow_mut(|hit| {
+        hit.insert(scenario);
+    });
+    if let Ok(mut counts) = COUNTS.lock() {
+        *counts.entry(scenario).or_default() += 1;
+    }
+}
+
+/// Scenarios reached since the last call, clearing them
+pub fn take() -> BTreeSet<&'static str> {
+    HIT.with_borrow_mut(std::mem::take)
+}
+
+/// Marks the start of an input for `target`, dumping the summary when due
+pub fn begin(target: &'static str) {
+    TARGET.get_or_init(|| target);
+    let started = *STARTED.get_or_init(Instant::now);
+    let iterations = ITERATIONS.fetch_add(1, Ordering::Relaxed) + 1;
+
+    static EVERY: OnceLock<u64> = OnceLock::new();
+    let every = *EVERY.get_or_init(|| env_or(EVERY_VAR, 1000));
+    static INTERVAL: OnceLock<Duration> = OnceLock::new();
+    let interval = *INTERVAL.get_or_init(|| Duration::from_secs(env_or(INTERVAL_VAR, 60)));
+
+    let overdue = LAST_DUMP
+        .lock()
+        .map(|last| last.unwrap_or(started).elapsed() >= interval)
+        .unwrap_or(false);
+    if iterations.is_multiple_of(every) || overdue {
+        if let Err(e) = dump() {
+            eprintln!("Failed to write scenario summary: {}", e);
+        }
+    }
+}
+
+/// Positive integer in `var`, `default` when unset or invalid
+fn env_or(var: &str, default: u64) -> u64 {
+    std::env::var(var)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(default)
+}
+
+/// Counts so far, with each scenario's share of all inputs
+pub fn summary() -> Value {
+    let iterations = ITERATIONS.load(Ordering::Relaxed);
+    let elapsed = STARTED
+        .get()
+        .map(|started| started.elapsed().as_secs_f64())
+        .unwrap_or_default();
+    let execs_per_sec = if elapsed > 0.0 {
+        iterations as f64 / elapsed
+    } else {
+        0.0
+    };
+    let counts = COUNTS
+        .lock()
+        .map(|counts| counts.clone())
+        .unwrap_or_default();
+    let scenarios: serde_json::Map<String, Value> = counts
+        .into_iter()
+        .map(|(scenario, hits)| {
+            let share = if iterations == 0 {
+                0.0
+            } else {
+                hits as f64 / iterations as f64
+            };
+            (
+                scenario.to_string(),
+                json!({ "hits": hits, "per_input": share }),
+            )
+        })
+        .collect();
+
+    json!({
+        "target": TARGET.get().copied().unwrap_or("unknown"),
+        "iterations": iterations,
+        "elapsed_secs": elapsed,
+        "execs_per_sec": execs_per_sec,
+        "scenarios": scenarios,
+        "invariants": metrics::invariants(),
+        "compute_units": crate::compute_units::summary(),
+    })
+}
+
+/// Writes [`summary`] to `FUZZ_STATS_DIR/<target>.json` and as a Prometheus
+/// textfile to `FUZZ_STATS_DIR/<target>.prom`
+pub fn dump() -> FuzzResult<PathBuf> {
+    if let Ok(mut last) = LAST_DUMP.lock() {
+        *last = Some(Instant::now());
+    }
+    let dir = std::env::var(DIR_VAR).unwrap_or_else(|_| "fuzz_stats".into());
+    fs::create_dir_all(&dir)?;
+
+    let target = TARGET.get().copied().unwrap_or("unknown");
+    let summary = summary();
+    let path = PathBuf::from(dir).join(format!("{}.json", target));
+    fs::write(&path, serde_json::to_string_pretty(&summary)?)?;
+    metrics::write_textfile(&path.with_extension("prom"), &summary)?;
+
+    Ok(path)
+}
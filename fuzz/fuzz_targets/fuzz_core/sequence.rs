@@ -0,0 +1,465 @@
+use std::collections::HashSet;
+
+use super::scenarios;
+use crate::*;
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use vault_math::{compute_assets, compute_shares};
+
+/// Upper bound on operations per input, keeps a single iteration short
+const MAX_OPERATIONS: usize = 32;
+
+/// Upper bound on honest users, the attacker comes on top
+const MAX_HONEST_USERS: u8 = 3;
+
+/// A single step in the fuzzed sequence
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub enum Operation {
+    /// A user deposits (amount is reduced modulo their balance + 2)
+    Deposit { user: u8, amount: u64 },
+    /// A user redeems (shares are reduced modulo their position + 2)
+    Redeem { user: u8, shares: u64 },
+    /// Yield is minted straight into the vault, bypassing deposit
+    Donate { amount: u64 },
+    /// The attacker transfers tokens from their spare account into the vault
+    AttackerDonate { amount: u64 },
+    /// The clock jumps forward
+    Warp { slots: u16 },
+    /// Anyone calls transfer_ownership, with or without the real owner's pubkey
+    TransferOwnership { pass_current_owner: bool },
+}
+
+/// Fuzzable input for stateful multi-user sequence fuzzing
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct SequenceFuzzInput {
+    /// Token program the vault and its accounts belong to
+    #[serde(default)]
+    pub flavor: TokenFlavor,
+    /// Token decimals (for setup)
+    pub decimals: u8,
+    /// Number of honest users (1 to MAX_HONEST_USERS)
+    pub honest_users: u8,
+    /// Initial balance of every user (for setup)
+    pub initial_balance: u64,
+    /// Initial balance of the attacker's spare token account (for setup)
+    pub attacker_spare_balance: u64,
+    /// Operations executed in order against one environment
+    pub operations: Vec<Operation>,
+}
+
+/// Expected per-user state
+#[derive(Debug, Clone, Copy)]
+struct UserModel {
+    balance: u64,
+    shares: u64,
+}
+
+/// Expected state, updated only when an operation succeeds
+#[derive(Debug, Clone)]
+struct Model {
+    vault_balance: u64,
+    share_supply: u64,
+    /// Honest users first, the attacker last
+    users: Vec<UserModel>,
+    attacker_spare: u64,
+    owner: Pubkey,
+}
+
+impl Model {
+    /// Underlying each user could redeem right now
+    fn redeemable(&self) -> Vec<u64> {
+        self.users
+            .iter()
+            .map(|user| {
+                compute_assets(user.shares, self.share_supply, self.vault_balance).unwrap_or(0)
+            })
+            .collect()
+    }
+}
+
+/// Sends transactions, refreshing the blockhash when an identical transaction
+/// was already sent so repeated operations are not rejected as duplicates
+#[derive(Default)]
+pub struct Sender {
+    seen: HashSet<Signature>,
+}
+
+impl Sender {
+    pub async fn send(
+        &mut self,
+        env: &mut FuzzTestEnv,
+        ix: Instruction,
+        payer: &Keypair,
+    ) -> FuzzResult<Result<(), BanksClientError>> {
+        self.send_with_signers(env, ix, &[payer]).await
+    }
+
+    /// Deposits `amount` into `vault` as `user`, who signs and pays the fee
+    pub async fn deposit_as(
+        &mut self,
+        env: &mut FuzzTestEnv,
+        vault: &VaultAccounts,
+        user: &UserAccounts,
+        amount: u64,
+    ) -> FuzzResult<Result<(), BanksClientError>> {
+        let ix = deposit_ix(&env.program_id, vault, user, amount);
+        self.send(env, ix, &user.owner).await
+    }
+
+    /// Redeems `shares` from `vault` as `user`, who signs and pays the fee
+    pub async fn redeem_as(
+        &mut self,
+        env: &mut FuzzTestEnv,
+        vault: &VaultAccounts,
+        user: &UserAccounts,
+        shares: u64,
+    ) -> FuzzResult<Result<(), BanksClientError>> {
+        let ix = redeem_ix(&env.program_id, vault, user, shares);
+        self.send(env, ix, &user.owner).await
+    }
+
+    /// Like [`Sender::send`], with additional signers after the fee payer
+    /// (`signers[0]`)
+    pub async fn send_with_signers(
+        &mut self,
+        env: &mut FuzzTestEnv,
+        ix: Instruction,
+        signers: &[&Keypair],
+    ) -> FuzzResult<Result<(), BanksClientError>> {
+        self.send_instructions(env, &[ix], signers).await
+    }
+
+    /// Sends all of `instructions` in a single transaction
+    pub async fn send_instructions(
+        &mut self,
+        env: &mut FuzzTestEnv,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> FuzzResult<Result<(), BanksClientError>> {
+        let payer = signers[0].pubkey();
+        let mut tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer),
+            signers,
+            env.context.last_blockhash,
+        );
+
+        if !self.seen.insert(tx.signatures[0]) {
+            env.context.last_blockhash = env.context.get_new_latest_blockhash().await?;
+            tx = Transaction::new_signed_with_payer(
+                instructions,
+                Some(&payer),
+                signers,
+                env.context.last_blockhash,
+            );
+            self.seen.insert(tx.signatures[0]);
+        }
+
+        Ok(compute_units::process_transaction(&mut env.context, &env.program_id, tx).await)
+    }
+}
+
+/// Compares on-chain state against the model and checks global invariants
+async fn check_invariants(
+    env: &mut FuzzTestEnv,
+    setup: &CompleteSetup,
+    users: &[&UserAccounts],
+    model: &Model,
+    context: &str,
+) -> FuzzResult<()> {
+    let vault_balance =
+        get_token_balance(&mut env.context, &setup.vault.vault_token_account).await?;
+    let share_supply = get_mint_supply(&mut env.context, &setup.vault.share_mint).await?;
+    let protocol_state =
+        get_protocol_state(&mut env.context, &setup.protocol.protocol_state).await?;
+
+    // INVARIANT 1: CONSERVATION - every account holds exactly what the model says
+    assert_eq!(
+        vault_balance, model.vault_balance,
+        "Vault balance diverged {}",
+        context
+    );
+    let mut total_shares = 0u64;
+    for (index, (user, expected)) in users.iter().zip(&model.users).enumerate() {
+        let balance = get_token_balance(&mut env.context, &user.underlying_token_account).await?;
+        let shares = get_token_balance(&mut env.context, &user.share_token_account).await?;
+        assert_eq!(
+            balance, expected.balance,
+            "User {} balance diverged {}",
+            index, context
+        );
+        assert_eq!(
+            shares, expected.shares,
+            "User {} shares diverged {}",
+            index, context
+        );
+        total_shares += shares;
+    }
+
+    // INVARIANT 2: SHARE ACCOUNTING - user positions add up to the supply
+    assert_eq!(
+        share_supply, model.share_supply,
+        "Share supply diverged {}",
+        context
+    );
+    assert_eq!(
+        total_shares, share_supply,
+        "Share supply not fully held {}",
+        context
+    );
+
+    // INVARIANT 3: SOLVENCY - outstanding shares are always backed by assets
+    if share_supply > 0 {
+        assert!(
+            vault_balance > 0,
+            "Shares outstanding with an empty vault {}",
+            context
+        );
+    }
+
+    // INVARIANT 4: OWNERSHIP - only changes through a successful transfer
+    assert_eq!(
+        protocol_state.owner, model.owner,
+        "Protocol owner diverged {}",
+        context
+    );
+
+    Ok(())
+}
+
+/// Execute a single fuzz iteration over a sequence of operations
+pub async fn fuzz_sequence_once(input: SequenceFuzzInput) -> FuzzResult<()> {
+    let decimals = input.decimals % 19;
+    let honest_users = (input.honest_users % MAX_HONEST_USERS + 1) as usize;
+    let initial_balance = input.initial_balance % 1_000_000_000_000_000;
+    let attacker_spare_balance = input.attacker_spare_balance % 1_000_000_000_000_000;
+
+    let (mut env, setup) =
+        match setup_complete_environment(initial_balance, decimals, input.flavor).await {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("Setup failed: {}", e);
+                return Ok(());
+            }
+        };
+
+    // Additional honest users, funded like the default user, then the
+    // attacker, whose spare account it donates from
+    let extra_users = setup_n_users(
+        &mut env.context,
+        honest_users - 1,
+        &setup.underlying,
+        &setup.vault.share_mint,
+        &vec![initial_balance; honest_users - 1],
+    )
+    .await?;
+    let attacker = Attacker::new(
+        &mut env.context,
+        &setup.underlying,
+        &setup.vault,
+        initial_balance,
+        attacker_spare_balance,
+    )
+    .await?;
+
+    // Honest users first, the attacker last
+    let mut users: Vec<&UserAccounts> = vec![&setup.user];
+    users.extend(extra_users.iter());
+    users.push(&attacker.position);
+    let attacker_index = users.len() - 1;
+
+    let mut model = Model {
+        vault_balance: 0,
+        share_supply: 0,
+        users: vec![
+            UserModel {
+                balance: initial_balance,
+                shares: 0,
+            };
+            users.len()
+        ],
+        attacker_spare: attacker_spare_balance,
+        owner: setup.protocol.owner,
+    };
+    let mut sender = Sender::default();
+    let caller = env.context.payer.insecure_clone();
+
+    for (step, operation) in input.operations.iter().take(MAX_OPERATIONS).enumerate() {
+        let redeemable_before = model.redeemable();
+        let mut actor = None;
+
+        match *operation {
+            Operation::Deposit { user, amount } => {
+                let index = user as usize % users.len();
+                let position = model.users[index];
+                let amount = amount % position.balance.saturating_add(2);
+                let expected = compute_shares(amount, model.share_supply, model.vault_balance);
+                actor = Some(index);
+
+                let result = sender
+                    .deposit_as(&mut env, &setup.vault, users[index], amount)
+                    .await?;
+
+                match (result, expected) {
+                    (Ok(()), Ok(shares)) => {
+                        scenarios::hit(if model.share_supply == 0 {
+                            "FIRST_DEPOSIT"
+                        } else {
+                            "DEPOSIT"
+                        });
+                        model.vault_balance += amount;
+                        model.share_supply += shares;
+                        model.users[index].balance -= amount;
+                        model.users[index].shares += shares;
+                    }
+                    (Ok(()), Err(e)) => {
+                        panic!(
+                            "Deposit of {} succeeded but kernel rejects it: {:?}",
+                            amount, e
+                        )
+                    }
+                    (Err(_), Ok(_)) if amount > position.balance => {
+                        scenarios::hit("DEPOSIT_OVERDRAWN")
+                    }
+                    (Err(e), Ok(shares)) => {
+                        // Token-level overflow on the vault balance or supply is acceptable
+                        let overflows = model.vault_balance.checked_add(amount).is_none()
+                            || model.share_supply.checked_add(shares).is_none();
+                        assert!(overflows, "Valid deposit of {} failed: {:?}", amount, e);
+                        scenarios::hit("DEPOSIT_TOKEN_OVERFLOW");
+                    }
+                    (Err(_), Err(_)) => scenarios::hit("KERNEL_REJECTED"),
+                }
+            }
+            Operation::Redeem { user, shares } => {
+                let index = user as usize % users.len();
+                let position = model.users[index];
+                let shares = shares % position.shares.saturating_add(2);
+                let expected = compute_assets(shares, model.share_supply, model.vault_balance);
+                actor = Some(index);
+
+                let result = sender
+                    .redeem_as(&mut env, &setup.vault, users[index], shares)
+                    .await?;
+
+                match (result, expected) {
+                    (Ok(()), Ok(assets)) => {
+                        scenarios::hit(if shares == model.share_supply {
+                            "FULL_EXIT"
+                        } else {
+                            "REDEEM"
+                        });
+                        model.vault_balance -= assets;
+                        model.share_supply -= shares;
+                        model.users[index].balance += assets;
+                        model.users[index].shares -= shares;
+                    }
+                    (Ok(()), Err(e)) => {
+                        panic!(
+                            "Redeem of {} succeeded but kernel rejects it: {:?}",
+                            shares, e
+                        )
+                    }
+                    (Err(_), Ok(_)) if shares > position.shares => {
+                        scenarios::hit("REDEEM_OVERDRAWN")
+                    }
+                    (Err(e), Ok(_)) => panic!("Valid redeem of {} failed: {:?}", shares, e),
+                    (Err(_), Err(_)) => scenarios::hit("KERNEL_REJECTED"),
+                }
+            }
+            Operation::Donate { amount } => {
+                let amount = amount % 1_000_000_000_000;
+                if amount > 0
+                    && donate_to_vault(&mut env.context, &setup.underlying, &setup.vault, amount)
+                        .await
+                        .is_ok()
+                {
+                    scenarios::hit("YIELD_GROWTH");
+                    model.vault_balance += amount;
+                }
+            }
+            Operation::AttackerDonate { amount } => {
+                let amount = amount % model.attacker_spare.saturating_add(2);
+                actor = Some(attacker_index);
+
+                let result = attacker
+                    .donate(&mut env, &mut sender, &setup.vault, amount)
+                    .await?;
+
+                if result.is_ok() {
+                    scenarios::hit("ATTACKER_DONATION");
+                    model.vault_balance += amount;
+                    model.attacker_spare -= amount;
+                }
+            }
+            Operation::Warp { slots } => {
+                advance_slots(&mut env.context, slots as u64 + 1).await?;
+            }
+            Operation::TransferOwnership { pass_current_owner } => {
+                let current_owner = if pass_current_owner {
+                    model.owner
+                } else {
+                    new_address()
+                };
+                let new_owner = new_keypair().pubkey();
+
+                // Nobody in the sequence holds the owner's key, so the owner is
+                // passed by pubkey alone even where the build marks it a signer
+                let mut ix = transfer_ownership_ix(&env.program_id, &current_owner, &new_owner);
+                ix.accounts[1].is_signer = false;
+                let result = sender.send(&mut env, ix, &caller).await?;
+
+                if result.is_ok() {
+                    assert!(
+                        pass_current_owner,
+                        "Ownership transferred without naming the current owner"
+                    );
+                    model.owner = new_owner;
+                }
+            }
+        }
+
+        let context = format!("after step {} ({:?}), model: {:?}", step, operation, model);
+        check_invariants(&mut env, &setup, &users, &model, &context).await?;
+
+        // INVARIANT 5: NO GRIEFING - another participant's actions can never
+        // lower what a user could redeem (there are no fees to dilute them)
+        let redeemable_after = model.redeemable();
+        for (index, (before, after)) in redeemable_before.iter().zip(&redeemable_after).enumerate()
+        {
+            if Some(index) == actor {
+                continue;
+            }
+            assert!(
+                after >= before,
+                "CRITICAL VULNERABILITY: User {} redeemable value dropped {} -> {} {}{}",
+                index,
+                before,
+                after,
+                context,
+                if actor == Some(attacker_index) {
+                    " (attacker action)"
+                } else {
+                    ""
+                }
+            );
+        }
+    }
+
+    println!(
+        "✓ PASS - {} users, {} operations, final vault={}, supply={}",
+        users.len(),
+        input.operations.len().min(MAX_OPERATIONS),
+        model.vault_balance,
+        model.share_supply
+    );
+
+    Ok(())
+}
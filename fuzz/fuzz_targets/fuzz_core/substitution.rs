@@ -0,0 +1,340 @@
+use anchor_lang::{InstructionData, ToAccountMetas};
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signer};
+use spl_token::instruction as token_instruction;
+
+use super::sequence::Sender;
+use crate::*;
+
+/// Underlying balance each participant starts with
+const INITIAL_BALANCE: u64 = 1_000_000;
+
+/// Amount every position is opened with, larger than any fuzzed amount
+const POSITION: u64 = 10_000;
+
+/// Vault a candidate account legitimately belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    A,
+    B,
+}
+
+const ANY: &[Target] = &[Target::A, Target::B];
+const ONLY_A: &[Target] = &[Target::A];
+const ONLY_B: &[Target] = &[Target::B];
+const NONE: &[Target] = &[];
+
+/// One possible account for an instruction slot
+struct Candidate {
+    label: &'static str,
+    key: Pubkey,
+    valid_for: &'static [Target],
+}
+
+fn candidate(label: &'static str, key: Pubkey, valid_for: &'static [Target]) -> Candidate {
+    Candidate {
+        label,
+        key,
+        valid_for,
+    }
+}
+
+/// Fuzzable input for account substitution on deposit and redeem
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct SubstitutionFuzzInput {
+    /// Redeem instead of deposit
+    pub redeem: bool,
+    /// Amount or shares (reduced to 1..=1000 so valid calls always succeed)
+    pub amount: u16,
+    /// Candidate choice per account slot, in instruction order (signer excluded)
+    pub slots: [u8; 8],
+}
+
+/// Picks a candidate for a slot, landing on the canonical vault A account
+/// about half of the time so most inputs only substitute a few accounts
+fn pick(candidates: &[Candidate], choice: u8) -> &Candidate {
+    candidates
+        .get(choice as usize % (candidates.len() * 2))
+        .unwrap_or(&candidates[0])
+}
+
+/// Execute a single account substitution iteration
+pub async fn fuzz_substitution_once(input: SubstitutionFuzzInput) -> FuzzResult<()> {
+    let amount = input.amount as u64 % 1_000 + 1;
+
+    // ========================================
+    // SETUP: vault A and vault B, each with open positions
+    // ========================================
+
+    let (
+        mut env,
+        TwoVaultSetup {
+            a: setup,
+            vault_b,
+            user_b,
+            ..
+        },
+    ) = setup_two_vault_environment(INITIAL_BALANCE, 6, TokenFlavor::SplToken).await?;
+    let program_id = env.program_id;
+    let user = &setup.user;
+    let vault_a = &setup.vault;
+
+    // Second pair of vault A accounts owned by the same user
+    let alt_underlying = setup_token_account(
+        &mut env.context,
+        &vault_a.underlying_mint,
+        &user.owner.pubkey(),
+        TokenFlavor::SplToken,
+    )
+    .await?;
+    let alt_share = setup_token_account(
+        &mut env.context,
+        &vault_a.share_mint,
+        &user.owner.pubkey(),
+        TokenFlavor::SplToken,
+    )
+    .await?;
+    mint_tokens_to_user(
+        &mut env.context,
+        &setup.underlying.mint,
+        &setup.underlying.mint_authority,
+        &alt_underlying,
+        INITIAL_BALANCE,
+    )
+    .await?;
+
+    let victim = setup_user_accounts(
+        &mut env.context,
+        &vault_a.underlying_mint,
+        &vault_a.share_mint,
+        TokenFlavor::SplToken,
+    )
+    .await?;
+    mint_tokens_to_user(
+        &mut env.context,
+        &setup.underlying.mint,
+        &setup.underlying.mint_authority,
+        &victim.underlying_token_account,
+        INITIAL_BALANCE,
+    )
+    .await?;
+
+    // Mint and token accounts the attacker fully controls
+    let attacker = new_keypair();
+    let attacker_mint = setup_underlying_mint(&mut env.context, 6, TokenFlavor::SplToken).await?;
+    let attacker_mint_account = setup_token_account(
+        &mut env.context,
+        &attacker_mint.mint,
+        &user.owner.pubkey(),
+        TokenFlavor::SplToken,
+    )
+    .await?;
+    mint_tokens_to_user(
+        &mut env.context,
+        &attacker_mint.mint,
+        &attacker_mint.mint_authority,
+        &attacker_mint_account,
+        POSITION,
+    )
+    .await?;
+    let attacker_a_account = setup_token_account(
+        &mut env.context,
+        &vault_a.underlying_mint,
+        &attacker.pubkey(),
+        TokenFlavor::SplToken,
+    )
+    .await?;
+
+    let mut sender = Sender::default();
+    let opening = [
+        (
+            deposit_ix(&program_id, vault_a, user, POSITION * 2),
+            &user.owner,
+        ),
+        (
+            deposit_ix(&program_id, &vault_b, &user_b, POSITION),
+            &user.owner,
+        ),
+        (
+            deposit_ix(&program_id, vault_a, &victim, POSITION),
+            &victim.owner,
+        ),
+        (
+            token_instruction::transfer(
+                &spl_token::id(),
+                &user.share_token_account,
+                &alt_share,
+                &user.owner.pubkey(),
+                &[],
+                POSITION,
+            )?,
+            &user.owner,
+        ),
+    ];
+    for (ix, signer) in opening {
+        if let Err(e) = sender.send(&mut env, ix, signer).await? {
+            return Err(e.into());
+        }
+    }
+
+    // ========================================
+    // CANDIDATES PER ACCOUNT SLOT
+    // ========================================
+
+    let vault_authority = derive_vault_authority_pda(&program_id).0;
+    let slots: [Vec<Candidate>; 8] = [
+        vec![
+            candidate("vault A", vault_a.vault, ONLY_A),
+            candidate("vault B", vault_b.vault, ONLY_B),
+            candidate(
+                "vault A token account as vault",
+                vault_a.vault_token_account,
+                NONE,
+            ),
+            candidate("random vault", new_address(), NONE),
+        ],
+        vec![
+            candidate("mint A", vault_a.underlying_mint, ONLY_A),
+            candidate("mint B", vault_b.underlying_mint, ONLY_B),
+            candidate("attacker mint", attacker_mint.mint, NONE),
+        ],
+        vec![
+            candidate("vault A token account", vault_a.vault_token_account, ONLY_A),
+            candidate("vault B token account", vault_b.vault_token_account, ONLY_B),
+            candidate("user account as vault", user.underlying_token_account, NONE),
+            candidate("attacker account as vault", attacker_a_account, NONE),
+        ],
+        vec![
+            candidate("share mint A", vault_a.share_mint, ONLY_A),
+            candidate("share mint B", vault_b.share_mint, ONLY_B),
+            candidate("attacker mint as share mint", attacker_mint.mint, NONE),
+        ],
+        vec![
+            candidate("vault authority", vault_authority, ANY),
+            candidate(
+                "protocol state as authority",
+                setup.protocol.protocol_state,
+                NONE,
+            ),
+            candidate("random authority", new_address(), NONE),
+        ],
+        vec![
+            candidate("user underlying A", user.underlying_token_account, ONLY_A),
+            candidate("user second underlying A", alt_underlying, ONLY_A),
+            candidate("victim underlying A", victim.underlying_token_account, NONE),
+            candidate("user underlying B", user_b.underlying_token_account, ONLY_B),
+            candidate("user attacker-mint account", attacker_mint_account, NONE),
+        ],
+        vec![
+            candidate("user shares A", user.share_token_account, ONLY_A),
+            candidate("user second shares A", alt_share, ONLY_A),
+            candidate("victim shares A", victim.share_token_account, NONE),
+            candidate("user shares B", user_b.share_token_account, ONLY_B),
+            candidate(
+                "user attacker-mint account as shares",
+                attacker_mint_account,
+                NONE,
+            ),
+        ],
+        vec![
+            candidate("token program", spl_token::id(), ANY),
+            candidate(
+                "system program as token program",
+                solana_sdk::system_program::ID,
+                NONE,
+            ),
+        ],
+    ];
+
+    let chosen: Vec<&Candidate> = slots
+        .iter()
+        .zip(input.slots)
+        .map(|(candidates, choice)| pick(candidates, choice))
+        .collect();
+
+    // Valid only if every account belongs to the same vault
+    let valid = [Target::A, Target::B]
+        .iter()
+        .any(|target| chosen.iter().all(|c| c.valid_for.contains(target)));
+    let labels: Vec<&str> = chosen.iter().map(|c| c.label).collect();
+
+    // ========================================
+    // EXECUTE
+    // ========================================
+
+    let keys: Vec<Pubkey> = chosen.iter().map(|c| c.key).collect();
+    let flag = derive_flag_pda(&program_id, &keys[0]).0;
+    let player_record = derive_player_pda(&program_id, &flag, &user.owner.pubkey()).0;
+    let activity_stats = derive_activity_stats_pda(&program_id, &keys[0]).0;
+    let reserve_config = derive_reserve_config_pda(&program_id, &keys[0]).0;
+    let ix = if input.redeem {
+        let accounts = vault_pda::accounts::Redeem {
+            vault: keys[0],
+            underlying_mint: keys[1],
+            vault_token_account: keys[2],
+            share_mint: keys[3],
+            vault_authority: keys[4],
+            redeemer_underlying_account: keys[5],
+            redeemer_share_account: keys[6],
+            redeemer: user.owner.pubkey(),
+            flag,
+            player_record,
+            activity_stats,
+            reserve_config,
+            treasury: None,
+            protocol_state: None,
+            fee_exemption: derive_fee_exemption_pda(&program_id, &user.owner.pubkey()).0,
+            token_program: keys[7],
+        };
+        Instruction {
+            program_id,
+            accounts: accounts.to_account_metas(None),
+            data: vault_pda::instruction::Redeem { shares: amount }.data(),
+        }
+    } else {
+        let accounts = vault_pda::accounts::Deposit {
+            vault: keys[0],
+            underlying_mint: keys[1],
+            vault_token_account: keys[2],
+            share_mint: keys[3],
+            vault_authority: keys[4],
+            depositor_underlying_account: keys[5],
+            depositor_share_account: keys[6],
+            depositor: user.owner.pubkey(),
+            flag,
+            player_record,
+            activity_stats,
+            reserve_config,
+            treasury: None,
+            protocol_state: None,
+            token_program: keys[7],
+        };
+        Instruction {
+            program_id,
+            accounts: accounts.to_account_metas(None),
+            data: vault_pda::instruction::Deposit { amount }.data(),
+        }
+    };
+
+    let instruction = if input.redeem { "redeem" } else { "deposit" };
+    let result = sender.send(&mut env, ix, &user.owner).await?;
+
+    // ========================================
+    // INVARIANT CHECKS
+    // ========================================
+
+    match (result, valid) {
+        (Ok(()), true) | (Err(_), false) => {}
+        (Err(e), true) => panic!(
+            "Documented-valid {} with {:?} was rejected: {:?}",
+            instruction, labels, e
+        ),
+        (Ok(()), false) => panic!(
+            "CRITICAL VULNERABILITY: {} of {} accepted mismatched accounts {:?}",
+            instruction, amount, labels
+        ),
+    }
+
+    Ok(())
+}
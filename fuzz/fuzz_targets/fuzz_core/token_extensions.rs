@@ -0,0 +1,300 @@
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+};
+use spl_token_2022::instruction as token_2022_instruction;
+use vault_math::{compute_assets, compute_shares};
+
+use super::corruption::is_panic;
+use super::sequence::Sender;
+use crate::*;
+
+/// Underlying balance the user starts with, above any fuzzed deposit
+const INITIAL_BALANCE: u64 = 1 << 33;
+
+/// Fuzzable input for deposits and redeems against Token-2022 mints
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+pub struct TokenExtensionsFuzzInput {
+    pub extensions: MintExtensions,
+    /// Decimals of the underlying mint (reduced to 0..=9)
+    pub decimals: u8,
+    /// Have the freeze authority thaw the vault token account when new
+    /// accounts start frozen
+    pub thaw_vault: bool,
+    /// Deposit amount (plus one)
+    pub amount: u32,
+    /// Amount the permanent delegate pulls out of the vault between the
+    /// deposit and the redeem (reduced to the vault balance)
+    pub sweep: Option<u32>,
+    /// Fraction of the minted shares to redeem, in 256ths (plus one)
+    pub redeem_fraction: u8,
+}
+
+impl TokenExtensionsFuzzInput {
+    /// Whether no extension can block a transfer into or out of the vault
+    fn transfers_unrestricted(&self) -> bool {
+        !self.extensions.transfer_hook && (!self.extensions.default_frozen || self.thaw_vault)
+    }
+}
+
+/// Balances the program reads and writes
+#[derive(Debug, Clone, Copy)]
+struct Balances {
+    vault: u64,
+    supply: u64,
+    user_underlying: u64,
+    user_shares: u64,
+}
+
+async fn balances(
+    env: &mut FuzzTestEnv,
+    vault: &VaultAccounts,
+    user: &UserAccounts,
+) -> FuzzResult<Balances> {
+    Ok(Balances {
+        vault: get_token_balance(&mut env.context, &vault.vault_token_account).await?,
+        supply: get_mint_supply(&mut env.context, &vault.share_mint).await?,
+        user_underlying: get_token_balance(&mut env.context, &user.underlying_token_account)
+            .await?,
+        user_shares: get_token_balance(&mut env.context, &user.share_token_account).await?,
+    })
+}
+
+/// Sends `ix` paid by the test payer and signed by `signer`, failing the
+/// iteration on rejection (only used for setup steps that must succeed)
+async fn send_setup(
+    env: &mut FuzzTestEnv,
+    sender: &mut Sender,
+    ix: Instruction,
+    signer: &Keypair,
+) -> FuzzResult<()> {
+    let payer = env.context.payer.insecure_clone();
+    if let Err(e) = sender.send_with_signers(env, ix, &[&payer, signer]).await? {
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Execute a single Token-2022 extension iteration
+pub async fn fuzz_token_extensions_once(input: TokenExtensionsFuzzInput) -> FuzzResult<()> {
+    let token_program = spl_token_2022::id();
+    let amount = input.amount as u64 + 1;
+
+    // ========================================
+    // SETUP: Token-2022 mint with the fuzzed extensions
+    // ========================================
+
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    let protocol = setup_protocol(&mut env.context, &program_id).await?;
+    let underlying =
+        setup_token_2022_mint(&mut env.context, input.decimals % 10, &input.extensions).await?;
+    let authority = &underlying.mint_authority;
+
+    // INVARIANT 1: any Token-2022 mint can back a vault
+    let ix = with_token_program(
+        initialize_vault_ix(&program_id, &underlying.mint, &protocol.owner),
+        &token_program,
+    );
+    if let Err(e) = sender.send(&mut env, ix, &protocol.owner_keypair).await? {
+        panic!(
+            "initialize_vault rejected a Token-2022 mint with {:?}: {:?}",
+            input.extensions, e
+        );
+    }
+    let vault = derive_vault_pda(&program_id, &underlying.mint).0;
+    let vault = VaultAccounts {
+        vault,
+        vault_token_account: derive_vault_token_account_pda(&program_id, &vault).0,
+        share_mint: derive_share_mint_pda(&program_id, &vault).0,
+        underlying_mint: underlying.mint,
+        token_program,
+    };
+
+    let owner = new_keypair();
+    let user = UserAccounts {
+        underlying_token_account: setup_token_2022_account(
+            &mut env.context,
+            &underlying.mint,
+            &owner.pubkey(),
+        )
+        .await?,
+        share_token_account: setup_token_2022_account(
+            &mut env.context,
+            &vault.share_mint,
+            &owner.pubkey(),
+        )
+        .await?,
+        owner,
+    };
+
+    if input.extensions.default_frozen {
+        let mut thaw = vec![user.underlying_token_account];
+        if input.thaw_vault {
+            thaw.push(vault.vault_token_account);
+        }
+        for account in thaw {
+            let ix = token_2022_instruction::thaw_account(
+                &token_program,
+                &account,
+                &underlying.mint,
+                &authority.pubkey(),
+                &[],
+            )?;
+            send_setup(&mut env, &mut sender, ix, authority).await?;
+        }
+    }
+
+    let ix = token_2022_instruction::mint_to(
+        &token_program,
+        &underlying.mint,
+        &user.underlying_token_account,
+        &authority.pubkey(),
+        &[],
+        INITIAL_BALANCE,
+    )?;
+    send_setup(&mut env, &mut sender, ix, authority).await?;
+
+    // ========================================
+    // DEPOSIT
+    // ========================================
+
+    let before = balances(&mut env, &vault, &user).await?;
+    let ix = deposit_ix(&program_id, &vault, &user, amount);
+    let result = sender.send(&mut env, ix, &user.owner).await?;
+    let after = balances(&mut env, &vault, &user).await?;
+
+    if let Err(e) = result {
+        // INVARIANT 2: extensions reject with an error, never an abort
+        assert!(
+            !is_panic(&e),
+            "Deposit panicked with {:?}: {:?}",
+            input.extensions,
+            e
+        );
+        // INVARIANT 3: extensions that cannot block transfers do not block deposits
+        assert!(
+            !input.transfers_unrestricted(),
+            "Deposit of {} rejected with {:?}: {:?}",
+            amount,
+            input.extensions,
+            e
+        );
+        return Ok(());
+    }
+
+    // INVARIANT 4: shares are minted for what the vault received, not what was sent
+    let paid = before.user_underlying.wrapping_sub(after.user_underlying);
+    let received = after.vault.wrapping_sub(before.vault);
+    let minted = after.user_shares.wrapping_sub(before.user_shares);
+    assert_eq!(paid, amount, "Depositor paid the wrong amount");
+    assert!(received <= paid, "Vault received more than was sent");
+    assert_eq!(
+        after.supply.wrapping_sub(before.supply),
+        minted,
+        "Supply and balance diverged"
+    );
+    let fair = compute_shares(received, before.supply, before.vault).unwrap_or(0);
+    assert!(
+        minted <= fair,
+        "CRITICAL VULNERABILITY: {} shares minted for {} received ({} sent) with {:?}",
+        minted,
+        received,
+        paid,
+        input.extensions
+    );
+
+    // ========================================
+    // PERMANENT DELEGATE SWEEP
+    // ========================================
+
+    if let (true, Some(sweep)) = (input.extensions.permanent_delegate, input.sweep) {
+        let destination =
+            setup_token_2022_account(&mut env.context, &underlying.mint, &authority.pubkey())
+                .await?;
+        let ix = token_2022_instruction::transfer_checked(
+            &token_program,
+            &vault.vault_token_account,
+            &underlying.mint,
+            &destination,
+            &authority.pubkey(),
+            &[],
+            sweep as u64 % (after.vault + 1),
+            underlying.decimals,
+        )?;
+        // Frozen accounts may reject the sweep; the redeem below is checked
+        // against whatever balance is left either way
+        let payer = env.context.payer.insecure_clone();
+        let _ = sender
+            .send_with_signers(&mut env, ix, &[&payer, authority])
+            .await?;
+    }
+
+    // ========================================
+    // REDEEM
+    // ========================================
+
+    let before = balances(&mut env, &vault, &user).await?;
+    let shares = (before.user_shares as u128 * (input.redeem_fraction as u128 + 1) / 256) as u64;
+    if shares == 0 {
+        return Ok(());
+    }
+    let expected = compute_assets(shares, before.supply, before.vault);
+
+    let ix = redeem_ix(&program_id, &vault, &user, shares);
+    let result = sender.send(&mut env, ix, &user.owner).await?;
+    let after = balances(&mut env, &vault, &user).await?;
+
+    if let Err(e) = result {
+        // INVARIANT 2: extensions reject with an error, never an abort
+        assert!(
+            !is_panic(&e),
+            "Redeem panicked with {:?}: {:?}",
+            input.extensions,
+            e
+        );
+        // INVARIANT 3: a redeem worth something is never blocked by an
+        // extension that cannot block transfers
+        assert!(
+            !(input.transfers_unrestricted() && matches!(expected, Ok(assets) if assets > 0)),
+            "Redeem of {} shares rejected with {:?}: {:?}",
+            shares,
+            input.extensions,
+            e
+        );
+        return Ok(());
+    }
+
+    // INVARIANT 5: the vault pays out exactly the value of the burned shares
+    let outflow = before.vault.wrapping_sub(after.vault);
+    let received = after.user_underlying.wrapping_sub(before.user_underlying);
+    assert_eq!(
+        before.user_shares.wrapping_sub(after.user_shares),
+        shares,
+        "Redeemer burned the wrong amount"
+    );
+    assert_eq!(
+        before.supply.wrapping_sub(after.supply),
+        shares,
+        "Supply mis-burned"
+    );
+    assert_eq!(
+        Ok(outflow),
+        expected,
+        "Vault paid {} for {} shares with {:?}",
+        outflow,
+        shares,
+        input.extensions
+    );
+    assert!(
+        received <= outflow,
+        "Redeemer received {} but the vault only paid {}",
+        received,
+        outflow
+    );
+
+    Ok(())
+}
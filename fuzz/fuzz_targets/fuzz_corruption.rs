@@ -0,0 +1,19 @@
+use fuzz_helpers::fuzz_core::corruption::{fuzz_corruption_once, CorruptionFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use honggfuzz::fuzz;
+
+fn main() {
+    // Initialize honggfuzz
+    loop {
+        fuzz!(|input: CorruptionFuzzInput| {
+            reproducer::track("corruption", &input);
+            scenarios::begin("corruption");
+            // Run the async fuzz test
+            block_on(async {
+                if let Err(e) = fuzz_corruption_once(input).await {
+                    eprintln!("Fuzz iteration failed: {}", e);
+                }
+            });
+        });
+    }
+}
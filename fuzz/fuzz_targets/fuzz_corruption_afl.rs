@@ -0,0 +1,18 @@
+use fuzz_helpers::fuzz_core::corruption::{fuzz_corruption_once, CorruptionFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<CorruptionFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("corruption", &input);
+        scenarios::begin("corruption");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_corruption_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::corruption::{fuzz_corruption_once, CorruptionFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: CorruptionFuzzInput| {
+    reproducer::track("corruption", &input);
+    scenarios::begin("corruption");
+    // Run the async fuzz test
+    block_on(async {
+        if let Err(e) = fuzz_corruption_once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+});
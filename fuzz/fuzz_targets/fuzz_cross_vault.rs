@@ -0,0 +1,19 @@
+use fuzz_helpers::fuzz_core::cross_vault::{fuzz_cross_vault_once, CrossVaultFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use honggfuzz::fuzz;
+
+fn main() {
+    // Initialize honggfuzz
+    loop {
+        fuzz!(|input: CrossVaultFuzzInput| {
+            reproducer::track("cross_vault", &input);
+            scenarios::begin("cross_vault");
+            // Run the async fuzz test
+            block_on(async {
+                if let Err(e) = fuzz_cross_vault_once(input).await {
+                    eprintln!("Fuzz iteration failed: {}", e);
+                }
+            });
+        });
+    }
+}
@@ -0,0 +1,18 @@
+use fuzz_helpers::fuzz_core::cross_vault::{fuzz_cross_vault_once, CrossVaultFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<CrossVaultFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("cross_vault", &input);
+        scenarios::begin("cross_vault");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_cross_vault_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
@@ -0,0 +1,19 @@
+use fuzz_helpers::fuzz_core::decimals::{fuzz_decimals_once, DecimalsFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use honggfuzz::fuzz;
+
+fn main() {
+    // Initialize honggfuzz
+    loop {
+        fuzz!(|input: DecimalsFuzzInput| {
+            reproducer::track("decimals", &input);
+            scenarios::begin("decimals");
+            // Run the async fuzz test
+            block_on(async {
+                if let Err(e) = fuzz_decimals_once(input).await {
+                    eprintln!("Fuzz iteration failed: {}", e);
+                }
+            });
+        });
+    }
+}
@@ -0,0 +1,18 @@
+use fuzz_helpers::fuzz_core::decimals::{fuzz_decimals_once, DecimalsFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<DecimalsFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("decimals", &input);
+        scenarios::begin("decimals");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_decimals_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::decimals::{fuzz_decimals_once, DecimalsFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: DecimalsFuzzInput| {
+    reproducer::track("decimals", &input);
+    scenarios::begin("decimals");
+    // Run the async fuzz test
+    block_on(async {
+        if let Err(e) = fuzz_decimals_once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+});
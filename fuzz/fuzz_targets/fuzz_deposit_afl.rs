@@ -0,0 +1,4 @@
+use fuzz_helpers::fuzz_core::deposit::{fuzz_deposit_once, DepositFuzzInput};
+use fuzz_helpers::vault_fuzz_main;
+
+vault_fuzz_main!(afl, "deposit", DepositFuzzInput, fuzz_deposit_once);
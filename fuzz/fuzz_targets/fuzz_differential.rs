@@ -0,0 +1,19 @@
+use fuzz_helpers::fuzz_core::differential::{fuzz_differential_once, DifferentialFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use honggfuzz::fuzz;
+
+fn main() {
+    // Initialize honggfuzz
+    loop {
+        fuzz!(|input: DifferentialFuzzInput| {
+            reproducer::track("differential", &input);
+            scenarios::begin("differential");
+            // Run the async fuzz test
+            block_on(async {
+                if let Err(e) = fuzz_differential_once(input).await {
+                    eprintln!("Fuzz iteration failed: {}", e);
+                }
+            });
+        });
+    }
+}
@@ -0,0 +1,18 @@
+use fuzz_helpers::fuzz_core::differential::{fuzz_differential_once, DifferentialFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<DifferentialFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("differential", &input);
+        scenarios::begin("differential");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_differential_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
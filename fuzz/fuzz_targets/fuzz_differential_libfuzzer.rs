@@ -0,0 +1,16 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::differential::{fuzz_differential_once, DifferentialFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: DifferentialFuzzInput| {
+    reproducer::track("differential", &input);
+    scenarios::begin("differential");
+    // Run the async fuzz test
+    block_on(async {
+        if let Err(e) = fuzz_differential_once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+});
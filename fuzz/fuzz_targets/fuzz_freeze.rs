@@ -0,0 +1,4 @@
+use fuzz_helpers::fuzz_core::freeze::{fuzz_freeze_once, FreezeFuzzInput};
+use fuzz_helpers::vault_fuzz_main;
+
+vault_fuzz_main!(honggfuzz, "freeze", FreezeFuzzInput, fuzz_freeze_once);
@@ -0,0 +1,6 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::freeze::{fuzz_freeze_once, FreezeFuzzInput};
+use fuzz_helpers::vault_fuzz_main;
+
+vault_fuzz_main!(libfuzzer, "freeze", FreezeFuzzInput, fuzz_freeze_once);
@@ -0,0 +1,14 @@
+use fuzz_helpers::fuzz_core::kernel::{fuzz_kernel_once, KernelFuzzInput};
+use fuzz_helpers::fuzz_core::{reproducer, scenarios};
+use honggfuzz::fuzz;
+
+fn main() {
+    // Initialize honggfuzz
+    loop {
+        fuzz!(|input: KernelFuzzInput| {
+            reproducer::track("kernel", &input);
+            scenarios::begin("kernel");
+            fuzz_kernel_once(input);
+        });
+    }
+}
@@ -0,0 +1,13 @@
+use fuzz_helpers::fuzz_core::kernel::{fuzz_kernel_once, KernelFuzzInput};
+use fuzz_helpers::fuzz_core::{corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<KernelFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("kernel", &input);
+        scenarios::begin("kernel");
+        fuzz_kernel_once(input);
+    });
+}
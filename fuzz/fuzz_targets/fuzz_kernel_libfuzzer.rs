@@ -0,0 +1,11 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::kernel::{fuzz_kernel_once, KernelFuzzInput};
+use fuzz_helpers::fuzz_core::{reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: KernelFuzzInput| {
+    reproducer::track("kernel", &input);
+    scenarios::begin("kernel");
+    fuzz_kernel_once(input);
+});
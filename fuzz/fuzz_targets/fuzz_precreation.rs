@@ -0,0 +1,19 @@
+use fuzz_helpers::fuzz_core::precreation::{fuzz_precreation_once, PrecreationFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use honggfuzz::fuzz;
+
+fn main() {
+    // Initialize honggfuzz
+    loop {
+        fuzz!(|input: PrecreationFuzzInput| {
+            reproducer::track("precreation", &input);
+            scenarios::begin("precreation");
+            // Run the async fuzz test
+            block_on(async {
+                if let Err(e) = fuzz_precreation_once(input).await {
+                    eprintln!("Fuzz iteration failed: {}", e);
+                }
+            });
+        });
+    }
+}
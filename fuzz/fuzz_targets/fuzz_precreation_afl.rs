@@ -0,0 +1,18 @@
+use fuzz_helpers::fuzz_core::precreation::{fuzz_precreation_once, PrecreationFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<PrecreationFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("precreation", &input);
+        scenarios::begin("precreation");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_precreation_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::precreation::{fuzz_precreation_once, PrecreationFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: PrecreationFuzzInput| {
+    reproducer::track("precreation", &input);
+    scenarios::begin("precreation");
+    // Run the async fuzz test
+    block_on(async {
+        if let Err(e) = fuzz_precreation_once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+});
@@ -1,6 +1,19 @@
-// Placeholder for fuzz_redeem target
-// TODO: Implement fuzzing for redeem instruction
+use fuzz_helpers::fuzz_core::redeem::{fuzz_redeem_once, RedeemFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use honggfuzz::fuzz;
 
 fn main() {
-    println!("fuzz_redeem - To be implemented");
+    // Initialize honggfuzz
+    loop {
+        fuzz!(|input: RedeemFuzzInput| {
+            reproducer::track("redeem", &input);
+            scenarios::begin("redeem");
+            // Run the async fuzz test
+            block_on(async {
+                if let Err(e) = fuzz_redeem_once(input).await {
+                    eprintln!("Fuzz iteration failed: {}", e);
+                }
+            });
+        });
+    }
 }
@@ -0,0 +1,18 @@
+use fuzz_helpers::fuzz_core::redeem::{fuzz_redeem_once, RedeemFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<RedeemFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("redeem", &input);
+        scenarios::begin("redeem");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_redeem_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::redeem::{fuzz_redeem_once, RedeemFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: RedeemFuzzInput| {
+    reproducer::track("redeem", &input);
+    scenarios::begin("redeem");
+    // Run the async fuzz test
+    block_on(async {
+        if let Err(e) = fuzz_redeem_once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+});
@@ -0,0 +1,21 @@
+use fuzz_helpers::fuzz_core::remaining_accounts::{
+    fuzz_remaining_accounts_once, RemainingAccountsFuzzInput,
+};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use honggfuzz::fuzz;
+
+fn main() {
+    // Initialize honggfuzz
+    loop {
+        fuzz!(|input: RemainingAccountsFuzzInput| {
+            reproducer::track("remaining_accounts", &input);
+            scenarios::begin("remaining_accounts");
+            // Run the async fuzz test
+            block_on(async {
+                if let Err(e) = fuzz_remaining_accounts_once(input).await {
+                    eprintln!("Fuzz iteration failed: {}", e);
+                }
+            });
+        });
+    }
+}
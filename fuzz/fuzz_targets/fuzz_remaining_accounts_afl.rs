@@ -0,0 +1,20 @@
+use fuzz_helpers::fuzz_core::remaining_accounts::{
+    fuzz_remaining_accounts_once, RemainingAccountsFuzzInput,
+};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<RemainingAccountsFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("remaining_accounts", &input);
+        scenarios::begin("remaining_accounts");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_remaining_accounts_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
@@ -0,0 +1,18 @@
+use fuzz_helpers::fuzz_core::round_trip::{fuzz_round_trip_once, RoundTripFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<RoundTripFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("round_trip", &input);
+        scenarios::begin("round_trip");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_round_trip_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::round_trip::{fuzz_round_trip_once, RoundTripFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: RoundTripFuzzInput| {
+    reproducer::track("round_trip", &input);
+    scenarios::begin("round_trip");
+    // Run the async fuzz test
+    block_on(async {
+        if let Err(e) = fuzz_round_trip_once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+});
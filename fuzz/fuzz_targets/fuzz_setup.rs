@@ -3,24 +3,195 @@ use anchor_lang::ToAccountMetas;
 use anchor_lang::AccountDeserialize;
 use solana_program_test::*;
 use solana_sdk::{
+    clock::Clock,
     instruction::Instruction,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
     transaction::Transaction,
 };
-use spl_token::instruction as token_instruction;
-use vault_pda::state::{ProtocolState, Vault, VaultAuthority};
+use spl_token_2022::extension::{
+    default_account_state, interest_bearing_mint, transfer_fee, transfer_hook,
+    BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use spl_token_2022::instruction as token_2022_instruction;
+use std::cell::{Cell, RefCell};
+use std::mem::ManuallyDrop;
+use vault_pda::state::{Flag, PriceHistory, PricePoint, ProtocolState, Vault, VaultAuthority};
+
+pub mod attacker;
+pub mod backend;
+pub mod builder;
+pub mod compute_units;
+pub mod errors;
+pub mod fuzz_core;
+pub mod ordering;
+pub mod seed;
+pub mod snapshot;
+
+pub use attacker::{Attacker, FrontRun};
+pub use backend::{fund_account, fund_lamports, seed_packed_account, FuzzBackend};
+pub use builder::{FuzzEnv, FuzzEnvBuilder, VaultSetup};
+pub use errors::{decode_error, ProgramError, TxFailure};
+pub use ordering::{Block, BlockOutcome};
+pub use seed::{new_address, new_keypair, seed_from_input, seed_keypairs};
+pub use snapshot::{restore, snapshot, snapshot_addresses, SnapshotAccounts, StateSnapshot};
+pub use vault_pda_client::pda::{
+    derive_activity_stats_pda, derive_admin_log_pda, derive_fee_exemption_pda, derive_flag_pda,
+    derive_player_pda, derive_protocol_state_pda, derive_reserve_config_pda, derive_share_mint_pda,
+    derive_vault_authority_pda, derive_vault_pda, derive_vault_registry_pda,
+    derive_vault_token_account_pda,
+};
 
 // Re-export for convenience
 pub use solana_program_test::ProgramTestContext;
+pub use vault_invariants::Snapshot;
 
 // Custom error type for fuzzing
 pub type FuzzResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
+/// Name of the SBF build loaded from `SBF_OUT_DIR`, without `.so`
+///
+/// Harnesses generated by `cargo xtask new-challenge` set
+/// `VAULT_PROGRAM_NAME` from their build script so the shared sources load
+/// the cloned program instead.
+pub const PROGRAM_NAME: &str = match option_env!("VAULT_PROGRAM_NAME") {
+    Some(name) => name,
+    None => "vault_pda",
+};
+
 /// Test environment with program loaded
+///
+/// Dropping it hands the context back for the next `setup_program_test` on
+/// this thread when reuse is enabled (see `enable_context_reuse`).
 pub struct FuzzTestEnv {
     pub program_id: Pubkey,
-    pub context: ProgramTestContext,
+    pub context: ManuallyDrop<ProgramTestContext>,
+}
+
+impl Drop for FuzzTestEnv {
+    fn drop(&mut self) {
+        // SAFETY: `context` is not touched again after this
+        let context = unsafe { ManuallyDrop::take(&mut self.context) };
+        if REUSE_CONTEXT.get() {
+            CACHED_CONTEXT.with_borrow_mut(|cached| *cached = Some(context));
+        }
+    }
+}
+
+thread_local! {
+    /// Whether dropped environments are kept for the next iteration
+    static REUSE_CONTEXT: Cell<bool> = const { Cell::new(false) };
+
+    /// Context of the last dropped environment, waiting to be restored
+    static CACHED_CONTEXT: RefCell<Option<ProgramTestContext>> = const { RefCell::new(None) };
+
+    /// Accounts at fixed addresses as they were right after startup
+    static BASELINE: RefCell<StateSnapshot> = RefCell::new(StateSnapshot::default());
+}
+
+/// Token program a setup's mints and token accounts belong to
+///
+/// The program takes its token accounts through the token interface, so every
+/// setup helper creates accounts under either program.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    arbitrary::Arbitrary,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub enum TokenFlavor {
+    /// The original SPL Token program
+    #[default]
+    SplToken,
+    /// Token-2022, with no extensions unless a helper adds them
+    Token2022,
+}
+
+impl TokenFlavor {
+    /// Both flavors, for tests that run once under each token program
+    pub const ALL: [TokenFlavor; 2] = [TokenFlavor::SplToken, TokenFlavor::Token2022];
+
+    /// Address of the token program
+    pub fn program_id(self) -> Pubkey {
+        match self {
+            TokenFlavor::SplToken => spl_token::id(),
+            TokenFlavor::Token2022 => spl_token_2022::id(),
+        }
+    }
+
+    /// The flavor of an account owned by `owner`, if it is a token program
+    pub fn from_program_id(owner: &Pubkey) -> Option<Self> {
+        if *owner == spl_token::id() {
+            Some(TokenFlavor::SplToken)
+        } else if *owner == spl_token_2022::id() {
+            Some(TokenFlavor::Token2022)
+        } else {
+            None
+        }
+    }
+
+    /// Address of the program's wrapped SOL mint
+    pub fn native_mint(self) -> Pubkey {
+        match self {
+            TokenFlavor::SplToken => spl_token::native_mint::id(),
+            TokenFlavor::Token2022 => spl_token_2022::native_mint::id(),
+        }
+    }
+}
+
+/// Unusual but legal states a token account can be created in, see
+/// [`setup_edge_token_account`]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    arbitrary::Arbitrary,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub enum EdgeAccountState {
+    /// A delegate is approved for `delegated_amount`, which may exceed the
+    /// balance
+    Delegated { delegated_amount: u64 },
+    /// A close authority other than the owner is set
+    CloseAuthority,
+    /// The owner cannot be reassigned. Token-2022 records this as the
+    /// `ImmutableOwner` extension; SPL Token has no such extension, so this is
+    /// a plain account there
+    ImmutableOwner,
+    /// Wrapped SOL holding `lamports` above the rent-exempt reserve
+    Native { lamports: u32 },
+    /// Wrapped SOL holding exactly the rent-exempt reserve, so its balance is
+    /// zero and every lamport it has is rent
+    ZeroRentExcess,
+}
+
+impl EdgeAccountState {
+    /// Whether the account belongs to the native mint rather than the one
+    /// requested
+    pub fn is_native(self) -> bool {
+        matches!(
+            self,
+            EdgeAccountState::Native { .. } | EdgeAccountState::ZeroRentExcess
+        )
+    }
+}
+
+/// A token account created by [`setup_edge_token_account`]
+#[derive(Debug)]
+pub struct EdgeTokenAccount {
+    pub address: Pubkey,
+    /// Mint of the account, the native mint for wrapped SOL states
+    pub mint: Pubkey,
+    /// The delegate or close authority the state names, if any
+    pub authority: Option<Keypair>,
 }
 
 /// Protocol-level accounts (protocol state and vault authority)
@@ -32,9 +203,15 @@ pub struct ProtocolAccounts {
     pub owner_keypair: Keypair,
 }
 
+// `Keypair` is deliberately not `Clone`; these are throwaway test keys
 impl Clone for ProtocolAccounts {
     fn clone(&self) -> Self {
-        panic!("ProtocolAccounts cannot be cloned due to Keypair");
+        Self {
+            protocol_state: self.protocol_state,
+            vault_authority: self.vault_authority,
+            owner: self.owner,
+            owner_keypair: self.owner_keypair.insecure_clone(),
+        }
     }
 }
 
@@ -44,13 +221,17 @@ pub struct UnderlyingMintAccounts {
     pub mint: Pubkey,
     pub mint_authority: Keypair,
     pub decimals: u8,
+    pub flavor: TokenFlavor,
 }
 
 impl Clone for UnderlyingMintAccounts {
     fn clone(&self) -> Self {
-        // We can't clone Keypair, so we need to handle this differently
-        // For fuzzing purposes, we'll panic if someone tries to clone
-        panic!("UnderlyingMintAccounts cannot be cloned due to Keypair");
+        Self {
+            mint: self.mint,
+            mint_authority: self.mint_authority.insecure_clone(),
+            decimals: self.decimals,
+            flavor: self.flavor,
+        }
     }
 }
 
@@ -61,6 +242,8 @@ pub struct VaultAccounts {
     pub vault_token_account: Pubkey,
     pub share_mint: Pubkey,
     pub underlying_mint: Pubkey,
+    /// Token program the vault's mints and token accounts belong to
+    pub token_program: Pubkey,
 }
 
 /// User token accounts for interacting with vault
@@ -73,11 +256,16 @@ pub struct UserAccounts {
 
 impl Clone for UserAccounts {
     fn clone(&self) -> Self {
-        panic!("UserAccounts cannot be cloned due to Keypair");
+        Self {
+            owner: self.owner.insecure_clone(),
+            underlying_token_account: self.underlying_token_account,
+            share_token_account: self.share_token_account,
+        }
     }
 }
 
 /// Complete setup with all accounts
+#[derive(Debug, Clone)]
 pub struct CompleteSetup {
     pub protocol: ProtocolAccounts,
     pub underlying: UnderlyingMintAccounts,
@@ -85,29 +273,100 @@ pub struct CompleteSetup {
     pub user: UserAccounts,
 }
 
+/// Two vaults over different underlying mints in one environment
+#[derive(Debug, Clone)]
+pub struct TwoVaultSetup {
+    /// Vault A, with the protocol and the user
+    pub a: CompleteSetup,
+    pub underlying_b: UnderlyingMintAccounts,
+    pub vault_b: VaultAccounts,
+    /// The user's accounts for vault B, owned by the same keypair as `a.user`
+    pub user_b: UserAccounts,
+}
+
 // ============================================================================
 // Core Setup Functions
 // ============================================================================
 
 /// Creates the basic program test environment with vault program loaded
+///
+/// The program is loaded from the SBF build (`anchor build`), so point
+/// `SBF_OUT_DIR` at `target/deploy` when running outside the project root.
+///
+/// With reuse enabled, a context left by a previous iteration on this thread
+/// is restored to its baseline and returned instead of starting a new one.
 pub async fn setup_program_test() -> FuzzTestEnv {
     let program_id = vault_pda::id();
-    let program_test = ProgramTest::default();
 
-    let context = program_test.start_with_context().await;
+    if let Some(mut context) = CACHED_CONTEXT.with_borrow_mut(Option::take) {
+        if seed::context_reusable() && restore_baseline(&mut context).await.is_ok() {
+            seed::record_context_use(false);
+            return FuzzTestEnv {
+                program_id,
+                context: ManuallyDrop::new(context),
+            };
+        }
+    }
+
+    let mut program_test = ProgramTest::new(PROGRAM_NAME, program_id, None);
+    add_transfer_hooks(&mut program_test);
+    program_test.prefer_bpf(true);
+
+    let mut context = program_test.start_with_context().await;
+    seed::record_context_use(true);
+
+    if REUSE_CONTEXT.get() {
+        let addresses = fixed_addresses(&context, &program_id);
+        if let Ok(baseline) = snapshot_addresses(&mut context, addresses).await {
+            BASELINE.set(baseline);
+        }
+    }
 
     FuzzTestEnv {
         program_id,
-        context,
+        context: ManuallyDrop::new(context),
     }
 }
 
+/// Keeps dropped environments on this thread for the next iteration
+///
+/// Starting a `ProgramTest` costs hundreds of milliseconds, far more than a
+/// typical iteration. Only enable this when every iteration on the thread runs
+/// on the same long-lived tokio runtime: the cached `BanksClient` is served by
+/// a task on the runtime that started it.
+///
+/// Restoring resets the payer and the fixed-seed PDAs (`protocol_state`,
+/// `vault_authority`, `vault_registry`). Accounts at fresh addresses (mints, token accounts,
+/// per-mint vaults) are left behind, and the clock is never rewound.
+pub fn enable_context_reuse() {
+    REUSE_CONTEXT.set(true);
+}
+
+/// Addresses that every iteration writes to, whatever its inputs
+fn fixed_addresses(context: &ProgramTestContext, program_id: &Pubkey) -> Vec<Pubkey> {
+    vec![
+        context.payer.pubkey(),
+        derive_protocol_state_pda(program_id).0,
+        derive_vault_authority_pda(program_id).0,
+        derive_vault_registry_pda(program_id).0,
+    ]
+}
+
+/// Puts the fixed addresses back to their baseline and refreshes the blockhash
+async fn restore_baseline(context: &mut ProgramTestContext) -> FuzzResult<()> {
+    let baseline = BASELINE.with_borrow(Clone::clone);
+    if baseline.is_empty() {
+        return Err("No baseline recorded".into());
+    }
+    restore(context, &baseline).await
+}
+
 /// Initializes the protocol (calls initialize instruction)
 pub async fn setup_protocol(
     context: &mut ProgramTestContext,
     program_id: &Pubkey,
 ) -> FuzzResult<ProtocolAccounts> {
-    let owner_keypair = Keypair::new();
+    let owner_keypair = new_keypair();
     let owner = owner_keypair.pubkey();
 
     // Derive PDAs
@@ -115,40 +374,10 @@ pub async fn setup_protocol(
     let (vault_authority, _) = derive_vault_authority_pda(program_id);
 
     // Fund the owner account
-    let rent = context.banks_client.get_rent().await?;
-    let lamports = rent.minimum_balance(0) + 1_000_000_000; // 1 SOL
-
-    let ix = solana_sdk::system_instruction::transfer(
-        &context.payer.pubkey(),
-        &owner,
-        lamports,
-    );
-
-    let tx = Transaction::new_signed_with_payer(
-        &[ix],
-        Some(&context.payer.pubkey()),
-        &[&context.payer],
-        context.last_blockhash,
-    );
-
-    context.banks_client.process_transaction(tx).await?;
+    fund_account(context, &owner).await?;
 
     // Build initialize instruction
-    let accounts = vault_pda::accounts::Initialize {
-        protocol_state,
-        vault_authority,
-        owner,
-        payer: owner,
-        system_program: solana_sdk::system_program::ID,
-    };
-
-    let data = vault_pda::instruction::Initialize {}.data();
-
-    let ix = Instruction {
-        program_id: *program_id,
-        accounts: accounts.to_account_metas(None),
-        data,
-    };
+    let ix = initialize_ix(program_id, &owner);
 
     let tx = Transaction::new_signed_with_payer(
         &[ix],
@@ -157,7 +386,7 @@ pub async fn setup_protocol(
         context.last_blockhash,
     );
 
-    context.banks_client.process_transaction(tx).await?;
+    compute_units::process_transaction(context, program_id, tx).await?;
 
     Ok(ProtocolAccounts {
         protocol_state,
@@ -167,60 +396,24 @@ pub async fn setup_protocol(
     })
 }
 
-/// Creates a new SPL token mint to serve as underlying asset
+/// Creates a new token mint under `flavor` to serve as underlying asset
 pub async fn setup_underlying_mint(
     context: &mut ProgramTestContext,
     decimals: u8,
+    flavor: TokenFlavor,
 ) -> FuzzResult<UnderlyingMintAccounts> {
-    let mint_authority = Keypair::new();
-    let mint_keypair = Keypair::new();
-    let mint = mint_keypair.pubkey();
-
-    let rent = context.banks_client.get_rent().await?;
-    let mint_len = 82; // Size of Mint account in SPL Token program
-    let mint_rent = rent.minimum_balance(mint_len);
-
-    // Create mint account
-    let create_account_ix = solana_sdk::system_instruction::create_account(
-        &context.payer.pubkey(),
-        &mint,
-        mint_rent,
-        mint_len as u64,
-        &spl_token::id(),
-    );
-
-    // Initialize mint
-    let init_mint_ix = token_instruction::initialize_mint(
-        &spl_token::id(),
-        &mint,
-        &mint_authority.pubkey(),
-        None,
-        decimals,
-    )?;
-
-    let tx = Transaction::new_signed_with_payer(
-        &[create_account_ix, init_mint_ix],
-        Some(&context.payer.pubkey()),
-        &[&context.payer, &mint_keypair],
-        context.last_blockhash,
-    );
-
-    context.banks_client.process_transaction(tx).await?;
-
-    Ok(UnderlyingMintAccounts {
-        mint,
-        mint_authority,
-        decimals,
-    })
+    backend::create_mint(context, decimals, flavor).await
 }
 
-/// Initializes a vault for a given underlying mint
+/// Initializes a vault for a given underlying mint, which must belong to
+/// `flavor`'s token program
 pub async fn setup_vault(
     context: &mut ProgramTestContext,
     program_id: &Pubkey,
     vault_authority: &Pubkey,
     underlying_mint: &Pubkey,
     payer: &Keypair,
+    flavor: TokenFlavor,
 ) -> FuzzResult<VaultAccounts> {
     // Derive PDAs
     let (vault, _) = derive_vault_pda(program_id, underlying_mint);
@@ -236,10 +429,16 @@ pub async fn setup_vault(
         vault_authority: *vault_authority,
         payer: payer.pubkey(),
         system_program: solana_sdk::system_program::ID,
-        token_program: spl_token::id(),
+        token_program: flavor.program_id(),
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        activity_stats: derive_activity_stats_pda(program_id, &vault).0,
+        vault_registry: derive_vault_registry_pda(program_id).0,
     };
 
-    let data = vault_pda::instruction::InitializeVault {}.data();
+    let data = vault_pda::instruction::InitializeVault {
+        name: String::new(),
+    }
+    .data();
 
     let ix = Instruction {
         program_id: *program_id,
@@ -254,213 +453,854 @@ pub async fn setup_vault(
         context.last_blockhash,
     );
 
-    context.banks_client.process_transaction(tx).await?;
+    compute_units::process_transaction(context, program_id, tx).await?;
 
     Ok(VaultAccounts {
         vault,
         vault_token_account,
         share_mint,
         underlying_mint: *underlying_mint,
+        token_program: flavor.program_id(),
     })
 }
 
-/// Creates token accounts for a user (for deposits/redeems)
+/// Creates token accounts for a user (for deposits/redeems) under `flavor`
 pub async fn setup_user_accounts(
     context: &mut ProgramTestContext,
     underlying_mint: &Pubkey,
     share_mint: &Pubkey,
+    flavor: TokenFlavor,
 ) -> FuzzResult<UserAccounts> {
-    let owner = Keypair::new();
+    let owner = new_keypair();
 
     // Fund the owner account
-    let rent = context.banks_client.get_rent().await?;
-    let lamports = rent.minimum_balance(0) + 1_000_000_000; // 1 SOL
+    fund_account(context, &owner.pubkey()).await?;
 
-    let ix = solana_sdk::system_instruction::transfer(
-        &context.payer.pubkey(),
-        &owner.pubkey(),
-        lamports,
-    );
+    let underlying_token_account =
+        setup_token_account(context, underlying_mint, &owner.pubkey(), flavor).await?;
+    let share_token_account =
+        setup_token_account(context, share_mint, &owner.pubkey(), flavor).await?;
+
+    Ok(UserAccounts {
+        owner,
+        underlying_token_account,
+        share_token_account,
+    })
+}
+
+/// Creates a funded user with token accounts for `mint` and `share_mint`,
+/// holding `balance` underlying tokens
+pub async fn create_and_fund_user(
+    context: &mut ProgramTestContext,
+    mint: &UnderlyingMintAccounts,
+    share_mint: &Pubkey,
+    balance: u64,
+) -> FuzzResult<UserAccounts> {
+    let user = setup_user_accounts(context, &mint.mint, share_mint, mint.flavor).await?;
+    if balance > 0 {
+        mint_tokens_to_user(
+            context,
+            &mint.mint,
+            &mint.mint_authority,
+            &user.underlying_token_account,
+            balance,
+        )
+        .await?;
+    }
+    Ok(user)
+}
+
+/// Creates `n` funded users with token accounts for `mint` and `share_mint`
+///
+/// User `i` starts with `balances[i]` underlying tokens, or none past the end
+/// of `balances`.
+pub async fn setup_n_users(
+    context: &mut ProgramTestContext,
+    n: usize,
+    mint: &UnderlyingMintAccounts,
+    share_mint: &Pubkey,
+    balances: &[u64],
+) -> FuzzResult<Vec<UserAccounts>> {
+    let mut users = Vec::with_capacity(n);
+    for index in 0..n {
+        let balance = balances.get(index).copied().unwrap_or(0);
+        users.push(create_and_fund_user(context, mint, share_mint, balance).await?);
+    }
+    Ok(users)
+}
+
+/// Creates an additional token account for `mint` owned by `owner`, under
+/// `flavor`'s token program
+pub async fn setup_token_account(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    flavor: TokenFlavor,
+) -> FuzzResult<Pubkey> {
+    backend::create_token_account(context, mint, owner, flavor).await
+}
+
+/// Creates a token account owned by `owner` in the unusual but legal
+/// `state`, under `flavor`'s token program
+///
+/// The account holds no tokens unless it is wrapped SOL. Wrapped SOL states
+/// belong to `flavor`'s native mint, whatever `mint` is.
+pub async fn setup_edge_token_account(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    flavor: TokenFlavor,
+    state: EdgeAccountState,
+) -> FuzzResult<EdgeTokenAccount> {
+    backend::create_edge_token_account(context, mint, owner, flavor, state).await
+}
+
+/// Mints tokens to a user's underlying token account
+///
+/// SPL Token balances are written directly, with the same checks `mint_to`
+/// would make. Token-2022 accounts may carry extensions that change their
+/// layout, so for a Token-2022 mint a real `mint_to` is sent instead.
+pub async fn mint_tokens_to_user(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    mint_authority: &Keypair,
+    destination: &Pubkey,
+    amount: u64,
+) -> FuzzResult<()> {
+    let owner = context
+        .banks_client
+        .get_account(*mint)
+        .await?
+        .ok_or("Mint account not found")?
+        .owner;
+    if TokenFlavor::from_program_id(&owner) != Some(TokenFlavor::Token2022) {
+        return backend::mint_to(context, mint, mint_authority, destination, amount).await;
+    }
 
+    let ix = token_2022_instruction::mint_to(
+        &owner,
+        mint,
+        destination,
+        &mint_authority.pubkey(),
+        &[],
+        amount,
+    )?;
     let tx = Transaction::new_signed_with_payer(
         &[ix],
         Some(&context.payer.pubkey()),
-        &[&context.payer],
+        &[&context.payer, mint_authority],
         context.last_blockhash,
     );
+    context.banks_client.process_transaction(tx).await?;
+    Ok(())
+}
+
+/// Mints `amount` underlying tokens straight into `vault`, raising the value
+/// of every share without minting any (yield, or a donation attack)
+pub async fn donate_to_vault(
+    context: &mut ProgramTestContext,
+    underlying: &UnderlyingMintAccounts,
+    vault: &VaultAccounts,
+    amount: u64,
+) -> FuzzResult<()> {
+    mint_tokens_to_user(
+        context,
+        &underlying.mint,
+        &underlying.mint_authority,
+        &vault.vault_token_account,
+        amount,
+    )
+    .await
+}
+
+/// Makes the mint authority of `underlying` its freeze authority as well
+///
+/// Seeded mints start without one. The mint is rewritten in place, so this
+/// only works for mints without extensions; Token-2022 mints from
+/// [`setup_token_2022_mint`] already have one.
+pub async fn grant_freeze_authority(
+    context: &mut ProgramTestContext,
+    underlying: &UnderlyingMintAccounts,
+) -> FuzzResult<()> {
+    let mut state: spl_token::state::Mint =
+        backend::get_packed_account(context, &underlying.mint).await?;
+    state.freeze_authority =
+        solana_sdk::program_option::COption::Some(underlying.mint_authority.pubkey());
+    backend::seed_packed_account_for(context, underlying.flavor, &underlying.mint, state).await
+}
+
+/// Has the freeze authority of `underlying` freeze `account`
+pub async fn freeze_token_account(
+    context: &mut ProgramTestContext,
+    underlying: &UnderlyingMintAccounts,
+    account: &Pubkey,
+) -> FuzzResult<()> {
+    set_frozen(context, underlying, account, true).await
+}
 
+/// Has the freeze authority of `underlying` thaw `account`
+pub async fn thaw_token_account(
+    context: &mut ProgramTestContext,
+    underlying: &UnderlyingMintAccounts,
+    account: &Pubkey,
+) -> FuzzResult<()> {
+    set_frozen(context, underlying, account, false).await
+}
+
+async fn set_frozen(
+    context: &mut ProgramTestContext,
+    underlying: &UnderlyingMintAccounts,
+    account: &Pubkey,
+    frozen: bool,
+) -> FuzzResult<()> {
+    let instruction = if frozen {
+        token_2022_instruction::freeze_account
+    } else {
+        token_2022_instruction::thaw_account
+    };
+    let authority = &underlying.mint_authority;
+    let ix = instruction(
+        &underlying.flavor.program_id(),
+        account,
+        &underlying.mint,
+        &authority.pubkey(),
+        &[],
+    )?;
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, authority],
+        context.last_blockhash,
+    );
     context.banks_client.process_transaction(tx).await?;
+    Ok(())
+}
+
+/// Sets up everything: protocol + underlying mint + vault + user with tokens
+///
+/// Shorthand for the default `FuzzEnvBuilder` topology under `flavor`.
+pub async fn setup_complete_environment(
+    initial_user_balance: u64,
+    decimals: u8,
+    flavor: TokenFlavor,
+) -> FuzzResult<(FuzzTestEnv, CompleteSetup)> {
+    let (env, setup) = FuzzEnvBuilder::new()
+        .flavor(flavor)
+        .decimals(decimals)
+        .balance(initial_user_balance)
+        .build()
+        .await?;
+
+    Ok((env, setup.into_complete()))
+}
+
+/// Sets up a complete environment plus a second vault over a different
+/// underlying mint, with token accounts for the same user in both
+pub async fn setup_two_vault_environment(
+    initial_user_balance: u64,
+    decimals: u8,
+    flavor: TokenFlavor,
+) -> FuzzResult<(FuzzTestEnv, TwoVaultSetup)> {
+    let (env, setup) = FuzzEnvBuilder::new()
+        .flavor(flavor)
+        .vaults(2)
+        .decimals(decimals)
+        .balance(initial_user_balance)
+        .build()
+        .await?;
 
-    let account_len = 165; // Size of Token account in SPL Token program
+    Ok((env, setup.into_two_vault()))
+}
 
-    // Create underlying token account
-    let underlying_token_account = Keypair::new();
-    let create_underlying_ix = solana_sdk::system_instruction::create_account(
+/// Allocates and initializes a price history account for `vault`
+pub async fn setup_price_history(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    vault: &Pubkey,
+) -> FuzzResult<Pubkey> {
+    let price_history = new_keypair();
+    let rent = context.banks_client.get_rent().await?;
+
+    let create_ix = solana_sdk::system_instruction::create_account(
         &context.payer.pubkey(),
-        &underlying_token_account.pubkey(),
-        rent.minimum_balance(account_len),
-        account_len as u64,
-        &spl_token::id(),
+        &price_history.pubkey(),
+        rent.minimum_balance(PriceHistory::LEN),
+        PriceHistory::LEN as u64,
+        program_id,
     );
+    let init_ix = initialize_price_history_ix(program_id, &price_history.pubkey(), vault);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[create_ix, init_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &price_history],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await?;
+
+    Ok(price_history.pubkey())
+}
 
-    let init_underlying_ix = token_instruction::initialize_account(
-        &spl_token::id(),
-        &underlying_token_account.pubkey(),
-        underlying_mint,
-        &owner.pubkey(),
+// ============================================================================
+// Token-2022 Setup
+// ============================================================================
+
+/// Token-2022 extensions enabled on an underlying mint
+///
+/// Every authority an extension needs (fee config, hook, interest rate,
+/// freeze and permanent delegate) is the mint authority.
+#[derive(Debug, Clone, Default, arbitrary::Arbitrary, serde::Serialize, serde::Deserialize)]
+pub struct MintExtensions {
+    /// Transfer fee in basis points (reduced to at most 100%) and its cap
+    pub transfer_fee: Option<(u16, u64)>,
+    /// Route transfers through a hook program that is not deployed
+    pub transfer_hook: bool,
+    /// New token accounts start frozen
+    pub default_frozen: bool,
+    /// The mint authority can move tokens out of any account
+    pub permanent_delegate: bool,
+    /// Interest rate in basis points
+    pub interest_rate: Option<i16>,
+}
+
+impl MintExtensions {
+    /// Mint extension types to allocate space for
+    pub fn extension_types(&self) -> Vec<ExtensionType> {
+        let mut types = Vec::new();
+        if self.transfer_fee.is_some() {
+            types.push(ExtensionType::TransferFeeConfig);
+        }
+        if self.transfer_hook {
+            types.push(ExtensionType::TransferHook);
+        }
+        if self.default_frozen {
+            types.push(ExtensionType::DefaultAccountState);
+        }
+        if self.permanent_delegate {
+            types.push(ExtensionType::PermanentDelegate);
+        }
+        if self.interest_rate.is_some() {
+            types.push(ExtensionType::InterestBearingConfig);
+        }
+        types
+    }
+}
+
+/// Loads both `transfer-hook-mock` variants as builtins, so mints from
+/// [`setup_hooked_mint`] have a hook that actually runs
+pub fn add_transfer_hooks(program_test: &mut ProgramTest) {
+    // The hooks only exist natively, so they must not be looked up as SBF
+    program_test.prefer_bpf(false);
+    for variant in transfer_hook_mock::Variant::ALL {
+        program_test.add_program(
+            variant.name(),
+            variant.program_id(),
+            processor!(transfer_hook_mock::process_instruction),
+        );
+    }
+}
+
+/// Creates a Token-2022 mint with `extensions` to serve as underlying asset
+///
+/// A `transfer_hook` points at a program that is not deployed, so every
+/// transfer of the mint fails; use [`setup_hooked_mint`] for a hook that runs.
+pub async fn setup_token_2022_mint(
+    context: &mut ProgramTestContext,
+    decimals: u8,
+    extensions: &MintExtensions,
+) -> FuzzResult<UnderlyingMintAccounts> {
+    create_token_2022_mint(context, decimals, extensions, None).await
+}
+
+/// Creates a Token-2022 underlying mint whose transfers run the
+/// `transfer-hook-mock` `variant`
+///
+/// Transfers must list the hook program among their accounts, which the
+/// vault's own CPIs do not.
+pub async fn setup_hooked_mint(
+    context: &mut ProgramTestContext,
+    decimals: u8,
+    variant: transfer_hook_mock::Variant,
+) -> FuzzResult<UnderlyingMintAccounts> {
+    let extensions = MintExtensions {
+        transfer_hook: true,
+        ..MintExtensions::default()
+    };
+    create_token_2022_mint(context, decimals, &extensions, Some(variant.program_id())).await
+}
+
+async fn create_token_2022_mint(
+    context: &mut ProgramTestContext,
+    decimals: u8,
+    extensions: &MintExtensions,
+    hook_program: Option<Pubkey>,
+) -> FuzzResult<UnderlyingMintAccounts> {
+    let token_program = spl_token_2022::id();
+    let mint_authority = new_keypair();
+    let authority = mint_authority.pubkey();
+    let mint_keypair = new_keypair();
+    let mint = mint_keypair.pubkey();
+
+    let rent = context.banks_client.get_rent().await?;
+    let mint_len = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(
+        &extensions.extension_types(),
     )?;
 
-    // Create share token account
-    let share_token_account = Keypair::new();
-    let create_share_ix = solana_sdk::system_instruction::create_account(
+    let mut instructions = vec![solana_sdk::system_instruction::create_account(
         &context.payer.pubkey(),
-        &share_token_account.pubkey(),
-        rent.minimum_balance(account_len),
-        account_len as u64,
-        &spl_token::id(),
-    );
+        &mint,
+        rent.minimum_balance(mint_len),
+        mint_len as u64,
+        &token_program,
+    )];
+
+    // Extensions must be initialized before the mint itself
+    if let Some((basis_points, maximum_fee)) = extensions.transfer_fee {
+        instructions.push(transfer_fee::instruction::initialize_transfer_fee_config(
+            &token_program,
+            &mint,
+            Some(&authority),
+            Some(&authority),
+            basis_points % (transfer_fee::MAX_FEE_BASIS_POINTS + 1),
+            maximum_fee,
+        )?);
+    }
+    if extensions.transfer_hook {
+        instructions.push(transfer_hook::instruction::initialize(
+            &token_program,
+            &mint,
+            Some(authority),
+            Some(hook_program.unwrap_or_else(new_address)),
+        )?);
+    }
+    if extensions.default_frozen {
+        instructions.push(
+            default_account_state::instruction::initialize_default_account_state(
+                &token_program,
+                &mint,
+                &spl_token_2022::state::AccountState::Frozen,
+            )?,
+        );
+    }
+    if extensions.permanent_delegate {
+        instructions.push(token_2022_instruction::initialize_permanent_delegate(
+            &token_program,
+            &mint,
+            &authority,
+        )?);
+    }
+    if let Some(rate) = extensions.interest_rate {
+        instructions.push(interest_bearing_mint::instruction::initialize(
+            &token_program,
+            &mint,
+            Some(authority),
+            rate,
+        )?);
+    }
 
-    let init_share_ix = token_instruction::initialize_account(
-        &spl_token::id(),
-        &share_token_account.pubkey(),
-        share_mint,
-        &owner.pubkey(),
-    )?;
+    instructions.push(token_2022_instruction::initialize_mint2(
+        &token_program,
+        &mint,
+        &authority,
+        Some(&authority),
+        decimals,
+    )?);
 
     let tx = Transaction::new_signed_with_payer(
-        &[
-            create_underlying_ix,
-            init_underlying_ix,
-            create_share_ix,
-            init_share_ix,
-        ],
+        &instructions,
         Some(&context.payer.pubkey()),
-        &[
-            &context.payer,
-            &underlying_token_account,
-            &share_token_account,
-        ],
+        &[&context.payer, &mint_keypair],
         context.last_blockhash,
     );
 
     context.banks_client.process_transaction(tx).await?;
 
-    Ok(UserAccounts {
-        owner,
-        underlying_token_account: underlying_token_account.pubkey(),
-        share_token_account: share_token_account.pubkey(),
+    Ok(UnderlyingMintAccounts {
+        mint,
+        mint_authority,
+        decimals,
+        flavor: TokenFlavor::Token2022,
     })
 }
 
-/// Mints tokens to a user's underlying token account
-pub async fn mint_tokens_to_user(
+/// Creates a Token-2022 account for `mint` owned by `owner`, sized for the
+/// account extensions the mint's extensions require
+pub async fn setup_token_2022_account(
     context: &mut ProgramTestContext,
     mint: &Pubkey,
-    mint_authority: &Keypair,
-    destination: &Pubkey,
-    amount: u64,
-) -> FuzzResult<()> {
-    let mint_to_ix = token_instruction::mint_to(
-        &spl_token::id(),
+    owner: &Pubkey,
+) -> FuzzResult<Pubkey> {
+    let token_program = spl_token_2022::id();
+    let mint_account = context
+        .banks_client
+        .get_account(*mint)
+        .await?
+        .ok_or("Mint account not found")?;
+    let mint_extensions =
+        StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_account.data)?
+            .get_extension_types()?;
+    let account_len = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(
+        &ExtensionType::get_required_init_account_extensions(&mint_extensions),
+    )?;
+
+    let rent = context.banks_client.get_rent().await?;
+    let token_account = new_keypair();
+    let create_ix = solana_sdk::system_instruction::create_account(
+        &context.payer.pubkey(),
+        &token_account.pubkey(),
+        rent.minimum_balance(account_len),
+        account_len as u64,
+        &token_program,
+    );
+
+    let init_ix = token_2022_instruction::initialize_account3(
+        &token_program,
+        &token_account.pubkey(),
         mint,
-        destination,
-        &mint_authority.pubkey(),
-        &[],
-        amount,
+        owner,
     )?;
 
     let tx = Transaction::new_signed_with_payer(
-        &[mint_to_ix],
+        &[create_ix, init_ix],
         Some(&context.payer.pubkey()),
-        &[&context.payer, mint_authority],
+        &[&context.payer, &token_account],
         context.last_blockhash,
     );
 
     context.banks_client.process_transaction(tx).await?;
 
-    Ok(())
+    Ok(token_account.pubkey())
 }
 
-/// Sets up everything: protocol + underlying mint + vault + user with tokens
-pub async fn setup_complete_environment(
-    initial_user_balance: u64,
-    decimals: u8,
-) -> FuzzResult<(FuzzTestEnv, CompleteSetup)> {
-    let mut env = setup_program_test().await;
+/// Points every SPL Token account of `ix` at `token_program`, so the builders
+/// below that cannot see a `VaultAccounts` (such as `initialize_vault_ix`) can
+/// drive vaults created under Token-2022
+pub fn with_token_program(mut ix: Instruction, token_program: &Pubkey) -> Instruction {
+    for meta in &mut ix.accounts {
+        if meta.pubkey == spl_token::id() {
+            meta.pubkey = *token_program;
+        }
+    }
+    ix
+}
 
-    // Setup protocol
-    let protocol = setup_protocol(&mut env.context, &env.program_id).await?;
+// ============================================================================
+// Instruction Builders
+// ============================================================================
 
-    // Setup underlying mint
-    let underlying = setup_underlying_mint(&mut env.context, decimals).await?;
+/// Builds an `initialize` instruction where `owner` also pays for the accounts
+pub fn initialize_ix(program_id: &Pubkey, owner: &Pubkey) -> Instruction {
+    let accounts = vault_pda::accounts::Initialize {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        vault_authority: derive_vault_authority_pda(program_id).0,
+        owner: *owner,
+        payer: *owner,
+        system_program: solana_sdk::system_program::ID,
+        admin_log: derive_admin_log_pda(program_id).0,
+        vault_registry: derive_vault_registry_pda(program_id).0,
+    };
 
-    // Setup vault
-    let vault = setup_vault(
-        &mut env.context,
-        &env.program_id,
-        &protocol.vault_authority,
-        &underlying.mint,
-        &protocol.owner_keypair,
-    )
-    .await?;
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::Initialize {}.data(),
+    }
+}
 
-    // Setup user accounts
-    let user = setup_user_accounts(
-        &mut env.context,
-        &underlying.mint,
-        &vault.share_mint,
-    )
-    .await?;
+/// Builds an `initialize_vault` instruction for `underlying_mint`
+pub fn initialize_vault_ix(
+    program_id: &Pubkey,
+    underlying_mint: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    let (vault, _) = derive_vault_pda(program_id, underlying_mint);
 
-    // Mint initial tokens to user
-    if initial_user_balance > 0 {
-        mint_tokens_to_user(
-            &mut env.context,
-            &underlying.mint,
-            &underlying.mint_authority,
-            &user.underlying_token_account,
-            initial_user_balance,
-        )
-        .await?;
+    let accounts = vault_pda::accounts::InitializeVault {
+        vault,
+        underlying_mint: *underlying_mint,
+        vault_token_account: derive_vault_token_account_pda(program_id, &vault).0,
+        share_mint: derive_share_mint_pda(program_id, &vault).0,
+        vault_authority: derive_vault_authority_pda(program_id).0,
+        payer: *payer,
+        system_program: solana_sdk::system_program::ID,
+        token_program: spl_token::id(),
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        activity_stats: derive_activity_stats_pda(program_id, &vault).0,
+        vault_registry: derive_vault_registry_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::InitializeVault {
+            name: String::new(),
+        }
+        .data(),
     }
+}
 
-    let setup = CompleteSetup {
-        protocol,
-        underlying,
-        vault,
-        user,
+/// Builds a `deposit` instruction for `user` into `vault`
+pub fn deposit_ix(
+    program_id: &Pubkey,
+    vault: &VaultAccounts,
+    user: &UserAccounts,
+    amount: u64,
+) -> Instruction {
+    let flag = derive_flag_pda(program_id, &vault.vault).0;
+    let accounts = vault_pda::accounts::Deposit {
+        vault: vault.vault,
+        underlying_mint: vault.underlying_mint,
+        vault_token_account: vault.vault_token_account,
+        share_mint: vault.share_mint,
+        vault_authority: derive_vault_authority_pda(program_id).0,
+        depositor_underlying_account: user.underlying_token_account,
+        depositor_share_account: user.share_token_account,
+        depositor: user.owner.pubkey(),
+        flag,
+        player_record: derive_player_pda(program_id, &flag, &user.owner.pubkey()).0,
+        activity_stats: derive_activity_stats_pda(program_id, &vault.vault).0,
+        reserve_config: derive_reserve_config_pda(program_id, &vault.vault).0,
+        treasury: None,
+        protocol_state: None,
+        token_program: vault.token_program,
     };
 
-    Ok((env, setup))
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::Deposit { amount }.data(),
+    }
+}
+
+/// Builds a `redeem` instruction for `user` from `vault`
+pub fn redeem_ix(
+    program_id: &Pubkey,
+    vault: &VaultAccounts,
+    user: &UserAccounts,
+    shares: u64,
+) -> Instruction {
+    let flag = derive_flag_pda(program_id, &vault.vault).0;
+    let accounts = vault_pda::accounts::Redeem {
+        vault: vault.vault,
+        underlying_mint: vault.underlying_mint,
+        vault_token_account: vault.vault_token_account,
+        share_mint: vault.share_mint,
+        vault_authority: derive_vault_authority_pda(program_id).0,
+        redeemer_underlying_account: user.underlying_token_account,
+        redeemer_share_account: user.share_token_account,
+        redeemer: user.owner.pubkey(),
+        flag,
+        player_record: derive_player_pda(program_id, &flag, &user.owner.pubkey()).0,
+        activity_stats: derive_activity_stats_pda(program_id, &vault.vault).0,
+        reserve_config: derive_reserve_config_pda(program_id, &vault.vault).0,
+        treasury: None,
+        protocol_state: None,
+        fee_exemption: derive_fee_exemption_pda(program_id, &user.owner.pubkey()).0,
+        token_program: vault.token_program,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::Redeem { shares }.data(),
+    }
+}
+
+/// Builds a `transfer_ownership` instruction
+pub fn transfer_ownership_ix(
+    program_id: &Pubkey,
+    current_owner: &Pubkey,
+    new_owner: &Pubkey,
+) -> Instruction {
+    let accounts = vault_pda::accounts::TransferOwnership {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        current_owner: *current_owner,
+        new_owner: *new_owner,
+        admin_log: derive_admin_log_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::TransferOwnership {}.data(),
+    }
+}
+
+/// Builds an `initialize_price_history` instruction for an account already
+/// allocated with `PriceHistory::LEN` bytes
+pub fn initialize_price_history_ix(
+    program_id: &Pubkey,
+    price_history: &Pubkey,
+    vault: &Pubkey,
+) -> Instruction {
+    let accounts = vault_pda::accounts::InitializePriceHistory {
+        price_history: *price_history,
+        vault: *vault,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::InitializePriceHistory {}.data(),
+    }
+}
+
+/// Builds a `record_price` instruction for `vault`
+pub fn record_price_ix(
+    program_id: &Pubkey,
+    price_history: &Pubkey,
+    vault: &VaultAccounts,
+) -> Instruction {
+    let accounts = vault_pda::accounts::RecordPrice {
+        price_history: *price_history,
+        vault: vault.vault,
+        vault_token_account: vault.vault_token_account,
+        share_mint: vault.share_mint,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::RecordPrice {}.data(),
+    }
+}
+
+/// Builds an `initialize_flag` instruction planting the flag for `vault`
+pub fn initialize_flag_ix(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    vault: &VaultAccounts,
+) -> Instruction {
+    let accounts = vault_pda::accounts::InitializeFlag {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        flag: derive_flag_pda(program_id, &vault.vault).0,
+        vault: vault.vault,
+        vault_token_account: vault.vault_token_account,
+        owner: *owner,
+        system_program: solana_sdk::system_program::ID,
+        admin_log: derive_admin_log_pda(program_id).0,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::InitializeFlag {}.data(),
+    }
+}
+
+/// Builds a `register_player` instruction for the flag of `vault`, from
+/// which `player`'s deposits and redeems are counted
+pub fn register_player_ix(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    vault: &VaultAccounts,
+) -> Instruction {
+    let flag = derive_flag_pda(program_id, &vault.vault).0;
+    let accounts = vault_pda::accounts::RegisterPlayer {
+        flag,
+        player_record: derive_player_pda(program_id, &flag, player).0,
+        player: *player,
+        system_program: solana_sdk::system_program::ID,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::RegisterPlayer {}.data(),
+    }
+}
+
+/// Builds a `capture_flag` instruction for the flag of `vault`
+pub fn capture_flag_ix(program_id: &Pubkey, captor: &Pubkey, vault: &VaultAccounts) -> Instruction {
+    let flag = derive_flag_pda(program_id, &vault.vault).0;
+    let accounts = vault_pda::accounts::CaptureFlag {
+        flag,
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        player_record: derive_player_pda(program_id, &flag, captor).0,
+        vault: vault.vault,
+        vault_token_account: vault.vault_token_account,
+        captor: *captor,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::CaptureFlag {}.data(),
+    }
 }
 
 // ============================================================================
-// PDA Derivation Helpers
+// Compute Unit Helpers
 // ============================================================================
 
-/// Derive protocol state PDA
-pub fn derive_protocol_state_pda(program_id: &Pubkey) -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[b"protocol_state"], program_id)
+/// Simulates a transaction and returns the compute units it consumed
+///
+/// Fails if the simulated transaction itself fails, so a budget check can never
+/// pass by accident on an error path.
+pub async fn simulate_compute_units(
+    context: &mut ProgramTestContext,
+    tx: Transaction,
+) -> FuzzResult<u64> {
+    let simulation = context.banks_client.simulate_transaction(tx).await?;
+
+    if let Some(Err(e)) = simulation.result {
+        return Err(format!("Simulation failed: {:?}", e).into());
+    }
+
+    let details = simulation
+        .simulation_details
+        .ok_or("Simulation returned no details")?;
+
+    Ok(details.units_consumed)
 }
 
-/// Derive vault authority PDA
-pub fn derive_vault_authority_pda(program_id: &Pubkey) -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[b"vault_authority"], program_id)
+// ============================================================================
+// Clock Helpers
+// ============================================================================
+
+/// Warps to `slot`, or one slot past the current one if `slot` is not ahead,
+/// and refreshes the blockhash
+///
+/// The bank only moves forward, which is also what keeps a reused context
+/// valid (see `enable_context_reuse`).
+pub async fn warp_forward_to(context: &mut ProgramTestContext, slot: u64) -> FuzzResult<Clock> {
+    let clock = context.banks_client.get_sysvar::<Clock>().await?;
+    context.warp_to_slot(slot.max(clock.slot + 1))?;
+    context.last_blockhash = context.banks_client.get_latest_blockhash().await?;
+
+    Ok(context.banks_client.get_sysvar::<Clock>().await?)
 }
 
-/// Derive vault PDA
-pub fn derive_vault_pda(program_id: &Pubkey, underlying_mint: &Pubkey) -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[b"vault", underlying_mint.as_ref()], program_id)
+/// Moves the clock `slots` slots forward (at least one)
+pub async fn advance_slots(context: &mut ProgramTestContext, slots: u64) -> FuzzResult<Clock> {
+    let clock = context.banks_client.get_sysvar::<Clock>().await?;
+    warp_forward_to(context, clock.slot.saturating_add(slots)).await
 }
 
-/// Derive share mint PDA
-pub fn derive_share_mint_pda(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[b"share_mint", vault.as_ref()], program_id)
+/// Moves the clock to the first slot of the epoch `epochs` after the current
+/// one (at least one)
+pub async fn advance_epochs(context: &mut ProgramTestContext, epochs: u64) -> FuzzResult<Clock> {
+    let clock = context.banks_client.get_sysvar::<Clock>().await?;
+    let schedule = context.genesis_config().epoch_schedule.clone();
+    let slot = schedule.get_first_slot_in_epoch(clock.epoch.saturating_add(epochs.max(1)));
+    warp_forward_to(context, slot).await
 }
 
-/// Derive vault token account PDA
-pub fn derive_vault_token_account_pda(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[b"vault_token_account", vault.as_ref()], program_id)
+/// Overrides the `Clock` sysvar's `unix_timestamp`, leaving the slot alone
+///
+/// The next warp recomputes the timestamp, so set it after warping.
+pub async fn set_unix_timestamp(
+    context: &mut ProgramTestContext,
+    unix_timestamp: i64,
+) -> FuzzResult<Clock> {
+    let clock = Clock {
+        unix_timestamp,
+        ..context.banks_client.get_sysvar::<Clock>().await?
+    };
+    context.set_sysvar(&clock);
+
+    Ok(clock)
 }
 
 // ============================================================================
@@ -482,56 +1322,67 @@ pub async fn get_vault_state(
     Ok(vault_data)
 }
 
-/// Get token account balance
-pub async fn get_token_balance(
+/// Get the unpacked state of a token account under SPL Token or Token-2022
+///
+/// Extensions are skipped; the base state carries the delegate, the frozen
+/// flag and the close authority alongside the amount.
+pub async fn get_token_account_state(
     context: &mut ProgramTestContext,
     account: &Pubkey,
-) -> FuzzResult<u64> {
-    let account_data = context
+) -> FuzzResult<spl_token_2022::state::Account> {
+    let account = context
         .banks_client
         .get_account(*account)
         .await?
         .ok_or("Token account not found")?;
 
-    // Manually parse amount from token account data
-    // Token account structure: amount is at offset 64 (u64)
-    if account_data.data.len() < 72 {
-        return Err("Invalid token account data".into());
-    }
-
-    let amount = u64::from_le_bytes(
-        account_data.data[64..72]
-            .try_into()
-            .map_err(|_| "Failed to parse amount")?
-    );
-
-    Ok(amount)
+    let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)?;
+    Ok(state.base)
 }
 
-/// Get mint supply
-pub async fn get_mint_supply(
+/// Get the unpacked state of a mint under SPL Token or Token-2022
+pub async fn get_mint_state(
     context: &mut ProgramTestContext,
     mint: &Pubkey,
-) -> FuzzResult<u64> {
+) -> FuzzResult<spl_token_2022::state::Mint> {
     let account = context
         .banks_client
         .get_account(*mint)
         .await?
         .ok_or("Mint account not found")?;
 
-    // Manually parse supply from mint account data
-    // Mint account structure: supply is at offset 36 (u64)
-    if account.data.len() < 44 {
-        return Err("Invalid mint account data".into());
-    }
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data)?;
+    Ok(state.base)
+}
 
-    let supply = u64::from_le_bytes(
-        account.data[36..44]
-            .try_into()
-            .map_err(|_| "Failed to parse supply")?
-    );
+/// Get token account balance
+pub async fn get_token_balance(
+    context: &mut ProgramTestContext,
+    account: &Pubkey,
+) -> FuzzResult<u64> {
+    Ok(get_token_account_state(context, account).await?.amount)
+}
 
-    Ok(supply)
+/// Get mint supply
+pub async fn get_mint_supply(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+) -> FuzzResult<u64> {
+    Ok(get_mint_state(context, mint).await?.supply)
+}
+
+/// Get the balances `vault_invariants` checks around a deposit or redeem by `user`
+pub async fn get_snapshot(
+    context: &mut ProgramTestContext,
+    vault: &VaultAccounts,
+    user: &UserAccounts,
+) -> FuzzResult<Snapshot> {
+    Ok(Snapshot {
+        vault_balance: get_token_balance(context, &vault.vault_token_account).await?,
+        share_supply: get_mint_supply(context, &vault.share_mint).await?,
+        user_balance: get_token_balance(context, &user.underlying_token_account).await?,
+        user_shares: get_token_balance(context, &user.share_token_account).await?,
+    })
 }
 
 /// Get protocol state
@@ -563,3 +1414,51 @@ pub async fn get_vault_authority(
     let authority = VaultAuthority::try_deserialize(&mut account.data.as_ref())?;
     Ok(authority)
 }
+
+/// Get flag
+pub async fn get_flag(context: &mut ProgramTestContext, flag: &Pubkey) -> FuzzResult<Flag> {
+    let account = context
+        .banks_client
+        .get_account(*flag)
+        .await?
+        .ok_or("Flag account not found")?;
+
+    let flag = Flag::try_deserialize(&mut account.data.as_ref())?;
+    Ok(flag)
+}
+
+/// Get the number of recorded observations and the most recent one
+pub async fn get_latest_price_point(
+    context: &mut ProgramTestContext,
+    price_history: &Pubkey,
+) -> FuzzResult<(u64, Option<PricePoint>)> {
+    let account = context
+        .banks_client
+        .get_account(*price_history)
+        .await?
+        .ok_or("Price history account not found")?;
+
+    // Manually parse the zero-copy layout: discriminator (8), vault (32),
+    // head (8), len (8), then 24-byte points
+    if account.data.len() < PriceHistory::LEN {
+        return Err("Invalid price history data".into());
+    }
+    let read = |offset: usize| {
+        u64::from_le_bytes(account.data[offset..offset + 8].try_into().unwrap())
+    };
+
+    let head = read(40) as usize;
+    let len = read(48);
+    if len == 0 {
+        return Ok((0, None));
+    }
+
+    let index = (head + PriceHistory::CAPACITY - 1) % PriceHistory::CAPACITY;
+    let offset = 56 + index * 24;
+    let point = PricePoint {
+        slot: read(offset),
+        total_assets: read(offset + 8),
+        total_shares: read(offset + 16),
+    };
+    Ok((len, Some(point)))
+}
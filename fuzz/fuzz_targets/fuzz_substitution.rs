@@ -0,0 +1,19 @@
+use fuzz_helpers::fuzz_core::substitution::{fuzz_substitution_once, SubstitutionFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use honggfuzz::fuzz;
+
+fn main() {
+    // Initialize honggfuzz
+    loop {
+        fuzz!(|input: SubstitutionFuzzInput| {
+            reproducer::track("substitution", &input);
+            scenarios::begin("substitution");
+            // Run the async fuzz test
+            block_on(async {
+                if let Err(e) = fuzz_substitution_once(input).await {
+                    eprintln!("Fuzz iteration failed: {}", e);
+                }
+            });
+        });
+    }
+}
@@ -0,0 +1,18 @@
+use fuzz_helpers::fuzz_core::substitution::{fuzz_substitution_once, SubstitutionFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<SubstitutionFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("substitution", &input);
+        scenarios::begin("substitution");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_substitution_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
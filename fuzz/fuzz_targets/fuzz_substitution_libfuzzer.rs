@@ -0,0 +1,16 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::substitution::{fuzz_substitution_once, SubstitutionFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: SubstitutionFuzzInput| {
+    reproducer::track("substitution", &input);
+    scenarios::begin("substitution");
+    // Run the async fuzz test
+    block_on(async {
+        if let Err(e) = fuzz_substitution_once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+});
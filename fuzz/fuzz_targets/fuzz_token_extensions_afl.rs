@@ -0,0 +1,20 @@
+use fuzz_helpers::fuzz_core::token_extensions::{
+    fuzz_token_extensions_once, TokenExtensionsFuzzInput,
+};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<TokenExtensionsFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("token_extensions", &input);
+        scenarios::begin("token_extensions");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_token_extensions_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
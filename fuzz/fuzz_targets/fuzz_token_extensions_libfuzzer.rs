@@ -0,0 +1,18 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::token_extensions::{
+    fuzz_token_extensions_once, TokenExtensionsFuzzInput,
+};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: TokenExtensionsFuzzInput| {
+    reproducer::track("token_extensions", &input);
+    scenarios::begin("token_extensions");
+    // Run the async fuzz test
+    block_on(async {
+        if let Err(e) = fuzz_token_extensions_once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+});
@@ -1,6 +1,19 @@
-// Placeholder for fuzz_transfer_ownership target
-// TODO: Implement fuzzing for transfer_ownership instruction
+use fuzz_helpers::fuzz_core::authorization::{fuzz_authorization_once, AuthorizationFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use honggfuzz::fuzz;
 
 fn main() {
-    println!("fuzz_transfer_ownership - To be implemented");
+    // Initialize honggfuzz
+    loop {
+        fuzz!(|input: AuthorizationFuzzInput| {
+            reproducer::track("authorization", &input);
+            scenarios::begin("authorization");
+            // Run the async fuzz test
+            block_on(async {
+                if let Err(e) = fuzz_authorization_once(input).await {
+                    eprintln!("Fuzz iteration failed: {}", e);
+                }
+            });
+        });
+    }
 }
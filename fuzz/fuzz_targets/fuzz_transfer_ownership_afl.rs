@@ -0,0 +1,18 @@
+use fuzz_helpers::fuzz_core::authorization::{fuzz_authorization_once, AuthorizationFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, corpus, reproducer, scenarios};
+
+fn main() {
+    afl::fuzz!(|data: &[u8]| {
+        let Some(input) = corpus::decode::<AuthorizationFuzzInput>(data) else {
+            return;
+        };
+        reproducer::track("authorization", &input);
+        scenarios::begin("authorization");
+        // Run the async fuzz test
+        block_on(async {
+            if let Err(e) = fuzz_authorization_once(input).await {
+                eprintln!("Fuzz iteration failed: {}", e);
+            }
+        });
+    });
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use fuzz_helpers::fuzz_core::authorization::{fuzz_authorization_once, AuthorizationFuzzInput};
+use fuzz_helpers::fuzz_core::{block_on, reproducer, scenarios};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: AuthorizationFuzzInput| {
+    reproducer::track("authorization", &input);
+    scenarios::begin("authorization");
+    // Run the async fuzz test
+    block_on(async {
+        if let Err(e) = fuzz_authorization_once(input).await {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    });
+});
@@ -0,0 +1,166 @@
+//! Intra-block transaction ordering.
+//!
+//! Transactions that reach a leader together can land in any order, and
+//! whoever orders the block picks which. A [`Block`] is such a set of
+//! transactions from several users; [`Block::run`] rewinds to a snapshot and
+//! processes them in one ordering, each seeing the state the previous ones
+//! left, so a test can replay the same block in every ordering
+//! ([`Block::orderings`]) or only the adversarial ones ([`Block::sandwiches`])
+//! and compare what each participant ends up with.
+//!
+//! A failed transaction is recorded and the block carries on, as it would on
+//! a validator. The clock is not rewound between orderings (see
+//! [`snapshot`](crate::snapshot)).
+
+use solana_program_test::BanksClientError;
+use solana_sdk::{instruction::Instruction, signature::Keypair};
+
+use crate::fuzz_core::sequence::Sender;
+use crate::*;
+
+/// Most transactions a block may hold, so every ordering can be tried
+pub const MAX_BLOCK_TRANSACTIONS: usize = 6;
+
+/// Transactions that can land in a block in any order
+#[derive(Debug, Default)]
+pub struct Block {
+    transactions: Vec<BlockTransaction>,
+}
+
+#[derive(Debug)]
+struct BlockTransaction {
+    label: &'static str,
+    instructions: Vec<Instruction>,
+    /// The first one pays
+    signers: Vec<Keypair>,
+}
+
+/// Outcome of running a [`Block`] in one ordering
+#[derive(Debug)]
+pub struct BlockOutcome {
+    /// Every transaction's label and result, in the order they were processed
+    pub results: Vec<(&'static str, Result<(), BanksClientError>)>,
+}
+
+impl BlockOutcome {
+    /// Result of the transaction labelled `label`
+    pub fn result(&self, label: &str) -> Option<&Result<(), BanksClientError>> {
+        self.results
+            .iter()
+            .find(|(recorded, _)| *recorded == label)
+            .map(|(_, result)| result)
+    }
+
+    /// Whether the transaction labelled `label` landed
+    pub fn landed(&self, label: &str) -> bool {
+        matches!(self.result(label), Some(Ok(())))
+    }
+
+    /// The ordering as labels, e.g. `donate, deposit, redeem`
+    pub fn order(&self) -> String {
+        self.results
+            .iter()
+            .map(|(label, _)| *label)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl Block {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a transaction of `instructions` signed by `signers` (the first
+    /// one pays), returning its index
+    pub fn push(
+        &mut self,
+        label: &'static str,
+        instructions: Vec<Instruction>,
+        signers: &[&Keypair],
+    ) -> usize {
+        assert!(
+            self.transactions.len() < MAX_BLOCK_TRANSACTIONS,
+            "a block holds at most {} transactions",
+            MAX_BLOCK_TRANSACTIONS
+        );
+        assert!(!signers.is_empty(), "{} has no fee payer", label);
+        self.transactions.push(BlockTransaction {
+            label,
+            instructions,
+            signers: signers
+                .iter()
+                .map(|signer| signer.insecure_clone())
+                .collect(),
+        });
+        self.transactions.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// Label of the transaction at `index`
+    pub fn label(&self, index: usize) -> &'static str {
+        self.transactions[index].label
+    }
+
+    /// Every ordering of the block as transaction indices, starting with the
+    /// order they were pushed in
+    pub fn orderings(&self) -> Vec<Vec<usize>> {
+        let mut orderings = Vec::new();
+        permute(&mut (0..self.len()).collect::<Vec<_>>(), 0, &mut orderings);
+        orderings.sort();
+        orderings
+    }
+
+    /// Orderings that put `front` before `victim` and `back` after it
+    pub fn sandwiches(&self, front: usize, victim: usize, back: usize) -> Vec<Vec<usize>> {
+        self.orderings()
+            .into_iter()
+            .filter(|order| {
+                let position = |index| order.iter().position(|&i| i == index);
+                position(front) < position(victim) && position(victim) < position(back)
+            })
+            .collect()
+    }
+
+    /// Restores `start`, then processes the block's transactions in `order`
+    pub async fn run(
+        &self,
+        env: &mut FuzzTestEnv,
+        sender: &mut Sender,
+        start: &StateSnapshot,
+        order: &[usize],
+    ) -> FuzzResult<BlockOutcome> {
+        restore(&mut env.context, start).await?;
+
+        let mut results = Vec::with_capacity(order.len());
+        for &index in order {
+            let transaction = &self.transactions[index];
+            let signers: Vec<&Keypair> = transaction.signers.iter().collect();
+            let result = sender
+                .send_instructions(env, &transaction.instructions, &signers)
+                .await?;
+            results.push((transaction.label, result));
+        }
+        Ok(BlockOutcome { results })
+    }
+}
+
+/// Appends every permutation of `items[start..]` behind `items[..start]`
+fn permute(items: &mut [usize], start: usize, out: &mut Vec<Vec<usize>>) {
+    if start == items.len() {
+        out.push(items.to_vec());
+        return;
+    }
+    for i in start..items.len() {
+        items.swap(start, i);
+        permute(items, start + 1, out);
+        items.swap(start, i);
+    }
+}
@@ -0,0 +1,109 @@
+//! Deterministic keypairs and addresses.
+//!
+//! Helpers create every keypair with [`new_keypair`] and every throwaway
+//! address with [`new_address`]. Unseeded, these are random like
+//! `Keypair::new()`. Once a thread is seeded with [`seed_keypairs`], the n-th
+//! keypair is derived from the seed and n, so two runs of the same input
+//! produce the same mints, owners and vault PDAs, and reproducers and
+//! differential runs compare byte for byte.
+//!
+//! Wrappers seed from the input through `reproducer::track`, and both replay
+//! paths seed from the decoded input the same way, so a replay creates the
+//! addresses the fuzzer saw.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+
+use serde::Serialize;
+use solana_sdk::{
+    hash::hashv, pubkey::Pubkey, signature::Keypair, signer::keypair::keypair_from_seed,
+};
+
+thread_local! {
+    /// Seed of the running iteration and keypairs derived from it so far
+    static SEED: Cell<Option<(u64, u64)>> = const { Cell::new(None) };
+
+    /// Seeds that already ran on this thread's context
+    static USED_SEEDS: RefCell<HashSet<u64>> = RefCell::new(HashSet::new());
+}
+
+/// Derives every following keypair and address on this thread from `seed`
+pub fn seed_keypairs(seed: u64) {
+    SEED.set(Some((seed, 0)));
+}
+
+/// Goes back to random keypairs on this thread
+pub fn clear_seed() {
+    SEED.set(None);
+}
+
+/// Seed of the running iteration, if any
+pub fn current_seed() -> Option<u64> {
+    SEED.get().map(|(seed, _)| seed)
+}
+
+/// Seed for `input`, from the first 8 bytes of the SHA-256 of its JSON form
+///
+/// The input goes through a `serde_json::Value` first, which orders keys, so
+/// a typed input and the JSON a reproducer file stores for it derive the same
+/// seed.
+pub fn seed_for<T: Serialize + ?Sized>(input: &T) -> u64 {
+    let json = serde_json::to_value(input)
+        .and_then(|value| serde_json::to_vec(&value))
+        .unwrap_or_default();
+    let hash = hashv(&[b"vault-fuzz-seed", &json]);
+    u64::from_le_bytes(hash.to_bytes()[..8].try_into().unwrap())
+}
+
+/// Seeds this thread from `input`
+pub fn seed_from_input<T: Serialize + ?Sized>(input: &T) {
+    seed_keypairs(seed_for(input));
+}
+
+/// Next 32 bytes of key material for the running seed
+fn next_material(domain: &[u8]) -> Option<[u8; 32]> {
+    let (seed, counter) = SEED.get()?;
+    SEED.set(Some((seed, counter + 1)));
+    let hash = hashv(&[domain, &seed.to_le_bytes(), &counter.to_le_bytes()]);
+    Some(hash.to_bytes())
+}
+
+/// A keypair, derived from the thread's seed when there is one
+pub fn new_keypair() -> Keypair {
+    match next_material(b"keypair") {
+        Some(material) => keypair_from_seed(&material).expect("32 bytes of seed material"),
+        None => Keypair::new(),
+    }
+}
+
+/// An address nobody holds the key to, derived from the thread's seed when
+/// there is one
+pub fn new_address() -> Pubkey {
+    match next_material(b"address") {
+        Some(material) => Pubkey::new_from_array(material),
+        None => Pubkey::new_unique(),
+    }
+}
+
+/// Whether the cached context can serve the running iteration
+///
+/// A reused context keeps accounts at fresh addresses, so running a seed on
+/// it a second time would collide with the accounts of the first run.
+pub(crate) fn context_reusable() -> bool {
+    match current_seed() {
+        Some(seed) => !USED_SEEDS.with_borrow(|used| used.contains(&seed)),
+        None => true,
+    }
+}
+
+/// Records that the running iteration got a context, `fresh` or reused
+pub(crate) fn record_context_use(fresh: bool) {
+    USED_SEEDS.with_borrow_mut(|used| {
+        if fresh {
+            used.clear();
+        }
+        if let Some(seed) = current_seed() {
+            used.insert(seed);
+        }
+    });
+}
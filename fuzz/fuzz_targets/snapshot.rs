@@ -0,0 +1,210 @@
+//! Account snapshots for what-if branches.
+//!
+//! [`snapshot`] records the accounts of a setup (vault, mints, token accounts,
+//! user wallets and the payer) and [`restore`] writes them back, so a target
+//! can try one operation, look at the outcome and rewind to try another
+//! without rebuilding the environment:
+//!
+//! ```ignore
+//! let before = snapshot(&mut env.context, &setup).await?;
+//! sender.deposit_as(&mut env, &setup.vault, &setup.user, amount).await?;
+//! // ... inspect the deposit ...
+//! restore(&mut env.context, &before).await?;
+//! ```
+//!
+//! Only the recorded accounts are rewound. Accounts created after the
+//! snapshot at other addresses stay behind, and the clock is never rewound.
+
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    pubkey::Pubkey,
+    signature::Signer,
+};
+
+use crate::{
+    Attacker, CompleteSetup, FuzzEnv, FuzzResult, ProgramTestContext, ProtocolAccounts,
+    TwoVaultSetup, UnderlyingMintAccounts, UserAccounts, VaultAccounts, VaultSetup,
+};
+
+/// Accounts a setup is made of, as recorded by [`snapshot`]
+pub trait SnapshotAccounts {
+    /// Appends every address this setup owns to `addresses`
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>);
+}
+
+impl SnapshotAccounts for Pubkey {
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>) {
+        addresses.push(*self);
+    }
+}
+
+impl<T: SnapshotAccounts> SnapshotAccounts for [T] {
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>) {
+        for item in self {
+            item.collect_addresses(addresses);
+        }
+    }
+}
+
+impl<T: SnapshotAccounts> SnapshotAccounts for Vec<T> {
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>) {
+        self.as_slice().collect_addresses(addresses);
+    }
+}
+
+impl<T: SnapshotAccounts + ?Sized> SnapshotAccounts for &T {
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>) {
+        (**self).collect_addresses(addresses);
+    }
+}
+
+impl<A: SnapshotAccounts, B: SnapshotAccounts> SnapshotAccounts for (A, B) {
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>) {
+        self.0.collect_addresses(addresses);
+        self.1.collect_addresses(addresses);
+    }
+}
+
+impl SnapshotAccounts for ProtocolAccounts {
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>) {
+        addresses.extend([self.protocol_state, self.vault_authority, self.owner]);
+    }
+}
+
+impl SnapshotAccounts for UnderlyingMintAccounts {
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>) {
+        addresses.push(self.mint);
+    }
+}
+
+impl SnapshotAccounts for VaultAccounts {
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>) {
+        addresses.extend([
+            self.vault,
+            self.vault_token_account,
+            self.share_mint,
+            self.underlying_mint,
+        ]);
+    }
+}
+
+impl SnapshotAccounts for UserAccounts {
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>) {
+        addresses.extend([
+            self.owner.pubkey(),
+            self.underlying_token_account,
+            self.share_token_account,
+        ]);
+    }
+}
+
+impl SnapshotAccounts for Attacker {
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>) {
+        self.position.collect_addresses(addresses);
+        addresses.push(self.spare);
+    }
+}
+
+impl SnapshotAccounts for CompleteSetup {
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>) {
+        self.protocol.collect_addresses(addresses);
+        self.underlying.collect_addresses(addresses);
+        self.vault.collect_addresses(addresses);
+        self.user.collect_addresses(addresses);
+    }
+}
+
+impl SnapshotAccounts for TwoVaultSetup {
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>) {
+        self.a.collect_addresses(addresses);
+        self.underlying_b.collect_addresses(addresses);
+        self.vault_b.collect_addresses(addresses);
+        self.user_b.collect_addresses(addresses);
+    }
+}
+
+impl SnapshotAccounts for VaultSetup {
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>) {
+        self.underlying.collect_addresses(addresses);
+        self.vault.collect_addresses(addresses);
+        self.users.collect_addresses(addresses);
+    }
+}
+
+impl SnapshotAccounts for FuzzEnv {
+    fn collect_addresses(&self, addresses: &mut Vec<Pubkey>) {
+        self.protocol.collect_addresses(addresses);
+        self.vaults.collect_addresses(addresses);
+    }
+}
+
+/// Recorded state of a set of accounts
+///
+/// `None` marks an address that held no account, which [`restore`] empties
+/// again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateSnapshot {
+    accounts: Vec<(Pubkey, Option<Account>)>,
+}
+
+impl StateSnapshot {
+    /// Recorded account at `address`, if it was recorded and existed
+    pub fn get(&self, address: &Pubkey) -> Option<&Account> {
+        self.accounts
+            .iter()
+            .find(|(recorded, _)| recorded == address)
+            .and_then(|(_, account)| account.as_ref())
+    }
+
+    /// Every recorded address
+    pub fn addresses(&self) -> impl Iterator<Item = &Pubkey> {
+        self.accounts.iter().map(|(address, _)| address)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+}
+
+/// Records every account of `accounts`, plus the payer
+pub async fn snapshot(
+    context: &mut ProgramTestContext,
+    accounts: &(impl SnapshotAccounts + ?Sized),
+) -> FuzzResult<StateSnapshot> {
+    let mut addresses = vec![context.payer.pubkey()];
+    accounts.collect_addresses(&mut addresses);
+    snapshot_addresses(context, addresses).await
+}
+
+/// Records the accounts at `addresses`
+pub async fn snapshot_addresses(
+    context: &mut ProgramTestContext,
+    mut addresses: Vec<Pubkey>,
+) -> FuzzResult<StateSnapshot> {
+    addresses.sort_unstable();
+    addresses.dedup();
+
+    let mut recorded = Vec::with_capacity(addresses.len());
+    for address in addresses {
+        let account = context.banks_client.get_account(address).await?;
+        recorded.push((address, account));
+    }
+    Ok(StateSnapshot { accounts: recorded })
+}
+
+/// Writes every recorded account back and refreshes the blockhash
+///
+/// A transaction sent before the snapshot may still be rejected as already
+/// processed; `Sender` resigns such duplicates with a new blockhash.
+pub async fn restore(context: &mut ProgramTestContext, snapshot: &StateSnapshot) -> FuzzResult<()> {
+    for (address, account) in &snapshot.accounts {
+        // A zero-lamport account is gone as far as the runtime is concerned
+        let account = account
+            .clone()
+            .map(AccountSharedData::from)
+            .unwrap_or_default();
+        context.set_account(address, &account);
+    }
+    context.last_blockhash = context.banks_client.get_latest_blockhash().await?;
+    Ok(())
+}
@@ -0,0 +1,86 @@
+//! Attacker persona tests.
+//!
+//! Donations must come out of the spare account only, and a front-run must
+//! land before the pending transaction it jumps.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test attacker
+//! ```
+
+use fuzz_helpers::fuzz_core::sequence::Sender;
+use fuzz_helpers::*;
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn donation_front_runs_a_pending_deposit() {
+    let (mut env, setup) = setup_complete_environment(10_000, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let attacker = Attacker::new(&mut env.context, &setup.underlying, &setup.vault, 1, 500)
+        .await
+        .unwrap();
+    let mut sender = Sender::default();
+
+    sender
+        .deposit_as(&mut env, &setup.vault, &attacker.position, 1)
+        .await
+        .unwrap()
+        .expect("attacker deposit");
+
+    let pending = deposit_ix(&env.program_id, &setup.vault, &setup.user, 1_000);
+    let donation = attacker.donate_ix(&setup.vault, 500).unwrap();
+    let outcome = attacker
+        .front_run(
+            &mut env,
+            &mut sender,
+            donation,
+            pending,
+            &[&setup.user.owner],
+        )
+        .await
+        .unwrap();
+    outcome.attack.expect("donation");
+    outcome.pending.expect("victim deposit");
+
+    // The victim paid 1_000 at the price the donation set: 501 per share in
+    // the challenge build
+    let victim = get_snapshot(&mut env.context, &setup.vault, &setup.user)
+        .await
+        .unwrap();
+    assert_eq!(victim.vault_balance, 1 + 500 + 1_000);
+    assert_eq!(
+        victim.user_shares,
+        vault_math::compute_shares(1_000, 1, 1 + 500).unwrap()
+    );
+    assert_eq!(
+        get_token_balance(&mut env.context, &attacker.spare)
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn substituted_accounts_keep_their_flags() {
+    let (mut env, setup) = setup_complete_environment(0, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let attacker = Attacker::new(&mut env.context, &setup.underlying, &setup.vault, 0, 0)
+        .await
+        .unwrap();
+
+    let ix = deposit_ix(&env.program_id, &setup.vault, &setup.user, 1);
+    let forged = attacker.impersonate(&ix, &setup.user.owner.pubkey());
+    for (original, forged) in ix.accounts.iter().zip(&forged.accounts) {
+        assert_eq!(original.is_signer, forged.is_signer);
+        assert_eq!(original.is_writable, forged.is_writable);
+        if original.pubkey == setup.user.owner.pubkey() {
+            assert_eq!(forged.pubkey, attacker.pubkey());
+        } else {
+            assert_eq!(forged.pubkey, original.pubkey);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! Backend equivalence tests.
+//!
+//! The same scenario runs on `solana-program-test` and, with the `litesvm`
+//! feature, on LiteSVM. Both backends execute the same SBF build, so every
+//! observable result must match.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --features litesvm --test backends
+//! ```
+
+use fuzz_helpers::backend::*;
+use fuzz_helpers::*;
+
+const INITIAL_BALANCE: u64 = 1_000_000;
+const AMOUNTS: [u64; 4] = [1, 999, 100_000, INITIAL_BALANCE];
+
+/// Shares minted and assets paid back for each amount in `AMOUNTS`
+async fn run_scenario<B: FuzzBackend>(backend: &mut B) -> Vec<(u64, u64)> {
+    let program_id = vault_pda::id();
+    let setup = setup_complete_on(backend, &program_id, INITIAL_BALANCE, 6)
+        .await
+        .unwrap();
+
+    let mut results = Vec::new();
+    for amount in AMOUNTS {
+        let result = deposit_redeem_round_trip(backend, &program_id, &setup, amount)
+            .await
+            .unwrap();
+        assert_eq!(
+            token_balance(backend, &setup.user.underlying_token_account)
+                .await
+                .unwrap(),
+            INITIAL_BALANCE,
+            "round trip of {} lost funds",
+            amount
+        );
+        results.push(result);
+    }
+    assert_eq!(
+        mint_supply(backend, &setup.vault.share_mint).await.unwrap(),
+        0
+    );
+    results
+}
+
+#[tokio::test]
+async fn program_test_round_trips() {
+    let mut env = setup_program_test().await;
+    let results = run_scenario(&mut *env.context).await;
+    assert!(results.iter().all(|(shares, assets)| shares == assets));
+}
+
+#[cfg(feature = "litesvm")]
+#[tokio::test]
+async fn litesvm_matches_program_test() {
+    let mut env = setup_program_test().await;
+    let expected = run_scenario(&mut *env.context).await;
+
+    let mut litesvm = LiteSvmBackend::new().unwrap();
+    assert_eq!(run_scenario(&mut litesvm).await, expected);
+}
@@ -0,0 +1,179 @@
+//! Environment builder tests.
+//!
+//! `FuzzEnvBuilder` must produce exactly the topology it is asked for, under
+//! either token program.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test builder
+//! ```
+
+use fuzz_helpers::*;
+use solana_sdk::signature::Signer;
+
+async fn check_topology(token_2022: bool) {
+    let (mut env, setup) = FuzzEnvBuilder::new()
+        .users(3)
+        .vaults(2)
+        .decimals(9)
+        .token_2022(token_2022)
+        .balance(1_000)
+        .build()
+        .await
+        .expect("builder setup");
+
+    assert_eq!(setup.vaults.len(), 2);
+
+    // Setups clone with their keypairs, so a copy can still sign
+    let copy = setup.clone();
+    assert_eq!(
+        copy.protocol.owner_keypair.pubkey(),
+        setup.protocol.owner_keypair.pubkey()
+    );
+    assert_eq!(
+        copy.vaults[1].underlying.mint_authority.pubkey(),
+        setup.vaults[1].underlying.mint_authority.pubkey()
+    );
+    assert_ne!(setup.vaults[0].vault.vault, setup.vaults[1].vault.vault);
+
+    for vault in &setup.vaults {
+        assert_eq!(vault.users.len(), 3);
+        assert_eq!(vault.underlying.decimals, 9);
+        let mint = env
+            .context
+            .banks_client
+            .get_account(vault.underlying.mint)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(mint.owner, setup.token_program);
+
+        for (index, user) in vault.users.iter().enumerate() {
+            // User `i` is the same keypair in every vault
+            assert_eq!(user.owner.pubkey(), setup.user(0, index).owner.pubkey());
+            let balance = get_token_balance(&mut env.context, &user.underlying_token_account)
+                .await
+                .unwrap();
+            assert_eq!(balance, 1_000, "user {} underlying balance", index);
+            let shares = get_token_balance(&mut env.context, &user.share_token_account)
+                .await
+                .unwrap();
+            assert_eq!(shares, 0, "user {} share balance", index);
+        }
+    }
+}
+
+#[tokio::test]
+async fn builds_spl_token_topology() {
+    check_topology(false).await;
+}
+
+#[tokio::test]
+async fn builds_token_2022_topology() {
+    check_topology(true).await;
+}
+
+#[tokio::test]
+async fn funding_helpers_credit_their_targets() {
+    let (mut env, setup) = setup_complete_environment(0, 6, TokenFlavor::SplToken)
+        .await
+        .expect("complete setup");
+
+    // Lamports are added on top of an existing balance, or create the account
+    let payer = env.context.payer.pubkey();
+    let before = env.context.banks_client.get_balance(payer).await.unwrap();
+    fund_lamports(&mut *env.context, &payer, 42).await.unwrap();
+    let after = env.context.banks_client.get_balance(payer).await.unwrap();
+    assert_eq!(after, before + 42);
+
+    let fresh = solana_sdk::pubkey::Pubkey::new_unique();
+    fund_lamports(&mut *env.context, &fresh, 7).await.unwrap();
+    assert_eq!(
+        env.context.banks_client.get_balance(fresh).await.unwrap(),
+        7
+    );
+
+    let user = create_and_fund_user(
+        &mut env.context,
+        &setup.underlying,
+        &setup.vault.share_mint,
+        500,
+    )
+    .await
+    .expect("funded user");
+    let balance = get_token_balance(&mut env.context, &user.underlying_token_account)
+        .await
+        .unwrap();
+    assert_eq!(balance, 500);
+
+    donate_to_vault(&mut env.context, &setup.underlying, &setup.vault, 300)
+        .await
+        .expect("donation");
+    let vault_balance = get_token_balance(&mut env.context, &setup.vault.vault_token_account)
+        .await
+        .unwrap();
+    assert_eq!(vault_balance, 300);
+    assert_eq!(
+        get_mint_supply(&mut env.context, &setup.vault.share_mint)
+            .await
+            .unwrap(),
+        0,
+        "a donation mints no shares"
+    );
+}
+
+#[tokio::test]
+async fn complete_environment_round_trips_under_every_flavor() {
+    for flavor in TokenFlavor::ALL {
+        let (mut env, setup) = setup_complete_environment(1_000, 6, flavor)
+            .await
+            .expect("complete setup");
+        let program_id = env.program_id;
+
+        for address in [setup.underlying.mint, setup.vault.share_mint] {
+            let account = env
+                .context
+                .banks_client
+                .get_account(address)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                account.owner,
+                flavor.program_id(),
+                "{:?} mint owner",
+                flavor
+            );
+        }
+        assert_eq!(setup.vault.token_program, flavor.program_id());
+
+        donate_to_vault(&mut env.context, &setup.underlying, &setup.vault, 100)
+            .await
+            .expect("donation");
+        let ix = deposit_ix(&program_id, &setup.vault, &setup.user, 500);
+        backend::send(&mut *env.context, &[ix], &[&setup.user.owner])
+            .await
+            .expect("deposit");
+        let shares = get_token_balance(&mut env.context, &setup.user.share_token_account)
+            .await
+            .unwrap();
+        let ix = redeem_ix(&program_id, &setup.vault, &setup.user, shares);
+        backend::send(&mut *env.context, &[ix], &[&setup.user.owner])
+            .await
+            .expect("redeem");
+
+        let balance = get_token_balance(&mut env.context, &setup.user.underlying_token_account)
+            .await
+            .unwrap();
+        // Less what the virtual share keeps outside the challenge build
+        let payout = vault_math::compute_assets(shares, shares, 600).unwrap();
+        assert_eq!(
+            balance,
+            500 + payout,
+            "{:?}: the only holder takes the donation",
+            flavor
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! Clock helper tests.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test clock
+//! ```
+
+use fuzz_helpers::*;
+use solana_sdk::clock::Clock;
+
+#[tokio::test]
+async fn clock_only_moves_forward() {
+    let mut env = setup_program_test().await;
+    let start = env
+        .context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap();
+
+    let clock = advance_slots(&mut env.context, 10).await.unwrap();
+    assert_eq!(clock.slot, start.slot + 10);
+
+    // Zero still moves, since the bank cannot stay on a warped-to slot
+    let clock = advance_slots(&mut env.context, 0).await.unwrap();
+    assert_eq!(clock.slot, start.slot + 11);
+
+    let clock = advance_epochs(&mut env.context, 2).await.unwrap();
+    assert_eq!(clock.epoch, start.epoch + 2);
+    let schedule = env.context.genesis_config().epoch_schedule.clone();
+    assert_eq!(clock.slot, schedule.get_first_slot_in_epoch(clock.epoch));
+
+    let clock = warp_forward_to(&mut env.context, 0).await.unwrap();
+    assert!(clock.slot > schedule.get_first_slot_in_epoch(start.epoch + 2));
+}
+
+#[tokio::test]
+async fn unix_timestamp_is_overridden() {
+    let mut env = setup_program_test().await;
+    let before = advance_slots(&mut env.context, 1).await.unwrap();
+
+    set_unix_timestamp(&mut env.context, 1_700_000_000)
+        .await
+        .unwrap();
+    let clock = env
+        .context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap();
+    assert_eq!(clock.unix_timestamp, 1_700_000_000);
+    assert_eq!(clock.slot, before.slot);
+}
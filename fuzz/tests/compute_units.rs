@@ -0,0 +1,172 @@
+//! Compute unit regression tests.
+//!
+//! Every instruction is simulated against the SBF build of the program and the
+//! consumed compute units are compared with the budgets below. Raise a budget
+//! only deliberately, in the same change that makes the instruction heavier.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test compute_units
+//! ```
+
+use fuzz_helpers::*;
+use solana_sdk::{
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+const INITIALIZE_BUDGET: u64 = 40_000;
+const INITIALIZE_VAULT_BUDGET: u64 = 80_000;
+const DEPOSIT_BUDGET: u64 = 40_000;
+const REDEEM_BUDGET: u64 = 40_000;
+const TRANSFER_OWNERSHIP_BUDGET: u64 = 10_000;
+
+async fn measure(context: &mut ProgramTestContext, ix: Instruction, signers: &[&Keypair]) -> u64 {
+    let payer = signers[0].pubkey();
+    let tx =
+        Transaction::new_signed_with_payer(&[ix], Some(&payer), signers, context.last_blockhash);
+
+    simulate_compute_units(context, tx)
+        .await
+        .expect("instruction should succeed")
+}
+
+fn assert_within_budget(instruction: &str, used: u64, budget: u64) {
+    println!("{instruction}: {used} / {budget} CU");
+    assert!(
+        used <= budget,
+        "{instruction} consumed {used} compute units, budget is {budget}"
+    );
+}
+
+#[tokio::test]
+async fn instructions_stay_within_compute_budget() {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let payer = env.context.payer.insecure_clone();
+
+    // initialize
+    let ix = initialize_ix(&program_id, &payer.pubkey());
+    let used = measure(&mut env.context, ix, &[&payer]).await;
+    assert_within_budget("initialize", used, INITIALIZE_BUDGET);
+
+    let protocol = setup_protocol(&mut env.context, &program_id).await.unwrap();
+    let underlying = setup_underlying_mint(&mut env.context, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+
+    // initialize_vault
+    let ix = initialize_vault_ix(&program_id, &underlying.mint, &payer.pubkey());
+    let used = measure(&mut env.context, ix, &[&payer]).await;
+    assert_within_budget("initialize_vault", used, INITIALIZE_VAULT_BUDGET);
+
+    let vault = setup_vault(
+        &mut env.context,
+        &program_id,
+        &protocol.vault_authority,
+        &underlying.mint,
+        &protocol.owner_keypair,
+        TokenFlavor::SplToken,
+    )
+    .await
+    .unwrap();
+
+    let user = setup_user_accounts(
+        &mut env.context,
+        &underlying.mint,
+        &vault.share_mint,
+        TokenFlavor::SplToken,
+    )
+    .await
+    .unwrap();
+    mint_tokens_to_user(
+        &mut env.context,
+        &underlying.mint,
+        &underlying.mint_authority,
+        &user.underlying_token_account,
+        1_000_000,
+    )
+    .await
+    .unwrap();
+
+    // deposit, first deposit path
+    let ix = deposit_ix(&program_id, &vault, &user, 500_000);
+    let used = measure(&mut env.context, ix.clone(), &[&user.owner]).await;
+    assert_within_budget("deposit (first)", used, DEPOSIT_BUDGET);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&user.owner.pubkey()),
+        &[&user.owner],
+        env.context.last_blockhash,
+    );
+    env.context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+
+    // deposit, proportional path
+    let ix = deposit_ix(&program_id, &vault, &user, 250_000);
+    let used = measure(&mut env.context, ix, &[&user.owner]).await;
+    assert_within_budget("deposit (subsequent)", used, DEPOSIT_BUDGET);
+
+    // redeem
+    let ix = redeem_ix(&program_id, &vault, &user, 100_000);
+    let used = measure(&mut env.context, ix, &[&user.owner]).await;
+    assert_within_budget("redeem", used, REDEEM_BUDGET);
+
+    // transfer_ownership
+    let ix = transfer_ownership_ix(&program_id, &protocol.owner, &Keypair::new().pubkey());
+    let used = measure(&mut env.context, ix, &[&protocol.owner_keypair]).await;
+    assert_within_budget("transfer_ownership", used, TRANSFER_OWNERSHIP_BUDGET);
+}
+
+#[tokio::test]
+async fn fuzz_transactions_record_compute_units() {
+    let (mut env, setup) = setup_complete_environment(1_000_000, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let program_id = env.program_id;
+
+    let deposit = deposit_ix(&program_id, &setup.vault, &setup.user, 500_000);
+    let redeem = redeem_ix(&program_id, &setup.vault, &setup.user, 100_000);
+    assert_eq!(
+        compute_units::instruction_name(&deposit.data),
+        Some("deposit")
+    );
+    assert_eq!(
+        compute_units::instruction_name(&redeem.data),
+        Some("redeem")
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit, redeem],
+        Some(&setup.user.owner.pubkey()),
+        &[&setup.user.owner],
+        env.context.last_blockhash,
+    );
+    compute_units::process_transaction(&mut env.context, &program_id, tx)
+        .await
+        .unwrap();
+
+    let summary = compute_units::summary();
+    let max = summary["deposit+redeem"]["max"].as_u64().unwrap();
+    assert!(
+        max > 0 && max <= DEPOSIT_BUDGET + REDEEM_BUDGET,
+        "{}",
+        summary
+    );
+    let p50 = summary["deposit+redeem"]["p50"].as_u64().unwrap();
+    let p99 = summary["deposit+redeem"]["p99"].as_u64().unwrap();
+    assert!(p50 > 0 && p50 <= p99 && p99 <= max, "{}", summary);
+    assert!(
+        summary["initialize_vault"]["transactions"]
+            .as_u64()
+            .unwrap()
+            >= 1
+    );
+}
@@ -0,0 +1,442 @@
+//! Account constraint matrix.
+//!
+//! One test per Anchor constraint of `initialize`, `initialize_vault`,
+//! `deposit`, `redeem` and `transfer_ownership`. Each starts from an
+//! instruction that succeeds as built, breaks exactly that constraint (wrong
+//! seeds, stored bump, `has_one` target, owner program, account type, token
+//! mint or authority, missing signer, read-only where `mut` is required) and
+//! asserts the precise error.
+//!
+//! Anchor deserializes every account before it runs any constraint, and runs
+//! `init` constraints first, then each field's constraints in declaration
+//! order (seeds, `mut`, signer, `has_one`, token). Violations are chosen so
+//! nothing checked earlier fails first. `deposit` and `redeem` share one
+//! account list and so one table.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test constraints
+//! ```
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::{AccountDeserialize, AccountSerialize};
+use fuzz_helpers::fuzz_core::sequence::Sender;
+use fuzz_helpers::*;
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+use vault_pda::state::Vault;
+
+const BALANCE: u64 = 1_000_000;
+
+/// How a case breaks its instruction
+enum Violation {
+    /// Nothing: the instruction as built must succeed
+    Untouched,
+    /// Account `index` replaced by another address
+    Substitute(usize, fn(&Substitutes) -> Pubkey),
+    /// Account `index` passed read-only
+    ReadOnly(usize),
+    /// Account `index` passed without its signature
+    Unsigned(usize),
+    /// The program account at `index` copied to a fresh address, which is
+    /// passed instead
+    Copied(usize),
+    /// The bump stored in the program account at `index` changed. It is the
+    /// last field of `Vault`, `VaultAuthority` and `ProtocolState`
+    StoredBump(usize),
+    /// The vault at index 0 rewritten in place
+    RewriteVault(fn(&mut Vault, &Substitutes)),
+}
+
+/// How a case must end
+#[derive(Debug)]
+enum Expected {
+    /// The instruction succeeds
+    Passes,
+    /// An Anchor error code
+    Framework(ErrorCode),
+    /// A runtime error, from an account no Anchor type could read
+    Runtime(InstructionError),
+}
+
+impl From<ErrorCode> for Expected {
+    fn from(code: ErrorCode) -> Self {
+        Expected::Framework(code)
+    }
+}
+
+impl From<InstructionError> for Expected {
+    fn from(error: InstructionError) -> Self {
+        Expected::Runtime(error)
+    }
+}
+
+/// `code`, or success when `vulnerable` leaves the constraint out of the build
+fn unless_vulnerable(vulnerable: bool, code: ErrorCode) -> Expected {
+    if vulnerable {
+        Expected::Passes
+    } else {
+        Expected::Framework(code)
+    }
+}
+
+/// Addresses a violation can pass in place of the real account
+///
+/// Vault instructions run against vault A of a two-vault environment; the
+/// signer holds accounts in both vaults. `initialize` only has `fresh`.
+#[derive(Default)]
+struct Substitutes {
+    /// An address with no account
+    fresh: Pubkey,
+    protocol_state: Pubkey,
+    vault: Pubkey,
+    underlying_mint: Pubkey,
+    other_vault: Pubkey,
+    other_mint: Pubkey,
+    other_vault_token_account: Pubkey,
+    /// The signer's own accounts, for the underlying of either vault
+    own_underlying: Pubkey,
+    own_other_underlying: Pubkey,
+    /// Another depositor's accounts in vault A
+    stranger_underlying: Pubkey,
+    stranger_shares: Pubkey,
+}
+
+/// An instruction that succeeds as built, and what is needed to break it
+struct Case {
+    env: FuzzTestEnv,
+    ix: Instruction,
+    /// Keypairs of the accounts the instruction has signing
+    keypairs: Vec<Keypair>,
+    /// Pays the fee, so no account of the instruction has to sign for it
+    relayer: Keypair,
+    substitutes: Substitutes,
+}
+
+impl Case {
+    async fn new(
+        mut env: FuzzTestEnv,
+        ix: Instruction,
+        keypairs: Vec<Keypair>,
+        substitutes: Substitutes,
+    ) -> Self {
+        let relayer = new_keypair();
+        fund_account(&mut *env.context, &relayer.pubkey())
+            .await
+            .unwrap();
+        Self {
+            env,
+            ix,
+            keypairs,
+            relayer,
+            substitutes,
+        }
+    }
+
+    async fn account(&mut self, index: usize) -> (Pubkey, Account) {
+        let address = self.ix.accounts[index].pubkey;
+        let account = self
+            .env
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap();
+        (address, account)
+    }
+
+    async fn apply(&mut self, violation: Violation) {
+        match violation {
+            Violation::Untouched => {}
+            Violation::Substitute(index, substitute) => {
+                self.ix.accounts[index].pubkey = substitute(&self.substitutes);
+            }
+            Violation::ReadOnly(index) => self.ix.accounts[index].is_writable = false,
+            Violation::Unsigned(index) => self.ix.accounts[index].is_signer = false,
+            Violation::Copied(index) => {
+                let (_, account) = self.account(index).await;
+                let copy = new_address();
+                self.env.context.set_account(&copy, &account.into());
+                self.ix.accounts[index].pubkey = copy;
+            }
+            Violation::StoredBump(index) => {
+                let (address, mut account) = self.account(index).await;
+                let bump = account.data.last_mut().unwrap();
+                *bump = bump.wrapping_sub(1);
+                self.env.context.set_account(&address, &account.into());
+            }
+            Violation::RewriteVault(rewrite) => {
+                let (address, mut account) = self.account(0).await;
+                let mut vault = Vault::try_deserialize(&mut account.data.as_slice()).unwrap();
+                rewrite(&mut vault, &self.substitutes);
+                account.data.clear();
+                vault.try_serialize(&mut account.data).unwrap();
+                self.env.context.set_account(&address, &account.into());
+            }
+        }
+    }
+
+    /// Sends the instruction, signed by the relayer and whichever accounts
+    /// are still marked as signers
+    async fn send(&mut self) -> Result<(), BanksClientError> {
+        let mut signers = vec![&self.relayer];
+        for meta in self.ix.accounts.iter().filter(|meta| meta.is_signer) {
+            let keypair = self
+                .keypairs
+                .iter()
+                .find(|keypair| keypair.pubkey() == meta.pubkey)
+                .unwrap_or_else(|| panic!("no keypair for signer {}", meta.pubkey));
+            if !signers.iter().any(|signer| signer.pubkey() == meta.pubkey) {
+                signers.push(keypair);
+            }
+        }
+        Sender::default()
+            .send_instructions(&mut self.env, &[self.ix.clone()], &signers)
+            .await
+            .unwrap()
+    }
+
+    async fn check(mut self, violation: Violation, expected: Expected) {
+        self.apply(violation).await;
+        let result = self.send().await;
+        let error = match &expected {
+            Expected::Passes => {
+                result.expect("the instruction should succeed");
+                return;
+            }
+            Expected::Framework(code) => InstructionError::Custom(u32::from(*code)),
+            Expected::Runtime(error) => error.clone(),
+        };
+        match result {
+            Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+                0,
+                actual,
+            ))) if actual == error => {}
+            other => panic!("expected {expected:?}, got {other:?}"),
+        }
+    }
+}
+
+/// Two vaults with deposits in both and a stranger holding shares of vault A
+async fn vault_env() -> (FuzzTestEnv, TwoVaultSetup, Substitutes) {
+    let (mut env, setup) = setup_two_vault_environment(BALANCE, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let mut sender = Sender::default();
+    let stranger = create_and_fund_user(
+        &mut env.context,
+        &setup.a.underlying,
+        &setup.a.vault.share_mint,
+        BALANCE,
+    )
+    .await
+    .unwrap();
+    for (vault, user) in [
+        (&setup.a.vault, &setup.a.user),
+        (&setup.a.vault, &stranger),
+        (&setup.vault_b, &setup.user_b),
+    ] {
+        sender
+            .deposit_as(&mut env, vault, user, 10_000)
+            .await
+            .unwrap()
+            .expect("deposit");
+    }
+
+    let substitutes = Substitutes {
+        fresh: new_address(),
+        protocol_state: setup.a.protocol.protocol_state,
+        vault: setup.a.vault.vault,
+        underlying_mint: setup.a.vault.underlying_mint,
+        other_vault: setup.vault_b.vault,
+        other_mint: setup.vault_b.underlying_mint,
+        other_vault_token_account: setup.vault_b.vault_token_account,
+        own_underlying: setup.a.user.underlying_token_account,
+        own_other_underlying: setup.user_b.underlying_token_account,
+        stranger_underlying: stranger.underlying_token_account,
+        stranger_shares: stranger.share_token_account,
+    };
+    (env, setup, substitutes)
+}
+
+/// One `#[tokio::test]` per row, each breaking the `base()` case of the
+/// enclosing module
+macro_rules! violations {
+    ($($name:ident: $violation:expr => $expected:expr;)*) => {
+        $(
+            #[tokio::test]
+            async fn $name() {
+                base().await.check($violation, $expected.into()).await;
+            }
+        )*
+    };
+}
+
+mod initialize {
+    use super::Violation::*;
+    use super::*;
+
+    /// A fresh program, with separate owner and payer
+    async fn base() -> Case {
+        let mut env = setup_program_test().await;
+        let (owner, payer) = (new_keypair(), new_keypair());
+        for keypair in [&owner, &payer] {
+            fund_account(&mut *env.context, &keypair.pubkey())
+                .await
+                .unwrap();
+        }
+        let mut ix = initialize_ix(&env.program_id, &owner.pubkey());
+        ix.accounts[3].pubkey = payer.pubkey();
+        let substitutes = Substitutes {
+            fresh: new_address(),
+            ..Substitutes::default()
+        };
+        Case::new(env, ix, vec![owner, payer], substitutes).await
+    }
+
+    violations! {
+        accepts_the_instruction_as_built: Untouched => Expected::Passes;
+        protocol_state_seeds: Substitute(0, |s| s.fresh) => ErrorCode::ConstraintSeeds;
+        vault_authority_seeds: Substitute(1, |s| s.fresh) => ErrorCode::ConstraintSeeds;
+        owner_signer: Unsigned(2) => ErrorCode::AccountNotSigner;
+        payer_signer: Unsigned(3) => ErrorCode::AccountNotSigner;
+        system_program_id: Substitute(4, |_| spl_token::id()) => ErrorCode::InvalidProgramId;
+        admin_log_seeds: Substitute(5, |s| s.fresh) => ErrorCode::ConstraintSeeds;
+    }
+}
+
+mod initialize_vault {
+    use super::Violation::*;
+    use super::*;
+
+    /// A vault for a new underlying mint, paid for by the user of vault A
+    async fn base() -> Case {
+        let (mut env, setup, substitutes) = vault_env().await;
+        let underlying = setup_underlying_mint(&mut env.context, 6, TokenFlavor::SplToken)
+            .await
+            .unwrap();
+        let payer = setup.a.user.owner.insecure_clone();
+        let ix = initialize_vault_ix(&env.program_id, &underlying.mint, &payer.pubkey());
+        Case::new(env, ix, vec![payer], substitutes).await
+    }
+
+    violations! {
+        accepts_the_instruction_as_built: Untouched => Expected::Passes;
+        vault_seeds: Substitute(0, |s| s.fresh) => ErrorCode::ConstraintSeeds;
+        underlying_mint_owner: Substitute(1, |s| s.protocol_state) => ErrorCode::AccountOwnedByWrongProgram;
+        underlying_mint_initialized: Substitute(1, |s| s.fresh) => ErrorCode::AccountNotInitialized;
+        vault_token_account_seeds: Substitute(2, |s| s.fresh) => ErrorCode::ConstraintSeeds;
+        share_mint_seeds: Substitute(3, |s| s.fresh) => ErrorCode::ConstraintSeeds;
+        vault_authority_seeds: Copied(4) => ErrorCode::ConstraintSeeds;
+        vault_authority_bump: StoredBump(4) => ErrorCode::ConstraintSeeds;
+        vault_authority_owner: Substitute(4, |s| s.own_underlying) => ErrorCode::AccountOwnedByWrongProgram;
+        vault_authority_type: Substitute(4, |s| s.protocol_state) => ErrorCode::AccountDiscriminatorMismatch;
+        payer_signer: Unsigned(5) => ErrorCode::AccountNotSigner;
+        system_program_id: Substitute(6, |_| spl_token::id()) => ErrorCode::InvalidProgramId;
+        token_program_id: Substitute(7, |_| solana_sdk::system_program::ID) => ErrorCode::InvalidProgramId;
+        protocol_state_seeds: Copied(8) => ErrorCode::ConstraintSeeds;
+        protocol_state_bump: StoredBump(8) => ErrorCode::ConstraintSeeds;
+        activity_stats_seeds: Substitute(9, |s| s.fresh) => ErrorCode::ConstraintSeeds;
+    }
+}
+
+/// Rows shared by `deposit` and `redeem`, whose account lists match: vault,
+/// underlying mint, vault token account, share mint, vault authority, the
+/// user's underlying and share accounts, the user, the flag, the user's
+/// player record, the vault's activity stats, and the token program
+macro_rules! token_flow_violations {
+    () => {
+        violations! {
+            accepts_the_instruction_as_built: Untouched => Expected::Passes;
+            vault_seeds: Substitute(0, |s| s.other_vault) => ErrorCode::ConstraintSeeds;
+            vault_bump: StoredBump(0) => ErrorCode::ConstraintSeeds;
+            vault_owner: Substitute(0, |s| s.other_vault_token_account) => ErrorCode::AccountOwnedByWrongProgram;
+            vault_type: Substitute(0, |s| s.protocol_state) => ErrorCode::AccountDiscriminatorMismatch;
+            vault_initialized: Substitute(0, |s| s.fresh) => ErrorCode::AccountNotInitialized;
+            vault_has_one_underlying_mint: RewriteVault(|vault, s| vault.underlying_mint = s.other_mint) => ErrorCode::ConstraintHasOne;
+            vault_has_one_vault_token_account: Substitute(2, |s| s.other_vault_token_account) => ErrorCode::ConstraintHasOne;
+            underlying_mint_owner: Substitute(1, |s| s.protocol_state) => ErrorCode::AccountOwnedByWrongProgram;
+            vault_token_account_mut: ReadOnly(2) => ErrorCode::ConstraintMut;
+            vault_token_account_owner: Substitute(2, |s| s.vault) => ErrorCode::AccountOwnedByWrongProgram;
+            share_mint_mut: ReadOnly(3) => ErrorCode::ConstraintMut;
+            share_mint_owner: Substitute(3, |s| s.vault) => ErrorCode::AccountOwnedByWrongProgram;
+            vault_authority_seeds: Copied(4) => ErrorCode::ConstraintSeeds;
+            vault_authority_bump: StoredBump(4) => ErrorCode::ConstraintSeeds;
+            vault_authority_type: Substitute(4, |s| s.protocol_state) => ErrorCode::AccountDiscriminatorMismatch;
+            underlying_account_mut: ReadOnly(5) => ErrorCode::ConstraintMut;
+            underlying_account_mint: Substitute(5, |s| s.own_other_underlying) => ErrorCode::ConstraintTokenMint;
+            underlying_account_authority: Substitute(5, |s| s.stranger_underlying) => ErrorCode::ConstraintTokenOwner;
+            underlying_account_type: Substitute(5, |s| s.underlying_mint) => InstructionError::InvalidAccountData;
+            share_account_mut: ReadOnly(6) => ErrorCode::ConstraintMut;
+            share_account_mint: Substitute(6, |s| s.own_underlying) => ErrorCode::ConstraintTokenMint;
+            share_account_authority: Substitute(6, |s| s.stranger_shares) => ErrorCode::ConstraintTokenOwner;
+            user_signer: Unsigned(7) => ErrorCode::AccountNotSigner;
+            activity_stats_seeds: Copied(10) => ErrorCode::ConstraintSeeds;
+            activity_stats_mut: ReadOnly(10) => ErrorCode::ConstraintMut;
+            token_program_id: Substitute(11, |_| solana_sdk::system_program::ID) => ErrorCode::InvalidProgramId;
+        }
+    };
+}
+
+mod deposit {
+    use super::Violation::*;
+    use super::*;
+
+    /// The user of vault A deposits part of their balance
+    async fn base() -> Case {
+        let (env, setup, substitutes) = vault_env().await;
+        let ix = deposit_ix(&env.program_id, &setup.a.vault, &setup.a.user, 1_000);
+        Case::new(env, ix, vec![setup.a.user.owner], substitutes).await
+    }
+
+    token_flow_violations!();
+}
+
+mod redeem {
+    use super::Violation::*;
+    use super::*;
+
+    /// The user of vault A redeems part of their shares
+    async fn base() -> Case {
+        let (env, setup, substitutes) = vault_env().await;
+        let ix = redeem_ix(&env.program_id, &setup.a.vault, &setup.a.user, 1_000);
+        Case::new(env, ix, vec![setup.a.user.owner], substitutes).await
+    }
+
+    token_flow_violations!();
+}
+
+mod transfer_ownership {
+    use super::Violation::*;
+    use super::*;
+
+    /// The owner of the protocol hands it to a new address
+    async fn base() -> Case {
+        let (env, setup, substitutes) = vault_env().await;
+        let protocol = setup.a.protocol;
+        let ix = transfer_ownership_ix(&env.program_id, &protocol.owner, &new_address());
+        Case::new(env, ix, vec![protocol.owner_keypair], substitutes).await
+    }
+
+    violations! {
+        accepts_the_instruction_as_built: Untouched => Expected::Passes;
+        protocol_state_seeds: Copied(0) => ErrorCode::ConstraintSeeds;
+        protocol_state_bump: StoredBump(0) => ErrorCode::ConstraintSeeds;
+        protocol_state_mut: ReadOnly(0) => ErrorCode::ConstraintMut;
+        protocol_state_owner: Substitute(0, |s| s.own_underlying) => ErrorCode::AccountOwnedByWrongProgram;
+        protocol_state_type: Substitute(0, |s| s.vault) => ErrorCode::AccountDiscriminatorMismatch;
+        protocol_state_initialized: Substitute(0, |s| s.fresh) => ErrorCode::AccountNotInitialized;
+        current_owner_signer: Unsigned(1) => unless_vulnerable(vault_pda::VULN_OWNERSHIP, ErrorCode::ConstraintSigner);
+        admin_log_seeds: Copied(3) => ErrorCode::ConstraintSeeds;
+        admin_log_mut: ReadOnly(3) => ErrorCode::ConstraintMut;
+    }
+}
@@ -0,0 +1,82 @@
+//! Context reuse tests.
+//!
+//! With reuse enabled, an environment dropped at the end of an iteration is
+//! restored to its baseline by the next `setup_program_test` on the same
+//! thread. The restored environment must accept a full setup again, exactly
+//! like a fresh one.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test context_reuse
+//! ```
+
+use fuzz_helpers::*;
+use solana_sdk::{signature::Signer, transaction::Transaction};
+
+#[tokio::test]
+async fn reused_context_starts_from_baseline() {
+    enable_context_reuse();
+
+    let mut env = setup_program_test().await;
+    // A fresh context gets a fresh payer, so the payer identifies the context
+    let payer = env.context.payer.pubkey();
+    let payer_lamports = env.context.banks_client.get_balance(payer).await.unwrap();
+    setup_protocol(&mut env.context, &env.program_id)
+        .await
+        .expect("protocol setup on a fresh context");
+    drop(env);
+
+    let mut env = setup_program_test().await;
+    assert_eq!(env.context.payer.pubkey(), payer, "context was not reused");
+    assert_eq!(
+        env.context.banks_client.get_balance(payer).await.unwrap(),
+        payer_lamports,
+        "payer balance not restored"
+    );
+
+    let protocol_state = derive_protocol_state_pda(&env.program_id).0;
+    assert!(
+        env.context
+            .banks_client
+            .get_account(protocol_state)
+            .await
+            .unwrap()
+            .is_none(),
+        "protocol state survived the restore"
+    );
+
+    setup_protocol(&mut env.context, &env.program_id)
+        .await
+        .expect("protocol setup on a restored context");
+}
+
+#[tokio::test]
+async fn reused_context_runs_complete_setups() {
+    enable_context_reuse();
+
+    for _ in 0..3 {
+        let (mut env, setup) = setup_complete_environment(1_000_000, 6, TokenFlavor::SplToken)
+            .await
+            .unwrap();
+        let ix = deposit_ix(&env.program_id, &setup.vault, &setup.user, 1_000);
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&setup.user.owner.pubkey()),
+            &[&setup.user.owner],
+            env.context.last_blockhash,
+        );
+        env.context
+            .banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap();
+        assert_eq!(
+            get_token_balance(&mut env.context, &setup.vault.vault_token_account)
+                .await
+                .unwrap(),
+            1_000
+        );
+    }
+}
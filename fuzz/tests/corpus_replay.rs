@@ -0,0 +1,80 @@
+//! Corpus replay regression tests.
+//!
+//! Every input under `fuzz/corpus/<target>/` is run once through the body of
+//! the fuzz target that produced it, decoded exactly as the engines decode
+//! it. Inputs that once found a bug keep guarding against it without
+//! re-running the fuzzer. Corpus directories are named after the fuzz binary
+//! (`fuzz_deposit_libfuzzer`, `fuzz_redeem`, ...).
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test corpus_replay -- --nocapture
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use fuzz_helpers::fuzz_core::corpus::{replay, target_module};
+
+/// Regular files in `dir`, in a stable order
+fn sorted_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn replay_corpus() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus");
+    let Ok(entries) = fs::read_dir(&corpus) else {
+        println!("no corpus at {}", corpus.display());
+        return;
+    };
+
+    let mut targets: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    targets.sort();
+
+    for dir in targets {
+        let name = dir.file_name().unwrap().to_string_lossy().into_owned();
+        let module = target_module(&name);
+        let files = sorted_files(&dir);
+        println!("{}: {} inputs through {}", name, files.len(), module);
+
+        for path in files {
+            let data = fs::read(&path).unwrap();
+            // Undecodable inputs and iterations that bail out with an error are
+            // skipped by the engines too; only panics fail the replay
+            if let Err(e) = replay(module, &data) {
+                if e.to_string().starts_with("Unknown fuzz target") {
+                    panic!("{} has no fuzz body", name);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn corpus_directories_map_to_bodies() {
+    assert_eq!(target_module("fuzz_deposit_libfuzzer"), "deposit");
+    assert_eq!(target_module("fuzz_redeem_afl"), "redeem");
+    assert_eq!(target_module("fuzz_kernel"), "kernel");
+    assert_eq!(
+        target_module("fuzz_transfer_ownership_libfuzzer"),
+        "authorization"
+    );
+    assert_eq!(target_module("fuzz_all_instructions"), "sequence");
+
+    // The pure kernel needs no program build, so any bytes replay
+    replay("kernel", &[0xff; 32]).unwrap();
+}
@@ -0,0 +1,63 @@
+//! Decoding of custom error codes into typed failures.
+//!
+//! Pure: no program build is needed.
+//!
+//! ```bash
+//! cargo test -p vault-pda-fuzz --test errors
+//! ```
+
+use fuzz_helpers::*;
+use solana_program_test::BanksClientError;
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+use spl_token::error::TokenError;
+use vault_pda::instructions::{DepositError, RedeemError};
+
+fn failed_with(error: InstructionError) -> BanksClientError {
+    BanksClientError::TransactionError(TransactionError::InstructionError(0, error))
+}
+
+#[test]
+fn program_errors_decode_to_their_variant() {
+    for &variant in DepositError::VARIANTS {
+        let error = failed_with(InstructionError::Custom(variant.into()));
+        match decode_error::<DepositError>(&error) {
+            TxFailure::Program(decoded) => assert_eq!(decoded.name(), variant.name()),
+            other => panic!("{:?} decoded as {:?}", variant, other),
+        }
+    }
+}
+
+#[test]
+fn error_enums_are_decoded_per_instruction() {
+    // Every enum starts at ERROR_CODE_OFFSET, so the same code means different things
+    let error = failed_with(InstructionError::Custom(RedeemError::MathOverflow.into()));
+    assert!(matches!(
+        decode_error::<RedeemError>(&error),
+        TxFailure::Program(RedeemError::MathOverflow)
+    ));
+    assert!(matches!(
+        decode_error::<DepositError>(&error),
+        TxFailure::Program(DepositError::MathOverflow)
+    ));
+}
+
+#[test]
+fn token_and_framework_errors_are_told_apart() {
+    let error = failed_with(InstructionError::Custom(
+        TokenError::InsufficientFunds as u32,
+    ));
+    assert!(matches!(
+        decode_error::<DepositError>(&error),
+        TxFailure::Token(TokenError::InsufficientFunds)
+    ));
+
+    // ConstraintSeeds
+    let error = failed_with(InstructionError::Custom(2006));
+    assert!(matches!(
+        decode_error::<DepositError>(&error),
+        TxFailure::Framework(2006)
+    ));
+
+    let error = failed_with(InstructionError::ProgramFailedToComplete);
+    assert!(decode_error::<DepositError>(&error).is_panic());
+}
@@ -0,0 +1,177 @@
+//! CTF flag tests.
+//!
+//! Taking over the protocol captures the flag exactly once, while an honest
+//! round trip through the vault, or tokens sent to the player, never does.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test flag
+//! ```
+
+use fuzz_helpers::fuzz_core::sequence::Sender;
+use fuzz_helpers::*;
+use solana_sdk::signature::Signer;
+use vault_pda::instructions::CaptureFlagError;
+use vault_pda::state::WinCondition;
+
+#[tokio::test]
+async fn ownership_takeover_captures_the_flag() {
+    let (mut env, setup) = setup_complete_environment(10_000, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let attacker = Attacker::new(&mut env.context, &setup.underlying, &setup.vault, 0, 0)
+        .await
+        .unwrap();
+    let mut sender = Sender::default();
+    let program_id = env.program_id;
+
+    let ix = initialize_flag_ix(&program_id, &setup.protocol.owner, &setup.vault);
+    sender
+        .send(&mut env, ix, &setup.protocol.owner_keypair)
+        .await
+        .unwrap()
+        .expect("initialize_flag");
+    let ix = register_player_ix(&program_id, &attacker.pubkey(), &setup.vault);
+    attacker
+        .send(&mut env, &mut sender, ix)
+        .await
+        .unwrap()
+        .expect("register_player");
+
+    // The owner never signs the takeover
+    let mut takeover =
+        transfer_ownership_ix(&program_id, &setup.protocol.owner, &attacker.pubkey());
+    takeover.accounts[1].is_signer = false;
+    let takeover = attacker
+        .send(&mut env, &mut sender, takeover)
+        .await
+        .unwrap();
+    assert_eq!(takeover.is_ok(), vault_pda::VULN_OWNERSHIP);
+
+    let capture = capture_flag_ix(&program_id, &attacker.pubkey(), &setup.vault);
+    let result = attacker
+        .send(&mut env, &mut sender, capture.clone())
+        .await
+        .unwrap();
+    let flag_address = derive_flag_pda(&program_id, &setup.vault.vault).0;
+    let flag = get_flag(&mut env.context, &flag_address).await.unwrap();
+
+    if !vault_pda::VULN_OWNERSHIP {
+        let err = result.expect_err("capture without an exploit");
+        assert!(matches!(
+            decode_error::<CaptureFlagError>(&err),
+            TxFailure::Program(CaptureFlagError::NotExploited)
+        ));
+        assert!(!flag.is_captured());
+        return;
+    }
+
+    result.expect("capture_flag");
+    assert_eq!(flag.captor, attacker.pubkey());
+    assert_eq!(flag.condition, Some(WinCondition::OwnsProtocol));
+
+    let err = attacker
+        .send(&mut env, &mut sender, capture)
+        .await
+        .unwrap()
+        .expect_err("second capture");
+    assert!(matches!(
+        decode_error::<CaptureFlagError>(&err),
+        TxFailure::Program(CaptureFlagError::AlreadyCaptured)
+    ));
+}
+
+#[tokio::test]
+async fn honest_round_trip_does_not_capture() {
+    let (mut env, setup) = setup_complete_environment(10_000, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let mut sender = Sender::default();
+    let program_id = env.program_id;
+    let player = &setup.user;
+
+    let ix = initialize_flag_ix(&program_id, &setup.protocol.owner, &setup.vault);
+    sender
+        .send(&mut env, ix, &setup.protocol.owner_keypair)
+        .await
+        .unwrap()
+        .expect("initialize_flag");
+    let ix = register_player_ix(&program_id, &player.owner.pubkey(), &setup.vault);
+    sender
+        .send(&mut env, ix, &player.owner)
+        .await
+        .unwrap()
+        .expect("register_player");
+
+    sender
+        .deposit_as(&mut env, &setup.vault, player, 10_000)
+        .await
+        .unwrap()
+        .expect("deposit");
+    let shares = get_token_balance(&mut env.context, &player.share_token_account)
+        .await
+        .unwrap();
+    sender
+        .redeem_as(&mut env, &setup.vault, player, shares)
+        .await
+        .unwrap()
+        .expect("redeem");
+
+    let ix = capture_flag_ix(&program_id, &player.owner.pubkey(), &setup.vault);
+    let err = sender
+        .send(&mut env, ix, &player.owner)
+        .await
+        .unwrap()
+        .expect_err("capture after a round trip");
+    assert!(matches!(
+        decode_error::<CaptureFlagError>(&err),
+        TxFailure::Program(CaptureFlagError::NotExploited)
+    ));
+}
+
+#[tokio::test]
+async fn tokens_sent_to_the_player_do_not_capture() {
+    let (mut env, setup) = setup_complete_environment(10_000, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let mut sender = Sender::default();
+    let program_id = env.program_id;
+    let player = &setup.user;
+
+    let ix = initialize_flag_ix(&program_id, &setup.protocol.owner, &setup.vault);
+    sender
+        .send(&mut env, ix, &setup.protocol.owner_keypair)
+        .await
+        .unwrap()
+        .expect("initialize_flag");
+    let ix = register_player_ix(&program_id, &player.owner.pubkey(), &setup.vault);
+    sender
+        .send(&mut env, ix, &player.owner)
+        .await
+        .unwrap()
+        .expect("register_player");
+
+    // Anyone can credit the player's wallet, which is no exploit
+    mint_tokens_to_user(
+        &mut env.context,
+        &setup.underlying.mint,
+        &setup.underlying.mint_authority,
+        &player.underlying_token_account,
+        1_000_000,
+    )
+    .await
+    .unwrap();
+
+    let ix = capture_flag_ix(&program_id, &player.owner.pubkey(), &setup.vault);
+    let err = sender
+        .send(&mut env, ix, &player.owner)
+        .await
+        .unwrap()
+        .expect_err("capture after a transfer in");
+    assert!(matches!(
+        decode_error::<CaptureFlagError>(&err),
+        TxFailure::Program(CaptureFlagError::NotExploited)
+    ));
+}
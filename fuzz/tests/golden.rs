@@ -0,0 +1,370 @@
+//! Golden snapshot tests of account state.
+//!
+//! A fixed scenario runs every core instruction once and, after each step,
+//! renders the accounts it touched (program state, mints, token accounts) to
+//! text and compares it with the fixture committed under `tests/golden/`. A
+//! layout or accounting change shows up as a diff against the fixture.
+//!
+//! Addresses are rendered by name (`vault.share_mint`, `alice.shares`, ...)
+//! and bumps as `canonical` when they match the canonical bump, so fixtures do
+//! not depend on the random mints and owners of a run. The address that owns
+//! a vault's tokens is always `token_authority`, so the challenge build and
+//! the reference fix share the same fixtures; the amounts are chosen to price
+//! the same with and without the virtual offset.
+//!
+//! After an intended change, rewrite the fixtures and review the diff:
+//!
+//! ```bash
+//! GOLDEN_UPDATE=1 SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test golden
+//! ```
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test golden
+//! ```
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use fuzz_helpers::backend::get_packed_account;
+use fuzz_helpers::fuzz_core::sequence::Sender;
+use fuzz_helpers::*;
+use solana_sdk::{program_option::COption, program_pack::Pack, pubkey::Pubkey, signature::Signer};
+use spl_token::state::{Account as TokenAccount, Mint};
+use vault_pda_client::{try_deserialize_any, AnyAccount};
+
+const BALANCE: u64 = 1_000_000;
+
+/// Fixtures, embedded so a challenge clone compiling this file finds them too
+const FIXTURES: &[(&str, &str)] = &[
+    ("initialize", include_str!("golden/initialize.txt")),
+    (
+        "initialize_vault",
+        include_str!("golden/initialize_vault.txt"),
+    ),
+    ("deposit", include_str!("golden/deposit.txt")),
+    ("redeem", include_str!("golden/redeem.txt")),
+    (
+        "transfer_ownership",
+        include_str!("golden/transfer_ownership.txt"),
+    ),
+];
+
+/// Renders accounts with every known address replaced by its name
+struct Golden {
+    program_id: Pubkey,
+    /// Earlier names win, so `token_authority` hides whichever account it is
+    names: Vec<(Pubkey, String)>,
+}
+
+impl Golden {
+    fn new(program_id: Pubkey) -> Self {
+        let mut golden = Self {
+            program_id,
+            names: Vec::new(),
+        };
+        golden.name(&program_id, "program");
+        golden.name(&spl_token::id(), "spl_token");
+        golden
+    }
+
+    fn name(&mut self, address: &Pubkey, name: &str) {
+        self.names.push((*address, name.to_string()));
+    }
+
+    fn address(&self, address: &Pubkey) -> String {
+        self.names
+            .iter()
+            .find(|(known, _)| known == address)
+            .map(|(_, name)| name.clone())
+            .unwrap_or_else(|| address.to_string())
+    }
+
+    fn option(&self, address: &COption<Pubkey>) -> String {
+        match address {
+            COption::Some(address) => self.address(address),
+            COption::None => "none".to_string(),
+        }
+    }
+
+    fn bump(bump: u8, canonical: u8) -> String {
+        if bump == canonical {
+            "canonical".to_string()
+        } else {
+            bump.to_string()
+        }
+    }
+
+    /// The accounts at `addresses`, in order, each under its name
+    async fn render(&self, env: &mut FuzzTestEnv, addresses: &[(&str, Pubkey)]) -> String {
+        let mut out = String::new();
+        for (name, address) in addresses {
+            let account = env
+                .context
+                .banks_client
+                .get_account(*address)
+                .await
+                .unwrap()
+                .unwrap_or_else(|| panic!("{name} does not exist"));
+            let fields = if account.owner == self.program_id {
+                self.program_account(&account.data)
+            } else if account.owner == spl_token::id() {
+                self.token_account(env, address, account.data.len()).await
+            } else {
+                panic!("{name} is owned by {}", account.owner);
+            };
+
+            writeln!(
+                out,
+                "{name}: {}, {} bytes, {} lamports",
+                self.address(&account.owner),
+                account.data.len(),
+                account.lamports
+            )
+            .unwrap();
+            for (field, value) in fields {
+                writeln!(out, "    {field}: {value}").unwrap();
+            }
+        }
+        out
+    }
+
+    fn program_account(&self, data: &[u8]) -> Vec<(&'static str, String)> {
+        let program_id = &self.program_id;
+        match try_deserialize_any(data).unwrap() {
+            AnyAccount::ProtocolState(state) => vec![
+                ("type", "ProtocolState".to_string()),
+                ("owner", self.address(&state.owner)),
+                (
+                    "bump",
+                    Self::bump(state.bump, derive_protocol_state_pda(program_id).1),
+                ),
+            ],
+            AnyAccount::VaultAuthority(authority) => vec![
+                ("type", "VaultAuthority".to_string()),
+                (
+                    "bump",
+                    Self::bump(authority.bump, derive_vault_authority_pda(program_id).1),
+                ),
+            ],
+            AnyAccount::Vault(vault) => vec![
+                ("type", "Vault".to_string()),
+                ("share_mint", self.address(&vault.share_mint)),
+                ("underlying_mint", self.address(&vault.underlying_mint)),
+                (
+                    "vault_token_account",
+                    self.address(&vault.vault_token_account),
+                ),
+                ("creator", self.address(&vault.creator)),
+                (
+                    "bump",
+                    Self::bump(
+                        vault.bump,
+                        derive_vault_pda(program_id, &vault.underlying_mint).1,
+                    ),
+                ),
+            ],
+            other => panic!("no golden rendering for {}", other.account_type().name()),
+        }
+    }
+
+    async fn token_account(
+        &self,
+        env: &mut FuzzTestEnv,
+        address: &Pubkey,
+        len: usize,
+    ) -> Vec<(&'static str, String)> {
+        if len == Mint::LEN {
+            let mint: Mint = get_packed_account(&mut *env.context, address)
+                .await
+                .unwrap();
+            return vec![
+                ("type", "Mint".to_string()),
+                ("mint_authority", self.option(&mint.mint_authority)),
+                ("supply", mint.supply.to_string()),
+                ("decimals", mint.decimals.to_string()),
+                ("is_initialized", mint.is_initialized.to_string()),
+                ("freeze_authority", self.option(&mint.freeze_authority)),
+            ];
+        }
+
+        let account: TokenAccount = get_packed_account(&mut *env.context, address)
+            .await
+            .unwrap();
+        vec![
+            ("type", "Account".to_string()),
+            ("mint", self.address(&account.mint)),
+            ("owner", self.address(&account.owner)),
+            ("amount", account.amount.to_string()),
+            ("delegate", self.option(&account.delegate)),
+            ("state", format!("{:?}", account.state)),
+            ("is_native", format!("{:?}", account.is_native)),
+            ("delegated_amount", account.delegated_amount.to_string()),
+            ("close_authority", self.option(&account.close_authority)),
+        ]
+    }
+}
+
+/// Compares `actual` with the fixture for `step`, or rewrites the fixture
+/// under `GOLDEN_UPDATE`
+fn check(step: &str, actual: &str) {
+    if std::env::var_os("GOLDEN_UPDATE").is_some() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(format!("{step}.txt"));
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = FIXTURES
+        .iter()
+        .find(|(name, _)| *name == step)
+        .map(|(_, fixture)| *fixture)
+        .unwrap_or_else(|| panic!("no fixture for {step}"));
+    if expected == actual {
+        return;
+    }
+
+    let mut diff = String::new();
+    let (expected_lines, actual_lines) = (
+        expected.lines().collect::<Vec<_>>(),
+        actual.lines().collect::<Vec<_>>(),
+    );
+    for index in 0..expected_lines.len().max(actual_lines.len()) {
+        let (old, new) = (expected_lines.get(index), actual_lines.get(index));
+        if old == new {
+            continue;
+        }
+        if let Some(old) = old {
+            writeln!(diff, "-{old}").unwrap();
+        }
+        if let Some(new) = new {
+            writeln!(diff, "+{new}").unwrap();
+        }
+    }
+    panic!(
+        "{step} no longer matches tests/golden/{step}.txt (GOLDEN_UPDATE=1 rewrites it):\n{diff}"
+    );
+}
+
+#[tokio::test]
+async fn account_state_matches_the_fixtures() {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    let mut golden = Golden::new(program_id);
+
+    // initialize
+    let protocol = setup_protocol(&mut env.context, &program_id).await.unwrap();
+    golden.name(&protocol.owner, "protocol.owner");
+    let accounts = [
+        ("protocol_state", protocol.protocol_state),
+        ("vault_authority", protocol.vault_authority),
+    ];
+    check("initialize", &golden.render(&mut env, &accounts).await);
+
+    // initialize_vault
+    let underlying = setup_underlying_mint(&mut env.context, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let vault = setup_vault(
+        &mut env.context,
+        &program_id,
+        &protocol.vault_authority,
+        &underlying.mint,
+        &protocol.owner_keypair,
+        TokenFlavor::SplToken,
+    )
+    .await
+    .unwrap();
+    golden.name(
+        &vault_pda::state::token_authority(&vault.vault),
+        "token_authority",
+    );
+    golden.name(&protocol.vault_authority, "vault_authority");
+    golden.name(&vault.vault, "vault");
+    golden.name(&vault.share_mint, "vault.share_mint");
+    golden.name(&vault.vault_token_account, "vault.vault_token_account");
+    golden.name(&underlying.mint, "underlying.mint");
+    golden.name(
+        &underlying.mint_authority.pubkey(),
+        "underlying.mint_authority",
+    );
+    let vault_accounts = [
+        ("vault", vault.vault),
+        ("vault.share_mint", vault.share_mint),
+        ("vault.vault_token_account", vault.vault_token_account),
+    ];
+    check(
+        "initialize_vault",
+        &golden.render(&mut env, &vault_accounts).await,
+    );
+
+    let mut users = Vec::new();
+    for name in ["alice", "bob"] {
+        let user = create_and_fund_user(&mut env.context, &underlying, &vault.share_mint, BALANCE)
+            .await
+            .unwrap();
+        golden.name(&user.owner.pubkey(), name);
+        golden.name(
+            &user.underlying_token_account,
+            &format!("{name}.underlying"),
+        );
+        golden.name(&user.share_token_account, &format!("{name}.shares"));
+        users.push(user);
+    }
+    let (alice, bob) = (&users[0], &users[1]);
+    let token_accounts = [
+        ("underlying.mint", underlying.mint),
+        ("vault.share_mint", vault.share_mint),
+        ("vault.vault_token_account", vault.vault_token_account),
+        ("alice.underlying", alice.underlying_token_account),
+        ("alice.shares", alice.share_token_account),
+        ("bob.underlying", bob.underlying_token_account),
+        ("bob.shares", bob.share_token_account),
+    ];
+
+    // deposit: 10,000 at 1:1, then 7,000 after 2,500 of yield for 5,600 shares
+    sender
+        .deposit_as(&mut env, &vault, alice, 10_000)
+        .await
+        .unwrap()
+        .expect("alice deposits");
+    donate_to_vault(&mut env.context, &underlying, &vault, 2_500)
+        .await
+        .unwrap();
+    sender
+        .deposit_as(&mut env, &vault, bob, 7_000)
+        .await
+        .unwrap()
+        .expect("bob deposits");
+    check("deposit", &golden.render(&mut env, &token_accounts).await);
+
+    // redeem: 4,001 of 15,600 shares against 19,500 tokens pays 5,001
+    sender
+        .redeem_as(&mut env, &vault, alice, 4_001)
+        .await
+        .unwrap()
+        .expect("alice redeems");
+    check("redeem", &golden.render(&mut env, &token_accounts).await);
+
+    // transfer_ownership
+    let new_owner = new_keypair();
+    fund_account(&mut *env.context, &new_owner.pubkey())
+        .await
+        .unwrap();
+    golden.name(&new_owner.pubkey(), "new_owner");
+    let ix = transfer_ownership_ix(&program_id, &protocol.owner, &new_owner.pubkey());
+    sender
+        .send(&mut env, ix, &protocol.owner_keypair)
+        .await
+        .unwrap()
+        .expect("transfer_ownership");
+    let accounts = [("protocol_state", protocol.protocol_state)];
+    check(
+        "transfer_ownership",
+        &golden.render(&mut env, &accounts).await,
+    );
+}
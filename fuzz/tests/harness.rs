@@ -0,0 +1,119 @@
+//! Declarative target tests.
+//!
+//! A target declared with `vault_fuzz_target!` must run its scenario on the
+//! requested setup, with the exchange invariants and hooks checked after
+//! every successful operation.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test harness
+//! ```
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use arbitrary::Arbitrary;
+use fuzz_helpers::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Arbitrary, Serialize, Deserialize)]
+struct RoundTripInput {
+    flavor: TokenFlavor,
+    deposits: Vec<(u8, u32)>,
+    donation: u32,
+}
+
+thread_local! {
+    static HOOK_CALLS: Rc<Cell<usize>> = Rc::new(Cell::new(0));
+}
+
+vault_fuzz_target! {
+    /// Deposits the fuzzed amounts, donates, then redeems everything
+    async fn fuzz_round_trip_once(input: RoundTripInput, harness) {
+        setup: FuzzEnvBuilder::new().flavor(input.flavor).users(3).balance(1 << 40),
+        scenario: {
+            let calls = HOOK_CALLS.with(Rc::clone);
+            harness.on_success(move |snapshot| {
+                calls.set(calls.get() + 1);
+                assert!(snapshot.share_supply == 0 || snapshot.vault_balance > 0);
+            });
+
+            for (user, amount) in input.deposits {
+                let _ = harness.deposit(user as usize, amount as u64).await?;
+            }
+            harness.donate(input.donation as u64).await?;
+            for user in 0..3 {
+                let shares = harness.balances(user).await?.user_shares;
+                if shares > 0 {
+                    harness.redeem(user, shares).await?.expect("redeem all shares");
+                }
+            }
+            assert_eq!(harness.balances(0).await?.share_supply, 0);
+        }
+    }
+}
+
+#[tokio::test]
+async fn declared_target_runs_its_scenario() {
+    for flavor in TokenFlavor::ALL {
+        HOOK_CALLS.with(|calls| calls.set(0));
+        let input = RoundTripInput {
+            flavor,
+            deposits: vec![(0, 1_000), (1, 250_000), (5, 7), (2, 0)],
+            donation: 10_000,
+        };
+        fuzz_round_trip_once(input).await.unwrap();
+
+        // Three deposits, the donation and three redeems succeed
+        assert_eq!(HOOK_CALLS.with(|calls| calls.get()), 7, "{:?}", flavor);
+    }
+}
+
+#[tokio::test]
+async fn harness_reports_rejected_operations() {
+    let mut harness = fuzz_core::harness::Harness::new(FuzzEnvBuilder::new().balance(100))
+        .await
+        .unwrap();
+
+    let failure = harness.deposit(0, 0).await.unwrap().unwrap_err();
+    assert!(matches!(failure, TxFailure::Program(_)), "{:?}", failure);
+
+    let minted = harness.deposit(0, 100).await.unwrap().expect("deposit");
+    assert_eq!(minted, harness.balances(0).await.unwrap().user_shares);
+    assert!(harness.redeem(0, minted + 1).await.unwrap().is_err());
+}
+
+#[tokio::test]
+async fn harness_counts_checked_invariants() {
+    let checks = |invariant: &str| {
+        fuzz_core::metrics::invariants()[invariant]
+            .as_u64()
+            .unwrap_or(0)
+    };
+    let deposits = checks("deposit_exchange");
+    let redeems = checks("redeem_exchange");
+
+    let mut harness = fuzz_core::harness::Harness::new(FuzzEnvBuilder::new().balance(100))
+        .await
+        .unwrap();
+    let minted = harness.deposit(0, 100).await.unwrap().expect("deposit");
+    harness.redeem(0, minted).await.unwrap().expect("redeem");
+
+    // Other tests in this binary may check invariants concurrently
+    assert!(checks("deposit_exchange") > deposits);
+    assert!(checks("redeem_exchange") > redeems);
+
+    let textfile = fuzz_core::metrics::prometheus(&fuzz_core::scenarios::summary());
+    assert!(
+        textfile.contains("invariant=\"deposit_exchange\""),
+        "{}",
+        textfile
+    );
+    assert!(
+        textfile.contains("vault_fuzz_execs_per_second"),
+        "{}",
+        textfile
+    );
+}
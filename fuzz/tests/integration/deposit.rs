@@ -0,0 +1,229 @@
+//! `deposit`: every `DepositError`, the account constraints and token
+//! failures.
+
+use vault_math::compute_shares;
+use vault_pda::instructions::DepositError;
+
+use super::*;
+
+#[tokio::test]
+async fn deposits_at_the_share_price() {
+    for flavor in TokenFlavor::ALL {
+        let (mut env, setup, mut sender) = vault_env(flavor).await;
+        let (vault, user) = (&setup.vault, &setup.user);
+
+        // The first deposit mints one share per token
+        sender
+            .deposit_as(&mut env, vault, user, 10_000)
+            .await
+            .unwrap()
+            .expect("first deposit");
+        let after_first = get_snapshot(&mut env.context, vault, user).await.unwrap();
+        assert_eq!(after_first.user_shares, 10_000, "{flavor:?}");
+        assert_eq!(after_first.share_supply, 10_000);
+        assert_eq!(after_first.vault_balance, 10_000);
+        assert_eq!(after_first.user_balance, BALANCE - 10_000);
+
+        // Later ones are priced off the vault's balance, yield included
+        donate_to_vault(&mut env.context, &setup.underlying, vault, 2_500)
+            .await
+            .unwrap();
+        let second = create_and_fund_user(
+            &mut env.context,
+            &setup.underlying,
+            &vault.share_mint,
+            BALANCE,
+        )
+        .await
+        .unwrap();
+        let expected = compute_shares(7_000, 10_000, 12_500).unwrap();
+        sender
+            .deposit_as(&mut env, vault, &second, 7_000)
+            .await
+            .unwrap()
+            .expect("second deposit");
+        let after = get_snapshot(&mut env.context, vault, &second)
+            .await
+            .unwrap();
+        assert_eq!(after.user_shares, expected, "{flavor:?}");
+        assert_eq!(after.share_supply, 10_000 + expected);
+        assert_eq!(after.vault_balance, 19_500);
+        assert_eq!(after.user_balance, BALANCE - 7_000);
+    }
+}
+
+#[tokio::test]
+async fn zero_amount_is_rejected() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+
+    let result = sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, 0)
+        .await
+        .unwrap();
+    assert_program_error(result, DepositError::InvalidAmount);
+}
+
+#[tokio::test]
+async fn shares_outstanding_against_an_empty_vault_are_rejected() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, 1_000)
+        .await
+        .unwrap()
+        .expect("deposit");
+    set_token_balance(&mut env, &setup.vault.vault_token_account, 0).await;
+
+    let result = sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, 1_000)
+        .await
+        .unwrap();
+    assert_program_error(result, DepositError::InvalidVaultState);
+}
+
+#[tokio::test]
+async fn share_overflow_is_rejected() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, 1)
+        .await
+        .unwrap()
+        .expect("deposit");
+    set_mint_supply(&mut env, &setup.vault.share_mint, u64::MAX).await;
+
+    // BALANCE tokens would be worth more than u64::MAX shares
+    let result = sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, BALANCE - 1)
+        .await
+        .unwrap();
+    assert_program_error(result, DepositError::MathOverflow);
+}
+
+#[tokio::test]
+async fn deposit_worth_less_than_a_share_is_rejected() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, 1)
+        .await
+        .unwrap()
+        .expect("deposit");
+    donate_to_vault(&mut env.context, &setup.underlying, &setup.vault, 10)
+        .await
+        .unwrap();
+    let before = get_snapshot(&mut env.context, &setup.vault, &setup.user)
+        .await
+        .unwrap();
+
+    let result = sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, 1)
+        .await
+        .unwrap();
+    assert_program_error(result, DepositError::InsufficientShares);
+    let after = get_snapshot(&mut env.context, &setup.vault, &setup.user)
+        .await
+        .unwrap();
+    assert_eq!(before, after, "a rejected deposit moved tokens");
+}
+
+#[tokio::test]
+async fn share_mint_must_be_the_vaults() {
+    let (mut env, setup) = setup_two_vault_environment(BALANCE, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let mut sender = Sender::default();
+
+    // Vault A's accounts, but vault B's share mint and the user's account for it
+    let vault = VaultAccounts {
+        share_mint: setup.vault_b.share_mint,
+        ..setup.a.vault.clone()
+    };
+    let user = UserAccounts {
+        share_token_account: setup.user_b.share_token_account,
+        ..setup.a.user.clone()
+    };
+    let result = sender
+        .deposit_as(&mut env, &vault, &user, 1_000)
+        .await
+        .unwrap();
+    assert_program_error(result, DepositError::InvalidShareMint);
+}
+
+#[tokio::test]
+async fn vault_token_account_must_be_the_vaults() {
+    let (mut env, setup) = setup_two_vault_environment(BALANCE, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let mut sender = Sender::default();
+
+    let vault = VaultAccounts {
+        vault_token_account: setup.vault_b.vault_token_account,
+        ..setup.a.vault.clone()
+    };
+    let result = sender
+        .deposit_as(&mut env, &vault, &setup.a.user, 1_000)
+        .await
+        .unwrap();
+    assert_framework_error(result, ErrorCode::ConstraintHasOne);
+}
+
+#[tokio::test]
+async fn depositor_must_own_the_source_account() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    let victim = create_and_fund_user(
+        &mut env.context,
+        &setup.underlying,
+        &setup.vault.share_mint,
+        BALANCE,
+    )
+    .await
+    .unwrap();
+
+    let user = UserAccounts {
+        underlying_token_account: victim.underlying_token_account,
+        ..setup.user.clone()
+    };
+    let result = sender
+        .deposit_as(&mut env, &setup.vault, &user, 1_000)
+        .await
+        .unwrap();
+    assert_framework_error(result, ErrorCode::ConstraintTokenOwner);
+}
+
+#[tokio::test]
+async fn source_account_must_hold_the_underlying() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+
+    // The depositor's share account in place of their underlying account
+    let user = UserAccounts {
+        underlying_token_account: setup.user.share_token_account,
+        ..setup.user.clone()
+    };
+    let result = sender
+        .deposit_as(&mut env, &setup.vault, &user, 1_000)
+        .await
+        .unwrap();
+    assert_framework_error(result, ErrorCode::ConstraintTokenMint);
+}
+
+#[tokio::test]
+async fn depositor_must_sign() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    let relayer = funded_keypair(&mut env).await;
+
+    let mut ix = deposit_ix(&env.program_id, &setup.vault, &setup.user, 1_000);
+    ix.accounts[7].is_signer = false;
+    let result = sender.send(&mut env, ix, &relayer).await.unwrap();
+    assert_framework_error(result, ErrorCode::AccountNotSigner);
+}
+
+#[tokio::test]
+async fn deposit_beyond_the_balance_fails_in_the_token_program() {
+    for flavor in TokenFlavor::ALL {
+        let (mut env, setup, mut sender) = vault_env(flavor).await;
+
+        let result = sender
+            .deposit_as(&mut env, &setup.vault, &setup.user, BALANCE + 1)
+            .await
+            .unwrap();
+        assert_token_error::<DepositError>(result, spl_token::error::TokenError::InsufficientFunds);
+    }
+}
@@ -0,0 +1,96 @@
+//! `initialize`. Repeated and out-of-order calls are covered by
+//! `tests/reinitialization.rs`.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_sdk::instruction::Instruction;
+use vault_pda::state::AdminAction;
+use vault_pda_client::{try_deserialize_any, AnyAccount};
+
+use super::*;
+
+/// `initialize` with `owner` and a separate `payer`
+fn initialize_paid_by(program_id: &Pubkey, owner: &Pubkey, payer: &Pubkey) -> Instruction {
+    let accounts = vault_pda::accounts::Initialize {
+        protocol_state: derive_protocol_state_pda(program_id).0,
+        vault_authority: derive_vault_authority_pda(program_id).0,
+        owner: *owner,
+        payer: *payer,
+        system_program: solana_sdk::system_program::ID,
+        admin_log: derive_admin_log_pda(program_id).0,
+        vault_registry: derive_vault_registry_pda(program_id).0,
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: vault_pda::instruction::Initialize {}.data(),
+    }
+}
+
+#[tokio::test]
+async fn initializes_the_protocol() {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    let owner = funded_keypair(&mut env).await;
+    let payer = funded_keypair(&mut env).await;
+
+    let ix = initialize_paid_by(&program_id, &owner.pubkey(), &payer.pubkey());
+    sender
+        .send_with_signers(&mut env, ix, &[&payer, &owner])
+        .await
+        .unwrap()
+        .expect("initialize");
+
+    let (protocol_state, state_bump) = derive_protocol_state_pda(&program_id);
+    let state = get_protocol_state(&mut env.context, &protocol_state)
+        .await
+        .unwrap();
+    assert_eq!(state.owner, owner.pubkey());
+    assert_eq!(state.bump, state_bump);
+
+    let (vault_authority, authority_bump) = derive_vault_authority_pda(&program_id);
+    let authority = get_vault_authority(&mut env.context, &vault_authority)
+        .await
+        .unwrap();
+    assert_eq!(authority.bump, authority_bump);
+
+    // The admin log opens with the initialization
+    let (admin_log, log_bump) = derive_admin_log_pda(&program_id);
+    let account = env
+        .context
+        .banks_client
+        .get_account(admin_log)
+        .await
+        .unwrap()
+        .unwrap();
+    let Ok(AnyAccount::AdminLog(log)) = try_deserialize_any(&account.data) else {
+        panic!("the admin log does not decode");
+    };
+    assert_eq!(log.bump, log_bump);
+    assert_eq!(log.len, 1);
+    let entry = log.iter().next().unwrap();
+    assert_eq!(entry.actor, owner.pubkey());
+    assert_eq!(
+        AdminAction::from_code(entry.action),
+        Some(AdminAction::Initialize)
+    );
+}
+
+#[tokio::test]
+async fn owner_must_sign() {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    let payer = funded_keypair(&mut env).await;
+
+    // Nobody may name someone else the owner without their signature
+    let mut ix = initialize_paid_by(&program_id, &new_address(), &payer.pubkey());
+    ix.accounts[2].is_signer = false;
+    let result = sender.send(&mut env, ix, &payer).await.unwrap();
+    assert_framework_error(result, ErrorCode::AccountNotSigner);
+
+    let protocol_state = derive_protocol_state_pda(&program_id).0;
+    assert!(get_protocol_state(&mut env.context, &protocol_state)
+        .await
+        .is_err());
+}
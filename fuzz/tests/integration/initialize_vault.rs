@@ -0,0 +1,120 @@
+//! `initialize_vault`. Repeated calls are covered by
+//! `tests/reinitialization.rs`.
+
+use solana_sdk::program_option::COption;
+
+use super::*;
+
+#[tokio::test]
+async fn initializes_a_vault() {
+    for flavor in TokenFlavor::ALL {
+        let mut env = setup_program_test().await;
+        let program_id = env.program_id;
+        let mut sender = Sender::default();
+        setup_protocol(&mut env.context, &program_id).await.unwrap();
+        let underlying = setup_underlying_mint(&mut env.context, 9, flavor)
+            .await
+            .unwrap();
+        let payer = funded_keypair(&mut env).await;
+
+        let ix = with_token_program(
+            initialize_vault_ix(&program_id, &underlying.mint, &payer.pubkey()),
+            &flavor.program_id(),
+        );
+        sender
+            .send(&mut env, ix, &payer)
+            .await
+            .unwrap()
+            .expect("initialize_vault");
+
+        let (vault, bump) = derive_vault_pda(&program_id, &underlying.mint);
+        let state = get_vault_state(&mut env.context, &vault).await.unwrap();
+        assert_eq!(state.underlying_mint, underlying.mint);
+        assert_eq!(
+            state.share_mint,
+            derive_share_mint_pda(&program_id, &vault).0
+        );
+        assert_eq!(
+            state.vault_token_account,
+            derive_vault_token_account_pda(&program_id, &vault).0
+        );
+        assert_eq!(state.bump, bump);
+
+        let authority = if vault_pda::VULN_GLOBAL_AUTHORITY {
+            derive_vault_authority_pda(&program_id).0
+        } else {
+            vault
+        };
+        let share_mint = get_mint_state(&mut env.context, &state.share_mint)
+            .await
+            .unwrap();
+        assert_eq!(share_mint.decimals, 9, "{flavor:?}");
+        assert_eq!(share_mint.supply, 0);
+        assert_eq!(share_mint.mint_authority, COption::Some(authority));
+        let token_account = get_token_account_state(&mut env.context, &state.vault_token_account)
+            .await
+            .unwrap();
+        assert_eq!(token_account.mint, underlying.mint);
+        assert_eq!(token_account.owner, authority);
+        assert_eq!(token_account.amount, 0);
+
+        for address in [state.share_mint, state.vault_token_account] {
+            let account = env
+                .context
+                .banks_client
+                .get_account(address)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(account.owner, flavor.program_id());
+        }
+    }
+}
+
+#[tokio::test]
+async fn requires_an_initialized_protocol() {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    let underlying = setup_underlying_mint(&mut env.context, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let payer = funded_keypair(&mut env).await;
+
+    let ix = initialize_vault_ix(&program_id, &underlying.mint, &payer.pubkey());
+    let result = sender.send(&mut env, ix, &payer).await.unwrap();
+    assert_framework_error(result, ErrorCode::AccountNotInitialized);
+}
+
+#[tokio::test]
+async fn underlying_mint_must_exist() {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    setup_protocol(&mut env.context, &program_id).await.unwrap();
+    let payer = funded_keypair(&mut env).await;
+
+    let ix = initialize_vault_ix(&program_id, &new_address(), &payer.pubkey());
+    let result = sender.send(&mut env, ix, &payer).await.unwrap();
+    assert_framework_error(result, ErrorCode::AccountNotInitialized);
+}
+
+#[tokio::test]
+async fn vault_address_must_derive_from_the_mint() {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    setup_protocol(&mut env.context, &program_id).await.unwrap();
+    let underlying = setup_underlying_mint(&mut env.context, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let other = setup_underlying_mint(&mut env.context, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let payer = funded_keypair(&mut env).await;
+
+    let mut ix = initialize_vault_ix(&program_id, &underlying.mint, &payer.pubkey());
+    ix.accounts[0].pubkey = derive_vault_pda(&program_id, &other.mint).0;
+    let result = sender.send(&mut env, ix, &payer).await.unwrap();
+    assert_framework_error(result, ErrorCode::ConstraintSeeds);
+}
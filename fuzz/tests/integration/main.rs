@@ -0,0 +1,130 @@
+//! Deterministic integration tests for the core instructions.
+//!
+//! One module per instruction covers its happy path and every error it
+//! documents, plus the account constraints Anchor enforces for it, under both
+//! token programs where the token program matters. `transactions` sends
+//! several instructions in one transaction. Expectations that differ between
+//! the challenge build and the reference fix read the program's `VULN_*`
+//! flags, or price off `vault_math`, so the suite passes under either.
+//!
+//! Some error paths need vault states no honest sequence of transactions
+//! reaches (shares outstanding against an empty vault, supplies near
+//! `u64::MAX`); those tests write SPL Token state straight into the bank.
+//!
+//! Requires the program to be built first, with the same features:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test integration
+//! ```
+
+use anchor_lang::error::ErrorCode;
+use fuzz_helpers::backend::get_packed_account;
+use fuzz_helpers::fuzz_core::sequence::Sender;
+use fuzz_helpers::*;
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+
+mod deposit;
+mod initialize;
+mod initialize_vault;
+mod migrate;
+mod redeem;
+mod transactions;
+mod transfer_ownership;
+
+/// Underlying balance every user starts with
+const BALANCE: u64 = 1_000_000;
+
+type TxResult = Result<(), BanksClientError>;
+
+/// A protocol, one vault and one funded user under `flavor`
+async fn vault_env(flavor: TokenFlavor) -> (FuzzTestEnv, CompleteSetup, Sender) {
+    let (env, setup) = setup_complete_environment(BALANCE, 6, flavor)
+        .await
+        .expect("setup");
+    (env, setup, Sender::default())
+}
+
+/// A fresh keypair holding enough SOL to pay fees and rent
+async fn funded_keypair(env: &mut FuzzTestEnv) -> Keypair {
+    let keypair = new_keypair();
+    fund_account(&mut *env.context, &keypair.pubkey())
+        .await
+        .expect("fund keypair");
+    keypair
+}
+
+/// Fails unless `result` was rejected with the program error `expected`
+#[track_caller]
+fn assert_program_error<E: ProgramError>(result: TxResult, expected: E) {
+    let error = result.expect_err(&format!("expected {expected:?}"));
+    match decode_error::<E>(&error) {
+        TxFailure::Program(actual) => assert_eq!(
+            Into::<u32>::into(actual),
+            expected.into(),
+            "{actual:?} != {expected:?}"
+        ),
+        other => panic!("expected {expected:?}, got {other:?}"),
+    }
+}
+
+/// Fails unless Anchor rejected `result` with `expected` before the handler
+/// ran
+#[track_caller]
+fn assert_framework_error(result: TxResult, expected: ErrorCode) {
+    let error = result.expect_err(&format!("expected {expected:?}"));
+    let code = match &error {
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        ))
+        | BanksClientError::SimulationError {
+            err: TransactionError::InstructionError(_, InstructionError::Custom(code)),
+            ..
+        } => Some(*code),
+        _ => None,
+    };
+    assert_eq!(
+        code,
+        Some(u32::from(expected)),
+        "expected {expected:?}, got {error:?}"
+    );
+}
+
+/// Fails unless SPL Token rejected a CPI of `result` with `expected`
+#[track_caller]
+fn assert_token_error<E: ProgramError>(result: TxResult, expected: spl_token::error::TokenError) {
+    let error = result.expect_err(&format!("expected {expected:?}"));
+    match decode_error::<E>(&error) {
+        TxFailure::Token(actual) => assert_eq!(actual, expected),
+        other => panic!("expected {expected:?}, got {other:?}"),
+    }
+}
+
+/// Overwrites the balance of the SPL Token account at `address`
+async fn set_token_balance(env: &mut FuzzTestEnv, address: &Pubkey, amount: u64) {
+    let mut account: spl_token::state::Account = get_packed_account(&mut *env.context, address)
+        .await
+        .expect("token account");
+    account.amount = amount;
+    seed_packed_account(&mut *env.context, address, account)
+        .await
+        .expect("store token account");
+}
+
+/// Overwrites the supply of the SPL Token mint at `address`
+async fn set_mint_supply(env: &mut FuzzTestEnv, address: &Pubkey, supply: u64) {
+    let mut mint: spl_token::state::Mint = get_packed_account(&mut *env.context, address)
+        .await
+        .expect("mint");
+    mint.supply = supply;
+    seed_packed_account(&mut *env.context, address, mint)
+        .await
+        .expect("store mint");
+}
@@ -0,0 +1,212 @@
+//! `migrate_protocol` and `migrate_vault` against accounts rewritten in the
+//! layouts of the first release.
+
+use anchor_lang::Discriminator;
+use solana_sdk::account::{AccountSharedData, ReadableAccount};
+use vault_pda::instructions::{DepositError, MigrateProtocolError, MigrateVaultError};
+use vault_pda::state::{ActivityStats, ProtocolState, Vault, VaultRegistry};
+use vault_pda_client::{migrate_protocol_ix, migrate_vault_ix, sunset_vault_ix};
+
+use super::*;
+
+/// Rewrites the protocol state and vault of `setup` as the first release
+/// stored them, and removes the accounts it did not have
+async fn downgrade(env: &mut FuzzTestEnv, setup: &CompleteSetup) {
+    let program_id = env.program_id;
+    let protocol = get_protocol_state(&mut env.context, &setup.protocol.protocol_state)
+        .await
+        .unwrap();
+    let mut legacy = ProtocolState::DISCRIMINATOR.to_vec();
+    legacy.extend_from_slice(protocol.owner.as_ref());
+    legacy.push(protocol.bump);
+    store(env, &setup.protocol.protocol_state, legacy).await;
+
+    let vault = get_vault_state(&mut env.context, &setup.vault.vault)
+        .await
+        .unwrap();
+    let mut legacy = Vault::DISCRIMINATOR.to_vec();
+    legacy.extend_from_slice(vault.share_mint.as_ref());
+    legacy.extend_from_slice(vault.underlying_mint.as_ref());
+    legacy.extend_from_slice(vault.vault_token_account.as_ref());
+    legacy.push(vault.bump);
+    store(env, &setup.vault.vault, legacy).await;
+
+    for address in [
+        derive_admin_log_pda(&program_id).0,
+        derive_vault_registry_pda(&program_id).0,
+        derive_activity_stats_pda(&program_id, &setup.vault.vault).0,
+    ] {
+        env.context
+            .set_account(&address, &AccountSharedData::default());
+    }
+}
+
+/// Replaces the data of the program account at `address`, keeping its
+/// lamports
+async fn store(env: &mut FuzzTestEnv, address: &Pubkey, data: Vec<u8>) {
+    let account = env
+        .context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut legacy = AccountSharedData::new(account.lamports, data.len(), &env.program_id);
+    legacy.set_data_from_slice(&data);
+    env.context.set_account(address, &legacy);
+}
+
+async fn account_len(env: &mut FuzzTestEnv, address: &Pubkey) -> usize {
+    let account = env
+        .context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap();
+    account.data().len()
+}
+
+#[tokio::test]
+async fn migrates_a_legacy_deployment() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    let program_id = env.program_id;
+    let owner = &setup.protocol.owner_keypair;
+    let before = get_vault_state(&mut env.context, &setup.vault.vault)
+        .await
+        .unwrap();
+    downgrade(&mut env, &setup).await;
+
+    let ix = migrate_protocol_ix(&program_id, &owner.pubkey());
+    sender
+        .send(&mut env, ix, owner)
+        .await
+        .unwrap()
+        .expect("migrate_protocol");
+    let protocol = get_protocol_state(&mut env.context, &setup.protocol.protocol_state)
+        .await
+        .unwrap();
+    assert_eq!(protocol.owner, owner.pubkey());
+    assert_eq!(protocol.version, vault_pda::constants::PROGRAM_VERSION);
+    assert_eq!(protocol.features, ProtocolState::DEFAULT_FEATURES);
+    assert_eq!(
+        account_len(&mut env, &setup.protocol.protocol_state).await,
+        ProtocolState::LEN
+    );
+
+    let ix = migrate_vault_ix(&program_id, &setup.underlying.mint, &owner.pubkey());
+    sender
+        .send(&mut env, ix, owner)
+        .await
+        .unwrap()
+        .expect("migrate_vault");
+    let vault = get_vault_state(&mut env.context, &setup.vault.vault)
+        .await
+        .unwrap();
+    assert_eq!(vault.share_mint, before.share_mint);
+    assert_eq!(vault.vault_token_account, before.vault_token_account);
+    assert_eq!(vault.bump, before.bump);
+    assert_eq!(vault.creator, owner.pubkey());
+    assert!(!vault.sunset);
+    assert_eq!(account_len(&mut env, &setup.vault.vault).await, Vault::LEN);
+
+    let registry = env
+        .context
+        .banks_client
+        .get_account(derive_vault_registry_pda(&program_id).0)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(VaultRegistry::vaults(registry.data()), &[setup.vault.vault]);
+    let stats = derive_activity_stats_pda(&program_id, &setup.vault.vault).0;
+    assert_eq!(account_len(&mut env, &stats).await, ActivityStats::LEN);
+
+    // The migrated vault serves deposits again
+    sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, 1_000)
+        .await
+        .unwrap()
+        .expect("deposit");
+}
+
+#[tokio::test]
+async fn migrated_vaults_can_be_sunset() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    let program_id = env.program_id;
+    let owner = &setup.protocol.owner_keypair;
+    sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, 1_000)
+        .await
+        .unwrap()
+        .expect("deposit");
+    downgrade(&mut env, &setup).await;
+
+    for ix in [
+        migrate_protocol_ix(&program_id, &owner.pubkey()),
+        migrate_vault_ix(&program_id, &setup.underlying.mint, &owner.pubkey()),
+        sunset_vault_ix(&program_id, &setup.underlying.mint, &owner.pubkey()),
+    ] {
+        sender.send(&mut env, ix, owner).await.unwrap().unwrap();
+    }
+    let vault = get_vault_state(&mut env.context, &setup.vault.vault)
+        .await
+        .unwrap();
+    assert!(vault.sunset);
+
+    let result = sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, 1_000)
+        .await
+        .unwrap();
+    assert_program_error(result, DepositError::VaultSunset);
+    sender
+        .redeem_as(&mut env, &setup.vault, &setup.user, 1_000)
+        .await
+        .unwrap()
+        .expect("redeem");
+}
+
+#[tokio::test]
+async fn only_the_owner_migrates() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    let program_id = env.program_id;
+    let owner = &setup.protocol.owner_keypair;
+    downgrade(&mut env, &setup).await;
+    let attacker = funded_keypair(&mut env).await;
+
+    let ix = migrate_protocol_ix(&program_id, &attacker.pubkey());
+    let result = sender.send(&mut env, ix, &attacker).await.unwrap();
+    assert_program_error(result, MigrateProtocolError::Unauthorized);
+
+    let ix = migrate_protocol_ix(&program_id, &owner.pubkey());
+    sender
+        .send(&mut env, ix, owner)
+        .await
+        .unwrap()
+        .expect("migrate_protocol");
+
+    let ix = migrate_vault_ix(&program_id, &setup.underlying.mint, &attacker.pubkey());
+    let result = sender.send(&mut env, ix, &attacker).await.unwrap();
+    assert_program_error(result, MigrateVaultError::Unauthorized);
+}
+
+#[tokio::test]
+async fn current_accounts_are_not_migrated() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    let program_id = env.program_id;
+    let owner = &setup.protocol.owner_keypair;
+
+    // Migrating a current vault would recreate its activity stats
+    let ix = migrate_vault_ix(&program_id, &setup.underlying.mint, &owner.pubkey());
+    let result = sender.send(&mut env, ix, owner).await.unwrap();
+    assert!(result.is_err());
+    assert_eq!(account_len(&mut env, &setup.vault.vault).await, Vault::LEN);
+
+    // Migrating a live protocol state would recreate its admin log
+    let ix = migrate_protocol_ix(&program_id, &owner.pubkey());
+    let result = sender.send(&mut env, ix, owner).await.unwrap();
+    assert!(result.is_err());
+    assert_eq!(
+        account_len(&mut env, &setup.protocol.protocol_state).await,
+        ProtocolState::LEN
+    );
+}
@@ -0,0 +1,332 @@
+//! `redeem`: every `RedeemError`, the account constraints and token
+//! failures.
+
+use vault_math::compute_assets;
+use vault_pda::instructions::RedeemError;
+use vault_pda_client::set_withdrawal_limit_ix;
+
+use super::*;
+
+#[tokio::test]
+async fn redeems_at_the_share_price() {
+    for flavor in TokenFlavor::ALL {
+        let (mut env, setup, mut sender) = vault_env(flavor).await;
+        let (vault, user) = (&setup.vault, &setup.user);
+        sender
+            .deposit_as(&mut env, vault, user, 10_000)
+            .await
+            .unwrap()
+            .expect("deposit");
+        donate_to_vault(&mut env.context, &setup.underlying, vault, 3_000)
+            .await
+            .unwrap();
+
+        // Half the shares take half the vault, yield included
+        let expected = compute_assets(5_000, 10_000, 13_000).unwrap();
+        sender
+            .redeem_as(&mut env, vault, user, 5_000)
+            .await
+            .unwrap()
+            .expect("redeem");
+        let after = get_snapshot(&mut env.context, vault, user).await.unwrap();
+        assert_eq!(
+            after.user_balance,
+            BALANCE - 10_000 + expected,
+            "{flavor:?}"
+        );
+        assert_eq!(after.user_shares, 5_000);
+        assert_eq!(after.share_supply, 5_000);
+        assert_eq!(after.vault_balance, 13_000 - expected);
+
+        // The rest empties the position
+        let expected = compute_assets(5_000, 5_000, 13_000 - expected).unwrap();
+        sender
+            .redeem_as(&mut env, vault, user, 5_000)
+            .await
+            .unwrap()
+            .expect("redeem the rest");
+        let last = get_snapshot(&mut env.context, vault, user).await.unwrap();
+        assert_eq!(last.user_balance, after.user_balance + expected);
+        assert_eq!(last.user_shares, 0);
+        assert_eq!(last.share_supply, 0);
+    }
+}
+
+#[tokio::test]
+async fn zero_shares_are_rejected() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, 1_000)
+        .await
+        .unwrap()
+        .expect("deposit");
+
+    let result = sender
+        .redeem_as(&mut env, &setup.vault, &setup.user, 0)
+        .await
+        .unwrap();
+    assert_program_error(result, RedeemError::InvalidAmount);
+}
+
+#[tokio::test]
+async fn redeem_without_shares_outstanding_is_rejected() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+
+    let result = sender
+        .redeem_as(&mut env, &setup.vault, &setup.user, 1)
+        .await
+        .unwrap();
+    assert_program_error(result, RedeemError::NoShares);
+}
+
+#[tokio::test]
+async fn redeem_from_an_empty_vault_is_rejected() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, 1_000)
+        .await
+        .unwrap()
+        .expect("deposit");
+    set_token_balance(&mut env, &setup.vault.vault_token_account, 0).await;
+
+    let result = sender
+        .redeem_as(&mut env, &setup.vault, &setup.user, 1_000)
+        .await
+        .unwrap();
+    assert_program_error(result, RedeemError::EmptyVault);
+}
+
+#[tokio::test]
+async fn asset_overflow_is_rejected() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, 1_000)
+        .await
+        .unwrap()
+        .expect("deposit");
+    set_mint_supply(&mut env, &setup.vault.share_mint, 1).await;
+    set_token_balance(&mut env, &setup.vault.vault_token_account, u64::MAX).await;
+
+    // 1,000 shares of a single-share supply would be worth more than u64::MAX
+    let result = sender
+        .redeem_as(&mut env, &setup.vault, &setup.user, 1_000)
+        .await
+        .unwrap();
+    assert_program_error(result, RedeemError::MathOverflow);
+}
+
+#[tokio::test]
+async fn redeem_worth_less_than_a_token_is_rejected() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, 2)
+        .await
+        .unwrap()
+        .expect("deposit");
+    set_token_balance(&mut env, &setup.vault.vault_token_account, 1).await;
+    let before = get_snapshot(&mut env.context, &setup.vault, &setup.user)
+        .await
+        .unwrap();
+
+    let result = sender
+        .redeem_as(&mut env, &setup.vault, &setup.user, 1)
+        .await
+        .unwrap();
+    assert_program_error(result, RedeemError::InsufficientUnderlying);
+    let after = get_snapshot(&mut env.context, &setup.vault, &setup.user)
+        .await
+        .unwrap();
+    assert_eq!(before, after, "a rejected redeem burned shares");
+}
+
+#[tokio::test]
+async fn redeems_past_the_withdrawal_limit_are_rejected() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    let program_id = env.program_id;
+    let owner = &setup.protocol.owner_keypair;
+    let (vault, user) = (&setup.vault, &setup.user);
+    sender
+        .deposit_as(&mut env, vault, user, 10_000)
+        .await
+        .unwrap()
+        .expect("deposit");
+    let ix = set_withdrawal_limit_ix(
+        &program_id,
+        &setup.underlying.mint,
+        &owner.pubkey(),
+        1_500,
+        10,
+    );
+    sender
+        .send(&mut env, ix, owner)
+        .await
+        .unwrap()
+        .expect("set_withdrawal_limit");
+
+    sender
+        .redeem_as(&mut env, vault, user, 1_000)
+        .await
+        .unwrap()
+        .expect("redeem under the limit");
+    let result = sender
+        .redeem_as(&mut env, vault, user, 1_000)
+        .await
+        .unwrap();
+    assert_program_error(result, RedeemError::WithdrawalLimitExceeded);
+    sender
+        .redeem_as(&mut env, vault, user, 500)
+        .await
+        .unwrap()
+        .expect("redeem up to the limit");
+
+    // Lifting the limit reopens redeems
+    let ix = set_withdrawal_limit_ix(&program_id, &setup.underlying.mint, &owner.pubkey(), 0, 0);
+    sender
+        .send(&mut env, ix, owner)
+        .await
+        .unwrap()
+        .expect("lift the limit");
+    sender
+        .redeem_as(&mut env, vault, user, 1_000)
+        .await
+        .unwrap()
+        .expect("redeem without a limit");
+}
+
+#[tokio::test]
+async fn share_mint_must_be_the_vaults() {
+    let (mut env, setup) = setup_two_vault_environment(BALANCE, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let mut sender = Sender::default();
+    for (vault, user) in [
+        (&setup.a.vault, &setup.a.user),
+        (&setup.vault_b, &setup.user_b),
+    ] {
+        sender
+            .deposit_as(&mut env, vault, user, 10_000)
+            .await
+            .unwrap()
+            .expect("deposit");
+    }
+
+    // Vault A's accounts, but vault B's share mint and the user's B shares
+    let vault = VaultAccounts {
+        share_mint: setup.vault_b.share_mint,
+        ..setup.a.vault.clone()
+    };
+    let user = UserAccounts {
+        share_token_account: setup.user_b.share_token_account,
+        ..setup.a.user.clone()
+    };
+    let result = sender
+        .redeem_as(&mut env, &vault, &user, 10_000)
+        .await
+        .unwrap();
+
+    // The challenge build pays out vault A for vault B's shares
+    if vault_pda::VULN_SHARE_MINT {
+        result.expect("cross-vault redeem in the challenge build");
+        let balance = get_token_balance(&mut env.context, &setup.a.vault.vault_token_account)
+            .await
+            .unwrap();
+        assert_eq!(balance, 0, "vault A was not drained");
+    } else {
+        assert_program_error(result, RedeemError::InvalidShareMint);
+    }
+}
+
+#[tokio::test]
+async fn vault_token_account_must_be_the_vaults() {
+    let (mut env, setup) = setup_two_vault_environment(BALANCE, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let mut sender = Sender::default();
+    sender
+        .deposit_as(&mut env, &setup.a.vault, &setup.a.user, 1_000)
+        .await
+        .unwrap()
+        .expect("deposit");
+
+    let vault = VaultAccounts {
+        vault_token_account: setup.vault_b.vault_token_account,
+        ..setup.a.vault.clone()
+    };
+    let result = sender
+        .redeem_as(&mut env, &vault, &setup.a.user, 1_000)
+        .await
+        .unwrap();
+    assert_framework_error(result, ErrorCode::ConstraintHasOne);
+}
+
+#[tokio::test]
+async fn redeemer_must_own_the_shares() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    let victim = create_and_fund_user(
+        &mut env.context,
+        &setup.underlying,
+        &setup.vault.share_mint,
+        BALANCE,
+    )
+    .await
+    .unwrap();
+    sender
+        .deposit_as(&mut env, &setup.vault, &victim, 1_000)
+        .await
+        .unwrap()
+        .expect("deposit");
+
+    let user = UserAccounts {
+        share_token_account: victim.share_token_account,
+        ..setup.user.clone()
+    };
+    let result = sender
+        .redeem_as(&mut env, &setup.vault, &user, 1_000)
+        .await
+        .unwrap();
+    assert_framework_error(result, ErrorCode::ConstraintTokenOwner);
+}
+
+#[tokio::test]
+async fn redeemer_must_sign() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    sender
+        .deposit_as(&mut env, &setup.vault, &setup.user, 1_000)
+        .await
+        .unwrap()
+        .expect("deposit");
+    let relayer = funded_keypair(&mut env).await;
+
+    let mut ix = redeem_ix(&env.program_id, &setup.vault, &setup.user, 1_000);
+    ix.accounts[7].is_signer = false;
+    let result = sender.send(&mut env, ix, &relayer).await.unwrap();
+    assert_framework_error(result, ErrorCode::AccountNotSigner);
+}
+
+#[tokio::test]
+async fn redeem_beyond_the_position_fails_in_the_token_program() {
+    for flavor in TokenFlavor::ALL {
+        let (mut env, setup, mut sender) = vault_env(flavor).await;
+        let other = create_and_fund_user(
+            &mut env.context,
+            &setup.underlying,
+            &setup.vault.share_mint,
+            BALANCE,
+        )
+        .await
+        .unwrap();
+        for user in [&setup.user, &other] {
+            sender
+                .deposit_as(&mut env, &setup.vault, user, 1_000)
+                .await
+                .unwrap()
+                .expect("deposit");
+        }
+
+        let result = sender
+            .redeem_as(&mut env, &setup.vault, &setup.user, 1_001)
+            .await
+            .unwrap();
+        assert_token_error::<RedeemError>(result, spl_token::error::TokenError::InsufficientFunds);
+    }
+}
@@ -0,0 +1,174 @@
+//! Several instructions in one transaction. Each sees the state the previous
+//! one left, and a failing instruction reverts the whole transaction.
+
+use vault_math::compute_shares;
+use vault_pda::instructions::RedeemError;
+
+use super::*;
+
+#[tokio::test]
+async fn initialize_and_create_a_vault_together() {
+    for flavor in TokenFlavor::ALL {
+        let mut env = setup_program_test().await;
+        let program_id = env.program_id;
+        let mut sender = Sender::default();
+        let owner = funded_keypair(&mut env).await;
+        let underlying = setup_underlying_mint(&mut env.context, 6, flavor)
+            .await
+            .unwrap();
+
+        let instructions = [
+            initialize_ix(&program_id, &owner.pubkey()),
+            with_token_program(
+                initialize_vault_ix(&program_id, &underlying.mint, &owner.pubkey()),
+                &flavor.program_id(),
+            ),
+        ];
+        sender
+            .send_instructions(&mut env, &instructions, &[&owner])
+            .await
+            .unwrap()
+            .expect("initialize + initialize_vault");
+
+        let protocol_state = derive_protocol_state_pda(&program_id).0;
+        let state = get_protocol_state(&mut env.context, &protocol_state)
+            .await
+            .unwrap();
+        assert_eq!(state.owner, owner.pubkey());
+        let vault = derive_vault_pda(&program_id, &underlying.mint).0;
+        let vault = get_vault_state(&mut env.context, &vault).await.unwrap();
+        assert_eq!(vault.underlying_mint, underlying.mint, "{flavor:?}");
+    }
+}
+
+#[tokio::test]
+async fn deposit_and_redeem_together() {
+    for flavor in TokenFlavor::ALL {
+        let (mut env, setup, mut sender) = vault_env(flavor).await;
+        let program_id = env.program_id;
+        let (vault, user) = (&setup.vault, &setup.user);
+
+        // The first deposit mints 1:1, so redeeming every share returns it all
+        let instructions = [
+            deposit_ix(&program_id, vault, user, 50_000),
+            redeem_ix(&program_id, vault, user, 50_000),
+        ];
+        sender
+            .send_instructions(&mut env, &instructions, &[&user.owner])
+            .await
+            .unwrap()
+            .expect("deposit + redeem");
+
+        let after = get_snapshot(&mut env.context, vault, user).await.unwrap();
+        assert_eq!(after.user_balance, BALANCE, "{flavor:?}");
+        assert_eq!(after.user_shares, 0);
+        assert_eq!(after.share_supply, 0);
+        assert_eq!(after.vault_balance, 0);
+    }
+}
+
+#[tokio::test]
+async fn a_failing_instruction_reverts_the_transaction() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    let program_id = env.program_id;
+    let (vault, user) = (&setup.vault, &setup.user);
+    let before = get_snapshot(&mut env.context, vault, user).await.unwrap();
+
+    let instructions = [
+        deposit_ix(&program_id, vault, user, 50_000),
+        redeem_ix(&program_id, vault, user, 0),
+    ];
+    let result = sender
+        .send_instructions(&mut env, &instructions, &[&user.owner])
+        .await
+        .unwrap();
+    assert!(
+        matches!(
+            &result,
+            Err(BanksClientError::TransactionError(
+                TransactionError::InstructionError(1, _)
+            ))
+        ),
+        "expected the redeem to fail, got {result:?}"
+    );
+    assert_program_error(result, RedeemError::InvalidAmount);
+
+    let after = get_snapshot(&mut env.context, vault, user).await.unwrap();
+    assert_eq!(before, after, "the deposit was not reverted");
+}
+
+#[tokio::test]
+async fn deposits_in_one_transaction_are_priced_in_order() {
+    let (mut env, setup) = FuzzEnvBuilder::new()
+        .users(2)
+        .balance(BALANCE)
+        .build()
+        .await
+        .unwrap();
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    let (first, second) = (setup.user(0, 0), setup.user(0, 1));
+    let vault = &setup.vaults[0];
+
+    sender
+        .deposit_as(&mut env, &vault.vault, first, 1_000)
+        .await
+        .unwrap()
+        .expect("opening deposit");
+    donate_to_vault(&mut env.context, &vault.underlying, &vault.vault, 500)
+        .await
+        .unwrap();
+
+    let instructions = [
+        deposit_ix(&program_id, &vault.vault, first, 300),
+        deposit_ix(&program_id, &vault.vault, second, 700),
+    ];
+    sender
+        .send_instructions(&mut env, &instructions, &[&first.owner, &second.owner])
+        .await
+        .unwrap()
+        .expect("two deposits");
+
+    let first_shares = compute_shares(300, 1_000, 1_500).unwrap();
+    let second_shares = compute_shares(700, 1_000 + first_shares, 1_800).unwrap();
+    let first_after = get_snapshot(&mut env.context, &vault.vault, first)
+        .await
+        .unwrap();
+    let second_after = get_snapshot(&mut env.context, &vault.vault, second)
+        .await
+        .unwrap();
+    assert_eq!(first_after.user_shares, 1_000 + first_shares);
+    assert_eq!(second_after.user_shares, second_shares);
+    assert_eq!(
+        second_after.share_supply,
+        1_000 + first_shares + second_shares
+    );
+    assert_eq!(second_after.vault_balance, 2_500);
+}
+
+#[tokio::test]
+async fn ownership_round_trip_in_one_transaction() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    let program_id = env.program_id;
+    let interim = funded_keypair(&mut env).await;
+    let owner = &setup.protocol;
+
+    // Both owners sign, which the challenge build does not ask for
+    let mut instructions = [
+        transfer_ownership_ix(&program_id, &owner.owner, &interim.pubkey()),
+        transfer_ownership_ix(&program_id, &interim.pubkey(), &owner.owner),
+    ];
+    for ix in &mut instructions {
+        ix.accounts[1].is_signer = true;
+    }
+    sender
+        .send_instructions(&mut env, &instructions, &[&owner.owner_keypair, &interim])
+        .await
+        .unwrap()
+        .expect("transfer there and back");
+
+    let state = get_protocol_state(&mut env.context, &owner.protocol_state)
+        .await
+        .unwrap();
+    assert_eq!(state.owner, owner.owner);
+}
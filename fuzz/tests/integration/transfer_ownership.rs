@@ -0,0 +1,68 @@
+//! `transfer_ownership`.
+
+use vault_pda::instructions::TransferOwnershipError;
+
+use super::*;
+
+async fn owner(env: &mut FuzzTestEnv) -> Pubkey {
+    let protocol_state = derive_protocol_state_pda(&env.program_id).0;
+    get_protocol_state(&mut env.context, &protocol_state)
+        .await
+        .unwrap()
+        .owner
+}
+
+#[tokio::test]
+async fn owner_transfers_ownership() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    let program_id = env.program_id;
+    let new_owner = funded_keypair(&mut env).await;
+
+    let ix = transfer_ownership_ix(&program_id, &setup.protocol.owner, &new_owner.pubkey());
+    sender
+        .send(&mut env, ix, &setup.protocol.owner_keypair)
+        .await
+        .unwrap()
+        .expect("transfer_ownership");
+    assert_eq!(owner(&mut env).await, new_owner.pubkey());
+
+    // The previous owner has no say any more
+    let ix = transfer_ownership_ix(&program_id, &setup.protocol.owner, &setup.protocol.owner);
+    let result = sender
+        .send(&mut env, ix, &setup.protocol.owner_keypair)
+        .await
+        .unwrap();
+    assert_program_error(result, TransferOwnershipError::Unauthorized);
+    assert_eq!(owner(&mut env).await, new_owner.pubkey());
+}
+
+#[tokio::test]
+async fn current_owner_must_be_the_owner() {
+    let (mut env, _setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    let program_id = env.program_id;
+    let attacker = funded_keypair(&mut env).await;
+
+    let ix = transfer_ownership_ix(&program_id, &attacker.pubkey(), &attacker.pubkey());
+    let result = sender.send(&mut env, ix, &attacker).await.unwrap();
+    assert_program_error(result, TransferOwnershipError::Unauthorized);
+}
+
+#[tokio::test]
+async fn current_owner_must_sign() {
+    let (mut env, setup, mut sender) = vault_env(TokenFlavor::SplToken).await;
+    let program_id = env.program_id;
+    let attacker = funded_keypair(&mut env).await;
+
+    let mut ix = transfer_ownership_ix(&program_id, &setup.protocol.owner, &attacker.pubkey());
+    ix.accounts[1].is_signer = false;
+    let result = sender.send(&mut env, ix, &attacker).await.unwrap();
+
+    // The challenge build hands the protocol to anyone who names the owner
+    if vault_pda::VULN_OWNERSHIP {
+        result.expect("takeover in the challenge build");
+        assert_eq!(owner(&mut env).await, attacker.pubkey());
+    } else {
+        assert_framework_error(result, ErrorCode::ConstraintSigner);
+        assert_eq!(owner(&mut env).await, setup.protocol.owner);
+    }
+}
@@ -0,0 +1,422 @@
+//! Negative tests against a token program that cheats.
+//!
+//! `malicious-token` speaks the SPL Token instruction set but reports wrong
+//! decimals, skims transfers or calls back into its caller (see
+//! `malicious_token::Behavior`). Each behavior is loaded as a builtin at its
+//! own address next to the vault. The first tests prove the mock really
+//! misbehaves when called directly; the rest hand it to the vault, as the
+//! token program or as the owner of a mint or token account, and require
+//! Anchor's token interface to refuse it before any CPI is issued.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test malicious_token
+//! ```
+
+use std::mem::ManuallyDrop;
+
+use anchor_lang::error::ErrorCode;
+use fuzz_helpers::backend::get_packed_account;
+use fuzz_helpers::fuzz_core::sequence::Sender;
+use fuzz_helpers::*;
+use malicious_token::Behavior;
+use solana_program_test::{processor, BanksClientError, ProgramTest};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::TransactionError,
+};
+use spl_token::error::TokenError;
+use spl_token::state::{Account as TokenAccount, Mint};
+
+const BALANCE: u64 = 1_000_000;
+
+/// The vault and every behavior of the mock in one program test
+async fn setup() -> FuzzTestEnv {
+    let program_id = vault_pda::id();
+    let mut program_test = ProgramTest::new(PROGRAM_NAME, program_id, None);
+
+    // The mock only exists natively, so it must not be looked up as SBF
+    program_test.prefer_bpf(false);
+    for behavior in Behavior::ALL {
+        program_test.add_program(
+            behavior.name(),
+            behavior.program_id(),
+            processor!(malicious_token::process_instruction),
+        );
+    }
+
+    FuzzTestEnv {
+        program_id,
+        context: ManuallyDrop::new(program_test.start_with_context().await),
+    }
+}
+
+/// A fresh keypair holding enough SOL to pay fees and rent
+async fn funded_keypair(env: &mut FuzzTestEnv) -> Keypair {
+    let keypair = new_keypair();
+    fund_account(&mut *env.context, &keypair.pubkey())
+        .await
+        .expect("fund keypair");
+    keypair
+}
+
+/// `ix`, built for SPL Token, sent to the mock for `behavior` instead
+fn mock(mut ix: Instruction, behavior: Behavior) -> Instruction {
+    ix.program_id = behavior.program_id();
+    ix
+}
+
+/// Creates a mint owned by the mock for `behavior`, asking for `decimals`
+async fn create_mock_mint(
+    env: &mut FuzzTestEnv,
+    sender: &mut Sender,
+    behavior: Behavior,
+    authority: &Keypair,
+    decimals: u8,
+) -> Pubkey {
+    let mint = new_keypair();
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    let create = system_instruction::create_account(
+        &authority.pubkey(),
+        &mint.pubkey(),
+        rent.minimum_balance(Mint::LEN),
+        Mint::LEN as u64,
+        &behavior.program_id(),
+    );
+    let initialize = spl_token::instruction::initialize_mint2(
+        &spl_token::id(),
+        &mint.pubkey(),
+        &authority.pubkey(),
+        None,
+        decimals,
+    )
+    .unwrap();
+    sender
+        .send_instructions(
+            env,
+            &[create, mock(initialize, behavior)],
+            &[authority, &mint],
+        )
+        .await
+        .unwrap()
+        .expect("create mock mint");
+    mint.pubkey()
+}
+
+/// Creates a token account of the mock's `mint` owned by `owner`, who pays
+async fn create_mock_account(
+    env: &mut FuzzTestEnv,
+    sender: &mut Sender,
+    behavior: Behavior,
+    mint: &Pubkey,
+    owner: &Keypair,
+) -> Pubkey {
+    let account = new_keypair();
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    let create = system_instruction::create_account(
+        &owner.pubkey(),
+        &account.pubkey(),
+        rent.minimum_balance(TokenAccount::LEN),
+        TokenAccount::LEN as u64,
+        &behavior.program_id(),
+    );
+    let initialize = spl_token::instruction::initialize_account3(
+        &spl_token::id(),
+        &account.pubkey(),
+        mint,
+        &owner.pubkey(),
+    )
+    .unwrap();
+    sender
+        .send_instructions(
+            env,
+            &[create, mock(initialize, behavior)],
+            &[owner, &account],
+        )
+        .await
+        .unwrap()
+        .expect("create mock token account");
+    account.pubkey()
+}
+
+/// The error the first failing instruction of `result` returned
+#[track_caller]
+fn instruction_error(result: Result<(), BanksClientError>) -> InstructionError {
+    match result.expect_err("expected the transaction to fail") {
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, error))
+        | BanksClientError::SimulationError {
+            err: TransactionError::InstructionError(_, error),
+            ..
+        } => error,
+        other => panic!("expected an instruction error, got {other:?}"),
+    }
+}
+
+fn framework(code: ErrorCode) -> InstructionError {
+    InstructionError::Custom(code.into())
+}
+
+#[tokio::test]
+async fn mock_reports_wrong_decimals() {
+    let mut env = setup().await;
+    let mut sender = Sender::default();
+    let authority = funded_keypair(&mut env).await;
+
+    let mint = create_mock_mint(
+        &mut env,
+        &mut sender,
+        Behavior::WrongDecimals,
+        &authority,
+        6,
+    )
+    .await;
+    let state: Mint = get_packed_account(&mut *env.context, &mint).await.unwrap();
+    assert_eq!(state.decimals, 7);
+
+    // Every other behavior checks the decimals the caller states
+    let mint = create_mock_mint(&mut env, &mut sender, Behavior::Skim, &authority, 6).await;
+    let account =
+        create_mock_account(&mut env, &mut sender, Behavior::Skim, &mint, &authority).await;
+    let ix = spl_token::instruction::mint_to_checked(
+        &spl_token::id(),
+        &mint,
+        &account,
+        &authority.pubkey(),
+        &[],
+        1_000,
+        9,
+    )
+    .unwrap();
+    let result = sender
+        .send(&mut env, mock(ix, Behavior::Skim), &authority)
+        .await
+        .unwrap();
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::Custom(TokenError::MintDecimalsMismatch as u32)
+    );
+}
+
+#[tokio::test]
+async fn mock_skims_transfers() {
+    let mut env = setup().await;
+    let mut sender = Sender::default();
+    let behavior = Behavior::Skim;
+    let authority = funded_keypair(&mut env).await;
+    let mint = create_mock_mint(&mut env, &mut sender, behavior, &authority, 6).await;
+    let source = create_mock_account(&mut env, &mut sender, behavior, &mint, &authority).await;
+    let destination = create_mock_account(&mut env, &mut sender, behavior, &mint, &authority).await;
+
+    let mint_to = spl_token::instruction::mint_to(
+        &spl_token::id(),
+        &mint,
+        &source,
+        &authority.pubkey(),
+        &[],
+        1_000,
+    )
+    .unwrap();
+    let transfer = spl_token::instruction::transfer(
+        &spl_token::id(),
+        &source,
+        &destination,
+        &authority.pubkey(),
+        &[],
+        100,
+    )
+    .unwrap();
+    sender
+        .send_instructions(
+            &mut env,
+            &[mock(mint_to, behavior), mock(transfer, behavior)],
+            &[&authority],
+        )
+        .await
+        .unwrap()
+        .expect("mint and transfer");
+
+    let source: TokenAccount = get_packed_account(&mut *env.context, &source)
+        .await
+        .unwrap();
+    let destination: TokenAccount = get_packed_account(&mut *env.context, &destination)
+        .await
+        .unwrap();
+    assert_eq!(source.amount, 900);
+    assert_eq!(destination.amount, 99);
+}
+
+#[tokio::test]
+async fn mock_reenters_its_caller() {
+    let mut env = setup().await;
+    let mut sender = Sender::default();
+    let behavior = Behavior::Reenter;
+    let authority = funded_keypair(&mut env).await;
+    let mint = create_mock_mint(&mut env, &mut sender, behavior, &authority, 6).await;
+
+    // The vault, passed last, is called with no data and finds no instruction
+    let mut ix = spl_token::instruction::mint_to(
+        &spl_token::id(),
+        &mint,
+        &new_address(),
+        &authority.pubkey(),
+        &[],
+        1,
+    )
+    .unwrap();
+    ix.accounts
+        .push(AccountMeta::new_readonly(env.program_id, false));
+    let result = sender
+        .send(&mut env, mock(ix, behavior), &authority)
+        .await
+        .unwrap();
+    assert_eq!(
+        instruction_error(result),
+        framework(ErrorCode::InstructionFallbackNotFound)
+    );
+}
+
+#[tokio::test]
+async fn vault_refuses_the_mock_as_token_program() {
+    let mut env = setup().await;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    let protocol = setup_protocol(&mut env.context, &program_id).await.unwrap();
+    let underlying = setup_underlying_mint(&mut env.context, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let vault = setup_vault(
+        &mut env.context,
+        &program_id,
+        &protocol.vault_authority,
+        &underlying.mint,
+        &protocol.owner_keypair,
+        TokenFlavor::SplToken,
+    )
+    .await
+    .unwrap();
+    let user = create_and_fund_user(&mut env.context, &underlying, &vault.share_mint, BALANCE)
+        .await
+        .unwrap();
+    sender
+        .deposit_as(&mut env, &vault, &user, 10_000)
+        .await
+        .unwrap()
+        .expect("deposit");
+    let other = setup_underlying_mint(&mut env.context, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let payer = funded_keypair(&mut env).await;
+
+    for behavior in Behavior::ALL {
+        let token_program = behavior.program_id();
+        let mocked = VaultAccounts {
+            token_program,
+            ..vault.clone()
+        };
+
+        let ix = with_token_program(
+            initialize_vault_ix(&program_id, &other.mint, &payer.pubkey()),
+            &token_program,
+        );
+        let result = sender.send(&mut env, ix, &payer).await.unwrap();
+        assert_eq!(
+            instruction_error(result),
+            framework(ErrorCode::InvalidProgramId),
+            "initialize_vault through {behavior:?}"
+        );
+
+        let result = sender
+            .deposit_as(&mut env, &mocked, &user, 1_000)
+            .await
+            .unwrap();
+        assert_eq!(
+            instruction_error(result),
+            framework(ErrorCode::InvalidProgramId),
+            "deposit through {behavior:?}"
+        );
+
+        let result = sender
+            .redeem_as(&mut env, &mocked, &user, 1_000)
+            .await
+            .unwrap();
+        assert_eq!(
+            instruction_error(result),
+            framework(ErrorCode::InvalidProgramId),
+            "redeem through {behavior:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn vault_refuses_accounts_the_mock_owns() {
+    let mut env = setup().await;
+    let program_id = env.program_id;
+    let mut sender = Sender::default();
+    let protocol = setup_protocol(&mut env.context, &program_id).await.unwrap();
+    let underlying = setup_underlying_mint(&mut env.context, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let vault = setup_vault(
+        &mut env.context,
+        &program_id,
+        &protocol.vault_authority,
+        &underlying.mint,
+        &protocol.owner_keypair,
+        TokenFlavor::SplToken,
+    )
+    .await
+    .unwrap();
+    let user = create_and_fund_user(&mut env.context, &underlying, &vault.share_mint, BALANCE)
+        .await
+        .unwrap();
+
+    for behavior in Behavior::ALL {
+        let mint = create_mock_mint(&mut env, &mut sender, behavior, &user.owner, 6).await;
+
+        // A vault over the mock's mint
+        let ix = initialize_vault_ix(&program_id, &mint, &user.owner.pubkey());
+        let result = sender.send(&mut env, ix, &user.owner).await.unwrap();
+        assert_eq!(
+            instruction_error(result),
+            framework(ErrorCode::AccountOwnedByWrongProgram),
+            "initialize_vault over {behavior:?}"
+        );
+
+        // A deposit paid from one of the mock's token accounts, laid out
+        // exactly like an SPL Token account of the real underlying mint
+        let account =
+            create_mock_account(&mut env, &mut sender, behavior, &mint, &user.owner).await;
+        let mut data = env
+            .context
+            .banks_client
+            .get_account(account)
+            .await
+            .unwrap()
+            .unwrap();
+        let mut state = TokenAccount::unpack(&data.data).unwrap();
+        state.mint = underlying.mint;
+        state.amount = BALANCE;
+        TokenAccount::pack(state, &mut data.data).unwrap();
+        env.context.set_account(&account, &data.into());
+
+        let mocked = UserAccounts {
+            underlying_token_account: account,
+            ..user.clone()
+        };
+        let result = sender
+            .deposit_as(&mut env, &vault, &mocked, 1_000)
+            .await
+            .unwrap();
+        assert_eq!(
+            instruction_error(result),
+            framework(ErrorCode::AccountOwnedByWrongProgram),
+            "deposit from {behavior:?}"
+        );
+    }
+}
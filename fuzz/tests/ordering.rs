@@ -0,0 +1,288 @@
+//! Intra-block ordering tests.
+//!
+//! An attacker holding the only share of a vault races a victim's deposit
+//! with a donation and a redeem, and the block is replayed in every ordering.
+//! Whatever the ordering, the victim's outcome stays within these bounds:
+//!
+//! - Value is conserved: what the victim loses is what the attacker gains
+//!   plus what is left in the vault once everyone has exited, so the
+//!   attacker never gains more than the victim loses.
+//! - The victim loses less than half its deposit, plus one unit. A deposit
+//!   that would mint no shares is rejected, so the victim's deposit mints at
+//!   least one share, and what rounds away is less than one share at the
+//!   price it paid; the redeem rounds away less than one unit more.
+//! - Depositing before the donation costs the victim nothing.
+//! - Only the sandwich (donation, deposit, redeem) pays the attacker, and
+//!   only in the challenge build (`vuln-inflation`). With the virtual offset
+//!   the victim can still lose up to the bound above, but the attacker loses
+//!   more.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test ordering
+//! ```
+
+use fuzz_helpers::fuzz_core::sequence::Sender;
+use fuzz_helpers::*;
+
+/// Tokens the attacker donates
+const DONATION: u64 = 1_000_000;
+
+/// Just under two shares at the price the donation sets in the challenge
+/// build, so almost half the deposit rounds away
+const VICTIM_DEPOSIT: u64 = 2 * (DONATION + 1) - 1;
+
+/// A vault whose only share the attacker holds, and a snapshot of it
+struct Race {
+    env: FuzzTestEnv,
+    setup: CompleteSetup,
+    attacker: Attacker,
+    sender: Sender,
+    start: StateSnapshot,
+    victim_balance: u64,
+}
+
+/// Where everyone stands once the block has run and both have exited
+#[derive(Debug)]
+struct Settlement {
+    victim_loss: i128,
+    attacker_profit: i128,
+    residual: i128,
+}
+
+impl Race {
+    async fn new(victim_balance: u64) -> Self {
+        let (mut env, setup) = setup_complete_environment(victim_balance, 6, TokenFlavor::SplToken)
+            .await
+            .unwrap();
+        let attacker = Attacker::new(
+            &mut env.context,
+            &setup.underlying,
+            &setup.vault,
+            1,
+            DONATION,
+        )
+        .await
+        .unwrap();
+        let mut sender = Sender::default();
+        sender
+            .deposit_as(&mut env, &setup.vault, &attacker.position, 1)
+            .await
+            .unwrap()
+            .expect("attacker deposit");
+        let start = snapshot(&mut env.context, &(&setup, &attacker))
+            .await
+            .unwrap();
+        Self {
+            env,
+            setup,
+            attacker,
+            sender,
+            start,
+            victim_balance,
+        }
+    }
+
+    /// Donation, victim deposit of `amount` and the attacker's redeem, in
+    /// that order
+    fn sandwich_block(&self, amount: u64) -> Block {
+        let program_id = self.env.program_id;
+        let mut block = Block::new();
+        block.push(
+            "donate",
+            vec![self
+                .attacker
+                .donate_ix(&self.setup.vault, DONATION)
+                .unwrap()],
+            &[self.attacker.keypair()],
+        );
+        block.push(
+            "deposit",
+            vec![deposit_ix(
+                &program_id,
+                &self.setup.vault,
+                &self.setup.user,
+                amount,
+            )],
+            &[&self.setup.user.owner],
+        );
+        block.push(
+            "redeem",
+            vec![redeem_ix(
+                &program_id,
+                &self.setup.vault,
+                &self.attacker.position,
+                1,
+            )],
+            &[self.attacker.keypair()],
+        );
+        block
+    }
+
+    /// Redeems whatever shares `user` still holds
+    async fn exit(&mut self, user: &UserAccounts) {
+        let shares = get_token_balance(&mut self.env.context, &user.share_token_account)
+            .await
+            .unwrap();
+        if shares > 0 {
+            self.sender
+                .redeem_as(&mut self.env, &self.setup.vault, user, shares)
+                .await
+                .unwrap()
+                .expect("exit");
+        }
+    }
+
+    /// Exits both positions and measures them against where they started:
+    /// the victim with its whole balance, the attacker with one token for its
+    /// first deposit plus the donation
+    async fn settle(&mut self) -> Settlement {
+        let victim = self.setup.user.clone();
+        let attacker = self.attacker.position.clone();
+        self.exit(&victim).await;
+        self.exit(&attacker).await;
+
+        let context = &mut *self.env.context;
+        let victim_balance = get_token_balance(context, &victim.underlying_token_account)
+            .await
+            .unwrap() as i128;
+        let attacker_balance = get_token_balance(context, &attacker.underlying_token_account)
+            .await
+            .unwrap() as i128
+            + get_token_balance(context, &self.attacker.spare)
+                .await
+                .unwrap() as i128;
+        assert_eq!(
+            get_mint_supply(context, &self.setup.vault.share_mint)
+                .await
+                .unwrap(),
+            0
+        );
+
+        Settlement {
+            victim_loss: self.victim_balance as i128 - victim_balance,
+            attacker_profit: attacker_balance - (1 + DONATION) as i128,
+            residual: get_token_balance(context, &self.setup.vault.vault_token_account)
+                .await
+                .unwrap() as i128,
+        }
+    }
+}
+
+/// Panics unless `settlement` is within the bounds every ordering must keep
+#[track_caller]
+fn assert_bounded(settlement: &Settlement, deposit: u64, order: &str) {
+    assert_eq!(
+        settlement.victim_loss,
+        settlement.attacker_profit + settlement.residual,
+        "value not conserved in [{}]: {:?}",
+        order,
+        settlement
+    );
+    assert!(
+        settlement.residual >= 0 && settlement.attacker_profit <= settlement.victim_loss,
+        "attacker gained more than the victim lost in [{}]: {:?}",
+        order,
+        settlement
+    );
+    assert!(
+        2 * settlement.victim_loss < deposit as i128 + 2,
+        "victim lost half its deposit of {} or more in [{}]: {:?}",
+        deposit,
+        order,
+        settlement
+    );
+}
+
+#[tokio::test]
+async fn every_ordering_keeps_the_victim_within_bounds() {
+    let mut race = Race::new(VICTIM_DEPOSIT).await;
+    let block = race.sandwich_block(VICTIM_DEPOSIT);
+    let sandwich = vec![0, 1, 2];
+    assert_eq!(block.orderings().len(), 6);
+    assert_eq!(block.sandwiches(0, 1, 2), vec![sandwich.clone()]);
+
+    let mut sandwich_profit = None;
+    for order in block.orderings() {
+        let outcome = block
+            .run(&mut race.env, &mut race.sender, &race.start, &order)
+            .await
+            .unwrap();
+        let label = outcome.order();
+        assert!(outcome.landed("donate"), "donation failed in [{}]", label);
+
+        let settlement = race.settle().await;
+        assert_bounded(&settlement, VICTIM_DEPOSIT, &label);
+
+        let victim_first = order.iter().position(|&i| i == 1) < order.iter().position(|&i| i == 0);
+        if victim_first {
+            assert!(
+                settlement.victim_loss <= 0,
+                "victim lost {} depositing first in [{}]",
+                settlement.victim_loss,
+                label
+            );
+        }
+        if order == sandwich {
+            sandwich_profit = Some(settlement.attacker_profit);
+        } else {
+            assert!(
+                settlement.attacker_profit <= 0,
+                "attacker gained {} without sandwiching in [{}]",
+                settlement.attacker_profit,
+                label
+            );
+        }
+    }
+
+    let sandwich_profit = sandwich_profit.unwrap();
+    if vault_pda::VULN_INFLATION {
+        assert!(
+            sandwich_profit > 0,
+            "the sandwich should pay in the challenge build"
+        );
+    } else {
+        assert!(
+            sandwich_profit < 0,
+            "the virtual offset should make the sandwich cost the attacker, got {}",
+            sandwich_profit
+        );
+    }
+}
+
+#[tokio::test]
+async fn sandwich_loss_is_bounded_for_any_deposit() {
+    let largest = 10 * DONATION;
+    let mut race = Race::new(largest).await;
+
+    // Below, at and between multiples of the price the donation sets
+    for deposit in [
+        DONATION / 2,
+        DONATION + 1,
+        VICTIM_DEPOSIT,
+        3 * (DONATION + 1) - 1,
+        largest,
+    ] {
+        let block = race.sandwich_block(deposit);
+        for order in block.sandwiches(0, 1, 2) {
+            let outcome = block
+                .run(&mut race.env, &mut race.sender, &race.start, &order)
+                .await
+                .unwrap();
+            let settlement = race.settle().await;
+            assert_bounded(
+                &settlement,
+                deposit,
+                &format!("{} of {}", outcome.order(), deposit),
+            );
+            if !outcome.landed("deposit") {
+                assert_eq!(
+                    settlement.victim_loss, 0,
+                    "a rejected deposit cost the victim"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! Re-initialization attack tests.
+//!
+//! Every way of running `initialize` and `initialize_vault` more than once, or
+//! out of order, must be rejected without touching the accounts a previous
+//! successful call created. Raced orderings send competing transactions in
+//! the same slot (or in the same transaction) and require exactly one winner.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test reinitialization
+//! ```
+
+use fuzz_helpers::*;
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+/// Funds a fresh keypair so it can pay for transactions and rent
+async fn funded_keypair(context: &mut ProgramTestContext) -> Keypair {
+    let keypair = Keypair::new();
+    let ix = solana_sdk::system_instruction::transfer(
+        &context.payer.pubkey(),
+        &keypair.pubkey(),
+        10_000_000_000,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+    keypair
+}
+
+fn transaction(
+    context: &ProgramTestContext,
+    instructions: &[Instruction],
+    payer: &Keypair,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &[payer],
+        context.last_blockhash,
+    )
+}
+
+async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    payer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let tx = transaction(context, instructions, payer);
+    context.banks_client.process_transaction(tx).await
+}
+
+/// Sends two transactions into the same slot and returns both results
+async fn race(
+    context: &mut ProgramTestContext,
+    first: (&[Instruction], &Keypair),
+    second: (&[Instruction], &Keypair),
+) -> (Result<(), BanksClientError>, Result<(), BanksClientError>) {
+    let first = transaction(context, first.0, first.1);
+    let second = transaction(context, second.0, second.1);
+    let mut first_client = context.banks_client.clone();
+    let mut second_client = context.banks_client.clone();
+    tokio::join!(
+        first_client.process_transaction(first),
+        second_client.process_transaction(second)
+    )
+}
+
+async fn assert_protocol(env: &mut FuzzTestEnv, owner: &Pubkey) {
+    let program_id = env.program_id;
+    let state = get_protocol_state(&mut env.context, &derive_protocol_state_pda(&program_id).0)
+        .await
+        .unwrap();
+    assert_eq!(&state.owner, owner, "protocol owner was overwritten");
+    assert_eq!(state.bump, derive_protocol_state_pda(&program_id).1);
+
+    let authority =
+        get_vault_authority(&mut env.context, &derive_vault_authority_pda(&program_id).0)
+            .await
+            .unwrap();
+    assert_eq!(authority.bump, derive_vault_authority_pda(&program_id).1);
+}
+
+async fn assert_vault(env: &mut FuzzTestEnv, underlying_mint: &Pubkey) {
+    let program_id = env.program_id;
+    let (vault, bump) = derive_vault_pda(&program_id, underlying_mint);
+    let state = get_vault_state(&mut env.context, &vault).await.unwrap();
+    assert_eq!(&state.underlying_mint, underlying_mint);
+    assert_eq!(
+        state.share_mint,
+        derive_share_mint_pda(&program_id, &vault).0
+    );
+    assert_eq!(
+        state.vault_token_account,
+        derive_vault_token_account_pda(&program_id, &vault).0
+    );
+    assert_eq!(state.bump, bump);
+}
+
+#[tokio::test]
+async fn initialize_twice_is_rejected() {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let owner = funded_keypair(&mut env.context).await;
+    let attacker = funded_keypair(&mut env.context).await;
+
+    send(
+        &mut env.context,
+        &[initialize_ix(&program_id, &owner.pubkey())],
+        &owner,
+    )
+    .await
+    .unwrap();
+
+    // Attacker tries to become owner, then the owner repeats the call
+    for caller in [&attacker, &owner] {
+        let result = send(
+            &mut env.context,
+            &[initialize_ix(&program_id, &caller.pubkey())],
+            caller,
+        )
+        .await;
+        assert!(
+            result.is_err(),
+            "second initialize by {} succeeded",
+            caller.pubkey()
+        );
+        env.context.last_blockhash = env.context.get_new_latest_blockhash().await.unwrap();
+    }
+
+    assert_protocol(&mut env, &owner.pubkey()).await;
+}
+
+#[tokio::test]
+async fn initialize_twice_in_one_transaction_is_rejected_atomically() {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let owner = funded_keypair(&mut env.context).await;
+
+    let ix = initialize_ix(&program_id, &owner.pubkey());
+    let result = send(&mut env.context, &[ix.clone(), ix], &owner).await;
+    assert!(
+        result.is_err(),
+        "double initialize in one transaction succeeded"
+    );
+
+    let protocol_state = derive_protocol_state_pda(&program_id).0;
+    let account = env
+        .context
+        .banks_client
+        .get_account(protocol_state)
+        .await
+        .unwrap();
+    assert!(
+        account.is_none(),
+        "failed transaction left the protocol initialized"
+    );
+}
+
+#[tokio::test]
+async fn raced_initialize_has_exactly_one_winner() {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let first = funded_keypair(&mut env.context).await;
+    let second = funded_keypair(&mut env.context).await;
+
+    let (first_result, second_result) = race(
+        &mut env.context,
+        (&[initialize_ix(&program_id, &first.pubkey())], &first),
+        (&[initialize_ix(&program_id, &second.pubkey())], &second),
+    )
+    .await;
+
+    let winner = match (first_result, second_result) {
+        (Ok(()), Err(_)) => first.pubkey(),
+        (Err(_), Ok(())) => second.pubkey(),
+        results => panic!("expected exactly one initialize to win: {:?}", results),
+    };
+    assert_protocol(&mut env, &winner).await;
+}
+
+#[tokio::test]
+async fn initialize_vault_before_initialize_is_rejected() {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let payer = funded_keypair(&mut env.context).await;
+    let underlying = setup_underlying_mint(&mut env.context, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+
+    let result = send(
+        &mut env.context,
+        &[initialize_vault_ix(
+            &program_id,
+            &underlying.mint,
+            &payer.pubkey(),
+        )],
+        &payer,
+    )
+    .await;
+    assert!(
+        result.is_err(),
+        "initialize_vault succeeded before initialize"
+    );
+
+    let vault = derive_vault_pda(&program_id, &underlying.mint).0;
+    assert!(env
+        .context
+        .banks_client
+        .get_account(vault)
+        .await
+        .unwrap()
+        .is_none());
+
+    // The regular order still works afterwards
+    send(
+        &mut env.context,
+        &[initialize_ix(&program_id, &payer.pubkey())],
+        &payer,
+    )
+    .await
+    .unwrap();
+    env.context.last_blockhash = env.context.get_new_latest_blockhash().await.unwrap();
+    send(
+        &mut env.context,
+        &[initialize_vault_ix(
+            &program_id,
+            &underlying.mint,
+            &payer.pubkey(),
+        )],
+        &payer,
+    )
+    .await
+    .unwrap();
+    assert_vault(&mut env, &underlying.mint).await;
+}
+
+#[tokio::test]
+async fn initialize_vault_twice_for_the_same_mint_is_rejected() {
+    let (mut env, setup) = setup_complete_environment(1_000_000, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let program_id = env.program_id;
+    let attacker = funded_keypair(&mut env.context).await;
+
+    // Open a position so an overwrite would be observable
+    send(
+        &mut env.context,
+        &[deposit_ix(&program_id, &setup.vault, &setup.user, 500_000)],
+        &setup.user.owner,
+    )
+    .await
+    .unwrap();
+
+    for caller in [&attacker, &setup.protocol.owner_keypair] {
+        let result = send(
+            &mut env.context,
+            &[initialize_vault_ix(
+                &program_id,
+                &setup.underlying.mint,
+                &caller.pubkey(),
+            )],
+            caller,
+        )
+        .await;
+        assert!(
+            result.is_err(),
+            "second initialize_vault by {} succeeded",
+            caller.pubkey()
+        );
+        env.context.last_blockhash = env.context.get_new_latest_blockhash().await.unwrap();
+    }
+
+    assert_vault(&mut env, &setup.underlying.mint).await;
+    assert_protocol(&mut env, &setup.protocol.owner).await;
+    assert_eq!(
+        get_token_balance(&mut env.context, &setup.vault.vault_token_account)
+            .await
+            .unwrap(),
+        500_000
+    );
+    assert_eq!(
+        get_mint_supply(&mut env.context, &setup.vault.share_mint)
+            .await
+            .unwrap(),
+        500_000
+    );
+}
+
+#[tokio::test]
+async fn raced_initialize_vault_has_exactly_one_winner() {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let protocol = setup_protocol(&mut env.context, &program_id).await.unwrap();
+    let underlying = setup_underlying_mint(&mut env.context, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let attacker = funded_keypair(&mut env.context).await;
+
+    let (first_result, second_result) = race(
+        &mut env.context,
+        (
+            &[initialize_vault_ix(
+                &program_id,
+                &underlying.mint,
+                &protocol.owner,
+            )],
+            &protocol.owner_keypair,
+        ),
+        (
+            &[initialize_vault_ix(
+                &program_id,
+                &underlying.mint,
+                &attacker.pubkey(),
+            )],
+            &attacker,
+        ),
+    )
+    .await;
+
+    assert!(
+        first_result.is_ok() != second_result.is_ok(),
+        "expected exactly one initialize_vault to win: {:?} {:?}",
+        first_result,
+        second_result
+    );
+    assert_vault(&mut env, &underlying.mint).await;
+}
@@ -0,0 +1,58 @@
+//! Replays reproducer files written by failing fuzz iterations.
+//!
+//! A fuzz target that trips an invariant writes its input to
+//! `reproducers/<target>-<timestamp>.json` and prints the command to replay
+//! it. Without `REPRODUCER` set, every file in the reproducer directory is
+//! replayed, so a fixed bug stays fixed until its file is deleted.
+//!
+//! ```bash
+//! anchor build
+//! REPRODUCER=reproducers/deposit-1760000000.000000000.json \
+//!     SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test reproducers -- --nocapture
+//! ```
+
+use std::fs;
+use std::path::PathBuf;
+
+use fuzz_helpers::fuzz_core::kernel::KernelFuzzInput;
+use fuzz_helpers::fuzz_core::reproducer::{replay, replay_file, DIR_VAR, FILE_VAR};
+
+fn reproducer_files() -> Vec<PathBuf> {
+    if let Ok(path) = std::env::var(FILE_VAR) {
+        return vec![PathBuf::from(path)];
+    }
+    let dir = std::env::var(DIR_VAR).unwrap_or_else(|_| "reproducers".into());
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn replay_reproducers() {
+    for path in reproducer_files() {
+        println!("replaying {}", path.display());
+        if let Err(e) = replay_file(&path) {
+            eprintln!("Fuzz iteration failed: {}", e);
+        }
+    }
+}
+
+#[test]
+fn inputs_round_trip_through_json() {
+    let input = KernelFuzzInput {
+        amount: 1_000,
+        redeem_shares: u64::MAX,
+        total_shares: 7,
+        total_assets: 3,
+    };
+    let value = serde_json::to_value(&input).unwrap();
+    replay("kernel", value).unwrap();
+
+    assert!(replay("no_such_target", serde_json::Value::Null).is_err());
+}
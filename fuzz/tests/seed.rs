@@ -0,0 +1,78 @@
+//! Deterministic seeding tests.
+//!
+//! With the same seed, setup helpers must create the same addresses, and a
+//! reused context must never be handed to a seed that already ran on it.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test seed
+//! ```
+
+use fuzz_helpers::fuzz_core::deposit::DepositFuzzInput;
+use fuzz_helpers::seed::seed_for;
+use fuzz_helpers::*;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+async fn setup_addresses(seed: u64) -> Vec<Pubkey> {
+    seed_keypairs(seed);
+    let (_env, setup) = setup_complete_environment(1_000, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    vec![
+        setup.protocol.owner,
+        setup.underlying.mint,
+        setup.underlying.mint_authority.pubkey(),
+        setup.vault.vault,
+        setup.vault.share_mint,
+        setup.user.owner.pubkey(),
+        setup.user.underlying_token_account,
+        setup.user.share_token_account,
+    ]
+}
+
+#[tokio::test]
+async fn same_seed_creates_same_addresses() {
+    let first = setup_addresses(7).await;
+    let second = setup_addresses(7).await;
+    assert_eq!(first, second);
+
+    let other = setup_addresses(8).await;
+    assert!(first.iter().all(|address| !other.contains(address)));
+}
+
+#[tokio::test]
+async fn repeated_seed_does_not_reuse_its_context() {
+    enable_context_reuse();
+
+    seed_keypairs(9);
+    let (env, _) = setup_complete_environment(1_000, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let payer = env.context.payer.pubkey();
+    drop(env);
+
+    // The same addresses again would collide with the cached context's
+    seed_keypairs(9);
+    let (env, _) = setup_complete_environment(1_000, 6, TokenFlavor::SplToken)
+        .await
+        .expect("setup with a repeated seed");
+    assert_ne!(env.context.payer.pubkey(), payer, "context was reused");
+}
+
+#[test]
+fn reproducer_json_derives_the_same_seed() {
+    let input = DepositFuzzInput {
+        flavor: TokenFlavor::Token2022,
+        amount: 1_000,
+        initial_balance: 5_000,
+        decimals: 6,
+        yield_amount: 0,
+        do_initial_deposit: true,
+        initial_deposit_amount: 10,
+    };
+    let json = serde_json::to_string(&input).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(seed_for(&input), seed_for(&value));
+}
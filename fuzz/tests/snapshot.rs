@@ -0,0 +1,68 @@
+//! State snapshot tests.
+//!
+//! Restoring a snapshot must put every recorded account back exactly, so a
+//! target can branch from the same state more than once.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test snapshot
+//! ```
+
+use fuzz_helpers::fuzz_core::sequence::Sender;
+use fuzz_helpers::*;
+
+#[tokio::test]
+async fn restore_rewinds_a_deposit() {
+    let (mut env, setup) = setup_complete_environment(1_000_000, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let mut sender = Sender::default();
+
+    let before = get_snapshot(&mut env.context, &setup.vault, &setup.user)
+        .await
+        .unwrap();
+    let state = snapshot(&mut env.context, &setup).await.unwrap();
+    assert!(state.get(&setup.vault.vault).is_some());
+
+    // Branch twice from the same state
+    for amount in [1_000, 250_000] {
+        sender
+            .deposit_as(&mut env, &setup.vault, &setup.user, amount)
+            .await
+            .unwrap()
+            .expect("deposit");
+        let after = get_snapshot(&mut env.context, &setup.vault, &setup.user)
+            .await
+            .unwrap();
+        assert_eq!(after.vault_balance, before.vault_balance + amount);
+
+        restore(&mut env.context, &state).await.unwrap();
+        let restored = get_snapshot(&mut env.context, &setup.vault, &setup.user)
+            .await
+            .unwrap();
+        assert_eq!(restored, before, "deposit of {} not rewound", amount);
+        assert_eq!(snapshot(&mut env.context, &setup).await.unwrap(), state);
+    }
+}
+
+#[tokio::test]
+async fn restore_removes_accounts_created_after_the_snapshot() {
+    let (mut env, setup) = setup_complete_environment(0, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let extra = solana_sdk::pubkey::Pubkey::new_unique();
+
+    let state = snapshot(&mut env.context, &(&setup, extra)).await.unwrap();
+    assert!(state.get(&extra).is_none());
+
+    fund_lamports(&mut *env.context, &extra, 1_000_000)
+        .await
+        .unwrap();
+    restore(&mut env.context, &state).await.unwrap();
+    assert_eq!(
+        env.context.banks_client.get_balance(extra).await.unwrap(),
+        0
+    );
+}
@@ -0,0 +1,299 @@
+//! Model-based state machine tests.
+//!
+//! The vault is described as a state machine over the ERC-4626 reference model
+//! in `fuzz_core::reference`. Transitions are only generated (and, after
+//! shrinking, only kept) while their preconditions hold, every one of them
+//! must then succeed on-chain and pass the `vault_invariants` checks the fuzz
+//! targets use, and after each step every balance must match the model. On
+//! failure proptest shrinks the sequence down to a minimal counterexample,
+//! which the coverage-guided fuzzers cannot do.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test state_machine
+//! ```
+
+use fuzz_helpers::fuzz_core::reference::ReferenceVault;
+use fuzz_helpers::fuzz_core::sequence::Sender;
+use fuzz_helpers::*;
+use proptest::prelude::*;
+use proptest::strategy::Union;
+use proptest_state_machine::{prop_state_machine, ReferenceStateMachine, StateMachineTest};
+use tokio::runtime::Runtime;
+use vault_invariants::{assert_exchange, Exchange};
+
+/// Number of depositors sharing the vault
+const USERS: u8 = 2;
+
+/// Underlying balance every user starts with
+const INITIAL_BALANCE: u64 = 1_000_000_000;
+
+/// Largest single donation into the vault
+const MAX_DONATION: u64 = 1_000_000_000;
+
+#[derive(Debug, Clone)]
+enum Transition {
+    Deposit { user: u8, amount: u64 },
+    Redeem { user: u8, shares: u64 },
+    Donate { amount: u64 },
+}
+
+struct VaultMachine;
+
+impl ReferenceStateMachine for VaultMachine {
+    type State = ReferenceVault;
+    type Transition = Transition;
+
+    fn init_state() -> BoxedStrategy<Self::State> {
+        let mut vault = ReferenceVault::default();
+        for user in 0..USERS {
+            vault.fund(user, INITIAL_BALANCE);
+        }
+        Just(vault).boxed()
+    }
+
+    fn transitions(state: &Self::State) -> BoxedStrategy<Self::Transition> {
+        let mut options = vec![(
+            1,
+            (1..=MAX_DONATION)
+                .prop_map(|amount| Transition::Donate { amount })
+                .boxed(),
+        )];
+
+        for user in 0..USERS {
+            let position = state.position(user);
+            if position.assets > 0 {
+                options.push((
+                    3,
+                    (1..=position.assets)
+                        .prop_map(move |amount| Transition::Deposit { user, amount })
+                        .boxed(),
+                ));
+            }
+            if position.shares > 0 {
+                options.push((
+                    3,
+                    (1..=position.shares)
+                        .prop_map(move |shares| Transition::Redeem { user, shares })
+                        .boxed(),
+                ));
+            }
+        }
+
+        Union::new_weighted(options).boxed()
+    }
+
+    fn preconditions(state: &Self::State, transition: &Self::Transition) -> bool {
+        match *transition {
+            Transition::Deposit { user, amount } => {
+                amount <= state.position(user).assets
+                    && state
+                        .preview_deposit(amount)
+                        .is_ok_and(|quote| quote.floor > 0)
+            }
+            Transition::Redeem { user, shares } => {
+                shares <= state.position(user).shares
+                    && state
+                        .preview_redeem(shares)
+                        .is_ok_and(|quote| quote.floor > 0)
+            }
+            Transition::Donate { .. } => true,
+        }
+    }
+
+    fn apply(mut state: Self::State, transition: &Self::Transition) -> Self::State {
+        match *transition {
+            Transition::Deposit { user, amount } => {
+                state.deposit(user, amount).expect("precondition holds");
+            }
+            Transition::Redeem { user, shares } => {
+                state.redeem(user, shares).expect("precondition holds");
+            }
+            Transition::Donate { amount } => {
+                state.donate(amount).expect("donations stay below u64::MAX");
+            }
+        }
+        state
+    }
+}
+
+struct Vault {
+    runtime: Runtime,
+    env: FuzzTestEnv,
+    setup: CompleteSetup,
+    second: UserAccounts,
+    sender: Sender,
+}
+
+fn user_accounts<'a>(
+    setup: &'a CompleteSetup,
+    second: &'a UserAccounts,
+    index: u8,
+) -> &'a UserAccounts {
+    match index {
+        0 => &setup.user,
+        _ => second,
+    }
+}
+
+struct VaultStateMachineTest;
+
+impl StateMachineTest for VaultStateMachineTest {
+    type SystemUnderTest = Vault;
+    type Reference = VaultMachine;
+
+    fn init_test(_ref_state: &ReferenceVault) -> Self::SystemUnderTest {
+        let runtime = Runtime::new().unwrap();
+        let (env, setup, second) = runtime.block_on(async {
+            let (mut env, setup) =
+                setup_complete_environment(INITIAL_BALANCE, 6, TokenFlavor::SplToken)
+                    .await
+                    .unwrap();
+            let second = setup_user_accounts(
+                &mut env.context,
+                &setup.underlying.mint,
+                &setup.vault.share_mint,
+                TokenFlavor::SplToken,
+            )
+            .await
+            .unwrap();
+            mint_tokens_to_user(
+                &mut env.context,
+                &setup.underlying.mint,
+                &setup.underlying.mint_authority,
+                &second.underlying_token_account,
+                INITIAL_BALANCE,
+            )
+            .await
+            .unwrap();
+            (env, setup, second)
+        });
+
+        Vault {
+            runtime,
+            env,
+            setup,
+            second,
+            sender: Sender::default(),
+        }
+    }
+
+    fn apply(
+        mut vault: Self::SystemUnderTest,
+        ref_state: &ReferenceVault,
+        transition: Transition,
+    ) -> Self::SystemUnderTest {
+        let Vault {
+            runtime,
+            env,
+            setup,
+            second,
+            sender,
+        } = &mut vault;
+
+        match transition {
+            Transition::Deposit { user, amount } => {
+                let accounts = user_accounts(setup, second, user);
+                let before = runtime
+                    .block_on(get_snapshot(&mut env.context, &setup.vault, accounts))
+                    .unwrap();
+                let ix = deposit_ix(&env.program_id, &setup.vault, accounts, amount);
+                let result = runtime
+                    .block_on(sender.send(env, ix, &accounts.owner))
+                    .unwrap();
+                assert!(result.is_ok(), "deposit of {} failed: {:?}", amount, result);
+                let after = runtime
+                    .block_on(get_snapshot(&mut env.context, &setup.vault, accounts))
+                    .unwrap();
+                assert_exchange(&before, &after, Exchange::Deposit { amount });
+            }
+            Transition::Redeem { user, shares } => {
+                let accounts = user_accounts(setup, second, user);
+                let before = runtime
+                    .block_on(get_snapshot(&mut env.context, &setup.vault, accounts))
+                    .unwrap();
+                let ix = redeem_ix(&env.program_id, &setup.vault, accounts, shares);
+                let result = runtime
+                    .block_on(sender.send(env, ix, &accounts.owner))
+                    .unwrap();
+                assert!(result.is_ok(), "redeem of {} failed: {:?}", shares, result);
+                let after = runtime
+                    .block_on(get_snapshot(&mut env.context, &setup.vault, accounts))
+                    .unwrap();
+                assert_exchange(&before, &after, Exchange::Redeem { shares });
+            }
+            Transition::Donate { amount } => {
+                runtime
+                    .block_on(donate_to_vault(
+                        &mut env.context,
+                        &setup.underlying,
+                        &setup.vault,
+                        amount,
+                    ))
+                    .unwrap();
+            }
+        }
+
+        // Postconditions: every balance matches the model after the transition
+        let vault_balance = runtime
+            .block_on(get_token_balance(
+                &mut env.context,
+                &setup.vault.vault_token_account,
+            ))
+            .unwrap();
+        let share_supply = runtime
+            .block_on(get_mint_supply(&mut env.context, &setup.vault.share_mint))
+            .unwrap();
+        assert_eq!(
+            vault_balance, ref_state.total_assets,
+            "total assets diverged"
+        );
+        assert_eq!(
+            share_supply, ref_state.total_supply,
+            "total supply diverged"
+        );
+
+        for index in 0..USERS {
+            let accounts = user_accounts(setup, second, index);
+            let position = ref_state.position(index);
+            let assets = runtime
+                .block_on(get_token_balance(
+                    &mut env.context,
+                    &accounts.underlying_token_account,
+                ))
+                .unwrap();
+            let shares = runtime
+                .block_on(get_token_balance(
+                    &mut env.context,
+                    &accounts.share_token_account,
+                ))
+                .unwrap();
+            assert_eq!(assets, position.assets, "user {} assets diverged", index);
+            assert_eq!(shares, position.shares, "user {} shares diverged", index);
+        }
+
+        vault
+    }
+
+    fn check_invariants(_vault: &Self::SystemUnderTest, ref_state: &ReferenceVault) {
+        // Outstanding shares are always backed by assets
+        if ref_state.total_supply > 0 {
+            assert!(
+                ref_state.total_assets > 0,
+                "shares outstanding with an empty vault"
+            );
+        }
+    }
+}
+
+prop_state_machine! {
+    #![proptest_config(ProptestConfig {
+        cases: 16,
+        .. ProptestConfig::default()
+    })]
+
+    #[test]
+    fn vault_matches_reference_model(sequential 1..24 => VaultStateMachineTest);
+}
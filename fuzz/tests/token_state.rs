@@ -0,0 +1,236 @@
+//! Token state helpers against SPL Token and Token-2022 accounts.
+//!
+//! `get_token_balance` and `get_mint_supply` unpack through
+//! `StateWithExtensions`, so they read Token-2022 accounts that carry
+//! extensions as well as plain SPL Token accounts.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test token_state
+//! ```
+
+use fuzz_helpers::*;
+use solana_sdk::program_option::COption;
+use solana_sdk::signature::{Keypair, Signer};
+use spl_token_2022::extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions};
+
+#[tokio::test]
+async fn spl_token_state_unpacks() {
+    let mut env = setup_program_test().await;
+    let underlying = setup_underlying_mint(&mut env.context, 6, TokenFlavor::SplToken)
+        .await
+        .unwrap();
+    let owner = Keypair::new().pubkey();
+    let account = setup_token_account(
+        &mut env.context,
+        &underlying.mint,
+        &owner,
+        TokenFlavor::SplToken,
+    )
+    .await
+    .unwrap();
+    mint_tokens_to_user(
+        &mut env.context,
+        &underlying.mint,
+        &underlying.mint_authority,
+        &account,
+        1_000,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut env.context, &account).await.unwrap(),
+        1_000
+    );
+    assert_eq!(
+        get_mint_supply(&mut env.context, &underlying.mint)
+            .await
+            .unwrap(),
+        1_000
+    );
+
+    let state = get_token_account_state(&mut env.context, &account)
+        .await
+        .unwrap();
+    assert_eq!(state.owner, owner);
+    assert_eq!(state.delegate, COption::None);
+    assert!(!state.is_frozen());
+
+    let mint = get_mint_state(&mut env.context, &underlying.mint)
+        .await
+        .unwrap();
+    assert_eq!(mint.decimals, 6);
+    assert_eq!(
+        mint.mint_authority,
+        COption::Some(underlying.mint_authority.pubkey())
+    );
+}
+
+#[tokio::test]
+async fn token_2022_state_with_extensions_unpacks() {
+    let mut env = setup_program_test().await;
+    let extensions = MintExtensions {
+        transfer_fee: Some((100, 1_000)),
+        default_frozen: true,
+        ..MintExtensions::default()
+    };
+    let underlying = setup_token_2022_mint(&mut env.context, 9, &extensions)
+        .await
+        .unwrap();
+    let owner = Keypair::new().pubkey();
+    let account = setup_token_2022_account(&mut env.context, &underlying.mint, &owner)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut env.context, &account).await.unwrap(),
+        0
+    );
+    assert_eq!(
+        get_mint_supply(&mut env.context, &underlying.mint)
+            .await
+            .unwrap(),
+        0
+    );
+
+    let state = get_token_account_state(&mut env.context, &account)
+        .await
+        .unwrap();
+    assert_eq!(state.mint, underlying.mint);
+    assert!(state.is_frozen(), "default account state was not applied");
+
+    let mint = get_mint_state(&mut env.context, &underlying.mint)
+        .await
+        .unwrap();
+    assert_eq!(mint.decimals, 9);
+}
+
+#[tokio::test]
+async fn freeze_helpers_toggle_account_state() {
+    for flavor in [TokenFlavor::SplToken, TokenFlavor::Token2022] {
+        let mut env = setup_program_test().await;
+        let underlying = setup_underlying_mint(&mut env.context, 6, flavor)
+            .await
+            .unwrap();
+        let owner = Keypair::new().pubkey();
+        let account = setup_token_account(&mut env.context, &underlying.mint, &owner, flavor)
+            .await
+            .unwrap();
+
+        // Seeded mints have no freeze authority until one is granted
+        let mint = get_mint_state(&mut env.context, &underlying.mint)
+            .await
+            .unwrap();
+        assert_eq!(mint.freeze_authority, COption::None);
+        grant_freeze_authority(&mut env.context, &underlying)
+            .await
+            .unwrap();
+        let mint = get_mint_state(&mut env.context, &underlying.mint)
+            .await
+            .unwrap();
+        assert_eq!(
+            mint.freeze_authority,
+            COption::Some(underlying.mint_authority.pubkey())
+        );
+
+        freeze_token_account(&mut env.context, &underlying, &account)
+            .await
+            .unwrap();
+        let state = get_token_account_state(&mut env.context, &account)
+            .await
+            .unwrap();
+        assert!(state.is_frozen(), "{flavor:?}");
+
+        thaw_token_account(&mut env.context, &underlying, &account)
+            .await
+            .unwrap();
+        let state = get_token_account_state(&mut env.context, &account)
+            .await
+            .unwrap();
+        assert!(!state.is_frozen(), "{flavor:?}");
+    }
+}
+
+#[tokio::test]
+async fn edge_token_accounts_unpack() {
+    for flavor in TokenFlavor::ALL {
+        let mut env = setup_program_test().await;
+        let underlying = setup_underlying_mint(&mut env.context, 6, flavor)
+            .await
+            .unwrap();
+        let owner = Keypair::new().pubkey();
+        let states = [
+            EdgeAccountState::Delegated {
+                delegated_amount: u64::MAX,
+            },
+            EdgeAccountState::CloseAuthority,
+            EdgeAccountState::ImmutableOwner,
+            EdgeAccountState::Native { lamports: 5_000 },
+            EdgeAccountState::ZeroRentExcess,
+        ];
+
+        for edge in states {
+            let created =
+                setup_edge_token_account(&mut env.context, &underlying.mint, &owner, flavor, edge)
+                    .await
+                    .unwrap();
+            let state = get_token_account_state(&mut env.context, &created.address)
+                .await
+                .unwrap();
+            let raw = env
+                .context
+                .banks_client
+                .get_account(created.address)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(raw.owner, flavor.program_id(), "{flavor:?} {edge:?}");
+            assert_eq!(state.owner, owner, "{flavor:?} {edge:?}");
+            assert_eq!(state.mint, created.mint, "{flavor:?} {edge:?}");
+            let authority = created.authority.as_ref().map(|key| key.pubkey());
+
+            match edge {
+                EdgeAccountState::Delegated { delegated_amount } => {
+                    assert_eq!(state.delegate, COption::from(authority));
+                    assert_eq!(state.delegated_amount, delegated_amount);
+                }
+                EdgeAccountState::CloseAuthority => {
+                    assert_eq!(state.close_authority, COption::from(authority));
+                }
+                EdgeAccountState::ImmutableOwner => {
+                    let extensions =
+                        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&raw.data)
+                            .unwrap()
+                            .get_extension_types()
+                            .unwrap();
+                    let expected = match flavor {
+                        TokenFlavor::SplToken => vec![],
+                        TokenFlavor::Token2022 => vec![ExtensionType::ImmutableOwner],
+                    };
+                    assert_eq!(extensions, expected, "{flavor:?}");
+                }
+                EdgeAccountState::Native { .. } | EdgeAccountState::ZeroRentExcess => {
+                    assert_eq!(created.mint, flavor.native_mint());
+                    let COption::Some(reserve) = state.is_native else {
+                        panic!("{flavor:?} {edge:?} is not native");
+                    };
+                    assert_eq!(raw.lamports - reserve, state.amount, "{flavor:?} {edge:?}");
+                    let expected = match edge {
+                        EdgeAccountState::Native { lamports } => lamports as u64,
+                        _ => 0,
+                    };
+                    assert_eq!(state.amount, expected, "{flavor:?} {edge:?}");
+                }
+            }
+            if !matches!(
+                edge,
+                EdgeAccountState::Delegated { .. } | EdgeAccountState::CloseAuthority
+            ) {
+                assert!(created.authority.is_none(), "{flavor:?} {edge:?}");
+            }
+        }
+    }
+}
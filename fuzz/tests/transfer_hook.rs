@@ -0,0 +1,218 @@
+//! Token-2022 mints with a transfer hook that actually runs.
+//!
+//! `setup_program_test` loads both `transfer-hook-mock` variants, and
+//! `setup_hooked_mint` creates an underlying mint pointed at one of them. The
+//! first tests transfer such a mint directly, listing the hook program, to
+//! show each variant is called and decides the transfer. The last one hands
+//! hooked mints to the vault: its `transfer_checked` CPI does not pass the
+//! hook program along, so Token-2022 cannot call the hook and the deposit
+//! fails whichever variant the mint names.
+//!
+//! Requires the program to be built first:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-pda-fuzz --test transfer_hook
+//! ```
+
+use fuzz_helpers::fuzz_core::sequence::Sender;
+use fuzz_helpers::*;
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+use spl_token_2022::instruction as token_2022_instruction;
+use transfer_hook_mock::{Variant, REJECTED};
+
+const BALANCE: u64 = 1_000_000;
+
+/// A hooked mint and two of its accounts, the first holding `BALANCE`
+struct Hooked {
+    underlying: UnderlyingMintAccounts,
+    owner: Keypair,
+    source: Pubkey,
+    destination: Pubkey,
+}
+
+async fn hooked(env: &mut FuzzTestEnv, variant: Variant) -> Hooked {
+    let underlying = setup_hooked_mint(&mut env.context, 6, variant)
+        .await
+        .unwrap();
+    let owner = new_keypair();
+    fund_account(&mut *env.context, &owner.pubkey())
+        .await
+        .unwrap();
+    let source = setup_token_2022_account(&mut env.context, &underlying.mint, &owner.pubkey())
+        .await
+        .unwrap();
+    let destination = setup_token_2022_account(&mut env.context, &underlying.mint, &owner.pubkey())
+        .await
+        .unwrap();
+    mint_tokens_to_user(
+        &mut env.context,
+        &underlying.mint,
+        &underlying.mint_authority,
+        &source,
+        BALANCE,
+    )
+    .await
+    .unwrap();
+    Hooked {
+        underlying,
+        owner,
+        source,
+        destination,
+    }
+}
+
+/// A `transfer_checked` of `amount`, listing the hook program when `variant`
+/// is given
+fn transfer_ix(hooked: &Hooked, amount: u64, variant: Option<Variant>) -> Instruction {
+    let mut ix = token_2022_instruction::transfer_checked(
+        &spl_token_2022::id(),
+        &hooked.source,
+        &hooked.underlying.mint,
+        &hooked.destination,
+        &hooked.owner.pubkey(),
+        &[],
+        amount,
+        hooked.underlying.decimals,
+    )
+    .unwrap();
+    if let Some(variant) = variant {
+        ix.accounts
+            .push(AccountMeta::new_readonly(variant.program_id(), false));
+    }
+    ix
+}
+
+/// The error the first failing instruction of `result` returned
+#[track_caller]
+fn instruction_error(result: Result<(), BanksClientError>) -> InstructionError {
+    match result.expect_err("expected the transaction to fail") {
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, error))
+        | BanksClientError::SimulationError {
+            err: TransactionError::InstructionError(_, error),
+            ..
+        } => error,
+        other => panic!("expected an instruction error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn benign_hook_allows_transfers() {
+    let mut env = setup_program_test().await;
+    let mut sender = Sender::default();
+    let hooked = hooked(&mut env, Variant::Benign).await;
+
+    let ix = transfer_ix(&hooked, 1_000, Some(Variant::Benign));
+    sender
+        .send(&mut env, ix, &hooked.owner)
+        .await
+        .unwrap()
+        .expect("hooked transfer");
+
+    let source = get_token_balance(&mut env.context, &hooked.source)
+        .await
+        .unwrap();
+    let destination = get_token_balance(&mut env.context, &hooked.destination)
+        .await
+        .unwrap();
+    assert_eq!(source, BALANCE - 1_000);
+    assert_eq!(destination, 1_000);
+}
+
+#[tokio::test]
+async fn malicious_hook_rejects_transfers() {
+    let mut env = setup_program_test().await;
+    let mut sender = Sender::default();
+    let hooked = hooked(&mut env, Variant::Malicious).await;
+
+    let ix = transfer_ix(&hooked, 1_000, Some(Variant::Malicious));
+    let result = sender.send(&mut env, ix, &hooked.owner).await.unwrap();
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::Custom(REJECTED)
+    );
+
+    let source = get_token_balance(&mut env.context, &hooked.source)
+        .await
+        .unwrap();
+    assert_eq!(source, BALANCE);
+}
+
+#[tokio::test]
+async fn transfer_must_list_the_hook_program() {
+    for variant in Variant::ALL {
+        let mut env = setup_program_test().await;
+        let mut sender = Sender::default();
+        let hooked = hooked(&mut env, variant).await;
+
+        let ix = transfer_ix(&hooked, 1_000, None);
+        let result = sender.send(&mut env, ix, &hooked.owner).await.unwrap();
+        assert_eq!(
+            instruction_error(result),
+            InstructionError::MissingAccount,
+            "{variant:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn vault_cannot_move_hooked_tokens() {
+    for variant in Variant::ALL {
+        let mut env = setup_program_test().await;
+        let program_id = env.program_id;
+        let mut sender = Sender::default();
+        let protocol = setup_protocol(&mut env.context, &program_id).await.unwrap();
+        let hooked = hooked(&mut env, variant).await;
+        let token_program = spl_token_2022::id();
+
+        // Creating the vault transfers nothing, so the hook is not involved
+        let ix = with_token_program(
+            initialize_vault_ix(&program_id, &hooked.underlying.mint, &protocol.owner),
+            &token_program,
+        );
+        sender
+            .send(&mut env, ix, &protocol.owner_keypair)
+            .await
+            .unwrap()
+            .expect("initialize_vault over a hooked mint");
+        let vault = derive_vault_pda(&program_id, &hooked.underlying.mint).0;
+        let vault = VaultAccounts {
+            vault,
+            vault_token_account: derive_vault_token_account_pda(&program_id, &vault).0,
+            share_mint: derive_share_mint_pda(&program_id, &vault).0,
+            underlying_mint: hooked.underlying.mint,
+            token_program,
+        };
+        let user = UserAccounts {
+            owner: hooked.owner.insecure_clone(),
+            underlying_token_account: hooked.source,
+            share_token_account: setup_token_2022_account(
+                &mut env.context,
+                &vault.share_mint,
+                &hooked.owner.pubkey(),
+            )
+            .await
+            .unwrap(),
+        };
+
+        let result = sender
+            .deposit_as(&mut env, &vault, &user, 1_000)
+            .await
+            .unwrap();
+        assert_eq!(
+            instruction_error(result),
+            InstructionError::MissingAccount,
+            "{variant:?}"
+        );
+        let balance = get_token_balance(&mut env.context, &hooked.source)
+            .await
+            .unwrap();
+        assert_eq!(balance, BALANCE, "{variant:?}");
+    }
+}
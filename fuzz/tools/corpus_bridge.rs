@@ -0,0 +1,48 @@
+//! Copies a corpus produced by one fuzzing engine into another engine's seed
+//! directory.
+//!
+//! ```bash
+//! # libFuzzer corpus -> AFL++ seeds
+//! cargo run --bin corpus_bridge -- corpus/fuzz_deposit_libfuzzer afl/fuzz_deposit/in
+//!
+//! # AFL++ queue -> honggfuzz input
+//! cargo run --bin corpus_bridge -- afl/fuzz_deposit/out/default/queue \
+//!     hfuzz_workspace/fuzz_deposit/input
+//! ```
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use fuzz_helpers::fuzz_core::corpus::{bridge, AFL_MAX_LEN};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (src, dst, max_len) = match args.as_slice() {
+        [src, dst] => (src, dst, AFL_MAX_LEN),
+        [src, dst, max_len] => match max_len.parse() {
+            Ok(max_len) => (src, dst, max_len),
+            Err(_) => {
+                eprintln!("invalid max length: {}", max_len);
+                return ExitCode::FAILURE;
+            }
+        },
+        _ => {
+            eprintln!("usage: corpus_bridge <src_dir> <dst_dir> [max_len]");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match bridge(&PathBuf::from(src), &PathBuf::from(dst), max_len) {
+        Ok(stats) => {
+            println!(
+                "copied {} inputs ({} duplicates, {} over {} bytes skipped)",
+                stats.copied, stats.duplicates, stats.oversized, max_len
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("bridge failed: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Replays a corpus and keeps the shortest input per distinct set of
+//! scenarios reached, so fuzzing starts from a small, deduplicated corpus.
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo run --bin minimize -- \
+//!     corpus/fuzz_deposit_libfuzzer corpus/fuzz_deposit_libfuzzer.min
+//! ```
+//!
+//! The fuzz body is picked from the source directory name unless given as a
+//! third argument (`deposit`, `redeem`, ...).
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use fuzz_helpers::fuzz_core::corpus::{minimize, target_module};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (src, dst, target) = match args.as_slice() {
+        [src, dst] => {
+            let name = PathBuf::from(src)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            (src, dst, target_module(&name).to_string())
+        }
+        [src, dst, target] => (src, dst, target.clone()),
+        _ => {
+            eprintln!("usage: minimize <src_dir> <dst_dir> [target]");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match minimize(&PathBuf::from(src), &PathBuf::from(dst), &target) {
+        Ok(stats) => {
+            for (signature, count) in &stats.signatures {
+                println!("{:>6}  {}", count, signature);
+            }
+            println!(
+                "kept {} of {} inputs through {}",
+                stats.kept, stats.inputs, target
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("minimize failed: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
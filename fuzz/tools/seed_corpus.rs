@@ -0,0 +1,237 @@
+//! Generates seed inputs from the program's Anchor IDL.
+//!
+//! Every integer argument of every instruction gets the boundary values of its
+//! type (0, 1, powers of ten, the midpoint and the maximum). Instructions with
+//! a fuzz body taking plain integer inputs (`deposit`, `redeem`, and the pure
+//! kernels both feed) get one seed per boundary, written into
+//! `<out>/fuzz_<target>_libfuzzer` with content-hash names as `corpus_bridge`
+//! names them. Each seed is decoded back and compared with the intended input
+//! before it is written, so a change in the `arbitrary` encoding drops seeds
+//! instead of writing misleading ones.
+//!
+//! ```bash
+//! anchor build
+//! cargo run --bin seed_corpus -- ../target/idl/vault_pda.json corpus
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use fuzz_helpers::fuzz_core::corpus::decode;
+use fuzz_helpers::fuzz_core::deposit::DepositFuzzInput;
+use fuzz_helpers::fuzz_core::kernel::KernelFuzzInput;
+use fuzz_helpers::fuzz_core::redeem::RedeemFuzzInput;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Token decimals worth seeding: none, the common ones and the largest
+const DECIMALS: [u8; 4] = [0, 6, 9, 18];
+
+/// Boundary values for an IDL integer type, widened to `u64`
+fn boundaries(ty: &str) -> Vec<u64> {
+    let max = match ty {
+        "u8" => u8::MAX as u64,
+        "u16" => u16::MAX as u64,
+        "u32" => u32::MAX as u64,
+        "u64" | "u128" => u64::MAX,
+        _ => return Vec::new(),
+    };
+    let mut values = vec![0, 1, 2, max / 2, max - 1, max];
+    values.extend((1..20).map(|exp| 10u64.pow(exp)).filter(|v| *v < max));
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+/// Little-endian field encoding, as `arbitrary` decodes integers and bools
+#[derive(Default)]
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn u64(mut self, value: u64) -> Self {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn u8(mut self, value: u8) -> Self {
+        self.0.push(value);
+        self
+    }
+
+    fn bool(self, value: bool) -> Self {
+        self.u8(value as u8)
+    }
+}
+
+/// Collects verified seeds per target directory
+#[derive(Default)]
+struct Seeds {
+    by_target: BTreeMap<&'static str, Vec<Vec<u8>>>,
+    rejected: usize,
+}
+
+impl Seeds {
+    /// Keeps `bytes` if they decode back to exactly `intended`
+    fn push<T>(&mut self, target: &'static str, intended: &T, bytes: Encoder)
+    where
+        T: Serialize + for<'a> arbitrary::Arbitrary<'a>,
+    {
+        let decoded = decode::<T>(&bytes.0).and_then(|d| serde_json::to_value(d).ok());
+        if decoded.is_some() && decoded == serde_json::to_value(intended).ok() {
+            self.by_target.entry(target).or_default().push(bytes.0);
+        } else {
+            self.rejected += 1;
+        }
+    }
+}
+
+fn deposit_seed(seeds: &mut Seeds, amount: u64, decimals: u8, with_position: bool) {
+    let input = DepositFuzzInput {
+        amount,
+        initial_balance: amount,
+        decimals,
+        yield_amount: 0,
+        do_initial_deposit: with_position,
+        initial_deposit_amount: 1_000_000,
+    };
+    let bytes = Encoder::default()
+        .u64(input.amount)
+        .u64(input.initial_balance)
+        .u8(input.decimals)
+        .u64(input.yield_amount)
+        .bool(input.do_initial_deposit)
+        .u64(input.initial_deposit_amount);
+    seeds.push("fuzz_deposit_libfuzzer", &input, bytes);
+}
+
+fn redeem_seed(seeds: &mut Seeds, shares: u64, decimals: u8, with_yield: bool) {
+    let input = RedeemFuzzInput {
+        deposit_amount: 1_000_000,
+        decimals,
+        yield_amount: if with_yield { 1_000 } else { 0 },
+        shares,
+        do_second_redeem: false,
+        second_shares: 0,
+    };
+    let bytes = Encoder::default()
+        .u64(input.deposit_amount)
+        .u8(input.decimals)
+        .u64(input.yield_amount)
+        .u64(input.shares)
+        .bool(input.do_second_redeem)
+        .u64(input.second_shares);
+    seeds.push("fuzz_redeem_libfuzzer", &input, bytes);
+}
+
+fn kernel_seed(seeds: &mut Seeds, amount: u64, total: u64) {
+    let input = KernelFuzzInput {
+        amount,
+        redeem_shares: amount,
+        total_shares: total,
+        total_assets: total,
+    };
+    let bytes = Encoder::default()
+        .u64(input.amount)
+        .u64(input.redeem_shares)
+        .u64(input.total_shares)
+        .u64(input.total_assets);
+    seeds.push("fuzz_kernel_libfuzzer", &input, bytes);
+}
+
+fn write_seeds(out: &Path, seeds: &Seeds) -> std::io::Result<()> {
+    for (target, inputs) in &seeds.by_target {
+        let dir = out.join(target);
+        fs::create_dir_all(&dir)?;
+        for data in inputs {
+            let mut hasher = DefaultHasher::new();
+            data.hash(&mut hasher);
+            fs::write(dir.join(format!("{:016x}", hasher.finish())), data)?;
+        }
+        println!("{:>5} seeds -> {}", inputs.len(), dir.display());
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (idl, out) = match args.as_slice() {
+        [idl, out] => (PathBuf::from(idl), PathBuf::from(out)),
+        _ => {
+            eprintln!("usage: seed_corpus <idl.json> <corpus_dir>");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let idl: Value = match fs::read(&idl)
+        .map_err(|e| e.to_string())
+        .and_then(|data| serde_json::from_slice(&data).map_err(|e| e.to_string()))
+    {
+        Ok(idl) => idl,
+        Err(e) => {
+            eprintln!("failed to read {}: {}", idl.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut seeds = Seeds::default();
+    let instructions = idl["instructions"].as_array().cloned().unwrap_or_default();
+    for instruction in &instructions {
+        let name = instruction["name"].as_str().unwrap_or_default();
+        let args = instruction["args"].as_array().cloned().unwrap_or_default();
+        let integer_args: Vec<(&str, Vec<u64>)> = args
+            .iter()
+            .filter_map(|arg| {
+                let values = boundaries(arg["type"].as_str()?);
+                (!values.is_empty()).then(|| (arg["name"].as_str().unwrap_or_default(), values))
+            })
+            .collect();
+
+        match (name, integer_args.as_slice()) {
+            ("deposit", [(_, amounts)]) => {
+                for &amount in amounts {
+                    for decimals in DECIMALS {
+                        deposit_seed(&mut seeds, amount, decimals, false);
+                        deposit_seed(&mut seeds, amount, decimals, true);
+                    }
+                    kernel_seed(&mut seeds, amount, 1_000_000);
+                }
+            }
+            ("redeem", [(_, shares)]) => {
+                for &shares in shares {
+                    for decimals in DECIMALS {
+                        redeem_seed(&mut seeds, shares, decimals, false);
+                        redeem_seed(&mut seeds, shares, decimals, true);
+                    }
+                    kernel_seed(&mut seeds, 1_000_000, shares);
+                }
+            }
+            (_, args) => println!(
+                "{}: no integer-input fuzz body ({} integer args), skipped",
+                name,
+                args.len()
+            ),
+        }
+    }
+
+    if seeds.by_target.is_empty() {
+        eprintln!("no seeds generated; is this the vault_pda IDL?");
+        return ExitCode::FAILURE;
+    }
+    if seeds.rejected > 0 {
+        eprintln!(
+            "{} seeds did not decode back to their intended input and were dropped",
+            seeds.rejected
+        );
+    }
+
+    match write_seeds(&out, &seeds) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("failed to write seeds: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Read-only JSON API over the index.
+//!
+//! ```text
+//! GET /status                      indexed transactions and latest slot
+//! GET /vaults                      every vault's name, latest totals and flow counts
+//! GET /vaults/<vault>/deposits     newest first, `?limit=N` (100 by default)
+//! GET /vaults/<vault>/redeems
+//! GET /vaults/<vault>/reports      profit reports and the treasury's cut
+//! GET /vaults/<vault>/prices       share price after every deposit, redeem and report
+//! GET /captures                    flag captures in order
+//! ```
+
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::db::Db;
+
+const DEFAULT_LIMIT: u32 = 100;
+const MAX_LIMIT: u32 = 1_000;
+
+/// Serves requests on `server` from `db` until the server shuts down
+pub fn serve(server: Server, db: Db) {
+    for request in server.incoming_requests() {
+        let (status, body) = match handle(&db, &request) {
+            Ok(Some(body)) => (200, body),
+            Ok(None) => (404, json!({ "error": "not found" })),
+            Err(e) => (500, json!({ "error": e.to_string() })),
+        };
+        let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(header);
+        if let Err(e) = request.respond(response) {
+            eprintln!("indexer: failed to respond: {e}");
+        }
+    }
+}
+
+fn handle(db: &Db, request: &Request) -> rusqlite::Result<Option<Value>> {
+    if *request.method() != Method::Get {
+        return Ok(None);
+    }
+    let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+    let limit = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("limit="))
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(DEFAULT_LIMIT)
+        .min(MAX_LIMIT);
+
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let body = match segments.as_slice() {
+        ["status"] => db.status()?,
+        ["vaults"] => db.vaults()?,
+        ["vaults", vault, "deposits"] => db.deposits(vault, limit)?,
+        ["vaults", vault, "redeems"] => db.redeems(vault, limit)?,
+        ["vaults", vault, "reports"] => db.reports(vault, limit)?,
+        ["vaults", vault, "prices"] => db.prices(vault, limit)?,
+        ["captures"] => db.flag_captures()?,
+        _ => return Ok(None),
+    };
+    Ok(Some(body))
+}
@@ -0,0 +1,498 @@
+//! SQLite schema and queries.
+//!
+//! Every transaction that mentions the program is recorded once, so backfill
+//! and the live subscription can overlap without double counting. Price
+//! checkpoints are not stored separately: each deposit, redeem, report and
+//! skim event carries the vault's totals right after it.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde_json::{json, Value};
+
+use vault_pda_events::VaultEvent;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    failed INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS deposits (
+    signature TEXT NOT NULL,
+    idx INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    vault TEXT NOT NULL,
+    depositor TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    shares INTEGER NOT NULL,
+    total_assets INTEGER NOT NULL,
+    total_shares INTEGER NOT NULL,
+    PRIMARY KEY (signature, idx)
+);
+CREATE TABLE IF NOT EXISTS redeems (
+    signature TEXT NOT NULL,
+    idx INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    vault TEXT NOT NULL,
+    redeemer TEXT NOT NULL,
+    shares INTEGER NOT NULL,
+    amount INTEGER NOT NULL,
+    total_assets INTEGER NOT NULL,
+    total_shares INTEGER NOT NULL,
+    PRIMARY KEY (signature, idx)
+);
+CREATE TABLE IF NOT EXISTS flag_captures (
+    signature TEXT NOT NULL,
+    idx INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    flag TEXT NOT NULL,
+    vault TEXT NOT NULL,
+    captor TEXT NOT NULL,
+    condition TEXT NOT NULL,
+    PRIMARY KEY (signature, idx)
+);
+CREATE TABLE IF NOT EXISTS reports (
+    signature TEXT NOT NULL,
+    idx INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    vault TEXT NOT NULL,
+    treasury TEXT NOT NULL,
+    profit INTEGER NOT NULL,
+    reserve INTEGER NOT NULL,
+    shares INTEGER NOT NULL,
+    total_assets INTEGER NOT NULL,
+    total_shares INTEGER NOT NULL,
+    PRIMARY KEY (signature, idx)
+);
+CREATE TABLE IF NOT EXISTS skims (
+    signature TEXT NOT NULL,
+    idx INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    vault TEXT NOT NULL,
+    treasury TEXT NOT NULL,
+    profit INTEGER NOT NULL,
+    reserve INTEGER NOT NULL,
+    total_assets INTEGER NOT NULL,
+    total_shares INTEGER NOT NULL,
+    PRIMARY KEY (signature, idx)
+);
+CREATE TABLE IF NOT EXISTS vault_names (
+    vault TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    slot INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS deposits_vault ON deposits (vault, slot);
+CREATE INDEX IF NOT EXISTS redeems_vault ON redeems (vault, slot);
+CREATE INDEX IF NOT EXISTS reports_vault ON reports (vault, slot);
+CREATE INDEX IF NOT EXISTS skims_vault ON skims (vault, slot);
+CREATE VIEW IF NOT EXISTS price_checkpoints AS
+    SELECT signature, idx, slot, vault, total_assets, total_shares FROM deposits
+    UNION ALL
+    SELECT signature, idx, slot, vault, total_assets, total_shares FROM redeems
+    UNION ALL
+    SELECT signature, idx, slot, vault, total_assets, total_shares FROM reports
+    UNION ALL
+    SELECT signature, idx, slot, vault, total_assets, total_shares FROM skims;
+";
+
+pub struct Db {
+    conn: Connection,
+}
+
+impl Db {
+    /// Opens (or creates) the database at `path`
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        // Lets the API read while the indexer writes
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn has_transaction(&self, signature: &str) -> rusqlite::Result<bool> {
+        self.conn
+            .query_row(
+                "SELECT 1 FROM transactions WHERE signature = ?1",
+                [signature],
+                |_| Ok(()),
+            )
+            .optional()
+            .map(|row| row.is_some())
+    }
+
+    /// Records a transaction and its events, returning false if it was
+    /// already indexed. A failed transaction's events were rolled back, so
+    /// pass none.
+    pub fn index_transaction(
+        &mut self,
+        signature: &str,
+        slot: u64,
+        failed: bool,
+        events: &[VaultEvent],
+    ) -> rusqlite::Result<bool> {
+        let tx = self.conn.transaction()?;
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot, failed) VALUES (?1, ?2, ?3)",
+            params![signature, slot, failed],
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        for (idx, event) in events.iter().enumerate() {
+            match event {
+                VaultEvent::Deposit(e) => tx.execute(
+                    "INSERT INTO deposits VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        signature,
+                        idx,
+                        slot,
+                        e.vault.to_string(),
+                        e.depositor.to_string(),
+                        e.amount,
+                        e.shares,
+                        e.total_assets,
+                        e.total_shares
+                    ],
+                )?,
+                VaultEvent::Redeem(e) => tx.execute(
+                    "INSERT INTO redeems VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        signature,
+                        idx,
+                        slot,
+                        e.vault.to_string(),
+                        e.redeemer.to_string(),
+                        e.shares,
+                        e.amount,
+                        e.total_assets,
+                        e.total_shares
+                    ],
+                )?,
+                VaultEvent::FlagCaptured(e) => tx.execute(
+                    "INSERT INTO flag_captures VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        signature,
+                        idx,
+                        slot,
+                        e.flag.to_string(),
+                        e.vault.to_string(),
+                        e.captor.to_string(),
+                        format!("{:?}", e.condition)
+                    ],
+                )?,
+                VaultEvent::Report(e) => tx.execute(
+                    "INSERT INTO reports VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    params![
+                        signature,
+                        idx,
+                        slot,
+                        e.vault.to_string(),
+                        e.treasury.to_string(),
+                        e.profit,
+                        e.reserve,
+                        e.shares,
+                        e.total_assets,
+                        e.total_shares
+                    ],
+                )?,
+                VaultEvent::Skim(e) => tx.execute(
+                    "INSERT INTO skims VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        signature,
+                        idx,
+                        slot,
+                        e.vault.to_string(),
+                        e.treasury.to_string(),
+                        e.profit,
+                        e.reserve,
+                        e.total_assets,
+                        e.total_shares
+                    ],
+                )?,
+                // Backfill can index a rename before the name it replaced
+                VaultEvent::VaultNamed(e) => tx.execute(
+                    "INSERT INTO vault_names VALUES (?1, ?2, ?3)
+                     ON CONFLICT (vault) DO UPDATE SET name = excluded.name, slot = excluded.slot
+                     WHERE excluded.slot >= vault_names.slot",
+                    params![e.vault.to_string(), e.name(), slot],
+                )?,
+            };
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Indexed transaction count and the latest slot
+    pub fn status(&self) -> rusqlite::Result<Value> {
+        self.conn
+            .query_row("SELECT COUNT(*), MAX(slot) FROM transactions", [], |row| {
+                Ok(json!({
+                    "transactions": row.get::<_, u64>(0)?,
+                    "last_slot": row.get::<_, Option<u64>>(1)?,
+                }))
+            })
+    }
+
+    /// Every vault with its name, flow counts and latest checkpoint
+    pub fn vaults(&self) -> rusqlite::Result<Value> {
+        self.query(
+            "SELECT p.vault, p.slot, p.total_assets, p.total_shares,
+                (SELECT COUNT(*) FROM deposits d WHERE d.vault = p.vault),
+                (SELECT COUNT(*) FROM redeems r WHERE r.vault = p.vault),
+                (SELECT name FROM vault_names n WHERE n.vault = p.vault)
+             FROM price_checkpoints p
+             WHERE (p.slot, p.signature, p.idx) = (
+                SELECT slot, signature, idx FROM price_checkpoints
+                WHERE vault = p.vault ORDER BY slot DESC, signature DESC, idx DESC LIMIT 1)
+             ORDER BY p.vault",
+            [],
+            |row| {
+                let total_assets: u64 = row.get(2)?;
+                let total_shares: u64 = row.get(3)?;
+                Ok(json!({
+                    "vault": row.get::<_, String>(0)?,
+                    "name": row.get::<_, Option<String>>(6)?,
+                    "slot": row.get::<_, u64>(1)?,
+                    "total_assets": total_assets,
+                    "total_shares": total_shares,
+                    "share_price": share_price(total_assets, total_shares),
+                    "deposits": row.get::<_, u64>(4)?,
+                    "redeems": row.get::<_, u64>(5)?,
+                }))
+            },
+        )
+    }
+
+    /// A vault's most recent deposits, newest first
+    pub fn deposits(&self, vault: &str, limit: u32) -> rusqlite::Result<Value> {
+        self.query(
+            "SELECT signature, slot, depositor, amount, shares, total_assets, total_shares
+             FROM deposits WHERE vault = ?1 ORDER BY slot DESC, idx DESC LIMIT ?2",
+            params![vault, limit],
+            |row| {
+                Ok(json!({
+                    "signature": row.get::<_, String>(0)?,
+                    "slot": row.get::<_, u64>(1)?,
+                    "depositor": row.get::<_, String>(2)?,
+                    "amount": row.get::<_, u64>(3)?,
+                    "shares": row.get::<_, u64>(4)?,
+                    "total_assets": row.get::<_, u64>(5)?,
+                    "total_shares": row.get::<_, u64>(6)?,
+                }))
+            },
+        )
+    }
+
+    /// A vault's most recent redeems, newest first
+    pub fn redeems(&self, vault: &str, limit: u32) -> rusqlite::Result<Value> {
+        self.query(
+            "SELECT signature, slot, redeemer, shares, amount, total_assets, total_shares
+             FROM redeems WHERE vault = ?1 ORDER BY slot DESC, idx DESC LIMIT ?2",
+            params![vault, limit],
+            |row| {
+                Ok(json!({
+                    "signature": row.get::<_, String>(0)?,
+                    "slot": row.get::<_, u64>(1)?,
+                    "redeemer": row.get::<_, String>(2)?,
+                    "shares": row.get::<_, u64>(3)?,
+                    "amount": row.get::<_, u64>(4)?,
+                    "total_assets": row.get::<_, u64>(5)?,
+                    "total_shares": row.get::<_, u64>(6)?,
+                }))
+            },
+        )
+    }
+
+    /// A vault's most recent profit reports, newest first
+    pub fn reports(&self, vault: &str, limit: u32) -> rusqlite::Result<Value> {
+        self.query(
+            "SELECT signature, slot, treasury, profit, reserve, shares, total_assets, total_shares
+             FROM reports WHERE vault = ?1 ORDER BY slot DESC, idx DESC LIMIT ?2",
+            params![vault, limit],
+            |row| {
+                Ok(json!({
+                    "signature": row.get::<_, String>(0)?,
+                    "slot": row.get::<_, u64>(1)?,
+                    "treasury": row.get::<_, String>(2)?,
+                    "profit": row.get::<_, u64>(3)?,
+                    "reserve": row.get::<_, u64>(4)?,
+                    "shares": row.get::<_, u64>(5)?,
+                    "total_assets": row.get::<_, u64>(6)?,
+                    "total_shares": row.get::<_, u64>(7)?,
+                }))
+            },
+        )
+    }
+
+    /// A vault's most recent price checkpoints, newest first
+    pub fn prices(&self, vault: &str, limit: u32) -> rusqlite::Result<Value> {
+        self.query(
+            "SELECT signature, slot, total_assets, total_shares FROM price_checkpoints
+             WHERE vault = ?1 ORDER BY slot DESC, idx DESC LIMIT ?2",
+            params![vault, limit],
+            |row| {
+                let total_assets: u64 = row.get(2)?;
+                let total_shares: u64 = row.get(3)?;
+                Ok(json!({
+                    "signature": row.get::<_, String>(0)?,
+                    "slot": row.get::<_, u64>(1)?,
+                    "total_assets": total_assets,
+                    "total_shares": total_shares,
+                    "share_price": share_price(total_assets, total_shares),
+                }))
+            },
+        )
+    }
+
+    pub fn flag_captures(&self) -> rusqlite::Result<Value> {
+        self.query(
+            "SELECT signature, slot, flag, vault, captor, condition FROM flag_captures
+             ORDER BY slot, idx",
+            [],
+            |row| {
+                Ok(json!({
+                    "signature": row.get::<_, String>(0)?,
+                    "slot": row.get::<_, u64>(1)?,
+                    "flag": row.get::<_, String>(2)?,
+                    "vault": row.get::<_, String>(3)?,
+                    "captor": row.get::<_, String>(4)?,
+                    "condition": row.get::<_, String>(5)?,
+                }))
+            },
+        )
+    }
+
+    fn query<P: rusqlite::Params>(
+        &self,
+        sql: &str,
+        params: P,
+        row: impl FnMut(&Row) -> rusqlite::Result<Value>,
+    ) -> rusqlite::Result<Value> {
+        let mut statement = self.conn.prepare(sql)?;
+        let rows = statement.query_map(params, row)?;
+        rows.collect::<rusqlite::Result<Vec<_>>>().map(Value::Array)
+    }
+}
+
+/// Underlying per share, or `null` while no shares are in circulation
+fn share_price(total_assets: u64, total_shares: u64) -> Option<f64> {
+    (total_shares > 0).then(|| total_assets as f64 / total_shares as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::Pubkey;
+    use vault_pda_events::{DepositEvent, RedeemEvent, ReportEvent, VaultNamedEvent};
+
+    use super::*;
+
+    #[test]
+    fn indexes_each_transaction_once() {
+        let mut db = Db::open_in_memory().unwrap();
+        let vault = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let deposit = VaultEvent::Deposit(DepositEvent {
+            vault,
+            depositor: user,
+            amount: 1_000,
+            shares: 1_000,
+            total_assets: 1_000,
+            total_shares: 1_000,
+        });
+        let redeem = VaultEvent::Redeem(RedeemEvent {
+            vault,
+            redeemer: user,
+            shares: 500,
+            amount: 600,
+            total_assets: 900,
+            total_shares: 500,
+        });
+
+        assert!(db.index_transaction("a", 10, false, &[deposit]).unwrap());
+        assert!(db.index_transaction("b", 12, false, &[redeem]).unwrap());
+        assert!(db.index_transaction("c", 13, true, &[]).unwrap());
+        assert!(!db.index_transaction("a", 10, false, &[]).unwrap());
+        assert!(db.has_transaction("c").unwrap());
+
+        assert_eq!(db.status().unwrap()["transactions"], 3);
+        let vaults = db.vaults().unwrap();
+        assert_eq!(vaults.as_array().unwrap().len(), 1);
+        assert_eq!(vaults[0]["total_assets"], 900);
+        assert_eq!(vaults[0]["share_price"], 1.8);
+        assert_eq!(vaults[0]["deposits"], 1);
+
+        let vault = vault.to_string();
+        assert_eq!(db.deposits(&vault, 10).unwrap()[0]["signature"], "a");
+        assert_eq!(db.redeems(&vault, 10).unwrap()[0]["amount"], 600);
+        let prices = db.prices(&vault, 10).unwrap();
+        assert_eq!(prices.as_array().unwrap().len(), 2);
+        assert_eq!(prices[0]["signature"], "b");
+        assert_eq!(db.prices(&vault, 1).unwrap().as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn reports_are_price_checkpoints() {
+        let mut db = Db::open_in_memory().unwrap();
+        let vault = Pubkey::new_unique();
+        let report = VaultEvent::Report(ReportEvent {
+            vault,
+            treasury: Pubkey::new_unique(),
+            profit: 1_000,
+            reserve: 100,
+            shares: 52,
+            total_assets: 2_000,
+            total_shares: 1_052,
+        });
+
+        assert!(db.index_transaction("a", 10, false, &[report]).unwrap());
+
+        let vault = vault.to_string();
+        let reports = db.reports(&vault, 10).unwrap();
+        assert_eq!(reports[0]["reserve"], 100);
+        assert_eq!(reports[0]["shares"], 52);
+        assert_eq!(db.prices(&vault, 10).unwrap()[0]["total_shares"], 1_052);
+    }
+
+    #[test]
+    fn keeps_the_latest_name() {
+        let mut db = Db::open_in_memory().unwrap();
+        let vault = Pubkey::new_unique();
+        let named = |name: &str| {
+            let mut padded = [0; 32];
+            padded[..name.len()].copy_from_slice(name.as_bytes());
+            VaultEvent::VaultNamed(VaultNamedEvent {
+                vault,
+                underlying_mint: Pubkey::new_unique(),
+                name: padded,
+            })
+        };
+        let deposit = VaultEvent::Deposit(DepositEvent {
+            vault,
+            depositor: Pubkey::new_unique(),
+            amount: 1_000,
+            shares: 1_000,
+            total_assets: 1_000,
+            total_shares: 1_000,
+        });
+
+        db.index_transaction("b", 20, false, &[named("USDC v2")])
+            .unwrap();
+        // Backfilled after the rename
+        db.index_transaction("a", 10, false, &[named("USDC"), deposit])
+            .unwrap();
+
+        assert_eq!(db.vaults().unwrap()[0]["name"], "USDC v2");
+    }
+}
@@ -0,0 +1,135 @@
+//! Feeds the index from a cluster: history over RPC, new transactions over a
+//! websocket logs subscription.
+
+use anchor_lang::prelude::Pubkey;
+use solana_client::pubsub_client::PubsubClient;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::{
+    RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+};
+use solana_sdk::signature::Signature;
+use solana_transaction_status::UiTransactionEncoding;
+use vault_pda_events::parse_logs;
+
+use crate::db::Db;
+use crate::Result;
+
+/// Signatures per `getSignaturesForAddress` page, the RPC maximum
+const PAGE_SIZE: usize = 1_000;
+
+pub struct Indexer {
+    rpc: RpcClient,
+    program_id: Pubkey,
+    db: Db,
+}
+
+impl Indexer {
+    pub fn new(rpc: RpcClient, program_id: Pubkey, db: Db) -> Self {
+        Self {
+            rpc,
+            program_id,
+            db,
+        }
+    }
+
+    /// Subscribes to the program's logs, backfills everything since the last
+    /// indexed transaction, then indexes new transactions as they arrive.
+    /// Subscribing first leaves no gap between the two; the overlap is
+    /// indexed once. Returns when the subscription drops.
+    pub fn follow(&mut self, ws_url: &str) -> Result<()> {
+        let (subscription, notifications) = PubsubClient::logs_subscribe(
+            ws_url,
+            RpcTransactionLogsFilter::Mentions(vec![self.program_id.to_string()]),
+            RpcTransactionLogsConfig {
+                commitment: Some(self.rpc.commitment()),
+            },
+        )?;
+
+        let backfilled = self.backfill()?;
+        println!("Backfilled {backfilled} transactions, following {ws_url}");
+
+        for notification in notifications.iter() {
+            let logs = notification.value;
+            self.index(
+                &logs.signature,
+                notification.context.slot,
+                logs.err.is_some(),
+                &logs.logs,
+            )?;
+        }
+        drop(subscription);
+        Err("Logs subscription closed".into())
+    }
+
+    /// Indexes every transaction newer than the newest already indexed,
+    /// oldest first
+    pub fn backfill(&mut self) -> Result<usize> {
+        let mut pending = Vec::new();
+        let mut before = None;
+        'pages: loop {
+            let config = GetConfirmedSignaturesForAddress2Config {
+                before,
+                until: None,
+                limit: Some(PAGE_SIZE),
+                commitment: Some(self.rpc.commitment()),
+            };
+            let page = self
+                .rpc
+                .get_signatures_for_address_with_config(&self.program_id, config)?;
+            let full = page.len() == PAGE_SIZE;
+            for status in page {
+                if self.db.has_transaction(&status.signature)? {
+                    break 'pages;
+                }
+                before = Some(status.signature.parse::<Signature>()?);
+                pending.push(status);
+            }
+            if !full {
+                break;
+            }
+        }
+
+        for status in pending.iter().rev() {
+            let failed = status.err.is_some();
+            let logs = if failed {
+                Vec::new()
+            } else {
+                self.fetch_logs(&status.signature)?
+            };
+            self.index(&status.signature, status.slot, failed, &logs)?;
+        }
+        Ok(pending.len())
+    }
+
+    fn fetch_logs(&self, signature: &str) -> Result<Vec<String>> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(self.rpc.commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+        let transaction = self
+            .rpc
+            .get_transaction_with_config(&signature.parse()?, config)?;
+        Ok(transaction
+            .transaction
+            .meta
+            .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages))
+            .unwrap_or_default())
+    }
+
+    fn index(&mut self, signature: &str, slot: u64, failed: bool, logs: &[String]) -> Result<()> {
+        let events = if failed {
+            Vec::new()
+        } else {
+            parse_logs(&self.program_id, logs)
+        };
+        if self
+            .db
+            .index_transaction(signature, slot, failed, &events)?
+            && !events.is_empty()
+        {
+            println!("{signature} (slot {slot}): {} events", events.len());
+        }
+        Ok(())
+    }
+}
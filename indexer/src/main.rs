@@ -0,0 +1,119 @@
+//! Indexes the vault program's events into SQLite and serves them as JSON.
+//!
+//! ```bash
+//! indexer [--url <rpc>] [--ws <websocket>] [--db <path>] [--listen <addr>] [--program-id <pubkey>]
+//! ```
+//!
+//! Backfills every transaction of the program with `getSignaturesForAddress`,
+//! then follows new ones with a websocket logs subscription, decoding deposit,
+//! redeem and flag capture events into `--db` (`indexer.db` by default). The
+//! JSON API (see `api.rs`) listens on `--listen`, `127.0.0.1:8080` by
+//! default. `--ws` defaults to `--url` with the websocket scheme and, when it
+//! has a port, the next port, as for `solana-test-validator`. Restarting
+//! resumes from the newest indexed transaction.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+
+mod api;
+mod db;
+mod indexer;
+
+use db::Db;
+use indexer::Indexer;
+
+const USAGE: &str = "usage: indexer [--url <rpc>] [--ws <websocket>] [--db <path>] [--listen <addr>] [--program-id <pubkey>]";
+
+const DEFAULT_URL: &str = "http://127.0.0.1:8899";
+
+/// Wait before resubscribing after the websocket drops
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("indexer failed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<()> {
+    let mut url = DEFAULT_URL.to_string();
+    let mut ws = None;
+    let mut db_path = PathBuf::from("indexer.db");
+    let mut listen = "127.0.0.1:8080".to_string();
+    let mut program_id = vault_pda::ID;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--url" => url = args.next().ok_or(USAGE)?,
+            "--ws" => ws = Some(args.next().ok_or(USAGE)?),
+            "--db" => db_path = args.next().ok_or(USAGE)?.into(),
+            "--listen" => listen = args.next().ok_or(USAGE)?,
+            "--program-id" => program_id = args.next().ok_or(USAGE)?.parse()?,
+            _ => return Err(USAGE.into()),
+        }
+    }
+    let ws = ws.unwrap_or_else(|| ws_url(&url));
+
+    let mut indexer = Indexer::new(
+        RpcClient::new_with_commitment(url, CommitmentConfig::confirmed()),
+        program_id,
+        Db::open(&db_path)?,
+    );
+
+    let server = tiny_http::Server::http(&listen)
+        .map_err(|e| format!("Failed to listen on {listen}: {e}"))?;
+    let api_db = Db::open(&db_path)?;
+    std::thread::spawn(move || api::serve(server, api_db));
+    println!("Serving {} on http://{listen}", db_path.display());
+
+    loop {
+        if let Err(e) = indexer.follow(&ws) {
+            eprintln!("indexer: {e}, resubscribing");
+        }
+        std::thread::sleep(RECONNECT_DELAY);
+    }
+}
+
+/// The websocket URL the Solana CLI would derive from an RPC URL
+fn ws_url(url: &str) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("http", url));
+    let scheme = if scheme == "https" { "wss" } else { "ws" };
+    let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) => match port.parse::<u16>() {
+            Ok(port) => format!("{name}:{}", port + 1),
+            Err(_) => host.to_string(),
+        },
+        None => host.to_string(),
+    };
+    format!("{scheme}://{host}{path}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_websocket_urls() {
+        assert_eq!(ws_url("http://127.0.0.1:8899"), "ws://127.0.0.1:8900");
+        assert_eq!(
+            ws_url("https://api.devnet.solana.com"),
+            "wss://api.devnet.solana.com"
+        );
+        assert_eq!(
+            ws_url("http://localhost:8899/rpc"),
+            "ws://localhost:8900/rpc"
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! The invariants the monitor re-checks on every vault update.
+//!
+//! - The value of one share never decreases. Both conversions round in the
+//!   vault's favor and the program has no loss path, so any decrease means
+//!   value left the vault for nothing. Checked immediately, between
+//!   consecutive snapshots.
+//! - The vault holds what the indexed deposits put in minus what the indexed
+//!   redeems and skims paid out, and the share supply is what deposits and
+//!   profit reports minted minus what redeems burned. The indexer trails the chain,
+//!   so a discrepancy is reported only once it has persisted for the grace
+//!   period.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use anchor_lang::prelude::Pubkey;
+use vault_invariants::{Snapshot, PRECISION};
+
+/// Sums of a vault's indexed events
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Flows {
+    /// Underlying deposited
+    pub deposited: u64,
+    /// Underlying paid out by redeems and to the treasury by skims
+    pub paid_out: u64,
+    /// Shares minted by deposits and to the treasury by profit reports
+    pub minted: u64,
+    /// Shares burned by redeems
+    pub burned: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// Value per share, scaled by `PRECISION`, went down
+    PriceDecreased { before: u128, after: u128 },
+    /// Underlying left the vault without a redeem or skim paying it out
+    UnexplainedOutflow { expected: i128, actual: u64 },
+    /// Underlying arrived without a deposit, e.g. a donation inflating the
+    /// share price
+    UnexplainedInflow { expected: i128, actual: u64 },
+    /// Shares were minted or burned outside deposits, redeems and reports
+    SupplyMismatch { expected: i128, actual: u64 },
+}
+
+impl Violation {
+    /// Whether value may have been stolen, rather than only a warning sign
+    pub fn is_critical(&self) -> bool {
+        !matches!(self, Violation::UnexplainedInflow { .. })
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Violation::PriceDecreased { .. } => "price",
+            Violation::UnexplainedOutflow { .. } => "outflow",
+            Violation::UnexplainedInflow { .. } => "inflow",
+            Violation::SupplyMismatch { .. } => "supply",
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::PriceDecreased { before, after } => write!(
+                f,
+                "value per share decreased from {} to {}",
+                *before as f64 / PRECISION as f64,
+                *after as f64 / PRECISION as f64
+            ),
+            Violation::UnexplainedOutflow { expected, actual } => write!(
+                f,
+                "vault holds {actual} but indexed flows leave {expected}: tokens left without a redeem"
+            ),
+            Violation::UnexplainedInflow { expected, actual } => write!(
+                f,
+                "vault holds {actual} but indexed flows leave {expected}: tokens arrived without a deposit"
+            ),
+            Violation::SupplyMismatch { expected, actual } => write!(
+                f,
+                "share supply is {actual} but indexed flows leave {expected}"
+            ),
+        }
+    }
+}
+
+/// A price decrease between two consecutive snapshots
+pub fn check_price(before: &Snapshot, after: &Snapshot) -> Option<Violation> {
+    let (before, after) = (before.value_per_share()?, after.value_per_share()?);
+    (after < before).then_some(Violation::PriceDecreased { before, after })
+}
+
+/// Discrepancies between a snapshot and the indexed flows
+pub fn check_flows(snapshot: &Snapshot, flows: &Flows) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    let expected = flows.deposited as i128 - flows.paid_out as i128;
+    let actual = snapshot.vault_balance;
+    if (actual as i128) < expected {
+        violations.push(Violation::UnexplainedOutflow { expected, actual });
+    } else if actual as i128 > expected {
+        violations.push(Violation::UnexplainedInflow { expected, actual });
+    }
+
+    let expected = flows.minted as i128 - flows.burned as i128;
+    let actual = snapshot.share_supply;
+    if actual as i128 != expected {
+        violations.push(Violation::SupplyMismatch { expected, actual });
+    }
+    violations
+}
+
+struct Pending {
+    since: Instant,
+    reported: bool,
+}
+
+/// Per-vault state across updates
+pub struct Monitor {
+    grace: Duration,
+    last: HashMap<Pubkey, Snapshot>,
+    pending: HashMap<(Pubkey, &'static str), Pending>,
+}
+
+impl Monitor {
+    pub fn new(grace: Duration) -> Self {
+        Self {
+            grace,
+            last: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Checks `vault`'s latest snapshot, returning the violations to alert
+    /// on. A flow discrepancy is returned once, after persisting for the
+    /// grace period, and again only if it clears and comes back.
+    pub fn observe(
+        &mut self,
+        vault: Pubkey,
+        snapshot: Snapshot,
+        flows: Option<&Flows>,
+        now: Instant,
+    ) -> Vec<Violation> {
+        let mut alerts = Vec::new();
+        if let Some(before) = self.last.insert(vault, snapshot) {
+            alerts.extend(check_price(&before, &snapshot));
+        }
+
+        let Some(flows) = flows else {
+            return alerts;
+        };
+        let violations = check_flows(&snapshot, flows);
+        self.pending.retain(|(pending_vault, kind), _| {
+            *pending_vault != vault || violations.iter().any(|v| v.kind() == *kind)
+        });
+        for violation in violations {
+            let pending = self
+                .pending
+                .entry((vault, violation.kind()))
+                .or_insert(Pending {
+                    since: now,
+                    reported: false,
+                });
+            if !pending.reported && now.duration_since(pending.since) >= self.grace {
+                pending.reported = true;
+                alerts.push(violation);
+            }
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(vault_balance: u64, share_supply: u64) -> Snapshot {
+        Snapshot {
+            vault_balance,
+            share_supply,
+            ..Snapshot::default()
+        }
+    }
+
+    #[test]
+    fn alerts_on_price_decreases_at_once() {
+        let mut monitor = Monitor::new(Duration::from_secs(60));
+        let vault = Pubkey::new_unique();
+        let now = Instant::now();
+
+        assert!(monitor
+            .observe(vault, snapshot(1_000, 1_000), None, now)
+            .is_empty());
+        // A donation raises the price
+        assert!(monitor
+            .observe(vault, snapshot(2_000, 1_000), None, now)
+            .is_empty());
+        // A redeem paid from someone else's shares lowers it
+        let alerts = monitor.observe(vault, snapshot(1_000, 1_000), None, now);
+        assert!(matches!(alerts[..], [Violation::PriceDecreased { .. }]));
+        assert!(alerts[0].is_critical());
+        // Emptying the vault leaves no price to compare
+        assert!(monitor.observe(vault, snapshot(0, 0), None, now).is_empty());
+    }
+
+    #[test]
+    fn alerts_on_flow_discrepancies_after_the_grace_period() {
+        let grace = Duration::from_secs(60);
+        let mut monitor = Monitor::new(grace);
+        let vault = Pubkey::new_unique();
+        let start = Instant::now();
+        let flows = Flows {
+            deposited: 1_000,
+            paid_out: 400,
+            minted: 1_000,
+            burned: 400,
+        };
+
+        let consistent = snapshot(600, 600);
+        assert!(check_flows(&consistent, &flows).is_empty());
+        assert!(monitor
+            .observe(vault, consistent, Some(&flows), start)
+            .is_empty());
+
+        // Drained without a redeem: the price drop is reported at once, the
+        // outflow once the indexer has had time to catch up
+        let drained = snapshot(100, 600);
+        let alerts = monitor.observe(vault, drained, Some(&flows), start);
+        assert!(matches!(alerts[..], [Violation::PriceDecreased { .. }]));
+        let alerts = monitor.observe(vault, drained, Some(&flows), start + grace);
+        assert_eq!(
+            alerts,
+            vec![Violation::UnexplainedOutflow {
+                expected: 600,
+                actual: 100
+            }]
+        );
+        assert!(monitor
+            .observe(vault, drained, Some(&flows), start + 2 * grace)
+            .is_empty());
+
+        // Clears once accounted for, and a donation is only a warning
+        let flows = Flows {
+            paid_out: 900,
+            burned: 900,
+            ..flows
+        };
+        let donated = snapshot(150, 100);
+        assert!(monitor
+            .observe(vault, donated, Some(&flows), start + 3 * grace)
+            .is_empty());
+        let alerts = monitor.observe(vault, donated, Some(&flows), start + 4 * grace);
+        assert_eq!(alerts.len(), 1);
+        assert!(!alerts[0].is_critical());
+
+        assert_eq!(
+            check_flows(&snapshot(100, 50), &flows),
+            vec![Violation::SupplyMismatch {
+                expected: 100,
+                actual: 50
+            }]
+        );
+    }
+}
@@ -0,0 +1,42 @@
+//! Reads indexed flows from the `indexer`'s database.
+
+use std::path::Path;
+
+use rusqlite::{Connection, OpenFlags};
+
+use crate::checks::Flows;
+
+pub struct FlowSource {
+    conn: Connection,
+}
+
+impl FlowSource {
+    /// Opens the indexer's database read-only
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        Ok(Self { conn })
+    }
+
+    /// Sums of `vault`'s indexed deposits, redeems, profit reports and skims
+    pub fn flows(&self, vault: &str) -> rusqlite::Result<Flows> {
+        self.conn.query_row(
+            "SELECT
+                (SELECT COALESCE(SUM(amount), 0) FROM deposits WHERE vault = ?1),
+                (SELECT COALESCE(SUM(amount), 0) FROM redeems WHERE vault = ?1)
+                    + (SELECT COALESCE(SUM(reserve), 0) FROM skims WHERE vault = ?1),
+                (SELECT COALESCE(SUM(shares), 0) FROM deposits WHERE vault = ?1)
+                    + (SELECT COALESCE(SUM(shares), 0) FROM reports WHERE vault = ?1),
+                (SELECT COALESCE(SUM(shares), 0) FROM redeems WHERE vault = ?1)",
+            [vault],
+            |row| {
+                Ok(Flows {
+                    deposited: row.get(0)?,
+                    paid_out: row.get(1)?,
+                    minted: row.get(2)?,
+                    burned: row.get(3)?,
+                })
+            },
+        )
+    }
+}
@@ -0,0 +1,210 @@
+//! Watches deployed vaults and alerts when an invariant breaks.
+//!
+//! ```bash
+//! monitor [--url <rpc>] [--ws <websocket>] [--db <indexer.db>] [--grace <secs>]
+//!         [--program-id <pubkey>] [--vault <underlying-mint>]...
+//! ```
+//!
+//! Subscribes to every watched vault's token account and share mint. Each
+//! notification, and a periodic sweep, re-fetches the vault's balance and
+//! share supply in one consistent read and runs the checks in `checks.rs`.
+//! With `--db`, the `indexer`'s database supplies the deposit and redeem
+//! flows the balances are reconciled against; it must have indexed the
+//! program from the start. Alerts are printed to stdout as `ALERT critical`
+//! or `ALERT warning` lines, for whatever forwards them to a pager. `--ws`
+//! defaults to `--url` with the websocket scheme and, when it has a port, the
+//! next port.
+//!
+//! All vaults of the program are watched unless `--vault` names some. Build
+//! with `--no-default-features` when the deployed program is the fixed one.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use anchor_lang::prelude::Pubkey;
+use futures::stream::{select_all, StreamExt};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use vault_invariants::Snapshot;
+use vault_pda_client::pda::VaultAddresses;
+use vault_pda_client::{fetch_all_vaults, fetch_vault_state};
+
+mod checks;
+mod flows;
+
+use checks::Monitor;
+use flows::FlowSource;
+
+const USAGE: &str = "usage: monitor [--url <rpc>] [--ws <websocket>] [--db <indexer.db>] [--grace <secs>] [--program-id <pubkey>] [--vault <underlying-mint>]...";
+
+const DEFAULT_URL: &str = "http://127.0.0.1:8899";
+
+/// How long a flow discrepancy may last before it is reported, by default
+const DEFAULT_GRACE: Duration = Duration::from_secs(60);
+
+/// Every vault is re-checked this often even without notifications
+const SWEEP_INTERVAL: Duration = Duration::from_secs(15);
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+struct Config {
+    url: String,
+    ws: String,
+    db: Option<PathBuf>,
+    grace: Duration,
+    program_id: Pubkey,
+    vaults: Vec<Pubkey>,
+}
+
+fn main() -> ExitCode {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to build tokio runtime");
+
+    let result = parse_args(std::env::args().skip(1).collect())
+        .and_then(|config| runtime.block_on(run(config)));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("monitor failed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(args: Vec<String>) -> Result<Config> {
+    let mut url = DEFAULT_URL.to_string();
+    let mut ws = None;
+    let mut db = None;
+    let mut grace = DEFAULT_GRACE;
+    let mut program_id = vault_pda::ID;
+    let mut vaults = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--url" => url = args.next().ok_or(USAGE)?,
+            "--ws" => ws = Some(args.next().ok_or(USAGE)?),
+            "--db" => db = Some(args.next().ok_or(USAGE)?.into()),
+            "--grace" => grace = Duration::from_secs(args.next().ok_or(USAGE)?.parse()?),
+            "--program-id" => program_id = args.next().ok_or(USAGE)?.parse()?,
+            "--vault" => vaults.push(args.next().ok_or(USAGE)?.parse()?),
+            _ => return Err(USAGE.into()),
+        }
+    }
+
+    Ok(Config {
+        ws: ws.unwrap_or_else(|| ws_url(&url)),
+        url,
+        db,
+        grace,
+        program_id,
+        vaults,
+    })
+}
+
+async fn run(config: Config) -> Result<()> {
+    let rpc = RpcClient::new_with_commitment(config.url, CommitmentConfig::confirmed());
+    let program_id = config.program_id;
+
+    let mints = if config.vaults.is_empty() {
+        fetch_all_vaults(&rpc, &program_id)
+            .await?
+            .into_iter()
+            .map(|(_, vault)| vault.underlying_mint)
+            .collect()
+    } else {
+        config.vaults
+    };
+    if mints.is_empty() {
+        return Err("No vaults to monitor".into());
+    }
+    let flows = config.db.as_deref().map(FlowSource::open).transpose()?;
+
+    let pubsub = PubsubClient::new(&config.ws).await?;
+    let account_config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(rpc.commitment()),
+        ..Default::default()
+    };
+    let mut subscriptions = Vec::new();
+    for mint in &mints {
+        let addresses = VaultAddresses::new(&program_id, mint);
+        for account in [addresses.vault_token_account, addresses.share_mint] {
+            let (notifications, _unsubscribe) = pubsub
+                .account_subscribe(&account, Some(account_config.clone()))
+                .await?;
+            let mint = *mint;
+            subscriptions.push(notifications.map(move |_| mint).boxed());
+        }
+    }
+    let mut updates = select_all(subscriptions);
+    println!("Monitoring {} vaults on {}", mints.len(), config.ws);
+
+    let mut monitor = Monitor::new(config.grace);
+    let mut sweep = tokio::time::interval(SWEEP_INTERVAL);
+    loop {
+        let due = tokio::select! {
+            update = updates.next() => match update {
+                Some(mint) => vec![mint],
+                None => return Err("Account subscriptions closed".into()),
+            },
+            _ = sweep.tick() => mints.clone(),
+        };
+
+        for mint in due {
+            if let Err(e) = check(&rpc, &program_id, &mint, flows.as_ref(), &mut monitor).await {
+                eprintln!("monitor: failed to check the vault over {mint}: {e}");
+            }
+        }
+    }
+}
+
+/// Fetches one vault and reports every violation the monitor returns
+async fn check(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    flows: Option<&FlowSource>,
+    monitor: &mut Monitor,
+) -> Result<()> {
+    let state = fetch_vault_state(rpc, program_id, mint).await?;
+    let snapshot = Snapshot {
+        vault_balance: state.total_assets,
+        share_supply: state.total_shares,
+        ..Snapshot::default()
+    };
+    let flows = flows
+        .map(|source| source.flows(&state.address.to_string()))
+        .transpose()?;
+
+    for violation in monitor.observe(state.address, snapshot, flows.as_ref(), Instant::now()) {
+        let severity = if violation.is_critical() {
+            "critical"
+        } else {
+            "warning"
+        };
+        println!("ALERT {severity} vault {}: {violation}", state.address);
+    }
+    Ok(())
+}
+
+/// The websocket URL the Solana CLI would derive from an RPC URL
+fn ws_url(url: &str) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("http", url));
+    let scheme = if scheme == "https" { "wss" } else { "ws" };
+    let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) => match port.parse::<u16>() {
+            Ok(port) => format!("{name}:{}", port + 1),
+            Err(_) => host.to_string(),
+        },
+        None => host.to_string(),
+    };
+    format!("{scheme}://{host}{path}")
+}
@@ -0,0 +1,2 @@
+/// Longest team name, in bytes, stored on the team and the leaderboard
+pub const MAX_TEAM_NAME_LEN: usize = 32;
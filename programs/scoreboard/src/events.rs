@@ -0,0 +1,17 @@
+use anchor_lang::prelude::*;
+
+/// Emitted when a team's flag capture is scored
+#[event]
+pub struct CaptureRecordedEvent {
+    pub team: Pubkey,
+    pub challenge: Pubkey,
+    pub flag: Pubkey,
+    /// Points awarded, including any first blood bonus
+    pub points: u64,
+    /// Whether the team was first to solve the challenge
+    pub first_blood: bool,
+    /// Unix timestamp of the recording
+    pub solved_at: i64,
+    /// The team's score after the capture
+    pub score: u64,
+}
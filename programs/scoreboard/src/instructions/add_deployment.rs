@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Challenge, Deployment, Scoreboard};
+
+#[derive(Accounts)]
+pub struct AddDeployment<'info> {
+    #[account(
+        seeds = [b"scoreboard"],
+        bump = scoreboard.bump,
+        has_one = admin @ AddDeploymentError::Unauthorized,
+    )]
+    pub scoreboard: Account<'info, Scoreboard>,
+
+    pub challenge: Account<'info, Challenge>,
+
+    /// A flag can serve a single challenge
+    #[account(
+        init,
+        payer = admin,
+        space = Deployment::LEN,
+        seeds = [b"deployment", flag.key().as_ref()],
+        bump
+    )]
+    pub deployment: Account<'info, Deployment>,
+
+    /// CHECK: the flag planted in the deployed vault, owned by the
+    /// challenge's program and read by `Challenge::load_flag`
+    pub flag: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<AddDeployment>) -> Result<()> {
+    let flag = ctx.accounts.challenge.load_flag(&ctx.accounts.flag)?;
+    require!(!flag.is_captured(), AddDeploymentError::AlreadyCaptured);
+
+    let deployment = &mut ctx.accounts.deployment;
+
+    deployment.challenge = ctx.accounts.challenge.key();
+    deployment.flag = ctx.accounts.flag.key();
+    deployment.bump = ctx.bumps.deployment;
+
+    msg!("Deployment added!");
+    msg!("Challenge: {}", deployment.challenge);
+    msg!("Flag: {}", deployment.flag);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum AddDeploymentError {
+    #[msg("Only the scoreboard admin can add deployments")]
+    Unauthorized,
+    #[msg("The flag has already been captured")]
+    AlreadyCaptured,
+}
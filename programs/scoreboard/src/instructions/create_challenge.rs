@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Challenge, Scoreboard};
+
+#[derive(Accounts)]
+#[instruction(id: u32)]
+pub struct CreateChallenge<'info> {
+    #[account(
+        seeds = [b"scoreboard"],
+        bump = scoreboard.bump,
+        has_one = admin @ CreateChallengeError::Unauthorized,
+    )]
+    pub scoreboard: Account<'info, Scoreboard>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Challenge::LEN,
+        seeds = [b"challenge", id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<CreateChallenge>,
+    id: u32,
+    program_id: Pubkey,
+    points: u64,
+    first_blood_bonus: u64,
+) -> Result<()> {
+    require!(points > 0, CreateChallengeError::InvalidPoints);
+
+    let challenge = &mut ctx.accounts.challenge;
+
+    challenge.id = id;
+    challenge.program_id = program_id;
+    challenge.points = points;
+    challenge.first_blood_bonus = first_blood_bonus;
+    challenge.bump = ctx.bumps.challenge;
+
+    msg!("Challenge created!");
+    msg!("Challenge: {}", challenge.key());
+    msg!("Program: {}", program_id);
+    msg!("Points: {} (+{} first blood)", points, first_blood_bonus);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum CreateChallengeError {
+    #[msg("Only the scoreboard admin can create challenges")]
+    Unauthorized,
+    #[msg("A challenge must be worth points")]
+    InvalidPoints,
+}
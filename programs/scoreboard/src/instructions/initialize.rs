@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Leaderboard, Scoreboard};
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = Scoreboard::LEN,
+        seeds = [b"scoreboard"],
+        bump
+    )]
+    pub scoreboard: Account<'info, Scoreboard>,
+
+    /// The leaderboard account, pre-allocated by the client with `Leaderboard::LEN` bytes
+    #[account(zero)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+
+    /// The organizer
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<Initialize>) -> Result<()> {
+    let leaderboard = ctx.accounts.leaderboard.load_init()?;
+    let scoreboard = &mut ctx.accounts.scoreboard;
+
+    scoreboard.admin = ctx.accounts.admin.key();
+    scoreboard.leaderboard = ctx.accounts.leaderboard.key();
+    scoreboard.bump = ctx.bumps.scoreboard;
+
+    msg!("Scoreboard initialized!");
+    msg!("Scoreboard: {}", scoreboard.key());
+    msg!("Leaderboard: {}", scoreboard.leaderboard);
+    msg!("Capacity: {}", leaderboard.entries.len());
+
+    Ok(())
+}
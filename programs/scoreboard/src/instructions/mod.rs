@@ -0,0 +1,29 @@
+pub mod add_deployment;
+pub mod create_challenge;
+pub mod initialize;
+pub mod record_capture;
+pub mod register_team;
+
+pub use add_deployment::*;
+pub use create_challenge::*;
+pub use initialize::*;
+pub use record_capture::*;
+pub use register_team::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Same budget as vault-pda: `try_accounts` builds the whole accounts
+    /// struct on the 4KB SBF stack frame.
+    const MAX_ACCOUNTS_STACK_SIZE: usize = 512;
+
+    #[test]
+    fn record_capture_accounts_fit_stack_budget() {
+        let size = std::mem::size_of::<RecordCapture<'static>>();
+        assert!(
+            size <= MAX_ACCOUNTS_STACK_SIZE,
+            "RecordCapture accounts use {size} bytes"
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use anchor_lang::prelude::*;
+
+use crate::events::CaptureRecordedEvent;
+use crate::state::{Challenge, Deployment, Leaderboard, Scoreboard, Solve, Team};
+
+#[derive(Accounts)]
+pub struct RecordCapture<'info> {
+    #[account(
+        seeds = [b"scoreboard"],
+        bump = scoreboard.bump,
+        has_one = leaderboard,
+    )]
+    pub scoreboard: Account<'info, Scoreboard>,
+
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+
+    #[account(
+        mut,
+        seeds = [b"team", captain.key().as_ref()],
+        bump = team.bump,
+        has_one = captain,
+    )]
+    pub team: Account<'info, Team>,
+
+    #[account(mut)]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(
+        seeds = [b"deployment", flag.key().as_ref()],
+        bump = deployment.bump,
+        has_one = challenge,
+        has_one = flag,
+    )]
+    pub deployment: Account<'info, Deployment>,
+
+    /// CHECK: the captured flag, owned by the challenge's program and read
+    /// by `Challenge::load_flag`
+    pub flag: UncheckedAccount<'info>,
+
+    /// A team scores each challenge once, whichever deployment it captures
+    #[account(
+        init,
+        payer = captain,
+        space = Solve::LEN,
+        seeds = [b"solve", challenge.key().as_ref(), team.key().as_ref()],
+        bump
+    )]
+    pub solve: Box<Account<'info, Solve>>,
+
+    /// The team captain, who must have captured the flag
+    #[account(mut)]
+    pub captain: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The team holding the challenge's first blood, required when this
+    /// capture is from an earlier slot and takes it over
+    #[account(mut)]
+    pub first_blood_team: Option<Box<Account<'info, Team>>>,
+
+    /// That team's solve of the challenge, which loses its bonus
+    #[account(mut)]
+    pub first_blood_solve: Option<Box<Account<'info, Solve>>>,
+}
+
+pub fn handler(ctx: Context<RecordCapture>) -> Result<()> {
+    let solved_at = Clock::get()?.unix_timestamp;
+    let flag = ctx.accounts.challenge.load_flag(&ctx.accounts.flag)?;
+    require!(flag.is_captured(), RecordCaptureError::NotCaptured);
+    require_keys_eq!(
+        flag.captor,
+        ctx.accounts.captain.key(),
+        RecordCaptureError::NotCaptor
+    );
+
+    let challenge = &mut ctx.accounts.challenge;
+    let team = &mut ctx.accounts.team;
+    let mut leaderboard = ctx.accounts.leaderboard.load_mut()?;
+
+    // First blood goes to the earliest capture, whatever order the captures
+    // are recorded in; a tie keeps it with the one recorded first
+    let first_blood = challenge.solves == 0 || flag.captured_slot < challenge.first_blood_slot;
+    if first_blood && challenge.solves > 0 {
+        let (Some(holder), Some(holder_solve)) = (
+            ctx.accounts.first_blood_team.as_mut(),
+            ctx.accounts.first_blood_solve.as_mut(),
+        ) else {
+            return err!(RecordCaptureError::MissingFirstBloodHolder);
+        };
+        require_keys_eq!(
+            holder.key(),
+            challenge.first_blood_team,
+            RecordCaptureError::WrongFirstBloodHolder
+        );
+        require!(
+            holder_solve.team == holder.key() && holder_solve.challenge == challenge.key(),
+            RecordCaptureError::WrongFirstBloodHolder
+        );
+
+        let bonus = challenge.first_blood_bonus;
+        holder.score = holder
+            .score
+            .checked_sub(bonus)
+            .ok_or(RecordCaptureError::MathOverflow)?;
+        holder.first_bloods -= 1;
+        holder_solve.points = holder_solve
+            .points
+            .checked_sub(bonus)
+            .ok_or(RecordCaptureError::MathOverflow)?;
+        holder_solve.first_blood = false;
+
+        let index = leaderboard
+            .position(&holder.key())
+            .ok_or(RecordCaptureError::UnknownTeam)?;
+        leaderboard
+            .revoke_first_blood(index, bonus)
+            .ok_or(RecordCaptureError::MathOverflow)?;
+
+        msg!("First blood taken from: {}", holder.key());
+    }
+
+    let points = if first_blood {
+        challenge
+            .points
+            .checked_add(challenge.first_blood_bonus)
+            .ok_or(RecordCaptureError::MathOverflow)?
+    } else {
+        challenge.points
+    };
+
+    if first_blood {
+        challenge.first_blood_team = team.key();
+        challenge.first_blood_slot = flag.captured_slot;
+        challenge.first_blood_at = solved_at;
+    }
+    challenge.solves += 1;
+
+    team.score = team
+        .score
+        .checked_add(points)
+        .ok_or(RecordCaptureError::MathOverflow)?;
+    team.solves += 1;
+    team.first_bloods += u32::from(first_blood);
+    team.last_solve_at = solved_at;
+
+    let solve = &mut ctx.accounts.solve;
+    solve.team = team.key();
+    solve.challenge = challenge.key();
+    solve.flag = ctx.accounts.flag.key();
+    solve.captured_slot = flag.captured_slot;
+    solve.solved_at = solved_at;
+    solve.points = points;
+    solve.first_blood = first_blood;
+    solve.bump = ctx.bumps.solve;
+
+    let index = leaderboard
+        .position(&team.key())
+        .ok_or(RecordCaptureError::UnknownTeam)?;
+    let rank = leaderboard
+        .record_solve(index, points, first_blood, solved_at)
+        .ok_or(RecordCaptureError::MathOverflow)?;
+
+    msg!("Capture recorded!");
+    msg!("Team: {}", team.key());
+    msg!("Points: {}", points);
+    if first_blood {
+        msg!("First blood!");
+    }
+    msg!("Score: {} (rank {})", team.score, rank + 1);
+
+    emit!(CaptureRecordedEvent {
+        team: team.key(),
+        challenge: challenge.key(),
+        flag: solve.flag,
+        points,
+        first_blood,
+        solved_at,
+        score: team.score,
+    });
+
+    Ok(())
+}
+
+#[error_code]
+pub enum RecordCaptureError {
+    #[msg("The flag has not been captured")]
+    NotCaptured,
+    #[msg("The flag was captured by someone else")]
+    NotCaptor,
+    #[msg("The team is missing from the leaderboard")]
+    UnknownTeam,
+    #[msg("The capture takes over first blood: pass the holder's team and solve")]
+    MissingFirstBloodHolder,
+    #[msg("The accounts passed are not the first blood holder's team and solve")]
+    WrongFirstBloodHolder,
+    #[msg("Math overflow")]
+    MathOverflow,
+}
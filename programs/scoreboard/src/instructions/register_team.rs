@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_TEAM_NAME_LEN;
+use crate::state::{Leaderboard, Scoreboard, Team};
+
+#[derive(Accounts)]
+pub struct RegisterTeam<'info> {
+    #[account(
+        seeds = [b"scoreboard"],
+        bump = scoreboard.bump,
+        has_one = admin @ RegisterTeamError::Unauthorized,
+        has_one = leaderboard,
+    )]
+    pub scoreboard: Account<'info, Scoreboard>,
+
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+
+    /// One team per captain
+    #[account(
+        init,
+        payer = captain,
+        space = Team::LEN,
+        seeds = [b"team", captain.key().as_ref()],
+        bump
+    )]
+    pub team: Account<'info, Team>,
+
+    #[account(mut)]
+    pub captain: Signer<'info>,
+
+    /// The organizer, who admits each team so throwaway keys cannot fill
+    /// the leaderboard
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<RegisterTeam>, name: String) -> Result<()> {
+    require!(
+        !name.is_empty() && name.len() <= MAX_TEAM_NAME_LEN,
+        RegisterTeamError::InvalidName
+    );
+
+    let mut padded = [0u8; MAX_TEAM_NAME_LEN];
+    padded[..name.len()].copy_from_slice(name.as_bytes());
+
+    let team = &mut ctx.accounts.team;
+    team.captain = ctx.accounts.captain.key();
+    team.name = padded;
+    team.bump = ctx.bumps.team;
+
+    let mut leaderboard = ctx.accounts.leaderboard.load_mut()?;
+    require!(
+        leaderboard.push(team.key(), padded),
+        RegisterTeamError::LeaderboardFull
+    );
+
+    msg!("Team registered!");
+    msg!("Team: {}", team.key());
+    msg!("Name: {}", name);
+    msg!("Registered teams: {}", leaderboard.count);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum RegisterTeamError {
+    #[msg("Only the scoreboard admin can register teams")]
+    Unauthorized,
+    #[msg("Team names must be 1 to 32 bytes")]
+    InvalidName,
+    #[msg("Leaderboard is full")]
+    LeaderboardFull,
+}
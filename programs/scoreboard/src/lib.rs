@@ -0,0 +1,44 @@
+pub mod constants;
+pub mod events;
+pub mod instructions;
+pub mod state;
+
+use anchor_lang::prelude::*;
+
+pub use constants::*;
+pub use events::*;
+pub use instructions::*;
+pub use state::*;
+
+declare_id!("6Yu8vXnyoCH1ZndBASRT6t7egFobokgQXZdQYQ6rCf8b");
+
+#[program]
+pub mod scoreboard {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        initialize::handler(ctx)
+    }
+
+    pub fn create_challenge(
+        ctx: Context<CreateChallenge>,
+        id: u32,
+        program_id: Pubkey,
+        points: u64,
+        first_blood_bonus: u64,
+    ) -> Result<()> {
+        create_challenge::handler(ctx, id, program_id, points, first_blood_bonus)
+    }
+
+    pub fn add_deployment(ctx: Context<AddDeployment>) -> Result<()> {
+        add_deployment::handler(ctx)
+    }
+
+    pub fn register_team(ctx: Context<RegisterTeam>, name: String) -> Result<()> {
+        register_team::handler(ctx, name)
+    }
+
+    pub fn record_capture(ctx: Context<RecordCapture>) -> Result<()> {
+        record_capture::handler(ctx)
+    }
+}
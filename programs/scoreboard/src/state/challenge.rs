@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use vault_pda::state::Flag;
+
+/// One challenge, solvable once per team on any of its deployments
+#[account]
+#[derive(InitSpace)]
+pub struct Challenge {
+    /// Identifier chosen by the organizer, part of the PDA seeds
+    pub id: u32,
+    /// The vault program the challenge's flags belong to: vault-pda or a
+    /// level scaffolded from it, each with its own program ID
+    pub program_id: Pubkey,
+    /// Points for solving the challenge
+    pub points: u64,
+    /// Extra points for the first team to solve it
+    pub first_blood_bonus: u64,
+    /// The team whose flag was captured first, the default pubkey until a
+    /// solve is recorded
+    pub first_blood_team: Pubkey,
+    /// Slot that team's flag was captured at
+    pub first_blood_slot: u64,
+    /// Unix timestamp its solve was recorded at
+    pub first_blood_at: i64,
+    /// Number of teams that solved it
+    pub solves: u32,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl Challenge {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// Reads the vault `Flag` in `flag`, which must belong to the
+    /// challenge's program
+    ///
+    /// `Account<Flag>` would pin the owner to vault-pda's own ID, so scaffolded
+    /// levels check it here instead. Their `Flag` layout and discriminator are
+    /// the template's.
+    pub fn load_flag(&self, flag: &AccountInfo) -> Result<Flag> {
+        if *flag.owner != self.program_id {
+            return Err(error!(ErrorCode::ConstraintOwner)
+                .with_account_name("flag")
+                .with_pubkeys((*flag.owner, self.program_id)));
+        }
+        Flag::try_deserialize(&mut &flag.try_borrow_data()?[..])
+    }
+}
+
+/// A flag of the challenge's program serving as one instance of the challenge,
+/// typically one per team
+#[account]
+#[derive(InitSpace)]
+pub struct Deployment {
+    /// The challenge this flag belongs to
+    pub challenge: Pubkey,
+    /// The vault-pda `Flag` account
+    pub flag: Pubkey,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl Deployment {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// A team's solve of one challenge; its existence stops the team from
+/// scoring the challenge twice
+#[account]
+#[derive(InitSpace)]
+pub struct Solve {
+    pub team: Pubkey,
+    pub challenge: Pubkey,
+    /// The captured flag that was scored
+    pub flag: Pubkey,
+    /// Slot the flag was captured at
+    pub captured_slot: u64,
+    /// Unix timestamp of the recording
+    pub solved_at: i64,
+    /// Points awarded, including any first blood bonus
+    pub points: u64,
+    /// Whether this solve holds the challenge's first blood; cleared when a
+    /// capture from an earlier slot is recorded after it
+    pub first_blood: bool,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl Solve {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
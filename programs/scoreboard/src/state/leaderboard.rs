@@ -0,0 +1,179 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_TEAM_NAME_LEN;
+
+/// A team's standing, mirrored from its `Team` account
+#[zero_copy]
+#[repr(C)]
+pub struct LeaderboardEntry {
+    /// The `Team` account
+    pub team: Pubkey,
+    /// Team name, zero-padded UTF-8
+    pub name: [u8; MAX_TEAM_NAME_LEN],
+    /// Total points scored
+    pub score: u64,
+    /// Unix timestamp of the latest solve, breaks ties in favor of the
+    /// team that got there first
+    pub last_solve_at: i64,
+    /// Number of challenges solved
+    pub solves: u32,
+    /// Number of challenges solved first
+    pub first_bloods: u32,
+}
+
+impl LeaderboardEntry {
+    /// Whether `self` ranks strictly above `other`
+    pub fn ranks_above(&self, other: &Self) -> bool {
+        self.score > other.score
+            || (self.score == other.score && self.last_solve_at < other.last_solve_at)
+    }
+}
+
+/// Every registered team, sorted by rank.
+///
+/// Zero-copy and kept sorted on every update so the CTF frontend can render
+/// the standings from a single account fetch without decoding each team.
+/// Like `PriceHistory` in vault-pda, it is created by the client with
+/// `create_account` and initialized via `initialize`.
+#[account(zero_copy)]
+#[repr(C)]
+pub struct Leaderboard {
+    /// Number of populated entries in `entries`
+    pub count: u64,
+    /// Standings, best first; only the first `count` are valid
+    pub entries: [LeaderboardEntry; Leaderboard::CAPACITY],
+}
+
+const _: () = assert!(Leaderboard::LEN == 8 + std::mem::size_of::<Leaderboard>());
+
+impl Leaderboard {
+    pub const CAPACITY: usize = 512;
+
+    pub const LEN: usize = 8 + // discriminator
+        8 + // count
+        88 * Leaderboard::CAPACITY; // entries
+
+    /// Returns the standings, best first
+    pub fn as_slice(&self) -> &[LeaderboardEntry] {
+        &self.entries[..self.count as usize]
+    }
+
+    /// Rank of `team`, 0 being first
+    pub fn position(&self, team: &Pubkey) -> Option<usize> {
+        self.as_slice().iter().position(|entry| entry.team == *team)
+    }
+
+    /// Appends a team, returning `false` if the leaderboard is full
+    ///
+    /// Teams start without points, so appending keeps the order: they rank
+    /// behind every team that scored and in registration order otherwise.
+    pub fn push(&mut self, team: Pubkey, name: [u8; MAX_TEAM_NAME_LEN]) -> bool {
+        let count = self.count as usize;
+        if count >= Self::CAPACITY {
+            return false;
+        }
+        self.entries[count] = LeaderboardEntry {
+            team,
+            name,
+            score: 0,
+            last_solve_at: 0,
+            solves: 0,
+            first_bloods: 0,
+        };
+        self.count += 1;
+        true
+    }
+
+    /// Credits the entry at `index` with a solve and moves it up to its new
+    /// rank, which is returned, or `None` if the score overflows
+    ///
+    /// Scores only grow, so the entry can only overtake the ones above it.
+    pub fn record_solve(
+        &mut self,
+        index: usize,
+        points: u64,
+        first_blood: bool,
+        solved_at: i64,
+    ) -> Option<usize> {
+        let entry = &mut self.entries[index];
+        entry.score = entry.score.checked_add(points)?;
+        entry.solves += 1;
+        entry.first_bloods += u32::from(first_blood);
+        entry.last_solve_at = solved_at;
+
+        let mut index = index;
+        while index > 0 && self.entries[index].ranks_above(&self.entries[index - 1]) {
+            self.entries.swap(index, index - 1);
+            index -= 1;
+        }
+        Some(index)
+    }
+
+    /// Takes a first blood `bonus` back from the entry at `index` and moves
+    /// it down to its new rank, which is returned, or `None` if the entry
+    /// holds fewer points
+    ///
+    /// The entry loses points, so it can only fall behind the ones below it.
+    pub fn revoke_first_blood(&mut self, index: usize, bonus: u64) -> Option<usize> {
+        let entry = &mut self.entries[index];
+        entry.score = entry.score.checked_sub(bonus)?;
+        entry.first_bloods = entry.first_bloods.checked_sub(1)?;
+
+        let mut index = index;
+        while index + 1 < self.count as usize
+            && self.entries[index + 1].ranks_above(&self.entries[index])
+        {
+            self.entries.swap(index, index + 1);
+            index += 1;
+        }
+        Some(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn team(byte: u8) -> Pubkey {
+        Pubkey::new_from_array([byte; 32])
+    }
+
+    #[test]
+    fn solves_keep_the_leaderboard_sorted() {
+        let mut leaderboard: Box<Leaderboard> = Box::new(bytemuck::Zeroable::zeroed());
+        for byte in 1..=3 {
+            assert!(leaderboard.push(team(byte), [byte; MAX_TEAM_NAME_LEN]));
+        }
+
+        // The last team to register overtakes everyone with a first blood
+        assert_eq!(leaderboard.record_solve(2, 150, true, 10), Some(0));
+        // Equal scores rank the earlier solve first
+        assert_eq!(leaderboard.record_solve(2, 150, false, 20), Some(1));
+        assert_eq!(leaderboard.record_solve(1, 1, false, 30), Some(0));
+
+        let ranking: Vec<_> = leaderboard.as_slice().iter().map(|e| e.team).collect();
+        assert_eq!(ranking, [team(2), team(3), team(1)]);
+        assert_eq!(leaderboard.entries[1].first_bloods, 1);
+        assert_eq!(leaderboard.position(&team(1)), Some(2));
+    }
+
+    #[test]
+    fn revoked_first_bloods_move_down() {
+        let mut leaderboard: Box<Leaderboard> = Box::new(bytemuck::Zeroable::zeroed());
+        for byte in 1..=3 {
+            assert!(leaderboard.push(team(byte), [byte; MAX_TEAM_NAME_LEN]));
+        }
+        assert_eq!(leaderboard.record_solve(0, 150, true, 10), Some(0));
+        assert_eq!(leaderboard.record_solve(1, 100, false, 20), Some(1));
+
+        // An earlier capture recorded later takes the bonus away
+        assert_eq!(leaderboard.revoke_first_blood(0, 60), Some(1));
+        // A bonus is only taken back once
+        assert_eq!(leaderboard.revoke_first_blood(1, 0), None);
+
+        let ranking: Vec<_> = leaderboard.as_slice().iter().map(|e| e.team).collect();
+        assert_eq!(ranking, [team(2), team(1), team(3)]);
+        assert_eq!(leaderboard.entries[1].score, 90);
+        assert_eq!(leaderboard.entries[1].first_bloods, 0);
+    }
+}
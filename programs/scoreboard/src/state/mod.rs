@@ -0,0 +1,108 @@
+pub mod challenge;
+pub mod leaderboard;
+pub mod scoreboard;
+pub mod team;
+
+pub use challenge::*;
+pub use leaderboard::*;
+pub use scoreboard::*;
+pub use team::*;
+
+/// Every Borsh account, at its largest, fits the space it is allocated
+/// with. The leaderboard asserts its `LEN` against its layout where it is
+/// declared.
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::*;
+    use anchor_lang::AccountSerialize;
+
+    use super::*;
+    use crate::constants::MAX_TEAM_NAME_LEN;
+
+    #[track_caller]
+    fn assert_fits<T: AccountSerialize>(account: &T, len: usize) {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        assert!(
+            data.len() <= len,
+            "serializes to {} bytes, allocated {}",
+            data.len(),
+            len
+        );
+        assert_eq!(data.len(), len, "allocates space no field uses");
+    }
+
+    fn key() -> Pubkey {
+        Pubkey::new_unique()
+    }
+
+    #[test]
+    fn largest_accounts_fit_their_space() {
+        assert_fits(
+            &Scoreboard {
+                admin: key(),
+                leaderboard: key(),
+                bump: u8::MAX,
+            },
+            Scoreboard::LEN,
+        );
+        assert_fits(
+            &Challenge {
+                id: u32::MAX,
+                program_id: key(),
+                points: u64::MAX,
+                first_blood_bonus: u64::MAX,
+                first_blood_team: key(),
+                first_blood_slot: u64::MAX,
+                first_blood_at: i64::MAX,
+                solves: u32::MAX,
+                bump: u8::MAX,
+            },
+            Challenge::LEN,
+        );
+        assert_fits(
+            &Deployment {
+                challenge: key(),
+                flag: key(),
+                bump: u8::MAX,
+            },
+            Deployment::LEN,
+        );
+        assert_fits(
+            &Solve {
+                team: key(),
+                challenge: key(),
+                flag: key(),
+                captured_slot: u64::MAX,
+                solved_at: i64::MAX,
+                points: u64::MAX,
+                first_blood: true,
+                bump: u8::MAX,
+            },
+            Solve::LEN,
+        );
+        assert_fits(
+            &Team {
+                captain: key(),
+                name: [u8::MAX; MAX_TEAM_NAME_LEN],
+                score: u64::MAX,
+                solves: u32::MAX,
+                first_bloods: u32::MAX,
+                last_solve_at: i64::MAX,
+                bump: u8::MAX,
+            },
+            Team::LEN,
+        );
+    }
+
+    #[test]
+    fn documented_sizes_hold() {
+        // Pinned so a layout change is a deliberate one
+        assert_eq!(Scoreboard::LEN, 73);
+        assert_eq!(Challenge::LEN, 113);
+        assert_eq!(Deployment::LEN, 73);
+        assert_eq!(Solve::LEN, 130);
+        assert_eq!(Team::LEN, 97);
+        assert_eq!(Leaderboard::LEN, 45_072);
+    }
+}
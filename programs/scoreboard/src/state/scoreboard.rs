@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Scoreboard {
+    /// The organizer who creates challenges and adds deployments
+    pub admin: Pubkey,
+    /// The zero-copy leaderboard the frontend reads
+    pub leaderboard: Pubkey,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl Scoreboard {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_TEAM_NAME_LEN;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Team {
+    /// The wallet that registered the team and captures its flags
+    pub captain: Pubkey,
+    /// Team name, zero-padded UTF-8
+    pub name: [u8; MAX_TEAM_NAME_LEN],
+    /// Total points scored
+    pub score: u64,
+    /// Number of challenges solved
+    pub solves: u32,
+    /// Number of challenges solved first
+    pub first_bloods: u32,
+    /// Unix timestamp of the latest solve, 0 before the first one
+    pub last_solve_at: i64,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl Team {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
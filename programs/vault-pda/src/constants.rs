@@ -2,3 +2,35 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+// Intentionally vulnerable code paths compiled into this build (the
+// `vuln-*` features), so off-chain tooling built against the same features
+// can tell the challenge build from the reference fix
+pub const VULN_OWNERSHIP: bool = cfg!(feature = "vuln-ownership");
+pub const VULN_INFLATION: bool = cfg!(feature = "vuln-inflation");
+pub const VULN_SHARE_MINT: bool = cfg!(feature = "vuln-share-mint");
+pub const VULN_GLOBAL_AUTHORITY: bool = cfg!(feature = "vuln-global-authority");
+
+/// Longest vault name, in bytes
+pub const MAX_VAULT_NAME_LEN: usize = 32;
+
+/// This build's crate version as `[major, minor, patch]`, recorded in
+/// `ProtocolState` so mixed-version deployments can be told apart
+pub const PROGRAM_VERSION: [u8; 3] = parse_version(env!("CARGO_PKG_VERSION"));
+
+/// Parses the numeric `major.minor.patch` prefix of a version string
+const fn parse_version(version: &str) -> [u8; 3] {
+    let bytes = version.as_bytes();
+    let mut parts = [0u8; 3];
+    let mut part = 0;
+    let mut i = 0;
+    while i < bytes.len() && part < parts.len() {
+        match bytes[i] {
+            b'.' => part += 1,
+            digit @ b'0'..=b'9' => parts[part] = parts[part] * 10 + (digit - b'0'),
+            _ => break,
+        }
+        i += 1;
+    }
+    parts
+}
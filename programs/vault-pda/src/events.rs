@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_VAULT_NAME_LEN;
+use crate::state::WinCondition;
+
+/// Emitted after a successful deposit
+#[event]
+pub struct DepositEvent {
+    pub vault: Pubkey,
+    pub depositor: Pubkey,
+    /// Underlying tokens transferred into the vault
+    pub amount: u64,
+    /// Shares minted to the depositor
+    pub shares: u64,
+    /// Vault assets after the deposit
+    pub total_assets: u64,
+    /// Share supply after the deposit
+    pub total_shares: u64,
+}
+
+/// Emitted after a successful redeem
+#[event]
+pub struct RedeemEvent {
+    pub vault: Pubkey,
+    pub redeemer: Pubkey,
+    /// Shares burned from the redeemer; the withdrawal fee goes to the
+    /// treasury unburned
+    pub shares: u64,
+    /// Underlying tokens transferred out of the vault
+    pub amount: u64,
+    /// Vault assets after the redeem
+    pub total_assets: u64,
+    /// Share supply after the redeem
+    pub total_shares: u64,
+}
+
+/// Emitted when a player captures a vault's flag
+#[event]
+pub struct FlagCapturedEvent {
+    pub flag: Pubkey,
+    pub vault: Pubkey,
+    pub captor: Pubkey,
+    /// Slot of the capture
+    pub slot: u64,
+    /// Win condition the captor met
+    pub condition: WinCondition,
+}
+
+/// Emitted when a vault's profit is reported and split with its treasury
+#[event]
+pub struct ReportEvent {
+    pub vault: Pubkey,
+    pub treasury: Pubkey,
+    /// Underlying the vault made since the last report
+    pub profit: u64,
+    /// The treasury's cut of the profit
+    pub reserve: u64,
+    /// Shares minted to the treasury for its cut
+    pub shares: u64,
+    /// Vault assets at the report
+    pub total_assets: u64,
+    /// Share supply once the treasury's shares are minted
+    pub total_shares: u64,
+}
+
+/// Emitted when a vault's profit is reported and the treasury's cut paid out
+/// in underlying
+#[event]
+pub struct SkimEvent {
+    pub vault: Pubkey,
+    /// The underlying account the cut was paid to
+    pub treasury: Pubkey,
+    /// Underlying the vault made since the last report
+    pub profit: u64,
+    /// The treasury's cut of the profit, paid out of the vault
+    pub reserve: u64,
+    /// Vault assets once the cut has left
+    pub total_assets: u64,
+    /// Share supply at the skim
+    pub total_shares: u64,
+}
+
+/// Emitted when a vault is created and whenever it is renamed
+#[event]
+pub struct VaultNamedEvent {
+    pub vault: Pubkey,
+    pub underlying_mint: Pubkey,
+    /// The new name, zero-padded UTF-8
+    pub name: [u8; MAX_VAULT_NAME_LEN],
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{AdminAction, AdminLog, FeeExemption, ProtocolState};
+
+#[derive(Accounts)]
+pub struct AddFeeExemption<'info> {
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+        has_one = owner @ AddFeeExemptionError::Unauthorized,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// The exemption for `address`, one per address
+    #[account(
+        init,
+        payer = owner,
+        space = FeeExemption::LEN,
+        seeds = [b"fee_exemption", address.key().as_ref()],
+        bump
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+
+    /// CHECK: the redeemer to exempt, such as the protocol's router; only its
+    /// key is used
+    pub address: UncheckedAccount<'info>,
+
+    /// The protocol owner
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The protocol's admin log, which records this call
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.load()?.bump,
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+}
+
+/// Exempts `address` from every vault's withdrawal fee
+pub fn handler(ctx: Context<AddFeeExemption>) -> Result<()> {
+    let fee_exemption = &mut ctx.accounts.fee_exemption;
+    fee_exemption.address = ctx.accounts.address.key();
+    fee_exemption.bump = ctx.bumps.fee_exemption;
+
+    ctx.accounts.admin_log.load_mut()?.append(
+        Clock::get()?.slot,
+        ctx.accounts.owner.key(),
+        AdminAction::AddFeeExemption,
+        &[fee_exemption.address.as_ref()],
+    );
+
+    msg!("Fee exemption added!");
+    msg!("Address: {}", fee_exemption.address);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum AddFeeExemptionError {
+    #[msg("Only the protocol owner can exempt addresses from fees")]
+    Unauthorized,
+}
@@ -0,0 +1,278 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::events::FlagCapturedEvent;
+use crate::state::{Flag, Player, ProtocolState, Vault, WinCondition};
+
+#[derive(Accounts)]
+pub struct CaptureFlag<'info> {
+    #[account(mut, has_one = vault)]
+    pub flag: Account<'info, Flag>,
+
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// The captor's entry, created by `register_player`
+    #[account(
+        seeds = [b"player", flag.key().as_ref(), captor.key().as_ref()],
+        bump = player_record.bump,
+        has_one = flag,
+    )]
+    pub player_record: Account<'info, Player>,
+
+    /// The vault under attack
+    #[account(has_one = vault_token_account)]
+    pub vault: Account<'info, Vault>,
+
+    /// The vault's token account, whose balance shows what was donated
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub captor: Signer<'info>,
+}
+
+/// Records `captor` as the winner of `flag` at `slot` if they meet a win
+/// condition
+///
+/// Owning the protocol only counts for someone other than the guardian, and a
+/// profit is what the vault paid `player` beyond their deposits, less
+/// anything donated to the vault, which now holds `vault_assets`.
+pub fn process_capture_flag(
+    flag: &mut Flag,
+    protocol_owner: &Pubkey,
+    captor: Pubkey,
+    player: &Player,
+    vault_assets: u64,
+    slot: u64,
+) -> std::result::Result<WinCondition, CaptureFlagError> {
+    if flag.is_captured() {
+        return Err(CaptureFlagError::AlreadyCaptured);
+    }
+
+    let condition = if *protocol_owner == captor && captor != flag.guardian {
+        WinCondition::OwnsProtocol
+    } else if player.profit(flag.donations(vault_assets)) > 0 {
+        WinCondition::Profit
+    } else {
+        return Err(CaptureFlagError::NotExploited);
+    };
+
+    flag.captor = captor;
+    flag.captured_slot = slot;
+    flag.condition = Some(condition);
+
+    Ok(condition)
+}
+
+pub fn handler(ctx: Context<CaptureFlag>) -> Result<()> {
+    let captor = ctx.accounts.captor.key();
+    let slot = Clock::get()?.slot;
+    let flag = &mut ctx.accounts.flag;
+
+    let condition = process_capture_flag(
+        flag,
+        &ctx.accounts.protocol_state.owner,
+        captor,
+        &ctx.accounts.player_record,
+        ctx.accounts.vault_token_account.amount,
+        slot,
+    )?;
+
+    msg!("Flag captured!");
+    msg!("Captor: {}", captor);
+    msg!("Slot: {}", slot);
+    msg!("Condition: {:?}", condition);
+
+    emit!(FlagCapturedEvent {
+        flag: flag.key(),
+        vault: flag.vault,
+        captor,
+        slot,
+        condition,
+    });
+
+    Ok(())
+}
+
+#[error_code]
+pub enum CaptureFlagError {
+    #[msg("The flag has already been captured")]
+    AlreadyCaptured,
+    #[msg("No win condition is met")]
+    NotExploited,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::fixtures::{self, player};
+    use crate::state::Flow;
+
+    fn flag(guardian: Pubkey) -> Flag {
+        Flag {
+            guardian,
+            ..fixtures::flag()
+        }
+    }
+
+    fn deposit(amount: u64, shares: u64) -> Flow {
+        Flow::Deposit { amount, shares }
+    }
+
+    fn redeem(shares: u64, amount: u64) -> Flow {
+        Flow::Redeem {
+            shares,
+            amount,
+            vault_shares: true,
+        }
+    }
+
+    /// Counts `flow` into `flag`, and into `player` if it is theirs, moving
+    /// the vault's `assets` with it
+    fn apply(flag: &mut Flag, player: Option<&mut Player>, assets: &mut u64, flow: Flow) {
+        flag.record(flow);
+        if let Some(player) = player {
+            player.record(flow);
+        }
+        match flow {
+            Flow::Deposit { amount, .. } => *assets += amount,
+            Flow::Redeem { amount, .. } => *assets -= amount,
+        }
+    }
+
+    #[test]
+    fn owning_the_protocol_wins() {
+        let (guardian, captor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut flag = flag(guardian);
+
+        let condition = process_capture_flag(&mut flag, &captor, captor, &player(), 0, 42).unwrap();
+        assert_eq!(condition, WinCondition::OwnsProtocol);
+        assert_eq!(flag.captor, captor);
+        assert_eq!(flag.captured_slot, 42);
+        assert_eq!(flag.condition, Some(WinCondition::OwnsProtocol));
+    }
+
+    #[test]
+    fn guardian_owning_the_protocol_does_not_win() {
+        let guardian = Pubkey::new_unique();
+        let mut flag = flag(guardian);
+
+        let result = process_capture_flag(&mut flag, &guardian, guardian, &player(), 0, 42);
+        assert!(matches!(result, Err(CaptureFlagError::NotExploited)));
+        assert!(!flag.is_captured());
+    }
+
+    #[test]
+    fn an_honest_round_trip_does_not_win() {
+        let (owner, captor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut flag = flag(owner);
+        let mut player = player();
+        let mut assets = 0;
+
+        apply(
+            &mut flag,
+            Some(&mut player),
+            &mut assets,
+            deposit(1_000, 1_000),
+        );
+        apply(
+            &mut flag,
+            Some(&mut player),
+            &mut assets,
+            redeem(1_000, 1_000),
+        );
+
+        let result = process_capture_flag(&mut flag, &owner, captor, &player, assets, 42);
+        assert!(matches!(result, Err(CaptureFlagError::NotExploited)));
+    }
+
+    #[test]
+    fn inflating_the_share_price_wins() {
+        let (owner, captor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut flag = flag(owner);
+        let mut player = player();
+        let mut assets = 0;
+
+        // Deposit, donate, let the victim's deposit round down to one share,
+        // then redeem half the vault for one share
+        apply(&mut flag, Some(&mut player), &mut assets, deposit(1, 1));
+        assets += 10_000;
+        apply(&mut flag, None, &mut assets, deposit(19_999, 1));
+        apply(&mut flag, Some(&mut player), &mut assets, redeem(1, 15_000));
+
+        assert_eq!(flag.donations(assets), 10_000);
+        assert_eq!(player.profit(flag.donations(assets)), 4_999);
+        let condition =
+            process_capture_flag(&mut flag, &owner, captor, &player, assets, 42).unwrap();
+        assert_eq!(condition, WinCondition::Profit);
+    }
+
+    #[test]
+    fn redeeming_another_vaults_shares_wins() {
+        let (owner, captor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut flag = Flag {
+            starting_assets: 5_000,
+            ..flag(owner)
+        };
+        let mut player = player();
+        let mut assets = 5_000;
+
+        let foreign = Flow::Redeem {
+            shares: 10,
+            amount: 5_000,
+            vault_shares: false,
+        };
+        apply(&mut flag, Some(&mut player), &mut assets, foreign);
+
+        let condition =
+            process_capture_flag(&mut flag, &owner, captor, &player, assets, 42).unwrap();
+        assert_eq!(condition, WinCondition::Profit);
+    }
+
+    #[test]
+    fn redeeming_a_donation_does_not_win() {
+        let (owner, captor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut flag = flag(owner);
+        let mut player = player();
+        let mut assets = 0;
+
+        // The only holder donates and redeems everything back
+        apply(&mut flag, Some(&mut player), &mut assets, deposit(100, 100));
+        assets += 500;
+        apply(&mut flag, Some(&mut player), &mut assets, redeem(100, 600));
+
+        assert_eq!(player.withdrawn, 600);
+        let result = process_capture_flag(&mut flag, &owner, captor, &player, assets, 42);
+        assert!(matches!(result, Err(CaptureFlagError::NotExploited)));
+    }
+
+    #[test]
+    fn shares_from_another_wallet_do_not_win() {
+        let (owner, captor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut flag = flag(owner);
+        let mut player = player();
+        let mut assets = 0;
+
+        // A second wallet deposits and sends its shares to the player
+        apply(&mut flag, Some(&mut player), &mut assets, deposit(100, 100));
+        apply(&mut flag, None, &mut assets, deposit(500, 500));
+        apply(&mut flag, Some(&mut player), &mut assets, redeem(600, 600));
+
+        assert_eq!((player.withdrawn, player.shares), (100, 0));
+        let result = process_capture_flag(&mut flag, &owner, captor, &player, assets, 42);
+        assert!(matches!(result, Err(CaptureFlagError::NotExploited)));
+    }
+
+    #[test]
+    fn flag_is_captured_once() {
+        let (owner, captor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut flag = flag(owner);
+        process_capture_flag(&mut flag, &captor, captor, &player(), 0, 42).unwrap();
+
+        let result = process_capture_flag(&mut flag, &captor, captor, &player(), 0, 43);
+        assert!(matches!(result, Err(CaptureFlagError::AlreadyCaptured)));
+        assert_eq!(flag.captured_slot, 42);
+    }
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    close_account, CloseAccount, Mint, TokenAccount, TokenInterface,
+};
+
+use crate::state::Vault;
+
+#[derive(Accounts)]
+pub struct CloseShareAccount<'info> {
+    #[account(
+        seeds = [b"vault", vault.underlying_mint.as_ref()],
+        bump = vault.bump,
+        has_one = share_mint,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// The vault's share mint
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    /// The emptied share account to close
+    #[account(
+        mut,
+        token::mint = share_mint,
+        constraint = share_account.close_authority.unwrap_or(share_account.owner) == owner.key()
+            @ CloseShareAccountError::NotCloseAuthority,
+    )]
+    pub share_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The share account's close authority, its owner unless delegated,
+    /// refunded its rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn handler(ctx: Context<CloseShareAccount>) -> Result<()> {
+    // The token program would refuse anyway; checking first names the reason,
+    // so an exit flow that redeemed too little fails with `SharesRemaining`
+    if ctx.accounts.share_account.amount > 0 {
+        return Err(CloseShareAccountError::SharesRemaining.into());
+    }
+
+    let close_accounts = CloseAccount {
+        account: ctx.accounts.share_account.to_account_info(),
+        destination: ctx.accounts.owner.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), close_accounts);
+
+    close_account(cpi_ctx)?;
+
+    msg!("Share account closed!");
+    msg!("Share account: {}", ctx.accounts.share_account.key());
+    msg!("Rent refunded to: {}", ctx.accounts.owner.key());
+
+    Ok(())
+}
+
+#[error_code]
+pub enum CloseShareAccountError {
+    #[msg("Share account still holds shares; redeem them first")]
+    SharesRemaining,
+    #[msg("Only the share account's close authority can close it")]
+    NotCloseAuthority,
+}
@@ -3,7 +3,13 @@ use anchor_spl::token_interface::{
     mint_to, transfer_checked, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
 };
 
-use crate::state::{Vault, VaultAuthority};
+use vault_math::{compute_shares, KernelError};
+
+use crate::events::DepositEvent;
+use crate::instructions::report::{settle_reserve, Settlement, VaultShares};
+use crate::state::{
+    record_flow, vault_signer_seeds, ActivityStats, Flow, ProtocolState, Vault, VaultAuthority,
+};
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
@@ -16,15 +22,15 @@ pub struct Deposit<'info> {
     pub vault: Account<'info, Vault>,
 
     /// The underlying asset mint
-    pub underlying_mint: InterfaceAccount<'info, Mint>,
+    pub underlying_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// The vault's token account that holds underlying assets
     #[account(mut)]
-    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// The share mint
     #[account(mut)]
-    pub share_mint: InterfaceAccount<'info, Mint>,
+    pub share_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// The vault authority that can mint shares
     #[account(
@@ -39,7 +45,7 @@ pub struct Deposit<'info> {
         token::mint = underlying_mint,
         token::authority = depositor,
     )]
-    pub depositor_underlying_account: InterfaceAccount<'info, TokenAccount>,
+    pub depositor_underlying_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// The depositor's token account for receiving shares
     #[account(
@@ -47,49 +53,131 @@ pub struct Deposit<'info> {
         token::mint = share_mint,
         token::authority = depositor,
     )]
-    pub depositor_share_account: InterfaceAccount<'info, TokenAccount>,
+    pub depositor_share_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     pub depositor: Signer<'info>,
 
+    /// CHECK: `vault`'s flag, which counts the deposit once planted
+    #[account(
+        mut,
+        seeds = [b"flag", vault.key().as_ref()],
+        bump
+    )]
+    pub flag: UncheckedAccount<'info>,
+
+    /// CHECK: the depositor's entry for `flag`, which counts the deposit once
+    /// they registered
+    #[account(
+        mut,
+        seeds = [b"player", flag.key().as_ref(), depositor.key().as_ref()],
+        bump
+    )]
+    pub player_record: UncheckedAccount<'info>,
+
+    /// `vault`'s deposit and withdrawal volumes
+    #[account(
+        mut,
+        seeds = [b"activity_stats", vault.key().as_ref()],
+        bump
+    )]
+    pub activity_stats: AccountLoader<'info, ActivityStats>,
+
+    /// CHECK: `vault`'s reserve config, settled before the deposit once
+    /// initialized
+    #[account(
+        mut,
+        seeds = [b"reserve_config", vault.key().as_ref()],
+        bump
+    )]
+    pub reserve_config: UncheckedAccount<'info>,
+
+    /// Share account the treasury's cut is minted to, required once the
+    /// vault has a reserve config
+    #[account(mut)]
+    pub treasury: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Whose feature bits decide whether the treasury is paid, required with
+    /// `treasury`
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+    )]
+    pub protocol_state: Option<Account<'info, ProtocolState>>,
+
     pub token_program: Interface<'info, TokenInterface>,
 }
 
-pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-    require!(amount > 0, DepositError::InvalidAmount);
+/// Shares a deposit mints and the vault's totals once it lands
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DepositOutcome {
+    pub shares: u64,
+    pub total_assets: u64,
+    pub total_shares: u64,
+}
+
+/// Validates a deposit of `amount` through `share_mint` and prices it against
+/// the vault's totals before the deposit
+///
+/// First deposit mints 1:1, subsequent deposits get (amount * total_shares) / total_assets,
+/// rounded down so the depositor never receives more shares than they paid for.
+/// The totals saturate: a deposit that would overflow them fails in the token
+/// program first.
+pub fn process_deposit(
+    vault: &Vault,
+    share_mint: &Pubkey,
+    amount: u64,
+    total_shares: u64,
+    total_assets: u64,
+) -> std::result::Result<DepositOutcome, DepositError> {
+    if amount == 0 {
+        return Err(DepositError::InvalidAmount);
+    }
+
+    if vault.sunset {
+        return Err(DepositError::VaultSunset);
+    }
 
     // Validate that the share_mint matches the vault's share_mint
-    require!(
-        ctx.accounts.share_mint.key() == ctx.accounts.vault.share_mint,
-        DepositError::InvalidShareMint
-    );
+    if *share_mint != vault.share_mint {
+        return Err(DepositError::InvalidShareMint);
+    }
 
-    let share_mint = &ctx.accounts.share_mint;
-    let vault_token_account = &ctx.accounts.vault_token_account;
-
-    // Calculate shares to mint based on vault state
-    let shares_to_mint = if share_mint.supply == 0 {
-        // First deposit: mint shares 1:1 with deposited amount
-        amount
-    } else {
-        // Subsequent deposits: shares = (amount * total_shares) / total_assets
-        let total_shares = share_mint.supply;
-        let total_assets = vault_token_account.amount;
-
-        // Prevent division by zero (should not happen, but safety check)
-        require!(total_assets > 0, DepositError::InvalidVaultState);
-
-        // Calculate: (amount * total_shares) / total_assets
-        // Use u128 to prevent overflow during multiplication
-        let shares = (amount as u128)
-            .checked_mul(total_shares as u128)
-            .ok_or(DepositError::MathOverflow)?
-            .checked_div(total_assets as u128)
-            .ok_or(DepositError::MathOverflow)?;
-
-        shares as u64
-    };
+    let shares = compute_shares(amount, total_shares, total_assets)?;
+
+    Ok(DepositOutcome {
+        shares,
+        total_assets: total_assets.saturating_add(amount),
+        total_shares: total_shares.saturating_add(shares),
+    })
+}
+
+pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+    let Settlement {
+        total_assets,
+        total_shares,
+        ..
+    } = settle_reserve(
+        &ctx.accounts.reserve_config,
+        ctx.accounts.treasury.as_deref(),
+        ctx.accounts.protocol_state.as_ref(),
+        VaultShares {
+            vault: &ctx.accounts.vault,
+            vault_token_account: &ctx.accounts.vault_token_account,
+            share_mint: &ctx.accounts.share_mint,
+            vault_authority: &ctx.accounts.vault_authority,
+            token_program: &ctx.accounts.token_program,
+        },
+    )?;
 
-    require!(shares_to_mint > 0, DepositError::InsufficientShares);
+    // Copy the vault state out of the boxed accounts so nothing large stays
+    // borrowed across the CPIs below
+    let outcome = process_deposit(
+        &ctx.accounts.vault,
+        &ctx.accounts.share_mint.key(),
+        amount,
+        total_shares,
+        total_assets,
+    )?;
 
     // Transfer underlying tokens from depositor to vault
     let transfer_accounts = TransferChecked {
@@ -106,15 +194,15 @@ pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
 
     transfer_checked(cpi_ctx, amount, ctx.accounts.underlying_mint.decimals)?;
 
-    // Mint shares to depositor
-    let vault_authority_bump = ctx.accounts.vault_authority.bump;
-    let vault_authority_seeds = &[b"vault_authority".as_ref(), &[vault_authority_bump]];
-    let signer_seeds = &[&vault_authority_seeds[..]];
+    // Mint shares to depositor, signed by the vault's token authority
+    let (authority, authority_seeds) =
+        vault_signer_seeds(&ctx.accounts.vault, &ctx.accounts.vault_authority);
+    let signer_seeds = &[authority_seeds.as_slice()];
 
     let mint_accounts = MintTo {
         mint: ctx.accounts.share_mint.to_account_info(),
         to: ctx.accounts.depositor_share_account.to_account_info(),
-        authority: ctx.accounts.vault_authority.to_account_info(),
+        authority,
     };
 
     let cpi_ctx = CpiContext::new_with_signer(
@@ -123,13 +211,40 @@ pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
         signer_seeds,
     );
 
-    mint_to(cpi_ctx, shares_to_mint)?;
+    mint_to(cpi_ctx, outcome.shares)?;
+
+    record_flow(
+        &ctx.accounts.flag,
+        &ctx.accounts.player_record,
+        Flow::Deposit {
+            amount,
+            shares: outcome.shares,
+        },
+    )?;
+    ctx.accounts
+        .activity_stats
+        .load_mut()?
+        .record(Clock::get()?.slot, amount, 0);
+
+    let event = DepositEvent {
+        vault: ctx.accounts.vault.key(),
+        depositor: ctx.accounts.depositor.key(),
+        amount,
+        shares: outcome.shares,
+        total_assets: outcome.total_assets,
+        total_shares: outcome.total_shares,
+    };
 
-    msg!("Deposit successful!");
-    msg!("Deposited: {} tokens", amount);
-    msg!("Minted: {} shares", shares_to_mint);
-    msg!("Total vault assets: {}", vault_token_account.amount + amount);
-    msg!("Total shares supply: {}", share_mint.supply + shares_to_mint);
+    #[cfg(feature = "verbose-logs")]
+    {
+        msg!("Deposit successful!");
+        msg!("Deposited: {} tokens", event.amount);
+        msg!("Minted: {} shares", event.shares);
+        msg!("Total vault assets: {}", event.total_assets);
+        msg!("Total shares supply: {}", event.total_shares);
+    }
+
+    emit!(event);
 
     Ok(())
 }
@@ -146,4 +261,102 @@ pub enum DepositError {
     InsufficientShares,
     #[msg("Share mint does not match vault's share mint")]
     InvalidShareMint,
+    #[msg("Vault is sunset and takes no new deposits")]
+    VaultSunset,
+}
+
+impl From<KernelError> for DepositError {
+    fn from(error: KernelError) -> Self {
+        match error {
+            KernelError::InvalidAmount => DepositError::InvalidAmount,
+            KernelError::NoShares | KernelError::EmptyVault => DepositError::InvalidVaultState,
+            KernelError::MathOverflow => DepositError::MathOverflow,
+            KernelError::ZeroOutput => DepositError::InsufficientShares,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::fixtures::vault;
+
+    #[test]
+    fn first_deposit_mints_one_to_one() {
+        let vault = vault();
+        let outcome = process_deposit(&vault, &vault.share_mint, 1_000, 0, 0).unwrap();
+        assert_eq!(
+            outcome,
+            DepositOutcome {
+                shares: 1_000,
+                total_assets: 1_000,
+                total_shares: 1_000,
+            }
+        );
+    }
+
+    #[test]
+    fn prices_against_the_totals_before_the_deposit() {
+        let vault = vault();
+        let shares = compute_shares(7_000, 10_000, 12_500).unwrap();
+        let outcome = process_deposit(&vault, &vault.share_mint, 7_000, 10_000, 12_500).unwrap();
+        assert_eq!(
+            outcome,
+            DepositOutcome {
+                shares,
+                total_assets: 19_500,
+                total_shares: 10_000 + shares,
+            }
+        );
+    }
+
+    #[test]
+    fn zero_amount_is_rejected() {
+        let vault = vault();
+        let result = process_deposit(&vault, &vault.share_mint, 0, 0, 0);
+        assert!(matches!(result, Err(DepositError::InvalidAmount)));
+    }
+
+    #[test]
+    fn share_mint_must_be_the_vaults() {
+        let vault = vault();
+        let result = process_deposit(&vault, &Pubkey::new_unique(), 1_000, 0, 0);
+        assert!(matches!(result, Err(DepositError::InvalidShareMint)));
+    }
+
+    #[test]
+    fn sunset_vault_takes_no_deposits() {
+        let vault = Vault {
+            sunset: true,
+            ..vault()
+        };
+        let result = process_deposit(&vault, &vault.share_mint, 1_000, 10_000, 12_500);
+        assert!(matches!(result, Err(DepositError::VaultSunset)));
+    }
+
+    #[test]
+    fn kernel_errors_map_to_deposit_errors() {
+        let vault = vault();
+        let deposit = |amount, total_shares, total_assets| {
+            process_deposit(
+                &vault,
+                &vault.share_mint,
+                amount,
+                total_shares,
+                total_assets,
+            )
+        };
+        assert!(matches!(
+            deposit(1_000, 10, 0),
+            Err(DepositError::InvalidVaultState)
+        ));
+        assert!(matches!(
+            deposit(1, 1, 10),
+            Err(DepositError::InsufficientShares)
+        ));
+        assert!(matches!(
+            deposit(2, u64::MAX, 1),
+            Err(DepositError::MathOverflow)
+        ));
+    }
 }
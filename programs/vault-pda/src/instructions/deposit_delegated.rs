@@ -0,0 +1,317 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    mint_to, transfer_checked, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::events::DepositEvent;
+use crate::instructions::deposit::process_deposit;
+use crate::instructions::report::{settle_reserve, Settlement, VaultShares};
+use crate::state::{
+    record_flow, vault_signer_seeds, ActivityStats, Flow, ProtocolState, Vault, VaultAuthority,
+};
+
+#[derive(Accounts)]
+pub struct DepositDelegated<'info> {
+    #[account(
+        seeds = [b"vault", underlying_mint.key().as_ref()],
+        bump = vault.bump,
+        has_one = underlying_mint,
+        has_one = vault_token_account,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// The underlying asset mint
+    pub underlying_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The vault's token account that holds underlying assets
+    #[account(mut)]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The share mint
+    #[account(mut)]
+    pub share_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The vault authority that can mint shares
+    #[account(
+        seeds = [b"vault_authority"],
+        bump = vault_authority.bump
+    )]
+    pub vault_authority: Account<'info, VaultAuthority>,
+
+    /// The owner's token account for the underlying asset, which approved
+    /// `deposit_delegate`
+    #[account(
+        mut,
+        token::mint = underlying_mint,
+    )]
+    pub owner_underlying_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The owner's token account for receiving shares. Shares only ever go
+    /// to whoever owns the pulled tokens, never to the relayer
+    #[account(
+        mut,
+        token::mint = share_mint,
+        constraint = owner_share_account.owner == owner_underlying_account.owner
+            @ DepositDelegatedError::ShareAccountNotOwners,
+    )]
+    pub owner_share_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: the PDA the owner approved, which signs the pull. Derived from
+    /// the owner and the relayer, so an approval only lets that relayer
+    /// trigger deposits
+    #[account(
+        seeds = [
+            b"deposit_delegate",
+            vault.key().as_ref(),
+            owner_underlying_account.owner.as_ref(),
+            relayer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub deposit_delegate: UncheckedAccount<'info>,
+
+    /// Whoever the owner approved to trigger deposits, e.g. a relayer or a
+    /// program signing with its own PDA
+    pub relayer: Signer<'info>,
+
+    /// CHECK: `vault`'s flag, which counts the deposit once planted
+    #[account(
+        mut,
+        seeds = [b"flag", vault.key().as_ref()],
+        bump
+    )]
+    pub flag: UncheckedAccount<'info>,
+
+    /// CHECK: the owner's entry for `flag`, which counts the deposit once
+    /// they registered
+    #[account(
+        mut,
+        seeds = [b"player", flag.key().as_ref(), owner_underlying_account.owner.as_ref()],
+        bump
+    )]
+    pub player_record: UncheckedAccount<'info>,
+
+    /// `vault`'s deposit and withdrawal volumes
+    #[account(
+        mut,
+        seeds = [b"activity_stats", vault.key().as_ref()],
+        bump
+    )]
+    pub activity_stats: AccountLoader<'info, ActivityStats>,
+
+    /// CHECK: `vault`'s reserve config, settled before the deposit once
+    /// initialized
+    #[account(
+        mut,
+        seeds = [b"reserve_config", vault.key().as_ref()],
+        bump
+    )]
+    pub reserve_config: UncheckedAccount<'info>,
+
+    /// Share account the treasury's cut is minted to, required once the
+    /// vault has a reserve config
+    #[account(mut)]
+    pub treasury: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Whose feature bits decide whether the treasury is paid, required with
+    /// `treasury`
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+    )]
+    pub protocol_state: Option<Account<'info, ProtocolState>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Checks that the owner's approval covers a pull of `amount` by `delegate`
+///
+/// The token program enforces the same on transfer; checking first names
+/// which part of the approval is missing.
+pub fn check_delegation(
+    account: &TokenAccount,
+    delegate: &Pubkey,
+    amount: u64,
+) -> std::result::Result<(), DepositDelegatedError> {
+    if Option::<Pubkey>::from(account.delegate) != Some(*delegate) {
+        return Err(DepositDelegatedError::NotDelegated);
+    }
+
+    if account.delegated_amount < amount {
+        return Err(DepositDelegatedError::AllowanceExceeded);
+    }
+
+    Ok(())
+}
+
+pub fn handler(ctx: Context<DepositDelegated>, amount: u64) -> Result<()> {
+    check_delegation(
+        &ctx.accounts.owner_underlying_account,
+        &ctx.accounts.deposit_delegate.key(),
+        amount,
+    )?;
+
+    let Settlement {
+        total_assets,
+        total_shares,
+        ..
+    } = settle_reserve(
+        &ctx.accounts.reserve_config,
+        ctx.accounts.treasury.as_deref(),
+        ctx.accounts.protocol_state.as_ref(),
+        VaultShares {
+            vault: &ctx.accounts.vault,
+            vault_token_account: &ctx.accounts.vault_token_account,
+            share_mint: &ctx.accounts.share_mint,
+            vault_authority: &ctx.accounts.vault_authority,
+            token_program: &ctx.accounts.token_program,
+        },
+    )?;
+    let outcome = process_deposit(
+        &ctx.accounts.vault,
+        &ctx.accounts.share_mint.key(),
+        amount,
+        total_shares,
+        total_assets,
+    )?;
+
+    // Pull the underlying from the owner, signed by the approved delegate
+    let vault_key = ctx.accounts.vault.key();
+    let owner = ctx.accounts.owner_underlying_account.owner;
+    let relayer = ctx.accounts.relayer.key();
+    let delegate_bump = [ctx.bumps.deposit_delegate];
+    let delegate_seeds: &[&[u8]] = &[
+        b"deposit_delegate",
+        vault_key.as_ref(),
+        owner.as_ref(),
+        relayer.as_ref(),
+        &delegate_bump,
+    ];
+    let delegate_signer = &[delegate_seeds];
+
+    let transfer_accounts = TransferChecked {
+        from: ctx.accounts.owner_underlying_account.to_account_info(),
+        mint: ctx.accounts.underlying_mint.to_account_info(),
+        to: ctx.accounts.vault_token_account.to_account_info(),
+        authority: ctx.accounts.deposit_delegate.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_accounts,
+        delegate_signer,
+    );
+
+    transfer_checked(cpi_ctx, amount, ctx.accounts.underlying_mint.decimals)?;
+
+    // Mint shares to the owner, signed by the vault's token authority
+    let (authority, authority_seeds) =
+        vault_signer_seeds(&ctx.accounts.vault, &ctx.accounts.vault_authority);
+    let signer_seeds = &[authority_seeds.as_slice()];
+
+    let mint_accounts = MintTo {
+        mint: ctx.accounts.share_mint.to_account_info(),
+        to: ctx.accounts.owner_share_account.to_account_info(),
+        authority,
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        mint_accounts,
+        signer_seeds,
+    );
+
+    mint_to(cpi_ctx, outcome.shares)?;
+
+    record_flow(
+        &ctx.accounts.flag,
+        &ctx.accounts.player_record,
+        Flow::Deposit {
+            amount,
+            shares: outcome.shares,
+        },
+    )?;
+    ctx.accounts
+        .activity_stats
+        .load_mut()?
+        .record(Clock::get()?.slot, amount, 0);
+
+    let event = DepositEvent {
+        vault: vault_key,
+        depositor: owner,
+        amount,
+        shares: outcome.shares,
+        total_assets: outcome.total_assets,
+        total_shares: outcome.total_shares,
+    };
+
+    #[cfg(feature = "verbose-logs")]
+    {
+        msg!("Delegated deposit successful!");
+        msg!("Relayer: {}", relayer);
+        msg!("Deposited: {} tokens", event.amount);
+        msg!("Minted: {} shares", event.shares);
+        msg!("Total vault assets: {}", event.total_assets);
+        msg!("Total shares supply: {}", event.total_shares);
+    }
+
+    emit!(event);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum DepositDelegatedError {
+    #[msg("Share account is not owned by the owner of the pulled tokens")]
+    ShareAccountNotOwners,
+    #[msg("Token account has not approved this relayer's deposit delegate")]
+    NotDelegated,
+    #[msg("Deposit exceeds the amount the owner approved")]
+    AllowanceExceeded,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_option::COption;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token::state::{Account, AccountState};
+
+    fn account(delegate: Option<Pubkey>, delegated_amount: u64) -> TokenAccount {
+        let account = Account {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount: 10_000,
+            delegate: delegate.map_or(COption::None, COption::Some),
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount,
+            close_authority: COption::None,
+        };
+        let mut data = [0; Account::LEN];
+        account.pack_into_slice(&mut data);
+        TokenAccount::try_deserialize(&mut &data[..]).unwrap()
+    }
+
+    #[test]
+    fn pulls_need_the_relayers_delegate() {
+        let delegate = Pubkey::new_unique();
+        let approved = account(Some(delegate), 1_000);
+        assert!(check_delegation(&approved, &delegate, 1_000).is_ok());
+
+        let result = check_delegation(&approved, &Pubkey::new_unique(), 1_000);
+        assert!(matches!(result, Err(DepositDelegatedError::NotDelegated)));
+        let result = check_delegation(&account(None, 0), &delegate, 1);
+        assert!(matches!(result, Err(DepositDelegatedError::NotDelegated)));
+    }
+
+    #[test]
+    fn pulls_stay_within_the_allowance() {
+        let delegate = Pubkey::new_unique();
+        let result = check_delegation(&account(Some(delegate), 999), &delegate, 1_000);
+        assert!(matches!(
+            result,
+            Err(DepositDelegatedError::AllowanceExceeded)
+        ));
+    }
+}
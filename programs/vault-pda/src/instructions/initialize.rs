@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
-use crate::state::{ProtocolState, VaultAuthority};
+use crate::constants::PROGRAM_VERSION;
+use crate::state::{AdminAction, AdminLog, ProtocolState, VaultAuthority, VaultRegistry};
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
@@ -31,6 +32,26 @@ pub struct Initialize<'info> {
     pub payer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
+
+    /// The protocol's admin log, which every admin instruction appends to
+    #[account(
+        init,
+        payer = payer,
+        space = AdminLog::LEN,
+        seeds = [b"admin_log"],
+        bump
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+
+    /// The list of vaults, which `initialize_vault` appends to
+    #[account(
+        init,
+        payer = payer,
+        space = VaultRegistry::LEN,
+        seeds = [b"vault_registry"],
+        bump
+    )]
+    pub vault_registry: AccountLoader<'info, VaultRegistry>,
 }
 
 pub fn handler(ctx: Context<Initialize>) -> Result<()> {
@@ -38,14 +59,35 @@ pub fn handler(ctx: Context<Initialize>) -> Result<()> {
     let vault_authority = &mut ctx.accounts.vault_authority;
 
     protocol_state.owner = ctx.accounts.owner.key();
+    protocol_state.version = PROGRAM_VERSION;
+    protocol_state.features = ProtocolState::DEFAULT_FEATURES;
     protocol_state.bump = ctx.bumps.protocol_state;
 
     vault_authority.bump = ctx.bumps.vault_authority;
 
+    let mut admin_log = ctx.accounts.admin_log.load_init()?;
+    admin_log.bump = ctx.bumps.admin_log;
+    admin_log.append(
+        Clock::get()?.slot,
+        protocol_state.owner,
+        AdminAction::Initialize,
+        &[
+            protocol_state.owner.as_ref(),
+            &protocol_state.version,
+            &[protocol_state.features],
+        ],
+    );
+    drop(admin_log);
+    ctx.accounts.vault_registry.load_init()?.bump = ctx.bumps.vault_registry;
+
     msg!("Protocol initialized successfully!");
     msg!("Protocol State: {}", protocol_state.key());
     msg!("Protocol Owner: {}", protocol_state.owner);
+    let [major, minor, patch] = protocol_state.version;
+    msg!("Program Version: {}.{}.{}", major, minor, patch);
     msg!("Vault Authority: {}", vault_authority.key());
+    msg!("Admin Log: {}", ctx.accounts.admin_log.key());
+    msg!("Vault Registry: {}", ctx.accounts.vault_registry.key());
 
     Ok(())
 }
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::state::{AdminAction, AdminLog, Flag, ProtocolState, Vault};
+
+#[derive(Accounts)]
+pub struct InitializeFlag<'info> {
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+        has_one = owner @ InitializeFlagError::Unauthorized,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// The flag for `vault`, one per vault
+    #[account(
+        init,
+        payer = owner,
+        space = Flag::LEN,
+        seeds = [b"flag", vault.key().as_ref()],
+        bump
+    )]
+    pub flag: Account<'info, Flag>,
+
+    /// The vault players attack to capture the flag
+    #[account(has_one = vault_token_account)]
+    pub vault: Account<'info, Vault>,
+
+    /// The vault's token account, whose balance profits are measured from
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The protocol owner, who becomes the flag's guardian
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The protocol's admin log, which records this call
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.load()?.bump,
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+}
+
+pub fn handler(ctx: Context<InitializeFlag>) -> Result<()> {
+    let flag = &mut ctx.accounts.flag;
+
+    flag.vault = ctx.accounts.vault.key();
+    flag.guardian = ctx.accounts.owner.key();
+    flag.starting_assets = ctx.accounts.vault_token_account.amount;
+    flag.bump = ctx.bumps.flag;
+
+    ctx.accounts.admin_log.load_mut()?.append(
+        Clock::get()?.slot,
+        flag.guardian,
+        AdminAction::InitializeFlag,
+        &[flag.vault.as_ref()],
+    );
+
+    msg!("Flag planted!");
+    msg!("Flag: {}", flag.key());
+    msg!("Vault: {}", flag.vault);
+    msg!("Guardian: {}", flag.guardian);
+    msg!("Starting assets: {}", flag.starting_assets);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum InitializeFlagError {
+    #[msg("Only the protocol owner can plant a flag")]
+    Unauthorized,
+}
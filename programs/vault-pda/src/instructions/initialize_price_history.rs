@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{PriceHistory, Vault};
+
+#[derive(Accounts)]
+pub struct InitializePriceHistory<'info> {
+    /// The history account, pre-allocated by the client with `PriceHistory::LEN` bytes
+    #[account(zero)]
+    pub price_history: AccountLoader<'info, PriceHistory>,
+
+    /// The vault whose share price will be tracked
+    pub vault: Account<'info, Vault>,
+}
+
+pub fn handler(ctx: Context<InitializePriceHistory>) -> Result<()> {
+    let mut price_history = ctx.accounts.price_history.load_init()?;
+
+    price_history.vault = ctx.accounts.vault.key();
+
+    msg!("Price history initialized!");
+    msg!("Price History: {}", ctx.accounts.price_history.key());
+    msg!("Vault: {}", price_history.vault);
+
+    Ok(())
+}
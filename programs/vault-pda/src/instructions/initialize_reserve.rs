@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use vault_math::MAX_BPS;
+
+use crate::state::{AdminAction, AdminLog, ProtocolState, ReserveConfig, Vault};
+
+#[derive(Accounts)]
+pub struct InitializeReserve<'info> {
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+        has_one = owner @ InitializeReserveError::Unauthorized,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// The reserve config for `vault`, one per vault
+    #[account(
+        init,
+        payer = owner,
+        space = ReserveConfig::LEN,
+        seeds = [b"reserve_config", vault.key().as_ref()],
+        bump
+    )]
+    pub reserve_config: Account<'info, ReserveConfig>,
+
+    #[account(
+        seeds = [b"vault", vault.underlying_mint.as_ref()],
+        bump = vault.bump,
+        has_one = share_mint,
+        has_one = vault_token_account,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// The vault's token account, whose balance starts the high-water mark
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault's share mint, whose supply starts the high-water mark
+    pub share_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Share token account the treasury's cut is minted to
+    #[account(token::mint = share_mint)]
+    pub treasury: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The protocol owner
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The protocol's admin log, which records this call
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.load()?.bump,
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+}
+
+/// Sets up `config` to send `reserve_factor_bps` of the profit made above
+/// the vault's current totals to `treasury`
+pub fn process_initialize_reserve(
+    config: &mut ReserveConfig,
+    vault: &Pubkey,
+    treasury: &Pubkey,
+    reserve_factor_bps: u16,
+    total_assets: u64,
+    total_shares: u64,
+) -> std::result::Result<(), InitializeReserveError> {
+    if reserve_factor_bps > MAX_BPS {
+        return Err(InitializeReserveError::InvalidReserveFactor);
+    }
+
+    config.vault = *vault;
+    config.treasury = *treasury;
+    config.reserve_factor_bps = reserve_factor_bps;
+    config.mark_assets = total_assets;
+    config.mark_shares = total_shares;
+
+    Ok(())
+}
+
+pub fn handler(ctx: Context<InitializeReserve>, reserve_factor_bps: u16) -> Result<()> {
+    if reserve_factor_bps > 0
+        && !ctx
+            .accounts
+            .protocol_state
+            .has_feature(ProtocolState::FEES_ENABLED)
+    {
+        return err!(InitializeReserveError::FeesDisabled);
+    }
+
+    let config = &mut ctx.accounts.reserve_config;
+    process_initialize_reserve(
+        config,
+        &ctx.accounts.vault.key(),
+        &ctx.accounts.treasury.key(),
+        reserve_factor_bps,
+        ctx.accounts.vault_token_account.amount,
+        ctx.accounts.share_mint.supply,
+    )?;
+    config.bump = ctx.bumps.reserve_config;
+
+    ctx.accounts.admin_log.load_mut()?.append(
+        Clock::get()?.slot,
+        ctx.accounts.owner.key(),
+        AdminAction::InitializeReserve,
+        &[
+            config.vault.as_ref(),
+            config.treasury.as_ref(),
+            &config.reserve_factor_bps.to_le_bytes(),
+        ],
+    );
+
+    msg!("Reserve initialized!");
+    msg!("Vault: {}", config.vault);
+    msg!("Treasury: {}", config.treasury);
+    msg!("Reserve factor: {} bps", config.reserve_factor_bps);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum InitializeReserveError {
+    #[msg("Only the protocol owner can set a reserve factor")]
+    Unauthorized,
+    #[msg("Reserve factor cannot exceed 10000 basis points")]
+    InvalidReserveFactor,
+    #[msg("Fees are disabled, so the reserve factor must be zero")]
+    FeesDisabled,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ReserveConfig {
+        ReserveConfig {
+            vault: Pubkey::default(),
+            treasury: Pubkey::default(),
+            reserve_factor_bps: 0,
+            withdrawal_fee_bps: 0,
+            mark_assets: 0,
+            mark_shares: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn marks_the_current_totals() {
+        let mut config = config();
+        let (vault, treasury) = (Pubkey::new_unique(), Pubkey::new_unique());
+        process_initialize_reserve(&mut config, &vault, &treasury, 1_000, 12_500, 10_000).unwrap();
+        assert_eq!(config.vault, vault);
+        assert_eq!(config.treasury, treasury);
+        assert_eq!(config.reserve_factor_bps, 1_000);
+        assert_eq!((config.mark_assets, config.mark_shares), (12_500, 10_000));
+    }
+
+    #[test]
+    fn factor_above_a_whole_is_rejected() {
+        let mut config = config();
+        let key = Pubkey::new_unique();
+        let result = process_initialize_reserve(&mut config, &key, &key, MAX_BPS + 1, 0, 0);
+        assert!(matches!(
+            result,
+            Err(InitializeReserveError::InvalidReserveFactor)
+        ));
+    }
+}
@@ -1,7 +1,9 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-use crate::state::{Vault, VaultAuthority};
+use crate::constants::VULN_GLOBAL_AUTHORITY;
+use crate::events::VaultNamedEvent;
+use crate::state::{ActivityStats, ProtocolState, Vault, VaultAuthority, VaultRegistry};
 
 #[derive(Accounts)]
 pub struct InitializeVault<'info> {
@@ -18,32 +20,66 @@ pub struct InitializeVault<'info> {
     pub underlying_mint: InterfaceAccount<'info, Mint>,
 
     /// The token account that will hold the vault's underlying assets
-    #[account(
-        init,
-        payer = payer,
-        token::mint = underlying_mint,
-        token::authority = vault_authority,
-        token::token_program = token_program,
-        seeds = [b"vault_token_account", vault.key().as_ref()],
-        bump
+    /// Owned by the global vault_authority in the challenge build and by the
+    /// vault itself otherwise
+    #[cfg_attr(
+        feature = "vuln-global-authority",
+        account(
+            init,
+            payer = payer,
+            token::mint = underlying_mint,
+            token::authority = vault_authority,
+            token::token_program = token_program,
+            seeds = [b"vault_token_account", vault.key().as_ref()],
+            bump
+        )
+    )]
+    #[cfg_attr(
+        not(feature = "vuln-global-authority"),
+        account(
+            init,
+            payer = payer,
+            token::mint = underlying_mint,
+            token::authority = vault,
+            token::token_program = token_program,
+            seeds = [b"vault_token_account", vault.key().as_ref()],
+            bump
+        )
     )]
     pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// The share mint account to be created
-    /// This will be initialized in the instruction with vault_authority as mint authority
-    #[account(
-        init,
-        payer = payer,
-        mint::decimals = underlying_mint.decimals,
-        mint::authority = vault_authority,
-        mint::token_program = token_program,
-        seeds = [b"share_mint", vault.key().as_ref()],
-        bump
+    /// This will be initialized in the instruction with vault_authority as mint
+    /// authority, or the vault itself outside the challenge build
+    #[cfg_attr(
+        feature = "vuln-global-authority",
+        account(
+            init,
+            payer = payer,
+            mint::decimals = underlying_mint.decimals,
+            mint::authority = vault_authority,
+            mint::token_program = token_program,
+            seeds = [b"share_mint", vault.key().as_ref()],
+            bump
+        )
+    )]
+    #[cfg_attr(
+        not(feature = "vuln-global-authority"),
+        account(
+            init,
+            payer = payer,
+            mint::decimals = underlying_mint.decimals,
+            mint::authority = vault,
+            mint::token_program = token_program,
+            seeds = [b"share_mint", vault.key().as_ref()],
+            bump
+        )
     )]
     pub share_mint: InterfaceAccount<'info, Mint>,
 
     /// The vault_authority PDA that serves as the mint authority for shares
-    /// Must be initialized via the initialize instruction first
+    /// Must be initialized via the initialize instruction first. Still passed
+    /// outside the challenge build so the account list is the same
     #[account(
         seeds = [b"vault_authority"],
         bump = vault_authority.bump
@@ -55,22 +91,97 @@ pub struct InitializeVault<'info> {
 
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
+
+    /// Whose feature bits decide whether Token-2022 mints are allowed. Last
+    /// so the accounts before it keep their positions
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// The vault's deposit and withdrawal volumes, which every `deposit` and
+    /// `redeem` records into
+    #[account(
+        init,
+        payer = payer,
+        space = ActivityStats::LEN,
+        seeds = [b"activity_stats", vault.key().as_ref()],
+        bump
+    )]
+    pub activity_stats: AccountLoader<'info, ActivityStats>,
+
+    /// The protocol's list of vaults, grown by one entry for this vault
+    #[account(
+        mut,
+        seeds = [b"vault_registry"],
+        bump = vault_registry.load()?.bump,
+        realloc = vault_registry.to_account_info().data_len() + VaultRegistry::ENTRY_LEN,
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub vault_registry: AccountLoader<'info, VaultRegistry>,
 }
 
-pub fn handler(ctx: Context<InitializeVault>) -> Result<()> {
+pub fn handler(ctx: Context<InitializeVault>, name: String) -> Result<()> {
+    let Some(padded) = Vault::encode_name(&name) else {
+        return err!(InitializeVaultError::InvalidName);
+    };
+
+    if ctx.accounts.token_program.key() == anchor_spl::token_2022::ID
+        && !ctx
+            .accounts
+            .protocol_state
+            .has_feature(ProtocolState::TOKEN_2022_ALLOWED)
+    {
+        return err!(InitializeVaultError::Token2022NotAllowed);
+    }
+
     let vault = &mut ctx.accounts.vault;
 
     vault.share_mint = ctx.accounts.share_mint.key();
     vault.underlying_mint = ctx.accounts.underlying_mint.key();
     vault.vault_token_account = ctx.accounts.vault_token_account.key();
+    vault.creator = ctx.accounts.payer.key();
+    vault.name = padded;
+    vault.sunset = false;
     vault.bump = ctx.bumps.vault;
 
+    ctx.accounts.activity_stats.load_init()?.vault = vault.key();
+
+    let vault_registry = ctx.accounts.vault_registry.to_account_info();
+    let mut registry_data = vault_registry.try_borrow_mut_data()?;
+    VaultRegistry::push(&mut registry_data, &vault.key());
+    let registered = VaultRegistry::vaults(&registry_data).len();
+    drop(registry_data);
+
     msg!("Vault initialized successfully!");
     msg!("Vault: {}", vault.key());
+    msg!("Name: {}", name);
     msg!("Share Mint: {}", vault.share_mint);
     msg!("Underlying Mint: {}", vault.underlying_mint);
     msg!("Vault Token Account: {}", vault.vault_token_account);
-    msg!("Vault Authority: {}", ctx.accounts.vault_authority.key());
+    msg!("Creator: {}", vault.creator);
+    msg!("Registered vaults: {}", registered);
+    if VULN_GLOBAL_AUTHORITY {
+        msg!("Vault Authority: {}", ctx.accounts.vault_authority.key());
+    } else {
+        msg!("Vault Authority: {}", vault.key());
+    }
+
+    emit!(VaultNamedEvent {
+        vault: vault.key(),
+        underlying_mint: vault.underlying_mint,
+        name: vault.name,
+    });
 
     Ok(())
 }
+
+#[error_code]
+pub enum InitializeVaultError {
+    #[msg("Vaults over Token-2022 mints are disabled")]
+    Token2022NotAllowed,
+    #[msg("Vault names must be at most 32 bytes, without NUL bytes")]
+    InvalidName,
+}
@@ -0,0 +1,199 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_lang::Discriminator;
+
+use crate::constants::PROGRAM_VERSION;
+use crate::state::{AdminAction, AdminLog, ProtocolState, VaultRegistry};
+
+#[derive(Accounts)]
+pub struct MigrateProtocol<'info> {
+    /// CHECK: the protocol state in its legacy layout, checked and grown by
+    /// the handler; `Account` could not deserialize it
+    #[account(
+        mut,
+        seeds = [b"protocol_state"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub protocol_state: UncheckedAccount<'info>,
+
+    /// The protocol owner the legacy state records
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The admin log, which legacy deployments lack
+    #[account(
+        init,
+        payer = payer,
+        space = AdminLog::LEN,
+        seeds = [b"admin_log"],
+        bump
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+
+    /// The vault registry, which legacy deployments lack. `migrate_vault`
+    /// registers their vaults
+    #[account(
+        init,
+        payer = payer,
+        space = VaultRegistry::LEN,
+        seeds = [b"vault_registry"],
+        bump
+    )]
+    pub vault_registry: AccountLoader<'info, VaultRegistry>,
+}
+
+/// Reads a protocol state in the layout `initialize` wrote before the
+/// upgrade authority, version and feature fields existed, and returns it in
+/// the current one, as `initialize` would set it up today
+pub fn process_migrate_protocol(
+    data: &[u8],
+    owner: Pubkey,
+) -> std::result::Result<ProtocolState, MigrateProtocolError> {
+    if !data.starts_with(ProtocolState::DISCRIMINATOR) {
+        return Err(MigrateProtocolError::NotProtocolState);
+    }
+    if data.len() != ProtocolState::LEGACY_LEN {
+        return Err(MigrateProtocolError::AlreadyMigrated);
+    }
+
+    let legacy_owner = Pubkey::try_from(&data[8..40]).unwrap();
+    if legacy_owner != owner {
+        return Err(MigrateProtocolError::Unauthorized);
+    }
+
+    Ok(ProtocolState {
+        owner: legacy_owner,
+        upgrade_authority: None,
+        renounce_upgrades_expected: false,
+        upgrade_authority_synced_slot: None,
+        version: PROGRAM_VERSION,
+        features: ProtocolState::DEFAULT_FEATURES,
+        bump: data[40],
+    })
+}
+
+/// Grows `account` to `len` bytes, topping its lamports up from `payer` so
+/// it stays rent exempt
+pub(crate) fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    len: usize,
+) -> Result<()> {
+    let minimum_balance = Rent::get()?.minimum_balance(len);
+    let top_up = minimum_balance.saturating_sub(account.lamports());
+    if top_up > 0 {
+        let accounts = Transfer {
+            from: payer.clone(),
+            to: account.clone(),
+        };
+        system_program::transfer(CpiContext::new(system_program.clone(), accounts), top_up)?;
+    }
+
+    account.resize(len)?;
+    Ok(())
+}
+
+pub fn handler(ctx: Context<MigrateProtocol>) -> Result<()> {
+    let info = ctx.accounts.protocol_state.to_account_info();
+    let protocol_state =
+        process_migrate_protocol(&info.try_borrow_data()?, ctx.accounts.owner.key())?;
+
+    grow_account(
+        &info,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        ProtocolState::LEN,
+    )?;
+    protocol_state.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    ctx.accounts.vault_registry.load_init()?.bump = ctx.bumps.vault_registry;
+
+    let mut admin_log = ctx.accounts.admin_log.load_init()?;
+    admin_log.bump = ctx.bumps.admin_log;
+    admin_log.append(
+        Clock::get()?.slot,
+        ctx.accounts.owner.key(),
+        AdminAction::MigrateProtocol,
+        &[&protocol_state.version],
+    );
+
+    msg!("Protocol migrated!");
+    msg!("Protocol State: {}", info.key());
+    msg!("Protocol Owner: {}", protocol_state.owner);
+    let [major, minor, patch] = protocol_state.version;
+    msg!("Program Version: {}.{}.{}", major, minor, patch);
+    msg!("Admin Log: {}", ctx.accounts.admin_log.key());
+    msg!("Vault Registry: {}", ctx.accounts.vault_registry.key());
+
+    Ok(())
+}
+
+#[error_code]
+pub enum MigrateProtocolError {
+    #[msg("Only the protocol owner can migrate the protocol state")]
+    Unauthorized,
+    #[msg("The account is not a protocol state")]
+    NotProtocolState,
+    #[msg("The protocol state is already in the current layout")]
+    AlreadyMigrated,
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::AccountSerialize;
+
+    use super::*;
+    use crate::state::fixtures;
+
+    fn legacy(owner: Pubkey) -> Vec<u8> {
+        let mut data = ProtocolState::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(owner.as_ref());
+        data.push(254);
+        data
+    }
+
+    #[test]
+    fn migrates_the_legacy_layout() {
+        let owner = Pubkey::new_unique();
+
+        let protocol_state = process_migrate_protocol(&legacy(owner), owner).unwrap();
+        assert_eq!(protocol_state.owner, owner);
+        assert_eq!(protocol_state.bump, 254);
+        assert_eq!(protocol_state.upgrade_authority, None);
+        assert_eq!(protocol_state.version, PROGRAM_VERSION);
+        assert_eq!(protocol_state.features, ProtocolState::DEFAULT_FEATURES);
+
+        // The account the handler writes reads back as the current layout
+        let mut data = Vec::new();
+        protocol_state.try_serialize(&mut data).unwrap();
+        assert!(data.len() <= ProtocolState::LEN);
+        let read = ProtocolState::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(read.owner, owner);
+    }
+
+    #[test]
+    fn only_the_owner_migrates() {
+        let result = process_migrate_protocol(&legacy(Pubkey::new_unique()), Pubkey::new_unique());
+        assert!(matches!(result, Err(MigrateProtocolError::Unauthorized)));
+    }
+
+    #[test]
+    fn migrated_and_foreign_accounts_are_rejected() {
+        let protocol_state = fixtures::protocol_state();
+        let mut data = Vec::new();
+        protocol_state.try_serialize(&mut data).unwrap();
+        let result = process_migrate_protocol(&data, protocol_state.owner);
+        assert!(matches!(result, Err(MigrateProtocolError::AlreadyMigrated)));
+
+        let mut data = legacy(protocol_state.owner);
+        data[0] ^= 1;
+        let result = process_migrate_protocol(&data, protocol_state.owner);
+        assert!(matches!(result, Err(MigrateProtocolError::NotProtocolState)));
+    }
+}
@@ -0,0 +1,193 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::instructions::migrate_protocol::grow_account;
+use crate::state::{ActivityStats, AdminAction, AdminLog, ProtocolState, Vault, VaultRegistry};
+
+#[derive(Accounts)]
+pub struct MigrateVault<'info> {
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+        has_one = owner @ MigrateVaultError::Unauthorized,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// CHECK: a vault in its legacy layout, checked and grown by the
+    /// handler; `Account` could not deserialize it
+    #[account(mut, owner = crate::ID)]
+    pub vault: UncheckedAccount<'info>,
+
+    /// The protocol owner
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The protocol's list of vaults, grown by one entry for this vault
+    #[account(
+        mut,
+        seeds = [b"vault_registry"],
+        bump = vault_registry.load()?.bump,
+        realloc = vault_registry.to_account_info().data_len() + VaultRegistry::ENTRY_LEN,
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub vault_registry: AccountLoader<'info, VaultRegistry>,
+
+    /// The protocol's admin log, which records this call
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.load()?.bump,
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+
+    /// The statistics `deposit` and `redeem` require, which legacy vaults
+    /// lack
+    #[account(
+        init,
+        payer = payer,
+        space = ActivityStats::LEN,
+        seeds = [b"activity_stats", vault.key().as_ref()],
+        bump
+    )]
+    pub activity_stats: AccountLoader<'info, ActivityStats>,
+}
+
+/// Reads a vault in the layout `initialize_vault` wrote before the creator,
+/// name and sunset fields existed, and returns it in the current one
+///
+/// Who paid for a legacy vault was never recorded, so `creator`, who may
+/// rename it, becomes the protocol owner running the migration. The name
+/// starts empty and the vault open to deposits.
+pub fn process_migrate_vault(
+    data: &[u8],
+    creator: Pubkey,
+) -> std::result::Result<Vault, MigrateVaultError> {
+    if !data.starts_with(Vault::DISCRIMINATOR) {
+        return Err(MigrateVaultError::NotAVault);
+    }
+    if data.len() != Vault::LEGACY_LEN {
+        return Err(MigrateVaultError::AlreadyMigrated);
+    }
+
+    let key = |offset: usize| Pubkey::try_from(&data[offset..offset + 32]).unwrap();
+    Ok(Vault {
+        share_mint: key(8),
+        underlying_mint: key(40),
+        vault_token_account: key(72),
+        creator,
+        name: [0; 32],
+        sunset: false,
+        bump: data[104],
+    })
+}
+
+pub fn handler(ctx: Context<MigrateVault>) -> Result<()> {
+    let info = ctx.accounts.vault.to_account_info();
+    let vault = process_migrate_vault(&info.try_borrow_data()?, ctx.accounts.owner.key())?;
+
+    grow_account(
+        &info,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        Vault::LEN,
+    )?;
+    vault.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    let vault_registry = ctx.accounts.vault_registry.to_account_info();
+    let mut registry_data = vault_registry.try_borrow_mut_data()?;
+    VaultRegistry::push(&mut registry_data, info.key);
+    let registered = VaultRegistry::vaults(&registry_data).len();
+    drop(registry_data);
+
+    ctx.accounts.activity_stats.load_init()?.vault = info.key();
+
+    ctx.accounts.admin_log.load_mut()?.append(
+        Clock::get()?.slot,
+        ctx.accounts.owner.key(),
+        AdminAction::MigrateVault,
+        &[info.key.as_ref()],
+    );
+
+    msg!("Vault migrated!");
+    msg!("Vault: {}", info.key());
+    msg!("Underlying Mint: {}", vault.underlying_mint);
+    msg!("Creator: {}", vault.creator);
+    msg!("Registered vaults: {}", registered);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum MigrateVaultError {
+    #[msg("Only the protocol owner can migrate vaults")]
+    Unauthorized,
+    #[msg("The account is not a vault")]
+    NotAVault,
+    #[msg("The vault is already in the current layout")]
+    AlreadyMigrated,
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::AccountSerialize;
+
+    use super::*;
+    use crate::instructions::process_sunset_vault;
+    use crate::state::fixtures;
+
+    /// `vault` as the legacy `initialize_vault` wrote it
+    fn legacy(vault: &Vault) -> Vec<u8> {
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(vault.share_mint.as_ref());
+        data.extend_from_slice(vault.underlying_mint.as_ref());
+        data.extend_from_slice(vault.vault_token_account.as_ref());
+        data.push(vault.bump);
+        data
+    }
+
+    #[test]
+    fn migrates_the_legacy_layout() {
+        let vault = fixtures::vault();
+        let owner = Pubkey::new_unique();
+
+        let migrated = process_migrate_vault(&legacy(&vault), owner).unwrap();
+        assert_eq!(migrated.share_mint, vault.share_mint);
+        assert_eq!(migrated.underlying_mint, vault.underlying_mint);
+        assert_eq!(migrated.vault_token_account, vault.vault_token_account);
+        assert_eq!(migrated.bump, vault.bump);
+        assert_eq!(migrated.creator, owner);
+        assert_eq!(migrated.name(), "");
+        assert!(!migrated.sunset);
+
+        let mut data = Vec::new();
+        migrated.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), Vault::LEN);
+    }
+
+    #[test]
+    fn migrated_vaults_can_be_sunset() {
+        let mut vault =
+            process_migrate_vault(&legacy(&fixtures::vault()), Pubkey::new_unique()).unwrap();
+        process_sunset_vault(&mut vault).unwrap();
+        assert!(vault.sunset);
+    }
+
+    #[test]
+    fn migrated_and_foreign_accounts_are_rejected() {
+        let vault = fixtures::vault();
+        let mut data = Vec::new();
+        vault.try_serialize(&mut data).unwrap();
+        let result = process_migrate_vault(&data, vault.creator);
+        assert!(matches!(result, Err(MigrateVaultError::AlreadyMigrated)));
+
+        let mut data = legacy(&vault);
+        data[..8].copy_from_slice(ProtocolState::DISCRIMINATOR);
+        let result = process_migrate_vault(&data, vault.creator);
+        assert!(matches!(result, Err(MigrateVaultError::NotAVault)));
+    }
+}
@@ -1,11 +1,82 @@
+pub mod add_fee_exemption;
+pub mod capture_flag;
+pub mod close_share_account;
 pub mod deposit;
+pub mod deposit_delegated;
 pub mod initialize;
+pub mod initialize_flag;
+pub mod initialize_price_history;
+pub mod initialize_reserve;
 pub mod initialize_vault;
+pub mod migrate_protocol;
+pub mod migrate_vault;
+pub mod record_price;
 pub mod redeem;
+pub mod register_player;
+pub mod remove_fee_exemption;
+pub mod report;
+pub mod set_features;
+pub mod set_reserve_factor;
+pub mod set_vault_name;
+pub mod set_withdrawal_fee;
+pub mod set_withdrawal_limit;
+pub mod skim;
+pub mod sunset_vault;
+pub mod sync_upgrade_authority;
 pub mod transfer_ownership;
+pub mod verify_upgrade_authority;
 
+pub use add_fee_exemption::*;
+pub use capture_flag::*;
+pub use close_share_account::*;
 pub use deposit::*;
+pub use deposit_delegated::*;
 pub use initialize::*;
+pub use initialize_flag::*;
+pub use initialize_price_history::*;
+pub use initialize_reserve::*;
 pub use initialize_vault::*;
+pub use migrate_protocol::*;
+pub use migrate_vault::*;
+pub use record_price::*;
 pub use redeem::*;
+pub use register_player::*;
+pub use remove_fee_exemption::*;
+pub use report::*;
+pub use set_features::*;
+pub use set_reserve_factor::*;
+pub use set_vault_name::*;
+pub use set_withdrawal_fee::*;
+pub use set_withdrawal_limit::*;
+pub use skim::*;
+pub use sunset_vault::*;
+pub use sync_upgrade_authority::*;
 pub use transfer_ownership::*;
+pub use verify_upgrade_authority::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SBF programs get a 4KB stack frame and `try_accounts` builds the whole
+    /// accounts struct on it, so large accounts must stay boxed.
+    const MAX_ACCOUNTS_STACK_SIZE: usize = 512;
+
+    #[test]
+    fn deposit_accounts_fit_stack_budget() {
+        let size = std::mem::size_of::<Deposit<'static>>();
+        assert!(
+            size <= MAX_ACCOUNTS_STACK_SIZE,
+            "Deposit accounts use {size} bytes"
+        );
+    }
+
+    #[test]
+    fn redeem_accounts_fit_stack_budget() {
+        let size = std::mem::size_of::<Redeem<'static>>();
+        assert!(
+            size <= MAX_ACCOUNTS_STACK_SIZE,
+            "Redeem accounts use {size} bytes"
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::state::{PriceHistory, PricePoint, Vault};
+
+#[derive(Accounts)]
+pub struct RecordPrice<'info> {
+    #[account(mut, has_one = vault)]
+    pub price_history: AccountLoader<'info, PriceHistory>,
+
+    #[account(
+        has_one = share_mint,
+        has_one = vault_token_account,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// The vault's token account that holds underlying assets
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The share mint
+    pub share_mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Records the vault's totals at `slot`, returning the new observation
+///
+/// Anyone may record, so at most one observation is kept per slot: the
+/// buffer covers at least `CAPACITY` slots however often it is called.
+pub fn process_record_price(
+    price_history: &mut PriceHistory,
+    slot: u64,
+    total_assets: u64,
+    total_shares: u64,
+) -> std::result::Result<PricePoint, RecordPriceError> {
+    if price_history
+        .latest()
+        .is_some_and(|latest| latest.slot >= slot)
+    {
+        return Err(RecordPriceError::AlreadyRecorded);
+    }
+
+    let point = PricePoint {
+        slot,
+        total_assets,
+        total_shares,
+    };
+    price_history.push(point);
+    Ok(point)
+}
+
+pub fn handler(ctx: Context<RecordPrice>) -> Result<()> {
+    let mut price_history = ctx.accounts.price_history.load_mut()?;
+
+    let point = process_record_price(
+        &mut price_history,
+        Clock::get()?.slot,
+        ctx.accounts.vault_token_account.amount,
+        ctx.accounts.share_mint.supply,
+    )?;
+
+    msg!("Price recorded!");
+    msg!("Slot: {}", point.slot);
+    msg!("Total assets: {}", point.total_assets);
+    msg!("Total shares: {}", point.total_shares);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum RecordPriceError {
+    #[msg("A price was already recorded in this slot")]
+    AlreadyRecorded,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_once_per_slot() {
+        let mut price_history: Box<PriceHistory> = Box::new(bytemuck::Zeroable::zeroed());
+
+        process_record_price(&mut price_history, 10, 1_000, 1_000).unwrap();
+        let result = process_record_price(&mut price_history, 10, 2_000, 1_000);
+        assert!(matches!(result, Err(RecordPriceError::AlreadyRecorded)));
+        assert_eq!(price_history.len, 1);
+        assert_eq!(price_history.latest().unwrap().total_assets, 1_000);
+
+        process_record_price(&mut price_history, 11, 2_000, 1_000).unwrap();
+        assert_eq!(price_history.len, 2);
+    }
+}
@@ -3,7 +3,13 @@ use anchor_spl::token_interface::{
     burn, transfer_checked, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
 };
 
-use crate::state::{Vault, VaultAuthority};
+use vault_math::{compute_assets, withdrawal_fee, KernelError};
+
+use crate::events::RedeemEvent;
+use crate::instructions::report::{settle_reserve, ReportError, VaultShares};
+use crate::state::{
+    record_flow, vault_signer_seeds, ActivityStats, Flow, ProtocolState, Vault, VaultAuthority,
+};
 
 #[derive(Accounts)]
 pub struct Redeem<'info> {
@@ -16,15 +22,15 @@ pub struct Redeem<'info> {
     pub vault: Account<'info, Vault>,
 
     /// The underlying asset mint
-    pub underlying_mint: InterfaceAccount<'info, Mint>,
+    pub underlying_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// The vault's token account that holds underlying assets
     #[account(mut)]
-    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// The share mint
     #[account(mut)]
-    pub share_mint: InterfaceAccount<'info, Mint>,
+    pub share_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// The vault authority that can transfer from vault
     #[account(
@@ -39,7 +45,7 @@ pub struct Redeem<'info> {
         token::mint = underlying_mint,
         token::authority = redeemer,
     )]
-    pub redeemer_underlying_account: InterfaceAccount<'info, TokenAccount>,
+    pub redeemer_underlying_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// The redeemer's token account for burning shares
     #[account(
@@ -47,34 +53,183 @@ pub struct Redeem<'info> {
         token::mint = share_mint,
         token::authority = redeemer,
     )]
-    pub redeemer_share_account: InterfaceAccount<'info, TokenAccount>,
+    pub redeemer_share_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     pub redeemer: Signer<'info>,
 
+    /// CHECK: `vault`'s flag, which counts the redeem once planted
+    #[account(
+        mut,
+        seeds = [b"flag", vault.key().as_ref()],
+        bump
+    )]
+    pub flag: UncheckedAccount<'info>,
+
+    /// CHECK: the redeemer's entry for `flag`, which counts the redeem once
+    /// they registered
+    #[account(
+        mut,
+        seeds = [b"player", flag.key().as_ref(), redeemer.key().as_ref()],
+        bump
+    )]
+    pub player_record: UncheckedAccount<'info>,
+
+    /// `vault`'s deposit and withdrawal volumes, and the limit on the latter
+    #[account(
+        mut,
+        seeds = [b"activity_stats", vault.key().as_ref()],
+        bump
+    )]
+    pub activity_stats: AccountLoader<'info, ActivityStats>,
+
+    /// CHECK: `vault`'s reserve config, settled before the redeem once
+    /// initialized
+    #[account(
+        mut,
+        seeds = [b"reserve_config", vault.key().as_ref()],
+        bump
+    )]
+    pub reserve_config: UncheckedAccount<'info>,
+
+    /// Share account the treasury's cut is minted to and the withdrawal fee
+    /// paid to, required once the vault has a reserve config
+    #[account(mut)]
+    pub treasury: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Whose feature bits decide whether the treasury is paid, required with
+    /// `treasury`
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+    )]
+    pub protocol_state: Option<Account<'info, ProtocolState>>,
+
+    /// CHECK: the redeemer's fee exemption, which waives the withdrawal fee
+    /// once the protocol owner created it
+    #[account(
+        seeds = [b"fee_exemption", redeemer.key().as_ref()],
+        bump
+    )]
+    pub fee_exemption: UncheckedAccount<'info>,
+
     pub token_program: Interface<'info, TokenInterface>,
 }
 
+/// Underlying a redeem pays out and the vault's totals once it lands
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RedeemOutcome {
+    /// Shares handed to the treasury as the withdrawal fee
+    pub fee: u64,
+    /// Shares burned for `amount`
+    pub burned: u64,
+    pub amount: u64,
+    pub total_assets: u64,
+    pub total_shares: u64,
+}
+
+/// Validates a redeem of `shares` through `share_mint` and prices it against
+/// the vault's totals before the redeem
+///
+/// `withdrawal_fee_bps` of the shares, rounded up, go to the treasury and the
+/// rest are burned for (burned * total_assets) / total_shares, rounded down
+/// so the redeemer never receives more than their shares are worth. The
+/// totals saturate: a redeem of more shares than exist fails in the token
+/// program first.
+#[cfg_attr(feature = "vuln-share-mint", allow(unused_variables))]
+pub fn process_redeem(
+    vault: &Vault,
+    share_mint: &Pubkey,
+    shares: u64,
+    withdrawal_fee_bps: u16,
+    total_shares: u64,
+    total_assets: u64,
+) -> std::result::Result<RedeemOutcome, RedeemError> {
+    if shares == 0 {
+        return Err(RedeemError::InvalidAmount);
+    }
+
+    // Validate that the share_mint matches the vault's share_mint. The
+    // challenge build skips this and burns shares of any vault
+    #[cfg(not(feature = "vuln-share-mint"))]
+    if *share_mint != vault.share_mint {
+        return Err(RedeemError::InvalidShareMint);
+    }
+
+    let fee = withdrawal_fee(shares, withdrawal_fee_bps);
+    let burned = shares - fee;
+    if burned == 0 {
+        return Err(RedeemError::InsufficientUnderlying);
+    }
+    let amount = compute_assets(burned, total_shares, total_assets)?;
+
+    Ok(RedeemOutcome {
+        fee,
+        burned,
+        amount,
+        total_assets: total_assets.saturating_sub(amount),
+        total_shares: total_shares.saturating_sub(burned),
+    })
+}
+
 pub fn handler(ctx: Context<Redeem>, shares: u64) -> Result<()> {
-    require!(shares > 0, RedeemError::InvalidAmount);
+    let settlement = settle_reserve(
+        &ctx.accounts.reserve_config,
+        ctx.accounts.treasury.as_deref(),
+        ctx.accounts.protocol_state.as_ref(),
+        VaultShares {
+            vault: &ctx.accounts.vault,
+            vault_token_account: &ctx.accounts.vault_token_account,
+            share_mint: &ctx.accounts.share_mint,
+            vault_authority: &ctx.accounts.vault_authority,
+            token_program: &ctx.accounts.token_program,
+        },
+    )?;
 
-    let share_mint = &ctx.accounts.share_mint;
-    let vault_token_account = &ctx.accounts.vault_token_account;
+    // Exemptions only exist once the protocol owner created them
+    let withdrawal_fee_bps = if ctx.accounts.fee_exemption.owner == &crate::ID {
+        0
+    } else {
+        settlement.withdrawal_fee_bps
+    };
+
+    // Copy the vault state out of the boxed accounts so nothing large stays
+    // borrowed across the CPIs below
+    let outcome = process_redeem(
+        &ctx.accounts.vault,
+        &ctx.accounts.share_mint.key(),
+        shares,
+        withdrawal_fee_bps,
+        settlement.total_shares,
+        settlement.total_assets,
+    )?;
 
-    // Prevent division by zero
-    require!(share_mint.supply > 0, RedeemError::NoShares);
-    require!(vault_token_account.amount > 0, RedeemError::EmptyVault);
+    let slot = Clock::get()?.slot;
+    let withdrawable = ctx.accounts.activity_stats.load()?.withdrawable(slot);
+    if withdrawable.is_some_and(|withdrawable| outcome.amount > withdrawable) {
+        return Err(RedeemError::WithdrawalLimitExceeded.into());
+    }
 
-    // Calculate underlying tokens to return: (shares * total_assets) / total_shares
-    // Use u128 to prevent overflow during multiplication
-    let underlying_to_return = (shares as u128)
-        .checked_mul(vault_token_account.amount as u128)
-        .ok_or(RedeemError::MathOverflow)?
-        .checked_div(share_mint.supply as u128)
-        .ok_or(RedeemError::MathOverflow)?;
+    if outcome.fee > 0 {
+        // Hand the fee to the treasury before burning the rest
+        let treasury = ctx
+            .accounts
+            .treasury
+            .as_ref()
+            .ok_or(ReportError::MissingTreasury)?;
+        let transfer_accounts = TransferChecked {
+            from: ctx.accounts.redeemer_share_account.to_account_info(),
+            mint: ctx.accounts.share_mint.to_account_info(),
+            to: treasury.to_account_info(),
+            authority: ctx.accounts.redeemer.to_account_info(),
+        };
 
-    let underlying_to_return = underlying_to_return as u64;
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_accounts,
+        );
 
-    require!(underlying_to_return > 0, RedeemError::InsufficientUnderlying);
+        transfer_checked(cpi_ctx, outcome.fee, ctx.accounts.share_mint.decimals)?;
+    }
 
     // Burn shares from redeemer
     let burn_accounts = Burn {
@@ -88,18 +243,19 @@ pub fn handler(ctx: Context<Redeem>, shares: u64) -> Result<()> {
         burn_accounts,
     );
 
-    burn(cpi_ctx, shares)?;
+    burn(cpi_ctx, outcome.burned)?;
 
-    // Transfer underlying tokens from vault to redeemer
-    let vault_authority_bump = ctx.accounts.vault_authority.bump;
-    let vault_authority_seeds = &[b"vault_authority".as_ref(), &[vault_authority_bump]];
-    let signer_seeds = &[&vault_authority_seeds[..]];
+    // Transfer underlying tokens from vault to redeemer, signed by the vault's
+    // token authority
+    let (authority, authority_seeds) =
+        vault_signer_seeds(&ctx.accounts.vault, &ctx.accounts.vault_authority);
+    let signer_seeds = &[authority_seeds.as_slice()];
 
     let transfer_accounts = TransferChecked {
         from: ctx.accounts.vault_token_account.to_account_info(),
         mint: ctx.accounts.underlying_mint.to_account_info(),
         to: ctx.accounts.redeemer_underlying_account.to_account_info(),
-        authority: ctx.accounts.vault_authority.to_account_info(),
+        authority,
     };
 
     let cpi_ctx = CpiContext::new_with_signer(
@@ -108,13 +264,45 @@ pub fn handler(ctx: Context<Redeem>, shares: u64) -> Result<()> {
         signer_seeds,
     );
 
-    transfer_checked(cpi_ctx, underlying_to_return, ctx.accounts.underlying_mint.decimals)?;
+    transfer_checked(
+        cpi_ctx,
+        outcome.amount,
+        ctx.accounts.underlying_mint.decimals,
+    )?;
+
+    record_flow(
+        &ctx.accounts.flag,
+        &ctx.accounts.player_record,
+        Flow::Redeem {
+            shares,
+            amount: outcome.amount,
+            vault_shares: ctx.accounts.share_mint.key() == ctx.accounts.vault.share_mint,
+        },
+    )?;
+    ctx.accounts
+        .activity_stats
+        .load_mut()?
+        .record(slot, 0, outcome.amount);
+
+    let event = RedeemEvent {
+        vault: ctx.accounts.vault.key(),
+        redeemer: ctx.accounts.redeemer.key(),
+        shares: outcome.burned,
+        amount: outcome.amount,
+        total_assets: outcome.total_assets,
+        total_shares: outcome.total_shares,
+    };
+
+    #[cfg(feature = "verbose-logs")]
+    {
+        msg!("Redeem successful!");
+        msg!("Shares burned: {}", event.shares);
+        msg!("Underlying returned: {}", event.amount);
+        msg!("Remaining vault assets: {}", event.total_assets);
+        msg!("Remaining shares supply: {}", event.total_shares);
+    }
 
-    msg!("Redeem successful!");
-    msg!("Shares burned: {}", shares);
-    msg!("Underlying returned: {}", underlying_to_return);
-    msg!("Remaining vault assets: {}", vault_token_account.amount - underlying_to_return);
-    msg!("Remaining shares supply: {}", share_mint.supply - shares);
+    emit!(event);
 
     Ok(())
 }
@@ -131,4 +319,119 @@ pub enum RedeemError {
     MathOverflow,
     #[msg("Insufficient underlying tokens would be returned")]
     InsufficientUnderlying,
+    #[msg("Share mint does not match vault's share mint")]
+    InvalidShareMint,
+    #[msg("The vault's withdrawal limit for this window is reached")]
+    WithdrawalLimitExceeded,
+}
+
+impl From<KernelError> for RedeemError {
+    fn from(error: KernelError) -> Self {
+        match error {
+            KernelError::InvalidAmount => RedeemError::InvalidAmount,
+            KernelError::NoShares => RedeemError::NoShares,
+            KernelError::EmptyVault => RedeemError::EmptyVault,
+            KernelError::MathOverflow => RedeemError::MathOverflow,
+            KernelError::ZeroOutput => RedeemError::InsufficientUnderlying,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::fixtures::vault;
+
+    #[test]
+    fn prices_against_the_totals_before_the_redeem() {
+        let vault = vault();
+        let amount = compute_assets(4_001, 15_600, 19_500).unwrap();
+        let outcome = process_redeem(&vault, &vault.share_mint, 4_001, 0, 15_600, 19_500).unwrap();
+        assert_eq!(
+            outcome,
+            RedeemOutcome {
+                fee: 0,
+                burned: 4_001,
+                amount,
+                total_assets: 19_500 - amount,
+                total_shares: 11_599,
+            }
+        );
+    }
+
+    #[test]
+    fn the_withdrawal_fee_is_kept_from_the_burn() {
+        let vault = vault();
+        // 0.3% of 4_001 shares, rounded up
+        let amount = compute_assets(3_988, 15_600, 19_500).unwrap();
+        let outcome = process_redeem(&vault, &vault.share_mint, 4_001, 30, 15_600, 19_500).unwrap();
+        assert_eq!(
+            outcome,
+            RedeemOutcome {
+                fee: 13,
+                burned: 3_988,
+                amount,
+                total_assets: 19_500 - amount,
+                total_shares: 11_612,
+            }
+        );
+
+        // A redeem the fee would swallow whole pays nothing out
+        let result = process_redeem(&vault, &vault.share_mint, 1, 30, 15_600, 19_500);
+        assert!(matches!(result, Err(RedeemError::InsufficientUnderlying)));
+    }
+
+    #[test]
+    fn sunset_vault_still_redeems() {
+        let vault = Vault {
+            sunset: true,
+            ..vault()
+        };
+        assert!(process_redeem(&vault, &vault.share_mint, 4_001, 0, 15_600, 19_500).is_ok());
+    }
+
+    #[test]
+    fn zero_shares_are_rejected() {
+        let vault = vault();
+        let result = process_redeem(&vault, &vault.share_mint, 0, 0, 1_000, 1_000);
+        assert!(matches!(result, Err(RedeemError::InvalidAmount)));
+    }
+
+    #[test]
+    fn share_mint_must_be_the_vaults() {
+        let vault = vault();
+        let result = process_redeem(&vault, &Pubkey::new_unique(), 1_000, 0, 1_000, 1_000);
+
+        // The challenge build pays out for any vault's shares
+        if crate::VULN_SHARE_MINT {
+            assert_eq!(result.unwrap().amount, 1_000);
+        } else {
+            assert!(matches!(result, Err(RedeemError::InvalidShareMint)));
+        }
+    }
+
+    #[test]
+    fn kernel_errors_map_to_redeem_errors() {
+        let vault = vault();
+        let redeem = |shares, total_shares, total_assets| {
+            process_redeem(
+                &vault,
+                &vault.share_mint,
+                shares,
+                0,
+                total_shares,
+                total_assets,
+            )
+        };
+        assert!(matches!(redeem(1, 0, 1_000), Err(RedeemError::NoShares)));
+        assert!(matches!(redeem(1, 1_000, 0), Err(RedeemError::EmptyVault)));
+        assert!(matches!(
+            redeem(1, 2, 1),
+            Err(RedeemError::InsufficientUnderlying)
+        ));
+        assert!(matches!(
+            redeem(1_000, 1, u64::MAX),
+            Err(RedeemError::MathOverflow)
+        ));
+    }
 }
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Flag, Player};
+
+#[derive(Accounts)]
+pub struct RegisterPlayer<'info> {
+    pub flag: Account<'info, Flag>,
+
+    /// The player's entry, one per player and flag. `deposit` and `redeem`
+    /// count the player's flows through the flag's vault into it
+    #[account(
+        init,
+        payer = player,
+        space = Player::LEN,
+        seeds = [b"player", flag.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub player_record: Account<'info, Player>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Checks `flag` is still open and fills in `record` for it; its flows start
+/// at zero
+pub fn process_register_player(
+    flag: &Flag,
+    record: &mut Player,
+    flag_key: Pubkey,
+    player: Pubkey,
+) -> std::result::Result<(), RegisterPlayerError> {
+    if flag.is_captured() {
+        return Err(RegisterPlayerError::AlreadyCaptured);
+    }
+
+    record.flag = flag_key;
+    record.player = player;
+
+    Ok(())
+}
+
+pub fn handler(ctx: Context<RegisterPlayer>) -> Result<()> {
+    let flag_key = ctx.accounts.flag.key();
+    let player = ctx.accounts.player.key();
+
+    let player_record = &mut ctx.accounts.player_record;
+    process_register_player(&ctx.accounts.flag, player_record, flag_key, player)?;
+    player_record.bump = ctx.bumps.player_record;
+
+    msg!("Player registered!");
+    msg!("Player: {}", player_record.player);
+    msg!("Flag: {}", player_record.flag);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum RegisterPlayerError {
+    #[msg("The flag has already been captured")]
+    AlreadyCaptured,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::fixtures::{self, player};
+    use crate::state::WinCondition;
+
+    fn flag(condition: Option<WinCondition>) -> Flag {
+        Flag {
+            condition,
+            ..fixtures::flag()
+        }
+    }
+
+    #[test]
+    fn records_the_player() {
+        let flag = flag(None);
+        let mut record = player();
+        let (flag_key, player) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        process_register_player(&flag, &mut record, flag_key, player).unwrap();
+        assert_eq!(record.flag, flag_key);
+        assert_eq!(record.player, player);
+    }
+
+    #[test]
+    fn captured_flag_is_closed() {
+        let flag = flag(Some(WinCondition::Profit));
+        let result = process_register_player(
+            &flag,
+            &mut player(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        assert!(matches!(result, Err(RegisterPlayerError::AlreadyCaptured)));
+    }
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{AdminAction, AdminLog, FeeExemption, ProtocolState};
+
+#[derive(Accounts)]
+pub struct RemoveFeeExemption<'info> {
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+        has_one = owner @ RemoveFeeExemptionError::Unauthorized,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// The exemption to close, refunding its rent to the owner
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"fee_exemption", fee_exemption.address.as_ref()],
+        bump = fee_exemption.bump,
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+
+    /// The protocol owner
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The protocol's admin log, which records this call
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.load()?.bump,
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+}
+
+/// Makes the exempt address pay withdrawal fees again from its next redeem
+pub fn handler(ctx: Context<RemoveFeeExemption>) -> Result<()> {
+    let address = ctx.accounts.fee_exemption.address;
+
+    ctx.accounts.admin_log.load_mut()?.append(
+        Clock::get()?.slot,
+        ctx.accounts.owner.key(),
+        AdminAction::RemoveFeeExemption,
+        &[address.as_ref()],
+    );
+
+    msg!("Fee exemption removed!");
+    msg!("Address: {}", address);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum RemoveFeeExemptionError {
+    #[msg("Only the protocol owner can remove fee exemptions")]
+    Unauthorized,
+}
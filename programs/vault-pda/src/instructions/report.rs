@@ -0,0 +1,324 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface};
+
+use vault_math::{compute_reserve, KernelError, ReserveSplit};
+
+use crate::events::ReportEvent;
+use crate::state::{vault_signer_seeds, ProtocolState, ReserveConfig, Vault, VaultAuthority};
+
+#[derive(Accounts)]
+pub struct Report<'info> {
+    #[account(
+        mut,
+        seeds = [b"reserve_config", vault.key().as_ref()],
+        bump = reserve_config.bump,
+        has_one = vault,
+        has_one = treasury,
+    )]
+    pub reserve_config: Account<'info, ReserveConfig>,
+
+    #[account(
+        seeds = [b"vault", vault.underlying_mint.as_ref()],
+        bump = vault.bump,
+        has_one = share_mint,
+        has_one = vault_token_account,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// The vault's token account that holds underlying assets
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The share mint
+    #[account(mut)]
+    pub share_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The vault authority that can mint shares
+    #[account(
+        seeds = [b"vault_authority"],
+        bump = vault_authority.bump
+    )]
+    pub vault_authority: Account<'info, VaultAuthority>,
+
+    /// Share token account the treasury's cut is minted to
+    #[account(mut)]
+    pub treasury: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Whose feature bits decide whether the treasury is paid
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Splits the profit the vault made since `config`'s high-water mark and
+/// moves the mark to the vault's totals once the treasury is paid
+///
+/// With fees disabled the treasury's cut is zero but the mark still moves,
+/// so re-enabling them never pays out profit made in the meantime.
+pub fn process_report(
+    config: &mut ReserveConfig,
+    total_assets: u64,
+    total_shares: u64,
+    fees_enabled: bool,
+) -> std::result::Result<ReserveSplit, ReportError> {
+    let reserve_factor_bps = if fees_enabled {
+        config.reserve_factor_bps
+    } else {
+        0
+    };
+    let split = compute_reserve(
+        total_assets,
+        total_shares,
+        config.mark_assets,
+        config.mark_shares,
+        reserve_factor_bps,
+    )?;
+
+    config.mark_assets = split.mark_assets;
+    config.mark_shares = split.mark_shares;
+
+    Ok(split)
+}
+
+/// Anyone can report: the split only depends on the vault's balances, so the
+/// caller cannot move it
+pub fn handler(ctx: Context<Report>) -> Result<()> {
+    let event = settle(ctx.accounts)?;
+
+    #[cfg(feature = "verbose-logs")]
+    {
+        msg!("Report successful!");
+        msg!("Profit since the mark: {}", event.profit);
+        msg!("Treasury's cut: {}", event.reserve);
+        msg!("Minted to the treasury: {} shares", event.shares);
+    }
+
+    emit!(event);
+
+    Ok(())
+}
+
+/// Pays the treasury its cut of the profit since the mark, at the factor
+/// `accounts.reserve_config` holds now
+///
+/// `set_reserve_factor` settles through this before changing the factor.
+pub fn settle(accounts: &mut Report) -> Result<ReportEvent> {
+    let fees_enabled = accounts
+        .protocol_state
+        .has_feature(ProtocolState::FEES_ENABLED);
+    let vault = VaultShares {
+        vault: &accounts.vault,
+        vault_token_account: &accounts.vault_token_account,
+        share_mint: &accounts.share_mint,
+        vault_authority: &accounts.vault_authority,
+        token_program: &accounts.token_program,
+    };
+    pay_reserve(
+        &mut accounts.reserve_config,
+        &vault,
+        &accounts.treasury,
+        fees_enabled,
+    )
+}
+
+/// The vault a settlement prices, and what minting its shares takes
+pub struct VaultShares<'a, 'info> {
+    pub vault: &'a Account<'info, Vault>,
+    pub vault_token_account: &'a InterfaceAccount<'info, TokenAccount>,
+    pub share_mint: &'a InterfaceAccount<'info, Mint>,
+    pub vault_authority: &'a Account<'info, VaultAuthority>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+}
+
+/// A vault's totals once its reserve is settled, which a deposit or redeem
+/// is priced against
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Settlement {
+    pub total_assets: u64,
+    pub total_shares: u64,
+    /// The withdrawal fee a redeem pays the treasury, zero without a reserve
+    /// config or with fees disabled
+    pub withdrawal_fee_bps: u16,
+}
+
+/// Settles `vault`'s reserve ahead of a deposit or redeem, emitting the
+/// `ReportEvent`
+///
+/// Profit is whatever the vault holds above its supply at the mark price, so
+/// a deposit at a price above the mark would count as profit and pay the
+/// treasury out of the depositor's principal, and a redeem would take its
+/// share of the profit out before the treasury's cut. Settling first moves
+/// the mark to the current price, and the flow then trades at it. Vaults
+/// without a reserve config, and redeems of another vault's shares, which
+/// the challenge build lets through, settle nothing.
+pub fn settle_reserve<'info>(
+    reserve_config: &AccountInfo<'info>,
+    treasury: Option<&InterfaceAccount<'info, TokenAccount>>,
+    protocol_state: Option<&Account<'info, ProtocolState>>,
+    vault: VaultShares<'_, 'info>,
+) -> Result<Settlement> {
+    if reserve_config.owner != &crate::ID || vault.share_mint.key() != vault.vault.share_mint {
+        return Ok(Settlement {
+            total_assets: vault.vault_token_account.amount,
+            total_shares: vault.share_mint.supply,
+            withdrawal_fee_bps: 0,
+        });
+    }
+    let (Some(treasury), Some(protocol_state)) = (treasury, protocol_state) else {
+        return err!(ReportError::MissingTreasury);
+    };
+
+    let mut config = ReserveConfig::try_deserialize(&mut &reserve_config.try_borrow_data()?[..])?;
+    require_keys_eq!(treasury.key(), config.treasury, ReportError::WrongTreasury);
+    let fees_enabled = protocol_state.has_feature(ProtocolState::FEES_ENABLED);
+    let event = pay_reserve(&mut config, &vault, treasury, fees_enabled)?;
+    config.try_serialize(&mut &mut reserve_config.try_borrow_mut_data()?[..])?;
+
+    let settlement = Settlement {
+        total_assets: event.total_assets,
+        total_shares: event.total_shares,
+        withdrawal_fee_bps: if fees_enabled {
+            config.withdrawal_fee_bps
+        } else {
+            0
+        },
+    };
+    emit!(event);
+    Ok(settlement)
+}
+
+fn pay_reserve<'info>(
+    config: &mut ReserveConfig,
+    vault: &VaultShares<'_, 'info>,
+    treasury: &InterfaceAccount<'info, TokenAccount>,
+    fees_enabled: bool,
+) -> Result<ReportEvent> {
+    let total_assets = vault.vault_token_account.amount;
+    let total_shares = vault.share_mint.supply;
+    let split = process_report(config, total_assets, total_shares, fees_enabled)?;
+
+    if split.shares > 0 {
+        // Mint the treasury's shares, signed by the vault's token authority
+        let (authority, authority_seeds) = vault_signer_seeds(vault.vault, vault.vault_authority);
+        let signer_seeds = &[authority_seeds.as_slice()];
+
+        let mint_accounts = MintTo {
+            mint: vault.share_mint.to_account_info(),
+            to: treasury.to_account_info(),
+            authority,
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            vault.token_program.to_account_info(),
+            mint_accounts,
+            signer_seeds,
+        );
+
+        mint_to(cpi_ctx, split.shares)?;
+    }
+
+    Ok(ReportEvent {
+        vault: vault.vault.key(),
+        treasury: treasury.key(),
+        profit: split.profit,
+        reserve: split.reserve,
+        shares: split.shares,
+        total_assets,
+        total_shares: total_shares + split.shares,
+    })
+}
+
+#[error_code]
+pub enum ReportError {
+    #[msg("Math operation overflow")]
+    MathOverflow,
+    #[msg("The vault has a reserve config; pass its treasury and the protocol state")]
+    MissingTreasury,
+    #[msg("Treasury does not match the vault's reserve config")]
+    WrongTreasury,
+}
+
+impl From<KernelError> for ReportError {
+    fn from(_: KernelError) -> Self {
+        // `compute_reserve` only fails on overflow
+        ReportError::MathOverflow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(mark_assets: u64, mark_shares: u64) -> ReserveConfig {
+        ReserveConfig {
+            vault: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            reserve_factor_bps: 1_000,
+            withdrawal_fee_bps: 0,
+            mark_assets,
+            mark_shares,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn yield_pays_the_treasury_and_moves_the_mark() {
+        let mut config = config(1_000, 1_000);
+        let split = process_report(&mut config, 2_000, 1_000, true).unwrap();
+        assert_eq!(split.profit, 1_000);
+        assert_eq!(split.reserve, 100);
+        assert!(split.shares > 0);
+        assert_eq!(
+            (config.mark_assets, config.mark_shares),
+            (2_000, 1_000 + split.shares)
+        );
+    }
+
+    #[test]
+    fn reporting_twice_pays_once() {
+        let mut config = config(1_000, 1_000);
+        let first = process_report(&mut config, 2_000, 1_000, true).unwrap();
+        let second = process_report(&mut config, 2_000, 1_000 + first.shares, true).unwrap();
+        assert_eq!(second.shares, 0);
+    }
+
+    #[test]
+    fn deposits_after_yield_are_settled_first() {
+        // Unsettled, a 2_000 deposit at the vault's price of 2 would count as
+        // another 1_000 profit and double the treasury's cut
+        let mut unsettled = config(1_000, 1_000);
+        let minted = vault_math::compute_shares(2_000, 1_000, 2_000).unwrap();
+        let split = process_report(&mut unsettled, 4_000, 1_000 + minted, true).unwrap();
+        assert_eq!(split.reserve, 200);
+
+        let mut config = config(1_000, 1_000);
+        let settled = process_report(&mut config, 2_000, 1_000, true).unwrap();
+        assert_eq!(settled.reserve, 100);
+        let total_shares = 1_000 + settled.shares;
+        let minted = vault_math::compute_shares(2_000, total_shares, 2_000).unwrap();
+        let split = process_report(&mut config, 4_000, total_shares + minted, true).unwrap();
+        assert_eq!(split.shares, 0);
+    }
+
+    #[test]
+    fn a_loss_keeps_the_mark() {
+        let mut config = config(1_000, 1_000);
+        let split = process_report(&mut config, 500, 1_000, true).unwrap();
+        assert_eq!(split.shares, 0);
+        assert_eq!((config.mark_assets, config.mark_shares), (1_000, 1_000));
+    }
+
+    #[test]
+    fn disabled_fees_move_the_mark_without_paying() {
+        let mut config = config(1_000, 1_000);
+        let split = process_report(&mut config, 2_000, 1_000, false).unwrap();
+        assert_eq!((split.profit, split.reserve, split.shares), (1_000, 0, 0));
+        assert_eq!((config.mark_assets, config.mark_shares), (2_000, 1_000));
+
+        let split = process_report(&mut config, 2_000, 1_000, true).unwrap();
+        assert_eq!(split.shares, 0);
+    }
+}
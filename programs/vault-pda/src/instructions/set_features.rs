@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::PROGRAM_VERSION;
+use crate::state::{AdminAction, AdminLog, ProtocolState};
+
+#[derive(Accounts)]
+pub struct SetFeatures<'info> {
+    #[account(
+        mut,
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+        has_one = owner @ SetFeaturesError::Unauthorized,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// The protocol owner
+    pub owner: Signer<'info>,
+
+    /// The protocol's admin log, which records this call
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.load()?.bump,
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+}
+
+/// Replaces the enabled feature bits and records the running program's
+/// version, so calling it after an upgrade also stamps the new version
+pub fn process_set_features(
+    state: &mut ProtocolState,
+    features: u8,
+) -> std::result::Result<(), SetFeaturesError> {
+    if features & !ProtocolState::ALL_FEATURES != 0 {
+        return Err(SetFeaturesError::UnknownFeature);
+    }
+
+    state.features = features;
+    state.version = PROGRAM_VERSION;
+
+    Ok(())
+}
+
+pub fn handler(ctx: Context<SetFeatures>, features: u8) -> Result<()> {
+    let state = &mut ctx.accounts.protocol_state;
+    let previous = state.features;
+    process_set_features(state, features)?;
+
+    ctx.accounts.admin_log.load_mut()?.append(
+        Clock::get()?.slot,
+        ctx.accounts.owner.key(),
+        AdminAction::SetFeatures,
+        &[&[state.features]],
+    );
+
+    let [major, minor, patch] = state.version;
+    msg!("Features updated!");
+    msg!("Previous features: {:#05b}", previous);
+    msg!("New features: {:#05b}", state.features);
+    msg!("Program Version: {}.{}.{}", major, minor, patch);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum SetFeaturesError {
+    #[msg("Only the protocol owner can set features")]
+    Unauthorized,
+    #[msg("Feature bitmap has bits this program does not know")]
+    UnknownFeature,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::fixtures::protocol_state;
+
+    fn state() -> ProtocolState {
+        ProtocolState {
+            version: [0, 0, 0],
+            ..protocol_state()
+        }
+    }
+
+    #[test]
+    fn replaces_features_and_stamps_the_version() {
+        let mut state = state();
+        process_set_features(&mut state, ProtocolState::STRATEGIES_ENABLED).unwrap();
+        assert_eq!(state.features, ProtocolState::STRATEGIES_ENABLED);
+        assert!(!state.has_feature(ProtocolState::FEES_ENABLED));
+        assert_eq!(state.version, PROGRAM_VERSION);
+        assert_eq!(
+            PROGRAM_VERSION.map(|part| part.to_string()).join("."),
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+
+    #[test]
+    fn unknown_bits_are_rejected() {
+        let mut state = state();
+        let result = process_set_features(&mut state, 1 << 3);
+        assert!(matches!(result, Err(SetFeaturesError::UnknownFeature)));
+        assert_eq!(state.features, ProtocolState::DEFAULT_FEATURES);
+        assert_eq!(state.version, [0, 0, 0]);
+    }
+}
@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+
+use vault_math::MAX_BPS;
+
+// The glob brings in what `#[derive(Accounts)]` generates for `Report`
+use crate::instructions::report::{self, *};
+use crate::state::{AdminAction, AdminLog, ProtocolState, ReserveConfig};
+
+#[derive(Accounts)]
+pub struct SetReserveFactor<'info> {
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+        has_one = owner @ SetReserveFactorError::Unauthorized,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// The protocol owner
+    pub owner: Signer<'info>,
+
+    /// The vault's reserve, reported at the old factor before it changes
+    pub report: Report<'info>,
+
+    /// The protocol's admin log, which records this call
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.load()?.bump,
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+}
+
+/// Changes the treasury's cut of future profit
+///
+/// The handler reports first, so profit made since the last `report` is
+/// split at the old factor.
+pub fn process_set_reserve_factor(
+    config: &mut ReserveConfig,
+    reserve_factor_bps: u16,
+) -> std::result::Result<(), SetReserveFactorError> {
+    if reserve_factor_bps > MAX_BPS {
+        return Err(SetReserveFactorError::InvalidReserveFactor);
+    }
+
+    config.reserve_factor_bps = reserve_factor_bps;
+
+    Ok(())
+}
+
+pub fn handler(ctx: Context<SetReserveFactor>, reserve_factor_bps: u16) -> Result<()> {
+    if reserve_factor_bps > 0
+        && !ctx
+            .accounts
+            .protocol_state
+            .has_feature(ProtocolState::FEES_ENABLED)
+    {
+        return err!(SetReserveFactorError::FeesDisabled);
+    }
+
+    let event = report::settle(&mut ctx.accounts.report)?;
+    let settled = event.shares;
+    emit!(event);
+
+    let config = &mut ctx.accounts.report.reserve_config;
+    let previous = config.reserve_factor_bps;
+    process_set_reserve_factor(config, reserve_factor_bps)?;
+
+    ctx.accounts.admin_log.load_mut()?.append(
+        Clock::get()?.slot,
+        ctx.accounts.owner.key(),
+        AdminAction::SetReserveFactor,
+        &[
+            config.vault.as_ref(),
+            &config.reserve_factor_bps.to_le_bytes(),
+        ],
+    );
+
+    msg!("Reserve factor updated!");
+    msg!("Vault: {}", config.vault);
+    msg!("Previous factor: {} bps", previous);
+    msg!("Settled at the previous factor: {} shares", settled);
+    msg!("New factor: {} bps", config.reserve_factor_bps);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum SetReserveFactorError {
+    #[msg("Only the protocol owner can set a reserve factor")]
+    Unauthorized,
+    #[msg("Reserve factor cannot exceed 10000 basis points")]
+    InvalidReserveFactor,
+    #[msg("Fees are disabled, so the reserve factor must be zero")]
+    FeesDisabled,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ReserveConfig {
+        ReserveConfig {
+            vault: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            reserve_factor_bps: 1_000,
+            withdrawal_fee_bps: 0,
+            mark_assets: 12_500,
+            mark_shares: 10_000,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn only_the_factor_changes() {
+        let mut config = config();
+        let (mark_assets, mark_shares) = (config.mark_assets, config.mark_shares);
+        process_set_reserve_factor(&mut config, MAX_BPS).unwrap();
+        assert_eq!(config.reserve_factor_bps, MAX_BPS);
+        assert_eq!(
+            (config.mark_assets, config.mark_shares),
+            (mark_assets, mark_shares)
+        );
+    }
+
+    #[test]
+    fn pending_profit_settles_at_the_old_factor() {
+        let mut config = config();
+        let settled = report::process_report(&mut config, 25_000, 10_000, true).unwrap();
+        process_set_reserve_factor(&mut config, MAX_BPS).unwrap();
+        assert_eq!(settled.reserve, 1_250);
+
+        // The new factor only sees profit made after the settlement
+        let after =
+            report::process_report(&mut config, 25_000, 10_000 + settled.shares, true).unwrap();
+        assert_eq!(after.shares, 0);
+    }
+
+    #[test]
+    fn factor_above_a_whole_is_rejected() {
+        let mut config = config();
+        let result = process_set_reserve_factor(&mut config, MAX_BPS + 1);
+        assert!(matches!(
+            result,
+            Err(SetReserveFactorError::InvalidReserveFactor)
+        ));
+        assert_eq!(config.reserve_factor_bps, 1_000);
+    }
+}
@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+
+use crate::events::VaultNamedEvent;
+use crate::state::{AdminAction, AdminLog, Vault};
+
+#[derive(Accounts)]
+pub struct SetVaultName<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.underlying_mint.as_ref()],
+        bump = vault.bump,
+        has_one = creator @ SetVaultNameError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Who created the vault, or the protocol owner that migrated it
+    pub creator: Signer<'info>,
+
+    /// The protocol's admin log, which records this call
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.load()?.bump,
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+}
+
+/// Replaces the vault's name. Names are labels only: nothing else reads
+/// them, so no two vaults are kept from sharing one
+pub fn process_set_vault_name(
+    vault: &mut Vault,
+    name: &str,
+) -> std::result::Result<(), SetVaultNameError> {
+    vault.name = Vault::encode_name(name).ok_or(SetVaultNameError::InvalidName)?;
+
+    Ok(())
+}
+
+pub fn handler(ctx: Context<SetVaultName>, name: String) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let previous = vault.name();
+    process_set_vault_name(vault, &name)?;
+
+    ctx.accounts.admin_log.load_mut()?.append(
+        Clock::get()?.slot,
+        ctx.accounts.creator.key(),
+        AdminAction::SetVaultName,
+        &[vault.key().as_ref(), &vault.name],
+    );
+
+    msg!("Vault renamed!");
+    msg!("Vault: {}", vault.key());
+    msg!("Previous Name: {}", previous);
+    msg!("New Name: {}", name);
+
+    emit!(VaultNamedEvent {
+        vault: vault.key(),
+        underlying_mint: vault.underlying_mint,
+        name: vault.name,
+    });
+
+    Ok(())
+}
+
+#[error_code]
+pub enum SetVaultNameError {
+    #[msg("Only the vault's creator can rename it")]
+    Unauthorized,
+    #[msg("Vault names must be at most 32 bytes, without NUL bytes")]
+    InvalidName,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::fixtures;
+
+    fn vault() -> Vault {
+        Vault {
+            name: Vault::encode_name("USDC vault").unwrap(),
+            ..fixtures::vault()
+        }
+    }
+
+    #[test]
+    fn renames_up_to_the_maximum_length() {
+        let mut vault = vault();
+        process_set_vault_name(&mut vault, "Staked SOL \u{2013} epoch 600").unwrap();
+        assert_eq!(vault.name(), "Staked SOL \u{2013} epoch 600");
+
+        let longest = "x".repeat(32);
+        process_set_vault_name(&mut vault, &longest).unwrap();
+        assert_eq!(vault.name(), longest);
+
+        process_set_vault_name(&mut vault, "").unwrap();
+        assert_eq!(vault.name, [0; 32]);
+        assert_eq!(vault.name(), "");
+    }
+
+    #[test]
+    fn long_names_are_rejected() {
+        let mut vault = vault();
+        let result = process_set_vault_name(&mut vault, &"x".repeat(33));
+        assert!(matches!(result, Err(SetVaultNameError::InvalidName)));
+        assert_eq!(vault.name(), "USDC vault");
+    }
+
+    #[test]
+    fn names_with_nul_bytes_are_rejected() {
+        let mut vault = vault();
+        for name in ["USDC\0vault", "USDC vault\0"] {
+            let result = process_set_vault_name(&mut vault, name);
+            assert!(matches!(result, Err(SetVaultNameError::InvalidName)));
+        }
+        assert_eq!(vault.name(), "USDC vault");
+    }
+}
@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+
+use vault_math::MAX_WITHDRAWAL_FEE_BPS;
+
+use crate::state::{AdminAction, AdminLog, ProtocolState, ReserveConfig, Vault};
+
+#[derive(Accounts)]
+pub struct SetWithdrawalFee<'info> {
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+        has_one = owner @ SetWithdrawalFeeError::Unauthorized,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// The vault's reserve config, whose treasury is paid the fee
+    #[account(
+        mut,
+        seeds = [b"reserve_config", vault.key().as_ref()],
+        bump = reserve_config.bump,
+        has_one = vault,
+    )]
+    pub reserve_config: Account<'info, ReserveConfig>,
+
+    #[account(
+        seeds = [b"vault", vault.underlying_mint.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// The protocol owner
+    pub owner: Signer<'info>,
+
+    /// The protocol's admin log, which records this call
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.load()?.bump,
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+}
+
+/// Changes the share of every later redeem that goes to the treasury
+pub fn process_set_withdrawal_fee(
+    config: &mut ReserveConfig,
+    withdrawal_fee_bps: u16,
+    fees_enabled: bool,
+) -> std::result::Result<(), SetWithdrawalFeeError> {
+    if withdrawal_fee_bps > MAX_WITHDRAWAL_FEE_BPS {
+        return Err(SetWithdrawalFeeError::InvalidWithdrawalFee);
+    }
+    if withdrawal_fee_bps > 0 && !fees_enabled {
+        return Err(SetWithdrawalFeeError::FeesDisabled);
+    }
+
+    config.withdrawal_fee_bps = withdrawal_fee_bps;
+
+    Ok(())
+}
+
+pub fn handler(ctx: Context<SetWithdrawalFee>, withdrawal_fee_bps: u16) -> Result<()> {
+    let fees_enabled = ctx
+        .accounts
+        .protocol_state
+        .has_feature(ProtocolState::FEES_ENABLED);
+    let config = &mut ctx.accounts.reserve_config;
+    let previous = config.withdrawal_fee_bps;
+    process_set_withdrawal_fee(config, withdrawal_fee_bps, fees_enabled)?;
+
+    ctx.accounts.admin_log.load_mut()?.append(
+        Clock::get()?.slot,
+        ctx.accounts.owner.key(),
+        AdminAction::SetWithdrawalFee,
+        &[
+            config.vault.as_ref(),
+            &config.withdrawal_fee_bps.to_le_bytes(),
+        ],
+    );
+
+    msg!("Withdrawal fee updated!");
+    msg!("Vault: {}", config.vault);
+    msg!("Previous fee: {} bps", previous);
+    msg!("New fee: {} bps", config.withdrawal_fee_bps);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum SetWithdrawalFeeError {
+    #[msg("Only the protocol owner can set a withdrawal fee")]
+    Unauthorized,
+    #[msg("Withdrawal fee cannot exceed 1000 basis points")]
+    InvalidWithdrawalFee,
+    #[msg("Fees are disabled, so the withdrawal fee must be zero")]
+    FeesDisabled,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ReserveConfig {
+        ReserveConfig {
+            vault: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            reserve_factor_bps: 1_000,
+            withdrawal_fee_bps: 0,
+            mark_assets: 12_500,
+            mark_shares: 10_000,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn only_the_fee_changes() {
+        let mut config = config();
+        process_set_withdrawal_fee(&mut config, MAX_WITHDRAWAL_FEE_BPS, true).unwrap();
+        assert_eq!(config.withdrawal_fee_bps, MAX_WITHDRAWAL_FEE_BPS);
+        assert_eq!(config.reserve_factor_bps, 1_000);
+        assert_eq!((config.mark_assets, config.mark_shares), (12_500, 10_000));
+    }
+
+    #[test]
+    fn fees_above_the_cap_or_while_disabled_are_rejected() {
+        let mut config = config();
+        let result = process_set_withdrawal_fee(&mut config, MAX_WITHDRAWAL_FEE_BPS + 1, true);
+        assert!(matches!(
+            result,
+            Err(SetWithdrawalFeeError::InvalidWithdrawalFee)
+        ));
+
+        let result = process_set_withdrawal_fee(&mut config, 30, false);
+        assert!(matches!(result, Err(SetWithdrawalFeeError::FeesDisabled)));
+        assert_eq!(config.withdrawal_fee_bps, 0);
+
+        // Clearing the fee is always allowed
+        config.withdrawal_fee_bps = 30;
+        process_set_withdrawal_fee(&mut config, 0, false).unwrap();
+        assert_eq!(config.withdrawal_fee_bps, 0);
+    }
+}
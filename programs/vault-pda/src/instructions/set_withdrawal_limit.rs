@@ -0,0 +1,131 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{ActivityStats, AdminAction, AdminLog, ProtocolState, Vault};
+
+#[derive(Accounts)]
+pub struct SetWithdrawalLimit<'info> {
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+        has_one = owner @ SetWithdrawalLimitError::Unauthorized,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// The vault's statistics, which hold the limit `redeem` checks
+    #[account(
+        mut,
+        seeds = [b"activity_stats", vault.key().as_ref()],
+        bump
+    )]
+    pub activity_stats: AccountLoader<'info, ActivityStats>,
+
+    #[account(
+        seeds = [b"vault", vault.underlying_mint.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// The protocol owner
+    pub owner: Signer<'info>,
+
+    /// The protocol's admin log, which records this call
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.load()?.bump,
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+}
+
+/// Caps the underlying the vault's redeems pay out over any `window_slots`
+/// consecutive slots, or lifts the cap with a zero `withdrawal_limit`
+pub fn process_set_withdrawal_limit(
+    stats: &mut ActivityStats,
+    withdrawal_limit: u64,
+    window_slots: u64,
+) -> std::result::Result<(), SetWithdrawalLimitError> {
+    if withdrawal_limit > 0 && !(1..=ActivityStats::CAPACITY as u64).contains(&window_slots) {
+        return Err(SetWithdrawalLimitError::InvalidWindow);
+    }
+
+    stats.withdrawal_limit = withdrawal_limit;
+    stats.limit_window_slots = if withdrawal_limit > 0 {
+        window_slots
+    } else {
+        0
+    };
+
+    Ok(())
+}
+
+pub fn handler(
+    ctx: Context<SetWithdrawalLimit>,
+    withdrawal_limit: u64,
+    window_slots: u64,
+) -> Result<()> {
+    let mut stats = ctx.accounts.activity_stats.load_mut()?;
+    process_set_withdrawal_limit(&mut stats, withdrawal_limit, window_slots)?;
+
+    ctx.accounts.admin_log.load_mut()?.append(
+        Clock::get()?.slot,
+        ctx.accounts.owner.key(),
+        AdminAction::SetWithdrawalLimit,
+        &[
+            stats.vault.as_ref(),
+            &stats.withdrawal_limit.to_le_bytes(),
+            &stats.limit_window_slots.to_le_bytes(),
+        ],
+    );
+
+    msg!("Withdrawal limit updated!");
+    msg!("Vault: {}", stats.vault);
+    msg!("Limit: {}", stats.withdrawal_limit);
+    msg!("Window: {} slots", stats.limit_window_slots);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum SetWithdrawalLimitError {
+    #[msg("Only the protocol owner can set a withdrawal limit")]
+    Unauthorized,
+    #[msg("The window must span between 1 and 256 slots")]
+    InvalidWindow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats() -> Box<ActivityStats> {
+        Box::new(bytemuck::Zeroable::zeroed())
+    }
+
+    #[test]
+    fn sets_and_lifts_the_limit() {
+        let mut stats = stats();
+        process_set_withdrawal_limit(&mut stats, 1_000, 150).unwrap();
+        assert_eq!(
+            (stats.withdrawal_limit, stats.limit_window_slots),
+            (1_000, 150)
+        );
+
+        // Lifting the limit ignores the window
+        process_set_withdrawal_limit(&mut stats, 0, 0).unwrap();
+        assert_eq!((stats.withdrawal_limit, stats.limit_window_slots), (0, 0));
+        assert_eq!(stats.withdrawable(100), None);
+    }
+
+    #[test]
+    fn windows_past_the_buffer_are_rejected() {
+        let mut stats = stats();
+        for window_slots in [0, ActivityStats::CAPACITY as u64 + 1] {
+            let result = process_set_withdrawal_limit(&mut stats, 1_000, window_slots);
+            assert!(matches!(
+                result,
+                Err(SetWithdrawalLimitError::InvalidWindow)
+            ));
+        }
+        assert_eq!(stats.withdrawal_limit, 0);
+    }
+}
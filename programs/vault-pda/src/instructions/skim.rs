@@ -0,0 +1,212 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use vault_math::{compute_skim, KernelError, ReserveSplit};
+
+use crate::events::SkimEvent;
+use crate::state::{vault_signer_seeds, ProtocolState, ReserveConfig, Vault, VaultAuthority};
+
+#[derive(Accounts)]
+pub struct Skim<'info> {
+    #[account(
+        mut,
+        seeds = [b"reserve_config", vault.key().as_ref()],
+        bump = reserve_config.bump,
+        has_one = vault,
+        has_one = treasury,
+    )]
+    pub reserve_config: Account<'info, ReserveConfig>,
+
+    #[account(
+        seeds = [b"vault", vault.underlying_mint.as_ref()],
+        bump = vault.bump,
+        has_one = underlying_mint,
+        has_one = share_mint,
+        has_one = vault_token_account,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// The underlying asset mint
+    pub underlying_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The vault's token account the cut is paid out of
+    #[account(mut)]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The share mint
+    pub share_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The vault authority that can transfer from the vault
+    #[account(
+        seeds = [b"vault_authority"],
+        bump = vault_authority.bump
+    )]
+    pub vault_authority: Account<'info, VaultAuthority>,
+
+    /// The treasury's share account, whose owner is paid
+    pub treasury: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The treasury owner's underlying account the cut is paid to
+    #[account(
+        mut,
+        token::mint = underlying_mint,
+        constraint = treasury_underlying_account.owner == treasury.owner
+            @ SkimError::NotTheTreasury,
+    )]
+    pub treasury_underlying_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Whose feature bits decide whether the treasury is paid
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Splits the profit the vault made since `config`'s high-water mark and
+/// moves the mark to the vault's totals once the treasury's cut has left
+///
+/// With fees disabled the cut is zero but the mark still moves, as in
+/// `report`.
+pub fn process_skim(
+    config: &mut ReserveConfig,
+    total_assets: u64,
+    total_shares: u64,
+    fees_enabled: bool,
+) -> std::result::Result<ReserveSplit, SkimError> {
+    let reserve_factor_bps = if fees_enabled {
+        config.reserve_factor_bps
+    } else {
+        0
+    };
+    let split = compute_skim(
+        total_assets,
+        total_shares,
+        config.mark_assets,
+        config.mark_shares,
+        reserve_factor_bps,
+    )?;
+
+    config.mark_assets = split.mark_assets;
+    config.mark_shares = split.mark_shares;
+
+    Ok(split)
+}
+
+/// Anyone can skim: the cut only depends on the vault's balances and is
+/// only paid to the treasury's owner
+pub fn handler(ctx: Context<Skim>) -> Result<()> {
+    let total_shares = ctx.accounts.share_mint.supply;
+    let fees_enabled = ctx
+        .accounts
+        .protocol_state
+        .has_feature(ProtocolState::FEES_ENABLED);
+    let split = process_skim(
+        &mut ctx.accounts.reserve_config,
+        ctx.accounts.vault_token_account.amount,
+        total_shares,
+        fees_enabled,
+    )?;
+
+    if split.reserve > 0 {
+        // Pay the cut out of the vault, signed by the vault's token authority
+        let (authority, authority_seeds) =
+            vault_signer_seeds(&ctx.accounts.vault, &ctx.accounts.vault_authority);
+        let signer_seeds = &[authority_seeds.as_slice()];
+
+        let transfer_accounts = TransferChecked {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            mint: ctx.accounts.underlying_mint.to_account_info(),
+            to: ctx.accounts.treasury_underlying_account.to_account_info(),
+            authority,
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(
+            cpi_ctx,
+            split.reserve,
+            ctx.accounts.underlying_mint.decimals,
+        )?;
+    }
+
+    let event = SkimEvent {
+        vault: ctx.accounts.vault.key(),
+        treasury: ctx.accounts.treasury_underlying_account.key(),
+        profit: split.profit,
+        reserve: split.reserve,
+        total_assets: split.mark_assets,
+        total_shares,
+    };
+
+    #[cfg(feature = "verbose-logs")]
+    {
+        msg!("Skim successful!");
+        msg!("Profit since the mark: {}", event.profit);
+        msg!("Paid to the treasury: {}", event.reserve);
+    }
+
+    emit!(event);
+
+    Ok(())
+}
+
+#[error_code]
+pub enum SkimError {
+    #[msg("Math operation overflow")]
+    MathOverflow,
+    #[msg("The account is not owned by the treasury's owner")]
+    NotTheTreasury,
+}
+
+impl From<KernelError> for SkimError {
+    fn from(_: KernelError) -> Self {
+        // `compute_skim` only fails on overflow
+        SkimError::MathOverflow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(mark_assets: u64, mark_shares: u64) -> ReserveConfig {
+        ReserveConfig {
+            vault: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            reserve_factor_bps: 1_000,
+            withdrawal_fee_bps: 0,
+            mark_assets,
+            mark_shares,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn yield_pays_the_treasury_in_underlying() {
+        let mut config = config(1_000, 1_000);
+        let split = process_skim(&mut config, 2_000, 1_000, true).unwrap();
+        assert_eq!((split.profit, split.reserve, split.shares), (1_000, 100, 0));
+        assert_eq!((config.mark_assets, config.mark_shares), (1_900, 1_000));
+
+        // The vault holds what the skim left it
+        let split = process_skim(&mut config, 1_900, 1_000, true).unwrap();
+        assert_eq!(split.reserve, 0);
+    }
+
+    #[test]
+    fn disabled_fees_move_the_mark_without_paying() {
+        let mut config = config(1_000, 1_000);
+        let split = process_skim(&mut config, 2_000, 1_000, false).unwrap();
+        assert_eq!((split.profit, split.reserve), (1_000, 0));
+        assert_eq!((config.mark_assets, config.mark_shares), (2_000, 1_000));
+    }
+}
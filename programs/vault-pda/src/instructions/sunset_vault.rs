@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{AdminAction, AdminLog, ProtocolState, Vault};
+
+#[derive(Accounts)]
+pub struct SunsetVault<'info> {
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+        has_one = owner @ SunsetVaultError::Unauthorized,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.underlying_mint.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// The protocol owner
+    pub owner: Signer<'info>,
+
+    /// The protocol's admin log, which records this call
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.load()?.bump,
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+}
+
+/// Closes the vault to deposits for good
+///
+/// No instruction clears the flag, so holders can rely on the vault never
+/// taking new deposits again. Redeems are unaffected. Vaults that predate the
+/// flag are brought to the current layout by `migrate_vault` first.
+pub fn process_sunset_vault(vault: &mut Vault) -> std::result::Result<(), SunsetVaultError> {
+    if vault.sunset {
+        return Err(SunsetVaultError::AlreadySunset);
+    }
+
+    vault.sunset = true;
+
+    Ok(())
+}
+
+pub fn handler(ctx: Context<SunsetVault>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    process_sunset_vault(vault)?;
+
+    ctx.accounts.admin_log.load_mut()?.append(
+        Clock::get()?.slot,
+        ctx.accounts.owner.key(),
+        AdminAction::SunsetVault,
+        &[vault.key().as_ref()],
+    );
+
+    msg!("Vault sunset!");
+    msg!("Vault: {}", vault.key());
+    msg!("Deposits are closed; redeems stay open");
+
+    Ok(())
+}
+
+#[error_code]
+pub enum SunsetVaultError {
+    #[msg("Only the protocol owner can sunset a vault")]
+    Unauthorized,
+    #[msg("Vault is already sunset")]
+    AlreadySunset,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::fixtures::vault;
+
+    #[test]
+    fn sunset_is_one_way() {
+        let mut vault = vault();
+        process_sunset_vault(&mut vault).unwrap();
+        assert!(vault.sunset);
+
+        let result = process_sunset_vault(&mut vault);
+        assert!(matches!(result, Err(SunsetVaultError::AlreadySunset)));
+        assert!(vault.sunset);
+    }
+}
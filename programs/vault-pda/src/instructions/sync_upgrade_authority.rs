@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+use crate::state::{AdminAction, AdminLog, ProtocolState};
+
+#[derive(Accounts)]
+pub struct SyncUpgradeAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+        has_one = owner @ SyncUpgradeAuthorityError::Unauthorized,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// The program's `ProgramData`, which holds its upgrade authority
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    /// The protocol owner
+    pub owner: Signer<'info>,
+
+    /// The protocol's admin log, which records this call
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.load()?.bump,
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+}
+
+/// Records `upgrade_authority`, the one `ProgramData` holds at `slot`
+///
+/// Upgrades can only be claimed renounced once `ProgramData` has no authority
+/// left, so the flag never outruns the loader.
+pub fn process_sync_upgrade_authority(
+    protocol_state: &mut ProtocolState,
+    upgrade_authority: Option<Pubkey>,
+    renounce_upgrades_expected: bool,
+    slot: u64,
+) -> std::result::Result<(), SyncUpgradeAuthorityError> {
+    if renounce_upgrades_expected && upgrade_authority.is_some() {
+        return Err(SyncUpgradeAuthorityError::UpgradesNotRenounced);
+    }
+
+    protocol_state.upgrade_authority = upgrade_authority;
+    protocol_state.renounce_upgrades_expected = renounce_upgrades_expected;
+    protocol_state.upgrade_authority_synced_slot = Some(slot);
+
+    Ok(())
+}
+
+pub fn handler(ctx: Context<SyncUpgradeAuthority>, renounce_upgrades_expected: bool) -> Result<()> {
+    let protocol_state = &mut ctx.accounts.protocol_state;
+    process_sync_upgrade_authority(
+        protocol_state,
+        ctx.accounts.program_data.upgrade_authority_address,
+        renounce_upgrades_expected,
+        Clock::get()?.slot,
+    )?;
+
+    let authority = protocol_state.upgrade_authority.unwrap_or_default();
+    ctx.accounts.admin_log.load_mut()?.append(
+        Clock::get()?.slot,
+        ctx.accounts.owner.key(),
+        AdminAction::SyncUpgradeAuthority,
+        &[
+            authority.as_ref(),
+            &[protocol_state.renounce_upgrades_expected as u8],
+        ],
+    );
+
+    msg!("Upgrade authority synced!");
+    match protocol_state.upgrade_authority {
+        Some(authority) => msg!("Upgrade authority: {}", authority),
+        None => msg!("Upgrade authority: none"),
+    }
+    msg!(
+        "Renounce upgrades expected: {}",
+        protocol_state.renounce_upgrades_expected
+    );
+
+    Ok(())
+}
+
+#[error_code]
+pub enum SyncUpgradeAuthorityError {
+    #[msg("Only the protocol owner can sync the upgrade authority")]
+    Unauthorized,
+    #[msg("The program still has an upgrade authority")]
+    UpgradesNotRenounced,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::fixtures::protocol_state;
+
+    #[test]
+    fn records_the_loaders_authority() {
+        let mut state = protocol_state();
+        let authority = Pubkey::new_unique();
+        process_sync_upgrade_authority(&mut state, Some(authority), false, 42).unwrap();
+        assert_eq!(state.upgrade_authority, Some(authority));
+        assert!(!state.renounce_upgrades_expected);
+        assert_eq!(state.upgrade_authority_synced_slot, Some(42));
+
+        process_sync_upgrade_authority(&mut state, None, true, 43).unwrap();
+        assert_eq!(state.upgrade_authority, None);
+        assert!(state.renounce_upgrades_expected);
+    }
+
+    #[test]
+    fn renouncing_requires_an_immutable_program() {
+        let mut state = protocol_state();
+        let result =
+            process_sync_upgrade_authority(&mut state, Some(Pubkey::new_unique()), true, 42);
+        assert!(matches!(
+            result,
+            Err(SyncUpgradeAuthorityError::UpgradesNotRenounced)
+        ));
+        assert_eq!(state.upgrade_authority_synced_slot, None);
+    }
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::state::ProtocolState;
+use crate::state::{AdminAction, AdminLog, ProtocolState};
 
 #[derive(Accounts)]
 pub struct TransferOwnership<'info> {
@@ -11,23 +11,52 @@ pub struct TransferOwnership<'info> {
     )]
     pub protocol_state: Account<'info, ProtocolState>,
 
-    /// CHECK: Current protocol owner
+    /// CHECK: Current protocol owner. Only the hardened build requires its
+    /// signature
+    #[cfg_attr(not(feature = "vuln-ownership"), account(signer))]
     pub current_owner: UncheckedAccount<'info>,
 
     /// CHECK: New protocol owner
     pub new_owner: UncheckedAccount<'info>,
+
+    /// The protocol's admin log, which records this call
+    #[account(
+        mut,
+        seeds = [b"admin_log"],
+        bump = admin_log.load()?.bump,
+    )]
+    pub admin_log: AccountLoader<'info, AdminLog>,
+}
+
+/// Hands the protocol to `new_owner` if `current_owner` owns it
+pub fn process_transfer_ownership(
+    protocol_state: &mut ProtocolState,
+    current_owner: &Pubkey,
+    new_owner: &Pubkey,
+) -> std::result::Result<(), TransferOwnershipError> {
+    if *current_owner != protocol_state.owner {
+        return Err(TransferOwnershipError::Unauthorized);
+    }
+
+    protocol_state.owner = *new_owner;
+
+    Ok(())
 }
 
 pub fn handler(ctx: Context<TransferOwnership>) -> Result<()> {
-    let protocol_state = &mut ctx.accounts.protocol_state;
+    process_transfer_ownership(
+        &mut ctx.accounts.protocol_state,
+        &ctx.accounts.current_owner.key(),
+        &ctx.accounts.new_owner.key(),
+    )?;
 
-    require!(
-        ctx.accounts.current_owner.key() == protocol_state.owner,
-        TransferOwnershipError::Unauthorized
+    ctx.accounts.admin_log.load_mut()?.append(
+        Clock::get()?.slot,
+        ctx.accounts.current_owner.key(),
+        AdminAction::TransferOwnership,
+        &[ctx.accounts.new_owner.key.as_ref()],
     );
 
-    protocol_state.owner = ctx.accounts.new_owner.key();
-
     msg!("Ownership transferred!");
     msg!("Previous owner: {}", ctx.accounts.current_owner.key());
     msg!("New owner: {}", ctx.accounts.new_owner.key());
@@ -40,3 +69,26 @@ pub enum TransferOwnershipError {
     #[msg("Only the current owner can transfer ownership")]
     Unauthorized,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::fixtures::protocol_state;
+
+    #[test]
+    fn owner_hands_over_the_protocol() {
+        let (owner, new_owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut protocol_state = ProtocolState {
+            owner,
+            ..protocol_state()
+        };
+
+        process_transfer_ownership(&mut protocol_state, &owner, &new_owner).unwrap();
+        assert_eq!(protocol_state.owner, new_owner);
+
+        // The previous owner has no say any more
+        let result = process_transfer_ownership(&mut protocol_state, &owner, &owner);
+        assert!(matches!(result, Err(TransferOwnershipError::Unauthorized)));
+        assert_eq!(protocol_state.owner, new_owner);
+    }
+}
@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+use crate::state::ProtocolState;
+
+#[derive(Accounts)]
+pub struct VerifyUpgradeAuthority<'info> {
+    #[account(
+        seeds = [b"protocol_state"],
+        bump = protocol_state.bump,
+    )]
+    pub protocol_state: Account<'info, ProtocolState>,
+
+    /// The program's `ProgramData`, which holds its upgrade authority
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+/// Checks that `upgrade_authority`, the one `ProgramData` holds now, is the
+/// one last synced into `protocol_state`
+pub fn process_verify_upgrade_authority(
+    protocol_state: &ProtocolState,
+    upgrade_authority: Option<Pubkey>,
+) -> std::result::Result<(), VerifyUpgradeAuthorityError> {
+    if protocol_state.upgrade_authority_synced_slot.is_none() {
+        return Err(VerifyUpgradeAuthorityError::NotSynced);
+    }
+    if protocol_state.renounce_upgrades_expected && upgrade_authority.is_some() {
+        return Err(VerifyUpgradeAuthorityError::UpgradesNotRenounced);
+    }
+    if upgrade_authority != protocol_state.upgrade_authority {
+        return Err(VerifyUpgradeAuthorityError::UpgradeAuthorityMismatch);
+    }
+
+    Ok(())
+}
+
+/// Anyone can verify, so an integrator can put this in its own transactions
+/// (or invoke it) to only act while the recorded authority still holds
+pub fn handler(ctx: Context<VerifyUpgradeAuthority>) -> Result<()> {
+    process_verify_upgrade_authority(
+        &ctx.accounts.protocol_state,
+        ctx.accounts.program_data.upgrade_authority_address,
+    )?;
+
+    msg!("Upgrade authority verified!");
+
+    Ok(())
+}
+
+#[error_code]
+pub enum VerifyUpgradeAuthorityError {
+    #[msg("The upgrade authority was never synced")]
+    NotSynced,
+    #[msg("The program still has an upgrade authority")]
+    UpgradesNotRenounced,
+    #[msg("The program's upgrade authority changed since it was synced")]
+    UpgradeAuthorityMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::fixtures::protocol_state;
+
+    fn synced(
+        upgrade_authority: Option<Pubkey>,
+        renounce_upgrades_expected: bool,
+    ) -> ProtocolState {
+        ProtocolState {
+            upgrade_authority,
+            renounce_upgrades_expected,
+            upgrade_authority_synced_slot: Some(42),
+            ..protocol_state()
+        }
+    }
+
+    #[test]
+    fn passes_while_the_authority_holds() {
+        let authority = Pubkey::new_unique();
+        process_verify_upgrade_authority(&synced(Some(authority), false), Some(authority)).unwrap();
+        process_verify_upgrade_authority(&synced(None, true), None).unwrap();
+    }
+
+    #[test]
+    fn a_changed_authority_fails() {
+        let state = synced(Some(Pubkey::new_unique()), false);
+        assert!(matches!(
+            process_verify_upgrade_authority(&state, Some(Pubkey::new_unique())),
+            Err(VerifyUpgradeAuthorityError::UpgradeAuthorityMismatch)
+        ));
+        assert!(matches!(
+            process_verify_upgrade_authority(&state, None),
+            Err(VerifyUpgradeAuthorityError::UpgradeAuthorityMismatch)
+        ));
+        assert!(matches!(
+            process_verify_upgrade_authority(&synced(None, true), Some(Pubkey::new_unique())),
+            Err(VerifyUpgradeAuthorityError::UpgradesNotRenounced)
+        ));
+    }
+
+    #[test]
+    fn an_unsynced_state_fails() {
+        let state = ProtocolState {
+            upgrade_authority_synced_slot: None,
+            ..synced(None, false)
+        };
+        assert!(matches!(
+            process_verify_upgrade_authority(&state, None),
+            Err(VerifyUpgradeAuthorityError::NotSynced)
+        ));
+    }
+}
@@ -1,11 +1,13 @@
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod state;
 
 use anchor_lang::prelude::*;
 
 pub use constants::*;
+pub use events::*;
 pub use instructions::*;
 pub use state::*;
 
@@ -19,8 +21,8 @@ pub mod vault_pda {
         initialize::handler(ctx)
     }
 
-    pub fn initialize_vault(ctx: Context<InitializeVault>) -> Result<()> {
-        initialize_vault::handler(ctx)
+    pub fn initialize_vault(ctx: Context<InitializeVault>, name: String) -> Result<()> {
+        initialize_vault::handler(ctx, name)
     }
 
     pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
@@ -34,4 +36,108 @@ pub mod vault_pda {
     pub fn transfer_ownership(ctx: Context<TransferOwnership>) -> Result<()> {
         transfer_ownership::handler(ctx)
     }
+
+    pub fn initialize_price_history(ctx: Context<InitializePriceHistory>) -> Result<()> {
+        initialize_price_history::handler(ctx)
+    }
+
+    pub fn record_price(ctx: Context<RecordPrice>) -> Result<()> {
+        record_price::handler(ctx)
+    }
+
+    pub fn initialize_flag(ctx: Context<InitializeFlag>) -> Result<()> {
+        initialize_flag::handler(ctx)
+    }
+
+    pub fn register_player(ctx: Context<RegisterPlayer>) -> Result<()> {
+        register_player::handler(ctx)
+    }
+
+    pub fn capture_flag(ctx: Context<CaptureFlag>) -> Result<()> {
+        capture_flag::handler(ctx)
+    }
+
+    pub fn initialize_reserve(
+        ctx: Context<InitializeReserve>,
+        reserve_factor_bps: u16,
+    ) -> Result<()> {
+        initialize_reserve::handler(ctx, reserve_factor_bps)
+    }
+
+    pub fn set_reserve_factor(
+        ctx: Context<SetReserveFactor>,
+        reserve_factor_bps: u16,
+    ) -> Result<()> {
+        set_reserve_factor::handler(ctx, reserve_factor_bps)
+    }
+
+    pub fn report(ctx: Context<Report>) -> Result<()> {
+        report::handler(ctx)
+    }
+
+    pub fn skim(ctx: Context<Skim>) -> Result<()> {
+        skim::handler(ctx)
+    }
+
+    pub fn sync_upgrade_authority(
+        ctx: Context<SyncUpgradeAuthority>,
+        renounce_upgrades_expected: bool,
+    ) -> Result<()> {
+        sync_upgrade_authority::handler(ctx, renounce_upgrades_expected)
+    }
+
+    pub fn verify_upgrade_authority(ctx: Context<VerifyUpgradeAuthority>) -> Result<()> {
+        verify_upgrade_authority::handler(ctx)
+    }
+
+    pub fn sunset_vault(ctx: Context<SunsetVault>) -> Result<()> {
+        sunset_vault::handler(ctx)
+    }
+
+    pub fn set_features(ctx: Context<SetFeatures>, features: u8) -> Result<()> {
+        set_features::handler(ctx, features)
+    }
+
+    pub fn set_vault_name(ctx: Context<SetVaultName>, name: String) -> Result<()> {
+        set_vault_name::handler(ctx, name)
+    }
+
+    pub fn close_share_account(ctx: Context<CloseShareAccount>) -> Result<()> {
+        close_share_account::handler(ctx)
+    }
+
+    pub fn deposit_delegated(ctx: Context<DepositDelegated>, amount: u64) -> Result<()> {
+        deposit_delegated::handler(ctx, amount)
+    }
+
+    pub fn migrate_protocol(ctx: Context<MigrateProtocol>) -> Result<()> {
+        migrate_protocol::handler(ctx)
+    }
+
+    pub fn migrate_vault(ctx: Context<MigrateVault>) -> Result<()> {
+        migrate_vault::handler(ctx)
+    }
+
+    pub fn set_withdrawal_fee(
+        ctx: Context<SetWithdrawalFee>,
+        withdrawal_fee_bps: u16,
+    ) -> Result<()> {
+        set_withdrawal_fee::handler(ctx, withdrawal_fee_bps)
+    }
+
+    pub fn add_fee_exemption(ctx: Context<AddFeeExemption>) -> Result<()> {
+        add_fee_exemption::handler(ctx)
+    }
+
+    pub fn remove_fee_exemption(ctx: Context<RemoveFeeExemption>) -> Result<()> {
+        remove_fee_exemption::handler(ctx)
+    }
+
+    pub fn set_withdrawal_limit(
+        ctx: Context<SetWithdrawalLimit>,
+        withdrawal_limit: u64,
+        window_slots: u64,
+    ) -> Result<()> {
+        set_withdrawal_limit::handler(ctx, withdrawal_limit, window_slots)
+    }
 }
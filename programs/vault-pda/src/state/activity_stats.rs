@@ -0,0 +1,177 @@
+use anchor_lang::prelude::*;
+
+/// Volumes through a vault in a single slot
+#[zero_copy]
+#[repr(C)]
+pub struct ActivityBucket {
+    /// Slot the volumes were recorded in
+    pub slot: u64,
+    /// Underlying deposited in the slot
+    pub deposited: u64,
+    /// Underlying paid out by redeems in the slot
+    pub withdrawn: u64,
+}
+
+/// Ring buffer of a vault's deposit and withdrawal volumes, one bucket per
+/// slot with activity.
+///
+/// Zero-copy like `PriceHistory`, but small enough to be created as a PDA
+/// (`[b"activity_stats", vault]`) so each vault has at most one.
+/// `initialize_vault` creates it (`migrate_vault` for vaults from the first
+/// release), and `deposit` and `redeem` require it, so every call is counted
+/// and no redeem gets around the withdrawal limit.
+#[account(zero_copy)]
+#[repr(C)]
+pub struct ActivityStats {
+    /// The vault these statistics belong to
+    pub vault: Pubkey,
+    /// Index of the next bucket to write
+    pub head: u64,
+    /// Number of populated buckets, saturates at `CAPACITY`
+    pub len: u64,
+    /// Most underlying redeems may pay out over `limit_window_slots`, set by
+    /// the protocol owner; zero for no limit
+    pub withdrawal_limit: u64,
+    /// Number of slots, up to `CAPACITY`, the withdrawal limit spans
+    pub limit_window_slots: u64,
+    /// Buckets, oldest ones are overwritten once full
+    pub buckets: [ActivityBucket; ActivityStats::CAPACITY],
+}
+
+const _: () = assert!(ActivityStats::LEN == 8 + std::mem::size_of::<ActivityStats>());
+
+impl ActivityStats {
+    pub const CAPACITY: usize = 256;
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // vault
+        8 + // head
+        8 + // len
+        8 + // withdrawal_limit
+        8 + // limit_window_slots
+        24 * ActivityStats::CAPACITY; // buckets
+
+    /// Adds volumes at `slot` to its bucket, opening one (and overwriting the
+    /// oldest when full) if `slot` is not the latest
+    pub fn record(&mut self, slot: u64, deposited: u64, withdrawn: u64) {
+        let latest = (self.head as usize + Self::CAPACITY - 1) % Self::CAPACITY;
+        if self.len > 0 && self.buckets[latest].slot == slot {
+            let bucket = &mut self.buckets[latest];
+            bucket.deposited = bucket.deposited.saturating_add(deposited);
+            bucket.withdrawn = bucket.withdrawn.saturating_add(withdrawn);
+            return;
+        }
+
+        self.buckets[self.head as usize] = ActivityBucket {
+            slot,
+            deposited,
+            withdrawn,
+        };
+        self.head = (self.head + 1) % Self::CAPACITY as u64;
+        if (self.len as usize) < Self::CAPACITY {
+            self.len += 1;
+        }
+    }
+
+    /// Populated buckets, newest first
+    pub fn iter(&self) -> impl Iterator<Item = &ActivityBucket> {
+        (1..=self.len as usize).map(move |back| {
+            &self.buckets[(self.head as usize + Self::CAPACITY - back) % Self::CAPACITY]
+        })
+    }
+
+    /// Deposited and withdrawn volumes from `since_slot` on, if the buffer
+    /// still reaches back that far
+    pub fn volume_since(&self, since_slot: u64) -> Option<(u64, u64)> {
+        let oldest = self.iter().last().map_or(0, |bucket| bucket.slot);
+        if self.len as usize == Self::CAPACITY && since_slot < oldest {
+            return None;
+        }
+        Some(
+            self.iter()
+                .take_while(|bucket| bucket.slot >= since_slot)
+                .fold((0u64, 0u64), |(deposited, withdrawn), bucket| {
+                    (
+                        deposited.saturating_add(bucket.deposited),
+                        withdrawn.saturating_add(bucket.withdrawn),
+                    )
+                }),
+        )
+    }
+
+    /// Underlying redeems may still pay out at `slot` before the withdrawal
+    /// limit is reached, or `None` without a limit
+    ///
+    /// The window ends at `slot` and spans at most `CAPACITY` slots, so the
+    /// buffer always reaches back to its start.
+    pub fn withdrawable(&self, slot: u64) -> Option<u64> {
+        if self.withdrawal_limit == 0 {
+            return None;
+        }
+        let since_slot = (slot + 1).saturating_sub(self.limit_window_slots);
+        let (_, withdrawn) = self.volume_since(since_slot).unwrap_or((0, u64::MAX));
+        Some(self.withdrawal_limit.saturating_sub(withdrawn))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats() -> Box<ActivityStats> {
+        Box::new(bytemuck::Zeroable::zeroed())
+    }
+
+    #[test]
+    fn one_bucket_per_slot() {
+        let mut stats = stats();
+        stats.record(10, 100, 0);
+        stats.record(10, 50, 20);
+        stats.record(12, 0, 30);
+
+        assert_eq!(stats.len, 2);
+        let buckets: Vec<_> = stats
+            .iter()
+            .map(|b| (b.slot, b.deposited, b.withdrawn))
+            .collect();
+        assert_eq!(buckets, vec![(12, 0, 30), (10, 150, 20)]);
+        assert_eq!(stats.volume_since(11), Some((0, 30)));
+        assert_eq!(stats.volume_since(0), Some((150, 50)));
+    }
+
+    #[test]
+    fn volume_is_unknown_past_the_oldest_bucket() {
+        let mut stats = stats();
+        for slot in 0..ActivityStats::CAPACITY as u64 + 10 {
+            stats.record(slot, 1, 0);
+        }
+
+        assert_eq!(stats.len as usize, ActivityStats::CAPACITY);
+        assert_eq!(stats.iter().last().unwrap().slot, 10);
+        assert_eq!(stats.volume_since(9), None);
+        assert_eq!(
+            stats.volume_since(10),
+            Some((ActivityStats::CAPACITY as u64, 0))
+        );
+        assert_eq!(
+            stats.volume_since(11),
+            Some((ActivityStats::CAPACITY as u64 - 1, 0))
+        );
+    }
+
+    #[test]
+    fn withdrawals_are_limited_over_the_window() {
+        let mut stats = stats();
+        stats.record(10, 0, 600);
+        assert_eq!(stats.withdrawable(12), None);
+
+        stats.withdrawal_limit = 1_000;
+        stats.limit_window_slots = 5;
+        stats.record(12, 500, 300);
+        assert_eq!(stats.withdrawable(12), Some(100));
+        assert_eq!(stats.withdrawable(14), Some(100));
+        // Slot 10 leaves the window
+        assert_eq!(stats.withdrawable(15), Some(700));
+        assert_eq!(stats.withdrawable(17), Some(1_000));
+    }
+}
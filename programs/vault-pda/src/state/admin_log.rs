@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+/// An administrative instruction, as recorded in the `AdminLog`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AdminAction {
+    /// Params: the new owner
+    TransferOwnership = 1,
+    /// Params: the flagged vault
+    InitializeFlag = 2,
+    /// Params: the vault, the treasury and the reserve factor
+    InitializeReserve = 3,
+    /// Params: the vault and the new reserve factor
+    SetReserveFactor = 4,
+    /// Params: the synced authority, if any, and `renounce_upgrades_expected`
+    SyncUpgradeAuthority = 5,
+    /// Params: the vault
+    SunsetVault = 6,
+    /// Params: the new feature bitmap
+    SetFeatures = 7,
+    /// Params: the vault and its new, padded name
+    SetVaultName = 8,
+    /// Params: the program version the state was stamped with
+    MigrateProtocol = 9,
+    /// Params: the vault
+    MigrateVault = 10,
+    /// Params: the exempt address
+    AddFeeExemption = 11,
+    /// Params: the address that lost its exemption
+    RemoveFeeExemption = 12,
+    /// Params: the vault and the new withdrawal fee
+    SetWithdrawalFee = 13,
+    /// Params: the vault, the new limit and its window
+    SetWithdrawalLimit = 14,
+    /// Params: the initial owner, the program version and the feature bitmap
+    Initialize = 15,
+}
+
+impl AdminAction {
+    pub const ALL: [AdminAction; 15] = [
+        AdminAction::TransferOwnership,
+        AdminAction::InitializeFlag,
+        AdminAction::InitializeReserve,
+        AdminAction::SetReserveFactor,
+        AdminAction::SyncUpgradeAuthority,
+        AdminAction::SunsetVault,
+        AdminAction::SetFeatures,
+        AdminAction::SetVaultName,
+        AdminAction::MigrateProtocol,
+        AdminAction::MigrateVault,
+        AdminAction::AddFeeExemption,
+        AdminAction::RemoveFeeExemption,
+        AdminAction::SetWithdrawalFee,
+        AdminAction::SetWithdrawalLimit,
+        AdminAction::Initialize,
+    ];
+
+    /// The action an entry's `action` code stands for
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| *action as u8 == code)
+    }
+}
+
+/// One administrative instruction that ran
+#[zero_copy]
+#[repr(C)]
+pub struct AdminEntry {
+    /// Slot the instruction ran in
+    pub slot: u64,
+    /// The authority it ran as
+    pub actor: Pubkey,
+    /// `sha256` of its parameters, concatenated in the order `AdminAction`
+    /// lists them
+    pub params_hash: [u8; 32],
+    /// An `AdminAction` code
+    pub action: u8,
+    pub _padding: [u8; 7],
+}
+
+/// Ring buffer of the protocol's administrative instructions, so players and
+/// auditors can follow configuration changes without a historical RPC node.
+///
+/// A single PDA (`[b"admin_log"]`) that `initialize` creates with its own
+/// entry (`migrate_protocol` for deployments from the first release). Every
+/// admin instruction requires it and appends to it; nothing else writes it,
+/// and entries are only ever overwritten by newer ones once the buffer is
+/// full.
+#[account(zero_copy)]
+#[repr(C)]
+pub struct AdminLog {
+    /// Index of the next entry to write
+    pub head: u64,
+    /// Number of populated entries, saturates at `CAPACITY`
+    pub len: u64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+    /// Entries, oldest ones are overwritten once full
+    pub entries: [AdminEntry; AdminLog::CAPACITY],
+}
+
+const _: () = assert!(AdminLog::LEN == 8 + std::mem::size_of::<AdminLog>());
+
+impl AdminLog {
+    pub const CAPACITY: usize = 100;
+
+    pub const LEN: usize = 8 + // discriminator
+        8 + // head
+        8 + // len
+        1 + // bump
+        7 + // padding
+        80 * AdminLog::CAPACITY; // entries
+
+    /// Appends an entry, overwriting the oldest when full
+    pub fn append(&mut self, slot: u64, actor: Pubkey, action: AdminAction, params: &[&[u8]]) {
+        self.entries[self.head as usize] = AdminEntry {
+            slot,
+            actor,
+            params_hash: hashv(params).to_bytes(),
+            action: action as u8,
+            _padding: [0; 7],
+        };
+        self.head = (self.head + 1) % Self::CAPACITY as u64;
+        if (self.len as usize) < Self::CAPACITY {
+            self.len += 1;
+        }
+    }
+
+    /// Populated entries, newest first
+    pub fn iter(&self) -> impl Iterator<Item = &AdminEntry> {
+        (1..=self.len as usize).map(move |back| {
+            &self.entries[(self.head as usize + Self::CAPACITY - back) % Self::CAPACITY]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log() -> Box<AdminLog> {
+        Box::new(bytemuck::Zeroable::zeroed())
+    }
+
+    #[test]
+    fn records_newest_first_and_wraps() {
+        let mut log = log();
+        let actor = Pubkey::new_unique();
+        for slot in 0..AdminLog::CAPACITY as u64 + 3 {
+            log.append(slot, actor, AdminAction::SetFeatures, &[&[slot as u8]]);
+        }
+
+        assert_eq!(log.len as usize, AdminLog::CAPACITY);
+        let newest = log.iter().next().unwrap();
+        assert_eq!(newest.slot, AdminLog::CAPACITY as u64 + 2);
+        assert_eq!(newest.actor, actor);
+        assert_eq!(
+            AdminAction::from_code(newest.action),
+            Some(AdminAction::SetFeatures)
+        );
+        assert_eq!(
+            newest.params_hash,
+            hashv(&[&[(AdminLog::CAPACITY + 2) as u8]]).to_bytes()
+        );
+        assert_eq!(log.iter().last().unwrap().slot, 3);
+    }
+
+    #[test]
+    fn action_codes_round_trip() {
+        for action in AdminAction::ALL {
+            assert_eq!(AdminAction::from_code(action as u8), Some(action));
+        }
+        assert_eq!(AdminAction::from_code(0), None);
+    }
+}
@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+/// An address whose redeems pay no withdrawal fee, such as the protocol's
+/// own router or a migration contract.
+///
+/// A PDA (`[b"fee_exemption", address]`) the protocol owner creates with
+/// `add_fee_exemption` and closes with `remove_fee_exemption`. `redeem` only
+/// checks that the redeemer's exemption exists.
+#[account]
+#[derive(InitSpace)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeeExemption {
+    /// The exempt redeemer
+    #[cfg_attr(feature = "serde", serde(with = "crate::state::pubkey_serde"))]
+    pub address: Pubkey,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl FeeExemption {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
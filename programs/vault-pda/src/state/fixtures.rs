@@ -0,0 +1,59 @@
+//! Accounts the instruction tests start from.
+//!
+//! Every key is unique and every counter zero; tests override the fields
+//! they exercise with struct update syntax.
+
+use anchor_lang::prelude::*;
+
+use super::{Flag, Player, ProtocolState, Vault};
+
+pub fn protocol_state() -> ProtocolState {
+    ProtocolState {
+        owner: Pubkey::new_unique(),
+        upgrade_authority: None,
+        renounce_upgrades_expected: false,
+        upgrade_authority_synced_slot: None,
+        version: [0, 1, 0],
+        features: ProtocolState::DEFAULT_FEATURES,
+        bump: 255,
+    }
+}
+
+pub fn vault() -> Vault {
+    Vault {
+        share_mint: Pubkey::new_unique(),
+        underlying_mint: Pubkey::new_unique(),
+        vault_token_account: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        name: [0; 32],
+        sunset: false,
+        bump: 255,
+    }
+}
+
+/// An uncaptured flag
+pub fn flag() -> Flag {
+    Flag {
+        vault: Pubkey::new_unique(),
+        guardian: Pubkey::new_unique(),
+        captor: Pubkey::default(),
+        captured_slot: 0,
+        condition: None,
+        starting_assets: 0,
+        deposited: 0,
+        withdrawn: 0,
+        bump: 255,
+    }
+}
+
+/// A player with no flows yet
+pub fn player() -> Player {
+    Player {
+        flag: Pubkey::new_unique(),
+        player: Pubkey::new_unique(),
+        deposited: 0,
+        withdrawn: 0,
+        shares: 0,
+        bump: 255,
+    }
+}
@@ -0,0 +1,181 @@
+use anchor_lang::prelude::*;
+
+use vault_math::mul_div_floor;
+
+/// How a flag was captured
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WinCondition {
+    /// The captor owns the protocol without having planted the flag
+    OwnsProtocol,
+    /// The captor withdrew more underlying from the vault than they deposited
+    /// into it, beyond anything donated to the vault
+    Profit,
+}
+
+/// A deposit or redeem through a vault, as counted by its flag
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flow {
+    /// `amount` deposited for `shares`
+    Deposit { amount: u64, shares: u64 },
+    /// `shares` redeemed for `amount`. `vault_shares` is false when they were
+    /// another vault's, which the challenge build accepts
+    Redeem {
+        shares: u64,
+        amount: u64,
+        vault_shares: bool,
+    },
+}
+
+/// The CTF win condition for one vault.
+///
+/// Planted by the protocol owner; the first player to demonstrate an exploit
+/// through `capture_flag` is recorded and the flag cannot be captured again.
+#[account]
+#[derive(InitSpace)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Flag {
+    /// The vault under attack
+    #[cfg_attr(feature = "serde", serde(with = "crate::state::pubkey_serde"))]
+    pub vault: Pubkey,
+    /// The protocol owner who planted the flag
+    #[cfg_attr(feature = "serde", serde(with = "crate::state::pubkey_serde"))]
+    pub guardian: Pubkey,
+    /// The player who captured the flag, the default pubkey until then
+    #[cfg_attr(feature = "serde", serde(with = "crate::state::pubkey_serde"))]
+    pub captor: Pubkey,
+    /// Slot of the capture
+    pub captured_slot: u64,
+    /// Condition the captor met
+    pub condition: Option<WinCondition>,
+    /// The vault's underlying balance when the flag was planted
+    pub starting_assets: u64,
+    /// Underlying deposited into the vault since the flag was planted
+    pub deposited: u64,
+    /// Underlying paid out by the vault since the flag was planted
+    pub withdrawn: u64,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl Flag {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn is_captured(&self) -> bool {
+        self.condition.is_some()
+    }
+
+    /// Underlying that reached the vault without a deposit since the flag was
+    /// planted, given its balance now
+    ///
+    /// Anyone can transfer tokens into the vault, so a `Profit` only counts
+    /// beyond this: a player cannot win by donating and redeeming it back.
+    pub fn donations(&self, vault_assets: u64) -> u64 {
+        (vault_assets as u128 + self.withdrawn as u128)
+            .saturating_sub(self.deposited as u128 + self.starting_assets as u128)
+            .try_into()
+            .unwrap_or(u64::MAX)
+    }
+
+    /// Counts `flow` into the vault's totals
+    pub fn record(&mut self, flow: Flow) {
+        match flow {
+            Flow::Deposit { amount, .. } => self.deposited = self.deposited.saturating_add(amount),
+            Flow::Redeem { amount, .. } => self.withdrawn = self.withdrawn.saturating_add(amount),
+        }
+    }
+}
+
+/// A player's entry for one flag, recording their flows through its vault
+/// since registering
+#[account]
+#[derive(InitSpace)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Player {
+    /// The flag the player competes for
+    #[cfg_attr(feature = "serde", serde(with = "crate::state::pubkey_serde"))]
+    pub flag: Pubkey,
+    /// The player's wallet
+    #[cfg_attr(feature = "serde", serde(with = "crate::state::pubkey_serde"))]
+    pub player: Pubkey,
+    /// Underlying the player deposited
+    pub deposited: u64,
+    /// Underlying the player's redeems paid out, for shares they minted
+    pub withdrawn: u64,
+    /// Shares the player's deposits minted and they have not redeemed
+    pub shares: u64,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl Player {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// Underlying withdrawn beyond what the player deposited and `donations`
+    pub fn profit(&self, donations: u64) -> u64 {
+        self.withdrawn
+            .saturating_sub(self.deposited)
+            .saturating_sub(donations)
+    }
+
+    /// Counts `flow` against the player
+    ///
+    /// Only redeems of shares the player minted count: shares are
+    /// transferable, so redeeming ones bought or sent from another wallet is
+    /// no profit. Another vault's shares have no claim on this one, so
+    /// everything they paid out counts.
+    pub fn record(&mut self, flow: Flow) {
+        match flow {
+            Flow::Deposit { amount, shares } => {
+                self.deposited = self.deposited.saturating_add(amount);
+                self.shares = self.shares.saturating_add(shares);
+            }
+            Flow::Redeem {
+                amount,
+                vault_shares: false,
+                ..
+            } => self.withdrawn = self.withdrawn.saturating_add(amount),
+            Flow::Redeem {
+                shares,
+                amount,
+                vault_shares: true,
+            } => {
+                let counted = shares.min(self.shares);
+                // At most `amount`, so it fits
+                let paid = mul_div_floor(amount as u128, counted as u128, shares as u128)
+                    .map_or(0, |paid| paid as u64);
+                self.shares -= counted;
+                self.withdrawn = self.withdrawn.saturating_add(paid);
+            }
+        }
+    }
+}
+
+/// Counts `flow` into `flag` and `player_record` once they hold a planted
+/// flag and a registered player
+///
+/// Both are required at their PDAs by every deposit and redeem, so no flow
+/// through a flagged vault goes uncounted; before the flag is planted, or for
+/// a depositor who never registered, they are still empty and skipped.
+pub fn record_flow(flag: &AccountInfo, player_record: &AccountInfo, flow: Flow) -> Result<()> {
+    if flag.owner != &crate::ID {
+        return Ok(());
+    }
+    update::<Flag>(flag, |flag| flag.record(flow))?;
+
+    if player_record.owner == &crate::ID {
+        update::<Player>(player_record, |player| player.record(flow))?;
+    }
+
+    Ok(())
+}
+
+fn update<T: AccountSerialize + AccountDeserialize>(
+    account: &AccountInfo,
+    change: impl FnOnce(&mut T),
+) -> Result<()> {
+    let mut data = account.try_borrow_mut_data()?;
+    let mut state = T::try_deserialize(&mut &data[..])?;
+    change(&mut state);
+    state.try_serialize(&mut &mut data[..])
+}
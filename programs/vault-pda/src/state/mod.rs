@@ -1,7 +1,165 @@
+pub mod activity_stats;
+pub mod admin_log;
+pub mod fee_exemption;
+#[cfg(test)]
+pub mod fixtures;
+pub mod flag;
+pub mod price_history;
 pub mod protocol_state;
+#[cfg(feature = "serde")]
+pub mod pubkey_serde;
+pub mod reserve_config;
 pub mod vault;
 pub mod vault_authority;
+pub mod vault_registry;
 
+pub use activity_stats::*;
+pub use admin_log::*;
+pub use fee_exemption::*;
+pub use flag::*;
+pub use price_history::*;
 pub use protocol_state::*;
+pub use reserve_config::*;
 pub use vault::*;
 pub use vault_authority::*;
+pub use vault_registry::*;
+
+/// Every Borsh account, at its largest, fits the space it is allocated
+/// with. The zero-copy accounts assert their `LEN` against their layout
+/// where they are declared.
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::*;
+    use anchor_lang::AccountSerialize;
+
+    use super::*;
+
+    #[track_caller]
+    fn assert_fits<T: AccountSerialize>(account: &T, len: usize) {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        assert!(
+            data.len() <= len,
+            "serializes to {} bytes, allocated {}",
+            data.len(),
+            len
+        );
+        assert_eq!(data.len(), len, "allocates space no field uses");
+    }
+
+    fn key() -> Pubkey {
+        Pubkey::new_unique()
+    }
+
+    #[test]
+    fn largest_accounts_fit_their_space() {
+        assert_fits(
+            &ProtocolState {
+                owner: key(),
+                upgrade_authority: Some(key()),
+                renounce_upgrades_expected: true,
+                upgrade_authority_synced_slot: Some(u64::MAX),
+                version: [u8::MAX; 3],
+                features: u8::MAX,
+                bump: u8::MAX,
+            },
+            ProtocolState::LEN,
+        );
+        assert_fits(&VaultAuthority { bump: u8::MAX }, VaultAuthority::LEN);
+        assert_fits(
+            &Vault {
+                share_mint: key(),
+                underlying_mint: key(),
+                vault_token_account: key(),
+                creator: key(),
+                name: [u8::MAX; 32],
+                sunset: true,
+                bump: u8::MAX,
+            },
+            Vault::LEN,
+        );
+        assert_fits(
+            &Flag {
+                vault: key(),
+                guardian: key(),
+                captor: key(),
+                captured_slot: u64::MAX,
+                condition: Some(WinCondition::Profit),
+                starting_assets: u64::MAX,
+                deposited: u64::MAX,
+                withdrawn: u64::MAX,
+                bump: u8::MAX,
+            },
+            Flag::LEN,
+        );
+        assert_fits(
+            &Player {
+                flag: key(),
+                player: key(),
+                deposited: u64::MAX,
+                withdrawn: u64::MAX,
+                shares: u64::MAX,
+                bump: u8::MAX,
+            },
+            Player::LEN,
+        );
+        assert_fits(
+            &ReserveConfig {
+                vault: key(),
+                treasury: key(),
+                reserve_factor_bps: u16::MAX,
+                withdrawal_fee_bps: u16::MAX,
+                mark_assets: u64::MAX,
+                mark_shares: u64::MAX,
+                bump: u8::MAX,
+            },
+            ReserveConfig::LEN,
+        );
+        assert_fits(
+            &FeeExemption {
+                address: key(),
+                bump: u8::MAX,
+            },
+            FeeExemption::LEN,
+        );
+    }
+
+    #[test]
+    fn vault_memcmp_offsets_hold() {
+        let vault = Vault {
+            share_mint: key(),
+            underlying_mint: key(),
+            vault_token_account: key(),
+            creator: key(),
+            name: [0; 32],
+            sunset: false,
+            bump: 255,
+        };
+        let mut data = Vec::new();
+        vault.try_serialize(&mut data).unwrap();
+
+        let field = |offset: usize| &data[offset..offset + 32];
+        assert_eq!(
+            field(Vault::UNDERLYING_MINT_OFFSET),
+            vault.underlying_mint.as_ref()
+        );
+        assert_eq!(field(Vault::CREATOR_OFFSET), vault.creator.as_ref());
+    }
+
+    #[test]
+    fn documented_sizes_hold() {
+        // Pinned so a layout change is a deliberate one, with the README and
+        // golden fixtures updated alongside
+        assert_eq!(ProtocolState::LEN, 88);
+        assert_eq!(VaultAuthority::LEN, 9);
+        assert_eq!(Vault::LEN, 170);
+        assert_eq!(Flag::LEN, 139);
+        assert_eq!(Player::LEN, 97);
+        assert_eq!(ReserveConfig::LEN, 93);
+        assert_eq!(FeeExemption::LEN, 41);
+        assert_eq!(VaultRegistry::LEN, 9);
+        assert_eq!(PriceHistory::LEN, 24_632);
+        assert_eq!(ActivityStats::LEN, 6_216);
+        assert_eq!(AdminLog::LEN, 8_032);
+    }
+}
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+/// A single share price observation
+#[zero_copy]
+#[repr(C)]
+pub struct PricePoint {
+    /// Slot at which the observation was recorded
+    pub slot: u64,
+    /// Underlying assets held by the vault
+    pub total_assets: u64,
+    /// Outstanding share supply
+    pub total_shares: u64,
+}
+
+/// Fixed-size ring buffer of share price observations for a single vault.
+///
+/// Stored as a zero-copy account so recording an observation does not pay
+/// Borsh (de)serialization costs for the whole buffer. The account is larger
+/// than the 10KB CPI allocation limit, so it is created by the client with
+/// `create_account` and then initialized via `initialize_price_history`.
+#[account(zero_copy)]
+#[repr(C)]
+pub struct PriceHistory {
+    /// The vault this history belongs to
+    pub vault: Pubkey,
+    /// Index of the next slot to write
+    pub head: u64,
+    /// Number of populated entries, saturates at `CAPACITY`
+    pub len: u64,
+    /// Observations, oldest entries are overwritten once full
+    pub points: [PricePoint; PriceHistory::CAPACITY],
+}
+
+const _: () = assert!(PriceHistory::LEN == 8 + std::mem::size_of::<PriceHistory>());
+
+impl PriceHistory {
+    pub const CAPACITY: usize = 1024;
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // vault
+        8 + // head
+        8 + // len
+        24 * PriceHistory::CAPACITY; // points
+
+    /// Records an observation, overwriting the oldest one when full
+    pub fn push(&mut self, point: PricePoint) {
+        self.points[self.head as usize] = point;
+        self.head = (self.head + 1) % Self::CAPACITY as u64;
+        if (self.len as usize) < Self::CAPACITY {
+            self.len += 1;
+        }
+    }
+
+    /// Returns the most recent observation, if any
+    pub fn latest(&self) -> Option<&PricePoint> {
+        if self.len == 0 {
+            return None;
+        }
+        let index = (self.head as usize + Self::CAPACITY - 1) % Self::CAPACITY;
+        Some(&self.points[index])
+    }
+}
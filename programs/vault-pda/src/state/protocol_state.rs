@@ -1,15 +1,54 @@
 use anchor_lang::prelude::*;
 
 #[account]
+#[derive(InitSpace)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ProtocolState {
     /// The protocol owner who can perform administrative actions
+    #[cfg_attr(feature = "serde", serde(with = "crate::state::pubkey_serde"))]
     pub owner: Pubkey,
+    /// Upgrade authority the program's `ProgramData` held at the last
+    /// `sync_upgrade_authority`, `None` if the program is immutable
+    #[cfg_attr(feature = "serde", serde(with = "crate::state::pubkey_serde::option"))]
+    pub upgrade_authority: Option<Pubkey>,
+    /// Whether the owner claims the program can no longer be upgraded
+    pub renounce_upgrades_expected: bool,
+    /// Slot of the last `sync_upgrade_authority`, `None` if never synced
+    pub upgrade_authority_synced_slot: Option<u64>,
+    /// `[major, minor, patch]` of the program that initialized the protocol
+    /// or last ran `set_features`
+    pub version: [u8; 3],
+    /// Bitmap of the `ProtocolState::*` feature bits the owner enabled
+    pub features: u8,
     /// Bump seed for PDA derivation
     pub bump: u8,
 }
 
 impl ProtocolState {
-    pub const LEN: usize = 8 + // discriminator
-        32 + // owner
-        1; // bump
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// Size of the account before the upgrade authority, version and
+    /// feature fields were added: discriminator, `owner` and `bump`.
+    /// `migrate_protocol` grows such accounts to `LEN`
+    pub const LEGACY_LEN: usize = 8 + 32 + 1;
+
+    /// Profit reports may mint the treasury its reserve cut
+    pub const FEES_ENABLED: u8 = 1 << 0;
+    /// Reserved for yield strategies; no instruction deploys vault assets
+    /// yet, so only clients read it
+    pub const STRATEGIES_ENABLED: u8 = 1 << 1;
+    /// Vaults may be created over Token-2022 mints
+    pub const TOKEN_2022_ALLOWED: u8 = 1 << 2;
+
+    /// Every feature bit this program knows
+    pub const ALL_FEATURES: u8 =
+        Self::FEES_ENABLED | Self::STRATEGIES_ENABLED | Self::TOKEN_2022_ALLOWED;
+    /// What `initialize` enables: everything the program did before the
+    /// bitmap existed
+    pub const DEFAULT_FEATURES: u8 = Self::FEES_ENABLED | Self::TOKEN_2022_ALLOWED;
+
+    /// Whether every bit of `feature` is enabled
+    pub fn has_feature(&self, feature: u8) -> bool {
+        self.features & feature == feature
+    }
 }
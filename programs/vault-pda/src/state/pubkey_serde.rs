@@ -0,0 +1,39 @@
+//! Serde helpers that encode `Pubkey` fields as base58 strings, so JSON dumps
+//! of state accounts match what explorers and the Solana CLI print.
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(pubkey)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    Pubkey::from_str(&value).map_err(D::Error::custom)
+}
+
+/// The same encoding for `Option<Pubkey>` fields, with `None` as `null`
+pub mod option {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        pubkey: &Option<Pubkey>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match pubkey {
+            Some(pubkey) => super::serialize(pubkey, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Pubkey>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| Pubkey::from_str(&value).map_err(D::Error::custom))
+            .transpose()
+    }
+}
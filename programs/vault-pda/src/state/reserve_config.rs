@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+/// A vault's reserve factor: the cut of its profit minted to a treasury as
+/// shares.
+///
+/// Profit is measured against a high-water mark, the vault's totals at the
+/// last `report`, so the treasury is only paid for price gains once, and not
+/// at all until a drop below the mark is recovered. The same treasury takes
+/// the vault's withdrawal fee, a cut of every redeem's shares, from redeemers
+/// without a `FeeExemption`.
+#[account]
+#[derive(InitSpace)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReserveConfig {
+    /// The vault whose profit is split
+    #[cfg_attr(feature = "serde", serde(with = "crate::state::pubkey_serde"))]
+    pub vault: Pubkey,
+    /// Share token account the treasury's cut is minted to
+    #[cfg_attr(feature = "serde", serde(with = "crate::state::pubkey_serde"))]
+    pub treasury: Pubkey,
+    /// Basis points of every reported profit that go to the treasury
+    pub reserve_factor_bps: u16,
+    /// Basis points of every redeem's shares that go to the treasury, at
+    /// most `MAX_WITHDRAWAL_FEE_BPS`
+    pub withdrawal_fee_bps: u16,
+    /// Vault assets at the high-water mark
+    pub mark_assets: u64,
+    /// Share supply at the high-water mark, zero while unset
+    pub mark_shares: u64,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl ReserveConfig {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
@@ -1,21 +1,68 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::MAX_VAULT_NAME_LEN;
+
 #[account]
+#[derive(InitSpace)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vault {
     /// The mint account for shares tokens (minted on deposits, burned on redeems)
+    #[cfg_attr(feature = "serde", serde(with = "crate::state::pubkey_serde"))]
     pub share_mint: Pubkey,
     /// The mint account for the underlying asset held by the vault
+    #[cfg_attr(feature = "serde", serde(with = "crate::state::pubkey_serde"))]
     pub underlying_mint: Pubkey,
     /// The token account that holds the underlying assets
+    #[cfg_attr(feature = "serde", serde(with = "crate::state::pubkey_serde"))]
     pub vault_token_account: Pubkey,
+    /// Who paid for `initialize_vault`
+    #[cfg_attr(feature = "serde", serde(with = "crate::state::pubkey_serde"))]
+    pub creator: Pubkey,
+    /// Vault name, zero-padded UTF-8. Set at initialization and by
+    /// `set_vault_name`, for display only
+    pub name: [u8; MAX_VAULT_NAME_LEN],
+    /// Set by `sunset_vault`: deposits are closed for good, redeems stay open
+    pub sunset: bool,
     /// Bump seed for PDA derivation
     pub bump: u8,
 }
 
 impl Vault {
-    pub const LEN: usize = 8 + // discriminator
-        32 + // share_mint
-        32 + // underlying_mint
-        32 + // vault_token_account
-        1; // bump
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// Size of the account before `creator`, `name` and `sunset` were
+    /// added: discriminator, the three keys and `bump`. `migrate_vault`
+    /// grows such accounts to `LEN`
+    pub const LEGACY_LEN: usize = 8 + 32 * 3 + 1;
+
+    /// Offset of `underlying_mint` in the account data, for
+    /// `getProgramAccounts` memcmp filters. Only fixed-size fields come
+    /// before it, so it does not move
+    pub const UNDERLYING_MINT_OFFSET: usize = 8 + 32;
+
+    /// Offset of `creator` in the account data, for memcmp filters
+    pub const CREATOR_OFFSET: usize = 8 + 32 * 3;
+
+    /// Pads `name` for the `name` field, or `None` if it is too long or
+    /// holds a NUL byte, which the padding would make ambiguous
+    pub fn encode_name(name: &str) -> Option<[u8; MAX_VAULT_NAME_LEN]> {
+        if name.contains('\0') {
+            return None;
+        }
+        let mut padded = [0u8; MAX_VAULT_NAME_LEN];
+        padded
+            .get_mut(..name.len())?
+            .copy_from_slice(name.as_bytes());
+        Some(padded)
+    }
+
+    /// The name without its padding
+    pub fn name(&self) -> String {
+        let len = self
+            .name
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .map_or(0, |last| last + 1);
+        String::from_utf8_lossy(&self.name[..len]).into_owned()
+    }
 }
@@ -1,12 +1,50 @@
+use std::slice;
+
 use anchor_lang::prelude::*;
 
+use crate::constants::VULN_GLOBAL_AUTHORITY;
+use crate::state::Vault;
+
 #[account]
+#[derive(InitSpace)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VaultAuthority {
     /// Bump seed for PDA derivation
     pub bump: u8,
 }
 
 impl VaultAuthority {
-    pub const LEN: usize = 8 + // discriminator
-        1; // bump
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// Address that owns `vault`'s token account and mints its shares: the global
+/// `vault_authority` PDA in the challenge build, the vault itself otherwise
+pub fn token_authority(vault: &Pubkey) -> Pubkey {
+    if VULN_GLOBAL_AUTHORITY {
+        Pubkey::find_program_address(&[b"vault_authority"], &crate::ID).0
+    } else {
+        *vault
+    }
+}
+
+/// The [`token_authority`] account and the seeds the program signs as it
+/// with, for CPIs that move `vault`'s underlying or mint and burn its shares
+pub fn vault_signer_seeds<'a, 'info>(
+    vault: &'a Account<'info, Vault>,
+    vault_authority: &'a Account<'info, VaultAuthority>,
+) -> (AccountInfo<'info>, Vec<&'a [u8]>) {
+    if VULN_GLOBAL_AUTHORITY {
+        let seeds = vec![
+            b"vault_authority".as_ref(),
+            slice::from_ref(&vault_authority.bump),
+        ];
+        (vault_authority.to_account_info(), seeds)
+    } else {
+        let seeds = vec![
+            b"vault".as_ref(),
+            vault.underlying_mint.as_ref(),
+            slice::from_ref(&vault.bump),
+        ];
+        (vault.to_account_info(), seeds)
+    }
 }
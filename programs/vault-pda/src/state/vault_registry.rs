@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+/// Append-only list of every vault created by the protocol.
+///
+/// A single PDA (`[b"vault_registry"]`) that `initialize` creates empty.
+/// The vaults are not a field: they trail the header as raw keys, and
+/// `initialize_vault` grows the account by one key, paid for by the vault's
+/// payer, and appends the new vault; `migrate_vault` does the same for the
+/// vaults of a deployment that predates the registry. Nothing else writes
+/// it, so it has no capacity to fill and lists exactly the vaults the
+/// program created.
+#[account(zero_copy)]
+#[repr(C)]
+pub struct VaultRegistry {
+    pub bump: u8,
+}
+
+const _: () = assert!(VaultRegistry::LEN == 8 + std::mem::size_of::<VaultRegistry>());
+
+impl VaultRegistry {
+    /// Size of the empty registry; each vault adds `ENTRY_LEN` bytes
+    pub const LEN: usize = 8 + // discriminator
+        1; // bump
+
+    pub const ENTRY_LEN: usize = 32;
+
+    /// The registered vaults in the registry's account `data`
+    pub fn vaults(data: &[u8]) -> &[Pubkey] {
+        let tail = data.get(Self::LEN..).unwrap_or_default();
+        bytemuck::cast_slice(&tail[..tail.len() / Self::ENTRY_LEN * Self::ENTRY_LEN])
+    }
+
+    pub fn contains(data: &[u8], vault: &Pubkey) -> bool {
+        Self::vaults(data).contains(vault)
+    }
+
+    /// Writes `vault` into the last entry of `data`, which the caller has
+    /// just grown by `ENTRY_LEN` bytes
+    pub fn push(data: &mut [u8], vault: &Pubkey) {
+        let start = data.len() - Self::ENTRY_LEN;
+        data[start..].copy_from_slice(vault.as_ref());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vaults_trail_the_header() {
+        let mut data = vec![0; VaultRegistry::LEN];
+        assert!(VaultRegistry::vaults(&data).is_empty());
+
+        let vaults = [Pubkey::new_unique(), Pubkey::new_unique()];
+        for vault in &vaults {
+            data.resize(data.len() + VaultRegistry::ENTRY_LEN, 0);
+            VaultRegistry::push(&mut data, vault);
+        }
+
+        assert_eq!(VaultRegistry::vaults(&data), &vaults);
+        assert!(VaultRegistry::contains(&data, &vaults[1]));
+        assert!(!VaultRegistry::contains(&data, &Pubkey::new_unique()));
+    }
+}
@@ -0,0 +1,77 @@
+//! Runs a scripted sequence of vault operations against a validator.
+//!
+//! ```bash
+//! scenario-runner <script.toml|script.json> [--url <rpc>] [--keypair <path>]
+//! ```
+//!
+//! The vault must already be deployed (`anchor localnet`, or
+//! `solana-test-validator` with `--bpf-program`). The payer is `--keypair`,
+//! else the Solana CLI keypair, else a fresh one funded by airdrop. Balances
+//! and share prices are printed after every step and summarized at the end.
+
+use std::process::ExitCode;
+
+use solana_sdk::signature::{read_keypair_file, Keypair};
+
+mod runner;
+mod script;
+
+use runner::Runner;
+use script::Script;
+
+const USAGE: &str =
+    "usage: scenario-runner <script.toml|script.json> [--url <rpc>] [--keypair <path>]";
+
+const DEFAULT_URL: &str = "http://127.0.0.1:8899";
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("scenario-runner failed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut path = None;
+    let mut url = None;
+    let mut keypair = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--url" => url = Some(args.next().ok_or(USAGE)?),
+            "--keypair" => keypair = Some(args.next().ok_or(USAGE)?),
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => return Err(USAGE.into()),
+        }
+    }
+    let path = path.ok_or(USAGE)?;
+
+    let script = Script::parse(&path, &std::fs::read_to_string(&path)?)?;
+    let url = url
+        .or(script.rpc_url.clone())
+        .unwrap_or_else(|| DEFAULT_URL.to_string());
+    let payer = match keypair {
+        Some(keypair) => read_keypair_file(&keypair)?,
+        None => default_keypair(),
+    };
+
+    let mut runner = Runner::new(&url, payer)?;
+    for (index, step) in script.steps.iter().enumerate() {
+        runner
+            .run(step)
+            .map_err(|e| format!("step {} ({step:?}): {e}", index + 1))?;
+    }
+    runner.print_summary()
+}
+
+/// The Solana CLI keypair if there is one
+fn default_keypair() -> Keypair {
+    std::env::var("HOME")
+        .ok()
+        .and_then(|home| read_keypair_file(format!("{home}/.config/solana/id.json")).ok())
+        .unwrap_or_else(Keypair::new)
+}
@@ -0,0 +1,294 @@
+//! Executes scripts against a validator over RPC.
+//!
+//! Unlike the fuzz backends, nothing is seeded: mints and token accounts are
+//! created with real SPL Token instructions, so the runner works on any
+//! cluster the vault is deployed to. The payer signs every transaction, owns
+//! the protocol and is the mint authority of every underlying mint.
+
+use std::collections::HashMap;
+
+use fuzz_helpers::{
+    deposit_ix, derive_share_mint_pda, derive_vault_pda, derive_vault_token_account_pda,
+    initialize_ix, initialize_vault_ix, redeem_ix, FuzzResult, UserAccounts, VaultAccounts,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+use vault_math::compute_assets;
+use vault_pda_client::blocking::{send, top_up, Sent};
+
+use crate::script::Step;
+
+pub struct Runner {
+    rpc: RpcClient,
+    payer: Keypair,
+    program_id: Pubkey,
+    /// Vaults by script name
+    vaults: HashMap<String, VaultAccounts>,
+    /// Users by script name
+    users: HashMap<String, Keypair>,
+    /// Token accounts by vault and user name
+    positions: HashMap<(String, String), UserAccounts>,
+}
+
+impl Runner {
+    /// Connects to `url`, checks the vault is deployed and tops up `payer`
+    pub fn new(url: &str, payer: Keypair) -> FuzzResult<Self> {
+        let rpc = RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed());
+        let program_id = vault_pda::id();
+
+        let deployed = rpc
+            .get_account_with_commitment(&program_id, rpc.commitment())?
+            .value
+            .is_some_and(|account| account.executable);
+        if !deployed {
+            return Err(format!("vault-pda is not deployed at {program_id} on {url}").into());
+        }
+
+        top_up(&rpc, &payer.pubkey())?;
+
+        Ok(Self {
+            rpc,
+            payer,
+            program_id,
+            vaults: HashMap::new(),
+            users: HashMap::new(),
+            positions: HashMap::new(),
+        })
+    }
+
+    pub fn run(&mut self, step: &Step) -> FuzzResult<()> {
+        match step {
+            Step::Initialize => {
+                let ix = initialize_ix(&self.program_id, &self.payer.pubkey());
+                self.send(&[ix], &[])?;
+                println!("initialize: owner {}", self.payer.pubkey());
+            }
+            Step::CreateVault { vault, decimals } => {
+                if self.vaults.contains_key(vault) {
+                    return Err(format!("vault {vault} already exists").into());
+                }
+                let mint = self.create_mint(*decimals)?;
+                let ix = initialize_vault_ix(&self.program_id, &mint, &self.payer.pubkey());
+                self.send(&[ix], &[])?;
+
+                let address = derive_vault_pda(&self.program_id, &mint).0;
+                let accounts = VaultAccounts {
+                    vault: address,
+                    vault_token_account: derive_vault_token_account_pda(&self.program_id, &address)
+                        .0,
+                    share_mint: derive_share_mint_pda(&self.program_id, &address).0,
+                    underlying_mint: mint,
+                    token_program: spl_token::id(),
+                };
+                self.vaults.insert(vault.clone(), accounts);
+                println!("create_vault {vault}: {address}, underlying mint {mint}");
+            }
+            Step::Deposit {
+                vault,
+                user,
+                amount,
+                count,
+            } => {
+                let accounts = self.vault(vault)?.clone();
+                let position = self.position(vault, user)?;
+                for _ in 0..*count {
+                    let mint = spl_token::instruction::mint_to(
+                        &spl_token::id(),
+                        &accounts.underlying_mint,
+                        &position.underlying_token_account,
+                        &self.payer.pubkey(),
+                        &[],
+                        *amount,
+                    )?;
+                    let deposit = deposit_ix(&self.program_id, &accounts, &position, *amount);
+                    self.send(&[mint, deposit], &[&position.owner])?;
+                }
+                let shares = self.token_balance(&position.share_token_account)?;
+                println!("deposit {vault} {user}: {amount} x{count}, holds {shares} shares");
+            }
+            Step::Yield { vault, amount } => {
+                let accounts = self.vault(vault)?.clone();
+                let ix = spl_token::instruction::mint_to(
+                    &spl_token::id(),
+                    &accounts.underlying_mint,
+                    &accounts.vault_token_account,
+                    &self.payer.pubkey(),
+                    &[],
+                    *amount,
+                )?;
+                self.send(&[ix], &[])?;
+                println!("yield {vault}: {amount}");
+            }
+            Step::Redeem {
+                vault,
+                user,
+                shares,
+            } => {
+                let accounts = self.vault(vault)?.clone();
+                let position = self.position(vault, user)?;
+                let shares = match shares {
+                    Some(shares) => *shares,
+                    None => self.token_balance(&position.share_token_account)?,
+                };
+                let before = self.token_balance(&position.underlying_token_account)?;
+                let ix = redeem_ix(&self.program_id, &accounts, &position, shares);
+                self.send(&[ix], &[&position.owner])?;
+                let assets = self.token_balance(&position.underlying_token_account)? - before;
+                println!("redeem {vault} {user}: {shares} shares for {assets}");
+            }
+        }
+
+        if let Some(vault) = step_vault(step) {
+            self.print_vault(vault)?;
+        }
+        Ok(())
+    }
+
+    /// Prints every vault and every user's position
+    pub fn print_summary(&self) -> FuzzResult<()> {
+        println!("\n=== Summary ===");
+        let mut names: Vec<_> = self.vaults.keys().collect();
+        names.sort();
+        for vault in names {
+            self.print_vault(vault)?;
+        }
+
+        let mut positions: Vec<_> = self.positions.iter().collect();
+        positions.sort_by(|a, b| a.0.cmp(b.0));
+        for ((vault, user), position) in positions {
+            let underlying = self.token_balance(&position.underlying_token_account)?;
+            let shares = self.token_balance(&position.share_token_account)?;
+            let value = self.share_value(&self.vaults[vault], shares)?;
+            println!("  {user} in {vault}: {underlying} underlying, {shares} shares worth {value}");
+        }
+        Ok(())
+    }
+
+    fn print_vault(&self, vault: &str) -> FuzzResult<()> {
+        let accounts = self.vault(vault)?;
+        let assets = self.token_balance(&accounts.vault_token_account)?;
+        let supply = self.mint_supply(&accounts.share_mint)?;
+        let price = if supply == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.6}", assets as f64 / supply as f64)
+        };
+        println!("  vault {vault}: {assets} underlying, {supply} shares, share price {price}");
+        Ok(())
+    }
+
+    fn vault(&self, vault: &str) -> FuzzResult<&VaultAccounts> {
+        self.vaults
+            .get(vault)
+            .ok_or_else(|| format!("unknown vault {vault}, add a create_vault step").into())
+    }
+
+    /// `user`'s token accounts for `vault`, created on first use
+    fn position(&mut self, vault: &str, user: &str) -> FuzzResult<UserAccounts> {
+        let key = (vault.to_string(), user.to_string());
+        if let Some(position) = self.positions.get(&key) {
+            return Ok(position.clone());
+        }
+
+        let accounts = self.vault(vault)?.clone();
+        let owner = self
+            .users
+            .entry(user.to_string())
+            .or_insert_with(Keypair::new)
+            .pubkey();
+        let position = UserAccounts {
+            underlying_token_account: self
+                .create_token_account(&accounts.underlying_mint, &owner)?,
+            share_token_account: self.create_token_account(&accounts.share_mint, &owner)?,
+            owner: self.users[user].insecure_clone(),
+        };
+        self.positions.insert(key, position.clone());
+        Ok(position)
+    }
+
+    fn create_mint(&self, decimals: u8) -> FuzzResult<Pubkey> {
+        let mint = Keypair::new();
+        let create = system_instruction::create_account(
+            &self.payer.pubkey(),
+            &mint.pubkey(),
+            self.rpc.get_minimum_balance_for_rent_exemption(Mint::LEN)?,
+            Mint::LEN as u64,
+            &spl_token::id(),
+        );
+        let init = spl_token::instruction::initialize_mint2(
+            &spl_token::id(),
+            &mint.pubkey(),
+            &self.payer.pubkey(),
+            None,
+            decimals,
+        )?;
+        self.send(&[create, init], &[&mint])?;
+        Ok(mint.pubkey())
+    }
+
+    fn create_token_account(&self, mint: &Pubkey, owner: &Pubkey) -> FuzzResult<Pubkey> {
+        let account = Keypair::new();
+        let create = system_instruction::create_account(
+            &self.payer.pubkey(),
+            &account.pubkey(),
+            self.rpc
+                .get_minimum_balance_for_rent_exemption(TokenAccount::LEN)?,
+            TokenAccount::LEN as u64,
+            &spl_token::id(),
+        );
+        let init = spl_token::instruction::initialize_account3(
+            &spl_token::id(),
+            &account.pubkey(),
+            mint,
+            owner,
+        )?;
+        self.send(&[create, init], &[&account])?;
+        Ok(account.pubkey())
+    }
+
+    /// Sends `instructions` paid for and signed by the payer plus `signers`
+    fn send(&self, instructions: &[Instruction], signers: &[&Keypair]) -> FuzzResult<()> {
+        match send(&self.rpc, &self.payer, instructions, signers)? {
+            Sent::Confirmed(_) => Ok(()),
+            Sent::Rejected(signature, error) => {
+                Err(format!("transaction {signature} was rejected: {error}").into())
+            }
+        }
+    }
+
+    fn token_balance(&self, address: &Pubkey) -> FuzzResult<u64> {
+        let data = self.rpc.get_account_data(address)?;
+        Ok(TokenAccount::unpack(&data)?.amount)
+    }
+
+    fn mint_supply(&self, mint: &Pubkey) -> FuzzResult<u64> {
+        let data = self.rpc.get_account_data(mint)?;
+        Ok(Mint::unpack(&data)?.supply)
+    }
+
+    /// Underlying `shares` would redeem for at the current price
+    fn share_value(&self, vault: &VaultAccounts, shares: u64) -> FuzzResult<u64> {
+        let assets = self.token_balance(&vault.vault_token_account)?;
+        let supply = self.mint_supply(&vault.share_mint)?;
+        Ok(compute_assets(shares, supply, assets).unwrap_or(0))
+    }
+}
+
+/// The vault `step` changes, if any
+fn step_vault(step: &Step) -> Option<&str> {
+    match step {
+        Step::Initialize => None,
+        Step::CreateVault { vault, .. }
+        | Step::Deposit { vault, .. }
+        | Step::Yield { vault, .. }
+        | Step::Redeem { vault, .. } => Some(vault),
+    }
+}
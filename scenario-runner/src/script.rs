@@ -0,0 +1,121 @@
+//! Scenario scripts.
+//!
+//! A script is a list of steps, as TOML or JSON:
+//!
+//! ```toml
+//! [[step]]
+//! op = "initialize"
+//!
+//! [[step]]
+//! op = "create_vault"
+//! vault = "usdc"
+//! decimals = 6
+//!
+//! [[step]]
+//! op = "deposit"
+//! vault = "usdc"
+//! user = "alice"
+//! amount = 1_000_000
+//! count = 3
+//! ```
+//!
+//! Vaults and users are named by the script. A user is created the first
+//! time a step names them, and `deposit` mints the underlying it needs, so a
+//! script only states the operations it is about.
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Script {
+    /// RPC endpoint, unless `--url` overrides it
+    #[serde(default)]
+    pub rpc_url: Option<String>,
+    #[serde(rename = "step", alias = "steps")]
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Step {
+    /// Initializes the protocol with the payer as its owner
+    Initialize,
+    /// Creates an underlying mint and a vault over it
+    CreateVault {
+        vault: String,
+        #[serde(default = "default_decimals")]
+        decimals: u8,
+    },
+    /// Mints underlying to `user` and deposits it, `count` times
+    Deposit {
+        vault: String,
+        user: String,
+        amount: u64,
+        #[serde(default = "default_count")]
+        count: u32,
+    },
+    /// Mints underlying straight into the vault's token account, raising the
+    /// share price without minting shares
+    Yield { vault: String, amount: u64 },
+    /// Redeems `shares`, or every share `user` holds
+    Redeem {
+        vault: String,
+        user: String,
+        #[serde(default)]
+        shares: Option<u64>,
+    },
+}
+
+fn default_decimals() -> u8 {
+    6
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+impl Script {
+    /// Parses `source` as JSON when `path` ends in `.json`, as TOML otherwise
+    pub fn parse(path: &str, source: &str) -> Result<Self, String> {
+        if path.ends_with(".json") {
+            serde_json::from_str(source).map_err(|e| format!("{path}: {e}"))
+        } else {
+            toml::from_str(source).map_err(|e| format!("{path}: {e}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_scenarios_parse() {
+        let scenarios = [
+            ("yield.toml", include_str!("../scenarios/yield.toml")),
+            (
+                "inflation.json",
+                include_str!("../scenarios/inflation.json"),
+            ),
+        ];
+        for (path, source) in scenarios {
+            let script = Script::parse(path, source).unwrap();
+            assert!(matches!(script.steps[0], Step::Initialize), "{path}");
+        }
+    }
+
+    #[test]
+    fn defaults_and_unknown_fields() {
+        let script = Script::parse(
+            "s.toml",
+            "[[step]]\nop = \"deposit\"\nvault = \"v\"\nuser = \"u\"\namount = 5\n",
+        )
+        .unwrap();
+        assert!(matches!(script.steps[0], Step::Deposit { count: 1, .. }));
+
+        let typo = Script::parse(
+            "s.toml",
+            "[[step]]\nop = \"redeem\"\nvault = \"v\"\nuser = \"u\"\nshare = 5\n",
+        );
+        assert!(typo.is_err());
+    }
+}
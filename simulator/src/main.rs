@@ -0,0 +1,123 @@
+//! Monte Carlo simulation of the vault's share accounting.
+//!
+//! ```bash
+//! simulate [--trajectories <n>] [--steps <n>] [--users <n>] [--seed <n>]
+//!          [--deposit-fee-bps <bps>] [--redeem-fee-bps <bps>] [--deposit-cap <amount>]
+//!          [--virtual-offset <n>] [--max-deposit <amount>]
+//!          [--max-yield-bps <bps>] [--max-loss-bps <bps>]
+//! ```
+//!
+//! Each trajectory is a fresh vault put through random deposits, redeems,
+//! yield and losses by a handful of users, who all exit in full at the end.
+//! Conversions use `vault-math`, so rounding is the program's to the unit.
+//! The summary gives the distribution of shareholders' net returns, the
+//! rounding each user lost and the worst single conversion, and the underlying
+//! stranded in the vault once everyone has left: the numbers to look at when
+//! tuning fees, caps and the virtual offset.
+//!
+//! `--virtual-offset` defaults to the challenge build's (none); build with
+//! `--no-default-features` to default to the fixed program's. Runs are
+//! reproducible for a given `--seed`.
+
+use std::process::ExitCode;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+mod model;
+mod stats;
+
+use model::{run_trajectory, Params};
+use stats::Distribution;
+
+const USAGE: &str = "usage: simulate [--trajectories <n>] [--steps <n>] [--users <n>] [--seed <n>]
+                [--deposit-fee-bps <bps>] [--redeem-fee-bps <bps>] [--deposit-cap <amount>]
+                [--virtual-offset <n>] [--max-deposit <amount>]
+                [--max-yield-bps <bps>] [--max-loss-bps <bps>]";
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("simulate: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<()> {
+    let mut trajectories: u64 = 10_000;
+    let mut seed: u64 = 0;
+    let mut params = Params::default();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(USAGE);
+        match arg.as_str() {
+            "--trajectories" => trajectories = value()?.parse()?,
+            "--steps" => params.steps = value()?.parse()?,
+            "--users" => params.users = value()?.parse()?,
+            "--seed" => seed = value()?.parse()?,
+            "--deposit-fee-bps" => params.deposit_fee_bps = bps(&value()?)?,
+            "--redeem-fee-bps" => params.redeem_fee_bps = bps(&value()?)?,
+            "--deposit-cap" => params.deposit_cap = Some(value()?.parse()?),
+            "--virtual-offset" => params.virtual_offset = value()?.parse()?,
+            "--max-deposit" => params.max_deposit = value()?.parse()?,
+            "--max-yield-bps" => params.max_yield_bps = value()?.parse()?,
+            "--max-loss-bps" => params.max_loss_bps = bps(&value()?)?,
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => return Err(USAGE.into()),
+        }
+    }
+    if params.users == 0 || params.max_deposit == 0 {
+        return Err("--users and --max-deposit must be positive".into());
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut returns = Distribution::default();
+    let mut user_leakage = Distribution::default();
+    let mut worst_leakage = Distribution::default();
+    let mut residual = Distribution::default();
+    let (mut fees, mut capped, mut rejected, mut stranded) = (0u128, 0u64, 0u64, 0u64);
+
+    for _ in 0..trajectories {
+        let outcome = run_trajectory(&params, &mut rng);
+        for user in outcome.users.iter().filter(|user| user.deposited > 0) {
+            returns.push(user.net_return().unwrap_or_default() * 100.0);
+            user_leakage.push(user.leakage);
+        }
+        worst_leakage.push(outcome.worst_conversion_leakage);
+        residual.push(outcome.residual as f64);
+        fees += outcome.fees as u128;
+        capped += outcome.capped;
+        rejected += outcome.rejected;
+        stranded += outcome.stranded;
+    }
+
+    println!("{params:#?}");
+    println!("trajectories: {trajectories}, seed: {seed}");
+    println!();
+    println!("{}", Distribution::header());
+    println!("{}", returns.row("net return %"));
+    println!("{}", user_leakage.row("user leakage"));
+    println!("{}", worst_leakage.row("worst leakage"));
+    println!("{}", residual.row("residual"));
+    println!();
+    println!("fees collected:      {fees}");
+    println!("deposits over cap:   {capped}");
+    println!("kernel rejections:   {rejected}");
+    println!("stranded positions:  {stranded}");
+    Ok(())
+}
+
+fn bps(value: &str) -> Result<u16> {
+    match value.parse()? {
+        bps @ 0..=10_000 => Ok(bps),
+        bps => Err(format!("{bps} bps is more than 100%").into()),
+    }
+}
@@ -0,0 +1,346 @@
+//! One randomized trajectory through the vault's share accounting.
+//!
+//! Conversions go through `vault_math::shares_with_offset` and
+//! `assets_with_offset` with the same acceptance rules as the on-chain
+//! kernels, so a trajectory mints and pays out exactly what the program would.
+//! Fees and the deposit cap are modeled on top: the program charges neither,
+//! and they are here to be tuned before it does.
+
+use rand::rngs::StdRng;
+use rand::Rng;
+use vault_math::{assets_with_offset, mul_div_ceil, shares_with_offset};
+
+/// Basis points in one whole
+const BPS: u128 = 10_000;
+
+/// Chance in percent that a step is a deposit, a redeem or a yield event; the
+/// rest are losses
+const DEPOSIT_PERCENT: u32 = 40;
+const REDEEM_PERCENT: u32 = 25;
+const YIELD_PERCENT: u32 = 20;
+
+/// Chance in percent that a redeem burns the user's whole position
+const FULL_EXIT_PERCENT: u32 = 30;
+
+/// Knobs of a simulation run
+#[derive(Debug, Clone)]
+pub struct Params {
+    /// Shareholders per trajectory
+    pub users: usize,
+    /// Random steps before everyone exits
+    pub steps: usize,
+    /// Largest single deposit; amounts are log-uniform in `1..=max_deposit`
+    pub max_deposit: u64,
+    /// Taken out of every deposit before it is converted, rounding up
+    pub deposit_fee_bps: u16,
+    /// Taken out of every redemption after it is converted, rounding up
+    pub redeem_fee_bps: u16,
+    /// Deposits that would take the vault's assets above this are rejected
+    pub deposit_cap: Option<u64>,
+    /// Virtual shares and assets on both sides of every conversion
+    pub virtual_offset: u64,
+    /// Largest yield event, in basis points of the vault's assets
+    pub max_yield_bps: u16,
+    /// Largest loss event, in basis points of the vault's assets
+    pub max_loss_bps: u16,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            users: 4,
+            steps: 50,
+            max_deposit: 1_000_000_000,
+            deposit_fee_bps: 0,
+            redeem_fee_bps: 0,
+            deposit_cap: None,
+            virtual_offset: vault_math::VIRTUAL_OFFSET,
+            max_yield_bps: 500,
+            max_loss_bps: 500,
+        }
+    }
+}
+
+/// A shareholder's position over the trajectory
+#[derive(Debug, Clone, Default)]
+pub struct User {
+    pub shares: u64,
+    /// Underlying paid in, fees included
+    pub deposited: u64,
+    /// Underlying received, after fees
+    pub withdrawn: u64,
+    /// Value lost to rounding in the user's conversions, in underlying
+    pub leakage: f64,
+}
+
+impl User {
+    /// Net return on what the user paid in, `None` if they never deposited
+    pub fn net_return(&self) -> Option<f64> {
+        (self.deposited > 0)
+            .then(|| (self.withdrawn as f64 - self.deposited as f64) / self.deposited as f64)
+    }
+}
+
+/// Everything a finished trajectory reports
+#[derive(Debug, Clone, Default)]
+pub struct Outcome {
+    pub users: Vec<User>,
+    /// Fees collected on deposits and redemptions
+    pub fees: u64,
+    /// Underlying added by yield events
+    pub yielded: u64,
+    /// Underlying removed by loss events
+    pub lost: u64,
+    /// Underlying left in the vault after every user exited
+    pub residual: u64,
+    /// Largest rounding loss of a single conversion, in underlying
+    pub worst_conversion_leakage: f64,
+    /// Deposits rejected by the cap
+    pub capped: u64,
+    /// Operations rejected by the kernel rules (zero output, empty vault,
+    /// overflow)
+    pub rejected: u64,
+    /// Positions that could not be redeemed at the final exit
+    pub stranded: u64,
+}
+
+/// Vault totals and the trajectory's bookkeeping
+struct Vault<'a> {
+    params: &'a Params,
+    assets: u64,
+    shares: u64,
+    outcome: Outcome,
+}
+
+impl<'a> Vault<'a> {
+    fn new(params: &'a Params) -> Self {
+        Self {
+            params,
+            assets: 0,
+            shares: 0,
+            outcome: Outcome {
+                users: vec![User::default(); params.users],
+                ..Outcome::default()
+            },
+        }
+    }
+
+    /// Underlying one share is worth, counting the virtual offset
+    fn price(&self) -> f64 {
+        let offset = self.params.virtual_offset as f64;
+        if self.shares == 0 && offset == 0.0 {
+            return 1.0;
+        }
+        (self.assets as f64 + offset) / (self.shares as f64 + offset)
+    }
+
+    fn deposit(&mut self, user: usize, amount: u64) {
+        let fee = fee(amount, self.params.deposit_fee_bps);
+        let net = amount - fee;
+        if let Some(cap) = self.params.deposit_cap {
+            if self.assets.saturating_add(net) > cap {
+                self.outcome.capped += 1;
+                return;
+            }
+        }
+        // The kernel's rules: no zero amounts, no deposits into a vault whose
+        // shares are worth nothing, no zero-share mints
+        let minted = (net > 0 && !(self.shares > 0 && self.assets == 0))
+            .then(|| shares_with_offset(net, self.shares, self.assets, self.params.virtual_offset))
+            .flatten()
+            .filter(|&shares| shares > 0);
+        let Some(minted) = minted else {
+            self.outcome.rejected += 1;
+            return;
+        };
+
+        self.record_leakage(user, net as f64 - minted as f64 * self.price());
+        self.assets += net;
+        self.shares += minted;
+        self.outcome.fees += fee;
+        let position = &mut self.outcome.users[user];
+        position.shares += minted;
+        position.deposited += amount;
+    }
+
+    fn redeem(&mut self, user: usize, shares: u64) -> bool {
+        let paid = (shares > 0 && self.shares > 0 && self.assets > 0)
+            .then(|| {
+                assets_with_offset(shares, self.shares, self.assets, self.params.virtual_offset)
+            })
+            .flatten()
+            // With an offset, shares priced below one unit can claim more
+            // than the vault holds; the program's transfer out would fail
+            .filter(|&assets| assets > 0 && assets <= self.assets);
+        let Some(assets) = paid else {
+            self.outcome.rejected += 1;
+            return false;
+        };
+
+        self.record_leakage(user, shares as f64 * self.price() - assets as f64);
+        let fee = fee(assets, self.params.redeem_fee_bps);
+        self.assets -= assets;
+        self.shares -= shares;
+        self.outcome.fees += fee;
+        let position = &mut self.outcome.users[user];
+        position.shares -= shares;
+        position.withdrawn += assets - fee;
+        true
+    }
+
+    fn record_leakage(&mut self, user: usize, leakage: f64) {
+        // Floating point can put an exact conversion a hair below zero
+        let leakage = leakage.max(0.0);
+        self.outcome.users[user].leakage += leakage;
+        self.outcome.worst_conversion_leakage = self.outcome.worst_conversion_leakage.max(leakage);
+    }
+
+    fn grow(&mut self, bps: u16) {
+        let gain = (self.assets as u128 * bps as u128 / BPS) as u64;
+        self.assets = self.assets.saturating_add(gain);
+        self.outcome.yielded += gain;
+    }
+
+    fn shrink(&mut self, bps: u16) {
+        let loss = (self.assets as u128 * bps as u128 / BPS) as u64;
+        self.assets -= loss;
+        self.outcome.lost += loss;
+    }
+}
+
+/// `bps` of `amount`, rounding up so fees favor the vault
+fn fee(amount: u64, bps: u16) -> u64 {
+    mul_div_ceil(amount as u128, bps.min(BPS as u16) as u128, BPS).unwrap_or(0) as u64
+}
+
+/// Log-uniform in `1..=max`, so small deposits (where rounding bites) are as
+/// common as large ones
+fn log_uniform(rng: &mut StdRng, max: u64) -> u64 {
+    let exponent = rng.gen_range(0.0..=(max.max(1) as f64).ln());
+    (exponent.exp() as u64).clamp(1, max.max(1))
+}
+
+/// Runs `params.steps` random deposits, yields, losses and redeems, then
+/// redeems every remaining position
+pub fn run_trajectory(params: &Params, rng: &mut StdRng) -> Outcome {
+    let mut vault = Vault::new(params);
+    if params.users == 0 {
+        return vault.outcome;
+    }
+
+    for _ in 0..params.steps {
+        let user = rng.gen_range(0..params.users);
+        match rng.gen_range(0..100) {
+            roll if roll < DEPOSIT_PERCENT => {
+                let amount = log_uniform(rng, params.max_deposit);
+                vault.deposit(user, amount);
+            }
+            roll if roll < DEPOSIT_PERCENT + REDEEM_PERCENT => {
+                let position = vault.outcome.users[user].shares;
+                if position == 0 {
+                    continue;
+                }
+                let shares = if rng.gen_range(0..100) < FULL_EXIT_PERCENT {
+                    position
+                } else {
+                    rng.gen_range(1..=position)
+                };
+                vault.redeem(user, shares);
+            }
+            roll if roll < DEPOSIT_PERCENT + REDEEM_PERCENT + YIELD_PERCENT => {
+                vault.grow(rng.gen_range(0..=params.max_yield_bps));
+            }
+            _ => vault.shrink(rng.gen_range(0..=params.max_loss_bps)),
+        }
+    }
+
+    for user in 0..params.users {
+        let position = vault.outcome.users[user].shares;
+        if position > 0 && !vault.redeem(user, position) {
+            vault.outcome.stranded += 1;
+        }
+    }
+    vault.outcome.residual = vault.assets;
+    vault.outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn run(params: &Params, seed: u64) -> Outcome {
+        run_trajectory(params, &mut StdRng::seed_from_u64(seed))
+    }
+
+    #[test]
+    fn trajectories_conserve_underlying() {
+        for virtual_offset in [0, 1, 1_000] {
+            let params = Params {
+                deposit_fee_bps: 30,
+                redeem_fee_bps: 10,
+                max_deposit: 10_000,
+                virtual_offset,
+                ..Params::default()
+            };
+            for seed in 0..200 {
+                let outcome = run(&params, seed);
+                let deposited: u64 = outcome.users.iter().map(|user| user.deposited).sum();
+                let withdrawn: u64 = outcome.users.iter().map(|user| user.withdrawn).sum();
+                assert_eq!(
+                    deposited + outcome.yielded,
+                    withdrawn + outcome.fees + outcome.lost + outcome.residual,
+                    "offset {virtual_offset}, seed {seed}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn trajectories_are_deterministic_per_seed() {
+        let params = Params::default();
+        let first = run(&params, 7);
+        let second = run(&params, 7);
+        assert_eq!(first.residual, second.residual);
+        assert_eq!(first.fees, second.fees);
+        let withdrawn = |outcome: &Outcome| -> Vec<u64> {
+            outcome.users.iter().map(|user| user.withdrawn).collect()
+        };
+        assert_eq!(withdrawn(&first), withdrawn(&second));
+    }
+
+    #[test]
+    fn flat_vault_never_pays_out_more_than_it_took() {
+        let params = Params {
+            max_yield_bps: 0,
+            max_loss_bps: 0,
+            virtual_offset: 1,
+            ..Params::default()
+        };
+        for seed in 0..200 {
+            for user in run(&params, seed).users {
+                assert!(user.withdrawn <= user.deposited, "seed {seed}: {user:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn cap_rejects_deposits_above_it() {
+        let params = Params {
+            deposit_cap: Some(1),
+            max_yield_bps: 0,
+            ..Params::default()
+        };
+        let outcome = run(&params, 3);
+        assert!(outcome.capped > 0);
+        assert!(outcome.users.iter().all(|user| user.deposited <= 1));
+    }
+
+    #[test]
+    fn fees_round_up() {
+        assert_eq!(fee(1, 1), 1);
+        assert_eq!(fee(10_000, 30), 30);
+        assert_eq!(fee(0, 30), 0);
+        assert_eq!(fee(5, u16::MAX), 5);
+    }
+}
@@ -0,0 +1,74 @@
+//! Percentile summaries of a metric over every trajectory.
+
+/// Every sample of one metric
+#[derive(Debug, Default)]
+pub struct Distribution {
+    samples: Vec<f64>,
+}
+
+impl Distribution {
+    pub fn push(&mut self, sample: f64) {
+        self.samples.push(sample);
+    }
+
+    /// Nearest-rank percentile, `None` without samples
+    pub fn percentile(&self, percent: f64) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted = self.samples.clone();
+        sorted.sort_by(f64::total_cmp);
+        let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+        Some(sorted[rank.clamp(1, sorted.len()) - 1])
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (!self.samples.is_empty())
+            .then(|| self.samples.iter().sum::<f64>() / self.samples.len() as f64)
+    }
+
+    /// Column names of the summary table
+    pub fn header() -> String {
+        let mut header = format!("{:<14}", "metric");
+        for column in ["min", "p1", "p5", "p50", "p95", "p99", "max", "mean"] {
+            header.push_str(&format!("{column:>12}"));
+        }
+        header
+    }
+
+    /// One line of the summary table, lined up under [`Self::header`]
+    pub fn row(&self, name: &str) -> String {
+        let mut row = format!("{name:<14}");
+        for percent in [0.0, 1.0, 5.0, 50.0, 95.0, 99.0, 100.0] {
+            row.push_str(&cell(self.percentile(percent)));
+        }
+        row.push_str(&cell(self.mean()));
+        row
+    }
+}
+
+fn cell(value: Option<f64>) -> String {
+    match value {
+        Some(value) => format!("{value:>12.4}"),
+        None => format!("{:>12}", "-"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let mut distribution = Distribution::default();
+        for sample in (1..=100).rev() {
+            distribution.push(sample as f64);
+        }
+        assert_eq!(distribution.percentile(0.0), Some(1.0));
+        assert_eq!(distribution.percentile(5.0), Some(5.0));
+        assert_eq!(distribution.percentile(50.0), Some(50.0));
+        assert_eq!(distribution.percentile(100.0), Some(100.0));
+        assert_eq!(distribution.mean(), Some(50.5));
+        assert_eq!(Distribution::default().percentile(50.0), None);
+    }
+}
@@ -0,0 +1,134 @@
+//! Failure bundles.
+//!
+//! When a check fails the soak stops and writes everything needed to
+//! understand the failure into `<out>/<unix-time>/`:
+//!
+//! - `violation.json`: the failed check, the step that failed it, the seed,
+//!   how long the run had gone and the slot it stopped at
+//! - `history.jsonl`: the most recent steps, oldest first, each with its
+//!   signature (for `solana confirm -v`) and the vault's totals after it
+//! - `accounts.json`: every account the soak created or touched as it is
+//!   now, raw data in base64 with the program account type where there is one
+//! - `ledgers.json`: each vault's running totals
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use serde_json::{json, Value};
+use vault_pda_client::AccountType;
+
+use crate::world::{Record, Step, World};
+
+/// The last steps of a run, oldest first
+pub struct History {
+    records: VecDeque<(u64, Record)>,
+    capacity: usize,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(capacity.min(1 << 16)),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, index: u64, record: Record) {
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back((index, record));
+    }
+}
+
+/// What the run was doing when the check failed
+pub struct Failure<'a> {
+    pub violation: &'a str,
+    /// `None` when drawing the step failed
+    pub step: Option<&'a Step>,
+    pub step_index: u64,
+    pub seed: u64,
+    pub elapsed: Duration,
+}
+
+/// Writes the bundle for `failure` under `out`, returning its directory
+pub fn write(
+    out: &Path,
+    failure: &Failure,
+    history: &History,
+    world: &World,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let unix_time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let dir = out.join(unix_time.to_string());
+    fs::create_dir_all(&dir)?;
+
+    let violation = json!({
+        "violation": failure.violation,
+        "step": failure.step,
+        "step_index": failure.step_index,
+        "seed": failure.seed,
+        "elapsed_secs": failure.elapsed.as_secs(),
+        // Best effort: the validator may be what failed
+        "slot": world.slot().ok(),
+        "payer": world.payer().to_string(),
+        "users": world.users().map(|user| user.to_string()).collect::<Vec<_>>(),
+    });
+    fs::write(
+        dir.join("violation.json"),
+        serde_json::to_string_pretty(&violation)?,
+    )?;
+
+    let mut lines = String::new();
+    for (index, record) in &history.records {
+        let mut line = serde_json::to_value(record)?;
+        line["index"] = (*index).into();
+        lines.push_str(&line.to_string());
+        lines.push('\n');
+    }
+    fs::write(dir.join("history.jsonl"), lines)?;
+
+    let labelled = world.accounts();
+    let addresses: Vec<_> = labelled.iter().map(|(_, address)| *address).collect();
+    let fetched = world
+        .fetch_many(&addresses)
+        .map_err(|e| format!("fetching accounts: {e:?}"))?;
+    let accounts: Vec<Value> = labelled
+        .iter()
+        .zip(fetched)
+        .map(|((label, address), account)| match account {
+            Some(account) => json!({
+                "label": label,
+                "address": address.to_string(),
+                "owner": account.owner.to_string(),
+                "lamports": account.lamports,
+                "type": AccountType::detect(&account.data).map(|ty| ty.name()),
+                "data": base64::engine::general_purpose::STANDARD.encode(&account.data),
+            }),
+            None => json!({ "label": label, "address": address.to_string(), "missing": true }),
+        })
+        .collect();
+    fs::write(
+        dir.join("accounts.json"),
+        serde_json::to_string_pretty(&accounts)?,
+    )?;
+
+    let ledgers: Vec<Value> = world
+        .vaults
+        .iter()
+        .map(|vault| {
+            json!({
+                "vault": vault.addresses.vault.to_string(),
+                "ledger": vault.ledger,
+            })
+        })
+        .collect();
+    fs::write(
+        dir.join("ledgers.json"),
+        serde_json::to_string_pretty(&ledgers)?,
+    )?;
+
+    Ok(dir)
+}
@@ -0,0 +1,176 @@
+//! Running totals of what the soak put into and took out of each vault.
+//!
+//! Per-operation checks only compare the two snapshots around a transaction,
+//! so a unit that goes missing between operations, or a rounding error that
+//! only shows after millions of them, slips past. The ledger sums every flow
+//! since the vault was created and is checked against the vault's balances
+//! after every step:
+//!
+//! - the vault holds exactly what was deposited and yielded minus what
+//!   redeems paid out
+//! - the share supply is exactly what deposits minted minus what redeems
+//!   burned, and every share is held by one of the soak's users
+//! - the value of one share never decreases, since both conversions round in
+//!   the vault's favor and the program has no loss path
+//!
+//! An RPC error in the middle of a step leaves it unknown whether the
+//! transaction landed, so the ledger is then [resynced](Ledger::resync) to
+//! the vault's balances and carries the difference from there on.
+
+use serde::Serialize;
+use vault_invariants::Snapshot;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Ledger {
+    /// Underlying deposited
+    pub deposited: u128,
+    /// Underlying minted straight into the vault as yield
+    pub yielded: u128,
+    /// Underlying paid out by redeems
+    pub paid_out: u128,
+    /// Shares minted by deposits
+    pub minted: u128,
+    /// Shares burned by redeems
+    pub burned: u128,
+    /// Value of one share at the last check, while shares are outstanding
+    pub value_per_share: Option<u128>,
+    /// Underlying and shares taken over from the vault by resyncs
+    pub resynced_assets: i128,
+    pub resynced_shares: i128,
+    pub resyncs: u64,
+}
+
+impl Ledger {
+    pub fn deposit(&mut self, amount: u64, minted: u64) {
+        self.deposited += amount as u128;
+        self.minted += minted as u128;
+    }
+
+    pub fn redeem(&mut self, burned: u64, paid_out: u64) {
+        self.burned += burned as u128;
+        self.paid_out += paid_out as u128;
+    }
+
+    pub fn yielded(&mut self, amount: u64) {
+        self.yielded += amount as u128;
+    }
+
+    /// Takes the vault's balance and share supply as correct after a step
+    /// whose effect is unknown
+    pub fn resync(&mut self, vault_balance: u64, share_supply: u64) {
+        self.resynced_assets = vault_balance as i128 - self.flows_balance();
+        self.resynced_shares = share_supply as i128 - self.flows_supply();
+        self.value_per_share = None;
+        self.resyncs += 1;
+    }
+
+    fn flows_balance(&self) -> i128 {
+        (self.deposited + self.yielded) as i128 - self.paid_out as i128
+    }
+
+    fn flows_supply(&self) -> i128 {
+        self.minted as i128 - self.burned as i128
+    }
+
+    /// Checks the vault's balance and share supply, and the shares the soak's
+    /// users hold, against the totals, recording the share value on success
+    pub fn check(
+        &mut self,
+        vault_balance: u64,
+        share_supply: u64,
+        held_shares: u128,
+    ) -> Result<(), String> {
+        let expected_balance = self.flows_balance() + self.resynced_assets;
+        if vault_balance as i128 != expected_balance {
+            return Err(format!(
+                "vault holds {vault_balance} underlying, deposits and yield minus redeems leave {expected_balance}"
+            ));
+        }
+
+        let expected_supply = self.flows_supply() + self.resynced_shares;
+        if share_supply as i128 != expected_supply {
+            return Err(format!(
+                "share supply is {share_supply}, deposits minted minus redeems burned is {expected_supply}"
+            ));
+        }
+        if share_supply as u128 != held_shares {
+            return Err(format!(
+                "share supply is {share_supply} but the soak's users hold {held_shares}"
+            ));
+        }
+
+        let value_per_share = Snapshot {
+            vault_balance,
+            share_supply,
+            ..Snapshot::default()
+        }
+        .value_per_share();
+        if let (Some(before), Some(after)) = (self.value_per_share, value_per_share) {
+            if after < before {
+                return Err(format!("value per share fell from {before} to {after}"));
+            }
+        }
+        self.value_per_share = value_per_share;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balanced_flows_pass() {
+        let mut ledger = Ledger::default();
+        ledger.deposit(1_000, 1_000);
+        ledger.yielded(100);
+        ledger.check(1_100, 1_000, 1_000).unwrap();
+
+        ledger.redeem(500, 550);
+        ledger.check(550, 500, 500).unwrap();
+        ledger.redeem(500, 550);
+        ledger.check(0, 0, 0).unwrap();
+        assert_eq!(ledger.value_per_share, None);
+    }
+
+    #[test]
+    fn stray_balance_changes_are_caught() {
+        let mut ledger = Ledger::default();
+        ledger.deposit(1_000, 1_000);
+
+        // A unit arriving or leaving outside the soak's operations
+        assert!(ledger.check(1_001, 1_000, 1_000).is_err());
+        assert!(ledger.check(999, 1_000, 1_000).is_err());
+        // Shares minted outside a deposit, or held outside the soak's users
+        assert!(ledger.check(1_000, 1_001, 1_001).is_err());
+        assert!(ledger.check(1_000, 1_000, 999).is_err());
+        ledger.check(1_000, 1_000, 1_000).unwrap();
+    }
+
+    #[test]
+    fn resync_adopts_the_vault_balances() {
+        let mut ledger = Ledger::default();
+        ledger.deposit(1_000, 1_000);
+        ledger.check(1_000, 1_000, 1_000).unwrap();
+
+        // A deposit of 500 landed but its RPC response was lost
+        ledger.resync(1_500, 1_500);
+        ledger.check(1_500, 1_500, 1_500).unwrap();
+        ledger.redeem(1_500, 1_500);
+        ledger.check(0, 0, 0).unwrap();
+        assert!(ledger.check(1, 0, 0).is_err());
+    }
+
+    #[test]
+    fn share_value_may_not_fall() {
+        let mut ledger = Ledger::default();
+        ledger.deposit(1_000, 1_000);
+        ledger.yielded(1_000);
+        ledger.check(2_000, 1_000, 1_000).unwrap();
+
+        // A redeem that overpays by a unit lowers everyone else's share value
+        ledger.redeem(500, 1_001);
+        let error = ledger.check(999, 500, 500).unwrap_err();
+        assert!(error.contains("value per share fell"), "{error}");
+    }
+}
@@ -0,0 +1,243 @@
+//! Long-running soak test against a validator.
+//!
+//! ```bash
+//! soak [--url <rpc>] [--keypair <path>] [--duration <secs>] [--vaults <n>] [--users <n>]
+//!      [--seed <n>] [--max-deposit <amount>] [--yield-interval <secs>] [--max-yield-bps <bps>]
+//!      [--admin-percent <n>] [--report <secs>] [--history <steps>] [--out <dir>]
+//! ```
+//!
+//! Creates fresh vaults (2 by default) with positions for many users (16),
+//! then until `--duration` (four hours) has passed sends a mixed workload:
+//! deposits of log-uniform size up to `--max-deposit`, partial and full
+//! redeems, yield minted into every vault every `--yield-interval` seconds,
+//! and, `--admin-percent` of the time, `record_price` or an ownership
+//! handover between the payer and a second keypair and back.
+//!
+//! Every deposit and redeem is checked against `vault-invariants` as it runs,
+//! and after every step the vault is checked against a ledger of everything
+//! the soak put in and took out (see `ledger.rs`), which catches slow drift
+//! that no single operation shows. The first failed check stops the run and
+//! writes a failure bundle under `--out` (`soak-failures` by default) with
+//! the recent transaction history and every account involved; the exit
+//! status is then non-zero. RPC errors are retried, up to 20 in a row.
+//!
+//! The vault must already be deployed. The payer is `--keypair`, else the
+//! Solana CLI keypair, else a fresh one funded by airdrop. Build with
+//! `--no-default-features` to soak the fixed program, whose share value
+//! includes the virtual offset.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use solana_sdk::signature::{read_keypair_file, Keypair};
+
+mod bundle;
+mod ledger;
+mod world;
+
+use bundle::{Failure, History};
+use world::{StepError, World};
+
+const USAGE: &str =
+    "usage: soak [--url <rpc>] [--keypair <path>] [--duration <secs>] [--vaults <n>] [--users <n>]
+            [--seed <n>] [--max-deposit <amount>] [--yield-interval <secs>] [--max-yield-bps <bps>]
+            [--admin-percent <n>] [--report <secs>] [--history <steps>] [--out <dir>]";
+
+const DEFAULT_URL: &str = "http://127.0.0.1:8899";
+
+/// Consecutive RPC errors after which the validator is considered gone
+const MAX_RPC_ERRORS: u32 = 20;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+struct Config {
+    url: String,
+    keypair: Option<String>,
+    duration: Duration,
+    vaults: usize,
+    users: usize,
+    seed: u64,
+    max_deposit: u64,
+    yield_interval: Duration,
+    max_yield_bps: u16,
+    admin_percent: u32,
+    report: Duration,
+    history: usize,
+    out: PathBuf,
+}
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("soak: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// The configuration, `None` when only the usage was asked for
+fn parse_args(args: Vec<String>) -> Result<Option<Config>> {
+    let mut config = Config {
+        url: DEFAULT_URL.to_string(),
+        keypair: None,
+        duration: Duration::from_secs(4 * 60 * 60),
+        vaults: 2,
+        users: 16,
+        seed: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        max_deposit: 1_000_000_000,
+        yield_interval: Duration::from_secs(30),
+        max_yield_bps: 100,
+        admin_percent: 2,
+        report: Duration::from_secs(60),
+        history: 10_000,
+        out: PathBuf::from("soak-failures"),
+    };
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(USAGE);
+        match arg.as_str() {
+            "--url" => config.url = value()?,
+            "--keypair" => config.keypair = Some(value()?),
+            "--duration" => config.duration = Duration::from_secs(value()?.parse()?),
+            "--vaults" => config.vaults = value()?.parse()?,
+            "--users" => config.users = value()?.parse()?,
+            "--seed" => config.seed = value()?.parse()?,
+            "--max-deposit" => config.max_deposit = value()?.parse()?,
+            "--yield-interval" => config.yield_interval = Duration::from_secs(value()?.parse()?),
+            "--max-yield-bps" => config.max_yield_bps = value()?.parse()?,
+            "--admin-percent" => config.admin_percent = value()?.parse()?,
+            "--report" => config.report = Duration::from_secs(value()?.parse()?),
+            "--history" => config.history = value()?.parse()?,
+            "--out" => config.out = value()?.into(),
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return Ok(None);
+            }
+            _ => return Err(USAGE.into()),
+        }
+    }
+    if config.vaults == 0 || config.users == 0 || config.max_deposit == 0 {
+        return Err("--vaults, --users and --max-deposit must be positive".into());
+    }
+    Ok(Some(config))
+}
+
+fn run(args: Vec<String>) -> Result<()> {
+    let Some(config) = parse_args(args)? else {
+        return Ok(());
+    };
+    let payer = match &config.keypair {
+        Some(keypair) => read_keypair_file(keypair)?,
+        None => default_keypair(),
+    };
+
+    println!(
+        "soak: seed {}, {} vaults, {} users, {}s against {}",
+        config.seed,
+        config.vaults,
+        config.users,
+        config.duration.as_secs(),
+        config.url
+    );
+    let mut world =
+        World::new(&config.url, payer, config.vaults, config.users).map_err(setup_error)?;
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut history = History::new(config.history.max(1));
+
+    let started = Instant::now();
+    let mut last_yield = started;
+    let mut last_report = started;
+    let (mut steps, mut rejected, mut rpc_errors, mut consecutive_errors) = (0u64, 0u64, 0u64, 0);
+    let mut pending_yields = Vec::new();
+
+    while started.elapsed() < config.duration {
+        if last_yield.elapsed() >= config.yield_interval {
+            last_yield = Instant::now();
+            pending_yields.extend(0..world.vaults.len());
+        }
+
+        let step = match pending_yields.pop() {
+            Some(vault) => world.yield_step(&mut rng, vault, config.max_yield_bps),
+            None => world.next_step(&mut rng, config.max_deposit, config.admin_percent),
+        };
+        let outcome = match step {
+            Ok(step) => world.run(step.clone()).map_err(|e| (e, Some(step))),
+            Err(e) => Err((e, None)),
+        };
+
+        match outcome {
+            Ok(record) => {
+                consecutive_errors = 0;
+                rejected += record.rejected.is_some() as u64;
+                history.push(steps, record);
+                steps += 1;
+            }
+            Err((StepError::Rpc(e), _)) => {
+                rpc_errors += 1;
+                consecutive_errors += 1;
+                eprintln!("soak: RPC error ({consecutive_errors} in a row): {e}");
+                if consecutive_errors >= MAX_RPC_ERRORS {
+                    return Err(
+                        format!("giving up after {MAX_RPC_ERRORS} RPC errors in a row").into(),
+                    );
+                }
+                std::thread::sleep(Duration::from_secs(1));
+            }
+            Err((StepError::Violation(violation), step)) => {
+                let failure = Failure {
+                    violation: &violation,
+                    step: step.as_ref(),
+                    step_index: steps,
+                    seed: config.seed,
+                    elapsed: started.elapsed(),
+                };
+                return Err(
+                    match bundle::write(&config.out, &failure, &history, &world) {
+                        Ok(dir) => {
+                            format!("{violation}\nfailure bundle written to {}", dir.display())
+                        }
+                        Err(e) => format!("{violation}\nfailed to write the failure bundle: {e}"),
+                    }
+                    .into(),
+                );
+            }
+        }
+
+        if last_report.elapsed() >= config.report {
+            last_report = Instant::now();
+            let elapsed = started.elapsed().as_secs_f64();
+            println!(
+                "soak: {:>6.0}s, {steps} steps ({:.1}/s), {rejected} rejected, {rpc_errors} RPC errors",
+                elapsed,
+                steps as f64 / elapsed
+            );
+        }
+    }
+
+    world.restore_ownership().map_err(setup_error)?;
+    println!(
+        "soak: passed, {steps} steps in {}s, {rejected} rejected, {rpc_errors} RPC errors",
+        started.elapsed().as_secs()
+    );
+    Ok(())
+}
+
+fn setup_error(e: StepError) -> Box<dyn std::error::Error> {
+    match e {
+        StepError::Rpc(e) => e,
+        StepError::Violation(violation) => violation.into(),
+    }
+}
+
+/// The Solana CLI keypair if there is one
+fn default_keypair() -> Keypair {
+    std::env::var("HOME")
+        .ok()
+        .and_then(|home| read_keypair_file(format!("{home}/.config/solana/id.json")).ok())
+        .unwrap_or_else(Keypair::new)
+}
@@ -0,0 +1,754 @@
+//! The vaults, users and positions a soak run drives over RPC.
+//!
+//! Every vault is created fresh over a new SPL Token mint the payer is the
+//! mint authority of, so its [`Ledger`] starts from zero and nothing else
+//! touches it. Users are plain keypairs; the payer pays every fee and mints
+//! whatever underlying a deposit needs. Each step is checked as it runs, and a
+//! failed check is returned as [`StepError::Violation`] rather than a panic, so
+//! the caller can write a failure bundle before stopping.
+
+use std::error::Error;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::Serialize;
+use solana_client::client_error::ClientError;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    instruction::{Instruction, InstructionError},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction,
+    transaction::TransactionError,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+use vault_invariants::{assert_exchange, Exchange, Snapshot};
+use vault_pda::state::{PriceHistory, ProtocolState};
+use vault_pda::RecordPriceError;
+use vault_pda_client::blocking::{send, top_up, Sent};
+use vault_pda_client::pda::{derive_admin_log_pda, derive_protocol_state_pda, VaultAddresses};
+use vault_pda_client::{
+    deserialize, initialize_ix, initialize_vault_ix, try_deserialize_any, AnyAccount,
+    DepositBuilder, RedeemBuilder,
+};
+
+use crate::ledger::Ledger;
+
+/// Accounts `getMultipleAccounts` returns at most per request
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Chance in percent that a step is a deposit, else a redeem; admin actions
+/// are drawn first, with their own rate
+const DEPOSIT_PERCENT: u32 = 55;
+
+/// Chance in percent that a redeem burns the user's whole position
+const FULL_EXIT_PERCENT: u32 = 30;
+
+/// Why a step could not complete
+#[derive(Debug)]
+pub enum StepError {
+    /// An invariant failed; the run stops and writes a failure bundle
+    Violation(String),
+    /// The validator could not be reached or answered nonsense; the run
+    /// carries on with the next step
+    Rpc(Box<dyn Error>),
+}
+
+impl From<ClientError> for StepError {
+    fn from(e: ClientError) -> Self {
+        StepError::Rpc(e.into())
+    }
+}
+
+impl From<&str> for StepError {
+    fn from(e: &str) -> Self {
+        StepError::Rpc(e.into())
+    }
+}
+
+impl From<ProgramError> for StepError {
+    fn from(e: ProgramError) -> Self {
+        StepError::Rpc(e.into())
+    }
+}
+
+/// One step of the workload
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Step {
+    Deposit {
+        vault: usize,
+        user: usize,
+        amount: u64,
+    },
+    Redeem {
+        vault: usize,
+        user: usize,
+        shares: u64,
+    },
+    Yield {
+        vault: usize,
+        amount: u64,
+    },
+    RecordPrice {
+        vault: usize,
+    },
+    /// Hands the protocol between the payer and the admin keypair
+    TransferOwnership {
+        #[serde(serialize_with = "base58")]
+        to: Pubkey,
+    },
+}
+
+fn base58<S: serde::Serializer>(key: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(key)
+}
+
+/// What a step did, kept in the history of the failure bundle
+#[derive(Debug, Clone, Serialize)]
+pub struct Record {
+    pub step: Step,
+    /// Signature of the step's vault transaction, if it sent one
+    pub signature: Option<String>,
+    /// The program's error, if it rejected the transaction
+    pub rejected: Option<String>,
+    /// The vault's balance and share supply after the step
+    pub vault_balance: u64,
+    pub share_supply: u64,
+}
+
+/// A user's token accounts for one vault
+#[derive(Debug, Clone)]
+pub struct Position {
+    pub underlying: Pubkey,
+    pub shares: Pubkey,
+}
+
+pub struct SoakVault {
+    pub underlying_mint: Pubkey,
+    pub addresses: VaultAddresses,
+    pub price_history: Pubkey,
+    /// Positions by user index
+    pub positions: Vec<Position>,
+    pub ledger: Ledger,
+}
+
+pub struct World {
+    rpc: RpcClient,
+    payer: Keypair,
+    /// Takes the protocol over and hands it back in ownership steps
+    admin: Keypair,
+    program_id: Pubkey,
+    users: Vec<Keypair>,
+    pub vaults: Vec<SoakVault>,
+    /// Current protocol owner, `None` when neither the payer nor the admin
+    /// owns it and ownership steps are skipped
+    protocol_owner: Option<Pubkey>,
+    /// Vault of a step that failed with an RPC error, whose ledger must be
+    /// resynced before it is checked again
+    unsettled: Option<usize>,
+}
+
+impl World {
+    /// Connects to `url`, initializes the protocol if needed and creates
+    /// `vaults` vaults with a position for each of `users` users
+    pub fn new(url: &str, payer: Keypair, vaults: usize, users: usize) -> Result<Self, StepError> {
+        let rpc = RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed());
+        let program_id = vault_pda::id();
+        let deployed = rpc
+            .get_account_with_commitment(&program_id, rpc.commitment())?
+            .value
+            .is_some_and(|account| account.executable);
+        if !deployed {
+            return Err(StepError::Rpc(
+                format!("vault-pda is not deployed at {program_id} on {url}").into(),
+            ));
+        }
+
+        let mut world = Self {
+            rpc,
+            payer,
+            admin: Keypair::new(),
+            program_id,
+            users: (0..users).map(|_| Keypair::new()).collect(),
+            vaults: Vec::new(),
+            protocol_owner: None,
+            unsettled: None,
+        };
+        world.top_up()?;
+
+        let protocol_state = derive_protocol_state_pda(&program_id).0;
+        world.protocol_owner = match world.fetch(&protocol_state)? {
+            Some(account) => {
+                let owner = deserialize::<ProtocolState>(&account.data)
+                    .map_err(|e| StepError::Rpc(e.into()))?
+                    .owner;
+                (owner == world.payer.pubkey()).then_some(owner)
+            }
+            None => {
+                let ix = initialize_ix(&program_id, &world.payer.pubkey());
+                world.send_confirmed(&[ix], &[])?;
+                Some(world.payer.pubkey())
+            }
+        };
+
+        for _ in 0..vaults {
+            let vault = world.create_vault()?;
+            world.vaults.push(vault);
+        }
+        Ok(world)
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    pub fn users(&self) -> impl Iterator<Item = Pubkey> + '_ {
+        self.users.iter().map(Keypair::pubkey)
+    }
+
+    /// Draws the next deposit, redeem or, `admin_percent` of the time, admin
+    /// action
+    pub fn next_step(
+        &self,
+        rng: &mut StdRng,
+        max_deposit: u64,
+        admin_percent: u32,
+    ) -> Result<Step, StepError> {
+        let vault = rng.gen_range(0..self.vaults.len());
+        if rng.gen_range(0..100) < admin_percent {
+            return Ok(match self.protocol_owner {
+                Some(owner) if rng.gen_bool(0.5) => Step::TransferOwnership {
+                    to: if owner == self.payer.pubkey() {
+                        self.admin.pubkey()
+                    } else {
+                        self.payer.pubkey()
+                    },
+                },
+                _ => Step::RecordPrice { vault },
+            });
+        }
+
+        let user = rng.gen_range(0..self.users.len());
+        let held = self.token_balance(&self.vaults[vault].positions[user].shares)?;
+        if held == 0 || rng.gen_range(0..100) < DEPOSIT_PERCENT {
+            // Log-uniform, so small deposits where rounding bites are common
+            let exponent = rng.gen_range(0.0..=(max_deposit as f64).ln());
+            let amount = (exponent.exp() as u64).clamp(1, max_deposit);
+            return Ok(Step::Deposit {
+                vault,
+                user,
+                amount,
+            });
+        }
+        let shares = if rng.gen_range(0..100) < FULL_EXIT_PERCENT {
+            held
+        } else {
+            rng.gen_range(1..=held)
+        };
+        Ok(Step::Redeem {
+            vault,
+            user,
+            shares,
+        })
+    }
+
+    /// The yield step for `vault`: up to `max_bps` of its balance, at least
+    /// one unit
+    pub fn yield_step(
+        &self,
+        rng: &mut StdRng,
+        vault: usize,
+        max_bps: u16,
+    ) -> Result<Step, StepError> {
+        let balance = self.token_balance(&self.vaults[vault].addresses.vault_token_account)?;
+        let bps = rng.gen_range(0..=max_bps) as u128;
+        let amount = (balance as u128 * bps / 10_000).max(1) as u64;
+        Ok(Step::Yield { vault, amount })
+    }
+
+    /// Runs `step`, checks it and the vault's ledger, and records it
+    pub fn run(&mut self, step: Step) -> Result<Record, StepError> {
+        self.top_up()?;
+        if let Some(vault) = self.unsettled {
+            let (balance, supply) = self.totals(vault)?;
+            self.vaults[vault].ledger.resync(balance, supply);
+            self.unsettled = None;
+        }
+
+        self.unsettled = Some(match step {
+            Step::Deposit { vault, .. }
+            | Step::Redeem { vault, .. }
+            | Step::Yield { vault, .. }
+            | Step::RecordPrice { vault } => vault,
+            Step::TransferOwnership { .. } => 0,
+        });
+        let (vault, sent) = match &step {
+            Step::Deposit {
+                vault,
+                user,
+                amount,
+            } => (*vault, self.deposit(*vault, *user, *amount)?),
+            Step::Redeem {
+                vault,
+                user,
+                shares,
+            } => (*vault, self.redeem(*vault, *user, *shares)?),
+            Step::Yield { vault, amount } => (*vault, self.add_yield(*vault, *amount)?),
+            Step::RecordPrice { vault } => (*vault, self.record_price(*vault)?),
+            Step::TransferOwnership { to } => (0, self.transfer_ownership(*to)?),
+        };
+
+        let (vault_balance, share_supply) = self.check_ledger(vault)?;
+        self.unsettled = None;
+        let (signature, rejected) = match sent {
+            Sent::Confirmed(signature) => (Some(signature.to_string()), None),
+            Sent::Rejected(signature, error) => {
+                (Some(signature.to_string()), Some(error.to_string()))
+            }
+        };
+        Ok(Record {
+            step,
+            signature,
+            rejected,
+            vault_balance,
+            share_supply,
+        })
+    }
+
+    /// Hands the protocol back to the payer if the admin holds it
+    pub fn restore_ownership(&mut self) -> Result<(), StepError> {
+        if self.protocol_owner == Some(self.admin.pubkey()) {
+            self.transfer_ownership(self.payer.pubkey())?;
+        }
+        Ok(())
+    }
+
+    fn deposit(&mut self, vault: usize, user: usize, amount: u64) -> Result<Sent, StepError> {
+        let position = self.vaults[vault].positions[user].clone();
+        let held = self.token_balance(&position.underlying)?;
+        if held < amount {
+            let ix = spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &self.vaults[vault].underlying_mint,
+                &position.underlying,
+                &self.payer.pubkey(),
+                &[],
+                amount - held,
+            )?;
+            self.send_confirmed(&[ix], &[])?;
+        }
+
+        let ix = DepositBuilder::new(
+            self.vaults[vault].underlying_mint,
+            self.users[user].pubkey(),
+            amount,
+        )
+        .program_id(self.program_id)
+        .depositor_underlying_account(position.underlying)
+        .depositor_share_account(position.shares)
+        .build();
+        self.exchange(vault, user, ix, Exchange::Deposit { amount })
+    }
+
+    fn redeem(&mut self, vault: usize, user: usize, shares: u64) -> Result<Sent, StepError> {
+        let position = &self.vaults[vault].positions[user];
+        let ix = RedeemBuilder::new(
+            self.vaults[vault].underlying_mint,
+            self.users[user].pubkey(),
+            shares,
+        )
+        .program_id(self.program_id)
+        .redeemer_underlying_account(position.underlying)
+        .redeemer_share_account(position.shares)
+        .build();
+        self.exchange(vault, user, ix, Exchange::Redeem { shares })
+    }
+
+    /// Sends a deposit or redeem as `user`, checks every exchange invariant
+    /// on success and that a rejection changed nothing
+    fn exchange(
+        &mut self,
+        vault: usize,
+        user: usize,
+        ix: Instruction,
+        exchange: Exchange,
+    ) -> Result<Sent, StepError> {
+        let before = self.snapshot(vault, user)?;
+        let sent = self.send(&[ix], &[&self.users[user]])?;
+        let after = self.snapshot(vault, user)?;
+
+        if let Sent::Rejected(_, error) = &sent {
+            if before != after {
+                return Err(StepError::Violation(format!(
+                    "{exchange:?} was rejected with {error} but moved funds: {before:?} -> {after:?}"
+                )));
+            }
+            return Ok(sent);
+        }
+
+        catch_unwind(AssertUnwindSafe(|| {
+            assert_exchange(&before, &after, exchange)
+        }))
+        .map_err(|panic| {
+            let message = panic
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_default();
+            StepError::Violation(format!("{exchange:?}: {message}: {before:?} -> {after:?}"))
+        })?;
+
+        let ledger = &mut self.vaults[vault].ledger;
+        match exchange {
+            Exchange::Deposit { amount } => {
+                ledger.deposit(amount, after.user_shares - before.user_shares)
+            }
+            Exchange::Redeem { shares } => {
+                ledger.redeem(shares, after.user_balance - before.user_balance)
+            }
+        }
+        Ok(sent)
+    }
+
+    fn add_yield(&mut self, vault: usize, amount: u64) -> Result<Sent, StepError> {
+        let ix = spl_token::instruction::mint_to(
+            &spl_token::id(),
+            &self.vaults[vault].underlying_mint,
+            &self.vaults[vault].addresses.vault_token_account,
+            &self.payer.pubkey(),
+            &[],
+            amount,
+        )?;
+        let signature = self.send_confirmed(&[ix], &[])?;
+        self.vaults[vault].ledger.yielded(amount);
+        Ok(Sent::Confirmed(signature))
+    }
+
+    /// Records the price and checks the newest observation is the vault's
+    /// current totals
+    fn record_price(&mut self, vault: usize) -> Result<Sent, StepError> {
+        let accounts = vault_pda::accounts::RecordPrice {
+            price_history: self.vaults[vault].price_history,
+            vault: self.vaults[vault].addresses.vault,
+            vault_token_account: self.vaults[vault].addresses.vault_token_account,
+            share_mint: self.vaults[vault].addresses.share_mint,
+        };
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: accounts.to_account_metas(None),
+            data: vault_pda::instruction::RecordPrice {}.data(),
+        };
+        let sent = self.send(&[ix], &[])?;
+        if let Sent::Rejected(_, error) = &sent {
+            // A step that lands in the same slot as the last observation
+            if *error == already_recorded() {
+                return Ok(sent);
+            }
+            return Err(StepError::Violation(format!(
+                "record_price was rejected with {error}"
+            )));
+        }
+
+        let data = self.account(&self.vaults[vault].price_history)?.data;
+        let Ok(AnyAccount::PriceHistory(history)) = try_deserialize_any(&data) else {
+            return Err(StepError::Violation(
+                "price history no longer decodes".into(),
+            ));
+        };
+        let Some(latest) = history.latest().copied() else {
+            return Err(StepError::Violation(
+                "record_price left the price history empty".into(),
+            ));
+        };
+        let (balance, supply) = self.totals(vault)?;
+        if (latest.total_assets, latest.total_shares) != (balance, supply) {
+            return Err(StepError::Violation(format!(
+                "record_price stored {} assets and {} shares, the vault holds {balance} and {supply}",
+                latest.total_assets, latest.total_shares
+            )));
+        }
+        Ok(sent)
+    }
+
+    /// Hands the protocol to `to`, signed by both the current owner and `to`,
+    /// and checks the owner changed
+    fn transfer_ownership(&mut self, to: Pubkey) -> Result<Sent, StepError> {
+        let current = self.protocol_owner.unwrap_or_default();
+        let accounts = vault_pda::accounts::TransferOwnership {
+            protocol_state: derive_protocol_state_pda(&self.program_id).0,
+            current_owner: current,
+            new_owner: to,
+            admin_log: derive_admin_log_pda(&self.program_id).0,
+        };
+        let mut metas = accounts.to_account_metas(None);
+        for meta in &mut metas[1..3] {
+            meta.is_signer = true;
+        }
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: metas,
+            data: vault_pda::instruction::TransferOwnership {}.data(),
+        };
+        let sent = self.send(&[ix], &[&self.admin])?;
+        if let Sent::Rejected(_, error) = &sent {
+            return Err(StepError::Violation(format!(
+                "transfer_ownership from {current} to {to} was rejected with {error}"
+            )));
+        }
+
+        let data = self
+            .account(&derive_protocol_state_pda(&self.program_id).0)?
+            .data;
+        let owner = deserialize::<ProtocolState>(&data)
+            .map_err(|e| StepError::Violation(format!("protocol state no longer decodes: {e}")))?
+            .owner;
+        if owner != to {
+            return Err(StepError::Violation(format!(
+                "transfer_ownership to {to} left {owner} as the owner"
+            )));
+        }
+        self.protocol_owner = Some(to);
+        Ok(sent)
+    }
+
+    /// Checks `vault`'s ledger, returning its balance and share supply
+    fn check_ledger(&mut self, vault: usize) -> Result<(u64, u64), StepError> {
+        let (balance, supply) = self.totals(vault)?;
+        let share_accounts: Vec<Pubkey> = self.vaults[vault]
+            .positions
+            .iter()
+            .map(|position| position.shares)
+            .collect();
+        let mut held = 0u128;
+        for chunk in share_accounts.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            for account in self.rpc.get_multiple_accounts(chunk)? {
+                let account = account.ok_or("share account disappeared")?;
+                held += TokenAccount::unpack(&account.data)?.amount as u128;
+            }
+        }
+        self.vaults[vault]
+            .ledger
+            .check(balance, supply, held)
+            .map_err(|e| StepError::Violation(format!("vault {vault}: {e}")))?;
+        Ok((balance, supply))
+    }
+
+    /// The four balances the exchange invariants compare, read in one request
+    fn snapshot(&self, vault: usize, user: usize) -> Result<Snapshot, StepError> {
+        let vault = &self.vaults[vault];
+        let position = &vault.positions[user];
+        let accounts = self.rpc.get_multiple_accounts(&[
+            vault.addresses.vault_token_account,
+            vault.addresses.share_mint,
+            position.underlying,
+            position.shares,
+        ])?;
+        let data = |index: usize| -> Result<Vec<u8>, StepError> {
+            Ok(accounts[index]
+                .as_ref()
+                .ok_or("snapshot account disappeared")?
+                .data
+                .clone())
+        };
+        Ok(Snapshot {
+            vault_balance: TokenAccount::unpack(&data(0)?)?.amount,
+            share_supply: Mint::unpack(&data(1)?)?.supply,
+            user_balance: TokenAccount::unpack(&data(2)?)?.amount,
+            user_shares: TokenAccount::unpack(&data(3)?)?.amount,
+        })
+    }
+
+    /// `vault`'s balance and share supply, read in one request
+    fn totals(&self, vault: usize) -> Result<(u64, u64), StepError> {
+        let snapshot = self.snapshot(vault, 0)?;
+        Ok((snapshot.vault_balance, snapshot.share_supply))
+    }
+
+    /// Every account the soak created or touches, for the failure bundle
+    pub fn accounts(&self) -> Vec<(String, Pubkey)> {
+        let mut accounts = vec![(
+            "protocol_state".to_string(),
+            derive_protocol_state_pda(&self.program_id).0,
+        )];
+        for (index, vault) in self.vaults.iter().enumerate() {
+            accounts.extend([
+                (format!("vault[{index}]"), vault.addresses.vault),
+                (
+                    format!("vault[{index}].token_account"),
+                    vault.addresses.vault_token_account,
+                ),
+                (
+                    format!("vault[{index}].share_mint"),
+                    vault.addresses.share_mint,
+                ),
+                (
+                    format!("vault[{index}].underlying_mint"),
+                    vault.underlying_mint,
+                ),
+                (format!("vault[{index}].price_history"), vault.price_history),
+            ]);
+            for (user, position) in vault.positions.iter().enumerate() {
+                accounts.push((
+                    format!("vault[{index}].user[{user}].underlying"),
+                    position.underlying,
+                ));
+                accounts.push((
+                    format!("vault[{index}].user[{user}].shares"),
+                    position.shares,
+                ));
+            }
+        }
+        accounts
+    }
+
+    /// Fetches `addresses`, `None` for those that do not exist
+    pub fn fetch_many(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>, StepError> {
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            accounts.extend(self.rpc.get_multiple_accounts(chunk)?);
+        }
+        Ok(accounts)
+    }
+
+    pub fn slot(&self) -> Result<u64, StepError> {
+        Ok(self.rpc.get_slot()?)
+    }
+
+    fn create_vault(&self) -> Result<SoakVault, StepError> {
+        let mint = Keypair::new();
+        let create = system_instruction::create_account(
+            &self.payer.pubkey(),
+            &mint.pubkey(),
+            self.rpc.get_minimum_balance_for_rent_exemption(Mint::LEN)?,
+            Mint::LEN as u64,
+            &spl_token::id(),
+        );
+        let init = spl_token::instruction::initialize_mint2(
+            &spl_token::id(),
+            &mint.pubkey(),
+            &self.payer.pubkey(),
+            None,
+            6,
+        )?;
+        let vault = initialize_vault_ix(
+            &self.program_id,
+            &mint.pubkey(),
+            &self.payer.pubkey(),
+            &spl_token::id(),
+            "soak",
+        );
+        self.send_confirmed(&[create, init, vault], &[&mint])?;
+        let addresses = VaultAddresses::new(&self.program_id, &mint.pubkey());
+
+        let price_history = Keypair::new();
+        let create = system_instruction::create_account(
+            &self.payer.pubkey(),
+            &price_history.pubkey(),
+            self.rpc
+                .get_minimum_balance_for_rent_exemption(PriceHistory::LEN)?,
+            PriceHistory::LEN as u64,
+            &self.program_id,
+        );
+        let accounts = vault_pda::accounts::InitializePriceHistory {
+            price_history: price_history.pubkey(),
+            vault: addresses.vault,
+        };
+        let init = Instruction {
+            program_id: self.program_id,
+            accounts: accounts.to_account_metas(None),
+            data: vault_pda::instruction::InitializePriceHistory {}.data(),
+        };
+        self.send_confirmed(&[create, init], &[&price_history])?;
+
+        let mut positions = Vec::with_capacity(self.users.len());
+        for user in &self.users {
+            positions.push(Position {
+                underlying: self.create_token_account(&mint.pubkey(), &user.pubkey())?,
+                shares: self.create_token_account(&addresses.share_mint, &user.pubkey())?,
+            });
+        }
+
+        Ok(SoakVault {
+            underlying_mint: mint.pubkey(),
+            addresses,
+            price_history: price_history.pubkey(),
+            positions,
+            ledger: Ledger::default(),
+        })
+    }
+
+    fn create_token_account(&self, mint: &Pubkey, owner: &Pubkey) -> Result<Pubkey, StepError> {
+        let account = Keypair::new();
+        let create = system_instruction::create_account(
+            &self.payer.pubkey(),
+            &account.pubkey(),
+            self.rpc
+                .get_minimum_balance_for_rent_exemption(TokenAccount::LEN)?,
+            TokenAccount::LEN as u64,
+            &spl_token::id(),
+        );
+        let init = spl_token::instruction::initialize_account3(
+            &spl_token::id(),
+            &account.pubkey(),
+            mint,
+            owner,
+        )?;
+        self.send_confirmed(&[create, init], &[&account])?;
+        Ok(account.pubkey())
+    }
+
+    /// Airdrops to the payer when it runs low, as hours of fees add up
+    fn top_up(&self) -> Result<(), StepError> {
+        Ok(top_up(&self.rpc, &self.payer.pubkey())?)
+    }
+
+    /// Sends `instructions` paid for and signed by the payer plus `signers`
+    fn send(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Sent, StepError> {
+        Ok(send(&self.rpc, &self.payer, instructions, signers)?)
+    }
+
+    /// Sends setup and yield transactions, which must not be rejected
+    fn send_confirmed(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Signature, StepError> {
+        match self.send(instructions, signers)? {
+            Sent::Confirmed(signature) => Ok(signature),
+            Sent::Rejected(signature, error) => Err(StepError::Rpc(
+                format!("transaction {signature} was rejected: {error}").into(),
+            )),
+        }
+    }
+
+    fn account(&self, address: &Pubkey) -> Result<Account, StepError> {
+        self.fetch(address)?
+            .ok_or_else(|| StepError::Rpc(format!("account {address} does not exist").into()))
+    }
+
+    fn fetch(&self, address: &Pubkey) -> Result<Option<Account>, StepError> {
+        Ok(self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())?
+            .value)
+    }
+
+    fn token_balance(&self, address: &Pubkey) -> Result<u64, StepError> {
+        Ok(TokenAccount::unpack(&self.account(address)?.data)?.amount)
+    }
+}
+
+/// The rejection of a second `record_price` in one slot
+fn already_recorded() -> TransactionError {
+    TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(RecordPriceError::AlreadyRecorded.into()),
+    )
+}
@@ -122,7 +122,7 @@ describe("vault-pda", () => {
 
     // Call initialize_vault instruction
     const tx = await program.methods
-      .initializeVault()
+      .initializeVault("Test vault")
       .accounts({
         vault: vaultPda,
         underlyingMint: underlyingMint,
@@ -143,6 +143,9 @@ describe("vault-pda", () => {
     expect(vaultAccount.underlyingMint.toString()).to.equal(
       underlyingMint.toString()
     );
+    expect(
+      Buffer.from(vaultAccount.name).toString("utf8").replace(/\0+$/, "")
+    ).to.equal("Test vault");
     expect(vaultAccount.bump).to.be.greaterThan(0);
 
     console.log("Vault created successfully!");
@@ -0,0 +1,131 @@
+//! The exploit bundle format.
+//!
+//! A bundle is the JSON a contestant submits, or their exploit binary prints:
+//! two phases of transactions around the victim's deposit. Each transaction
+//! is paid for and signed by the player; extra signers (fresh accounts, a
+//! second wallet) are listed by base58 secret key.
+//!
+//! ```json
+//! {
+//!   "before_victim": [
+//!     {
+//!       "instructions": [
+//!         {
+//!           "program_id": "8qsydpwMiRcFtJ8wrKkM4xrMMEWfnw2szibQGLgBw6KH",
+//!           "accounts": [
+//!             { "pubkey": "...", "is_signer": false, "is_writable": true }
+//!           ],
+//!           "data": [0, 1, 2]
+//!         }
+//!       ],
+//!       "signers": []
+//!     }
+//!   ],
+//!   "after_victim": []
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Keypair,
+};
+use vault_pda::state::pubkey_serde;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Bundle {
+    /// Sent before the victim deposits
+    #[serde(default)]
+    pub before_victim: Vec<BundleTransaction>,
+    /// Sent after the victim deposits
+    #[serde(default)]
+    pub after_victim: Vec<BundleTransaction>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BundleTransaction {
+    pub instructions: Vec<BundleInstruction>,
+    /// Base58 secret keys of signers besides the player
+    #[serde(default)]
+    pub signers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleInstruction {
+    #[serde(with = "pubkey_serde")]
+    pub program_id: Pubkey,
+    pub accounts: Vec<BundleAccount>,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleAccount {
+    #[serde(with = "pubkey_serde")]
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl BundleTransaction {
+    /// A transaction of `instructions` signed by the player alone
+    pub fn new(instructions: &[Instruction]) -> Self {
+        Self {
+            instructions: instructions.iter().map(BundleInstruction::from).collect(),
+            signers: Vec::new(),
+        }
+    }
+
+    pub fn to_instructions(&self) -> Vec<Instruction> {
+        self.instructions.iter().map(Instruction::from).collect()
+    }
+
+    /// Decodes `signers`, failing on a malformed key
+    pub fn keypairs(&self) -> Result<Vec<Keypair>, String> {
+        self.signers
+            .iter()
+            .map(|secret| {
+                let bytes = bs58::decode(secret)
+                    .into_vec()
+                    .map_err(|e| format!("invalid signer: {e}"))?;
+                Keypair::try_from(bytes.as_slice()).map_err(|e| format!("invalid signer: {e}"))
+            })
+            .collect()
+    }
+}
+
+impl From<&Instruction> for BundleInstruction {
+    fn from(ix: &Instruction) -> Self {
+        Self {
+            program_id: ix.program_id,
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|meta| BundleAccount {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: ix.data.clone(),
+        }
+    }
+}
+
+impl From<&BundleInstruction> for Instruction {
+    fn from(ix: &BundleInstruction) -> Self {
+        Instruction {
+            program_id: ix.program_id,
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: ix.data.clone(),
+        }
+    }
+}
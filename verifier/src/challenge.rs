@@ -0,0 +1,156 @@
+//! The challenge every exploit runs against.
+
+use fuzz_helpers::backend::{create_token_account, mint_to, send, setup_complete_on};
+use fuzz_helpers::seed::clear_seed;
+use fuzz_helpers::*;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use vault_pda::state::pubkey_serde;
+
+/// Underlying tokens the player starts with
+pub const PLAYER_BALANCE: u64 = 1_000_000;
+
+/// Underlying tokens the victim deposits between the bundle's two phases
+pub const VICTIM_DEPOSIT: u64 = 1_000_000;
+
+/// Decimals of the underlying mint
+pub const DECIMALS: u8 = 6;
+
+/// Seed of the challenge's keypairs unless `--seed` picks another
+pub const DEFAULT_SEED: u64 = 0;
+
+/// Every address an exploit needs, as JSON for contestants
+///
+/// Keypairs are derived from `seed`, so the same seed always builds the same
+/// accounts and a bundle written against `verifier describe` replays as is.
+/// The player's secret key is included: it only holds challenge funds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Challenge {
+    pub seed: u64,
+    #[serde(with = "pubkey_serde")]
+    pub program_id: Pubkey,
+    #[serde(with = "pubkey_serde")]
+    pub protocol_state: Pubkey,
+    #[serde(with = "pubkey_serde")]
+    pub vault_authority: Pubkey,
+    /// The protocol owner, who planted the flag
+    #[serde(with = "pubkey_serde")]
+    pub owner: Pubkey,
+    #[serde(with = "pubkey_serde")]
+    pub vault: Pubkey,
+    #[serde(with = "pubkey_serde")]
+    pub vault_token_account: Pubkey,
+    #[serde(with = "pubkey_serde")]
+    pub share_mint: Pubkey,
+    #[serde(with = "pubkey_serde")]
+    pub underlying_mint: Pubkey,
+    #[serde(with = "pubkey_serde")]
+    pub token_program: Pubkey,
+    #[serde(with = "pubkey_serde")]
+    pub flag: Pubkey,
+    #[serde(with = "pubkey_serde")]
+    pub player: Pubkey,
+    /// Base58 secret key of `player`, who signs and pays for every transaction
+    pub player_secret: String,
+    #[serde(with = "pubkey_serde")]
+    pub player_record: Pubkey,
+    #[serde(with = "pubkey_serde")]
+    pub player_underlying_account: Pubkey,
+    #[serde(with = "pubkey_serde")]
+    pub player_share_account: Pubkey,
+    pub player_balance: u64,
+    #[serde(with = "pubkey_serde")]
+    pub victim: Pubkey,
+    #[serde(with = "pubkey_serde")]
+    pub victim_underlying_account: Pubkey,
+    #[serde(with = "pubkey_serde")]
+    pub victim_share_account: Pubkey,
+    pub victim_deposit: u64,
+}
+
+/// A built challenge: its description and the keys the verifier signs with
+#[derive(Debug)]
+pub struct ChallengeSetup {
+    pub challenge: Challenge,
+    /// The protocol, vault and victim (`setup.user`)
+    pub setup: CompleteSetup,
+    pub player: Keypair,
+}
+
+/// Builds the challenge on `backend`: an empty vault with a planted flag, a
+/// registered player holding `PLAYER_BALANCE` and a victim holding
+/// `VICTIM_DEPOSIT` who has not deposited yet
+pub async fn setup_challenge<B: FuzzBackend>(
+    backend: &mut B,
+    program_id: &Pubkey,
+    seed: u64,
+) -> FuzzResult<ChallengeSetup> {
+    seed_keypairs(seed);
+    let setup = setup_complete_on(backend, program_id, VICTIM_DEPOSIT, DECIMALS).await?;
+
+    let ix = initialize_flag_ix(program_id, &setup.protocol.owner, &setup.vault);
+    send(backend, &[ix], &[&setup.protocol.owner_keypair]).await?;
+
+    let player = new_keypair();
+    fund_account(backend, &player.pubkey()).await?;
+    let player_underlying_account = create_token_account(
+        backend,
+        &setup.underlying.mint,
+        &player.pubkey(),
+        TokenFlavor::SplToken,
+    )
+    .await?;
+    let player_share_account = create_token_account(
+        backend,
+        &setup.vault.share_mint,
+        &player.pubkey(),
+        TokenFlavor::SplToken,
+    )
+    .await?;
+    mint_to(
+        backend,
+        &setup.underlying.mint,
+        &setup.underlying.mint_authority,
+        &player_underlying_account,
+        PLAYER_BALANCE,
+    )
+    .await?;
+
+    let ix = register_player_ix(program_id, &player.pubkey(), &setup.vault);
+    send(backend, &[ix], &[&player]).await?;
+    clear_seed();
+
+    let flag = derive_flag_pda(program_id, &setup.vault.vault).0;
+    let challenge = Challenge {
+        seed,
+        program_id: *program_id,
+        protocol_state: setup.protocol.protocol_state,
+        vault_authority: setup.protocol.vault_authority,
+        owner: setup.protocol.owner,
+        vault: setup.vault.vault,
+        vault_token_account: setup.vault.vault_token_account,
+        share_mint: setup.vault.share_mint,
+        underlying_mint: setup.vault.underlying_mint,
+        token_program: setup.vault.token_program,
+        flag,
+        player: player.pubkey(),
+        player_secret: player.to_base58_string(),
+        player_record: derive_player_pda(program_id, &flag, &player.pubkey()).0,
+        player_underlying_account,
+        player_share_account,
+        player_balance: PLAYER_BALANCE,
+        victim: setup.user.owner.pubkey(),
+        victim_underlying_account: setup.user.underlying_token_account,
+        victim_share_account: setup.user.share_token_account,
+        victim_deposit: VICTIM_DEPOSIT,
+    };
+
+    Ok(ChallengeSetup {
+        challenge,
+        setup,
+        player,
+    })
+}
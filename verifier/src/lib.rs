@@ -0,0 +1,205 @@
+//! Automated solution verifier.
+//!
+//! Builds a fresh challenge (see [`setup_challenge`]), runs a contestant's
+//! [`Bundle`] against it around a victim's deposit, and reports whether the
+//! exploit won: the player captured the flag, or left an invariant broken.
+//! The `verifier` binary wraps this for players and CTF infrastructure; the
+//! library runs on any `FuzzBackend`, so tests and other tools can verify
+//! bundles in process.
+
+pub mod bundle;
+pub mod challenge;
+
+pub use bundle::*;
+pub use challenge::*;
+
+use anchor_lang::AccountDeserialize;
+use fuzz_helpers::backend::{mint_supply, send, token_balance};
+use fuzz_helpers::*;
+use serde::Serialize;
+use solana_sdk::signature::{Keypair, Signer};
+use vault_math::compute_assets;
+use vault_pda::state::{Flag, ProtocolState, WinCondition};
+
+/// Result of one bundle transaction
+#[derive(Debug, Clone, Serialize)]
+pub struct TxOutcome {
+    /// `before_victim` or `after_victim`
+    pub phase: &'static str,
+    /// Position in its phase
+    pub index: usize,
+    /// Why the transaction failed, if it did
+    pub error: Option<String>,
+}
+
+/// An invariant the challenge state breaks after the bundle
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    pub invariant: &'static str,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    /// Whether the flag was captured by the player or an invariant broken
+    pub solved: bool,
+    /// How the player captured the flag, if they did
+    pub flag: Option<WinCondition>,
+    pub violations: Vec<Violation>,
+    pub transactions: Vec<TxOutcome>,
+    /// Why the victim's deposit failed, if it did
+    pub victim_deposit_error: Option<String>,
+}
+
+/// Runs `bundle` against `built` and checks the win conditions
+///
+/// Failed transactions do not stop the run, like on a real cluster; they are
+/// reported in [`Report::transactions`].
+pub async fn run_bundle<B: FuzzBackend>(
+    backend: &mut B,
+    built: &ChallengeSetup,
+    bundle: &Bundle,
+) -> FuzzResult<Report> {
+    let mut transactions = Vec::new();
+
+    run_phase(
+        backend,
+        &built.player,
+        "before_victim",
+        &bundle.before_victim,
+        &mut transactions,
+    )
+    .await;
+
+    let setup = &built.setup;
+    let ix = deposit_ix(
+        &built.challenge.program_id,
+        &setup.vault,
+        &setup.user,
+        VICTIM_DEPOSIT,
+    );
+    let victim_deposit_error = send(backend, &[ix], &[&setup.user.owner])
+        .await
+        .err()
+        .map(|e| e.to_string());
+
+    run_phase(
+        backend,
+        &built.player,
+        "after_victim",
+        &bundle.after_victim,
+        &mut transactions,
+    )
+    .await;
+
+    let flag = captured_flag(backend, &built.challenge).await?;
+    let violations = check_invariants(backend, built).await?;
+    Ok(Report {
+        solved: flag.is_some() || !violations.is_empty(),
+        flag,
+        violations,
+        transactions,
+        victim_deposit_error,
+    })
+}
+
+async fn run_phase<B: FuzzBackend>(
+    backend: &mut B,
+    player: &Keypair,
+    phase: &'static str,
+    bundle: &[BundleTransaction],
+    outcomes: &mut Vec<TxOutcome>,
+) {
+    for (index, tx) in bundle.iter().enumerate() {
+        let error = match tx.keypairs() {
+            Ok(extra) => {
+                let mut signers = vec![player];
+                signers.extend(extra.iter());
+                send(backend, &tx.to_instructions(), &signers)
+                    .await
+                    .err()
+                    .map(|e| e.to_string())
+            }
+            Err(e) => Some(e),
+        };
+        outcomes.push(TxOutcome {
+            phase,
+            index,
+            error,
+        });
+    }
+}
+
+/// The condition the player captured the flag under, if they did
+async fn captured_flag<B: FuzzBackend>(
+    backend: &mut B,
+    challenge: &Challenge,
+) -> FuzzResult<Option<WinCondition>> {
+    let account = backend
+        .get_account(&challenge.flag)
+        .await?
+        .ok_or("Flag account not found")?;
+    let flag = Flag::try_deserialize(&mut account.data.as_ref())?;
+
+    Ok(flag.condition.filter(|_| flag.captor == challenge.player))
+}
+
+/// Invariants an honest run keeps:
+///
+/// - `ownership`: the protocol owner is unchanged
+/// - `player_profit`: the player's registered account holds no more than it
+///   started with
+/// - `victim_loss`: the victim's underlying plus the value of their shares is
+///   within a token of what they deposited
+async fn check_invariants<B: FuzzBackend>(
+    backend: &mut B,
+    built: &ChallengeSetup,
+) -> FuzzResult<Vec<Violation>> {
+    let challenge = &built.challenge;
+    let mut violations = Vec::new();
+
+    let account = backend
+        .get_account(&challenge.protocol_state)
+        .await?
+        .ok_or("Protocol state account not found")?;
+    let protocol_state = ProtocolState::try_deserialize(&mut account.data.as_ref())?;
+    if protocol_state.owner != challenge.owner {
+        violations.push(Violation {
+            invariant: "ownership",
+            detail: format!(
+                "protocol owner changed from {} to {}",
+                challenge.owner, protocol_state.owner
+            ),
+        });
+    }
+
+    let player_balance = token_balance(backend, &challenge.player_underlying_account).await?;
+    if player_balance > challenge.player_balance {
+        violations.push(Violation {
+            invariant: "player_profit",
+            detail: format!(
+                "player holds {} underlying, started with {}",
+                player_balance, challenge.player_balance
+            ),
+        });
+    }
+
+    let vault_balance = token_balance(backend, &challenge.vault_token_account).await?;
+    let supply = mint_supply(backend, &challenge.share_mint).await?;
+    let victim_shares = token_balance(backend, &challenge.victim_share_account).await?;
+    let victim_balance = token_balance(backend, &challenge.victim_underlying_account).await?;
+    // What redeeming every share would pay; nothing if that rounds to zero
+    let share_value = compute_assets(victim_shares, supply, vault_balance).unwrap_or(0);
+    let victim_total = victim_balance.saturating_add(share_value);
+    if victim_total + 1 < challenge.victim_deposit {
+        violations.push(Violation {
+            invariant: "victim_loss",
+            detail: format!(
+                "victim's holdings are worth {}, down from {}",
+                victim_total, challenge.victim_deposit
+            ),
+        });
+    }
+
+    Ok(violations)
+}
@@ -0,0 +1,214 @@
+//! Verifies a contestant's exploit against a fresh challenge.
+//!
+//! ```bash
+//! verifier describe [--seed <n>]
+//! verifier run (--bundle <file> | --exploit <binary> [args..]) [--seed <n>] [--backend program-test|litesvm] [--json]
+//! ```
+//!
+//! `describe` prints the challenge's addresses as JSON. `run` builds the same
+//! challenge, sends the bundle and prints the report. An exploit binary reads
+//! the challenge JSON on stdin and prints its bundle JSON on stdout.
+//!
+//! Exits 0 when solved, 1 when not, 2 when the challenge could not be run.
+
+use std::io::Write;
+use std::process::{Command, ExitCode, Stdio};
+
+use fuzz_helpers::{setup_program_test, FuzzBackend, FuzzResult};
+use vault_verifier::{run_bundle, setup_challenge, Bundle, Challenge, Report, DEFAULT_SEED};
+
+const USAGE: &str = "usage: verifier describe [--seed <n>]
+       verifier run (--bundle <file> | --exploit <binary> [args..]) [--seed <n>] [--backend program-test|litesvm] [--json]";
+
+enum Source {
+    Bundle(String),
+    Exploit(Vec<String>),
+}
+
+struct Options {
+    seed: u64,
+    litesvm: bool,
+    json: bool,
+    source: Option<Source>,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, rest)) = args.split_first() else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    };
+    let options = match parse_options(rest) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to build tokio runtime");
+    let result = match command.as_str() {
+        "describe" => runtime.block_on(describe(&options)).map(|()| true),
+        "run" => runtime.block_on(run(&options)),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("verifier failed: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        seed: DEFAULT_SEED,
+        litesvm: false,
+        json: false,
+        source: None,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                let value = args.next().ok_or("--seed needs a value")?;
+                options.seed = value.parse().map_err(|e| format!("invalid seed: {e}"))?;
+            }
+            "--backend" => match args.next().map(String::as_str) {
+                Some("program-test") => options.litesvm = false,
+                Some("litesvm") => options.litesvm = true,
+                other => return Err(format!("unknown backend: {other:?}")),
+            },
+            "--json" => options.json = true,
+            "--bundle" => {
+                let path = args.next().ok_or("--bundle needs a file")?;
+                options.source = Some(Source::Bundle(path.clone()));
+            }
+            // Everything after the binary is passed to it
+            "--exploit" => {
+                let command: Vec<String> = args.by_ref().cloned().collect();
+                if command.is_empty() {
+                    return Err("--exploit needs a binary".into());
+                }
+                options.source = Some(Source::Exploit(command));
+            }
+            other => return Err(format!("unknown argument: {other}")),
+        }
+    }
+
+    Ok(options)
+}
+
+async fn describe(options: &Options) -> FuzzResult<()> {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let built = setup_challenge(&mut *env.context, &program_id, options.seed).await?;
+    println!("{}", serde_json::to_string_pretty(&built.challenge)?);
+    Ok(())
+}
+
+async fn run(options: &Options) -> FuzzResult<bool> {
+    let source = options
+        .source
+        .as_ref()
+        .ok_or("run needs --bundle or --exploit")?;
+
+    let report = if options.litesvm {
+        run_on_litesvm(options.seed, source).await?
+    } else {
+        let mut env = setup_program_test().await;
+        let program_id = env.program_id;
+        run_on(&mut *env.context, &program_id, options.seed, source).await?
+    };
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+    Ok(report.solved)
+}
+
+#[cfg(feature = "litesvm")]
+async fn run_on_litesvm(seed: u64, source: &Source) -> FuzzResult<Report> {
+    let mut backend = fuzz_helpers::backend::LiteSvmBackend::new()?;
+    run_on(&mut backend, &vault_pda::id(), seed, source).await
+}
+
+#[cfg(not(feature = "litesvm"))]
+async fn run_on_litesvm(_seed: u64, _source: &Source) -> FuzzResult<Report> {
+    Err("LiteSVM backend needs the `litesvm` feature".into())
+}
+
+async fn run_on<B: FuzzBackend>(
+    backend: &mut B,
+    program_id: &solana_sdk::pubkey::Pubkey,
+    seed: u64,
+    source: &Source,
+) -> FuzzResult<Report> {
+    let built = setup_challenge(backend, program_id, seed).await?;
+    let bundle = match source {
+        Source::Bundle(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        Source::Exploit(command) => run_exploit(command, &built.challenge)?,
+    };
+    run_bundle(backend, &built, &bundle).await
+}
+
+/// Runs the contestant's binary with the challenge on stdin
+fn run_exploit(command: &[String], challenge: &Challenge) -> FuzzResult<Bundle> {
+    let mut child = Command::new(&command[0])
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .ok_or("Exploit stdin unavailable")?
+        .write_all(serde_json::to_string(challenge)?.as_bytes())?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(format!("exploit exited with {}", output.status).into());
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+fn print_report(report: &Report) {
+    for tx in &report.transactions {
+        match &tx.error {
+            Some(e) => println!("{} #{}: failed: {e}", tx.phase, tx.index),
+            None => println!("{} #{}: ok", tx.phase, tx.index),
+        }
+    }
+    if let Some(e) = &report.victim_deposit_error {
+        println!("victim deposit: failed: {e}");
+    }
+    if let Some(condition) = &report.flag {
+        println!("flag captured: {condition:?}");
+    }
+    for violation in &report.violations {
+        println!(
+            "invariant broken: {}: {}",
+            violation.invariant, violation.detail
+        );
+    }
+    println!(
+        "{}",
+        if report.solved {
+            "SOLVED"
+        } else {
+            "NOT SOLVED"
+        }
+    );
+}
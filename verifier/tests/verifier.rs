@@ -0,0 +1,74 @@
+//! Verifier tests.
+//!
+//! The ownership takeover solves the challenge exactly when the program is
+//! built with `vuln-ownership`, and an empty bundle never does.
+//!
+//! Requires the program to be built first, with the same features:
+//!
+//! ```bash
+//! anchor build
+//! SBF_OUT_DIR=$PWD/target/deploy cargo test -p vault-verifier --test verifier
+//! ```
+
+use fuzz_helpers::*;
+use vault_pda::state::WinCondition;
+use vault_verifier::*;
+
+#[tokio::test]
+async fn ownership_takeover_solves_when_vulnerable() {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let built = setup_challenge(&mut *env.context, &program_id, DEFAULT_SEED)
+        .await
+        .unwrap();
+    let challenge = &built.challenge;
+
+    // The owner never signs the takeover
+    let mut takeover = transfer_ownership_ix(&program_id, &challenge.owner, &challenge.player);
+    takeover.accounts[1].is_signer = false;
+    let capture = capture_flag_ix(&program_id, &challenge.player, &built.setup.vault);
+    let bundle = Bundle {
+        before_victim: vec![BundleTransaction::new(&[takeover, capture])],
+        after_victim: Vec::new(),
+    };
+
+    // Round-trips through JSON like a submitted bundle
+    let bundle: Bundle = serde_json::from_str(&serde_json::to_string(&bundle).unwrap()).unwrap();
+    let report = run_bundle(&mut *env.context, &built, &bundle)
+        .await
+        .unwrap();
+
+    assert_eq!(report.solved, vault_pda::VULN_OWNERSHIP);
+    assert_eq!(report.transactions.len(), 1);
+    assert_eq!(
+        report.transactions[0].error.is_none(),
+        vault_pda::VULN_OWNERSHIP
+    );
+    assert!(report.victim_deposit_error.is_none());
+    if vault_pda::VULN_OWNERSHIP {
+        assert_eq!(report.flag, Some(WinCondition::OwnsProtocol));
+        assert!(report.violations.iter().any(|v| v.invariant == "ownership"));
+    } else {
+        assert_eq!(report.flag, None);
+        assert!(report.violations.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn empty_bundle_does_not_solve() {
+    let mut env = setup_program_test().await;
+    let program_id = env.program_id;
+    let built = setup_challenge(&mut *env.context, &program_id, DEFAULT_SEED)
+        .await
+        .unwrap();
+
+    let report = run_bundle(&mut *env.context, &built, &Bundle::default())
+        .await
+        .unwrap();
+
+    assert!(!report.solved);
+    assert!(report.transactions.is_empty());
+    assert!(report.victim_deposit_error.is_none());
+    assert_eq!(report.flag, None);
+    assert!(report.violations.is_empty());
+}
@@ -0,0 +1,31 @@
+//! Repository automation.
+//!
+//! ```bash
+//! cargo xtask new-challenge <name> [--features <flags>] [--program-id <pubkey>]
+//! ```
+
+use std::process::ExitCode;
+
+mod new_challenge;
+
+const USAGE: &str =
+    "usage: cargo xtask new-challenge <name> [--features <flags>] [--program-id <pubkey>]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.split_first() {
+        Some((command, rest)) if command == "new-challenge" => new_challenge::run(rest),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("xtask failed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,539 @@
+//! `new-challenge`: mints a new CTF level from the vault.
+//!
+//! Clones `programs/vault-pda` into `programs/<name>` with a fresh program ID
+//! and the chosen `vuln-*` features on by default, registers it in
+//! `Anchor.toml`, and generates fuzz and exploit harnesses under
+//! `challenges/<name>/`. The harnesses compile the shared sources of `fuzz/`
+//! and `exploits/` against the clone (its crate is renamed to `vault-pda` in
+//! their manifests), so every target, test and PoC runs against the new level
+//! without copying them.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Every vulnerability feature of the vault, in `vulnerable` order
+const VULN_FEATURES: [&str; 4] = [
+    "vuln-ownership",
+    "vuln-inflation",
+    "vuln-share-mint",
+    "vuln-global-authority",
+];
+
+const TEMPLATE: &str = "vault-pda";
+const TEMPLATE_LIB: &str = "vault_pda";
+
+/// Path from `challenges/<name>/<harness>/` back to the workspace root
+const TO_ROOT: &str = "../../..";
+
+#[derive(Debug)]
+struct Options {
+    /// Package name, kebab-case
+    name: String,
+    /// Enabled `vuln-*` features
+    features: Vec<&'static str>,
+    /// Program ID to declare; a keypair is generated when `None`
+    program_id: Option<String>,
+}
+
+pub fn run(args: &[String]) -> Result<()> {
+    let options = parse_args(args)?;
+    let root = workspace_root();
+    let lib = options.name.replace('-', "_");
+
+    let program_dir = root.join("programs").join(&options.name);
+    if program_dir.exists() {
+        return Err(format!("{} already exists", program_dir.display()).into());
+    }
+    let program_id = match &options.program_id {
+        Some(id) => id.clone(),
+        None => generate_program_id(&root, &lib)?,
+    };
+
+    copy_dir(&root.join("programs").join(TEMPLATE), &program_dir)?;
+    rewrite(&program_dir.join("Cargo.toml"), |manifest| {
+        program_manifest(manifest, &options.name, &lib, &options.features)
+    })?;
+    rewrite(&program_dir.join("src/lib.rs"), |source| {
+        program_source(source, &lib, &program_id)
+    })?;
+    rewrite(&root.join("Anchor.toml"), |anchor| {
+        anchor_toml(anchor, &lib, &program_id)
+    })?;
+
+    let harness_dir = root.join("challenges").join(&options.name);
+    let fuzz_dir = harness_dir.join("fuzz");
+    let exploits_dir = harness_dir.join("exploits");
+    fs::create_dir_all(&fuzz_dir)?;
+    fs::create_dir_all(&exploits_dir)?;
+
+    let fuzz_tests = test_names(&root.join("fuzz/tests"))?;
+    let manifest = fs::read_to_string(root.join("fuzz/Cargo.toml"))?;
+    fs::write(
+        fuzz_dir.join("Cargo.toml"),
+        fuzz_manifest(&manifest, &options, &lib, &fuzz_tests),
+    )?;
+    fs::write(
+        fuzz_dir.join("build.rs"),
+        format!(
+            "// Points the shared fuzz sources at this challenge's SBF build\n\
+             fn main() {{\n    println!(\"cargo:rustc-env=VAULT_PROGRAM_NAME={lib}\");\n}}\n"
+        ),
+    )?;
+
+    let exploit_tests = test_names(&root.join("exploits/tests"))?;
+    let manifest = fs::read_to_string(root.join("exploits/Cargo.toml"))?;
+    fs::write(
+        exploits_dir.join("Cargo.toml"),
+        exploits_manifest(&manifest, &options, &exploit_tests),
+    )?;
+
+    rewrite(&root.join("Cargo.toml"), |manifest| {
+        workspace_manifest(
+            manifest,
+            &[
+                format!("challenges/{}/fuzz", options.name),
+                format!("challenges/{}/exploits", options.name),
+            ],
+        )
+    })?;
+
+    println!("Created challenge {}", options.name);
+    println!("  program:    programs/{} ({})", options.name, program_id);
+    println!("  features:   {}", options.features.join(", "));
+    println!(
+        "  harnesses:  challenges/{}/{{fuzz,exploits}}",
+        options.name
+    );
+    println!();
+    println!("Next steps:");
+    println!("  anchor build -p {lib}");
+    println!(
+        "  SBF_OUT_DIR=$PWD/target/deploy cargo test -p {0}-fuzz -p {0}-exploits",
+        options.name
+    );
+    println!("  scoreboard create_challenge with program_id {program_id} to score its flags");
+
+    Ok(())
+}
+
+fn parse_args(args: &[String]) -> Result<Options> {
+    let mut name = None;
+    let mut features = VULN_FEATURES.to_vec();
+    let mut program_id = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--features" => {
+                let value = args.next().ok_or("--features needs a value")?;
+                features = parse_features(value)?;
+            }
+            "--program-id" => {
+                let value = args.next().ok_or("--program-id needs a value")?;
+                program_id = Some(value.clone());
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown flag {flag}").into()),
+            _ if name.is_none() => name = Some(arg.clone()),
+            _ => return Err(format!("unexpected argument {arg}").into()),
+        }
+    }
+
+    let name = name.ok_or(crate::USAGE)?;
+    validate_name(&name)?;
+    Ok(Options {
+        name,
+        features,
+        program_id,
+    })
+}
+
+/// Parses a comma-separated feature list; `vulnerable` selects every
+/// vulnerability and `none` builds the reference fix
+fn parse_features(value: &str) -> Result<Vec<&'static str>> {
+    let mut features = Vec::new();
+    for feature in value.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+        match feature {
+            "vulnerable" => features.extend(VULN_FEATURES),
+            "none" => {}
+            _ => match VULN_FEATURES.iter().find(|known| **known == feature) {
+                Some(known) => features.push(*known),
+                None => {
+                    return Err(format!(
+                        "unknown feature {feature}, expected one of {}, vulnerable or none",
+                        VULN_FEATURES.join(", ")
+                    )
+                    .into())
+                }
+            },
+        }
+    }
+    // Keep `vulnerable` order so manifests diff cleanly
+    Ok(VULN_FEATURES
+        .into_iter()
+        .filter(|known| features.contains(known))
+        .collect())
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid = name.starts_with(|c: char| c.is_ascii_lowercase())
+        && !name.ends_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if !valid {
+        return Err(format!("{name} is not a kebab-case crate name").into());
+    }
+    if name == TEMPLATE || name == "scoreboard" || name == "xtask" {
+        return Err(format!("{name} is already taken").into());
+    }
+    Ok(())
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives in the workspace root")
+        .to_path_buf()
+}
+
+/// Generates `target/deploy/<lib>-keypair.json`, where `anchor build` and
+/// `anchor deploy` look for the program keypair, and returns its pubkey
+fn generate_program_id(root: &Path, lib: &str) -> Result<String> {
+    let deploy = root.join("target/deploy");
+    fs::create_dir_all(&deploy)?;
+    let keypair = deploy.join(format!("{lib}-keypair.json"));
+    if !keypair.exists() {
+        solana_keygen(&[
+            "new",
+            "--no-bip39-passphrase",
+            "--silent",
+            "--outfile",
+            &keypair.to_string_lossy(),
+        ])?;
+    }
+    solana_keygen(&["pubkey", &keypair.to_string_lossy()])
+}
+
+fn solana_keygen(args: &[&str]) -> Result<String> {
+    let output = Command::new("solana-keygen")
+        .args(args)
+        .output()
+        .map_err(|e| format!("running solana-keygen: {e} (or pass --program-id)"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "solana-keygen {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+fn copy_dir(src: &Path, dst: &Path) -> Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_name() == "target" {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            copy_dir(&path, &dst.join(entry.file_name()))?;
+        } else {
+            fs::copy(&path, dst.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+fn rewrite(path: &Path, edit: impl FnOnce(&str) -> Result<String>) -> Result<()> {
+    let contents = fs::read_to_string(path)?;
+    fs::write(path, edit(&contents)?)?;
+    Ok(())
+}
+
+/// Replaces `from` in `text`, failing if the template no longer contains it
+fn replace_once(text: &str, from: &str, to: &str) -> Result<String> {
+    if !text.contains(from) {
+        return Err(format!("template changed: `{from}` not found").into());
+    }
+    Ok(text.replacen(from, to, 1))
+}
+
+fn default_features(features: &[&str]) -> String {
+    let quoted: Vec<String> = features.iter().map(|f| format!("\"{f}\"")).collect();
+    format!("default = [{}]", quoted.join(", "))
+}
+
+fn program_manifest(manifest: &str, name: &str, lib: &str, features: &[&str]) -> Result<String> {
+    let manifest = replace_once(
+        manifest,
+        &format!("name = \"{TEMPLATE}\""),
+        &format!("name = \"{name}\""),
+    )?;
+    let manifest = replace_once(
+        &manifest,
+        &format!("name = \"{TEMPLATE_LIB}\""),
+        &format!("name = \"{lib}\""),
+    )?;
+    let manifest = replace_once(
+        &manifest,
+        "description = \"Created with Anchor\"",
+        &format!("description = \"CTF level generated from {TEMPLATE}\""),
+    )?;
+    replace_once(
+        &manifest,
+        "default = [\"vulnerable\"]",
+        &default_features(features),
+    )
+}
+
+fn program_source(source: &str, lib: &str, program_id: &str) -> Result<String> {
+    let start = source
+        .find("declare_id!(\"")
+        .ok_or("template changed: declare_id! not found")?;
+    let end = start
+        + source[start..]
+            .find(")")
+            .ok_or("template changed: declare_id! not closed")?;
+    let source = format!(
+        "{}declare_id!(\"{program_id}\"{}",
+        &source[..start],
+        &source[end..]
+    );
+    replace_once(
+        &source,
+        &format!("pub mod {TEMPLATE_LIB} {{"),
+        &format!("pub mod {lib} {{"),
+    )
+}
+
+fn anchor_toml(anchor: &str, lib: &str, program_id: &str) -> Result<String> {
+    replace_once(
+        anchor,
+        "[programs.localnet]\n",
+        &format!("[programs.localnet]\n{lib} = \"{program_id}\"\n"),
+    )
+}
+
+fn workspace_manifest(manifest: &str, members: &[String]) -> Result<String> {
+    let start = manifest
+        .find("members = [")
+        .ok_or("workspace members not found")?;
+    let end = start
+        + manifest[start..]
+            .find("\n]")
+            .ok_or("workspace members not closed")?;
+    let added: String = members
+        .iter()
+        .map(|member| format!("\n    \"{member}\","))
+        .collect();
+    Ok(format!("{}{added}{}", &manifest[..end], &manifest[end..]))
+}
+
+/// Integration tests in `dir` as paths relative to it, sorted: `name.rs` files
+/// and `name/main.rs` multi-file tests
+fn test_names(dir: &Path) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().map(|name| name.to_string_lossy()) else {
+            continue;
+        };
+        if path.extension().is_some_and(|ext| ext == "rs") {
+            names.push(name.into_owned());
+        } else if path.join("main.rs").is_file() {
+            names.push(format!("{name}/main.rs"));
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// `[[test]]` entries for the shared tests of `crate_dir`, since a harness has
+/// no `tests/` directory of its own
+fn test_entries(crate_dir: &str, tests: &[String]) -> String {
+    tests
+        .iter()
+        .map(|test| {
+            let name = test.split(['/', '.']).next().unwrap_or(test);
+            format!(
+                "\n[[test]]\nname = \"{name}\"\npath = \"{TO_ROOT}/{crate_dir}/tests/{test}\"\n"
+            )
+        })
+        .collect()
+}
+
+/// The fuzz crate's manifest, pointed at the shared sources and the clone
+///
+/// Binaries are prefixed with the crate name so they do not collide with the
+/// vault's own in `target/`.
+fn fuzz_manifest(manifest: &str, options: &Options, lib: &str, tests: &[String]) -> String {
+    let mut out = String::new();
+    let mut section = "";
+    for line in manifest.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            section = trimmed;
+        }
+
+        let line = if trimmed == "name = \"vault-pda-fuzz\"" {
+            format!("name = \"{}-fuzz\"", options.name)
+        } else if trimmed == "edition = \"2021\"" && section == "[package]" {
+            format!("{line}\nbuild = \"build.rs\"")
+        } else if trimmed == "default = [\"vulnerable\"]" {
+            default_features(&options.features)
+        } else if section == "[[bin]]" && trimmed.starts_with("name = \"") {
+            line.replacen("name = \"", &format!("name = \"{lib}_"), 1)
+        } else if section == "[package.metadata]" {
+            line.replace("\"fuzz_", &format!("\"{lib}_fuzz_"))
+        } else {
+            line.replace(
+                "vault-pda = { path = \"../programs/vault-pda\"",
+                &format!(
+                    "vault-pda = {{ package = \"{}\", path = \"{TO_ROOT}/programs/{}\"",
+                    options.name, options.name
+                ),
+            )
+            .replace(
+                "path = \"../crates/",
+                &format!("path = \"{TO_ROOT}/crates/"),
+            )
+            .replace(
+                "path = \"fuzz_targets/",
+                &format!("path = \"{TO_ROOT}/fuzz/fuzz_targets/"),
+            )
+            .replace(
+                "path = \"tools/",
+                &format!("path = \"{TO_ROOT}/fuzz/tools/"),
+            )
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out.push_str(&test_entries("fuzz", tests));
+    out
+}
+
+/// The exploits crate's manifest, pointed at the shared sources and the
+/// challenge's fuzz harness
+///
+/// The harness dependency is keyed `fuzz_helpers`, the library name the shared
+/// sources import, since a renamed dependency is imported by its key.
+fn exploits_manifest(manifest: &str, options: &Options, tests: &[String]) -> String {
+    let mut out = String::new();
+    for line in manifest.lines() {
+        let trimmed = line.trim();
+        let line = if trimmed == "name = \"vault-exploits\"" {
+            format!("name = \"{}-exploits\"", options.name)
+        } else if trimmed == "name = \"vault_exploits\"" {
+            format!("{line}\npath = \"{TO_ROOT}/exploits/src/lib.rs\"")
+        } else if trimmed == "default = [\"vulnerable\"]" {
+            default_features(&options.features)
+        } else {
+            line.replace(
+                "vault-pda = { path = \"../programs/vault-pda\"",
+                &format!(
+                    "vault-pda = {{ package = \"{}\", path = \"{TO_ROOT}/programs/{}\"",
+                    options.name, options.name
+                ),
+            )
+            .replace(
+                "vault-pda-fuzz = { path = \"../fuzz\"",
+                &format!(
+                    "fuzz_helpers = {{ package = \"{}-fuzz\", path = \"../fuzz\"",
+                    options.name
+                ),
+            )
+            .replace("\"vault-pda-fuzz/", "\"fuzz_helpers/")
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out.push_str(&test_entries("exploits", tests));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn features_default_to_the_full_challenge() {
+        let options = parse_args(&args(&["level-2"])).unwrap();
+        assert_eq!(options.features, VULN_FEATURES);
+
+        let options = parse_args(&args(&[
+            "level-2",
+            "--features",
+            "vuln-share-mint,vuln-ownership",
+        ]))
+        .unwrap();
+        assert_eq!(options.features, ["vuln-ownership", "vuln-share-mint"]);
+
+        let options = parse_args(&args(&["level-2", "--features", "none"])).unwrap();
+        assert!(options.features.is_empty());
+
+        assert!(parse_args(&args(&["level-2", "--features", "vuln-reentrancy"])).is_err());
+        assert!(parse_args(&args(&["Level_2"])).is_err());
+        assert!(parse_args(&args(&["vault-pda"])).is_err());
+    }
+
+    #[test]
+    fn manifests_are_rewritten_for_the_clone() {
+        let root = workspace_root();
+        let options = parse_args(&args(&["level-2", "--features", "vuln-inflation"])).unwrap();
+
+        let program = fs::read_to_string(root.join("programs/vault-pda/Cargo.toml")).unwrap();
+        let program = program_manifest(&program, "level-2", "level_2", &options.features).unwrap();
+        assert!(program.contains("name = \"level-2\""));
+        assert!(program.contains("name = \"level_2\""));
+        assert!(program.contains("default = [\"vuln-inflation\"]"));
+
+        let source = fs::read_to_string(root.join("programs/vault-pda/src/lib.rs")).unwrap();
+        let source =
+            program_source(&source, "level_2", "11111111111111111111111111111111").unwrap();
+        assert!(source.contains("declare_id!(\"11111111111111111111111111111111\");"));
+        assert!(source.contains("pub mod level_2 {"));
+
+        let fuzz = fs::read_to_string(root.join("fuzz/Cargo.toml")).unwrap();
+        let fuzz = fuzz_manifest(
+            &fuzz,
+            &options,
+            "level_2",
+            &["attacker.rs".into(), "integration/main.rs".into()],
+        );
+        assert!(fuzz.contains("name = \"level-2-fuzz\""));
+        assert!(fuzz.contains("package = \"level-2\", path = \"../../../programs/level-2\""));
+        assert!(fuzz.contains("name = \"level_2_fuzz_deposit\""));
+        assert!(fuzz.contains("path = \"../../../fuzz/fuzz_targets/fuzz_deposit.rs\""));
+        assert!(fuzz.contains("path = \"../../../fuzz/tests/attacker.rs\""));
+        assert!(fuzz.contains("name = \"integration\""));
+        assert!(fuzz.contains("path = \"../../../fuzz/tests/integration/main.rs\""));
+        assert!(!fuzz.contains("path = \"fuzz_targets/"));
+
+        let exploits = fs::read_to_string(root.join("exploits/Cargo.toml")).unwrap();
+        let exploits = exploits_manifest(&exploits, &options, &["exploits.rs".into()]);
+        assert!(exploits.contains("fuzz_helpers = { package = \"level-2-fuzz\""));
+        assert!(exploits.contains("\"fuzz_helpers/vuln-ownership\""));
+        assert!(!exploits.contains("vault-pda-fuzz"));
+
+        let workspace = workspace_manifest(
+            "[workspace]\nmembers = [\n    \"fuzz\",\n]\n",
+            &["challenges/level-2/fuzz".into()],
+        )
+        .unwrap();
+        assert_eq!(
+            workspace,
+            "[workspace]\nmembers = [\n    \"fuzz\",\n    \"challenges/level-2/fuzz\",\n]\n"
+        );
+    }
+}